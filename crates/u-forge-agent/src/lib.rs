@@ -12,9 +12,7 @@
 //! suited for LLM consumption.
 
 use std::collections::HashMap;
-use std::sync::{Arc, LazyLock};
-
-use tiktoken_rs::CoreBPE;
+use std::sync::Arc;
 
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
@@ -22,9 +20,14 @@ use schemars::{schema_for, JsonSchema};
 use serde::Deserialize;
 
 use u_forge_core::ingest::rechunk_and_embed;
-use u_forge_core::search::{search_hybrid, HybridSearchConfig, NodeSearchResult};
-use u_forge_core::types::ObjectMetadata;
-use u_forge_core::{queue::InferenceQueue, types::ObjectId, KnowledgeGraph, PropertyIssue};
+use u_forge_core::search::{
+    search_hybrid, HybridSearchConfig, NodeSearchResult, QueryPreprocessing,
+};
+use u_forge_core::types::{CreateRelationshipRequest, ObjectMetadata};
+use u_forge_core::{
+    build_context, queue::InferenceQueue, types::ObjectId, ContextBuilderConfig, KnowledgeGraph,
+    PropertyIssue, DEFAULT_TOKENIZER_MODEL,
+};
 
 // ── History and token counting ────────────────────────────────────────────────
 
@@ -41,20 +44,14 @@ pub struct HistoryMessage {
 /// (role markers + separator bytes).
 const TOKENS_PER_MESSAGE: usize = 4;
 
-/// Cached o200k_harmony BPE tokenizer — constructed once, reused forever.
-///
-/// `o200k_harmony()` parses a ~200 k-entry vocabulary on every call; caching
-/// it here keeps repeated `count_tokens` invocations (e.g. inside
-/// [`select_history_window`]) from rebuilding the tokenizer each time.
-static O200K_BPE: LazyLock<CoreBPE> =
-    LazyLock::new(|| tiktoken_rs::o200k_harmony().expect("o200k_harmony is always available"));
-
-/// Count BPE tokens in `text` using the o200k_harmony encoding.
+/// Count BPE tokens in `text` using the default (o200k_harmony) encoding.
 ///
-/// o200k_harmony is used by GPT-4o and is becoming the standard for local
-/// open-weight models. Close enough for context-window budgeting.
+/// Thin wrapper over [`u_forge_core::count_tokens`] with
+/// [`DEFAULT_TOKENIZER_MODEL`] — o200k_harmony is used by GPT-4o and is
+/// becoming the standard for local open-weight models. Close enough for
+/// context-window budgeting.
 pub fn count_tokens(text: &str) -> usize {
-    O200K_BPE.encode_with_special_tokens(text).len()
+    u_forge_core::count_tokens(text, DEFAULT_TOKENIZER_MODEL)
 }
 
 /// Return the subset of `history` that fits inside the available token budget.
@@ -221,7 +218,10 @@ fn fts5_sanitize(query: &str) -> Option<String> {
 
 // ── Shared output formatter ───────────────────────────────────────────────────
 
-/// Format a single [`NodeSearchResult`] into LLM-readable text.
+/// Format a single [`NodeSearchResult`]'s card into LLM-readable text.
+///
+/// Chunk content is not included; [`HybridSearchTool`] appends it for all
+/// results at once via [`build_context`] so it fits a token budget.
 fn format_node_result(result: &NodeSearchResult, index: usize) -> String {
     let mut s = String::new();
     s.push_str(&format!(
@@ -272,18 +272,6 @@ fn format_node_result(result: &NodeSearchResult, index: usize) -> String {
             ));
         }
     }
-    if !result.chunks.is_empty() {
-        s.push_str("  Content:\n");
-        for chunk in result.chunks.iter().take(3) {
-            s.push_str(&format!("    • {}\n", chunk.content));
-        }
-        if result.chunks.len() > 3 {
-            s.push_str(&format!(
-                "    (… {} more chunks)\n",
-                result.chunks.len() - 3
-            ));
-        }
-    }
     s
 }
 
@@ -587,6 +575,7 @@ impl Tool for HybridSearchTool {
             limit: args.limit.unwrap_or(3),
             alpha: args.alpha.unwrap_or(0.5).clamp(0.0, 1.0),
             rerank: args.rerank.unwrap_or(true),
+            preprocess: Some(QueryPreprocessing::default()),
             ..HybridSearchConfig::default()
         };
 
@@ -611,6 +600,11 @@ impl Tool for HybridSearchTool {
             output.push_str(&format_node_result(result, i));
             output.push('\n');
         }
+        let context = build_context(&args.query, &results, &ContextBuilderConfig::default());
+        if !context.text.is_empty() {
+            output.push_str("Content:\n\n");
+            output.push_str(&context.text);
+        }
 
         Ok(output)
     }
@@ -790,6 +784,9 @@ pub struct UpsertEdgeArgs {
     pub edge_type: String,
     /// Optional weight (0.0–1.0). Defaults to 1.0.
     pub weight: Option<f32>,
+    /// Optional edge properties declared by the edge type's schema,
+    /// e.g. {"role": "quartermaster", "since": "1203"}.
+    pub properties: Option<HashMap<String, String>>,
 }
 
 /// Rig tool: create or update an edge (relationship) between two nodes.
@@ -876,9 +873,16 @@ impl Tool for UpsertEdgeTool {
         let source_id = resolve_node(&self.graph, &args.source)?;
         let target_id = resolve_node(&self.graph, &args.target)?;
 
-        let weight = args.weight.unwrap_or(1.0);
-        self.graph
-            .connect_objects_weighted_str(source_id, target_id, &args.edge_type, weight)
+        let request = CreateRelationshipRequest {
+            from: source_id,
+            to: target_id,
+            edge_type: args.edge_type.clone(),
+            weight: args.weight,
+            properties: args.properties.unwrap_or_default(),
+        };
+        let edge = self
+            .graph
+            .create_relationship(request)
             .map_err(|e| ToolError(format!("Failed to upsert edge: {e:#}")))?;
 
         // Re-embed both endpoints so the new relationship appears in semantic search.
@@ -915,9 +919,14 @@ impl Tool for UpsertEdgeTool {
             .unwrap_or_else(|| target_id.to_string());
 
         let mut output = format!(
-            "Edge created: {source_name} -[{}]-> {target_name} (weight: {weight:.2})",
-            args.edge_type,
+            "Edge created: {source_name} -[{}]-> {target_name} (weight: {:.2})",
+            args.edge_type, edge.weight,
         );
+        let mut properties: Vec<_> = edge.metadata.iter().collect();
+        properties.sort();
+        for (key, value) in properties {
+            output.push_str(&format!("\n  {key}: {value}"));
+        }
         for w in &reembed_warnings {
             output.push('\n');
             output.push_str(w);
@@ -1048,6 +1057,10 @@ impl GraphAgent {
             .map_err(|e| anyhow::anyhow!("Failed to build rig client: {e}"))?;
         let base_prompt: String = system_prompt.into();
         let schema_summary = graph.schema_prompt_summary_all();
+        let glossary = graph
+            .glossary()
+            .map(|g| g.prompt_section())
+            .unwrap_or_default();
 
         let tool_guidance = "\
 ## Tool-use guidelines
@@ -1060,11 +1073,15 @@ impl GraphAgent {
 4. **Stop when done.** After a successful tool call, report the result to the user. \
    Do not re-call a tool for the same node unless asked.";
 
-        let full_prompt = if schema_summary.is_empty() {
+        let mut full_prompt = if schema_summary.is_empty() {
             format!("{base_prompt}\n\n{tool_guidance}")
         } else {
             format!("{base_prompt}\n\n{tool_guidance}\n\n{schema_summary}")
         };
+        if !glossary.is_empty() {
+            full_prompt.push_str("\n\n");
+            full_prompt.push_str(&glossary);
+        }
 
         let cached_additional_params = Self::build_additional_params(&params);
 
@@ -0,0 +1,466 @@
+//! Shared access to one graph from several windows.
+//!
+//! A desktop session opens the same project in more than one window — the
+//! graph view, an editor, the play screen — and each issues reads and
+//! writes on its own schedule.  [`GraphActor`] owns the graph and hands out
+//! a [`WindowHandle`] per window:
+//!
+//! - Writes go through a single actor thread, one at a time, in the order
+//!   they arrive.  Each successful write is numbered and announced as a
+//!   [`GraphChange`], so every window observes changes in the same order.
+//! - Reads run on the [`KnowledgeGraphAsync`] storage pool, concurrently
+//!   with each other and with the actor.  A read started after a write's
+//!   future resolved sees that write.
+//! - [`WindowHandle::subscribe`] returns a [`ChangeSubscription`] filtered
+//!   to the objects the window shows, optionally skipping the window's own
+//!   writes.  A subscriber that falls too far behind receives
+//!   [`ChangeKind::Resync`] and should reload.
+
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, oneshot};
+
+use crate::async_graph::KnowledgeGraphAsync;
+use crate::error::{Result, UForgeError};
+use crate::types::{ChunkId, ChunkType, Edge, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Capacity of the [`GraphChange`] channel.
+pub const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+type Command = Box<dyn FnOnce(&KnowledgeGraph) + Send>;
+
+/// Identifies one window of a [`GraphActor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WindowId(pub u64);
+
+impl std::fmt::Display for WindowId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "window-{}", self.0)
+    }
+}
+
+/// What a [`GraphChange`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    ObjectAdded,
+    ObjectUpdated,
+    ObjectDeleted,
+    EdgeAdded,
+    EdgeRemoved,
+    ChunksChanged,
+    /// A [`WindowHandle::write`] that named its own objects.
+    Other,
+    /// The subscriber missed changes; reload everything.
+    Resync,
+}
+
+/// One committed write, in actor order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphChange {
+    /// Increases by one per write; gaps only follow a [`ChangeKind::Resync`].
+    pub seq: u64,
+    pub origin: WindowId,
+    pub kind: ChangeKind,
+    /// Objects the write touched; empty for [`ChangeKind::Resync`].
+    pub objects: Vec<ObjectId>,
+}
+
+/// Which changes a [`ChangeSubscription`] yields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeFilter {
+    /// Only changes touching one of these objects; `None` for all.
+    pub objects: Option<HashSet<ObjectId>>,
+    /// Also yield the subscribing window's own writes.
+    pub include_own: bool,
+}
+
+impl ChangeFilter {
+    /// Every change, including the window's own.
+    pub fn all() -> Self {
+        Self {
+            objects: None,
+            include_own: true,
+        }
+    }
+
+    /// Changes touching any of `ids`, from other windows.
+    pub fn objects(ids: impl IntoIterator<Item = ObjectId>) -> Self {
+        Self {
+            objects: Some(ids.into_iter().collect()),
+            include_own: false,
+        }
+    }
+
+    fn matches(&self, window: WindowId, change: &GraphChange) -> bool {
+        if change.kind == ChangeKind::Resync {
+            return true;
+        }
+        if change.origin == window && !self.include_own {
+            return false;
+        }
+        self.objects
+            .as_ref()
+            .is_none_or(|ids| change.objects.iter().any(|id| ids.contains(id)))
+    }
+}
+
+struct ActorShared {
+    commands: mpsc::Sender<Command>,
+    changes: broadcast::Sender<GraphChange>,
+    reads: KnowledgeGraphAsync,
+    next_window: AtomicU64,
+    next_seq: AtomicU64,
+}
+
+/// Owner of a graph shared by several windows; see the module docs.
+/// Clones share the actor, which stops once every clone and window handle
+/// is dropped.
+#[derive(Clone)]
+pub struct GraphActor {
+    shared: Arc<ActorShared>,
+}
+
+impl GraphActor {
+    /// Start the actor thread and read pool for `graph`.
+    pub fn new(graph: Arc<KnowledgeGraph>) -> Result<Self> {
+        let reads = KnowledgeGraphAsync::new(graph.clone())?;
+        let (commands, queue) = mpsc::channel::<Command>();
+        thread::Builder::new()
+            .name("u-forge-actor".to_string())
+            .spawn(move || {
+                while let Ok(command) = queue.recv() {
+                    command(&graph);
+                }
+            })
+            .context("Failed to start graph actor thread")?;
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Ok(Self {
+            shared: Arc::new(ActorShared {
+                commands,
+                changes,
+                reads,
+                next_window: AtomicU64::new(1),
+                next_seq: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// The shared graph.
+    pub fn graph(&self) -> &Arc<KnowledgeGraph> {
+        self.shared.reads.graph()
+    }
+
+    /// A handle for a newly opened window.
+    pub fn open_window(&self) -> WindowHandle {
+        WindowHandle {
+            id: WindowId(self.shared.next_window.fetch_add(1, Ordering::Relaxed)),
+            actor: self.clone(),
+        }
+    }
+}
+
+/// One window's access to a [`GraphActor`].
+#[derive(Clone)]
+pub struct WindowHandle {
+    id: WindowId,
+    actor: GraphActor,
+}
+
+impl WindowHandle {
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    /// Changes committed from now on that match `filter`.
+    pub fn subscribe(&self, filter: ChangeFilter) -> ChangeSubscription {
+        ChangeSubscription {
+            window: self.id,
+            filter,
+            receiver: self.actor.shared.changes.subscribe(),
+            last_seq: None,
+        }
+    }
+
+    /// Run `f` on the read pool; see [`KnowledgeGraphAsync::run`].
+    pub async fn read<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&KnowledgeGraph) -> Result<T> + Send + 'static,
+    {
+        self.actor.shared.reads.run(f).await
+    }
+
+    /// Queue `f` on the actor thread and await its result.  When it
+    /// succeeds, a [`GraphChange`] of `kind` naming `objects` plus any ids
+    /// `f` returns is broadcast before this resolves.  A panic in `f` is
+    /// returned as [`UForgeError::Internal`].
+    pub async fn write<T, F>(&self, kind: ChangeKind, objects: Vec<ObjectId>, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&KnowledgeGraph) -> Result<(T, Vec<ObjectId>)> + Send + 'static,
+    {
+        let origin = self.id;
+        let shared = self.actor.shared.clone();
+        let actor = shared.clone();
+        let (reply, result) = oneshot::channel();
+        let command: Command = Box::new(move |graph| {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| f(graph)))
+                .unwrap_or_else(|_| {
+                    Err(UForgeError::Internal(anyhow::anyhow!(
+                        "Graph write panicked"
+                    )))
+                })
+                .map(|(value, touched)| {
+                    let mut objects = objects;
+                    for id in touched {
+                        if !objects.contains(&id) {
+                            objects.push(id);
+                        }
+                    }
+                    // Only the actor thread touches the counter, so
+                    // sequence order is commit order.
+                    let seq = actor.next_seq.fetch_add(1, Ordering::Relaxed);
+                    let _ = actor.changes.send(GraphChange {
+                        seq,
+                        origin,
+                        kind,
+                        objects,
+                    });
+                    value
+                });
+            let _ = reply.send(outcome);
+        });
+        shared
+            .commands
+            .send(command)
+            .map_err(|_| UForgeError::Internal(anyhow::anyhow!("Graph actor has exited")))?;
+        result
+            .await
+            .map_err(|_| UForgeError::Internal(anyhow::anyhow!("Graph actor dropped the write")))?
+    }
+
+    // ── Writes ────────────────────────────────────────────────────────────────
+
+    pub async fn add_object(&self, metadata: ObjectMetadata) -> Result<ObjectId> {
+        self.write(ChangeKind::ObjectAdded, Vec::new(), move |g| {
+            let id = g.add_object(metadata)?;
+            Ok((id, vec![id]))
+        })
+        .await
+    }
+
+    pub async fn update_object(&self, metadata: ObjectMetadata) -> Result<()> {
+        let id = metadata.id;
+        self.write(ChangeKind::ObjectUpdated, vec![id], move |g| {
+            Ok((g.update_object(metadata)?, Vec::new()))
+        })
+        .await
+    }
+
+    pub async fn delete_object(&self, id: ObjectId) -> Result<()> {
+        self.write(ChangeKind::ObjectDeleted, vec![id], move |g| {
+            Ok((g.delete_object(id)?, Vec::new()))
+        })
+        .await
+    }
+
+    pub async fn add_edge(&self, edge: Edge, allow_violation: bool) -> Result<()> {
+        let objects = vec![edge.from, edge.to];
+        self.write(ChangeKind::EdgeAdded, objects, move |g| {
+            Ok((g.add_edge(edge, allow_violation)?, Vec::new()))
+        })
+        .await
+    }
+
+    pub async fn delete_edge(
+        &self,
+        from: ObjectId,
+        to: ObjectId,
+        edge_type: impl Into<String>,
+    ) -> Result<()> {
+        let edge_type = edge_type.into();
+        self.write(ChangeKind::EdgeRemoved, vec![from, to], move |g| {
+            Ok((g.delete_edge(from, to, &edge_type)?, Vec::new()))
+        })
+        .await
+    }
+
+    pub async fn add_text_chunk(
+        &self,
+        object_id: ObjectId,
+        content: String,
+        chunk_type: ChunkType,
+    ) -> Result<Vec<ChunkId>> {
+        self.write(ChangeKind::ChunksChanged, vec![object_id], move |g| {
+            Ok((
+                g.add_text_chunk(object_id, content, chunk_type)?,
+                Vec::new(),
+            ))
+        })
+        .await
+    }
+}
+
+/// A window's filtered view of the change stream.
+pub struct ChangeSubscription {
+    window: WindowId,
+    filter: ChangeFilter,
+    receiver: broadcast::Receiver<GraphChange>,
+    last_seq: Option<u64>,
+}
+
+impl ChangeSubscription {
+    /// The next matching change, or `None` once the actor has stopped.
+    pub async fn recv(&mut self) -> Option<GraphChange> {
+        loop {
+            let change = match self.receiver.recv().await {
+                Ok(change) => change,
+                Err(broadcast::error::RecvError::Lagged(_)) => GraphChange {
+                    seq: self.last_seq.map_or(0, |s| s + 1),
+                    origin: self.window,
+                    kind: ChangeKind::Resync,
+                    objects: Vec::new(),
+                },
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            self.last_seq = Some(change.seq);
+            if self.filter.matches(self.window, &change) {
+                return Some(change);
+            }
+        }
+    }
+
+    /// Like [`recv`](Self::recv) without waiting: `None` when no matching
+    /// change is queued.
+    pub fn try_recv(&mut self) -> Option<GraphChange> {
+        loop {
+            let change = match self.receiver.try_recv() {
+                Ok(change) => change,
+                Err(broadcast::error::TryRecvError::Lagged(_)) => GraphChange {
+                    seq: self.last_seq.map_or(0, |s| s + 1),
+                    origin: self.window,
+                    kind: ChangeKind::Resync,
+                    objects: Vec::new(),
+                },
+                Err(_) => return None,
+            };
+            self.last_seq = Some(change.seq);
+            if self.filter.matches(self.window, &change) {
+                return Some(change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::EdgeType;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_windows_share_ordered_changes() {
+        let (graph, _temp_dir) = create_test_graph();
+        let graph = Arc::new(graph);
+        let actor = GraphActor::new(graph).unwrap();
+        let editor = actor.open_window();
+        let map_view = actor.open_window();
+        let play = actor.open_window();
+        assert_ne!(editor.id(), map_view.id());
+
+        let mut everything = play.subscribe(ChangeFilter::all());
+        let mut editor_own = editor.subscribe(ChangeFilter::objects([]));
+
+        // Writes from several windows at once all land, in one order.
+        let mut tasks = Vec::new();
+        for (i, window) in [
+            editor.clone(),
+            map_view.clone(),
+            editor.clone(),
+            map_view.clone(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            tasks.push(tokio::spawn(async move {
+                window
+                    .add_object(ObjectMetadata::new("npc".to_string(), format!("NPC {i}")))
+                    .await
+                    .unwrap()
+            }));
+        }
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.push(task.await.unwrap());
+        }
+
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            let change = everything.recv().await.unwrap();
+            assert_eq!(change.kind, ChangeKind::ObjectAdded);
+            seen.push(change);
+        }
+        assert_eq!(
+            seen.iter().map(|c| c.seq).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        let mut touched: Vec<_> = seen.iter().map(|c| c.objects[0]).collect();
+        touched.sort_by_key(|id| id.0);
+        ids.sort_by_key(|id| id.0);
+        assert_eq!(touched, ids);
+        assert!(editor_own.try_recv().is_none());
+
+        // A window watching one object hears about it from other windows only.
+        let mut watcher = map_view.subscribe(ChangeFilter::objects([ids[0]]));
+        editor
+            .add_text_chunk(ids[1], "Unrelated".to_string(), ChunkType::UserNote)
+            .await
+            .unwrap();
+        map_view
+            .add_text_chunk(ids[0], "Own edit".to_string(), ChunkType::UserNote)
+            .await
+            .unwrap();
+        editor
+            .add_edge(Edge::new(ids[1], ids[0], EdgeType::new("knows")), true)
+            .await
+            .unwrap();
+        let change = watcher.recv().await.unwrap();
+        assert_eq!(change.kind, ChangeKind::EdgeAdded);
+        assert_eq!(change.origin, editor.id());
+        assert_eq!(change.seq, 6);
+
+        let first = ids[0];
+        let name = play
+            .read(move |g| Ok(g.get_object(first)?.unwrap().name))
+            .await
+            .unwrap();
+        assert!(name.starts_with("NPC"));
+
+        // Failed and panicking writes are not announced.
+        let err = editor
+            .write(
+                ChangeKind::Other,
+                vec![ids[0]],
+                |_| -> Result<((), Vec<ObjectId>)> { panic!("boom") },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        assert!(watcher.try_recv().is_none());
+        editor.delete_object(ids[0]).await.unwrap();
+        assert_eq!(
+            watcher.recv().await.unwrap().kind,
+            ChangeKind::ObjectDeleted
+        );
+    }
+}
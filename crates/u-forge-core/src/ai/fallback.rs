@@ -0,0 +1,282 @@
+//! Ordered embedding provider chain with automatic fallback.
+//!
+//! [`EmbeddingChain`] wraps providers in preference order — typically a local
+//! NPU/GPU model first and a remote endpoint after it — and is itself an
+//! [`EmbeddingProvider`], so it registers with the
+//! [`InferenceQueue`](crate::queue::InferenceQueue) like any single provider.
+//! Each call goes to the first provider that is not cooling down; one that
+//! fails is skipped for [`DEFAULT_RETRY_AFTER`] and the call moves on to the
+//! next.  When every provider is cooling down the chain tries them all in
+//! order anyway rather than failing without a request.
+//!
+//! Different models embed into different spaces even at the same
+//! dimensionality, so [`embed_tagged`](EmbeddingChain::embed_tagged) returns a
+//! [`TaggedEmbedding`] naming the model that produced the vector.  Store it
+//! with [`KnowledgeGraph::store_tagged_chunk_embedding`] and search with
+//! [`KnowledgeGraph::search_chunks_semantic_tagged`], which skips chunks
+//! embedded by a different model than the query.
+//!
+//! [`KnowledgeGraph::store_tagged_chunk_embedding`]: crate::KnowledgeGraph::store_tagged_chunk_embedding
+//! [`KnowledgeGraph::search_chunks_semantic_tagged`]: crate::KnowledgeGraph::search_chunks_semantic_tagged
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tracing::warn;
+
+use super::embeddings::{EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType};
+use crate::error::UForgeError;
+
+/// How long a failed provider is skipped before the chain tries it again.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// A vector and the model that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedEmbedding {
+    pub vector: Vec<f32>,
+    pub model: String,
+}
+
+/// Embedding providers tried in order; see the module docs.
+pub struct EmbeddingChain {
+    providers: Vec<Arc<dyn EmbeddingProvider>>,
+    /// When each provider last failed, if it is cooling down.
+    failed_at: Mutex<Vec<Option<Instant>>>,
+    retry_after: Duration,
+}
+
+impl EmbeddingChain {
+    /// Chain `providers`, most preferred first.  Fails with
+    /// [`UForgeError::ValidationFailed`] when the list is empty or the
+    /// providers disagree on dimensions, since their vectors share an index.
+    pub fn new(providers: Vec<Arc<dyn EmbeddingProvider>>) -> Result<Self> {
+        let Some(primary) = providers.first() else {
+            return Err(
+                UForgeError::ValidationFailed("Embedding chain needs a provider".to_string())
+                    .into(),
+            );
+        };
+        let dimensions = primary.dimensions()?;
+        for provider in &providers[1..] {
+            let other = provider.dimensions()?;
+            if other != dimensions {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "Embedding chain providers disagree on dimensions: {} has {other}, {} has {dimensions}",
+                    model_name(provider.as_ref()),
+                    model_name(primary.as_ref()),
+                ))
+                .into());
+            }
+        }
+        Ok(Self {
+            failed_at: Mutex::new(vec![None; providers.len()]),
+            providers,
+            retry_after: DEFAULT_RETRY_AFTER,
+        })
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Model of the provider the next call would go to first.
+    pub fn active_model(&self) -> String {
+        model_name(self.providers[self.order()[0]].as_ref())
+    }
+
+    /// Embed `text` with the first provider that answers.
+    pub async fn embed_tagged(&self, text: &str) -> Result<TaggedEmbedding> {
+        let mut last_error = None;
+        for index in self.order() {
+            let provider = &self.providers[index];
+            match provider.embed(text).await {
+                Ok(vector) => {
+                    self.failed_at.lock()[index] = None;
+                    return Ok(TaggedEmbedding {
+                        vector,
+                        model: model_name(provider.as_ref()),
+                    });
+                }
+                Err(e) => last_error = Some(self.mark_failed(index, e)),
+            }
+        }
+        Err(last_error.expect("chain has at least one provider"))
+    }
+
+    /// Embed `texts` with the first provider that answers for the whole
+    /// batch, so every vector comes from the same model.
+    pub async fn embed_batch_tagged(&self, texts: Vec<String>) -> Result<(Vec<Vec<f32>>, String)> {
+        let mut last_error = None;
+        for index in self.order() {
+            let provider = &self.providers[index];
+            match provider.embed_batch(texts.clone()).await {
+                Ok(vectors) => {
+                    self.failed_at.lock()[index] = None;
+                    return Ok((vectors, model_name(provider.as_ref())));
+                }
+                Err(e) => last_error = Some(self.mark_failed(index, e)),
+            }
+        }
+        Err(last_error.expect("chain has at least one provider"))
+    }
+
+    /// Provider indices to try: those not cooling down in preference order,
+    /// then the cooling ones.
+    fn order(&self) -> Vec<usize> {
+        let failed_at = self.failed_at.lock();
+        let cooling =
+            |i: &usize| failed_at[*i].is_some_and(|at| at.elapsed() < self.retry_after);
+        let (mut ready, cooling): (Vec<usize>, Vec<usize>) =
+            (0..self.providers.len()).partition(|i| !cooling(i));
+        ready.extend(cooling);
+        ready
+    }
+
+    fn mark_failed(&self, index: usize, error: anyhow::Error) -> anyhow::Error {
+        warn!(
+            model = %model_name(self.providers[index].as_ref()),
+            %error,
+            "Embedding provider failed — falling back"
+        );
+        self.failed_at.lock()[index] = Some(Instant::now());
+        error
+    }
+}
+
+/// The provider's model name, or its backend type when it has none.
+fn model_name(provider: &dyn EmbeddingProvider) -> String {
+    provider
+        .model_info()
+        .map(|info| info.name)
+        .unwrap_or_else(|| format!("{:?}", provider.provider_type()))
+}
+
+#[async_trait]
+impl EmbeddingProvider for EmbeddingChain {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_tagged(text).await.map(|tagged| tagged.vector)
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch_tagged(texts)
+            .await
+            .map(|(vectors, _)| vectors)
+    }
+
+    fn dimensions(&self) -> Result<usize> {
+        self.providers[0].dimensions()
+    }
+
+    /// The smallest limit in the chain, so text fits whichever provider
+    /// ends up embedding it.
+    fn max_tokens(&self) -> Result<usize> {
+        let mut limit = usize::MAX;
+        for provider in &self.providers {
+            limit = limit.min(provider.max_tokens()?);
+        }
+        Ok(limit)
+    }
+
+    fn provider_type(&self) -> EmbeddingProviderType {
+        self.providers[self.order()[0]].provider_type()
+    }
+
+    fn model_info(&self) -> Option<EmbeddingModelInfo> {
+        self.providers[self.order()[0]].model_info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Mock {
+        name: &'static str,
+        dimensions: usize,
+        up: AtomicBool,
+    }
+
+    impl Mock {
+        fn new(name: &'static str, dimensions: usize) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                dimensions,
+                up: AtomicBool::new(true),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for Mock {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            if !self.up.load(Ordering::Relaxed) {
+                anyhow::bail!("{} is unreachable", self.name);
+            }
+            Ok(vec![1.0; self.dimensions])
+        }
+
+        async fn embed_batch(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+            let mut out = Vec::new();
+            for text in texts {
+                out.push(self.embed(&text).await?);
+            }
+            Ok(out)
+        }
+
+        fn dimensions(&self) -> Result<usize> {
+            Ok(self.dimensions)
+        }
+
+        fn max_tokens(&self) -> Result<usize> {
+            Ok(512)
+        }
+
+        fn provider_type(&self) -> EmbeddingProviderType {
+            EmbeddingProviderType::Lemonade
+        }
+
+        fn model_info(&self) -> Option<EmbeddingModelInfo> {
+            Some(EmbeddingModelInfo {
+                name: self.name.to_string(),
+                dimensions: self.dimensions,
+                description: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_falls_back_and_recovers() {
+        let local = Mock::new("local", 4);
+        let remote = Mock::new("remote", 4);
+        let chain = EmbeddingChain::new(vec![local.clone() as Arc<dyn EmbeddingProvider>, remote.clone()])
+            .unwrap()
+            .with_retry_after(Duration::ZERO);
+        assert_eq!(chain.embed_tagged("a").await.unwrap().model, "local");
+
+        local.up.store(false, Ordering::Relaxed);
+        assert_eq!(chain.embed_tagged("a").await.unwrap().model, "remote");
+        let (vectors, model) = chain
+            .embed_batch_tagged(vec!["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!((vectors.len(), model.as_str()), (2, "remote"));
+
+        // With no cooldown the primary is retried as soon as it is back.
+        local.up.store(true, Ordering::Relaxed);
+        assert_eq!(chain.embed_tagged("a").await.unwrap().model, "local");
+
+        remote.up.store(false, Ordering::Relaxed);
+        local.up.store(false, Ordering::Relaxed);
+        assert!(chain.embed("a").await.is_err());
+
+        assert!(
+            EmbeddingChain::new(vec![local as Arc<dyn EmbeddingProvider>, Mock::new("wide", 8)])
+                .is_err()
+        );
+    }
+}
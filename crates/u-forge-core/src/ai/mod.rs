@@ -1,11 +1,13 @@
 //! AI provider abstractions: embedding and transcription.
 pub mod embeddings;
+pub mod fallback;
 pub mod transcription;
 
 pub use embeddings::{
     EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType,
     LemonadeProvider,
 };
+pub use fallback::{EmbeddingChain, TaggedEmbedding};
 pub use transcription::{
     LemonadeTranscriptionProvider, TranscriptionProvider,
     mime_for_filename,
@@ -0,0 +1,130 @@
+//! Object archival — cold storage for retired PCs and concluded arcs.
+//!
+//! [`KnowledgeGraph::archive_object`] sets an object aside without changing
+//! it: its properties, chunks, edges and history all stay, and
+//! [`KnowledgeGraph::get_object`] still returns it.  What changes is the
+//! default views: [`search_hybrid`](crate::search::search_hybrid) and
+//! [`KnowledgeGraph::get_graph_data`] leave archived objects out unless
+//! [`HybridSearchConfig::include_archived`](crate::search::HybridSearchConfig::include_archived)
+//! or [`GraphDataRequest::include_archived`](crate::graph_data::GraphDataRequest::include_archived)
+//! is set, and [`KnowledgeGraph::get_active_objects`] is the archive-aware
+//! counterpart of [`KnowledgeGraph::get_all_objects`].  Unlike deleting,
+//! archiving is undone by [`KnowledgeGraph::unarchive_object`] with nothing
+//! lost.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// An archived object and when it was archived.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedObject {
+    pub object: ObjectMetadata,
+    pub archived_at: DateTime<Utc>,
+}
+
+impl KnowledgeGraph {
+    /// Archive `id`.  Returns `false` when it was archived already.  Fails
+    /// with [`UForgeError::NotFound`] for an unknown object.
+    pub fn archive_object(&self, id: ObjectId) -> Result<bool> {
+        if self.get_object(id)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Object {id} not found")));
+        }
+        self.storage.archive_node(id, Utc::now())
+    }
+
+    /// Restore `id` to the default views.  Returns `false` when it was not
+    /// archived.
+    pub fn unarchive_object(&self, id: ObjectId) -> Result<bool> {
+        self.storage.unarchive_node(id)
+    }
+
+    pub fn is_archived(&self, id: ObjectId) -> Result<bool> {
+        Ok(self.storage.archived_nodes()?.contains_key(&id))
+    }
+
+    /// Ids of every archived object.
+    pub fn archived_ids(&self) -> Result<HashSet<ObjectId>> {
+        Ok(self.storage.archived_nodes()?.into_keys().collect())
+    }
+
+    /// Every archived object, most recently archived first.
+    pub fn archived_objects(&self) -> Result<Vec<ArchivedObject>> {
+        let mut out = Vec::new();
+        for (id, archived_at) in self.storage.archived_nodes()? {
+            if let Some(object) = self.get_object(id)? {
+                out.push(ArchivedObject {
+                    object,
+                    archived_at,
+                });
+            }
+        }
+        out.sort_by(|a, b| {
+            b.archived_at
+                .cmp(&a.archived_at)
+                .then_with(|| a.object.name.cmp(&b.object.name))
+        });
+        Ok(out)
+    }
+
+    /// Every object, leaving out archived ones unless `include_archived`.
+    pub fn get_active_objects(&self, include_archived: bool) -> Result<Vec<ObjectMetadata>> {
+        let mut objects = self.get_all_objects()?;
+        if !include_archived {
+            let archived = self.archived_ids()?;
+            objects.retain(|o| !archived.contains(&o.id));
+        }
+        Ok(objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_data::{GraphDataRequest, GraphScope};
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_archived_objects_leave_default_views() {
+        let (graph, _temp_dir) = create_test_graph();
+        let retired = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Old PC".to_string()))
+            .unwrap();
+        let active = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "New PC".to_string()))
+            .unwrap();
+        graph.connect_objects_str(retired, active, "knows").unwrap();
+
+        assert!(graph.archive_object(retired).unwrap());
+        assert!(!graph.archive_object(retired).unwrap());
+        assert!(graph.is_archived(retired).unwrap());
+        assert!(graph.get_object(retired).unwrap().is_some());
+
+        let names: Vec<String> = graph
+            .get_active_objects(false)
+            .unwrap()
+            .into_iter()
+            .map(|o| o.name)
+            .collect();
+        assert_eq!(names, ["New PC"]);
+        assert_eq!(graph.get_active_objects(true).unwrap().len(), 2);
+
+        let mut request = GraphDataRequest::new(GraphScope::All);
+        let data = graph.get_graph_data(&request).unwrap();
+        assert_eq!((data.objects.len(), data.edges.len()), (1, 0));
+        request.include_archived = true;
+        let data = graph.get_graph_data(&request).unwrap();
+        assert_eq!((data.objects.len(), data.edges.len()), (2, 1));
+
+        assert_eq!(graph.archived_objects().unwrap()[0].object.id, retired);
+        assert!(graph.unarchive_object(retired).unwrap());
+        assert!(graph.archived_ids().unwrap().is_empty());
+        let err = graph.archive_object(ObjectId::new_v4()).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+    }
+}
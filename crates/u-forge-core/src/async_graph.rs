@@ -0,0 +1,249 @@
+//! Async access to a [`KnowledgeGraph`] without blocking the runtime.
+//!
+//! Every storage call takes the SQLite connection mutex and does disk I/O,
+//! so calling the graph straight from an async handler stalls a runtime
+//! worker for the duration.  [`KnowledgeGraphAsync`] sends each call to a
+//! small pool of dedicated storage threads and awaits the result.  The pool
+//! is separate from Tokio's blocking pool, so slow graph work cannot starve
+//! other blocking tasks (file reads, audio decoding) and vice versa, and its
+//! fixed size bounds how many threads queue on the connection mutex.
+//!
+//! [`run`](KnowledgeGraphAsync::run) dispatches any closure over the graph;
+//! the common CRUD and query calls have named wrappers.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use anyhow::Context;
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use crate::error::{Result, UForgeError};
+use crate::graph::{CompactionReport, GraphStats};
+use crate::types::{ChunkId, ChunkType, Edge, ObjectId, ObjectMetadata, QueryResult, TextChunk};
+use crate::KnowledgeGraph;
+
+/// Storage threads started by [`KnowledgeGraphAsync::new`].
+pub const DEFAULT_STORAGE_THREADS: usize = 4;
+
+type Job = Box<dyn FnOnce(&KnowledgeGraph) + Send>;
+
+/// Cloneable async handle to a [`KnowledgeGraph`].  Clones share the graph
+/// and the thread pool; the threads exit once every clone is dropped.
+#[derive(Clone)]
+pub struct KnowledgeGraphAsync {
+    graph: Arc<KnowledgeGraph>,
+    jobs: mpsc::Sender<Job>,
+}
+
+impl KnowledgeGraphAsync {
+    /// Wrap `graph` with [`DEFAULT_STORAGE_THREADS`] storage threads.
+    pub fn new(graph: Arc<KnowledgeGraph>) -> Result<Self> {
+        Self::with_threads(graph, DEFAULT_STORAGE_THREADS)
+    }
+
+    /// Wrap `graph` with `threads` storage threads (at least one).
+    pub fn with_threads(graph: Arc<KnowledgeGraph>, threads: usize) -> Result<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        for i in 0..threads.max(1) {
+            let queue = queue.clone();
+            let graph = graph.clone();
+            thread::Builder::new()
+                .name(format!("u-forge-storage-{i}"))
+                .spawn(move || loop {
+                    // Hold the lock only while taking a job, not while running it.
+                    let job = queue.lock().recv();
+                    match job {
+                        Ok(job) => job(&graph),
+                        Err(_) => break,
+                    }
+                })
+                .context("Failed to start storage thread")?;
+        }
+        Ok(Self { graph, jobs })
+    }
+
+    /// Open (or create) the graph at `db_path` without blocking the runtime.
+    pub async fn open(db_path: impl Into<PathBuf>) -> Result<Self> {
+        let db_path = db_path.into();
+        let graph = tokio::task::spawn_blocking(move || KnowledgeGraph::new(db_path))
+            .await
+            .context("Failed to join graph open task")??;
+        Self::new(Arc::new(graph))
+    }
+
+    /// The wrapped graph, for synchronous callers that already run off the
+    /// runtime.
+    pub fn graph(&self) -> &Arc<KnowledgeGraph> {
+        &self.graph
+    }
+
+    /// Run `f` on a storage thread and await its result.  A panic in `f` is
+    /// returned as [`UForgeError::Internal`] rather than unwinding into the
+    /// caller.
+    pub async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&KnowledgeGraph) -> Result<T> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let job: Job = Box::new(move |graph| {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| f(graph))).unwrap_or_else(|_| {
+                Err(UForgeError::Internal(anyhow::anyhow!(
+                    "Storage call panicked"
+                )))
+            });
+            // The caller may have stopped waiting; nothing to do then.
+            let _ = reply.send(outcome);
+        });
+        self.jobs
+            .send(job)
+            .map_err(|_| UForgeError::Internal(anyhow::anyhow!("Storage threads have exited")))?;
+        result.await.map_err(|_| {
+            UForgeError::Internal(anyhow::anyhow!("Storage thread dropped the call"))
+        })?
+    }
+
+    // ── Objects ───────────────────────────────────────────────────────────────
+
+    pub async fn add_object(&self, metadata: ObjectMetadata) -> Result<ObjectId> {
+        self.run(move |g| g.add_object(metadata)).await
+    }
+
+    pub async fn get_object(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
+        self.run(move |g| g.get_object(id)).await
+    }
+
+    pub async fn get_all_objects(&self) -> Result<Vec<ObjectMetadata>> {
+        self.run(|g| g.get_all_objects()).await
+    }
+
+    pub async fn update_object(&self, metadata: ObjectMetadata) -> Result<()> {
+        self.run(move |g| g.update_object(metadata)).await
+    }
+
+    pub async fn delete_object(&self, id: ObjectId) -> Result<()> {
+        self.run(move |g| g.delete_object(id)).await
+    }
+
+    pub async fn find_by_name_only(&self, name: impl Into<String>) -> Result<Vec<ObjectMetadata>> {
+        let name = name.into();
+        self.run(move |g| g.find_by_name_only(&name)).await
+    }
+
+    // ── Edges ─────────────────────────────────────────────────────────────────
+
+    pub async fn connect_objects_str(
+        &self,
+        from: ObjectId,
+        to: ObjectId,
+        edge_type: impl Into<String>,
+    ) -> Result<()> {
+        let edge_type = edge_type.into();
+        self.run(move |g| g.connect_objects_str(from, to, &edge_type))
+            .await
+    }
+
+    pub async fn get_relationships(&self, id: ObjectId) -> Result<Vec<Edge>> {
+        self.run(move |g| g.get_relationships(id)).await
+    }
+
+    // ── Chunks and queries ────────────────────────────────────────────────────
+
+    pub async fn add_text_chunk(
+        &self,
+        object_id: ObjectId,
+        content: String,
+        chunk_type: ChunkType,
+    ) -> Result<Vec<ChunkId>> {
+        self.run(move |g| g.add_text_chunk(object_id, content, chunk_type))
+            .await
+    }
+
+    pub async fn get_text_chunks(&self, object_id: ObjectId) -> Result<Vec<TextChunk>> {
+        self.run(move |g| g.get_text_chunks(object_id)).await
+    }
+
+    pub async fn search_chunks_fts(
+        &self,
+        query: impl Into<String>,
+        limit: usize,
+    ) -> Result<Vec<(ChunkId, ObjectId, String)>> {
+        let query = query.into();
+        self.run(move |g| g.search_chunks_fts(&query, limit)).await
+    }
+
+    pub async fn query_subgraph(&self, start: ObjectId, max_hops: usize) -> Result<QueryResult> {
+        self.run(move |g| g.query_subgraph(start, max_hops)).await
+    }
+
+    pub async fn get_stats(&self) -> Result<GraphStats> {
+        self.run(|g| g.get_stats()).await
+    }
+
+    // ── Maintenance ───────────────────────────────────────────────────────────
+
+    pub async fn compact(&self) -> Result<CompactionReport> {
+        self.run(|g| g.compact()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use tempfile::TempDir;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_async_facade_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let graph = KnowledgeGraphAsync::open(temp_dir.path()).await.unwrap();
+
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Halia".to_string()))
+            .await
+            .unwrap();
+        let town = graph
+            .add_object(ObjectMetadata::new(
+                "location".to_string(),
+                "Phandalin".to_string(),
+            ))
+            .await
+            .unwrap();
+        graph
+            .connect_objects_str(id, town, "located_in")
+            .await
+            .unwrap();
+        graph
+            .add_text_chunk(
+                id,
+                "Guildmaster of the Miner's Exchange".to_string(),
+                ChunkType::Description,
+            )
+            .await
+            .unwrap();
+
+        // Concurrent calls from one runtime thread all complete.
+        let clone = graph.clone();
+        let (object, edges, hits) = tokio::join!(
+            graph.get_object(id),
+            clone.get_relationships(id),
+            graph.search_chunks_fts("Guildmaster", 5),
+        );
+        assert_eq!(object.unwrap().unwrap().name, "Halia");
+        assert_eq!(edges.unwrap().len(), 1);
+        assert_eq!(hits.unwrap()[0].1, id);
+        assert_eq!(graph.get_stats().await.unwrap().node_count, 2);
+
+        let err = graph
+            .run(|_| -> Result<()> { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Internal);
+        // The pool survives a panicking call.
+        assert!(graph.get_object(town).await.unwrap().is_some());
+    }
+}
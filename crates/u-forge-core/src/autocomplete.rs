@@ -0,0 +1,219 @@
+//! Completions for the relationship editor's target field.
+//!
+//! [`KnowledgeGraph::suggest_link_targets`] takes what the GM has typed so
+//! far, the object being edited and the edge type picked, and returns the
+//! objects worth offering:
+//!
+//! - **Name match** — exact beats prefix beats the start of a later word
+//!   beats anywhere in the name, case-insensitively.
+//! - **Schema** — when the edge type lists `allowed_target_types`, other
+//!   types are left out, so `member_of` only offers factions.
+//! - **Recency** — recently edited objects get up to
+//!   [`RECENCY_BOOST`], fading with a [`RECENCY_HALF_LIFE_DAYS`] half-life:
+//!   the NPC created this session outranks one from last year.
+//! - **Neighbourhood** — objects already connected to the edited object by
+//!   another edge get [`NEIGHBOR_BOOST`].
+//!
+//! The edited object itself, targets it already has an edge of this type
+//! to, and archived objects are never offered.  An empty query lists the
+//! most recent valid targets.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Number of suggestions returned.
+pub const SUGGESTION_LIMIT: usize = 10;
+
+/// Score added for an object edited just now.
+pub const RECENCY_BOOST: f32 = 0.3;
+
+/// Age at which the recency boost has halved.
+pub const RECENCY_HALF_LIFE_DAYS: f32 = 7.0;
+
+/// Score added for an object already connected to the edited one.
+pub const NEIGHBOR_BOOST: f32 = 0.1;
+
+/// Name rows fetched before scoring and filtering.
+const CANDIDATE_LIMIT: usize = 200;
+
+/// How the query matched a suggestion's name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameMatch {
+    Exact,
+    Prefix,
+    /// Start of a word after the first, e.g. "bal" in "Lord Baldur".
+    WordPrefix,
+    Contains,
+    /// Empty query: listed by recency alone.
+    Any,
+}
+
+impl NameMatch {
+    fn classify(name: &str, query: &str) -> Option<Self> {
+        if query.is_empty() {
+            return Some(NameMatch::Any);
+        }
+        let name = name.to_lowercase();
+        if name == query {
+            Some(NameMatch::Exact)
+        } else if name.starts_with(query) {
+            Some(NameMatch::Prefix)
+        } else if name
+            .split(|c: char| !c.is_alphanumeric())
+            .skip(1)
+            .any(|word| word.starts_with(query))
+        {
+            Some(NameMatch::WordPrefix)
+        } else if name.contains(query) {
+            Some(NameMatch::Contains)
+        } else {
+            None
+        }
+    }
+
+    fn score(self) -> f32 {
+        match self {
+            NameMatch::Exact => 1.0,
+            NameMatch::Prefix => 0.8,
+            NameMatch::WordPrefix => 0.6,
+            NameMatch::Contains => 0.4,
+            NameMatch::Any => 0.0,
+        }
+    }
+}
+
+/// One completion from [`KnowledgeGraph::suggest_link_targets`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkTargetSuggestion {
+    pub object: ObjectMetadata,
+    pub matched: NameMatch,
+    pub score: f32,
+}
+
+impl KnowledgeGraph {
+    /// Objects to offer as the target of an `edge_type` edge from
+    /// `context_object` while the GM has typed `partial_name`; see the
+    /// module docs.  Fails with [`UForgeError::NotFound`] for an unknown
+    /// context object.
+    pub fn suggest_link_targets(
+        &self,
+        partial_name: &str,
+        context_object: ObjectId,
+        edge_type: &str,
+    ) -> Result<Vec<LinkTargetSuggestion>> {
+        let source = self.get_object(context_object)?.ok_or_else(|| {
+            UForgeError::NotFound(format!("Object {context_object} not found"))
+        })?;
+        let query = partial_name.trim().to_lowercase();
+
+        let schema_name = source.schema_name.as_deref().unwrap_or("default");
+        let allowed_types: Vec<String> = self
+            .get_schema_manager()
+            .current_schema(schema_name)?
+            .and_then(|schema| schema.edge_types.get(edge_type).cloned())
+            .map(|edge| edge.allowed_target_types)
+            .unwrap_or_default();
+
+        let mut linked = HashSet::new();
+        let mut neighbors = HashSet::new();
+        for edge in self.get_relationships(context_object)? {
+            let other = if edge.from == context_object { edge.to } else { edge.from };
+            if edge.from == context_object && edge.edge_type.as_str() == edge_type {
+                linked.insert(other);
+            }
+            neighbors.insert(other);
+        }
+        let archived = self.archived_ids()?;
+
+        let candidates = if query.is_empty() {
+            self.get_all_objects()?
+        } else {
+            self.storage
+                .find_nodes_by_name_fragment(&query, CANDIDATE_LIMIT)?
+        };
+        let now = Utc::now();
+        let mut suggestions: Vec<LinkTargetSuggestion> = candidates
+            .into_iter()
+            .filter(|o| {
+                o.id != context_object
+                    && !linked.contains(&o.id)
+                    && !archived.contains(&o.id)
+                    && (allowed_types.is_empty() || allowed_types.contains(&o.object_type))
+            })
+            .filter_map(|object| {
+                let matched = NameMatch::classify(&object.name, &query)?;
+                let age_days = (now - object.updated_at).num_seconds().max(0) as f32 / 86_400.0;
+                let recency = RECENCY_BOOST * 0.5f32.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+                let neighbor = if neighbors.contains(&object.id) {
+                    NEIGHBOR_BOOST
+                } else {
+                    0.0
+                };
+                Some(LinkTargetSuggestion {
+                    score: matched.score() + recency + neighbor,
+                    matched,
+                    object,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.object.name.cmp(&b.object.name))
+        });
+        suggestions.truncate(SUGGESTION_LIMIT);
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_suggest_link_targets_filters_and_ranks() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |ty: &str, name: &str| {
+            graph
+                .add_object(ObjectMetadata::new(ty.to_string(), name.to_string()))
+                .unwrap()
+        };
+        let gundren = add("character", "Gundren Rockseeker");
+        add("faction", "Lords' Alliance");
+        let zhent = add("faction", "Zhentarim");
+        let mut old = ObjectMetadata::new("faction".to_string(), "Zhentarim Remnant".to_string());
+        old.updated_at = Utc::now() - chrono::Duration::days(365);
+        graph.add_object(old).unwrap();
+        add("location", "Zhentil Keep");
+
+        // `member_of` only targets factions; the fresher exact match wins.
+        let names = |query: &str, edge: &str| -> Vec<String> {
+            graph
+                .suggest_link_targets(query, gundren, edge)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.object.name)
+                .collect()
+        };
+        assert_eq!(names("zhent", "member_of"), ["Zhentarim", "Zhentarim Remnant"]);
+        assert_eq!(names("alliance", "member_of"), ["Lords' Alliance"]);
+        assert_eq!(names("keep", "related_to"), ["Zhentil Keep"]);
+
+        // Already linked: not offered again.
+        graph.connect_objects_str(gundren, zhent, "member_of").unwrap();
+        assert_eq!(names("zhent", "member_of"), ["Zhentarim Remnant"]);
+
+        let err = graph
+            .suggest_link_targets("x", ObjectId::new_v4(), "knows")
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,515 @@
+//! Campaign branches — alternate timelines of one project.
+//!
+//! A GM running the same setting for two groups lets the worlds drift apart:
+//! one table burned down the inn, the other befriended its owner.  A
+//! [`Branch`] is a named, persisted [`StagingLayer`] over the main graph that
+//! stores only what the branch changed (copy-on-write), so branches are cheap
+//! and main-graph developments made after branching show through unless the
+//! branch overrides them.
+//!
+//! - [`KnowledgeGraph::create_branch`] / [`KnowledgeGraph::switch_branch`]
+//!   manage branches and the project's active one.  While a branch is
+//!   active, the graph's object and edge reads (`get_object`,
+//!   `get_all_objects`, `find_by_name`, `get_relationships`, ...) see the
+//!   branch overlaid on main, and its writes (`add_object`, `update_object`,
+//!   `delete_object`, `add_edge`, `delete_edge`, `commit_staging`) go into
+//!   the branch instead of main.  [`KnowledgeGraph::save_branch_layer`]
+//!   replaces an overlay wholesale.
+//! - [`KnowledgeGraph::merge_branch`] shares a branch's developments with the
+//!   main graph.  Objects the main graph has edited or deleted since the
+//!   branch diverged on them are reported as [`BranchConflict`]s and stay in
+//!   the branch; everything else is committed in one transaction and leaves
+//!   the branch.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Result, UForgeError};
+use crate::staging::{StagingCommit, StagingLayer};
+use crate::types::{EdgeType, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Name of the main graph.  Reserved; never stored as a branch.
+pub const MAIN_BRANCH: &str = "main";
+
+/// `project_settings` key holding the active branch name.
+const ACTIVE_BRANCH_SETTING: &str = "active_branch";
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// A named copy-on-write overlay over the main graph.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    /// Unique per project, compared case-insensitively.
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// What the branch changed relative to the main graph.
+    pub layer: StagingLayer,
+    /// The main graph's `updated_at` for each object the layer mentions, as
+    /// it was when the branch first diverged on it (`None`: the object did
+    /// not exist in main).  Used to detect merge conflicts.
+    pub bases: HashMap<ObjectId, Option<chrono::DateTime<chrono::Utc>>>,
+}
+
+/// A branch change that cannot be merged as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BranchConflict {
+    /// The branch changed or deleted an object that the main graph has
+    /// edited (or deleted) since.
+    Object {
+        id: ObjectId,
+        deleted_on_main: bool,
+    },
+    /// A branch edge whose endpoint exists neither in the branch nor in the
+    /// main graph.
+    Edge {
+        from: ObjectId,
+        to: ObjectId,
+        edge_type: EdgeType,
+    },
+}
+
+/// Result of [`KnowledgeGraph::merge_branch`].
+#[derive(Debug, Clone, Default)]
+pub struct BranchMerge {
+    /// What was written to the main graph.
+    pub commit: StagingCommit,
+    /// Changes left in the branch.
+    pub conflicts: Vec<BranchConflict>,
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Create an empty branch.  Errors when `name` is blank, is
+    /// [`MAIN_BRANCH`], or is already taken.
+    pub fn create_branch(&self, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() || name.eq_ignore_ascii_case(MAIN_BRANCH) {
+            let message = format!("Invalid branch name '{name}'");
+            return Err(UForgeError::ValidationFailed(message));
+        }
+        let branch = Branch {
+            name: name.to_string(),
+            created_at: chrono::Utc::now(),
+            layer: StagingLayer::new(name),
+            bases: HashMap::new(),
+        };
+        if !self.storage.insert_branch(&branch)? {
+            return Err(UForgeError::Conflict(format!(
+                "Branch '{name}' already exists"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of all branches, excluding [`MAIN_BRANCH`].
+    pub fn list_branches(&self) -> Result<Vec<String>> {
+        self.storage.list_branch_names()
+    }
+
+    /// The branch named `name` (ignoring case), if any.
+    pub fn get_branch(&self, name: &str) -> Result<Option<Branch>> {
+        self.storage.get_branch(name)
+    }
+
+    /// Delete a branch and its overlay.  Deleting the active branch switches
+    /// back to [`MAIN_BRANCH`].  Returns `false` if there was none.
+    pub fn delete_branch(&self, name: &str) -> Result<bool> {
+        if self.active_branch()?.eq_ignore_ascii_case(name) {
+            self.storage.delete_setting(ACTIVE_BRANCH_SETTING)?;
+        }
+        let deleted = self.storage.delete_branch(name)?;
+        self.reload_active_branch()?;
+        Ok(deleted)
+    }
+
+    /// Name of the active branch; [`MAIN_BRANCH`] when none is selected or
+    /// the selected one has been deleted.
+    pub fn active_branch(&self) -> Result<String> {
+        Ok(self
+            .branch
+            .read()
+            .as_ref()
+            .map(|b| b.name.clone())
+            .unwrap_or_else(|| MAIN_BRANCH.to_string()))
+    }
+
+    /// Make `name` the project's active branch.  Pass [`MAIN_BRANCH`] to
+    /// return to the main graph.
+    pub fn switch_branch(&self, name: &str) -> Result<()> {
+        if name.eq_ignore_ascii_case(MAIN_BRANCH) {
+            self.storage.delete_setting(ACTIVE_BRANCH_SETTING)?;
+        } else {
+            let branch = self
+                .get_branch(name)?
+                .ok_or_else(|| UForgeError::NotFound(format!("Unknown branch '{name}'")))?;
+            self.storage.set_setting(ACTIVE_BRANCH_SETTING, &branch.name)?;
+        }
+        self.reload_active_branch()
+    }
+
+    /// The active branch's overlay (empty on [`MAIN_BRANCH`]).
+    pub fn active_layer(&self) -> Result<StagingLayer> {
+        Ok(self
+            .branch
+            .read()
+            .as_ref()
+            .map(|b| b.layer.clone())
+            .unwrap_or_else(|| StagingLayer::new(MAIN_BRANCH)))
+    }
+
+    /// Replace a branch's overlay with `layer`.
+    ///
+    /// Objects the layer mentions for the first time have their current
+    /// main-graph version recorded as the merge base.
+    pub fn save_branch_layer(&self, name: &str, layer: StagingLayer) -> Result<()> {
+        let mut branch = self
+            .get_branch(name)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown branch '{name}'")))?;
+        branch.layer = layer;
+        self.record_bases(&mut branch)?;
+        self.storage.update_branch(&branch)?;
+        self.reload_active_branch()
+    }
+
+    /// Changes in branch `name` that [`merge_branch`](Self::merge_branch)
+    /// would leave behind.
+    pub fn branch_conflicts(&self, name: &str) -> Result<Vec<BranchConflict>> {
+        let branch = self
+            .get_branch(name)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown branch '{name}'")))?;
+        self.conflicts_in(&branch)
+    }
+
+    /// Commit branch `name`'s non-conflicting changes to the main graph in
+    /// one transaction and remove them from the branch.
+    ///
+    /// With `only`, just the changes to those objects (and staged edges
+    /// touching them) are merged, so developments can be shared selectively.
+    /// An edge is merged only once both its endpoints exist in main.
+    pub fn merge_branch(&self, name: &str, only: Option<&[ObjectId]>) -> Result<BranchMerge> {
+        let mut branch = self
+            .get_branch(name)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown branch '{name}'")))?;
+        let selected = |id: ObjectId| match only {
+            Some(ids) => ids.contains(&id),
+            None => true,
+        };
+
+        let conflicts: Vec<BranchConflict> = self
+            .conflicts_in(&branch)?
+            .into_iter()
+            .filter(|c| match c {
+                BranchConflict::Object { id, .. } => selected(*id),
+                BranchConflict::Edge { from, to, .. } => selected(*from) || selected(*to),
+            })
+            .collect();
+        let blocked: HashSet<ObjectId> = conflicts
+            .iter()
+            .filter_map(|c| match c {
+                BranchConflict::Object { id, .. } => Some(*id),
+                BranchConflict::Edge { .. } => None,
+            })
+            .collect();
+
+        let mut merge = StagingLayer::new(&branch.name);
+        let mut rest = StagingLayer::new(branch.layer.name());
+        for obj in branch.layer.objects() {
+            if selected(obj.id) && !blocked.contains(&obj.id) {
+                merge.add_object(obj.clone());
+            } else {
+                rest.add_object(obj.clone());
+            }
+        }
+        for &id in branch.layer.deleted_objects() {
+            if selected(id) && !blocked.contains(&id) {
+                merge.delete_object(id);
+            } else {
+                rest.delete_object(id);
+            }
+        }
+        for edge in branch.layer.edges() {
+            let mut mergeable = selected(edge.from) || selected(edge.to);
+            for end in [edge.from, edge.to] {
+                mergeable = mergeable
+                    && (merge.get_object(end).is_some()
+                        || (!merge.is_deleted(end) && self.storage.get_node(end)?.is_some()));
+            }
+            if mergeable {
+                merge.add_edge(edge.clone());
+            } else {
+                rest.add_edge(edge.clone());
+            }
+        }
+        for (from, to, edge_type) in branch.layer.deleted_edges() {
+            if selected(*from) || selected(*to) {
+                merge.delete_edge(*from, *to, edge_type.as_str());
+            } else {
+                rest.delete_edge(*from, *to, edge_type.as_str());
+            }
+        }
+
+        let (merge, commit) = if merge.is_empty() {
+            (merge, StagingCommit::default())
+        } else {
+            let main = StagingLayer::default();
+            self.prepare_commit(merge, &self.staged(&main))?
+        };
+
+        let remaining: HashSet<ObjectId> = rest
+            .objects()
+            .iter()
+            .map(|o| o.id)
+            .chain(rest.deleted_objects().iter().copied())
+            .collect();
+        branch.bases.retain(|id, _| remaining.contains(id));
+        branch.layer = rest;
+        if merge.is_empty() {
+            self.storage.update_branch(&branch)?;
+        } else {
+            self.storage.merge_branch_staged(
+                merge.objects(),
+                merge.edges(),
+                merge.deleted_objects(),
+                merge.deleted_edges(),
+                &branch,
+            )?;
+        }
+        self.reload_active_branch()?;
+
+        Ok(BranchMerge { commit, conflicts })
+    }
+
+    /// Whether a branch other than [`MAIN_BRANCH`] is active.
+    pub(crate) fn on_branch(&self) -> bool {
+        self.branch.read().is_some()
+    }
+
+    /// Re-read the active branch into the graph's cache.
+    pub(crate) fn reload_active_branch(&self) -> Result<()> {
+        let branch = match self.storage.get_setting(ACTIVE_BRANCH_SETTING)? {
+            Some(name) => self.storage.get_branch(&name)?,
+            None => None,
+        };
+        *self.branch.write() = branch;
+        Ok(())
+    }
+
+    /// Apply `edit` to the active branch's overlay and persist it.  Errors
+    /// on [`MAIN_BRANCH`].
+    pub(crate) fn edit_active_branch<T>(
+        &self,
+        edit: impl FnOnce(&mut StagingLayer) -> T,
+    ) -> Result<T> {
+        let mut active = self.branch.write();
+        let Some(current) = active.as_mut() else {
+            return Err(UForgeError::NotFound("No active branch".to_string()));
+        };
+        let mut branch = current.clone();
+        let out = edit(&mut branch.layer);
+        self.record_bases(&mut branch)?;
+        self.storage.update_branch(&branch)?;
+        *current = branch;
+        Ok(out)
+    }
+
+    /// Drop bases of objects the overlay no longer mentions and record the
+    /// main graph's current version of newly mentioned ones.
+    fn record_bases(&self, branch: &mut Branch) -> Result<()> {
+        let layer = &branch.layer;
+        let mentioned: HashSet<ObjectId> = layer
+            .objects()
+            .iter()
+            .map(|o| o.id)
+            .chain(layer.deleted_objects().iter().copied())
+            .collect();
+        branch.bases.retain(|id, _| mentioned.contains(id));
+        for id in mentioned {
+            if let std::collections::hash_map::Entry::Vacant(entry) = branch.bases.entry(id) {
+                entry.insert(self.storage.get_node(id)?.map(|o| o.updated_at));
+            }
+        }
+        Ok(())
+    }
+
+    fn conflicts_in(&self, branch: &Branch) -> Result<Vec<BranchConflict>> {
+        let layer = &branch.layer;
+        let mut out = Vec::new();
+        let ids = layer
+            .objects()
+            .iter()
+            .map(|o| o.id)
+            .chain(layer.deleted_objects().iter().copied());
+        for id in ids {
+            let Some(&base) = branch.bases.get(&id) else {
+                continue;
+            };
+            let current = self.storage.get_node(id)?.map(|o| o.updated_at);
+            if current != base {
+                out.push(BranchConflict::Object {
+                    id,
+                    deleted_on_main: base.is_some() && current.is_none(),
+                });
+            }
+        }
+        for edge in layer.edges() {
+            for end in [edge.from, edge.to] {
+                if layer.get_object(end).is_none() && self.storage.get_node(end)?.is_none() {
+                    out.push(BranchConflict::Edge {
+                        from: edge.from,
+                        to: edge.to,
+                        edge_type: edge.edge_type.clone(),
+                    });
+                    break;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_branch_create_switch_and_diverge() {
+        let (graph, _tmp) = create_test_graph();
+        let inn = ObjectBuilder::location("Prancing Pony".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        graph.create_branch("Tuesday group").unwrap();
+        assert!(graph.create_branch("tuesday GROUP").is_err());
+        assert!(graph.create_branch("main").is_err());
+        assert_eq!(graph.list_branches().unwrap(), vec!["Tuesday group".to_string()]);
+
+        assert_eq!(graph.active_branch().unwrap(), MAIN_BRANCH);
+        graph.switch_branch("tuesday group").unwrap();
+        assert_eq!(graph.active_branch().unwrap(), "Tuesday group");
+        assert!(graph.switch_branch("nope").is_err());
+
+        let mut layer = graph.active_layer().unwrap();
+        layer.delete_object(inn);
+        graph.save_branch_layer("Tuesday group", layer).unwrap();
+
+        let layer = graph.active_layer().unwrap();
+        assert!(graph.staged(&layer).get_object(inn).unwrap().is_none());
+        assert!(graph.get_object(inn).unwrap().is_none());
+
+        graph.switch_branch(MAIN_BRANCH).unwrap();
+        assert!(graph.active_layer().unwrap().is_empty());
+        assert!(graph.get_object(inn).unwrap().is_some());
+
+        graph.switch_branch("Tuesday group").unwrap();
+        assert!(graph.delete_branch("Tuesday group").unwrap());
+        assert_eq!(graph.active_branch().unwrap(), MAIN_BRANCH);
+    }
+
+    #[test]
+    fn test_writes_on_active_branch_stay_in_branch() {
+        let (graph, _tmp) = create_test_graph();
+        let inn = ObjectBuilder::location("Prancing Pony".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.create_branch("alt").unwrap();
+        graph.switch_branch("alt").unwrap();
+
+        let owner = graph
+            .add_object(ObjectBuilder::character("Butterbur".to_string()).build())
+            .unwrap();
+        graph.connect_objects_str(owner, inn, "owns").unwrap();
+        let mut burned = graph.get_object(inn).unwrap().unwrap();
+        burned.name = "Burned Pony".to_string();
+        graph.update_object(burned).unwrap();
+
+        assert_eq!(graph.get_all_objects().unwrap().len(), 2);
+        assert_eq!(graph.get_relationships(inn).unwrap().len(), 1);
+        assert_eq!(graph.find_by_name_only("Burned Pony").unwrap().len(), 1);
+
+        graph.switch_branch(MAIN_BRANCH).unwrap();
+        assert!(graph.get_object(owner).unwrap().is_none());
+        assert!(graph.get_relationships(inn).unwrap().is_empty());
+        assert_eq!(graph.get_object(inn).unwrap().unwrap().name, "Prancing Pony");
+
+        let merge = graph.merge_branch("alt", None).unwrap();
+        assert_eq!(merge.commit.created, vec![owner]);
+        assert_eq!(merge.commit.updated, vec![inn]);
+        assert_eq!(graph.get_object(inn).unwrap().unwrap().name, "Burned Pony");
+        assert!(graph.get_branch("alt").unwrap().unwrap().layer.is_empty());
+    }
+
+    #[test]
+    fn test_merge_branch_lists_conflicts_and_keeps_them() {
+        let (graph, _tmp) = create_test_graph();
+        let inn = ObjectBuilder::location("Prancing Pony".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let town = ObjectBuilder::location("Bree".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.create_branch("alt").unwrap();
+
+        let mut layer = StagingLayer::new("alt");
+        let owner = layer.add_object(ObjectBuilder::character("Butterbur".to_string()).build());
+        layer.connect_str(owner, inn, "owns");
+        let mut burned = graph.get_object(inn).unwrap().unwrap();
+        burned.name = "Burned Pony".to_string();
+        layer.add_object(burned);
+        let mut renamed = graph.get_object(town).unwrap().unwrap();
+        renamed.name = "Bree-hill".to_string();
+        layer.add_object(renamed);
+        graph.save_branch_layer("alt", layer).unwrap();
+
+        // Main edits the inn after the branch diverged on it.
+        let mut inn_main = graph.get_object(inn).unwrap().unwrap();
+        inn_main.name = "Pony Inn".to_string();
+        graph.update_object(inn_main).unwrap();
+
+        let conflicts = graph.branch_conflicts("alt").unwrap();
+        assert_eq!(
+            conflicts,
+            vec![BranchConflict::Object {
+                id: inn,
+                deleted_on_main: false
+            }]
+        );
+
+        let merge = graph.merge_branch("alt", None).unwrap();
+        assert_eq!(merge.conflicts, conflicts);
+        assert_eq!(merge.commit.created, vec![owner]);
+        assert_eq!(merge.commit.updated, vec![town]);
+        assert_eq!(merge.commit.edges, 1);
+        assert_eq!(graph.get_object(town).unwrap().unwrap().name, "Bree-hill");
+        assert_eq!(graph.get_object(inn).unwrap().unwrap().name, "Pony Inn");
+
+        let branch = graph.get_branch("alt").unwrap().unwrap();
+        assert_eq!(branch.layer.objects().len(), 1);
+        assert_eq!(branch.layer.objects()[0].id, inn);
+        assert!(branch.layer.edges().is_empty());
+    }
+
+    #[test]
+    fn test_merge_branch_selective() {
+        let (graph, _tmp) = create_test_graph();
+        graph.create_branch("alt").unwrap();
+        let mut layer = StagingLayer::new("alt");
+        let a = layer.add_object(ObjectBuilder::character("Aragorn".to_string()).build());
+        let b = layer.add_object(ObjectBuilder::character("Boromir".to_string()).build());
+        layer.connect_str(a, b, "allied_with");
+        graph.save_branch_layer("alt", layer).unwrap();
+
+        // Only Aragorn is shared; the edge needs Boromir, so it stays behind.
+        let merge = graph.merge_branch("alt", Some(&[a])).unwrap();
+        assert_eq!(merge.commit.created, vec![a]);
+        assert_eq!(merge.commit.edges, 0);
+        assert!(graph.get_object(b).unwrap().is_none());
+
+        let merge = graph.merge_branch("alt", None).unwrap();
+        assert_eq!(merge.commit.created, vec![b]);
+        assert_eq!(merge.commit.edges, 1);
+        assert!(graph.get_branch("alt").unwrap().unwrap().layer.is_empty());
+    }
+}
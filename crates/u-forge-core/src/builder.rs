@@ -1,10 +1,37 @@
 //! Fluent builder for [`ObjectMetadata`].
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
-use crate::types::{ObjectId, ObjectMetadata};
+use crate::types::{Edge, EdgeType, Lifecycle, ObjectId, ObjectMetadata};
 use crate::KnowledgeGraph;
 
+/// The far end of a relationship declared on an [`ObjectBuilder`]: an
+/// existing object's id, or its exact name, resolved at
+/// [`add_to_graph`](ObjectBuilder::add_to_graph) time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelationshipTarget {
+    Id(ObjectId),
+    Name(String),
+}
+
+impl From<ObjectId> for RelationshipTarget {
+    fn from(id: ObjectId) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl From<&str> for RelationshipTarget {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for RelationshipTarget {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
 /// Fluent builder for constructing [`ObjectMetadata`] with TTRPG-friendly
 /// convenience constructors.
 ///
@@ -17,51 +44,60 @@ use crate::KnowledgeGraph;
 ///     .with_tag("wizard".to_string())
 ///     .build();
 /// ```
+///
+/// Relationships can be declared up front and are created by
+/// [`add_to_graph`](Self::add_to_graph):
+/// ```no_run
+/// # use u_forge_core::{KnowledgeGraph, ObjectBuilder};
+/// # fn demo(graph: &KnowledgeGraph) -> anyhow::Result<()> {
+/// ObjectBuilder::character("Frodo".to_string())
+///     .located_in("The Shire")
+///     .member_of("Fellowship of the Ring")
+///     .with_relationship("knows", "Gandalf")
+///     .add_to_graph(graph)?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct ObjectBuilder {
     metadata: ObjectMetadata,
+    /// Outgoing `(edge_type, target)` pairs created on `add_to_graph`.
+    relationships: Vec<(EdgeType, RelationshipTarget)>,
 }
 
 impl ObjectBuilder {
-    pub fn character(name: String) -> Self {
+    pub(crate) fn from_metadata(metadata: ObjectMetadata) -> Self {
         Self {
-            metadata: ObjectMetadata::new("character".to_string(), name),
+            metadata,
+            relationships: Vec::new(),
         }
     }
 
+    pub fn character(name: String) -> Self {
+        Self::from_metadata(ObjectMetadata::new("character".to_string(), name))
+    }
+
     pub fn location(name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new("location".to_string(), name),
-        }
+        Self::from_metadata(ObjectMetadata::new("location".to_string(), name))
     }
 
     pub fn faction(name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new("faction".to_string(), name),
-        }
+        Self::from_metadata(ObjectMetadata::new("faction".to_string(), name))
     }
 
     pub fn item(name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new("item".to_string(), name),
-        }
+        Self::from_metadata(ObjectMetadata::new("item".to_string(), name))
     }
 
     pub fn event(name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new("event".to_string(), name),
-        }
+        Self::from_metadata(ObjectMetadata::new("event".to_string(), name))
     }
 
     pub fn session(name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new("session".to_string(), name),
-        }
+        Self::from_metadata(ObjectMetadata::new("session".to_string(), name))
     }
 
     pub fn custom(object_type: String, name: String) -> Self {
-        Self {
-            metadata: ObjectMetadata::new(object_type, name),
-        }
+        Self::from_metadata(ObjectMetadata::new(object_type, name))
     }
 
     pub fn with_description(mut self, description: String) -> Self {
@@ -84,14 +120,88 @@ impl ObjectBuilder {
         self
     }
 
+    pub fn with_lifecycle(mut self, lifecycle: Lifecycle) -> Self {
+        self.metadata = self.metadata.with_lifecycle(lifecycle);
+        self
+    }
+
+    /// Replace the random id with [`ObjectId::deterministic`] for `seed` and
+    /// this object's type and name, so building the same object again (e.g.
+    /// in a fixture or a re-import) yields the same id.
+    pub fn with_deterministic_id(mut self, seed: &str) -> Self {
+        self.metadata.id =
+            ObjectId::deterministic(seed, &self.metadata.object_type, &self.metadata.name);
+        self
+    }
+
+    /// Declare an outgoing `edge_type` relationship to `target` (an id or an
+    /// exact object name), created by [`add_to_graph`](Self::add_to_graph).
+    pub fn with_relationship(
+        mut self,
+        edge_type: &str,
+        target: impl Into<RelationshipTarget>,
+    ) -> Self {
+        self.relationships.push((EdgeType::new(edge_type), target.into()));
+        self
+    }
+
+    /// Shorthand for `with_relationship("located_in", target)`.
+    pub fn located_in(self, target: impl Into<RelationshipTarget>) -> Self {
+        self.with_relationship("located_in", target)
+    }
+
+    /// Shorthand for `with_relationship("member_of", target)`.
+    pub fn member_of(self, target: impl Into<RelationshipTarget>) -> Self {
+        self.with_relationship("member_of", target)
+    }
 
     /// Consume the builder and return the finished [`ObjectMetadata`].
+    ///
+    /// Declared relationships are discarded; use
+    /// [`add_to_graph`](Self::add_to_graph) to create them.
     pub fn build(self) -> ObjectMetadata {
         self.metadata
     }
 
-    /// Build and immediately insert into `graph`.  Returns the new [`ObjectId`].
+    /// Build and immediately insert into `graph`, together with any declared
+    /// relationships.  Returns the new [`ObjectId`].
+    ///
+    /// Target names are resolved through the name index before anything is
+    /// written; an unknown or ambiguous name is an error and leaves the graph
+    /// unchanged.  The object is then created by
+    /// [`KnowledgeGraph::add_object`] — schema defaults, the
+    /// `ObjectCreated` event, branch overlay — and each edge by
+    /// [`KnowledgeGraph::add_edge`].  An edge that is rejected (e.g. by
+    /// strict edge mode) deletes the object again before the error returns.
     pub fn add_to_graph(self, graph: &KnowledgeGraph) -> Result<ObjectId> {
-        graph.add_object(self.build())
+        let mut edges = Vec::with_capacity(self.relationships.len());
+        for (edge_type, target) in self.relationships {
+            let target_id = match target {
+                RelationshipTarget::Id(target_id) => target_id,
+                RelationshipTarget::Name(name) => {
+                    let matches = graph.find_by_name_only(&name)?;
+                    match matches.as_slice() {
+                        [only] => only.id,
+                        [] => return Err(anyhow!("No object named '{name}'")),
+                        _ => {
+                            return Err(anyhow!(
+                                "{} objects are named '{name}'; pass an ObjectId instead",
+                                matches.len()
+                            ))
+                        }
+                    }
+                }
+            };
+            edges.push((target_id, edge_type));
+        }
+
+        let id = graph.add_object(self.metadata)?;
+        for (target_id, edge_type) in edges {
+            if let Err(err) = graph.add_edge(Edge::new(id, target_id, edge_type), false) {
+                graph.delete_object(id)?;
+                return Err(err.into());
+            }
+        }
+        Ok(id)
     }
 }
@@ -0,0 +1,582 @@
+//! World calendar and calendar-aware dates.
+//!
+//! Each project has one [`WorldCalendar`] — month names and lengths plus an
+//! optional era suffix — stored as JSON in the `calendar` project setting
+//! ([`KnowledgeGraph::set_calendar`]).  Without one, a Gregorian calendar
+//! without leap years is used.
+//!
+//! Properties of type [`PropertyType::Date`] hold a [`WorldDate`] in canonical
+//! form (`1492-03-15`, optionally `1492-03-15 14:30`).  Validation accepts
+//! anything [`WorldCalendar::parse_date`] understands — `15 Mirtul 1492`,
+//! `Mirtul 15, 1492 DR`, `1492-3-15` — and coercion rewrites it canonically.
+//! [`KnowledgeGraph::timeline`] lists every dated property in date order.
+//!
+//! Dates are checked against the calendar when written; changing the
+//! calendar later does not rewrite stored dates, so shrinking a month can
+//! leave dates that no longer validate.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::graph::KnowledgeGraphStorage;
+use crate::schema::PropertyType;
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the calendar JSON.
+pub const CALENDAR_SETTING: &str = "calendar";
+
+/// A named month of a [`WorldCalendar`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarMonth {
+    pub name: String,
+    pub days: u32,
+}
+
+/// The project's calendar.  Every year has the same months; there are no
+/// leap years.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldCalendar {
+    pub name: String,
+    pub months: Vec<CalendarMonth>,
+    /// Suffix written after years, e.g. `"DR"`; accepted (and ignored) when
+    /// parsing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub era: Option<String>,
+}
+
+impl Default for WorldCalendar {
+    fn default() -> Self {
+        const MONTHS: [(&str, u32); 12] = [
+            ("January", 31),
+            ("February", 28),
+            ("March", 31),
+            ("April", 30),
+            ("May", 31),
+            ("June", 30),
+            ("July", 31),
+            ("August", 31),
+            ("September", 30),
+            ("October", 31),
+            ("November", 30),
+            ("December", 31),
+        ];
+        Self::new("Gregorian (no leap years)", MONTHS.to_vec())
+    }
+}
+
+/// A day (and optional time of day) in the world calendar.  Orders
+/// chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WorldDate {
+    pub year: i64,
+    /// 1-based month index.
+    pub month: u32,
+    /// 1-based day of the month.
+    pub day: u32,
+    /// `(hour, minute)` on a 24-hour clock.
+    pub time: Option<(u32, u32)>,
+}
+
+impl WorldDate {
+    pub fn new(year: i64, month: u32, day: u32) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            time: None,
+        }
+    }
+
+    pub fn with_time(mut self, hour: u32, minute: u32) -> Self {
+        self.time = Some((hour, minute));
+        self
+    }
+}
+
+/// Canonical form: `year-MM-DD`, plus ` HH:MM` when a time is set.
+impl fmt::Display for WorldDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)?;
+        if let Some((hour, minute)) = self.time {
+            write!(f, " {hour:02}:{minute:02}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the canonical form written by `Display` (no calendar check; use
+/// [`WorldCalendar::parse_date`] for user input).
+impl std::str::FromStr for WorldDate {
+    type Err = UForgeError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || UForgeError::ValidationFailed(format!("Invalid date '{text}'"));
+        let text = text.trim();
+        let (day_part, time) = match text.split_once(' ') {
+            Some((day_part, time)) => {
+                let (h, m) = time.trim().split_once(':').ok_or_else(invalid)?;
+                let hour = h.parse().map_err(|_| invalid())?;
+                let minute = m.parse().map_err(|_| invalid())?;
+                (day_part, Some((hour, minute)))
+            }
+            None => (text, None),
+        };
+        let (year, month, day) = parse_numeric(day_part).ok_or_else(invalid)?;
+        Ok(WorldDate {
+            year,
+            month,
+            day,
+            time,
+        })
+    }
+}
+
+impl Serialize for WorldDate {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldDate {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(d)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl WorldCalendar {
+    pub fn new(name: &str, months: Vec<(&str, u32)>) -> Self {
+        Self {
+            name: name.to_string(),
+            months: months
+                .into_iter()
+                .map(|(name, days)| CalendarMonth {
+                    name: name.to_string(),
+                    days,
+                })
+                .collect(),
+            era: None,
+        }
+    }
+
+    pub fn with_era(mut self, era: &str) -> Self {
+        self.era = Some(era.to_string());
+        self
+    }
+
+    pub fn days_per_year(&self) -> u32 {
+        self.months.iter().map(|m| m.days).sum()
+    }
+
+    /// Problems that make the calendar unusable: no months, empty or
+    /// duplicate month names, or months without days.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.months.is_empty() {
+            errors.push("Calendar has no months".to_string());
+        }
+        for (i, month) in self.months.iter().enumerate() {
+            if month.name.trim().is_empty() {
+                errors.push(format!("Month {} has no name", i + 1));
+            } else if self.months[..i]
+                .iter()
+                .any(|m| m.name.eq_ignore_ascii_case(&month.name))
+            {
+                errors.push(format!("Duplicate month '{}'", month.name));
+            }
+            if month.days == 0 {
+                errors.push(format!("Month '{}' has no days", month.name));
+            }
+        }
+        errors
+    }
+
+    /// Parse `text` as a date in this calendar.
+    ///
+    /// Accepts `year-month-day` (year may be negative) or a month name with
+    /// the day before the year (`15 Mirtul 1492`, `Mirtul 15, 1492`), each
+    /// optionally followed by the era and a `HH:MM` time.  Fails with
+    /// [`UForgeError::ValidationFailed`] for unparsable text or a day the
+    /// calendar does not have.
+    pub fn parse_date(&self, text: &str) -> Result<WorldDate> {
+        let invalid = |reason: String| {
+            UForgeError::ValidationFailed(format!("Invalid date '{text}': {reason}"))
+        };
+
+        let mut rest = text.trim().to_string();
+        let mut time = None;
+        if let Some((head, last)) = rest.rsplit_once(char::is_whitespace) {
+            if let Some((h, m)) = last.split_once(':') {
+                let hour: u32 = h.parse().map_err(|_| invalid("bad hour".into()))?;
+                let minute: u32 = m.parse().map_err(|_| invalid("bad minute".into()))?;
+                if hour >= 24 || minute >= 60 {
+                    return Err(invalid(format!("no time {last}")));
+                }
+                time = Some((hour, minute));
+                rest = head.trim_end().to_string();
+            }
+        }
+        if let Some(era) = self.era.as_deref().filter(|e| !e.is_empty()) {
+            let start = rest.len().saturating_sub(era.len());
+            if rest
+                .get(start..)
+                .is_some_and(|tail| tail.eq_ignore_ascii_case(era))
+            {
+                rest = rest[..start].trim_end().to_string();
+            }
+        }
+
+        let (year, month, day) = match parse_numeric(&rest) {
+            Some(ymd) => ymd,
+            None => self
+                .parse_named(&rest)
+                .ok_or_else(|| invalid(format!("not a {} date", self.name)))?,
+        };
+        let date = WorldDate {
+            year,
+            month,
+            day,
+            time,
+        };
+        self.check(&date).map_err(invalid)?;
+        Ok(date)
+    }
+
+    /// `15 Mirtul 1492 DR`, with ` 14:30` when a time is set.
+    pub fn format_date(&self, date: &WorldDate) -> String {
+        let month = self
+            .months
+            .get((date.month as usize).wrapping_sub(1))
+            .map(|m| m.name.clone())
+            .unwrap_or_else(|| date.month.to_string());
+        let mut out = format!("{} {month} {}", date.day, date.year);
+        if let Some(era) = &self.era {
+            out.push(' ');
+            out.push_str(era);
+        }
+        if let Some((hour, minute)) = date.time {
+            out.push_str(&format!(" {hour:02}:{minute:02}"));
+        }
+        out
+    }
+
+    /// Days from 1 January of year 0 (in this calendar) to `date`; negative
+    /// before it.
+    pub fn day_number(&self, date: &WorldDate) -> i64 {
+        let before_month: u32 = self
+            .months
+            .iter()
+            .take(date.month.saturating_sub(1) as usize)
+            .map(|m| m.days)
+            .sum();
+        date.year * self.days_per_year() as i64 + before_month as i64 + date.day as i64 - 1
+    }
+
+    /// Whole days from `from` to `to` (negative when `to` is earlier).
+    pub fn days_between(&self, from: &WorldDate, to: &WorldDate) -> i64 {
+        self.day_number(to) - self.day_number(from)
+    }
+
+    /// `date` moved by `days` (backwards when negative), keeping its time.
+    pub fn add_days(&self, date: &WorldDate, days: i64) -> WorldDate {
+        let days_per_year = self.days_per_year().max(1) as i64;
+        let number = self.day_number(date) + days;
+        let mut remaining = number.rem_euclid(days_per_year) as u32;
+        let mut month = 1;
+        for m in &self.months {
+            if remaining < m.days {
+                break;
+            }
+            remaining -= m.days;
+            month += 1;
+        }
+        WorldDate {
+            year: number.div_euclid(days_per_year),
+            month,
+            day: remaining + 1,
+            time: date.time,
+        }
+    }
+
+    fn check(&self, date: &WorldDate) -> std::result::Result<(), String> {
+        let month = date
+            .month
+            .checked_sub(1)
+            .and_then(|i| self.months.get(i as usize))
+            .ok_or_else(|| format!("{} has no month {}", self.name, date.month))?;
+        if date.day == 0 || date.day > month.days {
+            return Err(format!("{} has {} days", month.name, month.days));
+        }
+        Ok(())
+    }
+
+    /// `15 Mirtul 1492` / `Mirtul 15, 1492`: the month name anywhere, then
+    /// the remaining two numbers as day and year.
+    fn parse_named(&self, text: &str) -> Option<(i64, u32, u32)> {
+        let lower = text.to_lowercase();
+        let mut months: Vec<(usize, &CalendarMonth)> = self.months.iter().enumerate().collect();
+        // Longest first, so "Deep Winter" wins over "Winter".
+        months.sort_by_key(|m| std::cmp::Reverse(m.1.name.len()));
+        let (index, month) = months.into_iter().find(|(_, m)| {
+            let name = m.name.to_lowercase();
+            lower.match_indices(&name).any(|(at, _)| {
+                let before = lower[..at].chars().next_back();
+                let after = lower[at + name.len()..].chars().next();
+                !before.is_some_and(char::is_alphanumeric)
+                    && !after.is_some_and(char::is_alphanumeric)
+            })
+        })?;
+        let name = month.name.to_lowercase();
+        let remainder = lower.replacen(&name, " ", 1);
+        let numbers: Vec<i64> = remainder
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|t| !t.is_empty())
+            .map(|t| t.trim_end_matches(|c: char| c.is_alphabetic()).parse().ok())
+            .collect::<Option<_>>()?;
+        match numbers[..] {
+            [day, year] if day > 0 => Some((year, index as u32 + 1, day as u32)),
+            _ => None,
+        }
+    }
+}
+
+/// `year-month-day`, where the year may carry a leading `-`.
+fn parse_numeric(text: &str) -> Option<(i64, u32, u32)> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, text),
+    };
+    let mut parts = body.split('-');
+    let (y, m, d) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let year: i64 = y.trim().parse().ok()?;
+    Some((
+        if negative { -year } else { year },
+        m.trim().parse().ok()?,
+        d.trim().parse().ok()?,
+    ))
+}
+
+/// The project calendar, or the default when none is set or the stored one
+/// is unreadable.
+pub(crate) fn load_calendar(storage: &KnowledgeGraphStorage) -> WorldCalendar {
+    storage
+        .get_setting(CALENDAR_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// One dated property of one object, from [`KnowledgeGraph::timeline`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEntry {
+    pub object_id: ObjectId,
+    pub object_name: String,
+    pub object_type: String,
+    pub property: String,
+    pub date: WorldDate,
+}
+
+impl KnowledgeGraph {
+    /// The project calendar (the default Gregorian one until set).
+    pub fn calendar(&self) -> WorldCalendar {
+        load_calendar(&self.storage)
+    }
+
+    /// Replace the project calendar.  Fails with
+    /// [`UForgeError::ValidationFailed`] if it has no usable months.
+    pub fn set_calendar(&self, calendar: &WorldCalendar) -> Result<()> {
+        let errors = calendar.validate();
+        if !errors.is_empty() {
+            return Err(UForgeError::ValidationFailed(errors.join("; ")));
+        }
+        let json = serde_json::to_string(calendar).context("Failed to serialize calendar")?;
+        self.storage.set_setting(CALENDAR_SETTING, &json)
+    }
+
+    /// Every `Date`-typed property value between `from` and `to` (inclusive,
+    /// either end open when `None`), in date order.  Values that do not parse
+    /// in the current calendar are skipped.
+    pub fn timeline(
+        &self,
+        from: Option<WorldDate>,
+        to: Option<WorldDate>,
+    ) -> Result<Vec<TimelineEntry>> {
+        let calendar = self.calendar();
+        let mut entries = Vec::new();
+        for object in self.get_all_objects()? {
+            let Some(type_schema) = self.object_type_schema_for(&object) else {
+                continue;
+            };
+            for (property, prop) in &type_schema.properties {
+                if !matches!(prop.property_type, PropertyType::Date) {
+                    continue;
+                }
+                let Some(date) = object
+                    .get_property(property)
+                    .and_then(|text| calendar.parse_date(&text).ok())
+                else {
+                    continue;
+                };
+                if from.is_some_and(|f| date_cmp(&date, &f).is_lt())
+                    || to.is_some_and(|t| date_cmp(&date, &t).is_gt())
+                {
+                    continue;
+                }
+                entries.push(TimelineEntry {
+                    object_id: object.id,
+                    object_name: object.name.clone(),
+                    object_type: object.object_type.clone(),
+                    property: property.clone(),
+                    date,
+                });
+            }
+        }
+        entries.sort_by(|a, b| {
+            a.date
+                .cmp(&b.date)
+                .then_with(|| a.object_name.cmp(&b.object_name))
+                .then_with(|| a.property.cmp(&b.property))
+        });
+        Ok(entries)
+    }
+}
+
+/// Compare by day, treating a bound without a time as covering the whole day.
+pub(crate) fn date_cmp(date: &WorldDate, bound: &WorldDate) -> Ordering {
+    let day = (date.year, date.month, date.day).cmp(&(bound.year, bound.month, bound.day));
+    match (day, bound.time) {
+        (Ordering::Equal, Some(_)) if date.time.is_some() => date.time.cmp(&bound.time),
+        (ordering, _) => ordering,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::schema::{ObjectTypeSchema, PropertySchema};
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    fn harptos() -> WorldCalendar {
+        WorldCalendar::new(
+            "Harptos",
+            vec![
+                ("Hammer", 30),
+                ("Alturiak", 30),
+                ("Ches", 30),
+                ("Mirtul", 30),
+            ],
+        )
+        .with_era("DR")
+    }
+
+    #[test]
+    fn test_parse_and_format_dates() {
+        let cal = harptos();
+        let date = WorldDate::new(1492, 4, 15);
+        assert_eq!(cal.parse_date("1492-04-15").unwrap(), date);
+        assert_eq!(cal.parse_date("15 Mirtul 1492").unwrap(), date);
+        assert_eq!(cal.parse_date("mirtul 15, 1492 DR").unwrap(), date);
+        assert_eq!(
+            cal.parse_date("15 Mirtul 1492 DR 14:30").unwrap(),
+            date.with_time(14, 30)
+        );
+        assert_eq!(
+            cal.parse_date("-20-1-1").unwrap(),
+            WorldDate::new(-20, 1, 1)
+        );
+        assert_eq!(cal.format_date(&date), "15 Mirtul 1492 DR");
+        assert_eq!(date.to_string(), "1492-04-15");
+
+        for bad in [
+            "31 Mirtul 1492",
+            "1492-05-01",
+            "Flamerule 1 1492",
+            "soon",
+            "1 Ches 1492 25:00",
+        ] {
+            let err = cal.parse_date(bad).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValidationFailed, "{bad}");
+        }
+
+        assert!(WorldDate::new(1491, 4, 30) < date);
+        assert!(date < date.with_time(0, 0));
+        assert_eq!(cal.days_between(&WorldDate::new(1491, 4, 15), &date), 120);
+        assert_eq!(cal.add_days(&date, 16), WorldDate::new(1493, 1, 1));
+        assert_eq!(cal.add_days(&date, -120), WorldDate::new(1491, 4, 15));
+    }
+
+    #[tokio::test]
+    async fn test_dates_validate_and_build_timeline() {
+        let (graph, _temp_dir) = create_test_graph();
+        assert_eq!(graph.calendar(), WorldCalendar::default());
+        graph.set_calendar(&harptos()).unwrap();
+        let err = graph
+            .set_calendar(&WorldCalendar::new("Empty", vec![]))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let battle = ObjectTypeSchema::new("battle".to_string(), "A battle".to_string())
+            .with_property("fought_on".to_string(), PropertySchema::date("When"));
+        graph.register_object_type("battle", battle).await.unwrap();
+        graph
+            .get_schema_manager()
+            .load_schema("default")
+            .await
+            .unwrap();
+
+        let mut props = serde_json::json!({ "fought_on": "3 Ches 1490 DR" })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(graph
+            .validate_and_coerce_properties("battle", &mut props)
+            .is_empty());
+        assert_eq!(props["fought_on"], serde_json::json!("1490-03-03"));
+        let mut bad = serde_json::json!({ "fought_on": "40 Ches 1490" })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert_eq!(
+            graph
+                .validate_and_coerce_properties("battle", &mut bad)
+                .len(),
+            1
+        );
+
+        for (name, date) in [
+            ("Late", "1495-01-01"),
+            ("Early", "1 Hammer 1480"),
+            ("Mid", "1490-03-03"),
+        ] {
+            ObjectBuilder::custom("battle".to_string(), name.to_string())
+                .with_property("fought_on".to_string(), date.to_string())
+                .add_to_graph(&graph)
+                .unwrap();
+        }
+        let names = |entries: Vec<TimelineEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.object_name).collect()
+        };
+        assert_eq!(
+            names(graph.timeline(None, None).unwrap()),
+            ["Early", "Mid", "Late"]
+        );
+        let window = graph
+            .timeline(
+                Some(WorldDate::new(1490, 3, 3)),
+                Some(WorldDate::new(1494, 1, 1)),
+            )
+            .unwrap();
+        assert_eq!(names(window), ["Mid"]);
+    }
+}
@@ -0,0 +1,247 @@
+//! Progress clocks — "the Red Sashes are 4/6 of the way to seizing the docks".
+//!
+//! A [`ProgressClock`] is a circle cut into segments that fill as a faction
+//! advances a plan, a quest deadline approaches, or an alarm is raised.
+//! Clocks are attached to an object (usually a faction or quest) and stored
+//! as a JSON array in its [`CLOCKS_KEY`] property; like other `_`-prefixed
+//! properties it is kept out of embeddings and player views, and travels
+//! with the object through history and export.
+//!
+//! [`KnowledgeGraph::tick_clock`] and [`KnowledgeGraph::reset_clock`]
+//! emit a [`GraphEvent::ClockChanged`] (see [`crate::events`]), so a UI can
+//! flash a clock that just filled.  Every clock in
+//! the project is listed on the prep sheet (see [`crate::prep`]).
+
+use crate::error::{Result, UForgeError};
+use crate::events::GraphEvent;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Property holding an object's clocks.
+pub const CLOCKS_KEY: &str = "_clocks";
+
+/// A segmented progress clock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressClock {
+    /// Unique among the clocks of one object, e.g. `"Seize the docks"`.
+    pub name: String,
+    pub segments: u32,
+    #[serde(default)]
+    pub filled: u32,
+    /// What happens when the clock fills.
+    #[serde(default)]
+    pub trigger: Option<String>,
+}
+
+impl ProgressClock {
+    pub fn new(name: impl Into<String>, segments: u32) -> Self {
+        Self {
+            name: name.into(),
+            segments,
+            filled: 0,
+            trigger: None,
+        }
+    }
+
+    pub fn with_trigger(mut self, trigger: impl Into<String>) -> Self {
+        self.trigger = Some(trigger.into());
+        self
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.filled >= self.segments
+    }
+
+    /// `"4/6"`.
+    pub fn progress_label(&self) -> String {
+        format!("{}/{}", self.filled, self.segments)
+    }
+}
+
+/// A change to one clock, returned and emitted by [`KnowledgeGraph::tick_clock`] and
+/// [`KnowledgeGraph::reset_clock`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClockEvent {
+    pub object_id: ObjectId,
+    /// The clock after the change.
+    pub clock: ProgressClock,
+    pub previous_filled: u32,
+    /// Whether this change filled the clock.  Ticking an already-full
+    /// clock does not complete it again.
+    pub completed: bool,
+}
+
+fn clocks_of(object: &ObjectMetadata) -> Result<Vec<ProgressClock>> {
+    match object.get_json_property(CLOCKS_KEY) {
+        Some(value) => Ok(serde_json::from_value(value.clone())
+            .with_context(|| format!("Failed to parse clocks of object {}", object.id))?),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl KnowledgeGraph {
+    /// The clocks attached to `id`, in the order they were added.
+    pub fn clocks(&self, id: ObjectId) -> Result<Vec<ProgressClock>> {
+        clocks_of(&self.object_for_clocks(id)?)
+    }
+
+    /// Every clock in the project as `(object, clocks)`, objects by name.
+    pub fn all_clocks(&self) -> Result<Vec<(ObjectMetadata, Vec<ProgressClock>)>> {
+        let mut out = Vec::new();
+        for object in self.get_all_objects()? {
+            let clocks = clocks_of(&object)?;
+            if !clocks.is_empty() {
+                out.push((object, clocks));
+            }
+        }
+        out.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        Ok(out)
+    }
+
+    /// Attach `clock` to `id`.  Errors with [`UForgeError::ValidationFailed`]
+    /// when it has no segments, is over-filled, or its name is taken.
+    pub fn add_clock(&self, id: ObjectId, clock: ProgressClock) -> Result<()> {
+        if clock.segments == 0 || clock.filled > clock.segments {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Clock '{}' must have at least one segment and at most {} filled",
+                clock.name, clock.segments
+            )));
+        }
+        let mut clocks = self.clocks(id)?;
+        if clocks.iter().any(|c| c.name == clock.name) {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Object {id} already has a clock named '{}'",
+                clock.name
+            )));
+        }
+        clocks.push(clock);
+        self.write_clocks(id, &clocks)
+    }
+
+    /// Detach clock `name` from `id`.  Returns whether it existed.
+    pub fn remove_clock(&self, id: ObjectId, name: &str) -> Result<bool> {
+        let mut clocks = self.clocks(id)?;
+        let before = clocks.len();
+        clocks.retain(|c| c.name != name);
+        if clocks.len() == before {
+            return Ok(false);
+        }
+        self.write_clocks(id, &clocks)?;
+        Ok(true)
+    }
+
+    /// Fill `ticks` more segments of clock `name` (negative ticks unfill),
+    /// clamped to the clock's range.
+    pub fn tick_clock(&self, id: ObjectId, name: &str, ticks: i32) -> Result<ClockEvent> {
+        self.update_clock(id, name, |clock| {
+            let filled = clock.filled as i64 + ticks as i64;
+            clock.filled = filled.clamp(0, clock.segments as i64) as u32;
+        })
+    }
+
+    /// Empty clock `name`.
+    pub fn reset_clock(&self, id: ObjectId, name: &str) -> Result<ClockEvent> {
+        self.update_clock(id, name, |clock| clock.filled = 0)
+    }
+
+    fn update_clock(
+        &self,
+        id: ObjectId,
+        name: &str,
+        change: impl FnOnce(&mut ProgressClock),
+    ) -> Result<ClockEvent> {
+        let mut clocks = self.clocks(id)?;
+        let clock = clocks.iter_mut().find(|c| c.name == name).ok_or_else(|| {
+            UForgeError::NotFound(format!("Object {id} has no clock named '{name}'"))
+        })?;
+        let previous_filled = clock.filled;
+        let was_complete = clock.is_complete();
+        change(clock);
+        let event = ClockEvent {
+            object_id: id,
+            clock: clock.clone(),
+            previous_filled,
+            completed: !was_complete && clock.is_complete(),
+        };
+        self.write_clocks(id, &clocks)?;
+        self.emit(GraphEvent::ClockChanged(event.clone()));
+        Ok(event)
+    }
+
+    fn object_for_clocks(&self, id: ObjectId) -> Result<ObjectMetadata> {
+        self.get_object(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}")))
+    }
+
+    fn write_clocks(&self, id: ObjectId, clocks: &[ProgressClock]) -> Result<()> {
+        let value: Value = serde_json::to_value(clocks).context("Failed to serialize clocks")?;
+        self.storage.set_node_property(id, CLOCKS_KEY, &value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_tick_and_reset_clock() {
+        let (graph, _temp_dir) = create_test_graph();
+        let sashes = graph
+            .add_object(ObjectMetadata::new(
+                "faction".to_string(),
+                "Red Sashes".to_string(),
+            ))
+            .unwrap();
+        let mut events = graph.subscribe_events();
+
+        graph
+            .add_clock(
+                sashes,
+                ProgressClock::new("Seize the docks", 4).with_trigger("Dock tolls double"),
+            )
+            .unwrap();
+        let err = graph
+            .add_clock(sashes, ProgressClock::new("Seize the docks", 6))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let event = graph.tick_clock(sashes, "Seize the docks", 3).unwrap();
+        assert_eq!(event.clock.progress_label(), "3/4");
+        assert!(!event.completed);
+        let event = graph.tick_clock(sashes, "Seize the docks", 5).unwrap();
+        assert_eq!(event.clock.filled, 4);
+        assert!(event.completed);
+        assert!(
+            !graph
+                .tick_clock(sashes, "Seize the docks", 1)
+                .unwrap()
+                .completed
+        );
+
+        let GraphEvent::ClockChanged(first) = events.try_recv().unwrap() else {
+            panic!("expected a clock event");
+        };
+        assert_eq!(first.previous_filled, 0);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            GraphEvent::ClockChanged(ClockEvent {
+                completed: true,
+                ..
+            })
+        ));
+
+        graph.reset_clock(sashes, "Seize the docks").unwrap();
+        assert_eq!(graph.clocks(sashes).unwrap()[0].filled, 0);
+        assert_eq!(graph.all_clocks().unwrap().len(), 1);
+
+        let err = graph.tick_clock(sashes, "Nope", 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(graph.remove_clock(sashes, "Seize the docks").unwrap());
+        assert!(graph.clocks(sashes).unwrap().is_empty());
+    }
+}
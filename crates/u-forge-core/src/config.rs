@@ -42,6 +42,7 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
+use crate::hooks::AutomationHook;
 use crate::lemonade::load::ModelLoadOptions;
 
 // ── EmbeddingDeviceConfig ─────────────────────────────────────────────────────
@@ -166,6 +167,14 @@ pub struct ModelConfig {
     #[serde(default = "default_embedding_model_preferences")]
     pub embedding_model_preferences: Vec<String>,
 
+    /// Embedding models trained on multilingual corpora.
+    ///
+    /// When the project's default language is not English, `ModelSelector`
+    /// only considers these models (in `embedding_model_preferences` order),
+    /// falling back to any embedding model if none is downloaded.
+    #[serde(default = "default_multilingual_embedding_models")]
+    pub multilingual_embedding_models: Vec<String>,
+
     /// Preference list for reranker models.
     #[serde(default = "default_reranker_model_preferences")]
     pub reranker_model_preferences: Vec<String>,
@@ -219,6 +228,7 @@ impl Default for ModelConfig {
             high_quality_embedding_models: default_hq_embedding_models(),
             llamacpp_backend_preference: default_llamacpp_backend_preference(),
             embedding_model_preferences: default_embedding_model_preferences(),
+            multilingual_embedding_models: default_multilingual_embedding_models(),
             reranker_model_preferences: default_reranker_model_preferences(),
             stt_model_preferences: default_stt_model_preferences(),
             llm_model_preferences: default_llm_model_preferences(),
@@ -363,6 +373,12 @@ pub struct ChatConfig {
     #[serde(default = "ChatConfig::default_hq_semantic_boost")]
     pub hq_semantic_boost: f32,
 
+    /// RRF weight of the BM25 sparse keyword path; `0.0` disables it.
+    ///
+    /// See [`HybridSearchConfig::sparse_weight`] for full semantics.
+    #[serde(default)]
+    pub sparse_weight: f32,
+
     /// Maximum tool-call round-trips the agent may make per user message.
     ///
     /// Each "turn" is one LLM call that may invoke tools; the agent loop
@@ -435,6 +451,7 @@ impl Default for ChatConfig {
             alpha: Self::default_alpha(),
             search_limit: Self::default_search_limit(),
             hq_semantic_boost: Self::default_hq_semantic_boost(),
+            sparse_weight: 0.0,
             max_tool_turns: Self::default_max_tool_turns(),
         }
     }
@@ -581,6 +598,12 @@ pub struct AppConfig {
     /// UI / display settings.
     #[serde(default)]
     pub ui: UiConfig,
+
+    /// Automation hooks fired on graph events (`[[hooks]]` tables; see
+    /// [`crate::hooks`]).  They live here rather than in the project so
+    /// that opening someone else's project never runs their scripts.
+    #[serde(default)]
+    pub hooks: Vec<AutomationHook>,
 }
 
 impl AppConfig {
@@ -685,6 +708,15 @@ fn default_embedding_model_preferences() -> Vec<String> {
     ]
 }
 
+fn default_multilingual_embedding_models() -> Vec<String> {
+    vec![
+        "embed-gemma-300m-FLM".to_string(),
+        "user.ggml-org/embeddinggemma-300M-GGUF".to_string(),
+        "nomic-embed-text-v2-moe-GGUF".to_string(),
+        "Qwen3-Embedding-8B-GGUF".to_string(),
+    ]
+}
+
 fn default_reranker_model_preferences() -> Vec<String> {
     vec!["bge-reranker-v2-m3-GGUF".to_string()]
 }
@@ -0,0 +1,419 @@
+//! Contradiction detection between chunks.
+//!
+//! Long campaigns accrete contradictions nobody notices ("killed in session 3"
+//! vs. "appears in session 7").  This module groups each object's chunks and
+//! asks the LLM to flag pairs of statements that cannot both be true.  Each
+//! flagged pair is stored as a [`ConsistencyWarning`] that the GM can browse
+//! and dismiss.
+//!
+//! # Pattern
+//!
+//! ```text
+//! detect_contradictions(graph, queue, object_id)   // one object
+//! scan_for_contradictions(graph, queue)            // every object with ≥ 2 chunks
+//!     → numbered chunk list → InferenceQueue::generate
+//!     → parse JSON findings → replace_consistency_warnings
+//! ```
+//!
+//! Like [`search_hybrid`](crate::search_hybrid), the analysis takes the
+//! [`InferenceQueue`] as a parameter — [`KnowledgeGraph`] itself has no AI
+//! dependency.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::{Result, UForgeError};
+use crate::lemonade::{ChatMessage, ChatRequest};
+use crate::queue::InferenceQueue;
+use crate::types::{ChunkId, ObjectId, TextChunk};
+use crate::KnowledgeGraph;
+
+/// Maximum number of chunks compared in a single LLM call.
+///
+/// Larger objects are split into overlapping windows of this size so the
+/// prompt stays well inside the model's context.
+pub const CHUNKS_PER_PROMPT: usize = 12;
+
+const SYSTEM_PROMPT: &str = "You audit tabletop RPG campaign notes for continuity errors. \
+You are given numbered statements about a single entity. Find pairs of statements that \
+directly contradict each other (they cannot both be true at the same point in the story). \
+Ignore differences in detail, tone, or emphasis. Respond with ONLY a JSON array, no prose: \
+[{\"a\": <number>, \"b\": <number>, \"explanation\": \"<one sentence>\"}]. \
+Respond with [] when nothing contradicts.";
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Unique identifier for a stored consistency warning.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConsistencyWarningId(pub uuid::Uuid);
+
+impl ConsistencyWarningId {
+    pub fn new_v4() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+
+    pub fn parse_str(s: &str) -> Result<Self, uuid::Error> {
+        uuid::Uuid::parse_str(s).map(Self)
+    }
+
+    /// Return the inner UUID formatted with hyphens (e.g. for SQL params).
+    pub fn hyphenated(&self) -> uuid::fmt::Hyphenated {
+        self.0.hyphenated()
+    }
+}
+
+impl std::fmt::Display for ConsistencyWarningId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Two chunks on the same object that the LLM believes contradict each other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyWarning {
+    pub id: ConsistencyWarningId,
+    pub object_id: ObjectId,
+    pub first_chunk: ChunkId,
+    pub second_chunk: ChunkId,
+    /// The model's one-sentence explanation of the conflict.
+    pub explanation: String,
+    /// Set once the GM has reviewed the warning.
+    pub dismissed: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Summary returned by [`scan_for_contradictions`].
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyReport {
+    /// Objects with at least two chunks that were sent to the LLM.
+    pub objects_scanned: usize,
+    /// Warnings written across all scanned objects.
+    pub warnings_found: usize,
+    /// Objects whose analysis failed (LLM error); their old warnings are kept.
+    pub failed_objects: Vec<ObjectId>,
+}
+
+/// One finding as emitted by the model (1-based statement numbers).
+#[derive(Debug, Deserialize)]
+struct RawFinding {
+    a: usize,
+    b: usize,
+    #[serde(default)]
+    explanation: String,
+}
+
+// ── Analysis ──────────────────────────────────────────────────────────────────
+
+/// Analyse one object's chunks and replace its stored warnings.
+///
+/// Returns the warnings the model found; pairs the GM already dismissed are
+/// returned but not stored again.  Objects with fewer than two
+/// chunks have nothing to compare and return an empty list without calling
+/// the LLM.  Errors if the queue has no text-generation worker.
+pub async fn detect_contradictions(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    object_id: ObjectId,
+) -> Result<Vec<ConsistencyWarning>> {
+    let chunks = graph.get_text_chunks(object_id)?;
+    if chunks.len() < 2 {
+        return Ok(Vec::new());
+    }
+    if !queue.has_text_generation() {
+        return Err(UForgeError::InferenceUnavailable(
+            "Contradiction detection requires a text-generation worker".to_string(),
+        ));
+    }
+
+    let name = graph
+        .get_object(object_id)?
+        .map(|o| o.name)
+        .unwrap_or_else(|| object_id.to_string());
+
+    let mut warnings: Vec<ConsistencyWarning> = Vec::new();
+    for window in chunk_windows(&chunks) {
+        let request = ChatRequest::new(vec![
+            ChatMessage::system(SYSTEM_PROMPT),
+            ChatMessage::user(build_prompt(&name, window)),
+        ])
+        .with_temperature(0.0);
+        let response = queue.generate(request).await?;
+        let text = response.first_content().unwrap_or_default();
+        for w in parse_findings(text, object_id, window) {
+            let duplicate = warnings.iter().any(|e| {
+                (e.first_chunk, e.second_chunk) == (w.first_chunk, w.second_chunk)
+                    || (e.first_chunk, e.second_chunk) == (w.second_chunk, w.first_chunk)
+            });
+            if !duplicate {
+                warnings.push(w);
+            }
+        }
+    }
+
+    debug!(
+        object = %object_id,
+        chunks = chunks.len(),
+        warnings = warnings.len(),
+        "Contradiction detection complete"
+    );
+    graph.replace_consistency_warnings(object_id, &warnings)?;
+    Ok(warnings)
+}
+
+/// Run [`detect_contradictions`] over every object with at least two chunks.
+///
+/// A failure on one object is logged and recorded in
+/// [`ConsistencyReport::failed_objects`]; the scan continues.
+pub async fn scan_for_contradictions(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+) -> Result<ConsistencyReport> {
+    if !queue.has_text_generation() {
+        return Err(UForgeError::InferenceUnavailable(
+            "Contradiction detection requires a text-generation worker".to_string(),
+        ));
+    }
+    let mut report = ConsistencyReport::default();
+    for object in graph.get_all_objects()? {
+        if graph.get_text_chunks(object.id)?.len() < 2 {
+            continue;
+        }
+        report.objects_scanned += 1;
+        match detect_contradictions(graph, queue, object.id).await {
+            Ok(found) => report.warnings_found += found.len(),
+            Err(e) => {
+                warn!(object = %object.id, "Contradiction detection failed: {e}");
+                report.failed_objects.push(object.id);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Split `chunks` into windows of at most [`CHUNKS_PER_PROMPT`].
+///
+/// Windows overlap by half so statements near a boundary are still compared
+/// with their neighbours.
+fn chunk_windows(chunks: &[TextChunk]) -> Vec<&[TextChunk]> {
+    if chunks.len() <= CHUNKS_PER_PROMPT {
+        return vec![chunks];
+    }
+    let step = CHUNKS_PER_PROMPT / 2;
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + CHUNKS_PER_PROMPT).min(chunks.len());
+        windows.push(&chunks[start..end]);
+        if end == chunks.len() {
+            break;
+        }
+        start += step;
+    }
+    windows
+}
+
+fn build_prompt(name: &str, chunks: &[TextChunk]) -> String {
+    let mut prompt = format!("Statements about \"{name}\":\n");
+    for (i, chunk) in chunks.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", i + 1, chunk.content.trim()));
+    }
+    prompt
+}
+
+/// Extract findings from the model's reply.
+///
+/// Tolerates surrounding prose or reasoning by parsing the outermost `[...]`
+/// span.  Out-of-range or self-referential indices are dropped.
+fn parse_findings(text: &str, object_id: ObjectId, chunks: &[TextChunk]) -> Vec<ConsistencyWarning> {
+    let (Some(start), Some(end)) = (text.find('['), text.rfind(']')) else {
+        warn!("LLM reply contained no JSON array; treating as no contradictions");
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let findings: Vec<RawFinding> = match serde_json::from_str(&text[start..=end]) {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("Failed to parse contradiction findings: {e}");
+            return Vec::new();
+        }
+    };
+
+    let now = chrono::Utc::now();
+    findings
+        .into_iter()
+        .filter(|f| {
+            f.a != f.b && (1..=chunks.len()).contains(&f.a) && (1..=chunks.len()).contains(&f.b)
+        })
+        .map(|f| ConsistencyWarning {
+            id: ConsistencyWarningId::new_v4(),
+            object_id,
+            first_chunk: chunks[f.a - 1].id,
+            second_chunk: chunks[f.b - 1].id,
+            explanation: f.explanation.trim().to_string(),
+            dismissed: false,
+            created_at: now,
+        })
+        .collect()
+}
+
+// ── Facade ────────────────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Replace the undismissed warnings for `object_id` (see
+    /// [`detect_contradictions`]).
+    pub fn replace_consistency_warnings(
+        &self,
+        object_id: ObjectId,
+        warnings: &[ConsistencyWarning],
+    ) -> Result<()> {
+        self.storage.replace_consistency_warnings(object_id, warnings)
+    }
+
+    /// All warnings across the graph, newest first.
+    pub fn get_consistency_warnings(&self, include_dismissed: bool) -> Result<Vec<ConsistencyWarning>> {
+        self.storage.get_consistency_warnings(None, include_dismissed)
+    }
+
+    /// Warnings for a single object, newest first.
+    pub fn get_object_consistency_warnings(
+        &self,
+        object_id: ObjectId,
+        include_dismissed: bool,
+    ) -> Result<Vec<ConsistencyWarning>> {
+        self.storage
+            .get_consistency_warnings(Some(object_id), include_dismissed)
+    }
+
+    /// Hide a warning from the default listing.  Re-running the analysis will
+    /// not re-create a warning for the same chunk pair.
+    pub fn dismiss_consistency_warning(&self, id: ConsistencyWarningId) -> Result<()> {
+        self.storage.dismiss_consistency_warning(id)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ChunkType;
+    use crate::ObjectBuilder;
+    use tempfile::TempDir;
+
+    fn graph_with_statements() -> (KnowledgeGraph, TempDir, ObjectId, Vec<TextChunk>) {
+        let (graph, tmp) = create_test_graph();
+        let id = ObjectBuilder::character("Boromir".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        for text in [
+            "Boromir was killed by orcs in session 3.",
+            "Boromir is a son of the Steward of Gondor.",
+            "Boromir appears alive at the council in session 7.",
+        ] {
+            graph
+                .add_text_chunk(id, text.to_string(), ChunkType::SessionNote)
+                .unwrap();
+        }
+        let chunks = graph.get_text_chunks(id).unwrap();
+        (graph, tmp, id, chunks)
+    }
+
+    #[test]
+    fn test_parse_findings_tolerates_prose_and_bad_indices() {
+        let (_graph, _tmp, id, chunks) = graph_with_statements();
+        let reply = "<think>checking</think> Here you go:\n\
+            [{\"a\": 1, \"b\": 3, \"explanation\": \"Dead in 3, alive in 7.\"},\
+             {\"a\": 2, \"b\": 2, \"explanation\": \"self\"},\
+             {\"a\": 1, \"b\": 9, \"explanation\": \"out of range\"}]";
+
+        let found = parse_findings(reply, id, &chunks);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].first_chunk, chunks[0].id);
+        assert_eq!(found[0].second_chunk, chunks[2].id);
+        assert_eq!(found[0].explanation, "Dead in 3, alive in 7.");
+
+        assert!(parse_findings("No contradictions found.", id, &chunks).is_empty());
+        assert!(parse_findings("[]", id, &chunks).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_windows_overlap() {
+        let (_graph, _tmp, id, _) = graph_with_statements();
+        let many: Vec<TextChunk> = (0..20)
+            .map(|i| TextChunk::new(id, format!("statement {i}"), ChunkType::UserNote))
+            .collect();
+        let windows = chunk_windows(&many);
+        assert_eq!(windows.len(), 3);
+        assert!(windows.iter().all(|w| w.len() <= CHUNKS_PER_PROMPT));
+        assert_eq!(windows.last().unwrap().last().unwrap().id, many[19].id);
+    }
+
+    #[test]
+    fn test_dismissed_warning_survives_rescan() {
+        let (graph, _tmp, id, chunks) = graph_with_statements();
+        let warning = ConsistencyWarning {
+            id: ConsistencyWarningId::new_v4(),
+            object_id: id,
+            first_chunk: chunks[0].id,
+            second_chunk: chunks[2].id,
+            explanation: "Dead in 3, alive in 7.".to_string(),
+            dismissed: false,
+            created_at: chrono::Utc::now(),
+        };
+        graph
+            .replace_consistency_warnings(id, std::slice::from_ref(&warning))
+            .unwrap();
+        assert_eq!(graph.get_consistency_warnings(false).unwrap().len(), 1);
+
+        graph.dismiss_consistency_warning(warning.id).unwrap();
+        assert!(graph.get_consistency_warnings(false).unwrap().is_empty());
+
+        // A rescan flagging the same pair (in either order) is suppressed.
+        let again = ConsistencyWarning {
+            id: ConsistencyWarningId::new_v4(),
+            first_chunk: chunks[2].id,
+            second_chunk: chunks[0].id,
+            ..warning
+        };
+        graph.replace_consistency_warnings(id, &[again]).unwrap();
+        assert!(graph.get_object_consistency_warnings(id, false).unwrap().is_empty());
+        assert_eq!(graph.get_object_consistency_warnings(id, true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_warning_removed_when_chunks_deleted() {
+        let (graph, _tmp, id, chunks) = graph_with_statements();
+        let warning = ConsistencyWarning {
+            id: ConsistencyWarningId::new_v4(),
+            object_id: id,
+            first_chunk: chunks[0].id,
+            second_chunk: chunks[2].id,
+            explanation: String::new(),
+            dismissed: false,
+            created_at: chrono::Utc::now(),
+        };
+        graph.replace_consistency_warnings(id, &[warning]).unwrap();
+        graph.delete_chunks_for_node(id).unwrap();
+        assert!(graph.get_consistency_warnings(true).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_detect_requires_text_generation_worker() {
+        let (graph, _tmp, id, _) = graph_with_statements();
+        let queue = crate::queue::InferenceQueueBuilder::new().build();
+        assert!(detect_contradictions(&graph, &queue, id).await.is_err());
+
+        // Nothing to compare → no LLM call, no error.
+        let lonely = ObjectBuilder::item("Horn of Gondor".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        assert!(detect_contradictions(&graph, &queue, lonely)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
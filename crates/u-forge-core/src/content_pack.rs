@@ -0,0 +1,394 @@
+//! Content packs — genre starter kits a community can publish.
+//!
+//! A pack is a directory:
+//!
+//! ```text
+//! my-pack/
+//!   pack.json     manifest: name, version, description, templates, random tables
+//!   schemas/      optional; JSON schema files as read by SchemaIngestion
+//!   data.jsonl    optional; seed objects and edges in the JSONL import format
+//! ```
+//!
+//! [`KnowledgeGraph::install_pack`] applies it to the project.  The schemas
+//! are saved as the schema `pack:<name>`; seed objects are imported with ids
+//! derived from the pack name ([`DataIngestion::with_id_seed`]), so
+//! reinstalling updates them instead of duplicating them, and edges are
+//! unique per endpoints and type anyway.  Templates and random tables are
+//! stored by name in the project settings, a later pack replacing an earlier
+//! one's entry of the same name.  Installing a pack at the version already
+//! installed does nothing, so `install_pack` can run on every startup.
+//!
+//! [`KnowledgeGraph::create_from_template`] instantiates a template;
+//! [`RandomTable::pick`] maps a die roll to an entry.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::info;
+
+use crate::error::{Result, UForgeError};
+use crate::ingest::data::{DataIngestion, JsonEntry};
+use crate::schema::SchemaIngestion;
+use crate::types::{ChunkType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Manifest file name inside a pack directory.
+pub const PACK_MANIFEST_FILE: &str = "pack.json";
+/// Schema directory inside a pack directory.
+pub const PACK_SCHEMA_DIR: &str = "schemas";
+/// Seed data file inside a pack directory.
+pub const PACK_DATA_FILE: &str = "data.jsonl";
+
+/// Settings key of the installed packs, by name.
+pub const INSTALLED_PACKS_SETTING: &str = "content_packs";
+/// Settings key of the object templates, by name.
+pub const TEMPLATES_SETTING: &str = "object_templates";
+/// Settings key of the random tables, by name.
+pub const RANDOM_TABLES_SETTING: &str = "random_tables";
+
+/// A pack's `pack.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub templates: Vec<ObjectTemplate>,
+    #[serde(default)]
+    pub random_tables: Vec<RandomTable>,
+}
+
+/// A starting point for a new object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectTemplate {
+    pub name: String,
+    pub object_type: String,
+    #[serde(default)]
+    pub description: String,
+    /// Copied into each object made from the template.
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+}
+
+/// A weighted table to roll on ("d20 tavern names").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RandomTable {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub entries: Vec<RandomTableEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RandomTableEntry {
+    /// How many faces of the die land here.
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    pub result: String,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl RandomTable {
+    /// The die the table is rolled with: the sum of the weights.
+    pub fn die_size(&self) -> u32 {
+        self.entries.iter().map(|e| e.weight).sum()
+    }
+
+    /// The entry a roll of `roll` (`1..=die_size()`) lands on.
+    pub fn pick(&self, roll: u32) -> Option<&RandomTableEntry> {
+        let mut upper = 0;
+        for entry in &self.entries {
+            upper += entry.weight;
+            if roll >= 1 && roll <= upper {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+/// A pack recorded in [`INSTALLED_PACKS_SETTING`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledPack {
+    pub name: String,
+    pub version: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// What [`KnowledgeGraph::install_pack`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PackInstall {
+    pub name: String,
+    pub version: String,
+    /// The same version was installed already; nothing was written.
+    pub already_installed: bool,
+    pub object_types: usize,
+    pub objects_created: usize,
+    pub objects_updated: usize,
+    pub relationships_created: usize,
+    pub templates: usize,
+    pub random_tables: usize,
+}
+
+impl KnowledgeGraph {
+    /// Install the pack in directory `path`; see the module docs.  Fails with
+    /// [`UForgeError::ValidationFailed`] for a malformed manifest.
+    pub async fn install_pack<P: AsRef<Path>>(&self, path: P) -> Result<PackInstall> {
+        let dir = path.as_ref();
+        let manifest_path = dir.join(PACK_MANIFEST_FILE);
+        let text = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read {manifest_path:?}"))?;
+        let manifest: PackManifest = serde_json::from_str(&text).map_err(|e| {
+            UForgeError::ValidationFailed(format!("Invalid pack manifest {manifest_path:?}: {e}"))
+        })?;
+        validate_manifest(&manifest)?;
+
+        let mut report = PackInstall {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            ..Default::default()
+        };
+        let mut installed: BTreeMap<String, InstalledPack> =
+            self.setting_map(INSTALLED_PACKS_SETTING)?;
+        if installed
+            .get(&manifest.name)
+            .is_some_and(|p| p.version == manifest.version)
+        {
+            report.already_installed = true;
+            return Ok(report);
+        }
+        let seed = format!("pack:{}", manifest.name);
+
+        let schema_dir = dir.join(PACK_SCHEMA_DIR);
+        if schema_dir.is_dir() {
+            let schema =
+                SchemaIngestion::load_schemas_from_directory(&schema_dir, &seed, &manifest.version)?;
+            report.object_types = schema.object_types.len();
+            self.get_schema_manager().save_schema(&schema).await?;
+        }
+
+        let data_file = dir.join(PACK_DATA_FILE);
+        if data_file.is_file() {
+            let mut ingestion = DataIngestion::new(self).with_id_seed(seed.clone());
+            ingestion.import_json_data(&data_file).await?;
+            let stats = ingestion.get_stats();
+            report.objects_created = stats.objects_created;
+            report.objects_updated = stats.objects_updated;
+            report.relationships_created = stats.relationships_created;
+            self.index_seed_objects(&seed, &data_file)?;
+        }
+
+        let mut templates: BTreeMap<String, ObjectTemplate> = self.setting_map(TEMPLATES_SETTING)?;
+        for template in &manifest.templates {
+            templates.insert(template.name.clone(), template.clone());
+        }
+        report.templates = manifest.templates.len();
+        self.save_setting_map(TEMPLATES_SETTING, &templates)?;
+
+        let mut tables: BTreeMap<String, RandomTable> = self.setting_map(RANDOM_TABLES_SETTING)?;
+        for table in &manifest.random_tables {
+            tables.insert(table.name.clone(), table.clone());
+        }
+        report.random_tables = manifest.random_tables.len();
+        self.save_setting_map(RANDOM_TABLES_SETTING, &tables)?;
+
+        installed.insert(
+            manifest.name.clone(),
+            InstalledPack {
+                name: manifest.name,
+                version: manifest.version,
+                installed_at: Utc::now(),
+            },
+        );
+        self.save_setting_map(INSTALLED_PACKS_SETTING, &installed)?;
+        info!(pack = %report.name, version = %report.version, "Content pack installed");
+        Ok(report)
+    }
+
+    /// Installed packs, by name.
+    pub fn installed_packs(&self) -> Result<Vec<InstalledPack>> {
+        Ok(self
+            .setting_map::<InstalledPack>(INSTALLED_PACKS_SETTING)?
+            .into_values()
+            .collect())
+    }
+
+    /// Every object template, by name.
+    pub fn object_templates(&self) -> Result<Vec<ObjectTemplate>> {
+        Ok(self
+            .setting_map::<ObjectTemplate>(TEMPLATES_SETTING)?
+            .into_values()
+            .collect())
+    }
+
+    /// Every random table, by name.
+    pub fn random_tables(&self) -> Result<Vec<RandomTable>> {
+        Ok(self
+            .setting_map::<RandomTable>(RANDOM_TABLES_SETTING)?
+            .into_values()
+            .collect())
+    }
+
+    pub fn random_table(&self, name: &str) -> Result<Option<RandomTable>> {
+        Ok(self
+            .setting_map::<RandomTable>(RANDOM_TABLES_SETTING)?
+            .remove(name))
+    }
+
+    /// Create an object called `name` from template `template`.  Fails with
+    /// [`UForgeError::NotFound`] for an unknown template.
+    pub fn create_from_template(&self, template: &str, name: &str) -> Result<ObjectId> {
+        let template = self
+            .setting_map::<ObjectTemplate>(TEMPLATES_SETTING)?
+            .remove(template)
+            .ok_or_else(|| UForgeError::NotFound(format!("No template named '{template}'")))?;
+        let mut object = ObjectMetadata::new(template.object_type, name.to_string());
+        object.properties = Value::Object(template.properties);
+        self.add_object(object)
+    }
+
+    /// Give each seed object from `data_file` that has no text yet a chunk,
+    /// so the pack's content is searchable straight away.
+    fn index_seed_objects(&self, seed: &str, data_file: &Path) -> Result<()> {
+        let text = fs::read_to_string(data_file)
+            .with_context(|| format!("Failed to read {data_file:?}"))?;
+        for line in text.lines() {
+            let Ok(JsonEntry::Node {
+                node_type,
+                properties,
+                ..
+            }) = serde_json::from_str::<JsonEntry>(line)
+            else {
+                continue;
+            };
+            let Some(name) = properties.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let id = ObjectId::deterministic(seed, &node_type, name);
+            let Some(object) = self.get_object(id)? else {
+                continue;
+            };
+            if self.get_text_chunks(id)?.is_empty() {
+                self.add_text_chunk(id, object.flatten_for_embedding(&[]), ChunkType::Imported)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn setting_map<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<BTreeMap<String, T>> {
+        match self.storage.get_setting(key)? {
+            Some(json) => {
+                Ok(serde_json::from_str(&json)
+                    .with_context(|| format!("Invalid '{key}' setting"))?)
+            }
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn save_setting_map<T: Serialize>(&self, key: &str, map: &BTreeMap<String, T>) -> Result<()> {
+        let json = serde_json::to_string(map).with_context(|| format!("Failed to serialize '{key}'"))?;
+        self.storage.set_setting(key, &json)
+    }
+}
+
+fn validate_manifest(manifest: &PackManifest) -> Result<()> {
+    let invalid = UForgeError::ValidationFailed;
+    if manifest.name.trim().is_empty() || manifest.version.trim().is_empty() {
+        return Err(invalid("A pack needs a name and a version".to_string()));
+    }
+    for template in &manifest.templates {
+        if template.name.trim().is_empty() || template.object_type.trim().is_empty() {
+            return Err(invalid(format!(
+                "Template '{}' in pack '{}' needs a name and an object type",
+                template.name, manifest.name
+            )));
+        }
+    }
+    for table in &manifest.random_tables {
+        if table.entries.is_empty() || table.entries.iter().any(|e| e.weight == 0) {
+            return Err(invalid(format!(
+                "Random table '{}' in pack '{}' needs entries with positive weights",
+                table.name, manifest.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_install_pack_is_idempotent() {
+        let pack_dir = TempDir::new().unwrap();
+        fs::write(
+            pack_dir.path().join(PACK_MANIFEST_FILE),
+            r#"{
+                "name": "gothic-horror",
+                "version": "1.0.0",
+                "templates": [{"name": "Vampire lord", "object_type": "npc",
+                               "properties": {"description": "Ancient and hungry."}}],
+                "random_tables": [{"name": "Omens", "entries": [
+                    {"weight": 2, "result": "A raven watches"},
+                    {"result": "The candles gutter"}]}]
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            pack_dir.path().join(PACK_DATA_FILE),
+            concat!(
+                r#"{"entitytype":"node","id":"1","nodetype":"location","properties":{"name":"Barovia"}}"#,
+                "\n",
+                r#"{"entitytype":"node","id":"2","nodetype":"npc","properties":{"name":"Ireena"}}"#,
+                "\n",
+                r#"{"entitytype":"edge","from":"Ireena","to":"Barovia","edgeType":"located_in"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let (graph, _temp_dir) = create_test_graph();
+        let first = graph.install_pack(pack_dir.path()).await.unwrap();
+        assert_eq!(
+            (first.objects_created, first.relationships_created, first.templates),
+            (2, 1, 1)
+        );
+        let again = graph.install_pack(pack_dir.path()).await.unwrap();
+        assert!(again.already_installed);
+        assert_eq!(graph.get_all_objects().unwrap().len(), 2);
+        assert_eq!(graph.installed_packs().unwrap()[0].version, "1.0.0");
+
+        // A new version re-applies onto the same objects.
+        let manifest = fs::read_to_string(pack_dir.path().join(PACK_MANIFEST_FILE)).unwrap();
+        fs::write(
+            pack_dir.path().join(PACK_MANIFEST_FILE),
+            manifest.replace("1.0.0", "1.1.0"),
+        )
+        .unwrap();
+        let upgrade = graph.install_pack(pack_dir.path()).await.unwrap();
+        assert_eq!((upgrade.objects_created, upgrade.objects_updated), (0, 2));
+        assert_eq!(graph.get_all_objects().unwrap().len(), 2);
+
+        let omens = graph.random_table("Omens").unwrap().unwrap();
+        assert_eq!(omens.die_size(), 3);
+        assert_eq!(omens.pick(2).unwrap().result, "A raven watches");
+        assert_eq!(omens.pick(3).unwrap().result, "The candles gutter");
+        assert!(omens.pick(4).is_none());
+
+        let strahd = graph.create_from_template("Vampire lord", "Strahd").unwrap();
+        let strahd = graph.get_object(strahd).unwrap().unwrap();
+        assert_eq!(strahd.properties["description"], "Ancient and hungry.");
+    }
+}
@@ -0,0 +1,393 @@
+//! Token-budgeted context assembly for RAG prompts.
+//!
+//! Rendering every chunk of every search hit overflows small context windows
+//! and repeats near-identical text.  [`build_context`] instead treats each
+//! chunk as a candidate, scores it, and greedily packs the best ones into a
+//! fixed token budget:
+//!
+//! * **Relevance** — the owning node's search score (normalised against the
+//!   best hit) blended with how many of the question's terms the chunk
+//!   contains.
+//! * **Recency** — newer chunks (e.g. the latest session notes) score higher.
+//! * **Diversity** — each chunk already taken from an object divides the
+//!   score of that object's remaining chunks, and
+//!   [`ContextBuilderConfig::max_chunks_per_object`] caps them outright.
+//! * **Dedupe** — chunks with the same content hash are only included once.
+//!
+//! Selected chunks are grouped under a short object card (`### name (type)`
+//! plus connected nodes) and tagged with `[n]` markers that map back to
+//! [`ContextCitation`]s.  The result converts into a [`RagContext`] so it can
+//! be dropped into [`build_rag_messages`](crate::rag::build_rag_messages).
+//! [`format_search_context`](crate::rag::format_search_context) is the
+//! unbudgeted case; [`ask_about`](crate::interrogate::ask_about), session
+//! recaps ([`draft_session_recap`](crate::ingest::draft_session_recap)) and
+//! the agent's search tool all go through it.
+//!
+//! ```text
+//! search_hybrid(graph, queue, ...)
+//!     → Vec<NodeSearchResult>
+//!     → build_context(question, &results, &config)   // → AssembledContext
+//!     → RagContext::from(assembled)
+//!     → build_rag_messages(...)
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::rag::RagContext;
+use crate::search::NodeSearchResult;
+use crate::text::{content_hash, count_tokens, DEFAULT_TOKENIZER_MODEL};
+use crate::types::{ChunkId, ObjectId, TextChunk};
+
+// ── Configuration ─────────────────────────────────────────────────────────────
+
+/// Tuning knobs for [`build_context`].
+#[derive(Debug, Clone)]
+pub struct ContextBuilderConfig {
+    /// Maximum number of tokens the assembled context may occupy, including
+    /// object cards and citation markers.
+    pub max_tokens: usize,
+
+    /// Model name passed to [`count_tokens`](crate::count_tokens) so the
+    /// budget is measured with the target model's tokenizer.
+    pub tokenizer_model: String,
+
+    /// Weight of the relevance signal in a chunk's score.
+    pub relevance_weight: f32,
+
+    /// Weight of the recency signal in a chunk's score.  `0.0` ignores chunk
+    /// age entirely.
+    pub recency_weight: f32,
+
+    /// Upper bound on chunks taken from any single object.
+    pub max_chunks_per_object: usize,
+}
+
+impl Default for ContextBuilderConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 2048,
+            tokenizer_model: DEFAULT_TOKENIZER_MODEL.to_string(),
+            relevance_weight: 1.0,
+            recency_weight: 0.2,
+            max_chunks_per_object: 3,
+        }
+    }
+}
+
+// ── Output types ──────────────────────────────────────────────────────────────
+
+/// Maps a `[n]` marker in [`AssembledContext::text`] back to its source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextCitation {
+    /// 1-based marker number as it appears in the text.
+    pub index: usize,
+    pub object_id: ObjectId,
+    pub object_name: String,
+    pub chunk_id: ChunkId,
+}
+
+/// Prompt-ready context block produced by [`build_context`].
+#[derive(Debug, Clone, Default)]
+pub struct AssembledContext {
+    /// Rendered context: one object card per contributing object followed by
+    /// its cited chunks.
+    pub text: String,
+
+    /// One entry per `[n]` marker, in marker order.
+    pub citations: Vec<ContextCitation>,
+
+    /// Number of distinct objects that contributed at least one chunk.
+    pub object_count: usize,
+
+    /// Tokens used by `text`, measured with the configured tokenizer.
+    pub tokens_used: usize,
+
+    /// Candidate chunks left out because of the budget, the per-object cap,
+    /// or duplicate content.
+    pub dropped_chunks: usize,
+}
+
+impl From<AssembledContext> for RagContext {
+    fn from(ctx: AssembledContext) -> Self {
+        RagContext {
+            formatted_context: ctx.text,
+            source_count: ctx.object_count,
+        }
+    }
+}
+
+// ── Assembly ──────────────────────────────────────────────────────────────────
+
+/// A chunk eligible for inclusion, with its pre-diversity score.
+struct Candidate<'a> {
+    result: usize,
+    chunk: &'a TextChunk,
+    score: f32,
+    tokens: usize,
+}
+
+/// Select, order, and render chunks from `results` into a context block that
+/// fits within `config.max_tokens`.
+///
+/// `question` feeds the lexical part of the relevance score; pass an empty
+/// string when there is no question (e.g. recap generation) to rank purely on
+/// search score and recency.  Results with no chunks contribute nothing.
+pub fn build_context(
+    question: &str,
+    results: &[NodeSearchResult],
+    config: &ContextBuilderConfig,
+) -> AssembledContext {
+    let model = config.tokenizer_model.as_str();
+    let terms = query_terms(question);
+
+    let max_score = results
+        .iter()
+        .map(|r| r.score)
+        .fold(f32::MIN, f32::max)
+        .max(f32::EPSILON);
+    let (oldest, newest) = results
+        .iter()
+        .flat_map(|r| r.chunks.iter().map(|c| c.created_at.timestamp()))
+        .fold((i64::MAX, i64::MIN), |(lo, hi), t| (lo.min(t), hi.max(t)));
+    let age_span = (newest - oldest).max(1) as f32;
+
+    // ── Score and dedupe candidates ─────────────────────────────────────────
+    let mut seen_hashes = HashSet::new();
+    let mut candidates = Vec::new();
+    let mut dropped_chunks = 0;
+    for (ri, result) in results.iter().enumerate() {
+        let node_relevance = (result.score / max_score).clamp(0.0, 1.0);
+        for chunk in &result.chunks {
+            if !seen_hashes.insert(content_hash(&normalize_whitespace(&chunk.content))) {
+                dropped_chunks += 1;
+                continue;
+            }
+            let relevance = if terms.is_empty() {
+                node_relevance
+            } else {
+                0.7 * node_relevance + 0.3 * term_overlap(&terms, &chunk.content)
+            };
+            let recency = (chunk.created_at.timestamp() - oldest) as f32 / age_span;
+            candidates.push(Candidate {
+                result: ri,
+                chunk,
+                score: config.relevance_weight * relevance + config.recency_weight * recency,
+                tokens: count_tokens(&chunk.content, model) + 4, // "[n] " + newline
+            });
+        }
+    }
+
+    // ── Greedy selection with per-object diversity penalty ──────────────────
+    let card_tokens: Vec<usize> = results
+        .iter()
+        .map(|r| count_tokens(&object_card(r), model))
+        .collect();
+    let mut taken_per_result: HashMap<usize, usize> = HashMap::new();
+    // Result indices in the order their first chunk was selected.
+    let mut object_order: Vec<usize> = Vec::new();
+    let mut selected: Vec<&Candidate> = Vec::new();
+    let mut remaining: Vec<&Candidate> = candidates.iter().collect();
+    let mut used = 0usize;
+
+    loop {
+        let best = remaining
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                let taken = taken_per_result.get(&c.result).copied().unwrap_or(0);
+                let card = if taken == 0 { card_tokens[c.result] } else { 0 };
+                taken < config.max_chunks_per_object && used + card + c.tokens <= config.max_tokens
+            })
+            .map(|(i, c)| {
+                let taken = taken_per_result.get(&c.result).copied().unwrap_or(0);
+                (i, c.score / (1 + taken) as f32)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some((i, _)) = best else { break };
+        let cand = remaining.swap_remove(i);
+        let taken = taken_per_result.entry(cand.result).or_insert(0);
+        if *taken == 0 {
+            used += card_tokens[cand.result];
+            object_order.push(cand.result);
+        }
+        *taken += 1;
+        used += cand.tokens;
+        selected.push(cand);
+    }
+    dropped_chunks += remaining.len();
+
+    // ── Render ──────────────────────────────────────────────────────────────
+    let mut sections = Vec::with_capacity(object_order.len());
+    let mut citations = Vec::with_capacity(selected.len());
+    for &ri in &object_order {
+        let result = &results[ri];
+        let mut chunks: Vec<_> = selected.iter().filter(|c| c.result == ri).collect();
+        // Chronological within an object so narrative order is preserved.
+        chunks.sort_by_key(|c| c.chunk.created_at);
+
+        let mut section = object_card(result);
+        for c in chunks {
+            let index = citations.len() + 1;
+            section.push_str(&format!("[{index}] {}\n", c.chunk.content.trim()));
+            citations.push(ContextCitation {
+                index,
+                object_id: result.node.id,
+                object_name: result.node.name.clone(),
+                chunk_id: c.chunk.id,
+            });
+        }
+        sections.push(section);
+    }
+
+    let text = sections.join("\n");
+    AssembledContext {
+        tokens_used: count_tokens(&text, model),
+        text,
+        citations,
+        object_count: object_order.len(),
+        dropped_chunks,
+    }
+}
+
+/// Header rendered once per object: name, type, and connected nodes.
+fn object_card(result: &NodeSearchResult) -> String {
+    let mut card = format!("### {} ({})\n", result.node.name, result.node.object_type);
+    if !result.connected_node_names.is_empty() {
+        let mut connected: Vec<&str> = result
+            .connected_node_names
+            .values()
+            .map(|c| c.name.as_str())
+            .collect();
+        connected.sort_unstable();
+        connected.dedup();
+        card.push_str("Connected: ");
+        card.push_str(&connected.join(", "));
+        card.push('\n');
+    }
+    card
+}
+
+/// Lowercased question words of three or more characters, deduplicated.
+fn query_terms(question: &str) -> Vec<String> {
+    let mut terms: Vec<String> = question
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort_unstable();
+    terms.dedup();
+    terms
+}
+
+/// Fraction of `terms` that appear in `content` (case-insensitive).
+fn term_overlap(terms: &[String], content: &str) -> f32 {
+    let content = content.to_lowercase();
+    let hits = terms.iter().filter(|t| content.contains(t.as_str())).count();
+    hits as f32 / terms.len() as f32
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchSources;
+    use crate::types::{ChunkType, ObjectMetadata};
+
+    fn result(name: &str, score: f32, chunks: &[&str]) -> NodeSearchResult {
+        let node = ObjectMetadata::new("character".to_string(), name.to_string());
+        let chunks = chunks
+            .iter()
+            .map(|c| TextChunk::new(node.id, c.to_string(), ChunkType::Description))
+            .collect();
+        NodeSearchResult {
+            node,
+            chunks,
+            edges: Vec::new(),
+            connected_node_names: HashMap::new(),
+            score,
+            sources: SearchSources::default(),
+        }
+    }
+
+    #[test]
+    fn test_citations_match_markers_and_sources() {
+        let results = vec![
+            result("Gandalf", 1.0, &["Gandalf is a wizard."]),
+            result("Frodo", 0.5, &["Frodo carries the ring."]),
+        ];
+        let ctx = build_context("who is the wizard", &results, &ContextBuilderConfig::default());
+
+        assert_eq!(ctx.object_count, 2);
+        assert_eq!(ctx.citations.len(), 2);
+        assert!(ctx.text.starts_with("### Gandalf (character)\n[1] Gandalf is a wizard."));
+        assert!(ctx.text.contains("[2] Frodo carries the ring."));
+        assert_eq!(ctx.citations[1].object_name, "Frodo");
+        assert_eq!(ctx.citations[1].chunk_id, results[1].chunks[0].id);
+        assert_eq!(ctx.dropped_chunks, 0);
+    }
+
+    #[test]
+    fn test_duplicate_chunks_are_included_once() {
+        let results = vec![
+            result("Gandalf", 1.0, &["The Grey Pilgrim."]),
+            result("Mithrandir", 0.9, &["The  Grey\nPilgrim."]),
+        ];
+        let ctx = build_context("", &results, &ContextBuilderConfig::default());
+        assert_eq!(ctx.citations.len(), 1);
+        assert_eq!(ctx.dropped_chunks, 1);
+    }
+
+    #[test]
+    fn test_per_object_cap_spreads_selection() {
+        let results = vec![
+            result("Gandalf", 1.0, &["One.", "Two.", "Three.", "Four."]),
+            result("Frodo", 0.1, &["Hobbit."]),
+        ];
+        let config = ContextBuilderConfig {
+            max_chunks_per_object: 2,
+            ..Default::default()
+        };
+        let ctx = build_context("", &results, &config);
+        let gandalf = ctx
+            .citations
+            .iter()
+            .filter(|c| c.object_name == "Gandalf")
+            .count();
+        assert_eq!(gandalf, 2);
+        assert!(ctx.citations.iter().any(|c| c.object_name == "Frodo"));
+        assert_eq!(ctx.dropped_chunks, 2);
+    }
+
+    #[test]
+    fn test_respects_token_budget() {
+        let long = "word ".repeat(200);
+        let results = vec![
+            result("Gandalf", 1.0, &[long.as_str()]),
+            result("Frodo", 0.5, &["Short note."]),
+        ];
+        let config = ContextBuilderConfig {
+            max_tokens: 50,
+            ..Default::default()
+        };
+        let ctx = build_context("", &results, &config);
+        assert!(ctx.tokens_used <= config.max_tokens);
+        assert_eq!(ctx.citations.len(), 1);
+        assert_eq!(ctx.citations[0].object_name, "Frodo");
+    }
+
+    #[test]
+    fn test_converts_into_rag_context() {
+        let results = vec![result("Gandalf", 1.0, &["A wizard."])];
+        let ctx = build_context("", &results, &ContextBuilderConfig::default());
+        let text = ctx.text.clone();
+        let rag: RagContext = ctx.into();
+        assert_eq!(rag.formatted_context, text);
+        assert_eq!(rag.source_count, 1);
+    }
+}
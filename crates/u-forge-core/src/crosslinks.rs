@@ -0,0 +1,122 @@
+//! References from this project to objects in another project.
+//!
+//! A shared "setting bible" project is referenced from several campaign
+//! projects rather than copied into each.  Every project carries a stable
+//! [`project_id`](KnowledgeGraph::project_id), created on first use and kept
+//! in the project settings, and an [`ExternalRef`] names a target by that id
+//! and its object id.  Nothing about the target is copied except a label
+//! (its name when the link was made), so the reference still reads sensibly
+//! while the other project is closed.
+//!
+//! References are resolved lazily:
+//! [`ProjectManager::resolve`](crate::projects::ProjectManager::resolve)
+//! looks the target up in whichever open project has that id, and
+//! [`ProjectManager::transclude`](crate::projects::ProjectManager::transclude)
+//! does so for every reference of an object.  Deleting the source object
+//! drops its references; deleting the target leaves them dangling, which
+//! resolution reports as [`ResolvedRef::Missing`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Settings key holding the project's stable id.
+pub const PROJECT_ID_SETTING: &str = "project_id";
+
+/// An edge from an object here to an object in another project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalRef {
+    pub id: Uuid,
+    pub source: ObjectId,
+    pub edge_type: String,
+    /// [`KnowledgeGraph::project_id`] of the project holding the target.
+    pub project_id: Uuid,
+    pub object_id: ObjectId,
+    /// The target's name when the reference was made or last refreshed.
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What an [`ExternalRef`] points at right now.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ResolvedRef {
+    Resolved {
+        /// Name the target project is open under.
+        project: String,
+        object: ObjectMetadata,
+    },
+    /// No open project has the target's project id.
+    ProjectClosed,
+    /// The target project is open but the object is gone.
+    Missing,
+}
+
+impl KnowledgeGraph {
+    /// This project's stable id, generated and saved the first time it is
+    /// asked for.
+    pub fn project_id(&self) -> Result<Uuid> {
+        if let Some(id) = self.storage.get_setting(PROJECT_ID_SETTING)? {
+            return Uuid::parse_str(&id).map_err(|e| {
+                UForgeError::ValidationFailed(format!("Invalid stored project id '{id}': {e}"))
+            });
+        }
+        let id = Uuid::new_v4();
+        self.storage
+            .set_setting(PROJECT_ID_SETTING, &id.to_string())?;
+        Ok(id)
+    }
+
+    /// Reference `object_id` in project `project_id` from `source` with
+    /// an `edge_type` edge, labelled `label`.  Making the same reference
+    /// again refreshes its label and returns the existing one.
+    ///
+    /// Fails with [`UForgeError::NotFound`] when `source` does not exist and
+    /// [`UForgeError::ValidationFailed`] when `project_id` is this project —
+    /// use an ordinary edge for that.
+    pub fn add_external_ref(
+        &self,
+        source: ObjectId,
+        edge_type: &str,
+        project_id: Uuid,
+        object_id: ObjectId,
+        label: &str,
+    ) -> Result<ExternalRef> {
+        if self.get_object(source)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Object {source} not found")));
+        }
+        if edge_type.trim().is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "An external reference needs an edge type".to_string(),
+            ));
+        }
+        if project_id == self.project_id()? {
+            return Err(UForgeError::ValidationFailed(
+                "External references must point at another project".to_string(),
+            ));
+        }
+        self.storage.upsert_external_ref(&ExternalRef {
+            id: Uuid::new_v4(),
+            source,
+            edge_type: edge_type.to_string(),
+            project_id,
+            object_id,
+            label: label.to_string(),
+            created_at: Utc::now(),
+        })
+    }
+
+    /// `source`'s references into other projects, oldest first.
+    pub fn external_refs(&self, source: ObjectId) -> Result<Vec<ExternalRef>> {
+        self.storage.list_external_refs(Some(source), None)
+    }
+
+    /// Remove reference `id`.  Returns `false` when it did not exist.
+    pub fn remove_external_ref(&self, id: Uuid) -> Result<bool> {
+        self.storage.delete_external_ref(id)
+    }
+}
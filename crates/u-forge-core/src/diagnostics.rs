@@ -0,0 +1,233 @@
+//! Per-operation timing for diagnosing performance reports.
+//!
+//! Storage queries, search, embedding sweeps and imports open an
+//! [`OperationGuard`] with [`trace_operation`].  While tracing is switched on
+//! with [`set_operation_tracing`] each guard opens a `tracing` span
+//! (`component`, `operation`, `items`, `duration_us`) that a subscriber can
+//! export, emits a debug event under the `u_forge::ops` target when it ends,
+//! and adds to process-wide counters: calls, items handled, total and
+//! slowest duration.  While it is off — the default — a guard costs one
+//! atomic load.
+//!
+//! [`KnowledgeGraph::diagnostics_trace`] bundles the counters with the read
+//! cache hit rate and the recent errors from [`crate::health`];
+//! [`KnowledgeGraph::export_diagnostics`] writes that as JSON for a user to
+//! attach to a bug report.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{debug, debug_span, field, Span};
+
+use crate::error::Result;
+use crate::graph::ReadCacheStats;
+use crate::health::{recent_errors, RecentError};
+use crate::KnowledgeGraph;
+
+static TRACING_ENABLED: AtomicBool = AtomicBool::new(false);
+
+static OPERATIONS: LazyLock<Mutex<BTreeMap<(&'static str, &'static str), OperationStats>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Turn operation tracing on or off for the whole process.
+pub fn set_operation_tracing(enabled: bool) {
+    TRACING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn operation_tracing_enabled() -> bool {
+    TRACING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Counters for one operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OperationStats {
+    /// Subsystem, e.g. `"storage"`, `"search"`, `"embedding"`, `"ingest"`.
+    pub component: &'static str,
+    pub operation: &'static str,
+    pub calls: u64,
+    /// Items handled (rows returned, chunks embedded, …), where reported.
+    pub items: u64,
+    pub total_us: u64,
+    pub max_us: u64,
+}
+
+impl OperationStats {
+    pub fn mean_us(&self) -> u64 {
+        self.total_us.checked_div(self.calls).unwrap_or(0)
+    }
+}
+
+/// Times one operation from [`trace_operation`] until dropped.
+pub struct OperationGuard {
+    /// `None` while tracing is off.
+    started: Option<(Instant, Span)>,
+    component: &'static str,
+    operation: &'static str,
+    items: u64,
+}
+
+/// Start timing `operation` of `component`.
+pub fn trace_operation(component: &'static str, operation: &'static str) -> OperationGuard {
+    let started = operation_tracing_enabled().then(|| {
+        let span = debug_span!(
+            "operation",
+            component,
+            operation,
+            items = field::Empty,
+            duration_us = field::Empty
+        );
+        (Instant::now(), span)
+    });
+    OperationGuard {
+        started,
+        component,
+        operation,
+        items: 0,
+    }
+}
+
+impl OperationGuard {
+    /// Record how many items the operation handled.
+    pub fn set_items(&mut self, items: usize) {
+        self.items = items as u64;
+    }
+
+    /// The operation's span; disabled while tracing is off.
+    pub fn span(&self) -> Span {
+        self.started
+            .as_ref()
+            .map_or_else(Span::none, |(_, span)| span.clone())
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let Some((started, span)) = self.started.take() else {
+            return;
+        };
+        let duration_us = started.elapsed().as_micros() as u64;
+        span.record("items", self.items);
+        span.record("duration_us", duration_us);
+        debug!(
+            target: "u_forge::ops",
+            component = self.component,
+            operation = self.operation,
+            items = self.items,
+            duration_us,
+            "Operation finished"
+        );
+        let mut operations = OPERATIONS.lock();
+        let stats = operations
+            .entry((self.component, self.operation))
+            .or_insert_with(|| OperationStats {
+                component: self.component,
+                operation: self.operation,
+                ..Default::default()
+            });
+        stats.calls += 1;
+        stats.items += self.items;
+        stats.total_us += duration_us;
+        stats.max_us = stats.max_us.max(duration_us);
+    }
+}
+
+/// Counters for every operation seen since the last reset, slowest total
+/// first.
+pub fn operation_stats() -> Vec<OperationStats> {
+    let mut stats: Vec<OperationStats> = OPERATIONS.lock().values().cloned().collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.total_us));
+    stats
+}
+
+pub fn reset_operation_stats() {
+    OPERATIONS.lock().clear();
+}
+
+/// Everything [`KnowledgeGraph::export_diagnostics`] writes.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsTrace {
+    pub captured_at: DateTime<Utc>,
+    pub tracing_enabled: bool,
+    pub operations: Vec<OperationStats>,
+    pub read_cache: ReadCacheStats,
+    /// Share of read cache lookups that hit, `None` before the first one.
+    pub read_cache_hit_rate: Option<f32>,
+    pub recent_errors: Vec<RecentError>,
+}
+
+impl KnowledgeGraph {
+    /// Current counters, cache statistics and recent errors.
+    pub fn diagnostics_trace(&self) -> DiagnosticsTrace {
+        let read_cache = self.read_cache_stats();
+        let lookups = read_cache.hits + read_cache.misses;
+        DiagnosticsTrace {
+            captured_at: Utc::now(),
+            tracing_enabled: operation_tracing_enabled(),
+            operations: operation_stats(),
+            read_cache_hit_rate: (lookups > 0).then(|| read_cache.hits as f32 / lookups as f32),
+            read_cache,
+            recent_errors: recent_errors(),
+        }
+    }
+
+    /// Write [`diagnostics_trace`](Self::diagnostics_trace) to `path` as
+    /// pretty JSON.
+    pub fn export_diagnostics<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(&self.diagnostics_trace())
+            .context("Failed to serialize diagnostics")?;
+        std::fs::write(path, json).with_context(|| format!("Failed to write {path:?}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+
+    #[test]
+    fn test_operation_tracing_counts_only_while_enabled() {
+        let (graph, temp_dir) = create_test_graph();
+        graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Elminster".to_string()))
+            .unwrap();
+        let all_objects_calls = || {
+            operation_stats()
+                .into_iter()
+                .find(|s| s.operation == "get_all_objects")
+                .map_or(0, |s| s.calls)
+        };
+
+        // Counters are process-wide, so compare against a baseline.
+        set_operation_tracing(false);
+        let before = all_objects_calls();
+        graph.get_all_objects().unwrap();
+        assert_eq!(all_objects_calls(), before);
+
+        set_operation_tracing(true);
+        graph.get_all_objects().unwrap();
+        graph.get_all_objects().unwrap();
+        set_operation_tracing(false);
+        // Other tests may query while tracing is on.
+        assert!(all_objects_calls() >= before + 2);
+
+        let path = temp_dir.path().join("trace.json");
+        graph.export_diagnostics(&path).unwrap();
+        let trace: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert!(trace["operations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|op| op["operation"] == "get_all_objects" && op["items"].as_u64() >= Some(2)));
+    }
+}
@@ -0,0 +1,246 @@
+//! Word-level text diffs for the revision history.
+//!
+//! [`diff_words`] compares two texts as runs of words and whitespace and
+//! returns [`DiffHunk`]s — equal, inserted or deleted text — in order, so
+//! concatenating the equal and deleted hunks gives the old text and the
+//! equal and inserted hunks the new one.  A UI renders them as a redline.
+//!
+//! [`KnowledgeGraph::diff_text`] diffs two revisions of a chunk (see
+//! [`KnowledgeGraph::get_chunk_history`]);
+//! [`KnowledgeGraph::diff_description`] diffs an object's description at
+//! two points in time, e.g. the start of two sessions.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ChunkId, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Largest number of token pairs compared cell by cell; beyond it the
+/// changed middle is reported as one deletion and one insertion.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// What a hunk does to the old text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of text with one [`DiffOp`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Split `text` into alternating runs of whitespace and non-whitespace.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != space) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Append `token` to `hunks`, merging with the last hunk when it has the
+/// same op.
+fn push(hunks: &mut Vec<DiffHunk>, op: DiffOp, token: &str) {
+    match hunks.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(token),
+        _ => hunks.push(DiffHunk {
+            op,
+            text: token.to_string(),
+        }),
+    }
+}
+
+/// Diff `old` against `new` word by word.
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffHunk> {
+    let a = tokenize(old);
+    let b = tokenize(new);
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut hunks = Vec::new();
+    for token in &a[..prefix] {
+        push(&mut hunks, DiffOp::Equal, token);
+    }
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_DIFF_CELLS {
+        for token in a_mid {
+            push(&mut hunks, DiffOp::Delete, token);
+        }
+        for token in b_mid {
+            push(&mut hunks, DiffOp::Insert, token);
+        }
+    } else {
+        // lcs[i][j]: longest common subsequence of a_mid[i..] and b_mid[j..].
+        let (n, m) = (a_mid.len(), b_mid.len());
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[at(i, j)] = if a_mid[i] == b_mid[j] {
+                    lcs[at(i + 1, j + 1)] + 1
+                } else {
+                    lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && a_mid[i] == b_mid[j] {
+                push(&mut hunks, DiffOp::Equal, a_mid[i]);
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[at(i, j + 1)] > lcs[at(i + 1, j)]) {
+                push(&mut hunks, DiffOp::Insert, b_mid[j]);
+                j += 1;
+            } else {
+                push(&mut hunks, DiffOp::Delete, a_mid[i]);
+                i += 1;
+            }
+        }
+    }
+    for token in &a[a.len() - suffix..] {
+        push(&mut hunks, DiffOp::Equal, token);
+    }
+    hunks
+}
+
+impl KnowledgeGraph {
+    /// Diff revision `rev_a` of `chunk_id` against revision `rev_b`.  Fails
+    /// with [`UForgeError::NotFound`] when either revision does not belong
+    /// to the chunk.
+    pub fn diff_text(&self, chunk_id: ChunkId, rev_a: i64, rev_b: i64) -> Result<Vec<DiffHunk>> {
+        let content = |revision: i64| -> Result<String> {
+            let found = self
+                .storage
+                .get_chunk_revision(revision)?
+                .filter(|r| r.chunk_id == chunk_id)
+                .ok_or_else(|| {
+                    UForgeError::NotFound(format!(
+                        "Revision {revision} does not belong to chunk {chunk_id}"
+                    ))
+                })?;
+            Ok(found.content)
+        };
+        Ok(diff_words(&content(rev_a)?, &content(rev_b)?))
+    }
+
+    /// Diff the `description` property of `id` as it was at `from` against
+    /// its value at `to`.  A description that did not exist yet (or an
+    /// object that did not) counts as empty.
+    pub fn diff_description(
+        &self,
+        id: ObjectId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<DiffHunk>> {
+        let description = |at: DateTime<Utc>| -> Result<String> {
+            Ok(self
+                .get_object_as_of(id, at)?
+                .and_then(|o| o.get_property("description"))
+                .unwrap_or_default())
+        };
+        Ok(diff_words(&description(from)?, &description(to)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{ChunkType, ObjectMetadata};
+
+    fn side(hunks: &[DiffHunk], skip: DiffOp) -> String {
+        hunks
+            .iter()
+            .filter(|h| h.op != skip)
+            .map(|h| h.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_words_reconstructs_both_sides() {
+        let old = "The cult meets at the old mill.";
+        let new = "The cult now meets at the  burned mill.";
+        let hunks = diff_words(old, new);
+        assert_eq!(side(&hunks, DiffOp::Insert), old);
+        assert_eq!(side(&hunks, DiffOp::Delete), new);
+        assert!(hunks
+            .iter()
+            .any(|h| h.op == DiffOp::Delete && h.text.contains("old")));
+        assert!(hunks
+            .iter()
+            .any(|h| h.op == DiffOp::Insert && h.text.contains("burned")));
+
+        assert!(diff_words("", "").is_empty());
+        assert_eq!(diff_words("same text", "same text").len(), 1);
+    }
+
+    #[test]
+    fn test_diff_text_between_revisions() {
+        let (graph, _temp_dir) = create_test_graph();
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Iarno".to_string()))
+            .unwrap();
+        let chunk = graph
+            .add_text_chunk(id, "A loyal wizard.".to_string(), ChunkType::UserNote)
+            .unwrap()[0];
+        graph
+            .update_text_chunk(chunk, "A treacherous wizard.", None)
+            .unwrap();
+        let history = graph.get_chunk_history(chunk).unwrap();
+
+        let hunks = graph
+            .diff_text(chunk, history[0].revision, history[1].revision)
+            .unwrap();
+        assert_eq!(
+            hunks,
+            vec![
+                DiffHunk {
+                    op: DiffOp::Equal,
+                    text: "A ".to_string()
+                },
+                DiffHunk {
+                    op: DiffOp::Delete,
+                    text: "loyal".to_string()
+                },
+                DiffHunk {
+                    op: DiffOp::Insert,
+                    text: "treacherous".to_string()
+                },
+                DiffHunk {
+                    op: DiffOp::Equal,
+                    text: " wizard.".to_string()
+                },
+            ]
+        );
+
+        let err = graph
+            .diff_text(ChunkId::new_v4(), history[0].revision, history[1].revision)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,267 @@
+//! Duplicate warnings when creating objects.
+//!
+//! Mid-session a GM types "Black Spider" without remembering that "The
+//! Black Spider" exists.  [`KnowledgeGraph::add_object_checked`] looks for
+//! objects of the same type that are probably the same thing before writing:
+//!
+//! - **Normalized** — equal after [`normalize_object_name`] (case,
+//!   punctuation, spacing and a leading article ignored).
+//! - **Fuzzy** — normalized names at least [`FUZZY_NAME_SIMILARITY`] alike
+//!   by edit distance, catching typos such as "Rockseker".
+//! - **Embedding** — with a name embedding supplied to
+//!   [`KnowledgeGraph::add_object_checked_with`], profile embeddings within
+//!   [`EMBEDDING_DUPLICATE_DISTANCE`] cosine distance.
+//!
+//! Finding candidates is not an error: unless `force` is set the object is
+//! held back and the [`DuplicateWarning`] returned, so the create flow can
+//! offer "open existing" or "create anyway" (the same call with `force`).
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::search::edit_distance;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Minimum `1 - distance / length` of two normalized names for a fuzzy match.
+pub const FUZZY_NAME_SIMILARITY: f32 = 0.85;
+
+/// Maximum profile-embedding cosine distance for an embedding match.
+pub const EMBEDDING_DUPLICATE_DISTANCE: f32 = 0.1;
+
+/// Profile-embedding neighbours considered per check.
+const EMBEDDING_CANDIDATES: usize = 10;
+
+const ARTICLES: [&str; 3] = ["the", "a", "an"];
+
+/// `"The  Black-Spider!"` → `"black spider"`: lowercase, punctuation as
+/// spaces, whitespace collapsed, a leading article dropped.
+pub fn normalize_object_name(name: &str) -> String {
+    let lowered: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let mut words: Vec<&str> = lowered.split_whitespace().collect();
+    if words.len() > 1 && ARTICLES.contains(&words[0]) {
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+/// Similarity of two normalized names in `0..=1` by edit distance.
+fn name_similarity(a: &str, b: &str) -> f32 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(a, b) as f32 / longest as f32
+}
+
+/// Why an existing object looks like a duplicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateMatch {
+    Normalized,
+    Fuzzy,
+    Embedding,
+}
+
+/// An existing object the new one may duplicate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateCandidate {
+    pub object_id: ObjectId,
+    pub name: String,
+    pub matched: DuplicateMatch,
+    /// `1.0` for a normalized match, otherwise the name or embedding
+    /// similarity.
+    pub score: f32,
+}
+
+/// Objects of the same type that look like the one being created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateWarning {
+    pub name: String,
+    pub object_type: String,
+    /// Best match first.
+    pub candidates: Vec<DuplicateCandidate>,
+}
+
+/// Result of [`KnowledgeGraph::add_object_checked`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckedAdd {
+    /// The new object, or `None` when it was held back as a likely
+    /// duplicate.
+    pub id: Option<ObjectId>,
+    pub warning: Option<DuplicateWarning>,
+}
+
+impl KnowledgeGraph {
+    /// Existing objects of `metadata`'s type that `metadata` probably
+    /// duplicates, best first; see the module docs.  `name_embedding`, when
+    /// given, is compared against profile embeddings.  Blank names have no
+    /// duplicates.
+    pub fn find_duplicate_candidates(
+        &self,
+        metadata: &ObjectMetadata,
+        name_embedding: Option<&[f32]>,
+    ) -> Result<Vec<DuplicateCandidate>> {
+        let normalized = normalize_object_name(&metadata.name);
+        if normalized.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut found: HashMap<ObjectId, DuplicateCandidate> = HashMap::new();
+        for (id, name) in self.storage.list_node_names_of_type(&metadata.object_type)? {
+            if id == metadata.id {
+                continue;
+            }
+            let other = normalize_object_name(&name);
+            let (matched, score) = if other == normalized {
+                (DuplicateMatch::Normalized, 1.0)
+            } else {
+                let score = name_similarity(&normalized, &other);
+                if score < FUZZY_NAME_SIMILARITY {
+                    continue;
+                }
+                (DuplicateMatch::Fuzzy, score)
+            };
+            found.insert(
+                id,
+                DuplicateCandidate {
+                    object_id: id,
+                    name,
+                    matched,
+                    score,
+                },
+            );
+        }
+
+        if let Some(embedding) = name_embedding {
+            for (id, _, distance) in self.search_profiles_semantic(embedding, EMBEDDING_CANDIDATES)? {
+                if distance > EMBEDDING_DUPLICATE_DISTANCE
+                    || id == metadata.id
+                    || found.contains_key(&id)
+                {
+                    continue;
+                }
+                let Some(object) = self.get_object(id)? else {
+                    continue;
+                };
+                if object.object_type != metadata.object_type {
+                    continue;
+                }
+                found.insert(
+                    id,
+                    DuplicateCandidate {
+                        object_id: id,
+                        name: object.name,
+                        matched: DuplicateMatch::Embedding,
+                        score: 1.0 - distance,
+                    },
+                );
+            }
+        }
+
+        let mut candidates: Vec<DuplicateCandidate> = found.into_values().collect();
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(candidates)
+    }
+
+    /// [`add_object`](Self::add_object) with a duplicate check by name.
+    /// Likely duplicates hold the object back unless `force` is set; the
+    /// warning is returned either way.
+    pub fn add_object_checked(&self, metadata: ObjectMetadata, force: bool) -> Result<CheckedAdd> {
+        self.add_object_checked_with(metadata, None, force)
+    }
+
+    /// [`add_object_checked`](Self::add_object_checked), also comparing
+    /// `name_embedding` against profile embeddings.
+    pub fn add_object_checked_with(
+        &self,
+        metadata: ObjectMetadata,
+        name_embedding: Option<&[f32]>,
+        force: bool,
+    ) -> Result<CheckedAdd> {
+        let candidates = self.find_duplicate_candidates(&metadata, name_embedding)?;
+        let warning = (!candidates.is_empty()).then(|| DuplicateWarning {
+            name: metadata.name.clone(),
+            object_type: metadata.object_type.clone(),
+            candidates,
+        });
+        let id = if warning.is_none() || force {
+            Some(self.add_object(metadata)?)
+        } else {
+            None
+        };
+        Ok(CheckedAdd { id, warning })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EMBEDDING_DIMENSIONS;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_add_object_checked_warns_on_duplicates() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |ty: &str, name: &str| {
+            graph
+                .add_object(ObjectMetadata::new(ty.to_string(), name.to_string()))
+                .unwrap()
+        };
+        let spider = add("character", "The Black Spider");
+        let gundren = add("character", "Gundren Rockseeker");
+        add("location", "Black Spider");
+        let nezznar = add("character", "Nezznar");
+
+        let check = |name: &str, force: bool| {
+            let object = ObjectMetadata::new("character".to_string(), name.to_string());
+            graph.add_object_checked(object, force).unwrap()
+        };
+        let held = check("black spider!", false);
+        assert_eq!(held.id, None);
+        let candidates = held.warning.unwrap().candidates;
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            (candidates[0].object_id, candidates[0].matched),
+            (spider, DuplicateMatch::Normalized)
+        );
+
+        let typo = check("Gundren Rocksekeer", false).warning.unwrap();
+        assert_eq!(
+            (typo.candidates[0].object_id, typo.candidates[0].matched),
+            (gundren, DuplicateMatch::Fuzzy)
+        );
+
+        let forced = check("Black Spider", true);
+        assert!(forced.id.is_some() && forced.warning.is_some());
+        let fresh = check("Sildar Hallwinter", false);
+        assert!(fresh.id.is_some() && fresh.warning.is_none());
+
+        // An embedding close to Nezznar's profile flags a different name.
+        let mut axis = vec![0.0; EMBEDDING_DIMENSIONS];
+        axis[0] = 1.0;
+        graph.upsert_profile_embedding(nezznar, &axis).unwrap();
+        let by_meaning = graph
+            .add_object_checked_with(
+                ObjectMetadata::new("character".to_string(), "Drow Mage".to_string()),
+                Some(&axis),
+                false,
+            )
+            .unwrap();
+        assert_eq!(by_meaning.id, None);
+        assert_eq!(
+            by_meaning.warning.unwrap().candidates[0].matched,
+            DuplicateMatch::Embedding
+        );
+    }
+}
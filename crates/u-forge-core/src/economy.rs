@@ -0,0 +1,276 @@
+//! Treasuries, currency and the transaction log.
+//!
+//! Amounts are whole numbers of the project currency's smallest
+//! denomination, so splitting 1 gp among three players is exact: 33 cp each
+//! and 1 cp left over.  The project currency is a list of
+//! [`Denomination`]s stored in the `currency` project setting (D&D coinage
+//! until set); [`KnowledgeGraph::parse_amount`] and
+//! [`KnowledgeGraph::format_amount`] convert to and from text like
+//! `"5 gp 3 sp"` with the same rules as `Currency`-typed properties.
+//!
+//! Any object can hold money: its balance is the integer
+//! [`TREASURY_BALANCE_KEY`] property.  [`KnowledgeGraph::create_treasury`]
+//! makes a dedicated `treasury` object owned by a party, NPC or faction.
+//! Every credit, debit, transfer and loot split is posted through the
+//! `ledger` table, which updates the balance and logs the entry in one
+//! transaction — balances are never edited directly.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{Result, UForgeError};
+use crate::schema::{format_currency, parse_currency, Denomination};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the currency's denominations as JSON.
+pub const CURRENCY_SETTING: &str = "currency";
+
+/// Property holding an object's balance in the smallest denomination.
+pub const TREASURY_BALANCE_KEY: &str = "treasury";
+
+/// Object type of dedicated treasuries.
+pub const TREASURY_TYPE: &str = "treasury";
+
+/// Edge type from a treasury to the party, NPC or faction that owns it.
+pub const TREASURY_OWNER_EDGE: &str = "owned_by";
+
+/// One credit or debit of one treasury.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub treasury_id: ObjectId,
+    /// Positive for credits, negative for debits.
+    pub amount: i64,
+    /// Balance after this entry.
+    pub balance: i64,
+    pub memo: String,
+    /// Shared by both sides of a transfer and every share of a loot split.
+    pub transfer_id: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Platinum, gold, electrum, silver and copper pieces.
+fn default_currency() -> Vec<Denomination> {
+    vec![
+        Denomination::new("pp", 1000),
+        Denomination::new("gp", 100),
+        Denomination::new("ep", 50),
+        Denomination::new("sp", 10),
+        Denomination::new("cp", 1),
+    ]
+}
+
+fn check_positive(amount: i64) -> Result<()> {
+    if amount <= 0 {
+        return Err(UForgeError::ValidationFailed(format!(
+            "Amount must be positive, got {amount}"
+        )));
+    }
+    Ok(())
+}
+
+impl KnowledgeGraph {
+    /// The project currency (D&D coinage until set).
+    pub fn currency(&self) -> Result<Vec<Denomination>> {
+        match self.storage.get_setting(CURRENCY_SETTING)? {
+            Some(json) => Ok(serde_json::from_str(&json).context("Failed to parse currency")?),
+            None => Ok(default_currency()),
+        }
+    }
+
+    /// Replace the project currency.  Needs at least one denomination, each
+    /// worth at least one unit.  Stored balances are not converted.
+    pub fn set_currency(&self, denominations: &[Denomination]) -> Result<()> {
+        if denominations.is_empty() || denominations.iter().any(|d| d.value == 0) {
+            return Err(UForgeError::ValidationFailed(
+                "A currency needs denominations worth at least one unit".to_string(),
+            ));
+        }
+        let json = serde_json::to_string(denominations).context("Failed to serialize currency")?;
+        self.storage.set_setting(CURRENCY_SETTING, &json)
+    }
+
+    /// Parse `"5 gp 3 sp"` (or a bare number of the smallest unit) in the
+    /// project currency.
+    pub fn parse_amount(&self, text: &str) -> Result<i64> {
+        parse_currency(text, &self.currency()?)
+            .ok_or_else(|| UForgeError::ValidationFailed(format!("Invalid amount '{text}'")))
+    }
+
+    /// `"5 gp 3 sp"` for 530 units in D&D coinage.
+    pub fn format_amount(&self, units: i64) -> Result<String> {
+        Ok(format_currency(units, &self.currency()?))
+    }
+
+    /// Create an empty `treasury` object named `name`, owned by `owner`
+    /// when given.
+    pub fn create_treasury(&self, name: &str, owner: Option<ObjectId>) -> Result<ObjectId> {
+        let mut metadata = ObjectMetadata::new(TREASURY_TYPE.to_string(), name.to_string());
+        if let Some(props) = metadata.properties.as_object_mut() {
+            props.insert(TREASURY_BALANCE_KEY.to_string(), 0.into());
+        }
+        let id = self.add_object(metadata)?;
+        if let Some(owner) = owner {
+            self.connect_objects_str(id, owner, TREASURY_OWNER_EDGE)?;
+        }
+        Ok(id)
+    }
+
+    /// Balance of `treasury_id` in the smallest denomination; 0 for objects
+    /// that never held money.
+    pub fn balance(&self, treasury_id: ObjectId) -> Result<i64> {
+        let object = self
+            .get_object(treasury_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown treasury {treasury_id}")))?;
+        Ok(object
+            .get_json_property(TREASURY_BALANCE_KEY)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0))
+    }
+
+    /// Add `amount` to `treasury_id`.
+    pub fn credit(&self, treasury_id: ObjectId, amount: i64, memo: &str) -> Result<LedgerEntry> {
+        check_positive(amount)?;
+        let mut entries =
+            self.storage
+                .post_ledger_entries(&[(treasury_id, amount)], memo, None, false)?;
+        Ok(entries.remove(0))
+    }
+
+    /// Take `amount` from `treasury_id`.  Fails with
+    /// [`UForgeError::ValidationFailed`] when it holds less, unless
+    /// `allow_overdraft` (for debts and faction deficits).
+    pub fn debit(
+        &self,
+        treasury_id: ObjectId,
+        amount: i64,
+        memo: &str,
+        allow_overdraft: bool,
+    ) -> Result<LedgerEntry> {
+        check_positive(amount)?;
+        let mut entries = self.storage.post_ledger_entries(
+            &[(treasury_id, -amount)],
+            memo,
+            None,
+            allow_overdraft,
+        )?;
+        Ok(entries.remove(0))
+    }
+
+    /// Move `amount` from `from` to `to` atomically.  Returns the debit and
+    /// the credit entries.
+    pub fn transfer(
+        &self,
+        from: ObjectId,
+        to: ObjectId,
+        amount: i64,
+        memo: &str,
+    ) -> Result<(LedgerEntry, LedgerEntry)> {
+        check_positive(amount)?;
+        let mut entries = self.storage.post_ledger_entries(
+            &[(from, -amount), (to, amount)],
+            memo,
+            Some(Uuid::new_v4()),
+            false,
+        )?;
+        let credit = entries.remove(1);
+        Ok((entries.remove(0), credit))
+    }
+
+    /// Share everything in `pool` equally among `recipients`, atomically.
+    /// The remainder that does not divide evenly stays in the pool.
+    /// Returns the pool's debit followed by one credit per recipient; empty
+    /// when there is nothing to hand out.
+    pub fn split_loot(
+        &self,
+        pool: ObjectId,
+        recipients: &[ObjectId],
+        memo: &str,
+    ) -> Result<Vec<LedgerEntry>> {
+        if recipients.is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "Loot needs at least one recipient".into(),
+            ));
+        }
+        let share = self.balance(pool)? / recipients.len() as i64;
+        if share <= 0 {
+            return Ok(Vec::new());
+        }
+        let mut postings = vec![(pool, -share * recipients.len() as i64)];
+        postings.extend(recipients.iter().map(|r| (*r, share)));
+        self.storage
+            .post_ledger_entries(&postings, memo, Some(Uuid::new_v4()), false)
+    }
+
+    /// The transaction log of `treasury_id`, newest first.
+    pub fn ledger(&self, treasury_id: ObjectId) -> Result<Vec<LedgerEntry>> {
+        self.storage.list_ledger_entries(treasury_id)
+    }
+
+    /// Sum of the balances of `treasury_ids`, e.g. a faction's holdings
+    /// across its treasuries.
+    pub fn total_balance(&self, treasury_ids: &[ObjectId]) -> Result<i64> {
+        let mut total = 0i64;
+        for id in treasury_ids {
+            total = total.saturating_add(self.balance(*id)?);
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_treasury_credit_debit_and_split() {
+        let (graph, _temp_dir) = create_test_graph();
+        let party = graph
+            .add_object(ObjectMetadata::new(
+                "party".to_string(),
+                "The Party".to_string(),
+            ))
+            .unwrap();
+        let pool = graph.create_treasury("Party loot", Some(party)).unwrap();
+        let pcs: Vec<ObjectId> = ["Ayla", "Brom", "Cyr"]
+            .iter()
+            .map(|name| {
+                graph
+                    .add_object(ObjectMetadata::new("pc".to_string(), name.to_string()))
+                    .unwrap()
+            })
+            .collect();
+
+        let hoard = graph.parse_amount("1 gp").unwrap();
+        assert_eq!(hoard, 100);
+        graph.credit(pool, hoard, "Goblin hoard").unwrap();
+
+        let entries = graph.split_loot(pool, &pcs, "Split the hoard").unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].amount, -99);
+        assert_eq!(graph.balance(pool).unwrap(), 1);
+        assert_eq!(graph.balance(pcs[0]).unwrap(), 33);
+        assert_eq!(graph.format_amount(33).unwrap(), "3 sp 3 cp");
+        assert_eq!(graph.total_balance(&pcs).unwrap(), 99);
+
+        let err = graph.debit(pcs[0], 50, "Rope", false).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+        assert_eq!(graph.balance(pcs[0]).unwrap(), 33);
+        graph.debit(pcs[0], 50, "Rope on credit", true).unwrap();
+        assert_eq!(graph.balance(pcs[0]).unwrap(), -17);
+
+        let (debit, credit) = graph.transfer(pcs[1], pcs[0], 17, "Settle up").unwrap();
+        assert_eq!(debit.transfer_id, credit.transfer_id);
+        assert_eq!(graph.balance(pcs[0]).unwrap(), 0);
+
+        let log = graph.ledger(pcs[0]).unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].memo, "Settle up");
+        assert_eq!(log[2].balance, 33);
+    }
+}
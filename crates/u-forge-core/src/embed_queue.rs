@@ -0,0 +1,167 @@
+//! Persisted embedding queue.
+//!
+//! A bulk sweep ([`embed_all_chunks`](crate::ingest::embed_all_chunks)) can
+//! take minutes after a large import.  Before it dispatches anything it
+//! writes its work list to the `embed_queue` table — one row per chunk and
+//! target index, with the hash of the content being embedded — and deletes
+//! each row once that chunk's vector is stored.  Rows left behind by a crash
+//! or a failed batch are the unfinished work: the next sweep (run at startup)
+//! embeds them first.
+//!
+//! The content hash keeps a resumed row honest.  If the chunk was rewritten
+//! or already embedded since it was queued, the row is dropped and the chunk,
+//! if still unembedded, is queued afresh with its current hash.  Rows for
+//! deleted chunks go with the chunk.
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::ingest::EmbeddingTarget;
+use crate::text::content_hash;
+use crate::types::{ChunkId, TextChunk};
+use crate::KnowledgeGraph;
+
+/// One chunk waiting for a vector in one index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedEmbedding {
+    pub chunk_id: ChunkId,
+    pub target: EmbeddingTarget,
+    /// Hash of the chunk content when it was queued.
+    pub content_hash: String,
+}
+
+impl KnowledgeGraph {
+    /// Queue `chunks` for the `target` index.
+    pub fn queue_embeddings(&self, target: EmbeddingTarget, chunks: &[TextChunk]) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let rows: Vec<(ChunkId, String)> = chunks
+            .iter()
+            .map(|c| (c.id, content_hash(&c.content)))
+            .collect();
+        self.storage.enqueue_embeddings(target.as_str(), &rows)
+    }
+
+    /// Chunks still queued for the `target` index, oldest first.
+    pub fn queued_embeddings(&self, target: EmbeddingTarget) -> Result<Vec<QueuedEmbedding>> {
+        Ok(self
+            .storage
+            .queued_embeddings(target.as_str())?
+            .into_iter()
+            .map(|(chunk_id, content_hash)| QueuedEmbedding {
+                chunk_id,
+                target,
+                content_hash,
+            })
+            .collect())
+    }
+
+    /// Remove `chunk_ids` from the `target` queue once their vectors are
+    /// stored.
+    pub fn dequeue_embeddings(&self, target: EmbeddingTarget, chunk_ids: &[ChunkId]) -> Result<()> {
+        if chunk_ids.is_empty() {
+            return Ok(());
+        }
+        self.storage
+            .dequeue_embeddings(target.as_str(), chunk_ids)
+            .map(|_| ())
+    }
+
+    /// Reconcile the `target` queue with `candidates`, the chunks that
+    /// currently lack a vector, and queue them all.  Returns the candidates
+    /// with the ones resumed from an earlier run first, in their queued
+    /// order, and how many were resumed.  Queued rows whose chunk is no
+    /// longer a candidate or whose content changed are dropped.
+    pub fn resume_embedding_queue(
+        &self,
+        target: EmbeddingTarget,
+        candidates: Vec<TextChunk>,
+    ) -> Result<(Vec<TextChunk>, usize)> {
+        let queued = self.queued_embeddings(target)?;
+        let mut pending: HashMap<ChunkId, TextChunk> =
+            candidates.into_iter().map(|c| (c.id, c)).collect();
+        let mut ordered = Vec::with_capacity(pending.len());
+        let mut stale = Vec::new();
+        for row in queued {
+            match pending.get(&row.chunk_id) {
+                Some(chunk) if content_hash(&chunk.content) == row.content_hash => {
+                    ordered.push(pending.remove(&row.chunk_id).expect("checked above"));
+                }
+                _ => stale.push(row.chunk_id),
+            }
+        }
+        let resumed = ordered.len();
+        self.dequeue_embeddings(target, &stale)?;
+
+        let mut fresh: Vec<TextChunk> = pending.into_values().collect();
+        fresh.sort_by_key(|c| c.created_at);
+        ordered.extend(fresh);
+        self.queue_embeddings(target, &ordered)?;
+        Ok((ordered, resumed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChunkType, ObjectMetadata};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_embed_queue_survives_reopen_and_drops_stale_rows() {
+        let temp_dir = TempDir::new().unwrap();
+        let (first, second) = {
+            let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
+            let id = graph
+                .add_object(ObjectMetadata::new("npc".to_string(), "Sildar".to_string()))
+                .unwrap();
+            let first = graph
+                .add_text_chunk(id, "A knight of Neverwinter.".to_string(), ChunkType::Imported)
+                .unwrap()[0];
+            let second = graph
+                .add_text_chunk(id, "Captured by goblins.".to_string(), ChunkType::Imported)
+                .unwrap()[0];
+            let chunks = graph.get_unembedded_chunks().unwrap();
+            graph
+                .queue_embeddings(EmbeddingTarget::Standard, &chunks)
+                .unwrap();
+            // The first vector landed before the "crash".
+            graph
+                .dequeue_embeddings(EmbeddingTarget::Standard, &[first])
+                .unwrap();
+            (first, second)
+        };
+
+        let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
+        let queued = graph.queued_embeddings(EmbeddingTarget::Standard).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].chunk_id, second);
+        assert!(graph
+            .queued_embeddings(EmbeddingTarget::HighQuality)
+            .unwrap()
+            .is_empty());
+
+        // The leftover row is resumed ahead of the rest.
+        let (ordered, resumed) = graph
+            .resume_embedding_queue(
+                EmbeddingTarget::Standard,
+                graph.get_unembedded_chunks().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(resumed, 1);
+        assert_eq!(ordered[0].id, second);
+        assert_eq!(ordered[1].id, first);
+
+        // A row whose chunk is no longer waiting is dropped.
+        let (ordered, resumed) = graph
+            .resume_embedding_queue(EmbeddingTarget::Standard, Vec::new())
+            .unwrap();
+        assert!(ordered.is_empty());
+        assert_eq!(resumed, 0);
+        assert!(graph
+            .queued_embeddings(EmbeddingTarget::Standard)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Per-project choice of whether, and when, to use embedding models.
+//!
+//! Embedding models are large downloads and keep a server busy.  A project
+//! on a metered or offline machine can run without them: keyword search,
+//! the graph, and the editor work the same, only semantic search and
+//! similarity features are unavailable.  The mode is a project setting, so
+//! it travels with the database:
+//!
+//! - [`EmbeddingMode::Eager`] (default) loads the embedding models when the
+//!   app connects to Lemonade and embeds everything pending at once.
+//! - [`EmbeddingMode::Lazy`] connects without them and loads them on the
+//!   first semantic or hybrid search.
+//! - [`EmbeddingMode::Off`] never loads them.  Saved objects are still
+//!   re-chunked so keyword search stays current, but nothing is embedded.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::{Result, UForgeError};
+use crate::KnowledgeGraph;
+
+/// Project setting key holding the [`EmbeddingMode`].
+pub const EMBEDDING_MODE_SETTING: &str = "embeddings";
+
+/// When a project loads embedding models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingMode {
+    Off,
+    Lazy,
+    #[default]
+    Eager,
+}
+
+impl EmbeddingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingMode::Off => "off",
+            EmbeddingMode::Lazy => "lazy",
+            EmbeddingMode::Eager => "eager",
+        }
+    }
+
+    /// Whether chunks and profiles may be embedded at all.
+    pub fn allows_embeddings(&self) -> bool {
+        *self != EmbeddingMode::Off
+    }
+
+    /// Whether embedding models are loaded when the app connects, rather
+    /// than on first use or never.
+    pub fn loads_at_startup(&self) -> bool {
+        *self == EmbeddingMode::Eager
+    }
+}
+
+impl fmt::Display for EmbeddingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EmbeddingMode {
+    type Err = UForgeError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(EmbeddingMode::Off),
+            "lazy" => Ok(EmbeddingMode::Lazy),
+            "eager" => Ok(EmbeddingMode::Eager),
+            other => Err(UForgeError::ValidationFailed(format!(
+                "Unknown embedding mode '{other}' (expected off, lazy, or eager)"
+            ))),
+        }
+    }
+}
+
+impl KnowledgeGraph {
+    /// The project's [`EmbeddingMode`].  An unreadable stored value falls
+    /// back to the default with a warning.
+    pub fn embedding_mode(&self) -> Result<EmbeddingMode> {
+        Ok(match self.storage.get_setting(EMBEDDING_MODE_SETTING)? {
+            None => EmbeddingMode::default(),
+            Some(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{e}; using the default");
+                EmbeddingMode::default()
+            }),
+        })
+    }
+
+    /// Set the project's [`EmbeddingMode`].  Existing vectors are kept when
+    /// switching to `Off`; they are simply no longer refreshed.
+    pub fn set_embedding_mode(&self, mode: EmbeddingMode) -> Result<()> {
+        self.storage
+            .set_setting(EMBEDDING_MODE_SETTING, mode.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_embedding_mode_setting() {
+        let (graph, _temp_dir) = create_test_graph();
+        assert_eq!(graph.embedding_mode().unwrap(), EmbeddingMode::Eager);
+
+        graph.set_embedding_mode(EmbeddingMode::Off).unwrap();
+        assert_eq!(graph.embedding_mode().unwrap(), EmbeddingMode::Off);
+        assert!(!EmbeddingMode::Off.allows_embeddings());
+        assert!(!EmbeddingMode::Lazy.loads_at_startup());
+
+        assert_eq!(
+            " Lazy ".parse::<EmbeddingMode>().unwrap(),
+            EmbeddingMode::Lazy
+        );
+        let err = "sometimes".parse::<EmbeddingMode>().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        graph
+            .storage
+            .set_setting(EMBEDDING_MODE_SETTING, "garbage")
+            .unwrap();
+        assert_eq!(graph.embedding_mode().unwrap(), EmbeddingMode::Eager);
+    }
+
+    #[tokio::test]
+    async fn test_rechunk_without_embeddings_keeps_fts_current() {
+        use crate::queue::InferenceQueueBuilder;
+        use crate::types::ObjectMetadata;
+
+        let (graph, _temp_dir) = create_test_graph();
+        graph.set_embedding_mode(EmbeddingMode::Off).unwrap();
+        let id = graph
+            .add_object(
+                ObjectMetadata::new("npc".to_string(), "Sister Garaele".to_string())
+                    .with_property("description".to_string(), "An elf cleric".to_string()),
+            )
+            .unwrap();
+
+        // No providers at all: nothing is embedded, but the node is chunked.
+        let queue = InferenceQueueBuilder::new().build();
+        let chunks = crate::ingest::rechunk_and_embed(&graph, &queue, None, id)
+            .await
+            .unwrap();
+        assert!(chunks > 0);
+        assert_eq!(graph.search_chunks_fts("cleric", 5).unwrap()[0].1, id);
+        assert_eq!(graph.get_stats().unwrap().embedded_count, 0);
+    }
+}
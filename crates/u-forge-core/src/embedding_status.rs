@@ -0,0 +1,329 @@
+//! Per-chunk embedding status and index coverage.
+//!
+//! Every time the embedding pipeline stores a chunk vector it records which
+//! model produced it, when, and the hash of the content it embedded (the
+//! `chunk_embeddings` table).  From that, each chunk is in one of three
+//! states per index ([`EmbeddingTarget`]):
+//!
+//! * [`ChunkEmbeddingStatus::NotEmbedded`] — no vector; invisible to
+//!   semantic search.
+//! * [`ChunkEmbeddingStatus::Embedded`] — a vector of the current content,
+//!   made by the index's current model.
+//! * [`ChunkEmbeddingStatus::Stale`] — a vector exists but the chunk's
+//!   content changed since, or it came from another model than the index's
+//!   current one (the model of the most recent vector written).
+//!
+//! Vectors stored before provenance was tracked count as embedded with an
+//! unknown model.  [`KnowledgeGraph::get_embedding_coverage`] sums the states
+//! ("93% of your world is searchable");
+//! [`KnowledgeGraph::clear_stale_embeddings`] drops stale vectors so the next
+//! [`embed_all_chunks`](crate::ingest::embed_all_chunks) sweep refills them.
+//!
+//! Vectors from an [`EmbeddingChain`](crate::ai::fallback::EmbeddingChain)
+//! carry the model that actually answered;
+//! [`KnowledgeGraph::store_tagged_chunk_embedding`] records it and
+//! [`KnowledgeGraph::search_chunks_semantic_tagged`] keeps other models'
+//! vectors out of a query's results.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::ai::fallback::TaggedEmbedding;
+use crate::error::{Result, UForgeError};
+use crate::graph::ChunkEmbeddingRecord;
+use crate::ingest::EmbeddingTarget;
+use crate::text::content_hash;
+use crate::types::{ChunkId, ObjectId, TextChunk};
+use crate::KnowledgeGraph;
+
+/// Where a chunk stands in one vector index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ChunkEmbeddingStatus {
+    NotEmbedded,
+    /// `model` and `embedded_at` are `None` for vectors stored before
+    /// provenance was tracked.
+    Embedded {
+        model: Option<String>,
+        embedded_at: Option<DateTime<Utc>>,
+    },
+    /// The vector no longer matches the content or the current model.
+    Stale {
+        model: String,
+        embedded_at: DateTime<Utc>,
+    },
+}
+
+/// Chunk counts per state for one index.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmbeddingCoverage {
+    pub target: &'static str,
+    /// Model of the most recent vector written, if any.
+    pub current_model: Option<String>,
+    pub total: usize,
+    pub embedded: usize,
+    pub stale: usize,
+    pub not_embedded: usize,
+}
+
+impl EmbeddingCoverage {
+    /// Share of chunks with an up-to-date vector, in `0.0..=100.0`.  An empty
+    /// project counts as fully covered.
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.embedded as f32 / self.total as f32 * 100.0
+        }
+    }
+}
+
+impl KnowledgeGraph {
+    /// Record that `model` just produced the `target` vectors of `chunks`.
+    /// Call after storing them.
+    pub fn record_chunk_embeddings(
+        &self,
+        target: EmbeddingTarget,
+        model: &str,
+        chunks: &[TextChunk],
+    ) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let rows: Vec<(ChunkId, String)> = chunks
+            .iter()
+            .map(|c| (c.id, content_hash(&c.content)))
+            .collect();
+        self.storage
+            .record_chunk_embeddings(target.as_str(), model, &rows)
+    }
+
+    /// `chunk_id`'s state in the `target` index.  Fails with
+    /// [`UForgeError::NotFound`] for an unknown chunk.
+    pub fn chunk_embedding_status(
+        &self,
+        target: EmbeddingTarget,
+        chunk_id: ChunkId,
+    ) -> Result<ChunkEmbeddingStatus> {
+        let current = self.current_embedding_model(target)?;
+        let record = self
+            .storage
+            .chunk_embedding_records(target.as_str(), Some(chunk_id))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown chunk {chunk_id}")))?;
+        Ok(status_of(&record, current.as_deref()))
+    }
+
+    /// Coverage of the standard index.
+    pub fn get_embedding_coverage(&self) -> Result<EmbeddingCoverage> {
+        self.embedding_coverage(EmbeddingTarget::Standard)
+    }
+
+    /// Coverage of the `target` index.
+    pub fn embedding_coverage(&self, target: EmbeddingTarget) -> Result<EmbeddingCoverage> {
+        let current_model = self.current_embedding_model(target)?;
+        let mut coverage = EmbeddingCoverage {
+            target: target.as_str(),
+            current_model,
+            total: 0,
+            embedded: 0,
+            stale: 0,
+            not_embedded: 0,
+        };
+        for record in self
+            .storage
+            .chunk_embedding_records(target.as_str(), None)?
+        {
+            coverage.total += 1;
+            match status_of(&record, coverage.current_model.as_deref()) {
+                ChunkEmbeddingStatus::NotEmbedded => coverage.not_embedded += 1,
+                ChunkEmbeddingStatus::Embedded { .. } => coverage.embedded += 1,
+                ChunkEmbeddingStatus::Stale { .. } => coverage.stale += 1,
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Drop every stale `target` vector so the next sweep re-embeds those
+    /// chunks.  Returns how many were dropped.
+    pub fn clear_stale_embeddings(&self, target: EmbeddingTarget) -> Result<usize> {
+        let current = self.current_embedding_model(target)?;
+        let stale: Vec<ChunkId> = self
+            .storage
+            .chunk_embedding_records(target.as_str(), None)?
+            .iter()
+            .filter(|r| {
+                matches!(
+                    status_of(r, current.as_deref()),
+                    ChunkEmbeddingStatus::Stale { .. }
+                )
+            })
+            .map(|r| r.chunk_id)
+            .collect();
+        if stale.is_empty() {
+            return Ok(0);
+        }
+        self.storage.delete_chunk_vectors(target.as_str(), &stale)
+    }
+
+    /// Store `embedding` as `chunk`'s `target` vector, tagged with the model
+    /// that produced it.
+    pub fn store_tagged_chunk_embedding(
+        &self,
+        target: EmbeddingTarget,
+        chunk: &TextChunk,
+        embedding: &TaggedEmbedding,
+    ) -> Result<()> {
+        match target {
+            EmbeddingTarget::Standard => self.upsert_chunk_embedding(chunk.id, &embedding.vector)?,
+            EmbeddingTarget::HighQuality => {
+                self.upsert_chunk_embedding_hq(chunk.id, &embedding.vector)?
+            }
+        }
+        self.record_chunk_embeddings(target, &embedding.model, std::slice::from_ref(chunk))
+    }
+
+    /// Nearest `target` chunks to `query`, leaving out chunks whose vector
+    /// was produced by a different model than the query's — their distances
+    /// are meaningless.  Untagged vectors are kept.
+    pub fn search_chunks_semantic_tagged(
+        &self,
+        target: EmbeddingTarget,
+        query: &TaggedEmbedding,
+        limit: usize,
+    ) -> Result<Vec<(ChunkId, ObjectId, String, f32)>> {
+        // Over-fetch so excluded chunks do not starve the result.
+        let candidates = limit.saturating_mul(3).max(limit);
+        let hits = match target {
+            EmbeddingTarget::Standard => self.search_chunks_semantic(&query.vector, candidates)?,
+            EmbeddingTarget::HighQuality => {
+                self.search_chunks_semantic_hq(&query.vector, candidates)?
+            }
+        };
+        let mut kept = Vec::with_capacity(limit);
+        for hit in hits {
+            let model = self
+                .storage
+                .chunk_embedding_records(target.as_str(), Some(hit.0))?
+                .into_iter()
+                .next()
+                .and_then(|r| r.provenance)
+                .map(|(model, _, _)| model);
+            if model.is_none_or(|m| m == query.model) {
+                kept.push(hit);
+                if kept.len() == limit {
+                    break;
+                }
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Model of the most recent `target` vector with provenance.
+    fn current_embedding_model(&self, target: EmbeddingTarget) -> Result<Option<String>> {
+        Ok(self
+            .storage
+            .chunk_embedding_records(target.as_str(), None)?
+            .into_iter()
+            .filter_map(|r| r.provenance)
+            .max_by(|a, b| a.2.cmp(&b.2))
+            .map(|(model, _, _)| model))
+    }
+}
+
+fn status_of(record: &ChunkEmbeddingRecord, current_model: Option<&str>) -> ChunkEmbeddingStatus {
+    if !record.has_vector {
+        return ChunkEmbeddingStatus::NotEmbedded;
+    }
+    let Some((model, hash, at)) = &record.provenance else {
+        return ChunkEmbeddingStatus::Embedded {
+            model: None,
+            embedded_at: None,
+        };
+    };
+    let embedded_at = DateTime::parse_from_rfc3339(at)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_default();
+    if *hash != content_hash(&record.content) || current_model.is_some_and(|m| m != model.as_str()) {
+        return ChunkEmbeddingStatus::Stale {
+            model: model.clone(),
+            embedded_at,
+        };
+    }
+    ChunkEmbeddingStatus::Embedded {
+        model: Some(model.clone()),
+        embedded_at: Some(embedded_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{ChunkType, ObjectMetadata};
+    use crate::EMBEDDING_DIMENSIONS;
+
+    #[test]
+    fn test_embedding_coverage_tracks_model_and_staleness() {
+        let (graph, _temp_dir) = create_test_graph();
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Toblen".to_string()))
+            .unwrap();
+        for text in ["Innkeeper of the Stonehill.", "Married to Trilena.", "Has a son."] {
+            graph
+                .add_text_chunk(id, text.to_string(), ChunkType::Imported)
+                .unwrap();
+        }
+        let chunks = graph.get_text_chunks(id).unwrap();
+        let vector = vec![0.1; EMBEDDING_DIMENSIONS];
+        for chunk in &chunks[..2] {
+            graph.upsert_chunk_embedding(chunk.id, &vector).unwrap();
+        }
+        graph
+            .record_chunk_embeddings(EmbeddingTarget::Standard, "flm/embed-gemma", &chunks[..1])
+            .unwrap();
+
+        // One tracked, one untracked legacy vector, one missing.
+        let coverage = graph.get_embedding_coverage().unwrap();
+        assert_eq!(
+            (coverage.total, coverage.embedded, coverage.not_embedded),
+            (3, 2, 1)
+        );
+        assert_eq!(coverage.current_model.as_deref(), Some("flm/embed-gemma"));
+        assert!((coverage.percent() - 66.666).abs() < 0.01);
+        assert_eq!(
+            graph
+                .chunk_embedding_status(EmbeddingTarget::Standard, chunks[2].id)
+                .unwrap(),
+            ChunkEmbeddingStatus::NotEmbedded
+        );
+
+        // Switching models makes the first chunk's vector stale.
+        graph.upsert_chunk_embedding(chunks[1].id, &vector).unwrap();
+        graph
+            .record_chunk_embeddings(EmbeddingTarget::Standard, "rocm/nomic", &chunks[1..2])
+            .unwrap();
+        assert!(matches!(
+            graph
+                .chunk_embedding_status(EmbeddingTarget::Standard, chunks[0].id)
+                .unwrap(),
+            ChunkEmbeddingStatus::Stale { ref model, .. } if model == "flm/embed-gemma"
+        ));
+        assert_eq!(graph.get_embedding_coverage().unwrap().stale, 1);
+
+        assert_eq!(
+            graph
+                .clear_stale_embeddings(EmbeddingTarget::Standard)
+                .unwrap(),
+            1
+        );
+        let coverage = graph.get_embedding_coverage().unwrap();
+        assert_eq!((coverage.embedded, coverage.not_embedded), (1, 2));
+        assert!(graph
+            .embedding_coverage(EmbeddingTarget::HighQuality)
+            .unwrap()
+            .current_model
+            .is_none());
+    }
+}
@@ -0,0 +1,618 @@
+//! Encounter balance estimates.
+//!
+//! An encounter is an object with [`ENCOUNTER_FOE_EDGE`] edges to the
+//! creatures in it (edge metadata [`FOE_COUNT_KEY`] for "4 goblins"); a
+//! party is the set of objects with a `member_of` edge to the party object.
+//! [`KnowledgeGraph::estimate_encounter_difficulty`] reads everyone's stat
+//! blocks (see [`crate::statblocks`]), picks the [`EncounterBudget`] for the
+//! foes' system and grades the fight from trivial to deadly:
+//!
+//! - **5e** ([`Dnd5eBudget`]) — the DMG's XP budget: monster XP by
+//!   challenge rating, scaled by the group-size multiplier, against the sum
+//!   of each character's level thresholds.
+//! - **SWN** ([`SwnBudget`]) — Stars Without Number has no official budget,
+//!   so each side is rated by hit dice (or level) plus attack and skill
+//!   bonuses, and the foes' total is compared with the party's.
+//!
+//! Other systems plug in by implementing [`EncounterBudget`] and calling
+//! [`KnowledgeGraph::estimate_encounter_difficulty_with`].  Fights whose
+//! threat reaches [`TPK_FACTOR`] times the deadly threshold are flagged as
+//! likely total party kills; prep sheets show the estimate for every
+//! encounter in the session.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::error::{Result, UForgeError};
+use crate::statblocks::StatBlock;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Edge type from an encounter to each creature in it.
+pub const ENCOUNTER_FOE_EDGE: &str = "includes";
+
+/// Edge metadata key holding how many of a creature appear (default 1).
+pub const FOE_COUNT_KEY: &str = "count";
+
+/// Edge type from a party member to the party.
+pub const PARTY_MEMBER_EDGE: &str = "member_of";
+
+/// Threat at or above this multiple of the deadly threshold is flagged as a
+/// likely total party kill.
+pub const TPK_FACTOR: f64 = 1.5;
+
+/// How hard a fight is expected to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncounterDifficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl fmt::Display for EncounterDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Trivial => "Trivial",
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+            Self::Deadly => "Deadly",
+        })
+    }
+}
+
+/// Threat levels at which a fight becomes easy, medium, hard and deadly,
+/// in the budget's own unit (XP for 5e).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DifficultyThresholds {
+    pub easy: f64,
+    pub medium: f64,
+    pub hard: f64,
+    pub deadly: f64,
+}
+
+impl DifficultyThresholds {
+    pub fn classify(&self, threat: f64) -> EncounterDifficulty {
+        if threat >= self.deadly {
+            EncounterDifficulty::Deadly
+        } else if threat >= self.hard {
+            EncounterDifficulty::Hard
+        } else if threat >= self.medium {
+            EncounterDifficulty::Medium
+        } else if threat >= self.easy {
+            EncounterDifficulty::Easy
+        } else {
+            EncounterDifficulty::Trivial
+        }
+    }
+}
+
+/// A party member or a creature in the encounter.
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub object: ObjectMetadata,
+    pub stat_block: Option<StatBlock>,
+    /// How many appear; always 1 for party members.
+    pub count: u32,
+}
+
+impl Combatant {
+    /// Numeric `key` from the stat block (stored or derived), falling back
+    /// to the object's properties.
+    pub fn number(&self, key: &str) -> Option<f64> {
+        let from_block = self
+            .stat_block
+            .as_ref()
+            .and_then(|b| b.get(key))
+            .and_then(|v| v.as_f64());
+        from_block.or_else(|| {
+            let value = self.object.get_json_property(key)?;
+            value
+                .as_f64()
+                .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        })
+    }
+
+    /// Text `key` from the stat block, falling back to the object's
+    /// properties.
+    pub fn text(&self, key: &str) -> Option<String> {
+        let from_block = self
+            .stat_block
+            .as_ref()
+            .and_then(|b| b.get(key))
+            .and_then(|v| v.as_str().map(str::to_string));
+        from_block.or_else(|| self.object.get_property(key))
+    }
+}
+
+/// What an [`EncounterBudget`] worked out.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetAssessment {
+    pub threat: f64,
+    pub thresholds: DifficultyThresholds,
+    /// Assumptions and skipped combatants, e.g. `"Ogre has no challenge
+    /// rating"`.
+    pub notes: Vec<String>,
+}
+
+/// Per-system encounter math.
+pub trait EncounterBudget {
+    /// The stat block system this budget understands, e.g. `"5e"`.
+    fn system(&self) -> &str;
+
+    /// Rate `foes` against `party`.  Fails with
+    /// [`UForgeError::ValidationFailed`] when the stats needed are missing
+    /// altogether.
+    fn assess(&self, party: &[Combatant], foes: &[Combatant]) -> Result<BudgetAssessment>;
+}
+
+/// The built-in budget for `system`, if there is one.
+pub fn encounter_budget(system: &str) -> Option<Box<dyn EncounterBudget>> {
+    match system {
+        "5e" => Some(Box::new(Dnd5eBudget)),
+        "swn" => Some(Box::new(SwnBudget)),
+        _ => None,
+    }
+}
+
+/// The estimate returned by [`KnowledgeGraph::estimate_encounter_difficulty`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EncounterEstimate {
+    pub encounter_id: ObjectId,
+    pub party_id: ObjectId,
+    pub system: String,
+    pub difficulty: EncounterDifficulty,
+    pub threat: f64,
+    pub thresholds: DifficultyThresholds,
+    /// Threat is at least [`TPK_FACTOR`] times the deadly threshold.
+    pub likely_tpk: bool,
+    pub notes: Vec<String>,
+}
+
+impl EncounterEstimate {
+    /// `"Deadly (1500 vs 1000 deadly), likely TPK"`.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{} ({:.0} vs {:.0} deadly)",
+            self.difficulty, self.threat, self.thresholds.deadly
+        );
+        if self.likely_tpk {
+            out.push_str(", likely TPK");
+        }
+        out
+    }
+}
+
+// ── D&D 5e ────────────────────────────────────────────────────────────────────
+
+/// Easy, medium, hard and deadly XP thresholds per character, levels 1–20.
+const DND5E_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700],
+];
+
+/// XP for challenge ratings 1–30.
+const DND5E_CR_XP: [u32; 30] = [
+    200, 450, 700, 1100, 1800, 2300, 2900, 3900, 5000, 5900, 7200, 8400, 10000, 11500, 13000,
+    15000, 18000, 20000, 22000, 25000, 33000, 41000, 50000, 62000, 75000, 90000, 105000, 120000,
+    135000, 155000,
+];
+
+/// Group-size multipliers, from a lone monster facing a large party up to a
+/// horde facing a small one.
+const DND5E_MULTIPLIERS: [f64; 8] = [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0];
+
+/// XP of challenge rating `cr` (`"1/4"`, `"0.5"`, `"3"`).
+fn dnd5e_cr_xp(cr: &str) -> Option<u32> {
+    let cr = cr.trim();
+    let value: f64 = match cr.split_once('/') {
+        Some((n, d)) => n.trim().parse::<f64>().ok()? / d.trim().parse::<f64>().ok()?,
+        None => cr.parse().ok()?,
+    };
+    Some(match value {
+        v if v <= 0.0 => 10,
+        v if v <= 0.125 => 25,
+        v if v <= 0.25 => 50,
+        v if v < 1.0 => 100,
+        v => *DND5E_CR_XP.get(v as usize - 1)?,
+    })
+}
+
+/// XP multiplier for `monsters` facing a party of `party_size`.
+fn dnd5e_multiplier(monsters: u32, party_size: usize) -> f64 {
+    let step: usize = match monsters {
+        0 | 1 => 1,
+        2 => 2,
+        3..=6 => 3,
+        7..=10 => 4,
+        11..=14 => 5,
+        _ => 6,
+    };
+    let step = match party_size {
+        0..=2 => step + 1,
+        3..=5 => step,
+        _ => step - 1,
+    };
+    DND5E_MULTIPLIERS[step]
+}
+
+/// The DMG XP budget for D&D 5e.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dnd5eBudget;
+
+impl EncounterBudget for Dnd5eBudget {
+    fn system(&self) -> &str {
+        "5e"
+    }
+
+    fn assess(&self, party: &[Combatant], foes: &[Combatant]) -> Result<BudgetAssessment> {
+        let mut notes = Vec::new();
+        let mut sums = [0u32; 4];
+        let mut members = 0;
+        for member in party {
+            let Some(level) = member.number("level") else {
+                notes.push(format!("{} has no level", member.object.name));
+                continue;
+            };
+            let row = DND5E_THRESHOLDS[(level.round() as usize).clamp(1, 20) - 1];
+            for (sum, threshold) in sums.iter_mut().zip(row) {
+                *sum += threshold;
+            }
+            members += 1;
+        }
+        if members == 0 {
+            return Err(UForgeError::ValidationFailed(
+                "No party member has a level".to_string(),
+            ));
+        }
+
+        let mut xp = 0u64;
+        let mut monsters = 0u32;
+        for foe in foes {
+            let Some(foe_xp) = foe.text("cr").as_deref().and_then(dnd5e_cr_xp) else {
+                notes.push(format!("{} has no challenge rating", foe.object.name));
+                continue;
+            };
+            xp += u64::from(foe_xp) * u64::from(foe.count);
+            monsters += foe.count;
+        }
+        let multiplier = dnd5e_multiplier(monsters, members);
+        if multiplier != 1.0 {
+            notes.push(format!(
+                "{monsters} monsters against {members} characters: XP ×{multiplier}"
+            ));
+        }
+
+        Ok(BudgetAssessment {
+            threat: xp as f64 * multiplier,
+            thresholds: DifficultyThresholds {
+                easy: f64::from(sums[0]),
+                medium: f64::from(sums[1]),
+                hard: f64::from(sums[2]),
+                deadly: f64::from(sums[3]),
+            },
+            notes,
+        })
+    }
+}
+
+// ── Stars Without Number ──────────────────────────────────────────────────────
+
+/// A heuristic budget for Stars Without Number: each combatant rates as
+/// hit dice (or level) + attack bonus + skill bonus, and the fight is easy
+/// at a quarter of the party's total, medium at half, hard at three
+/// quarters and deadly at parity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwnBudget;
+
+impl SwnBudget {
+    fn rating(combatant: &Combatant) -> f64 {
+        let hd = combatant
+            .number("hd")
+            .or_else(|| combatant.number("level"))
+            .unwrap_or(1.0);
+        let atk = combatant.number("atk").unwrap_or(0.0);
+        let skill = combatant.number("skill").unwrap_or(0.0);
+        (hd + atk + skill).max(0.0)
+    }
+}
+
+impl EncounterBudget for SwnBudget {
+    fn system(&self) -> &str {
+        "swn"
+    }
+
+    fn assess(&self, party: &[Combatant], foes: &[Combatant]) -> Result<BudgetAssessment> {
+        let strength: f64 = party.iter().map(Self::rating).sum();
+        if strength <= 0.0 {
+            return Err(UForgeError::ValidationFailed(
+                "The party has no combat rating".to_string(),
+            ));
+        }
+        let threat = foes
+            .iter()
+            .map(|f| Self::rating(f) * f64::from(f.count))
+            .sum();
+        Ok(BudgetAssessment {
+            threat,
+            thresholds: DifficultyThresholds {
+                easy: strength * 0.25,
+                medium: strength * 0.5,
+                hard: strength * 0.75,
+                deadly: strength,
+            },
+            notes: vec!["Rated as hit dice + attack + skill per combatant".to_string()],
+        })
+    }
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Estimate how hard `encounter_id` is for the members of `party_id`,
+    /// using the built-in budget for the foes' stat block system.
+    ///
+    /// Fails with [`UForgeError::NotFound`] when either object is missing,
+    /// and [`UForgeError::ValidationFailed`] when the party has no members,
+    /// the encounter no foes, no foe has a stat block, or the system has no
+    /// built-in budget.
+    pub fn estimate_encounter_difficulty(
+        &self,
+        encounter_id: ObjectId,
+        party_id: ObjectId,
+    ) -> Result<EncounterEstimate> {
+        let foes = self.encounter_foes(encounter_id)?;
+        let system = foes
+            .iter()
+            .find_map(|f| f.stat_block.as_ref())
+            .map(|b| b.layout.system.clone())
+            .ok_or_else(|| {
+                UForgeError::ValidationFailed(
+                    "No creature in the encounter has a stat block".to_string(),
+                )
+            })?;
+        let budget = encounter_budget(&system).ok_or_else(|| {
+            UForgeError::ValidationFailed(format!("No encounter budget for system '{system}'"))
+        })?;
+        self.estimate_encounter_difficulty_with(encounter_id, party_id, budget.as_ref())
+    }
+
+    /// [`estimate_encounter_difficulty`](Self::estimate_encounter_difficulty)
+    /// with a caller-supplied budget, for systems without a built-in one.
+    pub fn estimate_encounter_difficulty_with(
+        &self,
+        encounter_id: ObjectId,
+        party_id: ObjectId,
+        budget: &dyn EncounterBudget,
+    ) -> Result<EncounterEstimate> {
+        let foes = self.encounter_foes(encounter_id)?;
+        let party = self.party_members(party_id)?;
+        let assessment = budget.assess(&party, &foes)?;
+        Ok(EncounterEstimate {
+            encounter_id,
+            party_id,
+            system: budget.system().to_string(),
+            difficulty: assessment.thresholds.classify(assessment.threat),
+            likely_tpk: assessment.threat >= assessment.thresholds.deadly * TPK_FACTOR,
+            threat: assessment.threat,
+            thresholds: assessment.thresholds,
+            notes: assessment.notes,
+        })
+    }
+
+    fn encounter_foes(&self, encounter_id: ObjectId) -> Result<Vec<Combatant>> {
+        if self.get_object(encounter_id)?.is_none() {
+            return Err(UForgeError::NotFound(format!(
+                "Unknown encounter {encounter_id}"
+            )));
+        }
+        let mut foes = Vec::new();
+        for edge in self.get_relationships(encounter_id)? {
+            if edge.from != encounter_id || edge.edge_type.as_str() != ENCOUNTER_FOE_EDGE {
+                continue;
+            }
+            let count = edge
+                .metadata
+                .get(FOE_COUNT_KEY)
+                .and_then(|c| c.trim().parse().ok())
+                .unwrap_or(1);
+            if let Some(combatant) = self.combatant(edge.to, count)? {
+                foes.push(combatant);
+            }
+        }
+        if foes.is_empty() {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Encounter {encounter_id} has no creatures"
+            )));
+        }
+        Ok(foes)
+    }
+
+    fn party_members(&self, party_id: ObjectId) -> Result<Vec<Combatant>> {
+        if self.get_object(party_id)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Unknown party {party_id}")));
+        }
+        let mut members = Vec::new();
+        for edge in self.get_relationships(party_id)? {
+            if edge.to != party_id || edge.edge_type.as_str() != PARTY_MEMBER_EDGE {
+                continue;
+            }
+            if let Some(combatant) = self.combatant(edge.from, 1)? {
+                members.push(combatant);
+            }
+        }
+        if members.is_empty() {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Party {party_id} has no members"
+            )));
+        }
+        Ok(members)
+    }
+
+    fn combatant(&self, id: ObjectId, count: u32) -> Result<Option<Combatant>> {
+        let Some(object) = self.get_object(id)? else {
+            return Ok(None);
+        };
+        Ok(Some(Combatant {
+            stat_block: self.stat_block(id)?,
+            object,
+            count,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{Edge, EdgeType};
+    use serde_json::json;
+
+    fn add(graph: &KnowledgeGraph, object_type: &str, name: &str) -> ObjectId {
+        graph
+            .add_object(ObjectMetadata::new(
+                object_type.to_string(),
+                name.to_string(),
+            ))
+            .unwrap()
+    }
+
+    fn add_foes(graph: &KnowledgeGraph, encounter: ObjectId, foe: ObjectId, count: u32) {
+        let edge = Edge::new(encounter, foe, EdgeType::new(ENCOUNTER_FOE_EDGE))
+            .with_metadata(FOE_COUNT_KEY.to_string(), count.to_string());
+        graph.add_edge(edge, true).unwrap();
+    }
+
+    #[test]
+    fn test_dnd5e_math() {
+        assert_eq!(dnd5e_cr_xp("1/4"), Some(50));
+        assert_eq!(dnd5e_cr_xp("0"), Some(10));
+        assert_eq!(dnd5e_cr_xp("5"), Some(1800));
+        assert_eq!(dnd5e_cr_xp("31"), None);
+        assert_eq!(dnd5e_multiplier(1, 4), 1.0);
+        assert_eq!(dnd5e_multiplier(4, 4), 2.0);
+        assert_eq!(dnd5e_multiplier(1, 6), 0.5);
+        assert_eq!(dnd5e_multiplier(20, 2), 5.0);
+    }
+
+    #[test]
+    fn test_estimate_encounter_difficulty_5e() {
+        let (graph, _temp_dir) = create_test_graph();
+        let party = add(&graph, "party", "The Party");
+        for name in ["Ayla", "Brom", "Cyr", "Dara"] {
+            let mut metadata =
+                ObjectMetadata::new("player_character".to_string(), name.to_string());
+            metadata.set_property("level".to_string(), "1".to_string());
+            let pc = graph.add_object(metadata).unwrap();
+            graph
+                .connect_objects_str(pc, party, PARTY_MEMBER_EDGE)
+                .unwrap();
+        }
+
+        let goblin = add(&graph, "npc", "Goblin");
+        let ogre = add(&graph, "npc", "Ogre");
+        for (id, cr) in [(goblin, "1/4"), (ogre, "2")] {
+            let values = json!({
+                "ac": 15, "hp": 7, "cr": cr,
+                "str": 10, "dex": 10, "con": 10, "int": 10, "wis": 10, "cha": 10
+            });
+            graph
+                .set_stat_block(id, Some("5e"), values.as_object().cloned().unwrap())
+                .unwrap();
+        }
+
+        let skirmish = add(&graph, "encounter", "Ambush");
+        add_foes(&graph, skirmish, goblin, 4);
+        let estimate = graph
+            .estimate_encounter_difficulty(skirmish, party)
+            .unwrap();
+        assert_eq!(estimate.system, "5e");
+        assert_eq!(estimate.threat, 400.0);
+        assert_eq!(estimate.thresholds.deadly, 400.0);
+        assert_eq!(estimate.difficulty, EncounterDifficulty::Deadly);
+        assert!(!estimate.likely_tpk);
+
+        let lair = add(&graph, "encounter", "Ogre lair");
+        add_foes(&graph, lair, ogre, 2);
+        let estimate = graph.estimate_encounter_difficulty(lair, party).unwrap();
+        assert_eq!(estimate.threat, 1350.0);
+        assert!(estimate.likely_tpk);
+        assert_eq!(
+            estimate.summary(),
+            "Deadly (1350 vs 400 deadly), likely TPK"
+        );
+
+        let empty = add(&graph, "encounter", "Empty room");
+        let err = graph
+            .estimate_encounter_difficulty(empty, party)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+        let err = graph
+            .estimate_encounter_difficulty(lair, ObjectId::new_v4())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_estimate_encounter_difficulty_swn() {
+        let (graph, _temp_dir) = create_test_graph();
+        let party = add(&graph, "party", "Crew");
+        for name in ["Vex", "Oriel"] {
+            let pc = add(&graph, "player_character", name);
+            graph
+                .set_stat_block(
+                    pc,
+                    Some("swn"),
+                    json!({"hd": 3, "ac": 16, "atk": 2, "skill": 1})
+                        .as_object()
+                        .cloned()
+                        .unwrap(),
+                )
+                .unwrap();
+            graph
+                .connect_objects_str(pc, party, PARTY_MEMBER_EDGE)
+                .unwrap();
+        }
+        let thug = add(&graph, "npc", "Thug");
+        graph
+            .set_stat_block(
+                thug,
+                Some("swn"),
+                json!({"hd": 1, "ac": 10, "atk": 1})
+                    .as_object()
+                    .cloned()
+                    .unwrap(),
+            )
+            .unwrap();
+        let brawl = add(&graph, "encounter", "Bar brawl");
+        add_foes(&graph, brawl, thug, 3);
+
+        let estimate = graph.estimate_encounter_difficulty(brawl, party).unwrap();
+        assert_eq!(estimate.system, "swn");
+        assert_eq!(estimate.threat, 6.0);
+        assert_eq!(estimate.difficulty, EncounterDifficulty::Medium);
+    }
+}
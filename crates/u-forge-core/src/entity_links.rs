@@ -0,0 +1,431 @@
+//! Inline entity links in chunk text.
+//!
+//! GMs write `[[Name]]`, `[[Name|shown text]]` or `@Name` (underscores
+//! standing for spaces, `@Lord_Neverember`) inside their notes.  Whenever a
+//! chunk is saved through [`KnowledgeGraph::add_text_chunk`],
+//! [`KnowledgeGraph::add_timed_text_chunk`] or
+//! [`KnowledgeGraph::update_text_chunk`], [`KnowledgeGraph::index_chunk_links`]:
+//!
+//! - parses the link spans with [`parse_entity_links`];
+//! - resolves each target against object names, then glossary terms, then
+//!   names ignoring case;
+//! - stores the spans in the `chunk_links` table (see `graph/chunk_links.rs`),
+//!   resolved or not, so a later object of that name can be found by
+//!   re-indexing;
+//! - adds a [`MENTIONS_EDGE`] edge from the chunk's object to every resolved
+//!   target, and removes the ones no chunk of that object links any more.
+//!
+//! [`KnowledgeGraph::render_chunk`] turns a chunk into HTML or Markdown with
+//! the links resolved.  It follows the stored object ids, so a link keeps
+//! pointing at its object after a rename.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::markdown::markdown_file_name;
+use crate::types::{ChunkId, Edge, EdgeType, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Edge type written from a chunk's object to each object its text links.
+pub const MENTIONS_EDGE: &str = "mentions";
+
+/// Metadata key marking a [`MENTIONS_EDGE`] edge as maintained from inline
+/// links; only those are removed when the links go away.
+pub const INLINE_LINK_KEY: &str = "inline_link";
+
+/// How a link was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSyntax {
+    /// `[[Name]]` or `[[Name|label]]`.
+    Brackets,
+    /// `@Name`.
+    At,
+}
+
+/// One link found by [`parse_entity_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkSpan {
+    /// Byte range of the whole link, markup included.
+    pub start: usize,
+    pub end: usize,
+    /// The name linked to.
+    pub target: String,
+    /// Text shown for the link.
+    pub label: String,
+    pub syntax: LinkSyntax,
+}
+
+/// A stored link of a chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkLink {
+    pub chunk_id: ChunkId,
+    /// Byte range of the link in the chunk's content.
+    pub start: usize,
+    pub end: usize,
+    pub target: String,
+    /// `None` while no object has that name.
+    pub object_id: Option<ObjectId>,
+}
+
+/// Output of [`KnowledgeGraph::render_chunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkFormat {
+    Html,
+    Markdown,
+}
+
+/// Whether `text` contains anything that could be a link.
+pub fn has_link_syntax(text: &str) -> bool {
+    text.contains("[[") || text.contains('@')
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '\''
+}
+
+/// Every link in `text`, in order.  Malformed brackets and `@` inside a
+/// word (e-mail addresses) are left as plain text.
+pub fn parse_entity_links(text: &str) -> Vec<LinkSpan> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        if rest.starts_with("[[") {
+            if let Some(span) = parse_brackets(text, pos) {
+                pos = span.end;
+                spans.push(span);
+                continue;
+            }
+        } else if rest.starts_with('@') {
+            let after_word = text[..pos]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_');
+            if !after_word {
+                if let Some(span) = parse_at(text, pos) {
+                    pos = span.end;
+                    spans.push(span);
+                    continue;
+                }
+            }
+        }
+        pos += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    spans
+}
+
+fn parse_brackets(text: &str, start: usize) -> Option<LinkSpan> {
+    let inner_start = start + 2;
+    let close = text[inner_start..].find("]]")? + inner_start;
+    let inner = &text[inner_start..close];
+    if inner.contains(['[', ']', '\n']) {
+        return None;
+    }
+    let (target, label) = match inner.split_once('|') {
+        Some((target, label)) => (target.trim(), label.trim()),
+        None => (inner.trim(), inner.trim()),
+    };
+    if target.is_empty() {
+        return None;
+    }
+    Some(LinkSpan {
+        start,
+        end: close + 2,
+        target: target.to_string(),
+        label: if label.is_empty() { target } else { label }.to_string(),
+        syntax: LinkSyntax::Brackets,
+    })
+}
+
+fn parse_at(text: &str, start: usize) -> Option<LinkSpan> {
+    let name_start = start + 1;
+    let len: usize = text[name_start..]
+        .chars()
+        .take_while(|&c| is_name_char(c))
+        .map(char::len_utf8)
+        .sum();
+    // Trailing punctuation belongs to the sentence: "@Bob's" links Bob's,
+    // but "@Bob-" and "@Bob'" link Bob.
+    let name = text[name_start..name_start + len].trim_end_matches(['-', '\'', '_']);
+    if !name.chars().next().is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    let target = name.replace('_', " ");
+    Some(LinkSpan {
+        start,
+        end: name_start + name.len(),
+        label: target.clone(),
+        target,
+        syntax: LinkSyntax::At,
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Name → object lookups for resolving one batch of links.
+struct LinkResolver {
+    exact: HashMap<String, ObjectId>,
+    lowercase: HashMap<String, ObjectId>,
+    glossary: crate::glossary::Glossary,
+}
+
+impl LinkResolver {
+    fn new(graph: &KnowledgeGraph) -> Result<Self> {
+        let mut exact = HashMap::new();
+        let mut lowercase = HashMap::new();
+        for (id, name) in graph.storage.list_node_names()? {
+            lowercase.entry(name.to_lowercase()).or_insert(id);
+            exact.entry(name).or_insert(id);
+        }
+        Ok(Self {
+            exact,
+            lowercase,
+            glossary: graph.glossary()?,
+        })
+    }
+
+    fn resolve(&self, target: &str) -> Option<ObjectId> {
+        self.exact
+            .get(target)
+            .copied()
+            .or_else(|| self.glossary.lookup(target).and_then(|e| e.object_id))
+            .or_else(|| self.lowercase.get(&target.to_lowercase()).copied())
+    }
+}
+
+impl KnowledgeGraph {
+    /// The object a link to `target` resolves to: exact name, then glossary
+    /// term, then name ignoring case.
+    pub fn resolve_link_target(&self, target: &str) -> Result<Option<ObjectId>> {
+        Ok(LinkResolver::new(self)?.resolve(target.trim()))
+    }
+
+    /// Parse, resolve and store the links of `chunk_id`, and bring its
+    /// object's [`MENTIONS_EDGE`] edges in line; see the module docs.
+    /// Returns the stored links.  Fails with [`UForgeError::NotFound`] for
+    /// an unknown chunk.
+    pub fn index_chunk_links(&self, chunk_id: ChunkId) -> Result<Vec<ChunkLink>> {
+        let (owner, content) = self
+            .storage
+            .get_chunk_owner_and_content(chunk_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Chunk {chunk_id} not found")))?;
+
+        let spans = parse_entity_links(&content);
+        let links: Vec<ChunkLink> = if spans.is_empty() {
+            Vec::new()
+        } else {
+            let resolver = LinkResolver::new(self)?;
+            spans
+                .into_iter()
+                .map(|span| ChunkLink {
+                    chunk_id,
+                    start: span.start,
+                    end: span.end,
+                    object_id: resolver.resolve(&span.target),
+                    target: span.target,
+                })
+                .collect()
+        };
+        self.storage.replace_chunk_links(chunk_id, &links)?;
+        self.sync_mention_edges(owner)?;
+        Ok(links)
+    }
+
+    /// Add a mention edge for every object `owner`'s chunks link, and drop
+    /// inline-link mention edges none of them link any more.
+    fn sync_mention_edges(&self, owner: ObjectId) -> Result<()> {
+        let wanted: HashSet<ObjectId> = self
+            .storage
+            .get_links_from_node(owner)?
+            .into_iter()
+            .filter_map(|l| l.object_id)
+            .filter(|&id| id != owner)
+            .collect();
+        let mut existing = HashSet::new();
+        for edge in self.get_relationships(owner)? {
+            if edge.from != owner || edge.edge_type.as_str() != MENTIONS_EDGE {
+                continue;
+            }
+            existing.insert(edge.to);
+            let ours = edge.metadata.get(INLINE_LINK_KEY).is_some_and(|v| v == "true");
+            if ours && !wanted.contains(&edge.to) {
+                self.delete_edge(owner, edge.to, MENTIONS_EDGE)?;
+            }
+        }
+        for &target in wanted.difference(&existing) {
+            let mut edge = Edge::new(owner, target, EdgeType::new(MENTIONS_EDGE));
+            edge.metadata
+                .insert(INLINE_LINK_KEY.to_string(), "true".to_string());
+            // Notes link whatever they mention; schemas don't restrict that.
+            self.add_edge(edge, true)?;
+        }
+        Ok(())
+    }
+
+    /// The stored links of `chunk_id`, in text order.
+    pub fn get_chunk_links(&self, chunk_id: ChunkId) -> Result<Vec<ChunkLink>> {
+        self.storage.get_chunk_links(chunk_id)
+    }
+
+    /// Every chunk link that resolves to `object_id` — the notes that
+    /// mention it.
+    pub fn backlinks(&self, object_id: ObjectId) -> Result<Vec<ChunkLink>> {
+        self.storage.get_links_to_node(object_id)
+    }
+
+    /// The content of `chunk_id` with its links rendered as `format`.
+    ///
+    /// HTML escapes the text and writes resolved links as
+    /// `<a class="entity-link" data-object-id="…">`, unresolved ones as
+    /// `<span class="entity-link unresolved">`.  Markdown links resolved
+    /// targets to their [`markdown_file_name`] and leaves unresolved ones
+    /// as their label.  Fails with [`UForgeError::NotFound`] for an unknown
+    /// chunk.
+    pub fn render_chunk(&self, chunk_id: ChunkId, format: LinkFormat) -> Result<String> {
+        let (_, content) = self
+            .storage
+            .get_chunk_owner_and_content(chunk_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Chunk {chunk_id} not found")))?;
+        let stored: HashMap<usize, ObjectId> = self
+            .storage
+            .get_chunk_links(chunk_id)?
+            .into_iter()
+            .filter_map(|l| Some((l.start, l.object_id?)))
+            .collect();
+
+        let mut out = String::with_capacity(content.len());
+        let mut pos = 0;
+        for span in parse_entity_links(&content) {
+            let plain = &content[pos..span.start];
+            match format {
+                LinkFormat::Html => out.push_str(&escape_html(plain)),
+                LinkFormat::Markdown => out.push_str(plain),
+            }
+            let object = match stored.get(&span.start) {
+                Some(&id) => self.get_object(id)?,
+                None => None,
+            };
+            match (format, object) {
+                (LinkFormat::Html, Some(object)) => {
+                    let _ = write!(
+                        out,
+                        "<a class=\"entity-link\" data-object-id=\"{}\" title=\"{}\">{}</a>",
+                        object.id,
+                        escape_html(&object.name),
+                        escape_html(&span.label)
+                    );
+                }
+                (LinkFormat::Html, None) => {
+                    let _ = write!(
+                        out,
+                        "<span class=\"entity-link unresolved\">{}</span>",
+                        escape_html(&span.label)
+                    );
+                }
+                (LinkFormat::Markdown, Some(object)) => {
+                    let _ = write!(out, "[{}]({})", span.label, markdown_file_name(&object));
+                }
+                (LinkFormat::Markdown, None) => out.push_str(&span.label),
+            }
+            pos = span.end;
+        }
+        match format {
+            LinkFormat::Html => out.push_str(&escape_html(&content[pos..])),
+            LinkFormat::Markdown => out.push_str(&content[pos..]),
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glossary::GlossaryEntry;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{ChunkType, ObjectMetadata};
+
+    #[test]
+    fn test_parse_entity_links() {
+        let text = "Met [[Gundren Rockseeker|Gundren]] and @Sildar_Hallwinter; mail bob@example.com, [[ ]] [[Cragmaw";
+        let spans = parse_entity_links(text);
+        let found: Vec<_> = spans
+            .iter()
+            .map(|s| (s.target.as_str(), s.label.as_str(), s.syntax))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Gundren Rockseeker", "Gundren", LinkSyntax::Brackets),
+                ("Sildar Hallwinter", "Sildar Hallwinter", LinkSyntax::At),
+            ]
+        );
+        assert_eq!(&text[spans[1].start..spans[1].end], "@Sildar_Hallwinter");
+    }
+
+    #[test]
+    fn test_links_indexed_on_save_and_rendered() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |ty: &str, name: &str| {
+            graph
+                .add_object(ObjectMetadata::new(ty.to_string(), name.to_string()))
+                .unwrap()
+        };
+        let session = add("session", "Session 3");
+        let gundren = add("character", "Gundren Rockseeker");
+        let castle = add("location", "Cragmaw Castle");
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("the castle", "Cragmaw Castle").with_object(castle))
+            .unwrap();
+
+        let chunk = graph
+            .add_text_chunk(
+                session,
+                "@gundren_rockseeker is held in [[the castle|the keep]] by [[King Grol]] & co.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap()[0];
+        let links = graph.get_chunk_links(chunk).unwrap();
+        let resolved: Vec<_> = links.iter().map(|l| l.object_id).collect();
+        assert_eq!(resolved, [Some(gundren), Some(castle), None]);
+        assert_eq!(graph.backlinks(castle).unwrap().len(), 1);
+        let mentioned = |target: ObjectId| {
+            graph
+                .get_relationships(session)
+                .unwrap()
+                .iter()
+                .any(|e| e.to == target && e.edge_type.as_str() == MENTIONS_EDGE)
+        };
+        assert!(mentioned(gundren) && mentioned(castle));
+
+        let html = graph.render_chunk(chunk, LinkFormat::Html).unwrap();
+        assert!(html.contains(&format!("data-object-id=\"{castle}\"")));
+        assert!(html.contains("<span class=\"entity-link unresolved\">King Grol</span> &amp; co."));
+        let markdown = graph.render_chunk(chunk, LinkFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("[gundren rockseeker]("));
+        assert!(markdown.contains("by King Grol &"));
+
+        // Editing the link away drops its mention edge.
+        graph
+            .update_text_chunk(chunk, "Gundren is free; [[the castle]] is empty.", None)
+            .unwrap();
+        assert!(!mentioned(gundren) && mentioned(castle));
+    }
+}
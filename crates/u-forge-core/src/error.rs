@@ -1,8 +1,14 @@
-//! Application-level error types for u-forge.ai HTTP handler boundaries.
+//! Application-level error types for u-forge.ai API boundaries.
 //!
-//! Internal code uses [`anyhow::Error`] throughout.  This module provides
-//! [`AppError`], a typed enum that converts `anyhow` errors to appropriate
-//! HTTP responses at the axum handler boundary.
+//! The public [`crate::KnowledgeGraph`] and
+//! [`crate::graph::KnowledgeGraphStorage`] methods return [`Result`], whose
+//! error is a [`UForgeError`]; match on it directly or use
+//! [`UForgeError::kind`] for a serialisable [`ErrorKind`] to branch on.
+//! Lower-level helpers still use [`anyhow::Error`] with context, and `?`
+//! converts them at the boundary: `UForgeError::from(err)` recovers a typed
+//! failure raised inside the `anyhow` chain and wraps anything else as
+//! [`UForgeError::Internal`].  [`AppError`] maps the same kinds onto HTTP
+//! responses for axum handlers.
 //!
 //! # Phase 3 note
 //!
@@ -11,8 +17,12 @@
 //! The `From<anyhow::Error>` impl is present so handlers can use `?` with
 //! `anyhow`-returning functions once the axum dependency is wired.
 
+/// Result type of the public graph and storage APIs.
+pub type Result<T, E = UForgeError> = std::result::Result<T, E>;
+
 /// Returned by [`crate::graph::KnowledgeGraphStorage::new`] when the
-/// on-disk embedding dimensions differ from the compiled-in constants.
+/// on-disk embedding dimensions differ from the compiled-in constants, and
+/// by the embedding upserts when a vector's length does not fit its table.
 ///
 /// This indicates the embedding model was changed without recreating the
 /// database.  The caller should either re-index the database or pin the
@@ -28,10 +38,153 @@ pub struct EmbeddingDimensionMismatch {
     pub table: String,
     /// Dimensions recorded in the database at creation time.
     pub stored: usize,
-    /// Dimensions expected by the current compile-time constant.
+    /// Dimensions the current model produces (the compile-time constant at
+    /// open time, the vector's length on upsert).
     pub expected: usize,
 }
 
+/// Typed failure raised by the public [`crate::KnowledgeGraph`] and
+/// storage APIs.
+///
+/// Errors that were never classified come back as
+/// [`UForgeError::Internal`].
+#[derive(Debug, thiserror::Error)]
+pub enum UForgeError {
+    /// An object, edge, chunk, proposal, branch, or other record does not
+    /// exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// Input was rejected: invalid properties, names, or sizes.
+    #[error("{0}")]
+    ValidationFailed(String),
+    /// A change breaks the rules of the active schema (e.g. strict edge
+    /// mode).
+    #[error("{0}")]
+    SchemaConflict(String),
+    /// The target is in a state that does not allow the operation (already
+    /// exists, already resolved).
+    #[error("{0}")]
+    Conflict(String),
+    /// The database file is damaged or is not a u-forge database.
+    #[error("{0}")]
+    StorageCorruption(String),
+    /// The database was built with a different embedding model.
+    #[error(transparent)]
+    EmbeddingDimensionMismatch(#[from] EmbeddingDimensionMismatch),
+    /// No embedding provider is registered.
+    #[error("{0}")]
+    EmbeddingUnavailable(String),
+    /// No worker is registered for the requested inference capability.
+    #[error("{0}")]
+    InferenceUnavailable(String),
+    /// The caller cancelled the operation through its
+    /// [`CancellationToken`](crate::progress::CancellationToken).
+    #[error("Operation cancelled")]
+    Cancelled,
+    /// Anything else.
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+/// The variant of a [`UForgeError`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotFound,
+    ValidationFailed,
+    SchemaConflict,
+    Conflict,
+    StorageCorruption,
+    EmbeddingDimensionMismatch,
+    EmbeddingUnavailable,
+    InferenceUnavailable,
+    Cancelled,
+    Internal,
+}
+
+impl UForgeError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::ValidationFailed(_) => ErrorKind::ValidationFailed,
+            Self::SchemaConflict(_) => ErrorKind::SchemaConflict,
+            Self::Conflict(_) => ErrorKind::Conflict,
+            Self::StorageCorruption(_) => ErrorKind::StorageCorruption,
+            Self::EmbeddingDimensionMismatch(_) => ErrorKind::EmbeddingDimensionMismatch,
+            Self::EmbeddingUnavailable(_) => ErrorKind::EmbeddingUnavailable,
+            Self::InferenceUnavailable(_) => ErrorKind::InferenceUnavailable,
+            Self::Cancelled => ErrorKind::Cancelled,
+            Self::Internal(_) => ErrorKind::Internal,
+        }
+    }
+
+    /// The [`ErrorKind`] of `err` without consuming it.
+    pub fn kind_of(err: &anyhow::Error) -> ErrorKind {
+        if let Some(e) = err.downcast_ref::<UForgeError>() {
+            return e.kind();
+        }
+        if err.downcast_ref::<EmbeddingDimensionMismatch>().is_some() {
+            return ErrorKind::EmbeddingDimensionMismatch;
+        }
+        #[cfg(feature = "native")]
+        match err.downcast_ref::<rusqlite::Error>() {
+            Some(rusqlite::Error::QueryReturnedNoRows) => return ErrorKind::NotFound,
+            Some(rusqlite::Error::SqliteFailure(e, _))
+                if matches!(
+                    e.code,
+                    rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase
+                ) =>
+            {
+                return ErrorKind::StorageCorruption;
+            }
+            _ => {}
+        }
+        ErrorKind::Internal
+    }
+}
+
+impl From<anyhow::Error> for UForgeError {
+    /// Recover the typed error carried by `err`.  Only a raised
+    /// [`UForgeError`] keeps its own message; other classified errors use
+    /// the outermost context message.
+    fn from(err: anyhow::Error) -> Self {
+        let kind = Self::kind_of(&err);
+        let err = match err.downcast::<UForgeError>() {
+            Ok(e) => return e,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<EmbeddingDimensionMismatch>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        match kind {
+            ErrorKind::NotFound => Self::NotFound(err.to_string()),
+            ErrorKind::StorageCorruption => Self::StorageCorruption(err.to_string()),
+            _ => Self::Internal(err),
+        }
+    }
+}
+
+/// Library errors that `?` passes straight into a [`UForgeError`], classified
+/// the same way as when they arrive inside an `anyhow` chain.
+macro_rules! from_source_error {
+    ($($(#[$meta:meta])* $ty:ty),* $(,)?) => {$(
+        $(#[$meta])*
+        impl From<$ty> for UForgeError {
+            fn from(err: $ty) -> Self {
+                anyhow::Error::from(err).into()
+            }
+        }
+    )*};
+}
+
+from_source_error!(
+    #[cfg(feature = "native")]
+    rusqlite::Error,
+    serde_json::Error,
+    std::io::Error,
+);
+
 /// Application-level error returned by axum HTTP handlers.
 ///
 /// Convert any `anyhow::Error` via the `From` impl (or `?` operator) and let
@@ -47,7 +200,28 @@ pub enum AppError {
     BadRequest(String),
     /// An unexpected internal error occurred (HTTP 500).
     #[error("Internal error: {0}")]
-    Internal(#[from] anyhow::Error),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        UForgeError::from(err).into()
+    }
+}
+
+impl From<UForgeError> for AppError {
+    fn from(err: UForgeError) -> Self {
+        match err.kind() {
+            ErrorKind::NotFound => Self::NotFound(err.to_string()),
+            ErrorKind::ValidationFailed | ErrorKind::SchemaConflict | ErrorKind::Conflict => {
+                Self::BadRequest(err.to_string())
+            }
+            _ => match err {
+                UForgeError::Internal(e) => Self::Internal(e),
+                err => Self::Internal(err.into()),
+            },
+        }
+    }
 }
 
 // TODO Phase 3: add `impl axum::response::IntoResponse for AppError` here
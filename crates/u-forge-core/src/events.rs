@@ -0,0 +1,121 @@
+//! In-process change notifications.
+//!
+//! Subsystems that change the world on their own schedule — a clock being
+//! ticked, in-world time moving on — and milestones worth reacting to — an
+//! object created, a quest completed, a session ended — are announced as a
+//! [`GraphEvent`] on a `tokio::sync::broadcast` channel owned by the
+//! graph's storage, so every write path can announce what it created.  A UI subscribes once with
+//! [`KnowledgeGraph::subscribe_events`] and refreshes whatever the event
+//! touches; [`crate::hooks`] forwards them to scripts and webhooks.  Sending
+//! never fails: events emitted with no subscribers are dropped.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::calendar::WorldDate;
+use crate::clocks::ClockEvent;
+use crate::error::{Result, UForgeError};
+use crate::ingest::session_log::SESSION_TYPE;
+use crate::schedule::FiredEvent;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Session property stamped with the RFC 3339 time by
+/// [`KnowledgeGraph::end_session`].
+pub const SESSION_ENDED_AT_KEY: &str = "ended_at";
+
+/// Session property holding the recap.
+pub const SESSION_SUMMARY_KEY: &str = "summary";
+
+/// Capacity of the [`GraphEvent`] channel.
+pub(crate) const GRAPH_EVENT_CAPACITY: usize = 64;
+
+/// Something that changed, for [`KnowledgeGraph::subscribe_events`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GraphEvent {
+    /// A progress clock was ticked or reset.
+    ClockChanged(ClockEvent),
+    /// A scheduled event came due while advancing world time.
+    ScheduledEventFired(FiredEvent),
+    /// [`KnowledgeGraph::advance_world_time`] finished; sent after the
+    /// events it fired.
+    WorldTimeAdvanced {
+        from: Option<WorldDate>,
+        to: WorldDate,
+    },
+    /// An object was stored under an id not seen before — by
+    /// [`KnowledgeGraph::add_object`], a staging commit, an accepted
+    /// proposal or an import.  Updates of existing objects are not
+    /// announced.
+    ObjectCreated {
+        object_id: ObjectId,
+        object_type: String,
+        name: String,
+    },
+    /// A quest's `status` changed to `completed` through
+    /// [`KnowledgeGraph::update_object`].
+    QuestCompleted { quest_id: ObjectId, name: String },
+    /// [`KnowledgeGraph::end_session`] closed a session.
+    SessionEnded {
+        session_id: ObjectId,
+        name: String,
+        summary: Option<String>,
+    },
+}
+
+impl GraphEvent {
+    pub(crate) fn object_created(metadata: &ObjectMetadata) -> Self {
+        GraphEvent::ObjectCreated {
+            object_id: metadata.id,
+            object_type: metadata.object_type.clone(),
+            name: metadata.name.clone(),
+        }
+    }
+}
+
+impl KnowledgeGraph {
+    /// Receive every [`GraphEvent`] sent from now on.  Receivers that fall
+    /// more than 64 events behind get
+    /// [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe_events(&self) -> broadcast::Receiver<GraphEvent> {
+        self.storage.subscribe_events()
+    }
+
+    pub(crate) fn emit(&self, event: GraphEvent) {
+        // No subscribers is fine: the change was still saved.
+        self.storage.emit(event);
+    }
+
+    /// Mark `session` finished: stamp [`SESSION_ENDED_AT_KEY`], store
+    /// `summary` (when given) as its recap, and emit
+    /// [`GraphEvent::SessionEnded`] with the session's summary.  Fails with
+    /// [`UForgeError::NotFound`] for an unknown object and
+    /// [`UForgeError::ValidationFailed`] when it is not a session.
+    pub fn end_session(&self, session: ObjectId, summary: Option<&str>) -> Result<()> {
+        let mut object = self
+            .get_object(session)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown session {session}")))?;
+        if object.object_type != SESSION_TYPE {
+            return Err(UForgeError::ValidationFailed(format!(
+                "'{}' is a {}, not a session",
+                object.name, object.object_type
+            )));
+        }
+        if let Some(summary) = summary {
+            object.set_property(SESSION_SUMMARY_KEY.to_string(), summary.to_string());
+        }
+        object.set_property(
+            SESSION_ENDED_AT_KEY.to_string(),
+            chrono::Utc::now().to_rfc3339(),
+        );
+        let event = GraphEvent::SessionEnded {
+            session_id: session,
+            name: object.name.clone(),
+            summary: object.get_property(SESSION_SUMMARY_KEY),
+        };
+        self.update_object(object)?;
+        self.emit(event);
+        Ok(())
+    }
+}
@@ -0,0 +1,409 @@
+//! Bulk export of filtered selections — publishing part of a setting or
+//! handing content to a co-GM.
+//!
+//! [`KnowledgeGraph::export_selection`] takes an [`ExportFilter`] (a
+//! [`NodeFilter`] on type, tag, lifecycle and name, plus an optional
+//! player-visible-only switch) and writes the matching objects in one of
+//! three [`ExportFormat`]s:
+//!
+//! - **Json** — `export.jsonl` in the canonical import format (see
+//!   [`crate::ingest::data`]): one `node` line per object, then one `edge`
+//!   line per relationship between two exported objects.
+//! - **Markdown** — one document per object, as
+//!   [`KnowledgeGraph::export_markdown`] renders them.
+//! - **Archive** — `export.tar.gz` holding `manifest.json`, `export.jsonl`
+//!   and the Markdown documents under `markdown/`.
+//!
+//! With [`ExportFilter::player_visible_only`], objects hidden from players
+//! are skipped, the rest are redacted as in [`crate::visibility`], edges and
+//! links to hidden objects are dropped, and Markdown `Notes` are left out.
+
+use std::io::Write as _;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Result, UForgeError};
+use crate::graph_data::NodeFilter;
+use crate::ingest::data::JsonEntry;
+use crate::progress::{Progress, ProgressSink};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// File name of the JSONL data in Json exports and archives.
+pub const EXPORT_DATA_FILE: &str = "export.jsonl";
+
+/// File name of Archive exports.
+pub const EXPORT_ARCHIVE_FILE: &str = "export.tar.gz";
+
+/// Which objects [`KnowledgeGraph::export_selection`] writes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportFilter {
+    #[serde(default)]
+    pub filter: NodeFilter,
+    /// Only what players may see, redacted.
+    #[serde(default)]
+    pub player_visible_only: bool,
+}
+
+impl ExportFilter {
+    pub fn new(filter: NodeFilter) -> Self {
+        Self {
+            filter,
+            player_visible_only: false,
+        }
+    }
+
+    pub fn player_visible(mut self) -> Self {
+        self.player_visible_only = true;
+        self
+    }
+}
+
+/// Output format of [`KnowledgeGraph::export_selection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Archive,
+}
+
+/// One file of an export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportFile {
+    /// Relative path, e.g. `export.jsonl` or `markdown/terminus-1a2b3c4d.md`.
+    pub path: String,
+    pub content: Vec<u8>,
+}
+
+/// Describes an export; written into archives as `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub exported_at: DateTime<Utc>,
+    pub filter: ExportFilter,
+    pub object_count: usize,
+    pub edge_count: usize,
+}
+
+/// Result of [`KnowledgeGraph::export_selection`].
+#[derive(Debug, Clone)]
+pub struct SelectionExport {
+    pub manifest: ExportManifest,
+    /// Exported objects, by name.
+    pub object_ids: Vec<ObjectId>,
+    pub files: Vec<ExportFile>,
+}
+
+impl SelectionExport {
+    /// Write every file under `dir`, creating subdirectories as needed.
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        for file in &self.files {
+            let path = dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&path, &file.content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl KnowledgeGraph {
+    /// Export the objects matching `filter` in `format`; see the module
+    /// docs.
+    ///
+    /// Reports stage `"export"` to `progress` once per object written to
+    /// the JSONL data, then `"markdown"` per document for the Markdown and
+    /// Archive formats.  Cancelling fails with [`UForgeError::Cancelled`].
+    pub fn export_selection(
+        &self,
+        filter: &ExportFilter,
+        format: ExportFormat,
+        progress: &dyn ProgressSink,
+    ) -> Result<SelectionExport> {
+        let mut objects: Vec<ObjectMetadata> = Vec::new();
+        for object in self.get_all_objects()? {
+            if !filter.filter.matches(&object) {
+                continue;
+            }
+            if filter.player_visible_only {
+                objects.extend(self.redact_for_players(&object));
+            } else {
+                objects.push(object);
+            }
+        }
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let (jsonl, edge_count) = self.selection_jsonl(&objects, progress)?;
+        let manifest = ExportManifest {
+            exported_at: Utc::now(),
+            filter: filter.clone(),
+            object_count: objects.len(),
+            edge_count,
+        };
+        let markdown = |prefix: &str| -> Result<Vec<ExportFile>> {
+            let documents =
+                self.render_markdown_all(&objects, filter.player_visible_only, progress)?;
+            Ok(documents
+                .into_iter()
+                .map(|doc| ExportFile {
+                    path: format!("{prefix}{}", doc.file_name),
+                    content: doc.content.into_bytes(),
+                })
+                .collect())
+        };
+
+        let files = match format {
+            ExportFormat::Json => vec![ExportFile {
+                path: EXPORT_DATA_FILE.to_string(),
+                content: jsonl.into_bytes(),
+            }],
+            ExportFormat::Markdown => markdown("")?,
+            ExportFormat::Archive => {
+                let mut entries = vec![
+                    ExportFile {
+                        path: "manifest.json".to_string(),
+                        content: serde_json::to_vec_pretty(&manifest)
+                            .context("Failed to serialize export manifest")?,
+                    },
+                    ExportFile {
+                        path: EXPORT_DATA_FILE.to_string(),
+                        content: jsonl.into_bytes(),
+                    },
+                ];
+                entries.extend(markdown("markdown/")?);
+                vec![ExportFile {
+                    path: EXPORT_ARCHIVE_FILE.to_string(),
+                    content: write_tar_gz(&entries, manifest.exported_at)?,
+                }]
+            }
+        };
+
+        Ok(SelectionExport {
+            object_ids: objects.iter().map(|o| o.id).collect(),
+            manifest,
+            files,
+        })
+    }
+
+    /// `objects` and the edges among them as canonical JSONL, with the
+    /// number of edge lines.
+    fn selection_jsonl(
+        &self,
+        objects: &[ObjectMetadata],
+        progress: &dyn ProgressSink,
+    ) -> Result<(String, usize)> {
+        let mut out = String::new();
+        let mut line = |entry: &JsonEntry| -> Result<()> {
+            out.push_str(&serde_json::to_string(entry).context("Failed to serialize export")?);
+            out.push('\n');
+            Ok(())
+        };
+        for object in objects {
+            let mut properties = object.properties.as_object().cloned().unwrap_or_default();
+            properties.insert("name".to_string(), Value::String(object.name.clone()));
+            line(&JsonEntry::Node {
+                id: object.id.to_string(),
+                node_type: object.object_type.clone(),
+                properties,
+            })?;
+        }
+
+        let names: std::collections::HashMap<ObjectId, &str> =
+            objects.iter().map(|o| (o.id, o.name.as_str())).collect();
+        let mut edges = 0;
+        for (done, object) in objects.iter().enumerate() {
+            progress.check_cancelled()?;
+            progress.report(&Progress::new("export", done, Some(objects.len())));
+            for edge in self.get_relationships(object.id)? {
+                if edge.from != object.id {
+                    continue;
+                }
+                let Some(to) = names.get(&edge.to) else {
+                    continue;
+                };
+                line(&JsonEntry::Edge {
+                    from: object.name.clone(),
+                    to: to.to_string(),
+                    edge_type: edge.edge_type.as_str().to_string(),
+                })?;
+                edges += 1;
+            }
+        }
+        progress.report(&Progress::new("export", objects.len(), Some(objects.len())));
+        Ok((out, edges))
+    }
+}
+
+/// `entries` as a gzip-compressed ustar archive.
+fn write_tar_gz(entries: &[ExportFile], mtime: DateTime<Utc>) -> Result<Vec<u8>> {
+    let mut tar = Vec::new();
+    for entry in entries {
+        tar.extend_from_slice(&tar_header(&entry.path, entry.content.len(), mtime)?);
+        tar.extend_from_slice(&entry.content);
+        tar.resize(tar.len().next_multiple_of(512), 0);
+    }
+    // End of archive: two zero blocks.
+    tar.resize(tar.len() + 1024, 0);
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(&tar)
+        .context("Failed to compress export archive")?;
+    Ok(encoder
+        .finish()
+        .context("Failed to compress export archive")?)
+}
+
+/// A ustar header for a regular file.  Paths longer than 100 bytes are
+/// split into the 155-byte prefix field at a `/`.
+fn tar_header(path: &str, size: usize, mtime: DateTime<Utc>) -> Result<[u8; 512]> {
+    let (prefix, name) = if path.len() <= 100 {
+        ("", path)
+    } else {
+        path.rsplit_once('/')
+            .filter(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100)
+            .ok_or_else(|| {
+                UForgeError::ValidationFailed(format!("Export path too long for archive: {path}"))
+            })?
+    };
+
+    let mut header = [0u8; 512];
+    let mut field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    field(0, name.as_bytes());
+    field(100, b"0000644\0");
+    field(108, b"0000000\0");
+    field(116, b"0000000\0");
+    field(124, format!("{size:011o}\0").as_bytes());
+    field(
+        136,
+        format!("{:011o}\0", mtime.timestamp().max(0)).as_bytes(),
+    );
+    field(148, b"        ");
+    field(156, b"0");
+    field(257, b"ustar\0");
+    field(263, b"00");
+    field(345, prefix.as_bytes());
+
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::test_helpers::create_test_graph;
+    use crate::visibility::VISIBILITY_KEY;
+    use crate::ObjectBuilder;
+    use std::io::Read as _;
+    use tempfile::TempDir;
+
+    fn setting(graph: &KnowledgeGraph) -> (ObjectId, ObjectId, ObjectId) {
+        let daan = |name: &str| {
+            ObjectBuilder::location(name.to_string()).with_tag("kingdom-of-daan".to_string())
+        };
+        let capital = daan("Daan Keep")
+            .with_property(
+                "gm_notes".to_string(),
+                "The king is a doppelganger".to_string(),
+            )
+            .add_to_graph(graph)
+            .unwrap();
+        let vault = daan("Hidden Vault")
+            .with_property(VISIBILITY_KEY.to_string(), "gm".to_string())
+            .with_relationship("located_in", capital)
+            .add_to_graph(graph)
+            .unwrap();
+        let village = daan("Millbrook")
+            .with_relationship("located_in", capital)
+            .add_to_graph(graph)
+            .unwrap();
+        ObjectBuilder::location("Elsewhere".to_string())
+            .add_to_graph(graph)
+            .unwrap();
+        (capital, vault, village)
+    }
+
+    fn daan_filter() -> ExportFilter {
+        ExportFilter::new(NodeFilter {
+            object_types: vec!["location".to_string()],
+            tags: vec!["kingdom-of-daan".to_string()],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_export_selection_json_player_visible() {
+        let (graph, _temp_dir) = create_test_graph();
+        let (capital, vault, village) = setting(&graph);
+
+        let all = graph
+            .export_selection(&daan_filter(), ExportFormat::Json, &NoProgress)
+            .unwrap();
+        assert_eq!(all.object_ids.len(), 3);
+        assert_eq!(all.manifest.edge_count, 2);
+
+        let public = graph
+            .export_selection(
+                &daan_filter().player_visible(),
+                ExportFormat::Json,
+                &NoProgress,
+            )
+            .unwrap();
+        assert_eq!(public.object_ids, vec![capital, village]);
+        assert!(!public.object_ids.contains(&vault));
+        assert_eq!(public.manifest.edge_count, 1);
+        let jsonl = String::from_utf8(public.files[0].content.clone()).unwrap();
+        assert!(!jsonl.contains("doppelganger"));
+        let entries: Vec<JsonEntry> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(&entries[2], JsonEntry::Edge { from, .. } if from == "Millbrook"));
+    }
+
+    #[test]
+    fn test_export_selection_markdown_and_archive() {
+        let (graph, _temp_dir) = create_test_graph();
+        setting(&graph);
+        let filter = daan_filter().player_visible();
+
+        let markdown = graph
+            .export_selection(&filter, ExportFormat::Markdown, &NoProgress)
+            .unwrap();
+        assert_eq!(markdown.files.len(), 2);
+        let keep = String::from_utf8(markdown.files[0].content.clone()).unwrap();
+        assert!(keep.contains("# Daan Keep"));
+        assert!(keep.contains("Millbrook"));
+        assert!(!keep.contains("Hidden Vault"));
+
+        let archive = graph
+            .export_selection(&filter, ExportFormat::Archive, &NoProgress)
+            .unwrap();
+        assert_eq!(archive.files[0].path, EXPORT_ARCHIVE_FILE);
+        let mut tar = Vec::new();
+        flate2::read::GzDecoder::new(archive.files[0].content.as_slice())
+            .read_to_end(&mut tar)
+            .unwrap();
+        assert_eq!(tar.len() % 512, 0);
+        assert!(tar.starts_with(b"manifest.json\0"));
+        let text = String::from_utf8_lossy(&tar);
+        assert!(text.contains("markdown/daan-keep-"));
+        assert!(text.contains("\"object_count\": 2"));
+
+        let out = TempDir::new().unwrap();
+        archive.write_to(out.path()).unwrap();
+        assert!(out.path().join(EXPORT_ARCHIVE_FILE).exists());
+    }
+}
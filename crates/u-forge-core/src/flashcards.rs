@@ -0,0 +1,387 @@
+//! Spaced-repetition flashcards, so a GM remembers their own lore.
+//!
+//! [`KnowledgeGraph::flashcards`] turns canon objects into question/answer
+//! cards on demand:
+//!
+//! - **Relationship** — well-known edge types read as questions ("Who leads
+//!   the Free Traders Alliance?" from `led_by` or `leads`); other types
+//!   become fill-in-the-blank prompts ("Gundren employs …").  Several edges
+//!   of one kind from the same subject make one card with every answer.
+//! - **Property** — short scalar properties ("What is Gundren's race?").
+//! - **Description** — the first sentence of `description` ("Who is
+//!   Sildar?").
+//!
+//! Card ids are stable (`rel:leader:<id>`, `prop:race:<id>`, `desc:<id>`), so
+//! only the review schedule is stored, in `flashcard_reviews`, and it goes
+//! with the subject object.  [`KnowledgeGraph::review_flashcard`] applies
+//! SuperMemo-2 ([`schedule_review`]) to a 0–5 recall grade;
+//! [`KnowledgeGraph::due_flashcards`] lists overdue cards first, then new
+//! ones.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::entity_links::MENTIONS_EDGE;
+use crate::error::{Result, UForgeError};
+use crate::types::{Lifecycle, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Easiness factor of a card never reviewed.
+pub const DEFAULT_EASINESS: f32 = 2.5;
+
+/// SM-2's floor for the easiness factor.
+pub const MIN_EASINESS: f32 = 1.3;
+
+/// Lowest grade that counts as remembered.
+pub const PASSING_GRADE: u8 = 3;
+
+/// Highest recall grade ("perfect response").
+pub const MAX_GRADE: u8 = 5;
+
+/// Longest property value (in characters) asked as a card.
+pub const MAX_PROPERTY_ANSWER_CHARS: usize = 60;
+
+/// Longest description answer, in characters.
+const MAX_DESCRIPTION_ANSWER_CHARS: usize = 200;
+
+/// Edge types that read as a question: `(edge type, subject is the edge's
+/// target, card key, question)`.  Types sharing a key make the same card.
+const EDGE_QUESTIONS: [(&str, bool, &str, &str); 9] = [
+    ("led_by", false, "leader", "Who leads {}?"),
+    ("leads", true, "leader", "Who leads {}?"),
+    ("member_of", false, "member_of", "What is {} a member of?"),
+    ("located_in", false, "location", "Where is {}?"),
+    ("parent_of", true, "parent", "Who is a parent of {}?"),
+    ("child_of", false, "parent", "Who is a parent of {}?"),
+    ("married_to", false, "spouse", "Who is {} married to?"),
+    ("owned_by", false, "owner", "Who owns {}?"),
+    ("reports_to", false, "reports_to", "Who does {} report to?"),
+];
+
+/// Edge types that never make cards.
+const SKIPPED_EDGES: [&str; 1] = [MENTIONS_EDGE];
+
+/// Properties covered elsewhere or too long for a card.
+const SKIPPED_PROPERTIES: [&str; 4] = ["name", "description", "summary", "notes"];
+
+/// Object types asked about with "Who" rather than "What".
+const PERSON_TYPES: [&str; 3] = ["character", "npc", "player"];
+
+/// What a card was generated from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CardSource {
+    Relationship { edge_type: String },
+    Property { key: String },
+    Description,
+}
+
+/// SM-2 schedule of one card.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewState {
+    pub easiness: f32,
+    pub interval_days: u32,
+    /// Successful reviews in a row.
+    pub repetitions: u32,
+    /// Times the card was forgotten after being learned.
+    pub lapses: u32,
+    pub due_at: DateTime<Utc>,
+    pub last_reviewed_at: DateTime<Utc>,
+}
+
+/// A question about one object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub id: String,
+    /// The object the question is about.
+    pub object_id: ObjectId,
+    pub source: CardSource,
+    pub question: String,
+    pub answer: String,
+    /// `None` for a card never reviewed.
+    pub review: Option<ReviewState>,
+}
+
+/// SuperMemo-2: the schedule after grading a recall `grade` (0–5, capped)
+/// at `now`, given the card's `previous` schedule.  A failing grade
+/// restarts the intervals; every grade adjusts the easiness factor.
+pub fn schedule_review(
+    previous: Option<&ReviewState>,
+    grade: u8,
+    now: DateTime<Utc>,
+) -> ReviewState {
+    let grade = grade.min(MAX_GRADE);
+    let (easiness, interval_days, repetitions, lapses) = match previous {
+        Some(p) => (p.easiness, p.interval_days, p.repetitions, p.lapses),
+        None => (DEFAULT_EASINESS, 0, 0, 0),
+    };
+    let miss = f32::from(MAX_GRADE - grade);
+    let easiness = (easiness + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASINESS);
+    let (interval_days, repetitions, lapses) = if grade < PASSING_GRADE {
+        (1, 0, lapses + u32::from(repetitions > 0))
+    } else {
+        let interval = match repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (interval_days as f32 * easiness).round() as u32,
+        };
+        (interval, repetitions + 1, lapses)
+    };
+    ReviewState {
+        easiness,
+        interval_days,
+        repetitions,
+        lapses,
+        due_at: now + Duration::days(i64::from(interval_days)),
+        last_reviewed_at: now,
+    }
+}
+
+fn is_canon(object: &ObjectMetadata) -> bool {
+    matches!(object.lifecycle, None | Some(Lifecycle::Canon))
+}
+
+fn humanize(key: &str) -> String {
+    key.replace('_', " ")
+}
+
+/// A short answer for `value`, or `None` when it does not make a card.
+fn property_answer(value: &Value) -> Option<String> {
+    let answer = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(true) => "yes".to_string(),
+        Value::Bool(false) => "no".to_string(),
+        _ => return None,
+    };
+    let is_reference = ObjectId::parse_str(&answer).is_ok();
+    let fits = answer.chars().count() <= MAX_PROPERTY_ANSWER_CHARS;
+    (!answer.is_empty() && !is_reference && fits).then_some(answer)
+}
+
+/// The first sentence of `text`, cut to [`MAX_DESCRIPTION_ANSWER_CHARS`].
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && text[i + 1..].chars().next().is_none_or(char::is_whitespace)
+        })
+        .map_or(text.len(), |(i, _)| i + 1);
+    let sentence = &text[..end];
+    match sentence.char_indices().nth(MAX_DESCRIPTION_ANSWER_CHARS) {
+        Some((cut, _)) => format!("{}…", sentence[..cut].trim_end()),
+        None => sentence.to_string(),
+    }
+}
+
+impl KnowledgeGraph {
+    /// Every card the canon graph yields, with its review state, by subject
+    /// name.
+    pub fn flashcards(&self) -> Result<Vec<Flashcard>> {
+        let objects: HashMap<ObjectId, ObjectMetadata> = self
+            .get_all_objects()?
+            .into_iter()
+            .filter(is_canon)
+            .map(|o| (o.id, o))
+            .collect();
+        let mut reviews = self.storage.get_flashcard_reviews()?;
+        let mut cards = Vec::new();
+
+        // (card key, subject) → (source edge type, question, answers)
+        let mut relationships: HashMap<(String, ObjectId), (String, String, BTreeSet<String>)> =
+            HashMap::new();
+        for edge in self.get_all_edges()? {
+            let edge_type = edge.edge_type.as_str();
+            if SKIPPED_EDGES.contains(&edge_type) {
+                continue;
+            }
+            let known = EDGE_QUESTIONS.iter().find(|(ty, ..)| *ty == edge_type);
+            let (subject, answer) = match known {
+                Some((_, true, ..)) => (edge.to, edge.from),
+                _ => (edge.from, edge.to),
+            };
+            let (Some(subject), Some(answer)) = (objects.get(&subject), objects.get(&answer))
+            else {
+                continue;
+            };
+            let (key, question) = match known {
+                Some((_, _, key, question)) => {
+                    (key.to_string(), question.replace("{}", &subject.name))
+                }
+                None => (
+                    edge_type.to_string(),
+                    format!("{} {} …", subject.name, humanize(edge_type)),
+                ),
+            };
+            relationships
+                .entry((key, subject.id))
+                .or_insert_with(|| (edge_type.to_string(), question, BTreeSet::new()))
+                .2
+                .insert(answer.name.clone());
+        }
+        for ((key, subject), (edge_type, question, answers)) in relationships {
+            let id = format!("rel:{key}:{subject}");
+            cards.push(Flashcard {
+                review: reviews.remove(&id),
+                id,
+                object_id: subject,
+                source: CardSource::Relationship { edge_type },
+                question,
+                answer: answers.into_iter().collect::<Vec<_>>().join(", "),
+            });
+        }
+
+        for object in objects.values() {
+            let Some(properties) = object.properties.as_object() else {
+                continue;
+            };
+            for (key, value) in properties {
+                if key.starts_with('_') || SKIPPED_PROPERTIES.contains(&key.as_str()) {
+                    continue;
+                }
+                let Some(answer) = property_answer(value) else {
+                    continue;
+                };
+                let id = format!("prop:{key}:{}", object.id);
+                cards.push(Flashcard {
+                    review: reviews.remove(&id),
+                    id,
+                    object_id: object.id,
+                    source: CardSource::Property { key: key.clone() },
+                    question: format!("What is {}'s {}?", object.name, humanize(key)),
+                    answer,
+                });
+            }
+            let description = object.get_property("description").unwrap_or_default();
+            if !description.trim().is_empty() {
+                let who = if PERSON_TYPES.contains(&object.object_type.as_str()) {
+                    "Who"
+                } else {
+                    "What"
+                };
+                let id = format!("desc:{}", object.id);
+                cards.push(Flashcard {
+                    review: reviews.remove(&id),
+                    id,
+                    object_id: object.id,
+                    source: CardSource::Description,
+                    question: format!("{who} is {}?", object.name),
+                    answer: first_sentence(&description),
+                });
+            }
+        }
+
+        cards.sort_by(|a, b| {
+            objects[&a.object_id]
+                .name
+                .cmp(&objects[&b.object_id].name)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Ok(cards)
+    }
+
+    /// Up to `limit` cards to study at `now`: due cards, most overdue
+    /// first, then cards never reviewed.
+    pub fn due_flashcards(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<Flashcard>> {
+        let (mut due, new): (Vec<Flashcard>, Vec<Flashcard>) = self
+            .flashcards()?
+            .into_iter()
+            .filter(|c| c.review.as_ref().is_none_or(|r| r.due_at <= now))
+            .partition(|c| c.review.is_some());
+        due.sort_by_key(|c| c.review.as_ref().map(|r| r.due_at));
+        Ok(due.into_iter().chain(new).take(limit).collect())
+    }
+
+    /// Grade a recall of card `card_id` (0 = blackout … 5 = perfect) at
+    /// `now` and store the new schedule.  Fails with
+    /// [`UForgeError::ValidationFailed`] for a grade above [`MAX_GRADE`] and
+    /// [`UForgeError::NotFound`] for a card the graph no longer yields.
+    pub fn review_flashcard(
+        &self,
+        card_id: &str,
+        grade: u8,
+        now: DateTime<Utc>,
+    ) -> Result<ReviewState> {
+        if grade > MAX_GRADE {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Recall grade must be 0–{MAX_GRADE}, got {grade}"
+            )));
+        }
+        let card = self
+            .flashcards()?
+            .into_iter()
+            .find(|c| c.id == card_id)
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown flashcard '{card_id}'")))?;
+        let state = schedule_review(card.review.as_ref(), grade, now);
+        self.storage
+            .upsert_flashcard_review(&card.id, card.object_id, &state)?;
+        Ok(state)
+    }
+
+    /// Forget card `card_id`'s schedule so it comes back as new.  Returns
+    /// whether it had one.
+    pub fn reset_flashcard(&self, card_id: &str) -> Result<bool> {
+        self.storage.delete_flashcard_review(card_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_flashcards_generate_and_schedule() {
+        let (graph, _temp_dir) = create_test_graph();
+        let halia = ObjectBuilder::character("Halia Thornton".to_string())
+            .with_property("race".to_string(), "Human".to_string())
+            .with_property(
+                "description".to_string(),
+                "Guildmaster of the miners' exchange. Secretly a Zhentarim agent.".to_string(),
+            )
+            .add_to_graph(&graph)
+            .unwrap();
+        let traders = ObjectBuilder::faction("Free Traders Alliance".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(traders, halia, "led_by").unwrap();
+
+        let cards = graph.flashcards().unwrap();
+        let card = |id: &str| cards.iter().find(|c| c.id == id).unwrap();
+        let leader = card(&format!("rel:leader:{traders}"));
+        assert_eq!(leader.question, "Who leads Free Traders Alliance?");
+        assert_eq!(leader.answer, "Halia Thornton");
+        assert_eq!(card(&format!("prop:race:{halia}")).answer, "Human");
+        let who = card(&format!("desc:{halia}"));
+        assert_eq!(who.question, "Who is Halia Thornton?");
+        assert_eq!(who.answer, "Guildmaster of the miners' exchange.");
+
+        let now = Utc::now();
+        let first = graph.review_flashcard(&leader.id, 4, now).unwrap();
+        assert_eq!((first.interval_days, first.repetitions), (1, 1));
+        let second = graph.review_flashcard(&leader.id, 5, first.due_at).unwrap();
+        assert_eq!((second.interval_days, second.repetitions), (6, 2));
+        let forgot = graph.review_flashcard(&leader.id, 1, second.due_at).unwrap();
+        assert_eq!((forgot.interval_days, forgot.repetitions, forgot.lapses), (1, 0, 1));
+        assert!(forgot.easiness < second.easiness && forgot.easiness >= MIN_EASINESS);
+
+        // The reviewed card is not due yet; the new ones are.
+        let due = graph.due_flashcards(second.due_at, 10).unwrap();
+        assert_eq!(due.len(), cards.len() - 1);
+        assert!(due.iter().all(|c| c.review.is_none()));
+        assert_eq!(graph.due_flashcards(forgot.due_at, 1).unwrap()[0].id, leader.id);
+
+        let err = graph.review_flashcard(&leader.id, 6, now).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ValidationFailed);
+        let err = graph.review_flashcard("desc:nobody", 3, now).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+        assert!(graph.reset_flashcard(&leader.id).unwrap());
+        assert!(graph.flashcards().unwrap().iter().all(|c| c.review.is_none()));
+    }
+}
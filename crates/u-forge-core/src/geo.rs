@@ -0,0 +1,282 @@
+//! Coordinates and proximity queries.
+//!
+//! A [`PropertyType::Coordinates`](crate::schema::PropertyType::Coordinates)
+//! value is a JSON object `{"map": "<object id>", "x": 12.5, "y": 40}`.  With
+//! a `map` the point is in that map object's units (pixels, hexes, miles);
+//! without one it is a world position, e.g. longitude/latitude-like `x`/`y`.
+//! Editors may type the text form instead — `12.5, 40` or `12.5, 40 @ Sword
+//! Coast` (a map id or unique object name) — which validation coerces.
+//!
+//! Every coordinate-shaped property is kept in a spatial index, so
+//! [`KnowledgeGraph::find_objects_near`] answers "what is within a day's
+//! travel of the party" without scanning the graph.  Distances are straight
+//! lines in the coordinates' own units.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::{Result, UForgeError};
+use crate::graph::KnowledgeGraphStorage;
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// A position on a map or in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// Straight-line distance to `other`.
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// The value of a coordinates property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    /// Map object the point is relative to; `None` for world coordinates.
+    pub map: Option<ObjectId>,
+    pub point: Point,
+}
+
+impl Coordinates {
+    pub fn new(map: Option<ObjectId>, x: f64, y: f64) -> Self {
+        Self {
+            map,
+            point: Point::new(x, y),
+        }
+    }
+
+    /// Read the stored JSON form.  `None` unless `x` and `y` are numbers and
+    /// `map`, when present, is an object id.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let obj = value.as_object()?;
+        let x = obj.get("x")?.as_f64()?;
+        let y = obj.get("y")?.as_f64()?;
+        let map = match obj.get("map") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(id)) => Some(ObjectId::parse_str(id).ok()?),
+            Some(_) => return None,
+        };
+        Some(Self::new(map, x, y))
+    }
+
+    /// The stored JSON form.
+    pub fn to_value(&self) -> Value {
+        match self.map {
+            Some(map) => {
+                json!({ "map": map.hyphenated().to_string(), "x": self.point.x, "y": self.point.y })
+            }
+            None => json!({ "x": self.point.x, "y": self.point.y }),
+        }
+    }
+
+    /// Split the text form `x, y [@ map]` into the point and the map
+    /// reference, if any.
+    pub fn parse_text(text: &str) -> Option<(Point, Option<&str>)> {
+        let (numbers, map) = match text.split_once('@') {
+            Some((numbers, map)) => (numbers, Some(map.trim()).filter(|m| !m.is_empty())),
+            None => (text, None),
+        };
+        let (x, y) = numbers.split_once(',')?;
+        let x: f64 = x.trim().parse().ok()?;
+        let y: f64 = y.trim().parse().ok()?;
+        (x.is_finite() && y.is_finite()).then_some((Point::new(x, y), map))
+    }
+}
+
+/// The text form: `x, y`, plus ` @ <map id>` for map coordinates.
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}", self.point.x, self.point.y)?;
+        if let Some(map) = self.map {
+            write!(f, " @ {map}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the text form, resolving the map reference as an object id or the
+/// name of exactly one object.
+pub(crate) fn resolve_coordinates(
+    storage: &KnowledgeGraphStorage,
+    text: &str,
+) -> Option<Coordinates> {
+    let (point, map) = Coordinates::parse_text(text)?;
+    let map = match map {
+        None => None,
+        Some(reference) => match ObjectId::parse_str(reference) {
+            Ok(id) => Some(id),
+            Err(_) => match storage.find_nodes_by_name_only(reference).ok()?.as_slice() {
+                [only] => Some(only.id),
+                _ => return None,
+            },
+        },
+    };
+    Some(Coordinates { map, point })
+}
+
+/// An object with a coordinates property near the searched point, from
+/// [`KnowledgeGraph::find_objects_near`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NearbyObject {
+    pub object_id: ObjectId,
+    pub object_name: String,
+    pub object_type: String,
+    pub property: String,
+    pub point: Point,
+    pub distance: f64,
+}
+
+impl KnowledgeGraph {
+    /// Objects with a coordinates property on `map_id` (`None` for world
+    /// coordinates) no further than `radius` from `point`, nearest first.
+    /// An object appears once per matching property.
+    pub fn find_objects_near(
+        &self,
+        map_id: Option<ObjectId>,
+        point: Point,
+        radius: f64,
+    ) -> Result<Vec<NearbyObject>> {
+        if !(radius >= 0.0 && radius.is_finite()) {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Search radius must be a non-negative number, got {radius}"
+            )));
+        }
+        let mut out = Vec::new();
+        for (id, property, found, distance) in
+            self.storage.find_coordinates_near(map_id, point, radius)?
+        {
+            let Some(object) = self.get_object(id)? else {
+                continue;
+            };
+            out.push(NearbyObject {
+                object_id: id,
+                object_name: object.name,
+                object_type: object.object_type,
+                property,
+                point: found,
+                distance,
+            });
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::schema::{ObjectTypeSchema, PropertySchema};
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+
+    #[test]
+    fn test_coordinates_value_and_text_forms() {
+        let map = ObjectId::new_v4();
+        let coords = Coordinates::new(Some(map), 12.5, -4.0);
+        assert_eq!(Coordinates::from_value(&coords.to_value()), Some(coords));
+        assert_eq!(coords.to_string(), format!("12.5, -4 @ {map}"));
+        assert_eq!(
+            Coordinates::parse_text(&coords.to_string()),
+            Some((Point::new(12.5, -4.0), Some(map.to_string().as_str())))
+        );
+        assert_eq!(
+            Coordinates::parse_text(" 3,4 "),
+            Some((Point::new(3.0, 4.0), None))
+        );
+        assert_eq!(Coordinates::parse_text("north"), None);
+        assert_eq!(Coordinates::from_value(&json!({ "x": 1, "y": "2" })), None);
+        assert_eq!(
+            Coordinates::from_value(&json!({ "map": "nope", "x": 1, "y": 2 })),
+            None
+        );
+        assert_eq!(Point::new(0.0, 0.0).distance(&Point::new(3.0, 4.0)), 5.0);
+    }
+
+    #[tokio::test]
+    async fn test_find_objects_near() {
+        let (graph, _temp_dir) = create_test_graph();
+        let map = graph
+            .add_object(ObjectMetadata::new(
+                "map".to_string(),
+                "Sword Coast".to_string(),
+            ))
+            .unwrap();
+
+        let town = ObjectTypeSchema::new("town".to_string(), "A town".to_string())
+            .with_property("location".to_string(), PropertySchema::coordinates("Where"));
+        graph.register_object_type("town", town).await.unwrap();
+        graph
+            .get_schema_manager()
+            .load_schema("default")
+            .await
+            .unwrap();
+        let mut props = json!({ "location": "10, 10 @ Sword Coast" })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(graph
+            .validate_and_coerce_properties("town", &mut props)
+            .is_empty());
+        assert_eq!(
+            Coordinates::from_value(&props["location"]),
+            Some(Coordinates::new(Some(map), 10.0, 10.0))
+        );
+
+        let place = |name: &str, coords: Coordinates| {
+            graph
+                .add_object(
+                    ObjectMetadata::new("town".to_string(), name.to_string())
+                        .with_json_property("location".to_string(), coords.to_value()),
+                )
+                .unwrap()
+        };
+        place("Waterdeep", Coordinates::new(Some(map), 10.0, 10.0));
+        place("Daggerford", Coordinates::new(Some(map), 13.0, 14.0));
+        let neverwinter = place("Neverwinter", Coordinates::new(Some(map), 10.0, 40.0));
+        place("Elsewhere", Coordinates::new(None, 11.0, 11.0));
+
+        let near = graph
+            .find_objects_near(Some(map), Point::new(10.0, 10.0), 5.0)
+            .unwrap();
+        let names: Vec<&str> = near.iter().map(|n| n.object_name.as_str()).collect();
+        assert_eq!(names, ["Waterdeep", "Daggerford"]);
+        assert_eq!(near[1].distance, 5.0);
+
+        // Moving an object updates the index.
+        graph
+            .storage
+            .set_node_property(
+                neverwinter,
+                "location",
+                &Coordinates::new(Some(map), 9.0, 9.0).to_value(),
+            )
+            .unwrap();
+        let near = graph
+            .find_objects_near(Some(map), Point::new(10.0, 10.0), 2.0)
+            .unwrap();
+        let names: Vec<&str> = near.iter().map(|n| n.object_name.as_str()).collect();
+        assert_eq!(names, ["Waterdeep", "Neverwinter"]);
+
+        let world = graph
+            .find_objects_near(None, Point::new(10.0, 10.0), 2.0)
+            .unwrap();
+        assert_eq!(world.len(), 1);
+        assert_eq!(world[0].object_name, "Elsewhere");
+
+        let err = graph
+            .find_objects_near(Some(map), Point::new(0.0, 0.0), -1.0)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    }
+}
@@ -0,0 +1,323 @@
+//! Project glossary — in-world synonyms and abbreviations.
+//!
+//! Campaigns grow their own vocabulary: "the Company" means the Fellowship of
+//! the Ring, "MT" means Minas Tirith.  Each [`GlossaryEntry`] maps a term to
+//! its expansion and, optionally, to the object it names.  Entries live in
+//! the `glossary` table (see `graph/glossary.rs`) and feed three consumers:
+//!
+//! - **Mention detection** — [`KnowledgeGraph::detect_mentions`] finds object
+//!   names *and* glossary terms in free text and resolves them to objects.
+//! - **Query expansion** — [`preprocess_query`](crate::preprocess_query)
+//!   merges [`Glossary::synonym_map`] into its synonyms, so searching for
+//!   "the company" also matches chunks that say "Fellowship of the Ring".
+//! - **AI prompts** — [`Glossary::prompt_section`] is appended to the agent's
+//!   system prompt so extracted objects use canonical names.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// One glossary mapping.  `term` is unique per project, compared
+/// case-insensitively.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    /// The synonym or abbreviation as players write it (e.g. `"the Company"`).
+    pub term: String,
+    /// What it stands for (e.g. `"Fellowship of the Ring"`).
+    pub expansion: String,
+    /// The object this term refers to, if any.  Cleared when the object is
+    /// deleted.
+    pub object_id: Option<ObjectId>,
+    /// Free-form note shown alongside the entry.
+    pub note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl GlossaryEntry {
+    pub fn new(term: impl Into<String>, expansion: impl Into<String>) -> Self {
+        Self {
+            term: term.into(),
+            expansion: expansion.into(),
+            object_id: None,
+            note: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Link the entry to the object it names.
+    pub fn with_object(mut self, object_id: ObjectId) -> Self {
+        self.object_id = Some(object_id);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// All glossary entries of a project, ordered by term.
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[GlossaryEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry for `term`, compared case-insensitively.
+    pub fn lookup(&self, term: &str) -> Option<&GlossaryEntry> {
+        let term = term.trim().to_lowercase();
+        self.entries.iter().find(|e| e.term.to_lowercase() == term)
+    }
+
+    /// Lowercase term/expansion → alternatives, in both directions, for
+    /// [`QueryPreprocessing::synonyms`](crate::QueryPreprocessing::synonyms).
+    pub fn synonym_map(&self) -> HashMap<String, Vec<String>> {
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for e in &self.entries {
+            for (from, to) in [(&e.term, &e.expansion), (&e.expansion, &e.term)] {
+                let alts = map.entry(from.to_lowercase()).or_default();
+                if !alts.iter().any(|a| a.eq_ignore_ascii_case(to)) {
+                    alts.push(to.clone());
+                }
+            }
+        }
+        map
+    }
+
+    /// Markdown block listing every entry, for LLM system prompts.  Empty
+    /// when the glossary is empty.
+    pub fn prompt_section(&self) -> String {
+        if self.entries.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from(
+            "## Glossary\n\nThis campaign uses these synonyms and abbreviations. \
+             Use the expansion when naming objects.\n",
+        );
+        for e in &self.entries {
+            out.push_str(&format!("\n- **{}** → {}", e.term, e.expansion));
+            if let Some(note) = &e.note {
+                out.push_str(&format!(" ({note})"));
+            }
+        }
+        out
+    }
+}
+
+/// An object reference found in free text by
+/// [`KnowledgeGraph::detect_mentions`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mention {
+    /// Byte range of the match in the input text.
+    pub start: usize,
+    pub end: usize,
+    /// The object referred to.
+    pub object_id: ObjectId,
+    /// The glossary term that matched, or `None` for a direct name match.
+    pub via_term: Option<String>,
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// The project's glossary, ordered by term.
+    pub fn glossary(&self) -> Result<Glossary> {
+        Ok(Glossary::new(self.storage.list_glossary_entries()?))
+    }
+
+    /// Add or replace the entry for `entry.term`.
+    ///
+    /// Errors when the term or expansion is blank, or when `object_id` does
+    /// not exist.
+    pub fn upsert_glossary_entry(&self, entry: GlossaryEntry) -> Result<()> {
+        if entry.term.trim().is_empty() || entry.expansion.trim().is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "Glossary term and expansion must not be empty".to_string(),
+            ));
+        }
+        if let Some(id) = entry.object_id {
+            if self.get_object(id)?.is_none() {
+                let message = format!("Glossary entry refers to unknown object {id}");
+                return Err(UForgeError::NotFound(message));
+            }
+        }
+        self.storage.upsert_glossary_entry(&entry)
+    }
+
+    /// Remove the entry for `term`.  Returns `false` if there was none.
+    pub fn delete_glossary_entry(&self, term: &str) -> Result<bool> {
+        self.storage.delete_glossary_entry(term)
+    }
+
+    /// Find references to objects in `text` — exact object names and glossary
+    /// terms, matched case-insensitively (ASCII) on word boundaries.
+    ///
+    /// A glossary term resolves to its linked object, or else to the object
+    /// whose name equals its expansion; unresolvable terms are skipped.
+    /// Longer matches win over shorter ones starting at the same place, and
+    /// mentions never overlap.  Returned in text order.
+    pub fn detect_mentions(&self, text: &str) -> Result<Vec<Mention>> {
+        // (needle, object, via term), longest needle first.
+        let mut needles: Vec<(String, ObjectId, Option<String>)> = self
+            .storage
+            .list_node_names()?
+            .into_iter()
+            .map(|(id, name)| (name, id, None))
+            .collect();
+        for e in self.glossary()?.entries {
+            let target = match e.object_id {
+                Some(id) => Some(id),
+                None => self
+                    .find_by_name_only(&e.expansion)?
+                    .first()
+                    .map(|o| o.id),
+            };
+            if let Some(id) = target {
+                needles.push((e.term.clone(), id, Some(e.term)));
+            }
+        }
+        needles.retain(|(n, ..)| !n.trim().is_empty());
+        needles.sort_by_key(|n| std::cmp::Reverse(n.0.len()));
+
+        let bytes = text.as_bytes();
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while pos < text.len() {
+            let at_word_start = text.is_char_boundary(pos)
+                && !text[..pos].chars().next_back().is_some_and(char::is_alphanumeric);
+            let found = at_word_start
+                .then(|| {
+                    needles.iter().find(|(needle, ..)| {
+                        let end = pos + needle.len();
+                        end <= text.len()
+                            && text.is_char_boundary(end)
+                            && bytes[pos..end].eq_ignore_ascii_case(needle.as_bytes())
+                            && !text[end..].chars().next().is_some_and(char::is_alphanumeric)
+                    })
+                })
+                .flatten();
+            match found {
+                Some((needle, id, via)) => {
+                    out.push(Mention {
+                        start: pos,
+                        end: pos + needle.len(),
+                        object_id: *id,
+                        via_term: via.clone(),
+                    });
+                    pos += needle.len();
+                }
+                None => pos += 1,
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_glossary_crud_and_synonyms() {
+        let (graph, _tmp) = create_test_graph();
+        assert!(graph.glossary().unwrap().is_empty());
+
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("the Company", "Fellowship of the Ring"))
+            .unwrap();
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("MT", "Minas Tirith").with_note("city"))
+            .unwrap();
+        // Same term, different case → replaces.
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("THE COMPANY", "The Fellowship"))
+            .unwrap();
+        assert!(graph
+            .upsert_glossary_entry(GlossaryEntry::new("  ", "x"))
+            .is_err());
+
+        let glossary = graph.glossary().unwrap();
+        assert_eq!(glossary.entries().len(), 2);
+        assert_eq!(glossary.lookup("the company").unwrap().expansion, "The Fellowship");
+
+        let synonyms = glossary.synonym_map();
+        assert_eq!(synonyms["mt"], vec!["Minas Tirith".to_string()]);
+        assert_eq!(synonyms["minas tirith"], vec!["MT".to_string()]);
+        assert!(glossary.prompt_section().contains("**MT** → Minas Tirith (city)"));
+
+        assert!(graph.delete_glossary_entry("mt").unwrap());
+        assert!(!graph.delete_glossary_entry("mt").unwrap());
+    }
+
+    #[test]
+    fn test_detect_mentions_names_and_terms() {
+        let (graph, _tmp) = create_test_graph();
+        let city = ObjectBuilder::location("Minas Tirith".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let fellowship = ObjectBuilder::faction("Fellowship of the Ring".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("MT", "Minas Tirith"))
+            .unwrap();
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("the Company", "x").with_object(fellowship))
+            .unwrap();
+
+        let text = "The company rode to MT; minas tirith was quiet. MTs are unrelated.";
+        let mentions = graph.detect_mentions(text).unwrap();
+        let found: Vec<(&str, ObjectId, Option<&str>)> = mentions
+            .iter()
+            .map(|m| (&text[m.start..m.end], m.object_id, m.via_term.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("The company", fellowship, Some("the Company")),
+                ("MT", city, Some("MT")),
+                ("minas tirith", city, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glossary_entry_cleared_with_object() {
+        let (graph, _tmp) = create_test_graph();
+        let id = ObjectBuilder::character("Gandalf".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .upsert_glossary_entry(GlossaryEntry::new("Mithrandir", "Gandalf").with_object(id))
+            .unwrap();
+        assert!(graph
+            .upsert_glossary_entry(
+                GlossaryEntry::new("Stormcrow", "Gandalf").with_object(ObjectId::new_v4())
+            )
+            .is_err());
+
+        graph.delete_object(id).unwrap();
+        let glossary = graph.glossary().unwrap();
+        assert_eq!(glossary.lookup("mithrandir").unwrap().object_id, None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Persistence for object archival.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use crate::error::Result;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Mark `id` archived as of `at`.  Returns `false` when it already was,
+    /// keeping the original time.
+    pub fn archive_node(&self, id: ObjectId, at: DateTime<Utc>) -> Result<bool> {
+        let conn = self.conn.lock();
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO archived_objects (object_id, archived_at) VALUES (?1, ?2)",
+                params![id.hyphenated().to_string(), at.to_rfc3339()],
+            )
+            .context("Failed to archive object")?;
+        Ok(inserted > 0)
+    }
+
+    /// Take `id` out of the archive.  Returns whether it was archived.
+    pub fn unarchive_node(&self, id: ObjectId) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute(
+                "DELETE FROM archived_objects WHERE object_id = ?1",
+                params![id.hyphenated().to_string()],
+            )
+            .context("Failed to unarchive object")?;
+        Ok(deleted > 0)
+    }
+
+    /// Every archived object with when it was archived.
+    pub fn archived_nodes(&self) -> Result<HashMap<ObjectId, DateTime<Utc>>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT object_id, archived_at FROM archived_objects")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = HashMap::new();
+        for row in rows {
+            let (id, at) = row?;
+            let id = ObjectId::parse_str(&id)
+                .with_context(|| format!("Invalid object id in archived_objects: '{id}'"))?;
+            let at = DateTime::parse_from_rfc3339(&at)
+                .with_context(|| format!("Invalid archived_at: '{at}'"))?
+                .with_timezone(&Utc);
+            out.insert(id, at);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,127 @@
+//! Persistence for project branches.
+//!
+//! One row per branch in the `branches` table; the overlay and its base
+//! timestamps are stored as JSON.  `name` is the primary key with
+//! `COLLATE NOCASE`.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use crate::branches::Branch;
+use crate::error::Result;
+use crate::types::{Edge, EdgeType, ObjectId, ObjectMetadata};
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Insert a new branch.  Returns `false` (and changes nothing) when a
+    /// branch with the same name already exists.
+    pub fn insert_branch(&self, branch: &Branch) -> Result<bool> {
+        let (layer, bases) = branch_json(branch)?;
+        let conn = self.conn.lock();
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO branches (name, created_at, layer, bases)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![branch.name, branch.created_at.to_rfc3339(), layer, bases],
+            )
+            .context("Failed to insert branch")?;
+        Ok(inserted > 0)
+    }
+
+    /// Overwrite a branch's overlay and bases.  Returns `false` if the branch
+    /// does not exist.
+    pub fn update_branch(&self, branch: &Branch) -> Result<bool> {
+        let (layer, bases) = branch_json(branch)?;
+        let conn = self.conn.lock();
+        let updated = conn
+            .execute(
+                "UPDATE branches SET layer = ?2, bases = ?3 WHERE name = ?1",
+                params![branch.name, layer, bases],
+            )
+            .context("Failed to update branch")?;
+        Ok(updated > 0)
+    }
+
+    /// Apply a branch merge to the main graph and overwrite `branch` with
+    /// what is left of it, in one transaction.
+    pub fn merge_branch_staged(
+        &self,
+        nodes: &[ObjectMetadata],
+        edges: &[Edge],
+        deleted_nodes: &[ObjectId],
+        deleted_edges: &[(ObjectId, ObjectId, EdgeType)],
+        branch: &Branch,
+    ) -> Result<()> {
+        let (layer, bases) = branch_json(branch)?;
+        self.apply_staged_with(nodes, edges, deleted_nodes, deleted_edges, |conn| {
+            conn.execute(
+                "UPDATE branches SET layer = ?2, bases = ?3 WHERE name = ?1",
+                params![branch.name, layer, bases],
+            )
+            .context("Failed to update merged branch")?;
+            Ok(())
+        })
+    }
+
+    /// The branch named `name` (ignoring case), if any.
+    pub fn get_branch(&self, name: &str) -> Result<Option<Branch>> {
+        let conn = self.conn.lock();
+        let row = conn
+            .query_row(
+                "SELECT name, created_at, layer, bases FROM branches WHERE name = ?1",
+                params![name],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .context("Failed to load branch")?;
+
+        let Some((name, created_at, layer, bases)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(Branch {
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                .with_context(|| format!("Invalid branch created_at: '{created_at}'"))?
+                .with_timezone(&chrono::Utc),
+            layer: serde_json::from_str(&layer)
+                .with_context(|| format!("Invalid layer JSON for branch '{name}'"))?,
+            bases: serde_json::from_str(&bases)
+                .with_context(|| format!("Invalid bases JSON for branch '{name}'"))?,
+            name,
+        }))
+    }
+
+    /// Names of all branches, ordered (ignoring case).
+    pub fn list_branch_names(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT name FROM branches ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()
+            .context("Failed to list branches")?;
+        Ok(names)
+    }
+
+    /// Delete the branch named `name`.  Returns `false` if there was none.
+    pub fn delete_branch(&self, name: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute("DELETE FROM branches WHERE name = ?1", params![name])
+            .context("Failed to delete branch")?;
+        Ok(deleted > 0)
+    }
+}
+
+fn branch_json(branch: &Branch) -> Result<(String, String)> {
+    Ok((
+        serde_json::to_string(&branch.layer).context("Failed to serialise branch layer")?,
+        serde_json::to_string(&branch.bases).context("Failed to serialise branch bases")?,
+    ))
+}
@@ -0,0 +1,373 @@
+//! Size-bounded LRU cache of decoded nodes and chunk lists.
+//!
+//! Subgraph queries touch hub nodes over and over; without a cache each
+//! touch re-reads the row and re-parses its properties JSON, UUIDs, and
+//! timestamps.  [`get_node_shared`](KnowledgeGraphStorage::get_node_shared)
+//! and [`get_chunks_shared`](KnowledgeGraphStorage::get_chunks_shared) hand
+//! out `Arc`s to decoded values instead, and the owned-value getters clone
+//! from them.
+//!
+//! Invalidation is by epoch: triggers on `nodes` and `chunks` bump the single
+//! row of `storage_epoch` on every insert, update, and delete — from any
+//! write path and any connection, including another process — and a lookup
+//! that sees a new epoch empties the cache first.  Writes are rare next to
+//! reads, so flushing everything is cheaper than tracking what changed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Arc;
+
+use anyhow::Context;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::types::{ObjectId, ObjectMetadata, TextChunk};
+
+use super::storage::KnowledgeGraphStorage;
+
+/// Default byte budget of the read cache (64 MiB).
+pub const DEFAULT_READ_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Counters from [`KnowledgeGraphStorage::read_cache_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ReadCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Cached nodes plus cached chunk lists.
+    pub entries: usize,
+    /// Approximate bytes held.
+    pub bytes: usize,
+    pub capacity: usize,
+}
+
+/// Least-recently-used map bounded by the summed size of its values.
+struct Lru<K, V> {
+    entries: HashMap<K, (V, usize, u64)>,
+    /// Last-use tick → key, oldest first.
+    order: BTreeMap<u64, K>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K, tick: u64) -> Option<V> {
+        let (value, _, last_used) = self.entries.get_mut(key)?;
+        self.order.remove(last_used);
+        *last_used = tick;
+        self.order.insert(tick, key.clone());
+        Some(value.clone())
+    }
+
+    /// Insert and return the change in held bytes.
+    fn insert(&mut self, key: K, value: V, bytes: usize, tick: u64) -> isize {
+        let old = self.remove(&key);
+        self.entries.insert(key.clone(), (value, bytes, tick));
+        self.order.insert(tick, key);
+        bytes as isize - old as isize
+    }
+
+    fn remove(&mut self, key: &K) -> usize {
+        match self.entries.remove(key) {
+            Some((_, bytes, tick)) => {
+                self.order.remove(&tick);
+                bytes
+            }
+            None => 0,
+        }
+    }
+
+    fn oldest(&self) -> Option<(u64, K)> {
+        self.order.iter().next().map(|(t, k)| (*t, k.clone()))
+    }
+}
+
+struct CacheState {
+    epoch: i64,
+    nodes: Lru<ObjectId, Arc<ObjectMetadata>>,
+    chunks: Lru<ObjectId, Arc<[TextChunk]>>,
+    tick: u64,
+    bytes: usize,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+/// The read cache held by [`KnowledgeGraphStorage`].
+pub(crate) struct ReadCache {
+    state: Mutex<CacheState>,
+}
+
+impl ReadCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState {
+                epoch: -1,
+                nodes: Lru::new(),
+                chunks: Lru::new(),
+                tick: 0,
+                bytes: 0,
+                capacity,
+                hits: 0,
+                misses: 0,
+            }),
+        }
+    }
+}
+
+impl CacheState {
+    fn clear(&mut self) {
+        self.nodes = Lru::new();
+        self.chunks = Lru::new();
+        self.bytes = 0;
+    }
+
+    /// Empty the cache if the database changed since it was filled.
+    fn sync(&mut self, epoch: i64) {
+        if self.epoch != epoch {
+            self.clear();
+            self.epoch = epoch;
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn record(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+
+    fn add_bytes(&mut self, delta: isize) {
+        self.bytes = self.bytes.saturating_add_signed(delta);
+        // Evict whichever of the two maps holds the older entry.
+        while self.bytes > self.capacity {
+            let node = self.nodes.oldest();
+            let chunk = self.chunks.oldest();
+            let freed = match (node, chunk) {
+                (Some((nt, nk)), Some((ct, _))) if nt < ct => self.nodes.remove(&nk),
+                (_, Some((_, ck))) => self.chunks.remove(&ck),
+                (Some((_, nk)), None) => self.nodes.remove(&nk),
+                (None, None) => break,
+            };
+            self.bytes = self.bytes.saturating_sub(freed);
+        }
+    }
+}
+
+fn node_bytes(node: &ObjectMetadata) -> usize {
+    // Properties dominate; the JSON text length is a fair proxy.
+    std::mem::size_of::<ObjectMetadata>()
+        + node.name.len()
+        + node.object_type.len()
+        + node.properties.to_string().len()
+}
+
+fn chunks_bytes(chunks: &[TextChunk]) -> usize {
+    chunks
+        .iter()
+        .map(|c| std::mem::size_of::<TextChunk>() + c.content.len())
+        .sum()
+}
+
+/// Current value of the write epoch.
+pub(super) fn storage_epoch(conn: &Connection) -> Result<i64> {
+    Ok(conn
+        .query_row("SELECT value FROM storage_epoch WHERE id = 0", [], |row| {
+            row.get(0)
+        })
+        .optional()
+        .context("Failed to read storage epoch")?
+        .unwrap_or(0))
+}
+
+impl KnowledgeGraphStorage {
+    /// The node with `id`, shared from the read cache when possible.
+    pub fn get_node_shared(&self, id: ObjectId) -> Result<Option<Arc<ObjectMetadata>>> {
+        let epoch = storage_epoch(&self.conn.lock())?;
+        {
+            let mut state = self.cache.state.lock();
+            state.sync(epoch);
+            let tick = state.next_tick();
+            let cached = state.nodes.get(&id, tick);
+            state.record(cached.is_some());
+            if cached.is_some() {
+                return Ok(cached);
+            }
+        }
+        let Some(node) = self.read_node(id)? else {
+            return Ok(None);
+        };
+        let node = Arc::new(node);
+        let mut state = self.cache.state.lock();
+        if state.epoch == epoch && state.capacity > 0 {
+            let tick = state.next_tick();
+            let delta = state
+                .nodes
+                .insert(id, node.clone(), node_bytes(&node), tick);
+            state.add_bytes(delta);
+        }
+        Ok(Some(node))
+    }
+
+    /// The chunks of node `id` in storage order, shared from the read cache
+    /// when possible.
+    pub fn get_chunks_shared(&self, id: ObjectId) -> Result<Arc<[TextChunk]>> {
+        let epoch = storage_epoch(&self.conn.lock())?;
+        {
+            let mut state = self.cache.state.lock();
+            state.sync(epoch);
+            let tick = state.next_tick();
+            let cached = state.chunks.get(&id, tick);
+            state.record(cached.is_some());
+            if let Some(chunks) = cached {
+                return Ok(chunks);
+            }
+        }
+        let chunks: Arc<[TextChunk]> = self.read_chunks_for_node(id)?.into();
+        let mut state = self.cache.state.lock();
+        if state.epoch == epoch && state.capacity > 0 {
+            let tick = state.next_tick();
+            let delta = state
+                .chunks
+                .insert(id, chunks.clone(), chunks_bytes(&chunks), tick);
+            state.add_bytes(delta);
+        }
+        Ok(chunks)
+    }
+
+    /// Call `f` with node `id`'s raw properties JSON, borrowed straight from
+    /// SQLite's row buffer — no `String` copy, no parse.  Returns `None` for
+    /// an unknown id.  Useful for scanning many nodes for one key.
+    pub fn with_node_properties<R>(
+        &self,
+        id: ObjectId,
+        f: impl FnOnce(&str) -> R,
+    ) -> Result<Option<R>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn
+            .prepare_cached("SELECT properties FROM nodes WHERE id = ?1")
+            .context("Failed to prepare properties query")?;
+        let mut rows = stmt.query(params![id.hyphenated().to_string()])?;
+        match rows.next()? {
+            Some(row) => {
+                let text = row
+                    .get_ref(0)?
+                    .as_str()
+                    .context("Node properties are not text")?;
+                Ok(Some(f(text)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Set the read cache's byte budget; `0` disables caching.  Shrinking
+    /// evicts immediately.
+    pub fn set_read_cache_capacity(&self, bytes: usize) {
+        let mut state = self.cache.state.lock();
+        state.capacity = bytes;
+        state.add_bytes(0);
+    }
+
+    pub fn read_cache_stats(&self) -> ReadCacheStats {
+        let state = self.cache.state.lock();
+        ReadCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.nodes.entries.len() + state.chunks.entries.len(),
+            bytes: state.bytes,
+            capacity: state.capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChunkType;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_cache_hits_and_invalidates_on_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = KnowledgeGraphStorage::new(temp_dir.path()).unwrap();
+        let node = ObjectMetadata::new("location".to_string(), "Neverwinter".to_string())
+            .with_property("ruler".to_string(), "Dagult".to_string());
+        let id = node.id;
+        storage.upsert_node(node).unwrap();
+        storage
+            .upsert_chunk(TextChunk::new(
+                id,
+                "Jewel of the North".to_string(),
+                ChunkType::Description,
+            ))
+            .unwrap();
+
+        let first = storage.get_node_shared(id).unwrap().unwrap();
+        let second = storage.get_node_shared(id).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(storage.get_chunks_shared(id).unwrap().len(), 1);
+        assert_eq!(storage.get_chunks_shared(id).unwrap().len(), 1);
+        let stats = storage.read_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+
+        // Any write, by any path, invalidates.
+        storage
+            .set_node_property(id, "ruler", &serde_json::json!("Neverember"))
+            .unwrap();
+        let updated = storage.get_node(id).unwrap().unwrap();
+        assert_eq!(updated.get_property("ruler").as_deref(), Some("Neverember"));
+        assert_eq!(
+            storage
+                .with_node_properties(id, |json| json.contains("Neverember"))
+                .unwrap(),
+            Some(true)
+        );
+
+        storage.set_read_cache_capacity(0);
+        assert_eq!(storage.read_cache_stats().entries, 0);
+        storage.get_node_shared(id).unwrap().unwrap();
+        assert_eq!(storage.read_cache_stats().entries, 0);
+    }
+
+    #[test]
+    fn test_read_cache_evicts_least_recently_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = KnowledgeGraphStorage::new(temp_dir.path()).unwrap();
+        let ids: Vec<ObjectId> = ["Phandalin", "Thundertree", "Cragmaw"]
+            .into_iter()
+            .map(|name| {
+                let node = ObjectMetadata::new("location".to_string(), name.to_string());
+                let id = node.id;
+                storage.upsert_node(node).unwrap();
+                id
+            })
+            .collect();
+        let one = node_bytes(&storage.read_node(ids[0]).unwrap().unwrap());
+        storage.set_read_cache_capacity(one * 2 + one / 2);
+
+        storage.get_node_shared(ids[0]).unwrap();
+        storage.get_node_shared(ids[1]).unwrap();
+        storage.get_node_shared(ids[0]).unwrap();
+        // Over budget: the least recently used (ids[1]) goes.
+        storage.get_node_shared(ids[2]).unwrap();
+        assert_eq!(storage.read_cache_stats().entries, 2);
+        let hits = storage.read_cache_stats().hits;
+        storage.get_node_shared(ids[0]).unwrap();
+        storage.get_node_shared(ids[2]).unwrap();
+        assert_eq!(storage.read_cache_stats().hits, hits + 2);
+        storage.get_node_shared(ids[1]).unwrap();
+        assert_eq!(storage.read_cache_stats().hits, hits + 2);
+    }
+}
@@ -0,0 +1,61 @@
+//! Persistence for canonical-format imports.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::canonical::CanonicalGraph;
+use crate::error::Result;
+use crate::events::GraphEvent;
+
+use super::chunks::write_chunk;
+use super::edges::write_edge;
+use super::nodes::write_node;
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Write every record of `graph` as given — ids, timestamps and
+    /// revision numbers included — in one transaction.  Existing rows with
+    /// the same ids are overwritten; new objects are announced as
+    /// [`GraphEvent::ObjectCreated`] after the commit.
+    pub fn import_canonical(&self, graph: &CanonicalGraph) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut created = Vec::new();
+        for object in &graph.objects {
+            if write_node(&tx, object)? {
+                created.push(GraphEvent::object_created(object));
+            }
+        }
+        for edge in &graph.edges {
+            write_edge(&tx, edge)?;
+        }
+        for chunk in &graph.chunks {
+            write_chunk(&tx, chunk)?;
+        }
+        for revision in graph.revisions.iter().flatten() {
+            tx.execute(
+                "INSERT INTO chunk_revisions
+                     (id, chunk_id, content, content_hash, author, revised_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(id) DO UPDATE SET
+                     chunk_id     = excluded.chunk_id,
+                     content      = excluded.content,
+                     content_hash = excluded.content_hash,
+                     author       = excluded.author,
+                     revised_at   = excluded.revised_at",
+                params![
+                    revision.revision,
+                    revision.chunk_id.hyphenated().to_string(),
+                    revision.content,
+                    revision.content_hash,
+                    revision.author,
+                    revision.revised_at.to_rfc3339(),
+                ],
+            )
+            .context("Failed to import chunk revision")?;
+        }
+        tx.commit().context("Failed to commit canonical import")?;
+        created.into_iter().for_each(|event| self.emit(event));
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Persistence for inline entity links in chunk text.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use crate::entity_links::ChunkLink;
+use crate::error::Result;
+use crate::types::{ChunkId, ObjectId};
+
+use super::storage::KnowledgeGraphStorage;
+
+type LinkRow = (String, i64, i64, String, Option<String>);
+
+fn row_to_link((chunk_id, start, end, target, object_id): LinkRow) -> Result<ChunkLink> {
+    Ok(ChunkLink {
+        chunk_id: ChunkId::parse_str(&chunk_id)
+            .with_context(|| format!("Invalid chunk id in chunk_links: '{chunk_id}'"))?,
+        start: start as usize,
+        end: end as usize,
+        target,
+        object_id: object_id
+            .map(|id| {
+                ObjectId::parse_str(&id)
+                    .with_context(|| format!("Invalid object id in chunk_links: '{id}'"))
+            })
+            .transpose()?,
+    })
+}
+
+impl KnowledgeGraphStorage {
+    /// The owning object and text of a chunk, or `None` for an unknown id.
+    pub fn get_chunk_owner_and_content(
+        &self,
+        chunk_id: ChunkId,
+    ) -> Result<Option<(ObjectId, String)>> {
+        let conn = self.conn.lock();
+        let row = conn
+            .query_row(
+                "SELECT object_id, content FROM chunks WHERE id = ?1",
+                params![chunk_id.hyphenated().to_string()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()
+            .context("Failed to read chunk")?;
+        row.map(|(id, content)| {
+            let id = ObjectId::parse_str(&id)
+                .with_context(|| format!("Invalid object id in chunks: '{id}'"))?;
+            Ok((id, content))
+        })
+        .transpose()
+    }
+
+    /// Replace every stored link of `chunk_id` with `links`.
+    pub fn replace_chunk_links(&self, chunk_id: ChunkId, links: &[ChunkLink]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let id = chunk_id.hyphenated().to_string();
+        tx.execute("DELETE FROM chunk_links WHERE chunk_id = ?1", params![id])
+            .context("Failed to clear chunk links")?;
+        for link in links {
+            tx.execute(
+                "INSERT INTO chunk_links (chunk_id, start_byte, end_byte, target, object_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id,
+                    link.start as i64,
+                    link.end as i64,
+                    link.target,
+                    link.object_id.map(|o| o.hyphenated().to_string()),
+                ],
+            )
+            .context("Failed to store chunk link")?;
+        }
+        tx.commit().context("Failed to commit chunk links")?;
+        Ok(())
+    }
+
+    /// The stored links of `chunk_id`, in text order.
+    pub fn get_chunk_links(&self, chunk_id: ChunkId) -> Result<Vec<ChunkLink>> {
+        self.query_links(
+            "SELECT chunk_id, start_byte, end_byte, target, object_id
+             FROM chunk_links WHERE chunk_id = ?1 ORDER BY start_byte",
+            &chunk_id.hyphenated().to_string(),
+        )
+    }
+
+    /// Every stored link of any chunk of `object_id`.
+    pub fn get_links_from_node(&self, object_id: ObjectId) -> Result<Vec<ChunkLink>> {
+        self.query_links(
+            "SELECT l.chunk_id, l.start_byte, l.end_byte, l.target, l.object_id
+             FROM chunk_links l JOIN chunks c ON c.id = l.chunk_id
+             WHERE c.object_id = ?1
+             ORDER BY l.chunk_id, l.start_byte",
+            &object_id.hyphenated().to_string(),
+        )
+    }
+
+    /// Every stored link that resolves to `object_id`.
+    pub fn get_links_to_node(&self, object_id: ObjectId) -> Result<Vec<ChunkLink>> {
+        self.query_links(
+            "SELECT chunk_id, start_byte, end_byte, target, object_id
+             FROM chunk_links WHERE object_id = ?1
+             ORDER BY chunk_id, start_byte",
+            &object_id.hyphenated().to_string(),
+        )
+    }
+
+    fn query_links(&self, sql: &str, id: &str) -> Result<Vec<ChunkLink>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?;
+        rows.map(|row| row_to_link(row?)).collect()
+    }
+}
@@ -1,9 +1,10 @@
 //! Chunk storage methods for KnowledgeGraphStorage.
 
 use super::storage::*;
-use anyhow::{Context, Result};
-use rusqlite::params;
+use anyhow::Context;
+use rusqlite::{params, params_from_iter, Connection};
 
+use crate::error::Result;
 use crate::types::{ChunkId, ObjectId, TextChunk};
 
 impl KnowledgeGraphStorage {
@@ -19,25 +20,7 @@ impl KnowledgeGraphStorage {
     /// `chunks_fts` synchronised automatically.
     pub fn upsert_chunk(&self, chunk: TextChunk) -> Result<()> {
         let conn = self.conn.lock();
-        conn.execute(
-            "INSERT INTO chunks
-                 (id, object_id, chunk_type, content, token_count, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-             ON CONFLICT(id) DO UPDATE SET
-                 chunk_type  = excluded.chunk_type,
-                 content     = excluded.content,
-                 token_count = excluded.token_count",
-            params![
-                chunk.id.hyphenated().to_string(),
-                chunk.object_id.hyphenated().to_string(),
-                chunk_type_to_str(&chunk.chunk_type),
-                chunk.content,
-                chunk.token_count as i64,
-                chunk.created_at.to_rfc3339(),
-            ],
-        )
-        .context("Failed to upsert chunk")?;
-        Ok(())
+        write_chunk(&conn, &chunk)
     }
 
     /// Return all chunks that do not yet have a 768-dim embedding in `chunks_vec`.
@@ -46,52 +29,47 @@ impl KnowledgeGraphStorage {
     /// rowid exists — i.e. chunks that have never been embedded via
     /// [`upsert_chunk_embedding`](super::fts::KnowledgeGraphStorage::upsert_chunk_embedding).
     pub fn get_unembedded_chunks(&self) -> Result<Vec<TextChunk>> {
-        let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
-            "SELECT c.id, c.object_id, c.chunk_type, c.content, c.token_count, c.created_at
-             FROM chunks c
-             LEFT JOIN chunks_vec v ON c.rowid = v.rowid
-             WHERE v.rowid IS NULL",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, i64>(4)?,
-                row.get::<_, String>(5)?,
-            ))
-        })?;
-        let mut chunks = Vec::new();
-        for row in rows {
-            let (id_s, obj_s, ct_s, content, token_count, ca_s) = row?;
-            chunks.push(TextChunk {
-                id: ChunkId::parse_str(&id_s)
-                    .with_context(|| format!("Invalid chunk UUID: '{id_s}'"))?,
-                object_id: ObjectId::parse_str(&obj_s)
-                    .with_context(|| format!("Invalid object UUID in chunk: '{obj_s}'"))?,
-                chunk_type: str_to_chunk_type(&ct_s),
-                content,
-                token_count: token_count as usize,
-                created_at: chrono::DateTime::parse_from_rfc3339(&ca_s)
-                    .with_context(|| format!("Invalid chunk created_at: '{ca_s}'"))?
-                    .with_timezone(&chrono::Utc),
-            });
-        }
-        Ok(chunks)
+        self.read_unembedded_chunks("chunks_vec", None)
     }
 
     /// Return all chunks that do not yet have a 4096-dim embedding in `chunks_vec_hq`.
     pub fn get_unembedded_chunks_hq(&self) -> Result<Vec<TextChunk>> {
+        self.read_unembedded_chunks("chunks_vec_hq", None)
+    }
+
+    /// [`get_unembedded_chunks`](Self::get_unembedded_chunks) restricted to
+    /// `node_id`'s chunks.
+    pub fn get_unembedded_chunks_for_node(&self, node_id: ObjectId) -> Result<Vec<TextChunk>> {
+        self.read_unembedded_chunks("chunks_vec", Some(node_id))
+    }
+
+    /// [`get_unembedded_chunks_hq`](Self::get_unembedded_chunks_hq) restricted
+    /// to `node_id`'s chunks.
+    pub fn get_unembedded_chunks_hq_for_node(&self, node_id: ObjectId) -> Result<Vec<TextChunk>> {
+        self.read_unembedded_chunks("chunks_vec_hq", Some(node_id))
+    }
+
+    /// Chunks without a row in `vec_table`, optionally only `node_id`'s.
+    fn read_unembedded_chunks(
+        &self,
+        vec_table: &str,
+        node_id: Option<ObjectId>,
+    ) -> Result<Vec<TextChunk>> {
+        let node_filter = if node_id.is_some() {
+            "AND c.object_id = ?1"
+        } else {
+            ""
+        };
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
-            "SELECT c.id, c.object_id, c.chunk_type, c.content, c.token_count, c.created_at
+        let mut stmt = conn.prepare(&format!(
+            "SELECT c.id, c.object_id, c.chunk_type, c.content, c.token_count, c.created_at, c.language,
+                    c.start_ms, c.end_ms
              FROM chunks c
-             LEFT JOIN chunks_vec_hq v ON c.rowid = v.rowid
-             WHERE v.rowid IS NULL",
-        )?;
-        let rows = stmt.query_map([], |row| {
+             LEFT JOIN {vec_table} v ON c.rowid = v.rowid
+             WHERE v.rowid IS NULL {node_filter}"
+        ))?;
+        let node_id = node_id.map(|id| id.hyphenated().to_string());
+        let rows = stmt.query_map(params_from_iter(node_id.iter()), |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, String>(1)?,
@@ -99,11 +77,13 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(3)?,
                 row.get::<_, i64>(4)?,
                 row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<i64>>(7)?.zip(row.get::<_, Option<i64>>(8)?),
             ))
         })?;
         let mut chunks = Vec::new();
         for row in rows {
-            let (id_s, obj_s, ct_s, content, token_count, ca_s) = row?;
+            let (id_s, obj_s, ct_s, content, token_count, ca_s, language, time_range) = row?;
             chunks.push(TextChunk {
                 id: ChunkId::parse_str(&id_s)
                     .with_context(|| format!("Invalid chunk UUID: '{id_s}'"))?,
@@ -115,19 +95,29 @@ impl KnowledgeGraphStorage {
                 created_at: chrono::DateTime::parse_from_rfc3339(&ca_s)
                     .with_context(|| format!("Invalid chunk created_at: '{ca_s}'"))?
                     .with_timezone(&chrono::Utc),
+                language,
+                time_range: time_range.map(|(start, end)| (start as u64, end as u64)),
             });
         }
         Ok(chunks)
     }
 
-    /// Return all text chunks associated with `node_id`.
+    /// Return all text chunks associated with `node_id`, from the read cache
+    /// when possible.
     pub fn get_chunks_for_node(&self, node_id: ObjectId) -> Result<Vec<TextChunk>> {
+        Ok(self.get_chunks_shared(node_id)?.to_vec())
+    }
+
+    /// Read `node_id`'s chunks from the database, bypassing the read cache.
+    pub(super) fn read_chunks_for_node(&self, node_id: ObjectId) -> Result<Vec<TextChunk>> {
         let conn = self.conn.lock();
         let id_str = node_id.hyphenated().to_string();
         let mut stmt = conn.prepare(
-            "SELECT id, object_id, chunk_type, content, token_count, created_at
+            "SELECT id, object_id, chunk_type, content, token_count, created_at, language,
+                    start_ms, end_ms
              FROM chunks
-             WHERE object_id = ?1",
+             WHERE object_id = ?1
+             ORDER BY rowid",
         )?;
         let rows = stmt.query_map(params![id_str], |row| {
             Ok((
@@ -137,12 +127,14 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(3)?,
                 row.get::<_, i64>(4)?,
                 row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<i64>>(7)?.zip(row.get::<_, Option<i64>>(8)?),
             ))
         })?;
 
         let mut chunks = Vec::new();
         for row in rows {
-            let (id_s, obj_s, ct_s, content, token_count, ca_s) = row?;
+            let (id_s, obj_s, ct_s, content, token_count, ca_s, language, time_range) = row?;
             chunks.push(TextChunk {
                 id: ChunkId::parse_str(&id_s)
                     .with_context(|| format!("Invalid chunk UUID: '{id_s}'"))?,
@@ -154,6 +146,8 @@ impl KnowledgeGraphStorage {
                 created_at: chrono::DateTime::parse_from_rfc3339(&ca_s)
                     .with_context(|| format!("Invalid chunk created_at: '{ca_s}'"))?
                     .with_timezone(&chrono::Utc),
+                language,
+                time_range: time_range.map(|(start, end)| (start as u64, end as u64)),
             });
         }
         Ok(chunks)
@@ -175,4 +169,48 @@ impl KnowledgeGraphStorage {
             .context("Failed to delete chunks for node")?;
         Ok(deleted)
     }
+
+    /// Set the language tag of a single chunk.  Returns `false` if the chunk
+    /// does not exist.
+    pub fn set_chunk_language(&self, chunk_id: ChunkId, language: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock();
+        let updated = conn
+            .execute(
+                "UPDATE chunks SET language = ?1 WHERE id = ?2",
+                params![language, chunk_id.hyphenated().to_string()],
+            )
+            .context("Failed to set chunk language")?;
+        Ok(updated > 0)
+    }
+}
+
+/// The statement behind [`KnowledgeGraphStorage::upsert_chunk`], on a
+/// caller-held connection so it can run inside a transaction.
+pub(super) fn write_chunk(conn: &Connection, chunk: &TextChunk) -> Result<()> {
+    conn.execute(
+        "INSERT INTO chunks
+             (id, object_id, chunk_type, content, token_count, created_at, language,
+              start_ms, end_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+             chunk_type  = excluded.chunk_type,
+             content     = excluded.content,
+             token_count = excluded.token_count,
+             language    = excluded.language,
+             start_ms    = excluded.start_ms,
+             end_ms      = excluded.end_ms",
+        params![
+            chunk.id.hyphenated().to_string(),
+            chunk.object_id.hyphenated().to_string(),
+            chunk_type_to_str(&chunk.chunk_type),
+            chunk.content,
+            chunk.token_count as i64,
+            chunk.created_at.to_rfc3339(),
+            chunk.language,
+            chunk.time_range.map(|(start, _)| start as i64),
+            chunk.time_range.map(|(_, end)| end as i64),
+        ],
+    )
+    .context("Failed to upsert chunk")?;
+    Ok(())
 }
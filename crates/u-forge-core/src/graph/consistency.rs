@@ -0,0 +1,132 @@
+//! Persistence for LLM-flagged contradictions between chunks.
+//!
+//! Rows in `consistency_warnings` cascade from the owning node and from both
+//! referenced chunks, so deleting or rewriting a statement cleans up any
+//! warning that pointed at it.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::consistency::{ConsistencyWarning, ConsistencyWarningId};
+use crate::error::Result;
+use crate::types::{ChunkId, ObjectId};
+
+use super::storage::KnowledgeGraphStorage;
+
+const WARNING_COLUMNS: &str =
+    "id, object_id, first_chunk, second_chunk, explanation, dismissed, created_at";
+
+impl KnowledgeGraphStorage {
+    /// Replace the undismissed warnings for `object_id` with `warnings`.
+    ///
+    /// Dismissed warnings are kept, and any new warning for a chunk pair the
+    /// user already dismissed is skipped so re-running the analysis does not
+    /// resurrect it.  Runs in a single transaction.
+    pub fn replace_consistency_warnings(
+        &self,
+        object_id: ObjectId,
+        warnings: &[ConsistencyWarning],
+    ) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM consistency_warnings WHERE object_id = ?1 AND dismissed = 0",
+            params![object_id.hyphenated().to_string()],
+        )
+        .context("Failed to clear consistency warnings")?;
+        for w in warnings {
+            let first = w.first_chunk.hyphenated().to_string();
+            let second = w.second_chunk.hyphenated().to_string();
+            tx.execute(
+                "INSERT INTO consistency_warnings
+                     (id, object_id, first_chunk, second_chunk, explanation, dismissed, created_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7
+                 WHERE NOT EXISTS (
+                     SELECT 1 FROM consistency_warnings
+                     WHERE dismissed = 1
+                       AND ((first_chunk = ?3 AND second_chunk = ?4)
+                         OR (first_chunk = ?4 AND second_chunk = ?3))
+                 )",
+                params![
+                    w.id.hyphenated().to_string(),
+                    w.object_id.hyphenated().to_string(),
+                    first,
+                    second,
+                    w.explanation,
+                    w.dismissed,
+                    w.created_at.to_rfc3339(),
+                ],
+            )
+            .context("Failed to insert consistency warning")?;
+        }
+        tx.commit().context("Failed to commit consistency warnings")?;
+        Ok(())
+    }
+
+    /// List warnings, newest first.
+    ///
+    /// `object_id = None` lists every object; `include_dismissed = false`
+    /// hides warnings the user has already reviewed.
+    pub fn get_consistency_warnings(
+        &self,
+        object_id: Option<ObjectId>,
+        include_dismissed: bool,
+    ) -> Result<Vec<ConsistencyWarning>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {WARNING_COLUMNS} FROM consistency_warnings
+             WHERE (?1 IS NULL OR object_id = ?1)
+               AND (?2 OR dismissed = 0)
+             ORDER BY created_at DESC"
+        ))?;
+        let rows = stmt.query_map(
+            params![
+                object_id.map(|id| id.hyphenated().to_string()),
+                include_dismissed,
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, bool>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, oid, first, second, explanation, dismissed, created_at) = row?;
+            out.push(ConsistencyWarning {
+                id: ConsistencyWarningId::parse_str(&id)
+                    .with_context(|| format!("Invalid warning UUID: '{id}'"))?,
+                object_id: ObjectId::parse_str(&oid)
+                    .with_context(|| format!("Invalid object UUID: '{oid}'"))?,
+                first_chunk: ChunkId::parse_str(&first)
+                    .with_context(|| format!("Invalid chunk UUID: '{first}'"))?,
+                second_chunk: ChunkId::parse_str(&second)
+                    .with_context(|| format!("Invalid chunk UUID: '{second}'"))?,
+                explanation,
+                dismissed,
+                created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                    .with_context(|| format!("Invalid created_at timestamp: '{created_at}'"))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Mark a warning as reviewed.  Idempotent.
+    pub fn dismiss_consistency_warning(&self, id: ConsistencyWarningId) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE consistency_warnings SET dismissed = 1 WHERE id = ?1",
+            params![id.hyphenated().to_string()],
+        )
+        .context("Failed to dismiss consistency warning")?;
+        Ok(())
+    }
+}
@@ -1,42 +1,27 @@
 //! Edge CRUD methods for KnowledgeGraphStorage.
 
 use super::storage::*;
-use anyhow::{Context, Result};
-use rusqlite::params;
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
 use tracing::debug;
 
-use crate::types::{Edge, EdgeType, ObjectId};
+use crate::error::Result;
+use crate::types::{Edge, EdgeChanges, EdgeId, EdgeType, ObjectId};
 use std::collections::HashMap;
 
 impl KnowledgeGraphStorage {
-    /// Insert or replace an edge.
+    /// Insert or update an edge.
     ///
-    /// `INSERT OR REPLACE` is safe here because the `edges` table has no
-    /// cascading children.  The `UNIQUE(source_id, target_id, edge_type)`
-    /// constraint ensures a logical edge is stored at most once; re-inserting
-    /// the same (source, target, type) triplet updates `weight` and `metadata`.
+    /// The `UNIQUE(source_id, target_id, edge_type)` constraint ensures a
+    /// logical edge is stored at most once; re-inserting the same (source,
+    /// target, type) triplet updates `weight`, `metadata` and `created_at`
+    /// but keeps the stored [`EdgeId`].
     ///
     /// `EdgeType` is stored via `as_str()` and read back via `EdgeType::new(s)`,
     /// which round-trips correctly.
     pub fn upsert_edge(&self, edge: Edge) -> Result<()> {
         let conn = self.conn.lock();
-        let meta_json =
-            serde_json::to_string(&edge.metadata).context("Failed to serialise edge metadata")?;
-        conn.execute(
-            "INSERT OR REPLACE INTO edges
-                 (source_id, target_id, edge_type, weight, metadata, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                edge.from.hyphenated().to_string(),
-                edge.to.hyphenated().to_string(),
-                edge.edge_type.as_str(),
-                edge.weight as f64,
-                meta_json,
-                edge.created_at.to_rfc3339(),
-            ],
-        )
-        .context("Failed to upsert edge")?;
-        Ok(())
+        write_edge(&conn, &edge)
     }
 
     /// Return all edges incident on `node_id` (both outgoing **and** incoming).
@@ -48,7 +33,7 @@ impl KnowledgeGraphStorage {
         let conn = self.conn.lock();
         let id_str = node_id.hyphenated().to_string();
         let mut stmt = conn.prepare(
-            "SELECT source_id, target_id, edge_type, weight, metadata, created_at
+            "SELECT source_id, target_id, edge_type, weight, metadata, created_at, id
              FROM edges
              WHERE source_id = ?1 OR target_id = ?1",
         )?;
@@ -60,12 +45,13 @@ impl KnowledgeGraphStorage {
                 row.get::<_, f64>(3)?,
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
             ))
         })?;
 
         let mut edges = Vec::new();
         for row in rows {
-            let (src_s, tgt_s, et_s, weight, meta_s, ca_s) = row?;
+            let (src_s, tgt_s, et_s, weight, meta_s, ca_s, id_s) = row?;
             let metadata: HashMap<String, String> = match serde_json::from_str(&meta_s) {
                 Ok(m) => m,
                 Err(e) => {
@@ -74,6 +60,8 @@ impl KnowledgeGraphStorage {
                 }
             };
             edges.push(Edge {
+                id: EdgeId::parse_str(&id_s)
+                    .with_context(|| format!("Invalid edge id in edges table: '{id_s}'"))?,
                 from: ObjectId::parse_str(&src_s)
                     .with_context(|| format!("Invalid source UUID in edges table: '{src_s}'"))?,
                 to: ObjectId::parse_str(&tgt_s)
@@ -97,7 +85,7 @@ impl KnowledgeGraphStorage {
     pub fn get_all_edges(&self) -> Result<Vec<Edge>> {
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT source_id, target_id, edge_type, weight, metadata, created_at
+            "SELECT source_id, target_id, edge_type, weight, metadata, created_at, id
              FROM edges",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -108,12 +96,13 @@ impl KnowledgeGraphStorage {
                 row.get::<_, f64>(3)?,
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
             ))
         })?;
 
         let mut edges = Vec::new();
         for row in rows {
-            let (src_s, tgt_s, et_s, weight, meta_s, ca_s) = row?;
+            let (src_s, tgt_s, et_s, weight, meta_s, ca_s, id_s) = row?;
             let metadata: HashMap<String, String> = match serde_json::from_str(&meta_s) {
                 Ok(m) => m,
                 Err(e) => {
@@ -122,6 +111,8 @@ impl KnowledgeGraphStorage {
                 }
             };
             edges.push(Edge {
+                id: EdgeId::parse_str(&id_s)
+                    .with_context(|| format!("Invalid edge id in edges table: '{id_s}'"))?,
                 from: ObjectId::parse_str(&src_s)
                     .with_context(|| format!("Invalid source UUID in edges table: '{src_s}'"))?,
                 to: ObjectId::parse_str(&tgt_s)
@@ -163,6 +154,58 @@ impl KnowledgeGraphStorage {
         Ok(neighbors)
     }
 
+    /// The edge with id `id`, if it exists.
+    pub fn get_edge(&self, id: EdgeId) -> Result<Option<Edge>> {
+        let conn = self.conn.lock();
+        read_edge(&conn, id)
+    }
+
+    /// Apply `changes` to the edge with id `id` and return its new state, or
+    /// `None` if there is no such edge.
+    ///
+    /// Fails if the change would duplicate another edge's (source, target,
+    /// type) or point at a node that does not exist.
+    pub fn update_edge(&self, id: EdgeId, changes: &EdgeChanges) -> Result<Option<Edge>> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let Some(current) = read_edge(&tx, id)? else {
+            return Ok(None);
+        };
+        let updated = changes.apply_to(&current);
+        let meta_json = serde_json::to_string(&updated.metadata)
+            .context("Failed to serialise edge metadata")?;
+        tx.execute(
+            "UPDATE edges
+             SET source_id = ?1, target_id = ?2, edge_type = ?3, weight = ?4, metadata = ?5
+             WHERE id = ?6",
+            params![
+                updated.from.hyphenated().to_string(),
+                updated.to.hyphenated().to_string(),
+                updated.edge_type.as_str(),
+                updated.weight as f64,
+                meta_json,
+                id.hyphenated().to_string(),
+            ],
+        )
+        .with_context(|| {
+            format!(
+                "Failed to update edge {id}: {} -[{}]-> {} may already exist or name a missing node",
+                updated.from, updated.edge_type, updated.to
+            )
+        })?;
+        tx.commit()?;
+        Ok(Some(updated))
+    }
+
+    /// Delete the edge with id `id`.  Returns whether an edge was removed.
+    pub fn delete_edge_by_id(&self, id: EdgeId) -> Result<bool> {
+        let conn = self.conn.lock();
+        let removed = conn
+            .execute("DELETE FROM edges WHERE id = ?1", params![id.hyphenated().to_string()])
+            .context("Failed to delete edge")?;
+        Ok(removed > 0)
+    }
+
     /// Delete a specific edge identified by its (source, target, edge_type) triplet.
     ///
     /// Returns `Ok(())` even if the edge did not exist (idempotent delete).
@@ -180,3 +223,90 @@ impl KnowledgeGraphStorage {
         Ok(())
     }
 }
+
+/// The statement behind [`KnowledgeGraphStorage::upsert_edge`], on a
+/// caller-held connection so it can run inside a transaction.
+///
+/// An edge whose id is already taken by a different (source, target, type)
+/// — e.g. a stored edge re-typed by the caller and written back — is stored
+/// as a new edge under a fresh id rather than overwriting the other one.
+pub(super) fn write_edge(conn: &Connection, edge: &Edge) -> Result<()> {
+    let meta_json =
+        serde_json::to_string(&edge.metadata).context("Failed to serialise edge metadata")?;
+    let from = edge.from.hyphenated().to_string();
+    let to = edge.to.hyphenated().to_string();
+    let id_taken: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM edges WHERE id = ?1
+                 AND NOT (source_id = ?2 AND target_id = ?3 AND edge_type = ?4))",
+            params![edge.id.hyphenated().to_string(), from, to, edge.edge_type.as_str()],
+            |r| r.get(0),
+        )
+        .context("Failed to check edge id")?;
+    let id = if id_taken { EdgeId::new_v4() } else { edge.id };
+    conn.execute(
+        "INSERT INTO edges
+             (source_id, target_id, edge_type, weight, metadata, created_at, id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(source_id, target_id, edge_type) DO UPDATE SET
+             weight = excluded.weight,
+             metadata = excluded.metadata,
+             created_at = excluded.created_at",
+        params![
+            from,
+            to,
+            edge.edge_type.as_str(),
+            edge.weight as f64,
+            meta_json,
+            edge.created_at.to_rfc3339(),
+            id.hyphenated().to_string(),
+        ],
+    )
+    .context("Failed to upsert edge")?;
+    Ok(())
+}
+
+/// One edge by id, on a caller-held connection.
+fn read_edge(conn: &Connection, id: EdgeId) -> Result<Option<Edge>> {
+    let row = conn
+        .query_row(
+            "SELECT source_id, target_id, edge_type, weight, metadata, created_at
+             FROM edges WHERE id = ?1",
+            params![id.hyphenated().to_string()],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        )
+        .optional()
+        .context("Failed to read edge")?;
+    let Some((src_s, tgt_s, et_s, weight, meta_s, ca_s)) = row else {
+        return Ok(None);
+    };
+    let metadata: HashMap<String, String> = match serde_json::from_str(&meta_s) {
+        Ok(m) => m,
+        Err(e) => {
+            debug!("Edge metadata JSON parse failed (using empty): {e}");
+            HashMap::new()
+        }
+    };
+    Ok(Some(Edge {
+        id,
+        from: ObjectId::parse_str(&src_s)
+            .with_context(|| format!("Invalid source UUID in edges table: '{src_s}'"))?,
+        to: ObjectId::parse_str(&tgt_s)
+            .with_context(|| format!("Invalid target UUID in edges table: '{tgt_s}'"))?,
+        edge_type: EdgeType::new(et_s),
+        weight: weight as f32,
+        metadata,
+        created_at: chrono::DateTime::parse_from_rfc3339(&ca_s)
+            .with_context(|| format!("Invalid edge created_at: '{ca_s}'"))?
+            .with_timezone(&chrono::Utc),
+    }))
+}
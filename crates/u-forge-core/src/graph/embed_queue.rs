@@ -0,0 +1,77 @@
+//! Embedding queue rows for KnowledgeGraphStorage.
+//!
+//! Backed by the `embed_queue` table; see [`crate::embed_queue`] for who
+//! queues and drains it.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use super::storage::KnowledgeGraphStorage;
+use crate::error::Result;
+use crate::types::ChunkId;
+
+impl KnowledgeGraphStorage {
+    /// Queue `(chunk, content hash)` pairs for the `target` index in one
+    /// transaction.  A chunk already queued for `target` takes the new hash.
+    pub fn enqueue_embeddings(&self, target: &str, chunks: &[(ChunkId, String)]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .context("Failed to begin embed queue transaction")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        for (id, hash) in chunks {
+            tx.execute(
+                "INSERT INTO embed_queue (chunk_id, target, content_hash, queued_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(chunk_id, target) DO UPDATE SET content_hash = excluded.content_hash",
+                params![id.hyphenated().to_string(), target, hash, now],
+            )
+            .with_context(|| format!("Failed to queue {target} embedding for chunk {id}"))?;
+        }
+        tx.commit().context("Failed to commit embed queue")?;
+        Ok(())
+    }
+
+    /// Chunks queued for the `target` index with their queued content
+    /// hashes, oldest first.
+    pub fn queued_embeddings(&self, target: &str) -> Result<Vec<(ChunkId, String)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT chunk_id, content_hash FROM embed_queue WHERE target = ?1
+             ORDER BY queued_at, rowid",
+        )?;
+        let rows = stmt.query_map(params![target], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut queued = Vec::new();
+        for row in rows {
+            let (id, hash) = row?;
+            queued.push((
+                ChunkId::parse_str(&id)
+                    .with_context(|| format!("Invalid chunk UUID in embed queue: '{id}'"))?,
+                hash,
+            ));
+        }
+        Ok(queued)
+    }
+
+    /// Remove `chunk_ids` from the `target` queue.  Returns how many rows
+    /// were removed.
+    pub fn dequeue_embeddings(&self, target: &str, chunk_ids: &[ChunkId]) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .context("Failed to begin embed queue transaction")?;
+        let mut removed = 0;
+        for id in chunk_ids {
+            removed += tx
+                .execute(
+                    "DELETE FROM embed_queue WHERE chunk_id = ?1 AND target = ?2",
+                    params![id.hyphenated().to_string(), target],
+                )
+                .context("Failed to dequeue embedding")?;
+        }
+        tx.commit().context("Failed to commit embed queue")?;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,140 @@
+//! Chunk embedding provenance for KnowledgeGraphStorage.
+//!
+//! Backed by the `chunk_embeddings` table; see [`crate::embedding_status`]
+//! for how a row turns into a status.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use super::storage::KnowledgeGraphStorage;
+use crate::error::Result;
+use crate::types::ChunkId;
+
+/// One chunk as seen by one vector index.
+pub(crate) struct ChunkEmbeddingRecord {
+    pub chunk_id: ChunkId,
+    pub content: String,
+    pub has_vector: bool,
+    /// `(model, content_hash, embedded_at)` from the provenance row, if any.
+    pub provenance: Option<(String, String, String)>,
+}
+
+/// The vec0 table holding `target`'s vectors.
+fn vector_table(target: &str) -> &'static str {
+    match target {
+        "high_quality" => "chunks_vec_hq",
+        _ => "chunks_vec",
+    }
+}
+
+impl KnowledgeGraphStorage {
+    /// Record that `model` produced the `target` vectors of `(chunk, content
+    /// hash)` pairs just now, in one transaction.
+    pub fn record_chunk_embeddings(
+        &self,
+        target: &str,
+        model: &str,
+        chunks: &[(ChunkId, String)],
+    ) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .context("Failed to begin provenance transaction")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        for (id, hash) in chunks {
+            tx.execute(
+                "INSERT OR REPLACE INTO chunk_embeddings
+                     (chunk_id, target, model, content_hash, embedded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id.hyphenated().to_string(), target, model, hash, now],
+            )
+            .with_context(|| format!("Failed to record {target} embedding of chunk {id}"))?;
+        }
+        tx.commit().context("Failed to commit provenance")?;
+        Ok(())
+    }
+
+    /// Every chunk with its `target` vector state, or only `chunk_id`.
+    pub(crate) fn chunk_embedding_records(
+        &self,
+        target: &str,
+        chunk_id: Option<ChunkId>,
+    ) -> Result<Vec<ChunkEmbeddingRecord>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT c.id, c.content, v.rowid IS NOT NULL, e.model, e.content_hash, e.embedded_at
+             FROM chunks c
+             LEFT JOIN {} v ON v.rowid = c.rowid
+             LEFT JOIN chunk_embeddings e ON e.chunk_id = c.id AND e.target = ?1
+             WHERE ?2 IS NULL OR c.id = ?2",
+            vector_table(target)
+        ))?;
+        let rows = stmt.query_map(
+            params![target, chunk_id.map(|id| id.hyphenated().to_string())],
+            |row| {
+                let provenance = match (
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ) {
+                    (Some(model), Some(hash), Some(at)) => Some((model, hash, at)),
+                    _ => None,
+                };
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, bool>(2)?,
+                    provenance,
+                ))
+            },
+        )?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (id, content, has_vector, provenance) = row?;
+            records.push(ChunkEmbeddingRecord {
+                chunk_id: ChunkId::parse_str(&id)
+                    .with_context(|| format!("Invalid chunk UUID: '{id}'"))?,
+                content,
+                has_vector,
+                provenance,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Drop the `target` vectors (and their provenance) of `chunk_ids`, so
+    /// the next embedding sweep fills them in again.  Returns how many
+    /// vectors were removed.
+    pub fn delete_chunk_vectors(&self, target: &str, chunk_ids: &[ChunkId]) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .context("Failed to begin vector delete transaction")?;
+        let mut removed = 0;
+        for id in chunk_ids {
+            let id = id.hyphenated().to_string();
+            // vec0 matches on a plain rowid, so resolve it first.
+            let rowid: Option<i64> = tx
+                .query_row("SELECT rowid FROM chunks WHERE id = ?1", params![id], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .context("Failed to look up chunk")?;
+            if let Some(rowid) = rowid {
+                removed += tx
+                    .execute(
+                        &format!("DELETE FROM {} WHERE rowid = ?1", vector_table(target)),
+                        params![rowid],
+                    )
+                    .context("Failed to delete chunk vector")?;
+            }
+            tx.execute(
+                "DELETE FROM chunk_embeddings WHERE chunk_id = ?1 AND target = ?2",
+                params![id, target],
+            )
+            .context("Failed to delete chunk embedding provenance")?;
+        }
+        tx.commit().context("Failed to commit vector delete")?;
+        Ok(removed)
+    }
+}
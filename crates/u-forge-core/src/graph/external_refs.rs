@@ -0,0 +1,122 @@
+//! Persistence for cross-project references.
+
+use anyhow::Context;
+use rusqlite::params;
+use uuid::Uuid;
+
+use crate::crosslinks::ExternalRef;
+use crate::error::Result;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+fn parse_object_id(text: &str) -> Result<ObjectId> {
+    Ok(ObjectId::parse_str(text)
+        .with_context(|| format!("Invalid object id in external_refs: '{text}'"))?)
+}
+
+impl KnowledgeGraphStorage {
+    /// Insert `reference`, or refresh the label of the existing reference
+    /// with the same source, type, and target.  Returns the stored row.
+    pub fn upsert_external_ref(&self, reference: &ExternalRef) -> Result<ExternalRef> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO external_refs
+                 (id, source_id, edge_type, project_id, object_id, label, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(source_id, edge_type, project_id, object_id)
+                 DO UPDATE SET label = excluded.label",
+            params![
+                reference.id.to_string(),
+                reference.source.hyphenated().to_string(),
+                reference.edge_type,
+                reference.project_id.to_string(),
+                reference.object_id.hyphenated().to_string(),
+                reference.label,
+                reference.created_at.to_rfc3339(),
+            ],
+        )
+        .context("Failed to save external reference")?;
+        let id: String = conn.query_row(
+            "SELECT id FROM external_refs
+             WHERE source_id = ?1 AND edge_type = ?2 AND project_id = ?3 AND object_id = ?4",
+            params![
+                reference.source.hyphenated().to_string(),
+                reference.edge_type,
+                reference.project_id.to_string(),
+                reference.object_id.hyphenated().to_string(),
+            ],
+            |row| row.get(0),
+        )?;
+        drop(conn);
+        let id =
+            Uuid::parse_str(&id).with_context(|| format!("Invalid external ref id: '{id}'"))?;
+        Ok(self
+            .list_external_refs(None, Some(id))?
+            .pop()
+            .context("External reference vanished after save")?)
+    }
+
+    /// References from `source`, or the one with `id`, oldest first.
+    pub fn list_external_refs(
+        &self,
+        source: Option<ObjectId>,
+        id: Option<Uuid>,
+    ) -> Result<Vec<ExternalRef>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, source_id, edge_type, project_id, object_id, label, created_at
+             FROM external_refs
+             WHERE (?1 IS NULL OR source_id = ?1) AND (?2 IS NULL OR id = ?2)
+             ORDER BY created_at, rowid",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                source.map(|s| s.hyphenated().to_string()),
+                id.map(|i| i.to_string())
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, source, edge_type, project_id, object_id, label, created_at) = row?;
+            out.push(ExternalRef {
+                id: Uuid::parse_str(&id)
+                    .with_context(|| format!("Invalid external ref id: '{id}'"))?,
+                source: parse_object_id(&source)?,
+                edge_type,
+                project_id: Uuid::parse_str(&project_id)
+                    .with_context(|| format!("Invalid project id: '{project_id}'"))?,
+                object_id: parse_object_id(&object_id)?,
+                label,
+                created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                    .with_context(|| format!("Invalid external ref created_at: '{created_at}'"))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Delete reference `id`.  Returns whether it existed.
+    pub fn delete_external_ref(&self, id: Uuid) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute(
+                "DELETE FROM external_refs WHERE id = ?1",
+                params![id.to_string()],
+            )
+            .context("Failed to delete external reference")?;
+        Ok(deleted > 0)
+    }
+}
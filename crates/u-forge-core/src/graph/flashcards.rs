@@ -0,0 +1,105 @@
+//! Persistence for flashcard review schedules.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use crate::error::Result;
+use crate::flashcards::ReviewState;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Invalid time in flashcard_reviews: '{value}'"))?
+        .with_timezone(&Utc))
+}
+
+impl KnowledgeGraphStorage {
+    /// Every stored review schedule, by card id.
+    pub fn get_flashcard_reviews(&self) -> Result<HashMap<String, ReviewState>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT card_id, easiness, interval_days, repetitions, lapses, due_at,
+                    last_reviewed_at
+             FROM flashcard_reviews",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+        let mut out = HashMap::new();
+        for row in rows {
+            let (card_id, easiness, interval_days, repetitions, lapses, due_at, reviewed) = row?;
+            out.insert(
+                card_id,
+                ReviewState {
+                    easiness: easiness as f32,
+                    interval_days,
+                    repetitions,
+                    lapses,
+                    due_at: parse_time(&due_at)?,
+                    last_reviewed_at: parse_time(&reviewed)?,
+                },
+            );
+        }
+        Ok(out)
+    }
+
+    /// Store `state` as the schedule of card `card_id` about `object_id`.
+    pub fn upsert_flashcard_review(
+        &self,
+        card_id: &str,
+        object_id: ObjectId,
+        state: &ReviewState,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO flashcard_reviews
+                 (card_id, object_id, easiness, interval_days, repetitions, lapses, due_at,
+                  last_reviewed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(card_id) DO UPDATE SET
+                 easiness = excluded.easiness,
+                 interval_days = excluded.interval_days,
+                 repetitions = excluded.repetitions,
+                 lapses = excluded.lapses,
+                 due_at = excluded.due_at,
+                 last_reviewed_at = excluded.last_reviewed_at",
+            params![
+                card_id,
+                object_id.hyphenated().to_string(),
+                state.easiness as f64,
+                state.interval_days,
+                state.repetitions,
+                state.lapses,
+                state.due_at.to_rfc3339(),
+                state.last_reviewed_at.to_rfc3339(),
+            ],
+        )
+        .context("Failed to save flashcard review")?;
+        Ok(())
+    }
+
+    /// Forget card `card_id`'s schedule.  Returns whether it had one.
+    pub fn delete_flashcard_review(&self, card_id: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute(
+                "DELETE FROM flashcard_reviews WHERE card_id = ?1",
+                params![card_id],
+            )
+            .context("Failed to delete flashcard review")?;
+        Ok(deleted > 0)
+    }
+}
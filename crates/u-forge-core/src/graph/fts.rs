@@ -1,16 +1,33 @@
 //! Full-text and semantic search methods for KnowledgeGraphStorage.
 
 use super::storage::{self, *};
-use anyhow::{anyhow, Context, Result};
+use anyhow::Context;
 use rusqlite::params;
 
+use crate::diagnostics::trace_operation;
+use crate::error::{EmbeddingDimensionMismatch, Result};
+use crate::text::has_unspaced_script;
 use crate::types::{ChunkId, ObjectId};
 
+/// The FTS5 table to run `query` against: `chunks_fts_trigram` when it
+/// contains Chinese, Japanese, Thai or another script without word spaces,
+/// `chunks_fts` otherwise.
+fn fts_table(query: &str) -> &'static str {
+    if has_unspaced_script(query) {
+        "chunks_fts_trigram"
+    } else {
+        "chunks_fts"
+    }
+}
+
 impl KnowledgeGraphStorage {
     /// Full-text search over chunk content using the FTS5 index.
     ///
     /// `query` is an FTS5 query string — simple terms (`"wizard"`), phrases
     /// (`"grey hat"`), and prefix queries (`"wiz*"`) are all supported.
+    /// Queries in scripts without word spaces search the trigram index, so
+    /// they match anywhere inside a run of text; each of their terms needs
+    /// at least three characters.
     ///
     /// Returns at most `limit` results as `(ChunkId, ObjectId, content)` triples,
     /// ordered by FTS5 relevance rank.
@@ -19,17 +36,19 @@ impl KnowledgeGraphStorage {
         query: &str,
         limit: usize,
     ) -> Result<Vec<(ChunkId, ObjectId, String)>> {
+        let mut op = trace_operation("storage", "search_chunks_fts");
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
+        let table = fts_table(query);
+        let mut stmt = conn.prepare(&format!(
             "SELECT c.id, c.object_id, c.content
              FROM chunks c
              INNER JOIN (
                  SELECT rowid
-                 FROM   chunks_fts
-                 WHERE  chunks_fts MATCH ?1
+                 FROM   {table}
+                 WHERE  {table} MATCH ?1
                  LIMIT  ?2
-             ) fts ON c.rowid = fts.rowid",
-        )?;
+             ) fts ON c.rowid = fts.rowid"
+        ))?;
         let rows = stmt.query_map(params![query, limit as i64], |row| {
             Ok((
                 row.get::<_, String>(0)?,
@@ -49,9 +68,110 @@ impl KnowledgeGraphStorage {
                 content,
             ));
         }
+        op.set_items(results.len());
         Ok(results)
     }
 
+    /// BM25-ranked keyword search matching chunks that contain *any* of
+    /// `terms`.
+    ///
+    /// Unlike [`search_chunks_fts`](Self::search_chunks_fts), which requires
+    /// every term, this scores each chunk by FTS5's `bm25()` so a rare term
+    /// (a proper noun that appears in three chunks) outweighs common ones
+    /// (`tower`, `north`).  Each term is matched as a quoted literal, so FTS5
+    /// syntax in a term is never interpreted.
+    ///
+    /// Returns at most `limit` `(ChunkId, ObjectId, content, score)` tuples,
+    /// best first; `score` is the negated BM25 value (higher = better).
+    pub fn search_chunks_bm25(
+        &self,
+        terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<(ChunkId, ObjectId, String, f32)>> {
+        let query = terms
+            .iter()
+            .filter(|t| !t.is_empty())
+            .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock();
+        let table = fts_table(&query);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT c.id, c.object_id, c.content, fts.score
+             FROM (
+                 SELECT rowid, bm25({table}) AS score
+                 FROM   {table}
+                 WHERE  {table} MATCH ?1
+                 ORDER  BY score
+                 LIMIT  ?2
+             ) fts
+             INNER JOIN chunks c ON c.rowid = fts.rowid
+             ORDER BY fts.score"
+        ))?;
+        let rows = stmt.query_map(params![query, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (chunk_id_s, obj_id_s, content, score) = row?;
+            results.push((
+                ChunkId::parse_str(&chunk_id_s)
+                    .with_context(|| format!("Invalid chunk UUID in BM25 result: '{chunk_id_s}'"))?,
+                ObjectId::parse_str(&obj_id_s)
+                    .with_context(|| format!("Invalid object UUID in BM25 result: '{obj_id_s}'"))?,
+                content,
+                -score as f32,
+            ));
+        }
+        Ok(results)
+    }
+
+    /// `true` when `term` occurs in at least one chunk.
+    ///
+    /// Looks the term up in `chunks_fts_vocab`, so it must be a single
+    /// lowercase word as the FTS5 tokenizer would emit it.
+    pub fn is_indexed_term(&self, term: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM chunks_fts_vocab WHERE term = ?1)",
+                params![term],
+                |row| row.get(0),
+            )
+            .context("Failed to query FTS vocabulary")?)
+    }
+
+    /// Distinct lowercase words (alphanumeric runs) found in node names,
+    /// sorted — the vocabulary query spell-correction snaps to.
+    pub fn name_vocabulary(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT name FROM nodes")?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to list node names")?;
+
+        let mut words: Vec<String> = names
+            .iter()
+            .flat_map(|n| n.split(|c: char| !c.is_alphanumeric()))
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.dedup();
+        Ok(words)
+    }
+
     /// Store or update the embedding vector for an existing chunk.
     ///
     /// Looks up the chunk's integer `rowid` from the `chunks` table then
@@ -67,12 +187,12 @@ impl KnowledgeGraphStorage {
     /// * `embedding.len() != EMBEDDING_DIMENSIONS`.
     pub fn upsert_chunk_embedding(&self, chunk_id: ChunkId, embedding: &[f32]) -> Result<()> {
         if embedding.len() != EMBEDDING_DIMENSIONS {
-            return Err(anyhow!(
-                "Embedding dimension mismatch: expected {EMBEDDING_DIMENSIONS}, got {}. \
-                 Ensure the embedding model matches the vec0 table configuration \
-                 (EMBEDDING_DIMENSIONS constant in storage.rs).",
-                embedding.len()
-            ));
+            return Err(EmbeddingDimensionMismatch {
+                table: "chunks_vec".to_string(),
+                stored: EMBEDDING_DIMENSIONS,
+                expected: embedding.len(),
+            }
+            .into());
         }
 
         let conn = self.conn.lock();
@@ -104,6 +224,13 @@ impl KnowledgeGraphStorage {
         )
         .context("Failed to insert embedding into chunks_vec")?;
 
+        // Provenance belongs to the old vector; the caller records the new one.
+        conn.execute(
+            "DELETE FROM chunk_embeddings WHERE chunk_id = ?1 AND target = 'standard'",
+            params![chunk_id.hyphenated().to_string()],
+        )
+        .context("Failed to clear chunk embedding provenance")?;
+
         Ok(())
     }
 
@@ -124,6 +251,7 @@ impl KnowledgeGraphStorage {
         query_embedding: &[f32],
         limit: usize,
     ) -> Result<Vec<(ChunkId, ObjectId, String, f32)>> {
+        let mut op = trace_operation("storage", "search_chunks_semantic");
         let bytes: Vec<u8> = query_embedding
             .iter()
             .flat_map(|f| f.to_le_bytes())
@@ -166,6 +294,7 @@ impl KnowledgeGraphStorage {
                 distance,
             ));
         }
+        op.set_items(results.len());
         Ok(results)
     }
 
@@ -177,11 +306,12 @@ impl KnowledgeGraphStorage {
     /// table (4096-dim) instead of `chunks_vec` (768-dim).
     pub fn upsert_chunk_embedding_hq(&self, chunk_id: ChunkId, embedding: &[f32]) -> Result<()> {
         if embedding.len() != storage::HIGH_QUALITY_EMBEDDING_DIMENSIONS {
-            return Err(anyhow!(
-                "HQ embedding dimension mismatch: expected {}, got {}.",
-                storage::HIGH_QUALITY_EMBEDDING_DIMENSIONS,
-                embedding.len()
-            ));
+            return Err(EmbeddingDimensionMismatch {
+                table: "chunks_vec_hq".to_string(),
+                stored: storage::HIGH_QUALITY_EMBEDDING_DIMENSIONS,
+                expected: embedding.len(),
+            }
+            .into());
         }
 
         let conn = self.conn.lock();
@@ -207,6 +337,12 @@ impl KnowledgeGraphStorage {
         )
         .context("Failed to insert HQ embedding into chunks_vec_hq")?;
 
+        conn.execute(
+            "DELETE FROM chunk_embeddings WHERE chunk_id = ?1 AND target = 'high_quality'",
+            params![chunk_id.hyphenated().to_string()],
+        )
+        .context("Failed to clear chunk embedding provenance")?;
+
         Ok(())
     }
 
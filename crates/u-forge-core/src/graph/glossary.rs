@@ -0,0 +1,85 @@
+//! Persistence for the project glossary.
+//!
+//! One row per term in the `glossary` table.  `term` is the primary key with
+//! `COLLATE NOCASE`, so "MT" and "mt" are the same entry.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::error::Result;
+use crate::glossary::GlossaryEntry;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Insert an entry, or replace the one with the same term (ignoring case).
+    pub fn upsert_glossary_entry(&self, entry: &GlossaryEntry) -> Result<()> {
+        let conn = self.conn.lock();
+        // Delete first so a re-cased term replaces the stored spelling too.
+        conn.execute("DELETE FROM glossary WHERE term = ?1", params![entry.term])
+            .context("Failed to replace glossary entry")?;
+        conn.execute(
+            "INSERT INTO glossary (term, expansion, object_id, note, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                entry.term,
+                entry.expansion,
+                entry.object_id.map(|id| id.hyphenated().to_string()),
+                entry.note,
+                entry.created_at.to_rfc3339(),
+            ],
+        )
+        .context("Failed to insert glossary entry")?;
+        Ok(())
+    }
+
+    /// Every glossary entry, ordered by term (ignoring case).
+    pub fn list_glossary_entries(&self) -> Result<Vec<GlossaryEntry>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT term, expansion, object_id, note, created_at
+             FROM glossary
+             ORDER BY term",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (term, expansion, object_id, note, created_at) = row?;
+            out.push(GlossaryEntry {
+                term,
+                expansion,
+                object_id: object_id
+                    .map(|id| {
+                        ObjectId::parse_str(&id)
+                            .with_context(|| format!("Invalid object UUID in glossary: '{id}'"))
+                    })
+                    .transpose()?,
+                note,
+                created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                    .with_context(|| format!("Invalid glossary created_at: '{created_at}'"))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Delete the entry for `term` (ignoring case).  Returns `false` if there
+    /// was none.
+    pub fn delete_glossary_entry(&self, term: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute("DELETE FROM glossary WHERE term = ?1", params![term])
+            .context("Failed to delete glossary entry")?;
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,92 @@
+//! Storage and index probes for [`crate::KnowledgeGraph::health`].
+
+use std::thread;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::error::Result;
+
+use super::stats::joined;
+use super::storage::KnowledgeGraphStorage;
+
+/// How up to date the search indexes are relative to the stored chunks.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexHealth {
+    pub chunk_count: usize,
+    /// `false` when either FTS5 index (word or trigram) no longer matches
+    /// `chunks` (keyword search will miss or misreport text until it is
+    /// rebuilt).
+    pub fts_consistent: bool,
+    /// Chunks with a standard embedding in `chunks_vec`.
+    pub embedded_chunks: usize,
+    /// Chunks still waiting for a standard embedding.
+    pub unembedded_chunks: usize,
+    /// Chunks with a high-quality embedding in `chunks_vec_hq`.
+    pub embedded_hq_chunks: usize,
+    pub profile_count: usize,
+    /// Object profiles whose embedding is missing or stale.
+    pub unembedded_profiles: usize,
+}
+
+impl KnowledgeGraphStorage {
+    /// Path of the open database file, or `None` for an in-memory database.
+    pub fn database_path(&self) -> Option<String> {
+        self.conn
+            .lock()
+            .path()
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Run a trivial query to confirm the connection is usable.
+    pub fn ping(&self) -> Result<()> {
+        Ok(self
+            .conn
+            .lock()
+            .query_row("SELECT 1", [], |_| Ok(()))
+            .context("Failed to query database")?)
+    }
+
+    /// Compare the FTS and vector indexes against the chunk and profile
+    /// tables.  The FTS check reads the whole index, so this is O(chunks);
+    /// the counting scans run in parallel with it on their own readers.
+    pub fn index_health(&self) -> Result<IndexHealth> {
+        thread::scope(|s| {
+            let [chunks, embedded, embedded_hq, unembedded, profiles, unembedded_profiles] = [
+                "SELECT COUNT(*) FROM chunks",
+                "SELECT COUNT(*) FROM chunks_vec",
+                "SELECT COUNT(*) FROM chunks_vec_hq",
+                "SELECT COUNT(*) FROM chunks WHERE rowid NOT IN (SELECT rowid FROM chunks_vec)",
+                "SELECT COUNT(*) FROM node_profiles",
+                "SELECT COUNT(*) FROM node_profiles \
+                 WHERE rowid NOT IN (SELECT rowid FROM node_profiles_vec)",
+            ]
+            .map(|sql| s.spawn(move || self.read_count(sql)));
+            // With rank = 1 the integrity check also compares the index against
+            // the external content table; any mismatch is reported as an error.
+            // It is written as an INSERT, so it runs on the shared connection.
+            let fts_consistent = ["chunks_fts", "chunks_fts_trigram"].iter().all(|table| {
+                self.conn
+                    .lock()
+                    .execute(
+                        &format!(
+                            "INSERT INTO {table}({table}, rank) VALUES ('integrity-check', 1)"
+                        ),
+                        [],
+                    )
+                    .is_ok()
+            });
+
+            Ok(IndexHealth {
+                chunk_count: joined(chunks)?,
+                fts_consistent,
+                embedded_chunks: joined(embedded)?,
+                unembedded_chunks: joined(unembedded)?,
+                embedded_hq_chunks: joined(embedded_hq)?,
+                profile_count: joined(profiles)?,
+                unembedded_profiles: joined(unembedded_profiles)?,
+            })
+        })
+    }
+}
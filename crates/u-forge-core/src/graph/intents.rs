@@ -0,0 +1,77 @@
+//! Intent log rows for KnowledgeGraphStorage.
+//!
+//! Backed by the `intents` table; see [`crate::intents`] for what the kinds
+//! mean and who records and completes them.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use super::storage::KnowledgeGraphStorage;
+use crate::error::Result;
+use crate::types::ObjectId;
+
+impl KnowledgeGraphStorage {
+    /// Append one `kind` intent per object in `object_ids`, in one
+    /// transaction.
+    pub fn record_intents(&self, kind: &str, object_ids: &[ObjectId]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .context("Failed to begin intent transaction")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        for id in object_ids {
+            tx.execute(
+                "INSERT INTO intents (kind, object_id, recorded_at) VALUES (?1, ?2, ?3)",
+                params![kind, id.hyphenated().to_string(), now],
+            )
+            .with_context(|| format!("Failed to record {kind} intent for {id}"))?;
+        }
+        tx.commit().context("Failed to commit intents")?;
+        Ok(())
+    }
+
+    /// Highest seq of the pending `kind` intents for `object_id`.
+    pub fn latest_intent_seq(&self, kind: &str, object_id: ObjectId) -> Result<Option<i64>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                "SELECT MAX(seq) FROM intents WHERE kind = ?1 AND object_id = ?2",
+                params![kind, object_id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to query intents")
+            .map(Option::flatten)?)
+    }
+
+    /// Delete `object_id`'s `kind` intents with seq up to `up_to`.  Returns
+    /// how many were removed.
+    pub fn complete_intents(&self, kind: &str, object_id: ObjectId, up_to: i64) -> Result<usize> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .execute(
+                "DELETE FROM intents WHERE kind = ?1 AND object_id = ?2 AND seq <= ?3",
+                params![kind, object_id.hyphenated().to_string(), up_to],
+            )
+            .context("Failed to complete intents")?)
+    }
+
+    /// Objects with pending `kind` intents, oldest request first.
+    pub fn pending_intent_objects(&self, kind: &str) -> Result<Vec<ObjectId>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT object_id FROM intents WHERE kind = ?1
+             GROUP BY object_id ORDER BY MIN(seq)",
+        )?;
+        let rows = stmt.query_map(params![kind], |row| row.get::<_, String>(0))?;
+        let mut ids = Vec::new();
+        for row in rows {
+            let id = row?;
+            ids.push(
+                ObjectId::parse_str(&id)
+                    .with_context(|| format!("Invalid object UUID in intent: '{id}'"))?,
+            );
+        }
+        Ok(ids)
+    }
+}
@@ -0,0 +1,47 @@
+//! Background job records for KnowledgeGraphStorage.
+//!
+//! Backed by the `jobs` table; see [`crate::jobs`] for the record format and
+//! the states a job moves through.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::error::Result;
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Insert or replace the record of job `id`.
+    pub fn save_job(&self, id: &str, state: &str, record: &str, created_at: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO jobs (id, state, record, created_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(id) DO UPDATE SET state = excluded.state, record = excluded.record",
+            params![id, state, record, created_at],
+        )
+        .with_context(|| format!("Failed to save job {id}"))?;
+        Ok(())
+    }
+
+    /// Every stored job record, oldest first.
+    pub fn load_jobs(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT record FROM jobs ORDER BY created_at, id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        Ok(rows
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to load jobs")?)
+    }
+
+    /// Delete the jobs in any of `states`.  Returns how many were removed.
+    pub fn delete_jobs_in_states(&self, states: &[&str]) -> Result<usize> {
+        let conn = self.conn.lock();
+        let mut removed = 0;
+        for state in states {
+            removed += conn
+                .execute("DELETE FROM jobs WHERE state = ?1", params![state])
+                .context("Failed to delete jobs")?;
+        }
+        Ok(removed)
+    }
+}
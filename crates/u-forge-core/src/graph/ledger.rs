@@ -0,0 +1,142 @@
+//! Persistence for treasury ledgers.
+//!
+//! A posting writes one `ledger` row per treasury and the treasury node's
+//! balance property in a single transaction, so the balance always equals
+//! the last entry's `balance`.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+use uuid::Uuid;
+
+use crate::economy::{LedgerEntry, TREASURY_BALANCE_KEY};
+use crate::error::{Result, UForgeError};
+use crate::types::ObjectId;
+
+use super::profiles::refresh_node_profile;
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Apply `postings` (treasury, signed amount) in one transaction and
+    /// return the entries written, in order.  Fails — writing nothing — when
+    /// a treasury does not exist, or a debit would leave it below zero and
+    /// `allow_overdraft` is false.
+    pub fn post_ledger_entries(
+        &self,
+        postings: &[(ObjectId, i64)],
+        memo: &str,
+        transfer_id: Option<Uuid>,
+        allow_overdraft: bool,
+    ) -> Result<Vec<LedgerEntry>> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now();
+        let json_path = format!("$.{TREASURY_BALANCE_KEY}");
+        let mut entries = Vec::with_capacity(postings.len());
+        for &(treasury_id, amount) in postings {
+            let id_str = treasury_id.hyphenated().to_string();
+            let current: Option<i64> = tx
+                .query_row(
+                    "SELECT COALESCE(json_extract(properties, ?1), 0) FROM nodes WHERE id = ?2",
+                    params![json_path, id_str],
+                    |row| row.get(0),
+                )
+                .optional()
+                .context("Failed to read treasury balance")?;
+            let Some(current) = current else {
+                return Err(UForgeError::NotFound(format!(
+                    "Unknown treasury {treasury_id}"
+                )));
+            };
+            let balance = current.checked_add(amount).ok_or_else(|| {
+                UForgeError::ValidationFailed(format!("Balance of {treasury_id} would overflow"))
+            })?;
+            if balance < 0 && amount < 0 && !allow_overdraft {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "Treasury {treasury_id} holds {current}, cannot pay {}",
+                    -amount
+                )));
+            }
+
+            tx.execute(
+                "UPDATE nodes
+                 SET properties = json_set(properties, ?1, ?2),
+                     updated_at = ?3
+                 WHERE id = ?4",
+                params![json_path, balance, now.to_rfc3339(), id_str],
+            )
+            .context("Failed to update treasury balance")?;
+            refresh_node_profile(&tx, &id_str)?;
+
+            let entry = LedgerEntry {
+                id: Uuid::new_v4(),
+                treasury_id,
+                amount,
+                balance,
+                memo: memo.to_string(),
+                transfer_id,
+                recorded_at: now,
+            };
+            tx.execute(
+                "INSERT INTO ledger (id, treasury_id, amount, balance, memo, transfer_id, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    entry.id.to_string(),
+                    id_str,
+                    entry.amount,
+                    entry.balance,
+                    entry.memo,
+                    entry.transfer_id.map(|t| t.to_string()),
+                    now.to_rfc3339(),
+                ],
+            )
+            .context("Failed to insert ledger entry")?;
+            entries.push(entry);
+        }
+        tx.commit()?;
+        Ok(entries)
+    }
+
+    /// Ledger entries of `treasury_id`, newest first.
+    pub fn list_ledger_entries(&self, treasury_id: ObjectId) -> Result<Vec<LedgerEntry>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, amount, balance, memo, transfer_id, recorded_at
+             FROM ledger
+             WHERE treasury_id = ?1
+             ORDER BY recorded_at DESC, rowid DESC",
+        )?;
+        let rows = stmt.query_map(params![treasury_id.hyphenated().to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, amount, balance, memo, transfer_id, recorded_at) = row?;
+            out.push(LedgerEntry {
+                id: Uuid::parse_str(&id)
+                    .with_context(|| format!("Invalid ledger entry id: '{id}'"))?,
+                treasury_id,
+                amount,
+                balance,
+                memo,
+                transfer_id: transfer_id
+                    .map(|t| {
+                        Uuid::parse_str(&t)
+                            .with_context(|| format!("Invalid ledger transfer id: '{t}'"))
+                    })
+                    .transpose()?,
+                recorded_at: chrono::DateTime::parse_from_rfc3339(&recorded_at)
+                    .with_context(|| format!("Invalid ledger recorded_at: '{recorded_at}'"))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,214 @@
+//! Compaction: pruning orphaned index rows and old history, then reclaiming
+//! free pages.  Also online backups of the database file.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::error::{Result, UForgeError};
+use crate::progress::{Progress, ProgressSink};
+
+use super::storage::KnowledgeGraphStorage;
+
+/// What [`KnowledgeGraphStorage::compact`] removed and reclaimed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompactionReport {
+    /// Allocated database size before compacting.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Vectors whose chunk or object profile no longer exists.
+    pub orphan_vectors: usize,
+    /// Chunk revisions trimmed by the retention policy.
+    pub revisions: usize,
+    /// Node and edge history rows trimmed by the retention policy.
+    pub history_rows: usize,
+}
+
+impl CompactionReport {
+    /// Bytes freed on disk; 0 if the database grew.
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Allocated database size: `page_count * page_size`.
+fn database_bytes(conn: &Connection) -> Result<u64> {
+    let pages: i64 = conn
+        .query_row("PRAGMA page_count", [], |r| r.get(0))
+        .context("Failed to read page count")?;
+    let page_size: i64 = conn
+        .query_row("PRAGMA page_size", [], |r| r.get(0))
+        .context("Failed to read page size")?;
+    Ok((pages * page_size) as u64)
+}
+
+/// Delete rows of vector table `vec` whose rowid has no row in `owner`.
+/// vec0 tables only delete by rowid, so the orphans are collected first.
+fn prune_orphan_vectors(conn: &Connection, vec: &str, owner: &str) -> Result<usize> {
+    let orphans: Vec<i64> = conn
+        .prepare(&format!(
+            "SELECT rowid FROM {vec} WHERE rowid NOT IN (SELECT rowid FROM {owner})"
+        ))?
+        .query_map([], |r| r.get(0))?
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("Failed to find orphaned rows in {vec}"))?;
+    for rowid in &orphans {
+        conn.execute(
+            &format!("DELETE FROM {vec} WHERE rowid = ?1"),
+            params![rowid],
+        )
+        .with_context(|| format!("Failed to prune orphaned row from {vec}"))?;
+    }
+    Ok(orphans.len())
+}
+
+/// Pages copied per backup step between progress reports.
+const BACKUP_PAGES_PER_STEP: i32 = 256;
+
+impl KnowledgeGraphStorage {
+    /// Copy the database to a new file at `dest` with SQLite's online
+    /// backup API, reporting stage `"backup"` to `progress` in pages after
+    /// every step.  Fails with [`UForgeError::Conflict`] when `dest` exists.
+    /// Cancelling fails with [`UForgeError::Cancelled`] and removes the
+    /// partial copy.
+    pub fn backup_to(&self, dest: &Path, progress: &dyn ProgressSink) -> Result<()> {
+        if dest.exists() {
+            return Err(UForgeError::Conflict(format!("{dest:?} already exists")));
+        }
+        let result = self.copy_pages(dest, progress);
+        if result.is_err() {
+            let _ = std::fs::remove_file(dest);
+        }
+        result
+    }
+
+    fn copy_pages(&self, dest: &Path, progress: &dyn ProgressSink) -> Result<()> {
+        let conn = self.conn.lock();
+        let mut target = Connection::open(dest)
+            .with_context(|| format!("Failed to create backup at {dest:?}"))?;
+        let backup = Backup::new(&conn, &mut target).context("Failed to start backup")?;
+        loop {
+            progress.check_cancelled()?;
+            let step = backup
+                .step(BACKUP_PAGES_PER_STEP)
+                .context("Failed to copy database pages")?;
+            let pages = backup.progress();
+            let total = pages.pagecount.max(0) as usize;
+            let done = total.saturating_sub(pages.remaining.max(0) as usize);
+            progress.report(&Progress::new("backup", done, Some(total)));
+            match step {
+                StepResult::Done => return Ok(()),
+                StepResult::More => {}
+                // Another connection holds a lock; retry shortly.
+                StepResult::Busy | StepResult::Locked => {
+                    std::thread::sleep(std::time::Duration::from_millis(10))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Prune vectors whose chunk or profile is gone, trim revision history
+    /// beyond `revisions_per_chunk` and before `revisions_before`, trim
+    /// node and edge history before `history_before`, then merge the FTS
+    /// index, `VACUUM` and truncate the WAL.
+    ///
+    /// Trimming always keeps the newest revision of each chunk and the
+    /// newest history row of each node and edge.
+    pub fn compact(
+        &self,
+        revisions_per_chunk: Option<usize>,
+        revisions_before: Option<DateTime<Utc>>,
+        history_before: Option<DateTime<Utc>>,
+    ) -> Result<CompactionReport> {
+        let mut conn = self.conn.lock();
+        let bytes_before = database_bytes(&conn)?;
+
+        let tx = conn.transaction()?;
+        let mut orphan_vectors = 0;
+        for (vec, owner) in [
+            ("chunks_vec", "chunks"),
+            ("chunks_vec_hq", "chunks"),
+            ("node_profiles_vec", "node_profiles"),
+        ] {
+            orphan_vectors += prune_orphan_vectors(&tx, vec, owner)?;
+        }
+
+        let mut revisions = 0;
+        if let Some(keep) = revisions_per_chunk {
+            revisions += tx
+                .execute(
+                    "DELETE FROM chunk_revisions WHERE id IN (
+                         SELECT id FROM (
+                             SELECT id, ROW_NUMBER() OVER (
+                                 PARTITION BY chunk_id ORDER BY id DESC
+                             ) AS newer
+                             FROM chunk_revisions
+                         ) WHERE newer > ?1
+                     )",
+                    params![keep.max(1) as i64],
+                )
+                .context("Failed to trim chunk revisions by count")?;
+        }
+        if let Some(before) = revisions_before {
+            revisions += tx
+                .execute(
+                    "DELETE FROM chunk_revisions
+                     WHERE julianday(revised_at) < julianday(?1)
+                       AND id NOT IN (SELECT MAX(id) FROM chunk_revisions GROUP BY chunk_id)",
+                    params![before.to_rfc3339()],
+                )
+                .context("Failed to trim chunk revisions by age")?;
+        }
+
+        let mut history_rows = 0;
+        if let Some(before) = history_before {
+            let before = before.to_rfc3339();
+            history_rows += tx
+                .execute(
+                    "DELETE FROM node_history
+                     WHERE julianday(recorded_at) < julianday(?1)
+                       AND id NOT IN (SELECT MAX(id) FROM node_history GROUP BY node_id)",
+                    params![before],
+                )
+                .context("Failed to trim node history")?;
+            history_rows += tx
+                .execute(
+                    "DELETE FROM edge_history
+                     WHERE julianday(recorded_at) < julianday(?1)
+                       AND id NOT IN (
+                           SELECT MAX(id) FROM edge_history
+                           GROUP BY source_id, target_id, edge_type
+                       )",
+                    params![before],
+                )
+                .context("Failed to trim edge history")?;
+        }
+        for table in ["chunks_fts", "chunks_fts_trigram"] {
+            tx.execute(
+                &format!("INSERT INTO {table}({table}) VALUES ('optimize')"),
+                [],
+            )
+            .context("Failed to optimize FTS index")?;
+        }
+        tx.commit().context("Failed to commit compaction")?;
+
+        // VACUUM cannot run inside a transaction.
+        conn.execute_batch("VACUUM; PRAGMA optimize;")
+            .context("Failed to vacuum database")?;
+        // In-memory and rollback-journal databases report an error here;
+        // there is no WAL to truncate then.
+        let _ = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()));
+
+        Ok(CompactionReport {
+            bytes_before,
+            bytes_after: database_bytes(&conn)?,
+            orphan_vectors,
+            revisions,
+            history_rows,
+        })
+    }
+}
@@ -6,5 +6,40 @@ mod chunks;
 mod fts;
 mod traversal;
 mod positions;
+mod proposals;
+mod consistency;
+mod revisions;
+mod profiles;
+mod similarity;
+mod settings;
+mod glossary;
+mod speakers;
+mod health;
+mod history;
+mod staging;
+mod branches;
+mod search_log;
+mod spatial;
+mod secondary;
+mod cache;
+mod stats;
+mod intents;
+mod embed_queue;
+mod embedding_status;
+mod jobs;
+mod ledger;
+mod reveals;
+mod external_refs;
+mod archive;
+mod canonical;
+mod maintenance;
+mod chunk_links;
+mod flashcards;
 
 pub use storage::{KnowledgeGraphStorage, GraphStats, DEFAULT_EMBEDDING_CONTEXT_TOKENS, EMBEDDING_DIMENSIONS, HIGH_QUALITY_EMBEDDING_DIMENSIONS, MAX_CHUNK_TOKENS};
+pub use cache::{ReadCacheStats, DEFAULT_READ_CACHE_BYTES};
+pub use health::IndexHealth;
+pub use stats::{ChunkTypeStats, ExtendedStats};
+pub use search_log::SearchLogCounts;
+pub use maintenance::CompactionReport;
+pub(crate) use embedding_status::ChunkEmbeddingRecord;
@@ -1,10 +1,15 @@
 //! Node CRUD methods for KnowledgeGraphStorage.
 
+use super::profiles::refresh_node_profile;
+use super::spatial::refresh_node_coordinates;
 use super::storage::*;
-use anyhow::{Context, Result};
-use rusqlite::{params, OptionalExtension};
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
 
-use crate::types::{ObjectId, ObjectMetadata};
+use crate::diagnostics::trace_operation;
+use crate::error::{Result, UForgeError};
+use crate::events::GraphEvent;
+use crate::types::{Lifecycle, ObjectId, ObjectMetadata};
 
 impl KnowledgeGraphStorage {
     /// Insert or update a node.
@@ -14,38 +19,32 @@ impl KnowledgeGraphStorage {
     /// followed by an INSERT, which would fire the `ON DELETE CASCADE` on the
     /// `edges` and `chunks` tables and wipe out every relationship and text
     /// chunk every time a node property changes.
+    ///
+    /// A `lifecycle` of `None` stores `canon` for new rows and leaves the
+    /// existing lifecycle untouched on update.  Emits
+    /// [`GraphEvent::ObjectCreated`] when the id was not stored before.
     pub fn upsert_node(&self, metadata: ObjectMetadata) -> Result<()> {
-        let conn = self.conn.lock();
-        conn.execute(
-            "INSERT INTO nodes
-                 (id, object_type, schema_name, name, properties, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-             ON CONFLICT(id) DO UPDATE SET
-                 object_type  = excluded.object_type,
-                 schema_name  = excluded.schema_name,
-                 name         = excluded.name,
-                 properties   = excluded.properties,
-                 updated_at   = excluded.updated_at",
-            params![
-                metadata.id.hyphenated().to_string(),
-                metadata.object_type,
-                metadata.schema_name,
-                metadata.name,
-                metadata.properties.to_string(),
-                metadata.created_at.to_rfc3339(),
-                metadata.updated_at.to_rfc3339(),
-            ],
-        )
-        .context("Failed to upsert node")?;
+        let inserted = write_node(&self.conn.lock(), &metadata)?;
+        if inserted {
+            self.emit(GraphEvent::object_created(&metadata));
+        }
         Ok(())
     }
 
     /// Retrieve a node by its UUID.  Returns `Ok(None)` when the ID is unknown.
+    ///
+    /// Served from the read cache when possible; see
+    /// [`get_node_shared`](Self::get_node_shared) to avoid the clone.
     pub fn get_node(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
+        Ok(self.get_node_shared(id)?.map(|node| (*node).clone()))
+    }
+
+    /// Read a node from the database, bypassing the read cache.
+    pub(super) fn read_node(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
         let conn = self.conn.lock();
         let result = conn
             .query_row(
-                "SELECT id, object_type, schema_name, name, properties, created_at, updated_at
+                "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
                  FROM nodes
                  WHERE id = ?1",
                 params![id.hyphenated().to_string()],
@@ -58,6 +57,7 @@ impl KnowledgeGraphStorage {
                         row.get::<_, String>(4)?,
                         row.get::<_, String>(5)?,
                         row.get::<_, String>(6)?,
+                        row.get::<_, String>(7)?,
                     ))
                 },
             )
@@ -66,17 +66,18 @@ impl KnowledgeGraphStorage {
 
         match result {
             None => Ok(None),
-            Some((id_s, ot, sn, nm, props, ca, ua)) => {
-                Ok(Some(row_to_metadata(id_s, ot, sn, nm, props, ca, ua)?))
+            Some((id_s, ot, sn, nm, props, ca, ua, lc)) => {
+                Ok(Some(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?))
             }
         }
     }
 
     /// Return every node stored in the graph.
     pub fn get_all_objects(&self) -> Result<Vec<ObjectMetadata>> {
+        let mut op = trace_operation("storage", "get_all_objects");
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
              FROM nodes",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -88,14 +89,16 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
                 row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
             ))
         })?;
 
         let mut out = Vec::new();
         for row in rows {
-            let (id_s, ot, sn, nm, props, ca, ua) = row?;
-            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua)?);
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
         }
+        op.set_items(out.len());
         Ok(out)
     }
 
@@ -105,7 +108,7 @@ impl KnowledgeGraphStorage {
     pub fn find_nodes_by_name(&self, object_type: &str, name: &str) -> Result<Vec<ObjectMetadata>> {
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
              FROM nodes
              WHERE object_type = ?1 AND name = ?2",
         )?;
@@ -118,13 +121,14 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
                 row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
             ))
         })?;
 
         let mut out = Vec::new();
         for row in rows {
-            let (id_s, ot, sn, nm, props, ca, ua) = row?;
-            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua)?);
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
         }
         Ok(out)
     }
@@ -136,7 +140,7 @@ impl KnowledgeGraphStorage {
     pub fn find_nodes_by_name_only(&self, name: &str) -> Result<Vec<ObjectMetadata>> {
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
              FROM nodes
              WHERE name = ?1",
         )?;
@@ -149,13 +153,50 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
                 row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
+        }
+        Ok(out)
+    }
+
+    /// Nodes whose name contains `fragment`, ignoring ASCII case, shortest
+    /// name first.  At most `limit` rows.
+    pub fn find_nodes_by_name_fragment(
+        &self,
+        fragment: &str,
+        limit: usize,
+    ) -> Result<Vec<ObjectMetadata>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
+             FROM nodes
+             WHERE instr(lower(name), lower(?1)) > 0
+             ORDER BY length(name), name
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![fragment, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
             ))
         })?;
 
         let mut out = Vec::new();
         for row in rows {
-            let (id_s, ot, sn, nm, props, ca, ua) = row?;
-            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua)?);
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
         }
         Ok(out)
     }
@@ -171,7 +212,7 @@ impl KnowledgeGraphStorage {
     ) -> Result<Vec<ObjectMetadata>> {
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
              FROM nodes
              ORDER BY name
              LIMIT ?1 OFFSET ?2",
@@ -185,17 +226,129 @@ impl KnowledgeGraphStorage {
                 row.get::<_, String>(4)?,
                 row.get::<_, String>(5)?,
                 row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
             ))
         })?;
 
         let mut out = Vec::new();
         for row in rows {
-            let (id_s, ot, sn, nm, props, ca, ua) = row?;
-            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua)?);
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
+        }
+        Ok(out)
+    }
+
+    /// Return every node whose lifecycle is one of `lifecycles`, ordered by name.
+    ///
+    /// Backed by `idx_nodes_lifecycle`.
+    pub fn get_nodes_by_lifecycle(&self, lifecycles: &[Lifecycle]) -> Result<Vec<ObjectMetadata>> {
+        if lifecycles.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = (1..=lifecycles.len())
+            .map(|i| format!("?{i}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, object_type, schema_name, name, properties, created_at, updated_at, lifecycle
+             FROM nodes
+             WHERE lifecycle IN ({placeholders})
+             ORDER BY name"
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params_from_iter(lifecycles.iter().map(|l| l.as_str())),
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                    row.get::<_, String>(7)?,
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id_s, ot, sn, nm, props, ca, ua, lc) = row?;
+            out.push(row_to_metadata(id_s, ot, sn, nm, props, ca, ua, lc)?);
+        }
+        Ok(out)
+    }
+
+    /// `(id, name)` of every node, without loading properties.
+    pub fn list_node_names(&self) -> Result<Vec<(ObjectId, String)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT id, name FROM nodes")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, name) = row?;
+            out.push((
+                ObjectId::parse_str(&id).with_context(|| format!("Invalid node UUID: '{id}'"))?,
+                name,
+            ));
+        }
+        Ok(out)
+    }
+
+    /// `(id, name)` of every node of `object_type`.
+    pub fn list_node_names_of_type(&self, object_type: &str) -> Result<Vec<(ObjectId, String)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT id, name FROM nodes WHERE object_type = ?1")?;
+        let rows = stmt.query_map(params![object_type], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, name) = row?;
+            out.push((
+                ObjectId::parse_str(&id).with_context(|| format!("Invalid node UUID: '{id}'"))?,
+                name,
+            ));
         }
         Ok(out)
     }
 
+    /// Return the stored lifecycle of a node, or `None` if the ID is unknown.
+    pub fn get_node_lifecycle(&self, id: ObjectId) -> Result<Option<Lifecycle>> {
+        let conn = self.conn.lock();
+        let stored: Option<String> = conn
+            .query_row(
+                "SELECT lifecycle FROM nodes WHERE id = ?1",
+                params![id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to query node lifecycle")?;
+        Ok(stored.map(|s| str_to_lifecycle(&s)))
+    }
+
+    /// Change a node's lifecycle without touching its properties.
+    ///
+    /// The node's `updated_at` timestamp is bumped on every call.  Fails
+    /// with [`UForgeError::NotFound`] when no node has `id`.
+    pub fn set_node_lifecycle(&self, id: ObjectId, lifecycle: Lifecycle) -> Result<()> {
+        let conn = self.conn.lock();
+        let now = chrono::Utc::now().to_rfc3339();
+        let updated = conn
+            .execute(
+                "UPDATE nodes SET lifecycle = ?1, updated_at = ?2 WHERE id = ?3",
+                params![lifecycle.as_str(), now, id.hyphenated().to_string()],
+            )
+            .context("Failed to set node lifecycle")?;
+        if updated == 0 {
+            return Err(UForgeError::NotFound(format!("Object {id} not found")));
+        }
+        Ok(())
+    }
+
     /// Atomically set a single property on a node using SQLite's `json_set`.
     ///
     /// `value` must be a valid JSON-encoded value (e.g. `"\"foo\""` for a
@@ -208,22 +361,7 @@ impl KnowledgeGraphStorage {
         value: &serde_json::Value,
     ) -> Result<()> {
         let conn = self.conn.lock();
-        let json_path = format!("$.{key}");
-        let now = chrono::Utc::now().to_rfc3339();
-        conn.execute(
-            "UPDATE nodes
-             SET properties = json_set(properties, ?1, json(?2)),
-                 updated_at = ?3
-             WHERE id = ?4",
-            params![
-                json_path,
-                value.to_string(),
-                now,
-                id.hyphenated().to_string(),
-            ],
-        )
-        .context("Failed to set node property")?;
-        Ok(())
+        write_node_property(&conn, id, key, value)
     }
 
     /// Delete a node by ID.
@@ -240,3 +378,71 @@ impl KnowledgeGraphStorage {
         Ok(())
     }
 }
+
+/// The statement behind [`KnowledgeGraphStorage::upsert_node`], on a
+/// caller-held connection so it can run inside a transaction.  Returns
+/// whether the row was new; callers announce those as
+/// [`GraphEvent::ObjectCreated`] once their transaction commits.
+pub(super) fn write_node(conn: &Connection, metadata: &ObjectMetadata) -> Result<bool> {
+    let id = metadata.id.hyphenated().to_string();
+    let exists: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM nodes WHERE id = ?1)", params![id], |row| {
+            row.get(0)
+        })
+        .context("Failed to check for node")?;
+    conn.execute(
+        "INSERT INTO nodes
+             (id, object_type, schema_name, name, properties, created_at, updated_at,
+              lifecycle)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, 'canon'))
+         ON CONFLICT(id) DO UPDATE SET
+             object_type  = excluded.object_type,
+             schema_name  = excluded.schema_name,
+             name         = excluded.name,
+             properties   = excluded.properties,
+             updated_at   = excluded.updated_at,
+             lifecycle    = COALESCE(?8, nodes.lifecycle)",
+        params![
+            id,
+            metadata.object_type,
+            metadata.schema_name,
+            metadata.name,
+            metadata.properties.to_string(),
+            metadata.created_at.to_rfc3339(),
+            metadata.updated_at.to_rfc3339(),
+            metadata.lifecycle.map(|l| l.as_str()),
+        ],
+    )
+    .context("Failed to upsert node")?;
+    refresh_node_profile(conn, &id)?;
+    refresh_node_coordinates(conn, &id)?;
+    Ok(!exists)
+}
+
+/// The statement behind [`KnowledgeGraphStorage::set_node_property`], on a
+/// caller-held connection so it can run inside a transaction.
+pub(super) fn write_node_property(
+    conn: &Connection,
+    id: ObjectId,
+    key: &str,
+    value: &serde_json::Value,
+) -> Result<()> {
+    let json_path = format!("$.{key}");
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE nodes
+         SET properties = json_set(properties, ?1, json(?2)),
+             updated_at = ?3
+         WHERE id = ?4",
+        params![
+            json_path,
+            value.to_string(),
+            now,
+            id.hyphenated().to_string(),
+        ],
+    )
+    .context("Failed to set node property")?;
+    refresh_node_profile(conn, &id.hyphenated().to_string())?;
+    refresh_node_coordinates(conn, &id.hyphenated().to_string())?;
+    Ok(())
+}
@@ -4,9 +4,10 @@
 //! `ON DELETE CASCADE` keeps the table clean when nodes are removed.
 
 use std::collections::HashMap;
-use anyhow::{Context, Result};
+use anyhow::Context;
 use rusqlite::params;
 
+use crate::error::Result;
 use crate::types::ObjectId;
 
 use super::storage::KnowledgeGraphStorage;
@@ -0,0 +1,261 @@
+//! Object profile text and its embedding for KnowledgeGraphStorage.
+//!
+//! Every node gets one row in `node_profiles` holding
+//! [`ObjectMetadata::profile_text`] and its content hash.  The row is
+//! refreshed whenever the node is written; when the hash changes the row's
+//! vector in `node_profiles_vec` is dropped so it shows up in
+//! [`get_unembedded_profiles`](KnowledgeGraphStorage::get_unembedded_profiles)
+//! again.
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::{EmbeddingDimensionMismatch, Result};
+use crate::text::content_hash;
+use crate::types::{ObjectId, ObjectMetadata};
+
+use super::storage::{KnowledgeGraphStorage, EMBEDDING_DIMENSIONS};
+
+impl KnowledgeGraphStorage {
+    /// Recompute the profile of every node, e.g. for databases created before
+    /// profiles existed.  Returns how many profiles changed.
+    pub fn sync_node_profiles(&self) -> Result<usize> {
+        let ids: Vec<String> = {
+            let conn = self.conn.lock();
+            let mut stmt = conn.prepare("SELECT id FROM nodes")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()
+                .context("Failed to list nodes for profile sync")?
+        };
+        let mut changed = 0;
+        for id in ids {
+            let conn = self.conn.lock();
+            if refresh_node_profile(&conn, &id)? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Profiles whose text has no stored 768-dim embedding yet, as
+    /// `(object_id, profile_text)` pairs.
+    pub fn get_unembedded_profiles(&self) -> Result<Vec<(ObjectId, String)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT p.object_id, p.content
+             FROM node_profiles p
+             WHERE p.rowid NOT IN (SELECT rowid FROM node_profiles_vec)",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, content) = row?;
+            out.push((
+                ObjectId::parse_str(&id).with_context(|| format!("Invalid object UUID: '{id}'"))?,
+                content,
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Store or replace the profile embedding for `object_id`.
+    ///
+    /// # Errors
+    /// * `object_id` has no profile row (the node does not exist).
+    /// * `embedding.len() != EMBEDDING_DIMENSIONS`.
+    pub fn upsert_profile_embedding(&self, object_id: ObjectId, embedding: &[f32]) -> Result<()> {
+        if embedding.len() != EMBEDDING_DIMENSIONS {
+            return Err(EmbeddingDimensionMismatch {
+                table: "node_profiles_vec".to_string(),
+                stored: EMBEDDING_DIMENSIONS,
+                expected: embedding.len(),
+            }
+            .into());
+        }
+
+        let conn = self.conn.lock();
+        let rowid: i64 = conn
+            .query_row(
+                "SELECT rowid FROM node_profiles WHERE object_id = ?1",
+                params![object_id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .with_context(|| {
+                format!("upsert_profile_embedding: no profile for object '{object_id}'")
+            })?;
+
+        let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+        // vec0 has no upsert — delete then insert under the same lock.
+        conn.execute("DELETE FROM node_profiles_vec WHERE rowid = ?1", params![rowid])
+            .context("Failed to delete old profile embedding")?;
+        conn.execute(
+            "INSERT INTO node_profiles_vec(rowid, embedding) VALUES (?1, ?2)",
+            params![rowid, bytes],
+        )
+        .context("Failed to insert profile embedding")?;
+        Ok(())
+    }
+
+    /// Nearest-neighbour search over profile embeddings.
+    ///
+    /// Returns `(object_id, profile_text, distance)` ordered by ascending
+    /// cosine distance.  Objects without a profile vector are invisible.
+    pub fn search_profiles_semantic(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(ObjectId, String, f32)>> {
+        let bytes: Vec<u8> = query_embedding
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT p.object_id, p.content, v.distance
+             FROM node_profiles p
+             INNER JOIN (
+                 SELECT rowid, distance
+                 FROM   node_profiles_vec
+                 WHERE  embedding MATCH ?1
+                 ORDER  BY distance
+                 LIMIT  ?2
+             ) v ON p.rowid = v.rowid
+             ORDER BY v.distance",
+        )?;
+        let rows = stmt.query_map(params![bytes, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)? as f32,
+            ))
+        })?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, content, distance) = row?;
+            out.push((
+                ObjectId::parse_str(&id)
+                    .with_context(|| format!("Invalid object UUID in profile result: '{id}'"))?,
+                content,
+                distance,
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Objects whose profile embeddings lie nearest `object_id`'s, as
+    /// `(object_id, distance)` ordered by ascending cosine distance, at most
+    /// `limit` of them.  The object itself is excluded.
+    ///
+    /// Returns `None` when `object_id` has no profile embedding yet.
+    pub fn similar_profiles(
+        &self,
+        object_id: ObjectId,
+        limit: usize,
+    ) -> Result<Option<Vec<(ObjectId, f32)>>> {
+        let embedding: Option<Vec<u8>> = self
+            .conn
+            .lock()
+            .query_row(
+                "SELECT v.embedding
+                 FROM node_profiles p
+                 INNER JOIN node_profiles_vec v ON v.rowid = p.rowid
+                 WHERE p.object_id = ?1",
+                params![object_id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to load profile embedding")?;
+        let Some(embedding) = embedding else {
+            return Ok(None);
+        };
+        let query: Vec<f32> = embedding
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Ok(Some(
+            self.search_profiles_semantic(&query, limit + 1)?
+                .into_iter()
+                .filter(|(id, _, _)| *id != object_id)
+                .take(limit)
+                .map(|(id, _, distance)| (id, distance))
+                .collect(),
+        ))
+    }
+
+    /// Current profile text for `object_id`, if the node exists.
+    pub fn get_node_profile(&self, object_id: ObjectId) -> Result<Option<String>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                "SELECT content FROM node_profiles WHERE object_id = ?1",
+                params![object_id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to query node profile")?)
+    }
+}
+
+/// Rebuild the profile row for node `id` from its stored name, type, and
+/// properties.  Returns `true` when the profile text changed, in which case
+/// its old embedding has been removed.
+///
+/// Takes an already-locked connection so node writers can call it under the
+/// same lock as the write itself.
+pub(super) fn refresh_node_profile(conn: &Connection, id: &str) -> Result<bool> {
+    let Some((object_type, name, properties)) = conn
+        .query_row(
+            "SELECT object_type, name, properties FROM nodes WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .context("Failed to load node for profile refresh")?
+    else {
+        return Ok(false);
+    };
+
+    let mut meta = ObjectMetadata::new(object_type, name);
+    meta.properties = serde_json::from_str(&properties)
+        .with_context(|| format!("Invalid properties JSON on node '{id}'"))?;
+    let text = meta.profile_text();
+    let hash = content_hash(&text);
+
+    let existing: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT rowid, content_hash FROM node_profiles WHERE object_id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .context("Failed to query node profile")?;
+    if existing.as_ref().is_some_and(|(_, h)| *h == hash) {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "INSERT INTO node_profiles (object_id, content, content_hash, updated_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(object_id) DO UPDATE SET
+             content      = excluded.content,
+             content_hash = excluded.content_hash,
+             updated_at   = excluded.updated_at",
+        params![id, text, hash, chrono::Utc::now().to_rfc3339()],
+    )
+    .context("Failed to upsert node profile")?;
+    if let Some((rowid, _)) = existing {
+        conn.execute("DELETE FROM node_profiles_vec WHERE rowid = ?1", params![rowid])
+            .context("Failed to delete stale profile embedding")?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,194 @@
+//! Persistence for the AI proposal review queue.
+//!
+//! Proposals live in the `proposals` table, fully separate from `nodes` and
+//! `edges`.  The `change` column holds the JSON-serialised
+//! [`ProposedChange`]; `kind` duplicates its discriminant for filtering.
+
+use anyhow::{anyhow, Context};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::{Result, UForgeError};
+use crate::proposals::{Proposal, ProposalId, ProposalStatus, ProposedChange};
+use crate::types::{Edge, ObjectMetadata};
+
+use super::storage::KnowledgeGraphStorage;
+
+const PROPOSAL_COLUMNS: &str = "id, change, status, source, created_at, resolved_at";
+
+impl KnowledgeGraphStorage {
+    /// Insert a new proposal row.
+    pub fn insert_proposal(&self, proposal: &Proposal) -> Result<()> {
+        let change_json =
+            serde_json::to_string(&proposal.change).context("Failed to serialize proposal")?;
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO proposals (id, kind, change, status, source, created_at, resolved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                proposal.id.hyphenated().to_string(),
+                proposal.change.kind(),
+                change_json,
+                proposal.status.as_str(),
+                proposal.source,
+                proposal.created_at.to_rfc3339(),
+                proposal.resolved_at.map(|t| t.to_rfc3339()),
+            ],
+        )
+        .context("Failed to insert proposal")?;
+        Ok(())
+    }
+
+    /// Retrieve a proposal by ID.
+    pub fn get_proposal(&self, id: ProposalId) -> Result<Option<Proposal>> {
+        let conn = self.conn.lock();
+        let row = conn
+            .query_row(
+                &format!("SELECT {PROPOSAL_COLUMNS} FROM proposals WHERE id = ?1"),
+                params![id.hyphenated().to_string()],
+                read_proposal_row,
+            )
+            .optional()
+            .context("Failed to query proposal")?;
+        row.map(row_to_proposal).transpose()
+    }
+
+    /// List proposals ordered by creation time, optionally filtered by status.
+    pub fn list_proposals(&self, status: Option<ProposalStatus>) -> Result<Vec<Proposal>> {
+        let conn = self.conn.lock();
+        let rows: Vec<ProposalRow> = match status {
+            Some(status) => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {PROPOSAL_COLUMNS} FROM proposals
+                     WHERE status = ?1 ORDER BY created_at"
+                ))?;
+                let rows = stmt.query_map(params![status.as_str()], read_proposal_row)?;
+                rows.collect::<rusqlite::Result<_>>()
+                    .context("Failed to list proposals")?
+            }
+            None => {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {PROPOSAL_COLUMNS} FROM proposals ORDER BY created_at"
+                ))?;
+                let rows = stmt.query_map([], read_proposal_row)?;
+                rows.collect::<rusqlite::Result<_>>()
+                    .context("Failed to list proposals")?
+            }
+        };
+        rows.into_iter().map(row_to_proposal).collect()
+    }
+
+    /// Mark a pending proposal as resolved.
+    ///
+    /// When `edited` is `Some`, the stored change is replaced so the row
+    /// records what was actually applied.  Fails with
+    /// [`UForgeError::Conflict`] if the proposal was resolved already.
+    pub fn resolve_proposal(
+        &self,
+        id: ProposalId,
+        status: ProposalStatus,
+        edited: Option<&ProposedChange>,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        claim_pending(&conn, id, status, edited)
+    }
+
+    /// Upsert `nodes` and `edges` and mark pending proposal `id` accepted
+    /// (recording `edited` as in [`resolve_proposal`](Self::resolve_proposal)),
+    /// in one transaction.  The status flips from `pending` only, so of two
+    /// concurrent accepts exactly one applies; the other fails with
+    /// [`UForgeError::Conflict`] and changes nothing.  Callers validate the
+    /// change first, as [`KnowledgeGraph::commit_staging`] does.
+    ///
+    /// [`KnowledgeGraph::commit_staging`]: crate::KnowledgeGraph::commit_staging
+    pub fn accept_proposal(
+        &self,
+        id: ProposalId,
+        nodes: &[ObjectMetadata],
+        edges: &[Edge],
+        edited: Option<&ProposedChange>,
+    ) -> Result<()> {
+        self.apply_staged_with(nodes, edges, &[], &[], |conn| {
+            claim_pending(conn, id, ProposalStatus::Accepted, edited)
+        })
+    }
+}
+
+/// Move proposal `id` from `pending` to `status` — a compare-and-set, so a
+/// proposal is resolved once.
+fn claim_pending(
+    conn: &Connection,
+    id: ProposalId,
+    status: ProposalStatus,
+    edited: Option<&ProposedChange>,
+) -> Result<()> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let id_str = id.hyphenated().to_string();
+    let updated = match edited {
+        Some(change) => {
+            let change_json =
+                serde_json::to_string(change).context("Failed to serialize proposal")?;
+            conn.execute(
+                "UPDATE proposals
+                 SET status = ?1, resolved_at = ?2, kind = ?3, change = ?4
+                 WHERE id = ?5 AND status = 'pending'",
+                params![status.as_str(), now, change.kind(), change_json, id_str],
+            )
+        }
+        None => conn.execute(
+            "UPDATE proposals SET status = ?1, resolved_at = ?2
+             WHERE id = ?3 AND status = 'pending'",
+            params![status.as_str(), now, id_str],
+        ),
+    }
+    .context("Failed to resolve proposal")?;
+    if updated > 0 {
+        return Ok(());
+    }
+    let current: Option<String> = conn
+        .query_row(
+            "SELECT status FROM proposals WHERE id = ?1",
+            params![id_str],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to query proposal")?;
+    Err(match current {
+        Some(current) => UForgeError::Conflict(format!("Proposal {id} is already {current}")),
+        None => UForgeError::NotFound(format!("Proposal {id} not found")),
+    })
+}
+
+type ProposalRow = (String, String, String, Option<String>, String, Option<String>);
+
+fn read_proposal_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ProposalRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+fn row_to_proposal(
+    (id, change, status, source, created_at, resolved_at): ProposalRow,
+) -> Result<Proposal> {
+    Ok(Proposal {
+        id: ProposalId::parse_str(&id).context("Invalid proposal UUID")?,
+        change: serde_json::from_str(&change).context("Failed to parse proposal change")?,
+        status: ProposalStatus::parse(&status)
+            .ok_or_else(|| anyhow!("Unknown proposal status '{status}'"))?,
+        source,
+        created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+            .context("Invalid created_at")?
+            .with_timezone(&chrono::Utc),
+        resolved_at: resolved_at
+            .map(|t| {
+                chrono::DateTime::parse_from_rfc3339(&t)
+                    .map(|t| t.with_timezone(&chrono::Utc))
+                    .context("Invalid resolved_at")
+            })
+            .transpose()?,
+    })
+}
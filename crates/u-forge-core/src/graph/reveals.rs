@@ -0,0 +1,135 @@
+//! Persistence for reveal tracking.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::reveals::Reveal;
+use crate::types::{ChunkId, ObjectId};
+
+use super::storage::KnowledgeGraphStorage;
+
+fn parse_object_id(text: &str) -> Result<ObjectId> {
+    Ok(ObjectId::parse_str(text)
+        .with_context(|| format!("Invalid object id in reveals: '{text}'"))?)
+}
+
+impl KnowledgeGraphStorage {
+    /// Object that chunk `chunk_id` belongs to, if the chunk exists.
+    pub fn chunk_object_id(&self, chunk_id: ChunkId) -> Result<Option<ObjectId>> {
+        let conn = self.conn.lock();
+        let object_id: Option<String> = conn
+            .query_row(
+                "SELECT object_id FROM chunks WHERE id = ?1",
+                params![chunk_id.hyphenated().to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to look up chunk")?;
+        object_id.as_deref().map(parse_object_id).transpose()
+    }
+
+    /// Record that `object_id` (or one chunk of it) was shown to each of
+    /// `players`, in one transaction.
+    pub fn insert_reveals(
+        &self,
+        object_id: ObjectId,
+        chunk_id: Option<ChunkId>,
+        players: &[ObjectId],
+        session_id: Option<ObjectId>,
+    ) -> Result<Vec<Reveal>> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let now = chrono::Utc::now();
+        let mut reveals = Vec::with_capacity(players.len());
+        for &player_id in players {
+            let reveal = Reveal {
+                id: Uuid::new_v4(),
+                player_id,
+                object_id,
+                chunk_id,
+                session_id,
+                revealed_at: now,
+            };
+            tx.execute(
+                "INSERT INTO reveals (id, player_id, object_id, chunk_id, session_id, revealed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    reveal.id.to_string(),
+                    player_id.hyphenated().to_string(),
+                    object_id.hyphenated().to_string(),
+                    chunk_id.map(|c| c.hyphenated().to_string()),
+                    session_id.map(|s| s.hyphenated().to_string()),
+                    now.to_rfc3339(),
+                ],
+            )
+            .context("Failed to insert reveal")?;
+            reveals.push(reveal);
+        }
+        tx.commit()?;
+        Ok(reveals)
+    }
+
+    /// Reveals matching every filter given, newest first.
+    pub fn list_reveals(
+        &self,
+        player_id: Option<ObjectId>,
+        object_id: Option<ObjectId>,
+        session_id: Option<ObjectId>,
+    ) -> Result<Vec<Reveal>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, player_id, object_id, chunk_id, session_id, revealed_at
+             FROM reveals
+             WHERE (?1 IS NULL OR player_id = ?1)
+               AND (?2 IS NULL OR object_id = ?2)
+               AND (?3 IS NULL OR session_id = ?3)
+             ORDER BY revealed_at DESC, rowid DESC",
+        )?;
+        let filter = |id: Option<ObjectId>| id.map(|id| id.hyphenated().to_string());
+        let rows = stmt.query_map(
+            params![filter(player_id), filter(object_id), filter(session_id)],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, player_id, object_id, chunk_id, session_id, revealed_at) = row?;
+            out.push(Reveal {
+                id: Uuid::parse_str(&id).with_context(|| format!("Invalid reveal id: '{id}'"))?,
+                player_id: parse_object_id(&player_id)?,
+                object_id: parse_object_id(&object_id)?,
+                chunk_id: chunk_id
+                    .map(|c| {
+                        ChunkId::parse_str(&c)
+                            .with_context(|| format!("Invalid chunk id in reveals: '{c}'"))
+                    })
+                    .transpose()?,
+                session_id: session_id.as_deref().map(parse_object_id).transpose()?,
+                revealed_at: chrono::DateTime::parse_from_rfc3339(&revealed_at)
+                    .with_context(|| format!("Invalid reveal revealed_at: '{revealed_at}'"))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Delete reveal `id`.  Returns whether it existed.
+    pub fn delete_reveal(&self, id: Uuid) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute("DELETE FROM reveals WHERE id = ?1", params![id.to_string()])
+            .context("Failed to delete reveal")?;
+        Ok(deleted > 0)
+    }
+}
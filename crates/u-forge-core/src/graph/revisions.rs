@@ -0,0 +1,164 @@
+//! Chunk edit history for KnowledgeGraphStorage.
+//!
+//! Content edits go through [`update_chunk_content`](KnowledgeGraphStorage::update_chunk_content),
+//! which archives each version in `chunk_revisions` and drops the chunk's
+//! stale vectors so the embedding pipeline picks it up again.  Edits whose
+//! content hash matches the current content are no-ops: no revision is
+//! written and existing embeddings are kept.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use crate::error::Result;
+use crate::text::{content_hash, count_chunk_tokens};
+use crate::types::{ChunkId, ChunkRevision};
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Replace a chunk's content, recording the edit in `chunk_revisions`.
+    ///
+    /// Returns `Ok(false)` without writing anything when `content` hashes to
+    /// the same value as the stored content.  On a real change the chunk's
+    /// `token_count` is recomputed, FTS5 is updated by the `chunks_au`
+    /// trigger, and its rows in `chunks_vec` / `chunks_vec_hq` are deleted so
+    /// it is re-embedded on the next embedding pass.
+    ///
+    /// # Errors
+    /// * `chunk_id` does not exist in the `chunks` table.
+    pub fn update_chunk_content(
+        &self,
+        chunk_id: ChunkId,
+        content: &str,
+        author: Option<&str>,
+    ) -> Result<bool> {
+        let id_str = chunk_id.hyphenated().to_string();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        let (rowid, old_content, created_at): (i64, String, String) = tx
+            .query_row(
+                "SELECT rowid, content, created_at FROM chunks WHERE id = ?1",
+                params![id_str],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .with_context(|| format!("update_chunk_content: chunk '{chunk_id}' not found"))?;
+
+        let old_hash = content_hash(&old_content);
+        let new_hash = content_hash(content);
+        if old_hash == new_hash {
+            return Ok(false);
+        }
+
+        // Chunks edited for the first time have no history yet — record the
+        // original content as the baseline revision.
+        let has_history: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM chunk_revisions WHERE chunk_id = ?1)",
+                params![id_str],
+                |row| row.get(0),
+            )
+            .context("Failed to check chunk history")?;
+        if !has_history {
+            tx.execute(
+                "INSERT INTO chunk_revisions (chunk_id, content, content_hash, author, revised_at)
+                 VALUES (?1, ?2, ?3, NULL, ?4)",
+                params![id_str, old_content, old_hash, created_at],
+            )
+            .context("Failed to record baseline chunk revision")?;
+        }
+
+        tx.execute(
+            "INSERT INTO chunk_revisions (chunk_id, content, content_hash, author, revised_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id_str,
+                content,
+                new_hash,
+                author,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )
+        .context("Failed to record chunk revision")?;
+
+        tx.execute(
+            "UPDATE chunks SET content = ?1, token_count = ?2 WHERE id = ?3",
+            params![content, count_chunk_tokens(content).max(1) as i64, id_str],
+        )
+        .context("Failed to update chunk content")?;
+
+        // The old vectors describe the old text; drop them so the chunk shows
+        // up in get_unembedded_chunks() / get_unembedded_chunks_hq().
+        tx.execute("DELETE FROM chunks_vec WHERE rowid = ?1", params![rowid])
+            .context("Failed to delete stale embedding from chunks_vec")?;
+        tx.execute("DELETE FROM chunks_vec_hq WHERE rowid = ?1", params![rowid])
+            .context("Failed to delete stale embedding from chunks_vec_hq")?;
+
+        tx.commit().context("Failed to commit chunk edit")?;
+        Ok(true)
+    }
+
+    /// Every recorded revision of `chunk_id`, oldest first.
+    ///
+    /// Empty for chunks that have never been edited.
+    pub fn get_chunk_history(&self, chunk_id: ChunkId) -> Result<Vec<ChunkRevision>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, chunk_id, content, content_hash, author, revised_at
+             FROM chunk_revisions
+             WHERE chunk_id = ?1
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![chunk_id.hyphenated().to_string()], read_revision_row)?;
+        let mut out = Vec::new();
+        for row in rows {
+            out.push(row_to_revision(row?)?);
+        }
+        Ok(out)
+    }
+
+    /// Look up a single revision by number.
+    pub fn get_chunk_revision(&self, revision: i64) -> Result<Option<ChunkRevision>> {
+        let conn = self.conn.lock();
+        let row = conn
+            .query_row(
+                "SELECT id, chunk_id, content, content_hash, author, revised_at
+                 FROM chunk_revisions
+                 WHERE id = ?1",
+                params![revision],
+                read_revision_row,
+            )
+            .optional()
+            .context("Failed to query chunk revision")?;
+        row.map(row_to_revision).transpose()
+    }
+}
+
+type RevisionRow = (i64, String, String, String, Option<String>, String);
+
+fn read_revision_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RevisionRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+fn row_to_revision(
+    (revision, chunk_id, content, content_hash, author, revised_at): RevisionRow,
+) -> Result<ChunkRevision> {
+    Ok(ChunkRevision {
+        revision,
+        chunk_id: ChunkId::parse_str(&chunk_id)
+            .with_context(|| format!("Invalid chunk UUID: '{chunk_id}'"))?,
+        content,
+        content_hash,
+        author,
+        revised_at: chrono::DateTime::parse_from_rfc3339(&revised_at)
+            .with_context(|| format!("Invalid revised_at timestamp: '{revised_at}'"))?
+            .with_timezone(&chrono::Utc),
+    })
+}
@@ -0,0 +1,183 @@
+//! Persistence for search telemetry.
+//!
+//! One `search_log` row per recorded search and one `search_clicks` row per
+//! result opened from it.  Timestamps are stored as fixed-width RFC 3339 UTC
+//! strings (microseconds, `Z` suffix) so `since` filters can compare them as
+//! text.
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::params;
+
+use crate::error::Result;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+/// Totals over the search log, from [`KnowledgeGraphStorage::search_log_counts`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLogCounts {
+    pub searches: usize,
+    pub zero_result_searches: usize,
+    /// Searches with at least one clicked result.
+    pub clicked_searches: usize,
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Lower bound for `since` filters; the epoch when unset.
+fn since_bound(since: Option<DateTime<Utc>>) -> String {
+    timestamp(since.unwrap_or(DateTime::<Utc>::UNIX_EPOCH))
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(s)
+        .with_context(|| format!("Invalid search log timestamp: '{s}'"))?
+        .with_timezone(&Utc))
+}
+
+impl KnowledgeGraphStorage {
+    /// Append a search to the log.
+    pub fn insert_search_log(
+        &self,
+        id: &str,
+        query: &str,
+        normalized: &str,
+        result_count: usize,
+        searched_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO search_log (id, query, normalized, result_count, searched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                query,
+                normalized,
+                result_count as i64,
+                timestamp(searched_at)
+            ],
+        )
+        .context("Failed to record search")?;
+        Ok(())
+    }
+
+    /// Record that the result at `rank` (0-based) of search `search_id` was
+    /// opened.  Returns `false` if the search is not in the log.
+    pub fn insert_search_click(
+        &self,
+        search_id: &str,
+        object_id: ObjectId,
+        rank: usize,
+        clicked_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let conn = self.conn.lock();
+        let inserted = conn
+            .execute(
+                "INSERT INTO search_clicks (search_id, object_id, rank, clicked_at)
+                 SELECT id, ?2, ?3, ?4 FROM search_log WHERE id = ?1",
+                params![
+                    search_id,
+                    object_id.hyphenated().to_string(),
+                    rank as i64,
+                    timestamp(clicked_at)
+                ],
+            )
+            .context("Failed to record search click")?;
+        Ok(inserted > 0)
+    }
+
+    /// Search totals since `since` (all time when `None`).
+    pub fn search_log_counts(&self, since: Option<DateTime<Utc>>) -> Result<SearchLogCounts> {
+        let conn = self.conn.lock();
+        let (searches, zero, clicked): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(result_count = 0), 0),
+                        COALESCE(SUM(EXISTS (SELECT 1 FROM search_clicks c
+                                             WHERE c.search_id = s.id)), 0)
+                 FROM search_log s WHERE searched_at >= ?1",
+                params![since_bound(since)],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .context("Failed to count searches")?;
+        Ok(SearchLogCounts {
+            searches: searches as usize,
+            zero_result_searches: zero as usize,
+            clicked_searches: clicked as usize,
+        })
+    }
+
+    /// Normalised queries that returned nothing since `since`, most frequent
+    /// first: `(latest spelling, times searched, last searched)`.
+    pub fn zero_result_queries(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<(String, usize, DateTime<Utc>)>> {
+        let conn = self.conn.lock();
+        // SQLite returns the bare `query` column from the row holding MAX().
+        let mut stmt = conn.prepare(
+            "SELECT query, COUNT(*) AS n, MAX(searched_at) AS last
+             FROM search_log
+             WHERE result_count = 0 AND searched_at >= ?1
+             GROUP BY normalized
+             ORDER BY n DESC, last DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![since_bound(since), limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (query, count, last) = row?;
+            out.push((query, count as usize, parse_timestamp(&last)?));
+        }
+        Ok(out)
+    }
+
+    /// Objects opened from search results since `since`, most clicked first.
+    pub fn top_clicked_objects(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<(ObjectId, usize)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT object_id, COUNT(*) AS n
+             FROM search_clicks
+             WHERE clicked_at >= ?1
+             GROUP BY object_id
+             ORDER BY n DESC, object_id
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![since_bound(since), limit as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, count) = row?;
+            let id = ObjectId::parse_str(&id)
+                .with_context(|| format!("Invalid object UUID in search clicks: '{id}'"))?;
+            out.push((id, count as usize));
+        }
+        Ok(out)
+    }
+
+    /// Delete the whole search log.  Returns the number of searches removed.
+    pub fn clear_search_log(&self) -> Result<usize> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM search_clicks", [])
+            .context("Failed to clear search clicks")?;
+        let deleted = conn
+            .execute("DELETE FROM search_log", [])
+            .context("Failed to clear search log")?;
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,107 @@
+//! Read-only secondary instances of a KnowledgeGraphStorage database.
+//!
+//! Tooling (exporters, CLIs, a player-facing server) can open a project that
+//! a primary — usually the desktop app — has open and is writing.  The
+//! database runs in WAL mode, so a read-only connection never blocks the
+//! primary and each query sees every transaction committed before it
+//! started; there is no copy to refresh.  [`data_version`] tells a follower
+//! whether anything has been committed since it last looked.
+//!
+//! [`data_version`]: KnowledgeGraphStorage::data_version
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use tokio::sync::broadcast;
+
+use crate::error::{Result, UForgeError};
+use crate::events::GRAPH_EVENT_CAPACITY;
+
+use super::cache::{ReadCache, DEFAULT_READ_CACHE_BYTES};
+use super::storage::{
+    check_or_init_embedding_dims, register_sqlite_vec, KnowledgeGraphStorage, EMBEDDING_DIMENSIONS,
+    HIGH_QUALITY_EMBEDDING_DIMENSIONS,
+};
+
+impl KnowledgeGraphStorage {
+    /// Open the existing database at `<db_path>/knowledge.db` read-only.
+    ///
+    /// Nothing is created or migrated, so the primary must have opened the
+    /// project at least once.  Fails with [`UForgeError::NotFound`] when there
+    /// is no database there; every write on the returned storage fails.
+    pub fn open_secondary(db_path: &Path) -> Result<Self> {
+        let db_file = db_path.join("knowledge.db");
+        if !db_file.is_file() {
+            return Err(UForgeError::NotFound(format!(
+                "No knowledge graph at {db_file:?}"
+            )));
+        }
+        register_sqlite_vec();
+        let conn = open_read_only(&db_file)?;
+
+        let initialised = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'nodes'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()
+            .context("Failed to inspect secondary database")?
+            .is_some();
+        if !initialised {
+            return Err(UForgeError::NotFound(format!(
+                "{db_file:?} has not been initialised by a primary yet"
+            )));
+        }
+        check_or_init_embedding_dims(
+            &conn,
+            &[
+                ("chunks_vec", EMBEDDING_DIMENSIONS),
+                ("chunks_vec_hq", HIGH_QUALITY_EMBEDDING_DIMENSIONS),
+                ("node_profiles_vec", EMBEDDING_DIMENSIONS),
+            ],
+        )?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            db_file,
+            cache: ReadCache::new(DEFAULT_READ_CACHE_BYTES),
+            events: broadcast::channel(GRAPH_EVENT_CAPACITY).0,
+        })
+    }
+
+    /// SQLite's `data_version` for this connection: it changes whenever
+    /// another connection commits to the database.
+    pub fn data_version(&self) -> Result<i64> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))
+            .context("Failed to read data_version")?)
+    }
+
+    /// Whether this storage was opened by
+    /// [`open_secondary`](Self::open_secondary).
+    pub fn is_read_only(&self) -> bool {
+        self.conn
+            .lock()
+            .is_readonly(rusqlite::DatabaseName::Main)
+            .unwrap_or(false)
+    }
+}
+
+/// Open `db_file` read-only, for a secondary or a short-lived reader.
+pub(super) fn open_read_only(db_file: &Path) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        db_file,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .with_context(|| format!("Failed to open SQLite database read-only at {db_file:?}"))?;
+    conn.execute_batch(
+        "PRAGMA query_only = ON; PRAGMA busy_timeout = 5000; PRAGMA mmap_size = 268435456;",
+    )
+    .context("Failed to configure read-only connection")?;
+    Ok(conn)
+}
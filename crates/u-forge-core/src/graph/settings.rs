@@ -0,0 +1,47 @@
+//! Per-project key/value settings for KnowledgeGraphStorage.
+//!
+//! Backed by the `project_settings` table.  Values are plain strings; callers
+//! own their encoding.
+
+use anyhow::Context;
+use rusqlite::{params, OptionalExtension};
+
+use crate::error::Result;
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Value stored under `key`, if any.
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock();
+        Ok(conn
+            .query_row(
+                "SELECT value FROM project_settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("Failed to read setting '{key}'"))?)
+    }
+
+    /// Store `value` under `key`, replacing any previous value.
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO project_settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )
+        .with_context(|| format!("Failed to write setting '{key}'"))?;
+        Ok(())
+    }
+
+    /// Remove `key`.  Returns `false` if it was not set.
+    pub fn delete_setting(&self, key: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute("DELETE FROM project_settings WHERE key = ?1", params![key])
+            .with_context(|| format!("Failed to delete setting '{key}'"))?;
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,277 @@
+//! Near-duplicate chunk detection for KnowledgeGraphStorage.
+//!
+//! Uses the 768-dim `chunks_vec` index: a chunk's stored embedding is read
+//! back and used as the ANN query.  Only embedded chunks take part.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::{Result, UForgeError};
+use crate::text::content_hash;
+use crate::types::{ChunkId, DuplicateChunkGroup, ObjectId, SimilarChunk};
+
+use super::storage::KnowledgeGraphStorage;
+
+/// Neighbours fetched per chunk before the distance threshold is applied.
+const SIMILAR_CHUNK_CANDIDATES: usize = 32;
+
+impl KnowledgeGraphStorage {
+    /// Chunks whose embeddings are within `threshold` cosine distance of
+    /// `chunk_id`'s embedding, closest first.  The chunk itself is excluded.
+    ///
+    /// Returns an empty `Vec` when `chunk_id` has no embedding yet.
+    pub fn find_similar_chunks(
+        &self,
+        chunk_id: ChunkId,
+        threshold: f32,
+    ) -> Result<Vec<SimilarChunk>> {
+        let conn = self.conn.lock();
+        let Some((rowid, embedding)) = conn
+            .query_row(
+                "SELECT c.rowid, v.embedding
+                 FROM chunks c
+                 INNER JOIN chunks_vec v ON v.rowid = c.rowid
+                 WHERE c.id = ?1",
+                params![chunk_id.hyphenated().to_string()],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .optional()
+            .context("Failed to load chunk embedding")?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut out = Vec::new();
+        for (other, distance) in nearest(&conn, &embedding)? {
+            if other == rowid || distance > threshold {
+                continue;
+            }
+            let (id, object_id, content): (String, String, String) = conn
+                .query_row(
+                    "SELECT id, object_id, content FROM chunks WHERE rowid = ?1",
+                    params![other],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .context("Failed to load similar chunk")?;
+            out.push(SimilarChunk {
+                chunk_id: ChunkId::parse_str(&id)
+                    .with_context(|| format!("Invalid chunk UUID: '{id}'"))?,
+                object_id: ObjectId::parse_str(&object_id)
+                    .with_context(|| format!("Invalid object UUID: '{object_id}'"))?,
+                content,
+                distance,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Group every embedded chunk with its near-duplicates.
+    ///
+    /// Two chunks are linked when their cosine distance is at most
+    /// `threshold`; groups are the connected components of that relation,
+    /// so A≈B and B≈C puts all three together.  Singletons are omitted.
+    /// Groups are ordered by size, largest first.
+    ///
+    /// The scan runs an ANN query per chunk, so it uses its own
+    /// read-only connection and writers are not held up meanwhile.
+    pub fn find_duplicate_chunks(&self, threshold: f32) -> Result<Vec<DuplicateChunkGroup>> {
+        self.on_reader(|conn| duplicate_groups(conn, threshold))
+    }
+
+    /// Fold `duplicates` into `keeper` and delete them, in one transaction.
+    /// Returns the number deleted.
+    ///
+    /// Their edit history moves to `keeper` (followed by a revision of the
+    /// keeper's current content, so the latest revision still matches it),
+    /// their consistency warnings are re-pointed at `keeper` (dropping any
+    /// that would pair it with itself), and language or time-range metadata
+    /// the keeper lacks is taken from them.  Fails with
+    /// [`UForgeError::NotFound`] when `keeper` does not exist.
+    pub fn merge_chunks_into(&self, keeper: ChunkId, duplicates: &[ChunkId]) -> Result<usize> {
+        let keep = keeper.hyphenated().to_string();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let content: String = tx
+            .query_row(
+                "SELECT content FROM chunks WHERE id = ?1",
+                params![keep],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to load kept chunk")?
+            .ok_or_else(|| UForgeError::NotFound(format!("Chunk {keeper} not found")))?;
+
+        let mut deleted = 0;
+        for dup in duplicates.iter().filter(|&&d| d != keeper) {
+            let dup = dup.hyphenated().to_string();
+            tx.execute(
+                "UPDATE chunk_revisions SET chunk_id = ?1 WHERE chunk_id = ?2",
+                params![keep, dup],
+            )
+            .context("Failed to move chunk revisions")?;
+            for column in ["first_chunk", "second_chunk"] {
+                tx.execute(
+                    &format!("UPDATE consistency_warnings SET {column} = ?1 WHERE {column} = ?2"),
+                    params![keep, dup],
+                )
+                .context("Failed to move consistency warnings")?;
+            }
+            tx.execute(
+                "UPDATE chunks SET
+                     language = COALESCE(language, (SELECT language FROM chunks WHERE id = ?2)),
+                     start_ms = COALESCE(start_ms, (SELECT start_ms FROM chunks WHERE id = ?2)),
+                     end_ms   = COALESCE(end_ms,   (SELECT end_ms   FROM chunks WHERE id = ?2))
+                 WHERE id = ?1",
+                params![keep, dup],
+            )
+            .context("Failed to merge chunk metadata")?;
+            deleted += tx
+                .execute("DELETE FROM chunks WHERE id = ?1", params![dup])
+                .context("Failed to delete duplicate chunk")?;
+        }
+        tx.execute(
+            "DELETE FROM consistency_warnings WHERE first_chunk = second_chunk",
+            [],
+        )
+        .context("Failed to drop self-referencing warnings")?;
+
+        let latest: Option<String> = tx
+            .query_row(
+                "SELECT content_hash FROM chunk_revisions WHERE chunk_id = ?1
+                 ORDER BY id DESC LIMIT 1",
+                params![keep],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to load chunk history")?;
+        let hash = content_hash(&content);
+        if latest.is_some_and(|latest| latest != hash) {
+            tx.execute(
+                "INSERT INTO chunk_revisions (chunk_id, content, content_hash, author, revised_at)
+                 VALUES (?1, ?2, ?3, NULL, ?4)",
+                params![keep, content, hash, chrono::Utc::now().to_rfc3339()],
+            )
+            .context("Failed to record merged chunk revision")?;
+        }
+        tx.commit().context("Failed to commit chunk merge")?;
+        Ok(deleted)
+    }
+
+    /// Delete a single chunk.  Returns `false` if it did not exist.
+    ///
+    /// The `chunks_ad` / `chunks_vec_ad` / `chunks_vec_hq_ad` triggers clean up
+    /// the FTS5 and vector-index rows.
+    pub fn delete_chunk(&self, chunk_id: ChunkId) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute(
+                "DELETE FROM chunks WHERE id = ?1",
+                params![chunk_id.hyphenated().to_string()],
+            )
+            .context("Failed to delete chunk")?;
+        Ok(deleted > 0)
+    }
+}
+
+/// The body of [`KnowledgeGraphStorage::find_duplicate_chunks`].
+fn duplicate_groups(conn: &Connection, threshold: f32) -> Result<Vec<DuplicateChunkGroup>> {
+    // (rowid, chunk id, object id, embedding), oldest first so the union
+    // root — and therefore the suggested keeper — is the earliest chunk.
+    let mut stmt = conn.prepare(
+        "SELECT c.rowid, c.id, c.object_id, v.embedding
+         FROM chunks c
+         INNER JOIN chunks_vec v ON v.rowid = c.rowid
+         ORDER BY c.created_at, c.rowid",
+    )?;
+    let rows: Vec<(i64, String, String, Vec<u8>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<_>>()
+        .context("Failed to load chunk embeddings")?;
+    drop(stmt);
+
+    let index: HashMap<i64, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, (rowid, ..))| (*rowid, i))
+        .collect();
+    let mut parent: Vec<usize> = (0..rows.len()).collect();
+    let mut link_distance = vec![0.0f32; rows.len()];
+
+    for (i, (rowid, _, _, embedding)) in rows.iter().enumerate() {
+        for (other, distance) in nearest(conn, embedding)? {
+            if other == *rowid || distance > threshold {
+                continue;
+            }
+            let Some(&j) = index.get(&other) else { continue };
+            let (ri, rj) = (find_root(&mut parent, i), find_root(&mut parent, j));
+            let root = ri.min(rj);
+            if ri != rj {
+                parent[ri.max(rj)] = root;
+            }
+            link_distance[root] = link_distance[root]
+                .max(link_distance[ri.max(rj)])
+                .max(distance);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..rows.len() {
+        let root = find_root(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut out = Vec::new();
+    for (root, members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        let mut chunk_ids = Vec::with_capacity(members.len());
+        let mut object_ids: Vec<ObjectId> = Vec::new();
+        for &m in &members {
+            let (_, id, object_id, _) = &rows[m];
+            chunk_ids.push(
+                ChunkId::parse_str(id).with_context(|| format!("Invalid chunk UUID: '{id}'"))?,
+            );
+            let object_id = ObjectId::parse_str(object_id)
+                .with_context(|| format!("Invalid object UUID: '{object_id}'"))?;
+            if !object_ids.contains(&object_id) {
+                object_ids.push(object_id);
+            }
+        }
+        out.push(DuplicateChunkGroup {
+            chunk_ids,
+            object_ids,
+            max_distance: link_distance[root],
+        });
+    }
+    out.sort_by_key(|group| std::cmp::Reverse(group.chunk_ids.len()));
+    Ok(out)
+}
+
+/// `(rowid, distance)` of the chunks nearest to `embedding` (raw `float[768]`
+/// bytes as stored in `chunks_vec`).
+fn nearest(conn: &Connection, embedding: &[u8]) -> Result<Vec<(i64, f32)>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT rowid, distance
+         FROM   chunks_vec
+         WHERE  embedding MATCH ?1
+         ORDER  BY distance
+         LIMIT  ?2",
+    )?;
+    let rows = stmt.query_map(params![embedding, SIMILAR_CHUNK_CANDIDATES as i64], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)? as f32))
+    })?;
+    Ok(rows
+        .collect::<rusqlite::Result<_>>()
+        .context("Failed to query nearest chunks")?)
+}
+
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
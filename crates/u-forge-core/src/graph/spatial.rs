@@ -0,0 +1,106 @@
+//! Spatial index over coordinate properties for KnowledgeGraphStorage.
+//!
+//! Every top-level property shaped like [`Coordinates`](crate::geo::Coordinates) — an object with
+//! numeric `x` and `y` and an optional `map` id — gets a row in
+//! `node_coordinates`, refreshed whenever the node is written.  Lookups
+//! range-scan the `(map_id, x, y)` index for the bounding box of the search
+//! circle, then keep the points actually inside it.
+
+use anyhow::Context;
+use rusqlite::{params, Connection};
+
+use crate::error::Result;
+use crate::geo::Point;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+/// Index the coordinate properties of node `?1`, or of every node when `?1`
+/// is NULL.
+const INDEX_COORDINATES: &str = "
+    INSERT OR REPLACE INTO node_coordinates (node_id, property, map_id, x, y)
+    SELECT n.id, p.key, COALESCE(json_extract(p.value, '$.map'), ''),
+           json_extract(p.value, '$.x'), json_extract(p.value, '$.y')
+    FROM nodes n, json_each(n.properties) p
+    WHERE (?1 IS NULL OR n.id = ?1)
+      AND p.type = 'object'
+      AND json_type(p.value, '$.x') IN ('integer', 'real')
+      AND json_type(p.value, '$.y') IN ('integer', 'real')
+      AND COALESCE(json_type(p.value, '$.map'), 'null') IN ('text', 'null')";
+
+impl KnowledgeGraphStorage {
+    /// Coordinate properties on `map` (`None` for coordinates without a map)
+    /// within `radius` of `center`, nearest first, as
+    /// `(object_id, property, point, distance)`.
+    pub fn find_coordinates_near(
+        &self,
+        map: Option<ObjectId>,
+        center: Point,
+        radius: f64,
+    ) -> Result<Vec<(ObjectId, String, Point, f64)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT node_id, property, x, y FROM node_coordinates
+             WHERE map_id = ?1 AND x BETWEEN ?2 AND ?3 AND y BETWEEN ?4 AND ?5",
+        )?;
+        let map_id = map
+            .map(|id| id.hyphenated().to_string())
+            .unwrap_or_default();
+        let rows = stmt.query_map(
+            params![
+                map_id,
+                center.x - radius,
+                center.x + radius,
+                center.y - radius,
+                center.y + radius,
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    Point::new(row.get(2)?, row.get(3)?),
+                ))
+            },
+        )?;
+
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, property, point) = row.context("Failed to read node coordinates")?;
+            let distance = center.distance(&point);
+            if distance <= radius {
+                let id = ObjectId::parse_str(&id)
+                    .with_context(|| format!("Invalid object UUID: '{id}'"))?;
+                out.push((id, property, point, distance));
+            }
+        }
+        out.sort_by(|a, b| a.3.total_cmp(&b.3).then_with(|| a.1.cmp(&b.1)));
+        Ok(out)
+    }
+}
+
+/// Re-index the coordinate properties of node `id` after it is written.
+pub(super) fn refresh_node_coordinates(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM node_coordinates WHERE node_id = ?1",
+        params![id],
+    )
+    .context("Failed to clear node coordinates")?;
+    conn.execute(INDEX_COORDINATES, params![id])
+        .context("Failed to index node coordinates")?;
+    Ok(())
+}
+
+/// Index every node when the spatial index is empty, e.g. for databases
+/// created before it existed.
+pub(super) fn backfill_node_coordinates(conn: &Connection) -> Result<()> {
+    let indexed: i64 = conn
+        .query_row("SELECT COUNT(*) FROM node_coordinates", [], |row| {
+            row.get(0)
+        })
+        .context("Failed to count node coordinates")?;
+    if indexed == 0 {
+        conn.execute(INDEX_COORDINATES, params![Option::<String>::None])
+            .context("Failed to build spatial index")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Persistence for transcript speaker mappings.
+//!
+//! One row per speaker label in the `speaker_mappings` table.  `speaker` is
+//! the primary key with `COLLATE NOCASE`; the row is deleted with its object.
+
+use anyhow::Context;
+use rusqlite::params;
+
+use crate::error::Result;
+use crate::types::ObjectId;
+
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Map `speaker` to `object_id`, replacing any existing mapping for the
+    /// label (ignoring case).
+    pub fn upsert_speaker_mapping(&self, speaker: &str, object_id: ObjectId) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "DELETE FROM speaker_mappings WHERE speaker = ?1",
+            params![speaker],
+        )
+        .context("Failed to replace speaker mapping")?;
+        conn.execute(
+            "INSERT INTO speaker_mappings (speaker, object_id) VALUES (?1, ?2)",
+            params![speaker, object_id.hyphenated().to_string()],
+        )
+        .context("Failed to insert speaker mapping")?;
+        Ok(())
+    }
+
+    /// Every speaker mapping, ordered by label (ignoring case).
+    pub fn list_speaker_mappings(&self) -> Result<Vec<(String, ObjectId)>> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT speaker, object_id FROM speaker_mappings ORDER BY speaker")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (speaker, id) = row?;
+            let id = ObjectId::parse_str(&id)
+                .with_context(|| format!("Invalid object UUID in speaker mapping: '{id}'"))?;
+            out.push((speaker, id));
+        }
+        Ok(out)
+    }
+
+    /// Delete the mapping for `speaker` (ignoring case).  Returns `false` if
+    /// there was none.
+    pub fn delete_speaker_mapping(&self, speaker: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn
+            .execute(
+                "DELETE FROM speaker_mappings WHERE speaker = ?1",
+                params![speaker],
+            )
+            .context("Failed to delete speaker mapping")?;
+        Ok(deleted > 0)
+    }
+}
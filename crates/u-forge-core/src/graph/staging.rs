@@ -0,0 +1,73 @@
+//! Atomic application of a staging layer for KnowledgeGraphStorage.
+
+use anyhow::Context;
+use rusqlite::{params, Connection};
+
+use crate::error::Result;
+use crate::events::GraphEvent;
+use crate::types::{Edge, EdgeType, ObjectId, ObjectMetadata};
+
+use super::edges::write_edge;
+use super::nodes::write_node;
+use super::storage::KnowledgeGraphStorage;
+
+impl KnowledgeGraphStorage {
+    /// Delete `deleted_edges` and `deleted_nodes`, then upsert `nodes` and
+    /// `edges`, in a single transaction: either all of it happens or none.
+    /// New nodes are announced as [`GraphEvent::ObjectCreated`] after the
+    /// commit.
+    pub fn apply_staged(
+        &self,
+        nodes: &[ObjectMetadata],
+        edges: &[Edge],
+        deleted_nodes: &[ObjectId],
+        deleted_edges: &[(ObjectId, ObjectId, EdgeType)],
+    ) -> Result<()> {
+        self.apply_staged_with(nodes, edges, deleted_nodes, deleted_edges, |_| Ok(()))
+    }
+
+    /// [`apply_staged`](Self::apply_staged), running `also` in the same
+    /// transaction just before it commits.
+    pub(super) fn apply_staged_with(
+        &self,
+        nodes: &[ObjectMetadata],
+        edges: &[Edge],
+        deleted_nodes: &[ObjectId],
+        deleted_edges: &[(ObjectId, ObjectId, EdgeType)],
+        also: impl FnOnce(&Connection) -> Result<()>,
+    ) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        for (from, to, edge_type) in deleted_edges {
+            tx.execute(
+                "DELETE FROM edges WHERE source_id = ?1 AND target_id = ?2 AND edge_type = ?3",
+                params![
+                    from.hyphenated().to_string(),
+                    to.hyphenated().to_string(),
+                    edge_type.as_str(),
+                ],
+            )
+            .context("Failed to delete staged edge")?;
+        }
+        for id in deleted_nodes {
+            tx.execute(
+                "DELETE FROM nodes WHERE id = ?1",
+                params![id.hyphenated().to_string()],
+            )
+            .context("Failed to delete staged node")?;
+        }
+        let mut created = Vec::new();
+        for node in nodes {
+            if write_node(&tx, node)? {
+                created.push(GraphEvent::object_created(node));
+            }
+        }
+        for edge in edges {
+            write_edge(&tx, edge)?;
+        }
+        also(&tx)?;
+        tx.commit().context("Failed to commit staging layer")?;
+        created.into_iter().for_each(|event| self.emit(event));
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! Graph statistics, gathered in parallel.
+//!
+//! Dashboard numbers are whole-table aggregates; on a 100k-chunk project the
+//! chunk and vector scans take most of a second each, and run back to back
+//! behind the connection mutex they also stall every other read.  Here each
+//! aggregate runs on its own short-lived read-only connection on a scoped
+//! thread.  Under WAL each reader sees its own consistent snapshot of
+//! committed data, so the shared connection stays free, the wall time is
+//! roughly that of the slowest query, and the figures may straddle a
+//! concurrent commit (they are counts for display, not invariants).
+
+use std::collections::BTreeMap;
+use std::thread::{self, ScopedJoinHandle};
+
+use anyhow::Context;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::error::Result;
+
+use super::secondary::open_read_only;
+use super::storage::{GraphStats, KnowledgeGraphStorage};
+
+/// Count and token total for one chunk type, in [`ExtendedStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ChunkTypeStats {
+    pub chunks: usize,
+    pub tokens: usize,
+}
+
+/// Breakdown of the graph for the stats dashboard.
+#[derive(Debug, Clone)]
+pub struct ExtendedStats {
+    pub totals: GraphStats,
+    pub nodes_by_type: BTreeMap<String, usize>,
+    /// Keyed by stored lifecycle (`canon`, `draft`, …).
+    pub nodes_by_lifecycle: BTreeMap<String, usize>,
+    pub edges_by_type: BTreeMap<String, usize>,
+    pub chunks_by_type: BTreeMap<String, ChunkTypeStats>,
+    /// Nodes with no edges in either direction.
+    pub isolated_nodes: usize,
+}
+
+/// Wait for a stats worker, re-raising its panic on this thread.
+pub(super) fn joined<T>(handle: ScopedJoinHandle<'_, Result<T>>) -> Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+fn grouped(conn: &Connection, sql: &str) -> Result<Vec<(String, usize, usize)>> {
+    let mut stmt = conn
+        .prepare(sql)
+        .with_context(|| format!("Failed to prepare stats query: {sql}"))?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, i64>(1)? as usize,
+                r.get::<_, i64>(2)? as usize,
+            ))
+        })
+        .with_context(|| format!("Failed to run stats query: {sql}"))?;
+    Ok(rows
+        .collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("Failed to read stats query: {sql}"))?)
+}
+
+impl KnowledgeGraphStorage {
+    /// Run `f` on a fresh read-only connection, or on the shared connection
+    /// when one cannot be opened.
+    pub(super) fn on_reader<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        match open_read_only(&self.db_file) {
+            Ok(conn) => f(&conn),
+            Err(_) => f(&self.conn.lock()),
+        }
+    }
+
+    /// A single-integer aggregate, on its own reader.
+    pub(super) fn read_count(&self, sql: &str) -> Result<usize> {
+        self.on_reader(|conn| {
+            Ok(conn
+                .query_row(sql, [], |r| r.get::<_, i64>(0))
+                .map(|n| n as usize)
+                .with_context(|| format!("Failed to run stats query: {sql}"))?)
+        })
+    }
+
+    /// Return aggregate graph statistics, running the six aggregates in
+    /// parallel.
+    pub fn get_stats(&self) -> Result<GraphStats> {
+        thread::scope(|s| {
+            let nodes = s.spawn(|| self.read_count("SELECT COUNT(*) FROM nodes"));
+            let edges = s.spawn(|| self.read_count("SELECT COUNT(*) FROM edges"));
+            let chunks = s.spawn(|| self.read_count("SELECT COUNT(*) FROM chunks"));
+            let tokens =
+                s.spawn(|| self.read_count("SELECT COALESCE(SUM(token_count), 0) FROM chunks"));
+            let embedded = s.spawn(|| self.read_count("SELECT COUNT(*) FROM chunks_vec"));
+            let embedded_hq = s.spawn(|| self.read_count("SELECT COUNT(*) FROM chunks_vec_hq"));
+            Ok(GraphStats {
+                node_count: joined(nodes)?,
+                edge_count: joined(edges)?,
+                chunk_count: joined(chunks)?,
+                total_tokens: joined(tokens)?,
+                embedded_count: joined(embedded)?,
+                embedded_hq_count: joined(embedded_hq)?,
+            })
+        })
+    }
+
+    /// [`get_stats`](Self::get_stats) plus per-type breakdowns, all gathered
+    /// in parallel.
+    pub fn get_extended_stats(&self) -> Result<ExtendedStats> {
+        let group = |sql: &'static str| move || self.on_reader(|conn| grouped(conn, sql));
+        thread::scope(|s| {
+            let totals = s.spawn(|| self.get_stats());
+            let by_type = s.spawn(group(
+                "SELECT object_type, COUNT(*), 0 FROM nodes GROUP BY object_type",
+            ));
+            let by_lifecycle = s.spawn(group(
+                "SELECT lifecycle, COUNT(*), 0 FROM nodes GROUP BY lifecycle",
+            ));
+            let edges = s.spawn(group(
+                "SELECT edge_type, COUNT(*), 0 FROM edges GROUP BY edge_type",
+            ));
+            let chunks = s.spawn(group(
+                "SELECT chunk_type, COUNT(*), COALESCE(SUM(token_count), 0)
+                 FROM chunks GROUP BY chunk_type",
+            ));
+            let isolated = s.spawn(|| {
+                self.read_count(
+                    "SELECT COUNT(*) FROM nodes n
+                     WHERE NOT EXISTS (SELECT 1 FROM edges WHERE source_id = n.id)
+                       AND NOT EXISTS (SELECT 1 FROM edges WHERE target_id = n.id)",
+                )
+            });
+            let counts = |rows: Vec<(String, usize, usize)>| {
+                rows.into_iter().map(|(key, n, _)| (key, n)).collect()
+            };
+            Ok(ExtendedStats {
+                totals: joined(totals)?,
+                nodes_by_type: counts(joined(by_type)?),
+                nodes_by_lifecycle: counts(joined(by_lifecycle)?),
+                edges_by_type: counts(joined(edges)?),
+                chunks_by_type: joined(chunks)?
+                    .into_iter()
+                    .map(|(key, chunks, tokens)| (key, ChunkTypeStats { chunks, tokens }))
+                    .collect(),
+                isolated_nodes: joined(isolated)?,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChunkType, Edge, EdgeType, ObjectMetadata, TextChunk};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_get_extended_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = KnowledgeGraphStorage::new(temp_dir.path()).unwrap();
+        let npc = ObjectMetadata::new("npc".to_string(), "Sildar".to_string());
+        let town = ObjectMetadata::new("location".to_string(), "Phandalin".to_string());
+        let hermit = ObjectMetadata::new("npc".to_string(), "Reidoth".to_string());
+        let (npc_id, town_id) = (npc.id, town.id);
+        for node in [npc, town, hermit] {
+            storage.upsert_node(node).unwrap();
+        }
+        storage
+            .upsert_edge(Edge::new(npc_id, town_id, EdgeType::new("located_in")))
+            .unwrap();
+        let mut chunk = TextChunk::new(npc_id, "A knight".to_string(), ChunkType::Description);
+        chunk.token_count = 3;
+        storage.upsert_chunk(chunk).unwrap();
+
+        let stats = storage.get_extended_stats().unwrap();
+        assert_eq!(stats.totals.node_count, 3);
+        assert_eq!(stats.totals.edge_count, 1);
+        assert_eq!(stats.nodes_by_type["npc"], 2);
+        assert_eq!(stats.nodes_by_lifecycle["canon"], 3);
+        assert_eq!(stats.edges_by_type["located_in"], 1);
+        assert_eq!(
+            stats.chunks_by_type["description"],
+            ChunkTypeStats {
+                chunks: 1,
+                tokens: 3
+            }
+        );
+        assert_eq!(stats.isolated_nodes, 1);
+    }
+}
@@ -6,6 +6,8 @@
 //! * FTS5 content-table on `chunks` for full-text search.
 //! * Three DML triggers to keep the FTS5 index in sync with the `chunks` table.
 //! * `vec0` virtual table (`chunks_vec`) via sqlite-vec for ANN similarity search.
+//! * A memory-mapped read path (`mmap_size`) and an in-process LRU of decoded
+//!   nodes and chunks (see `cache.rs`).
 //!
 //! # Thread safety
 //! `Connection` is wrapped in `Arc<parking_lot::Mutex<Connection>>` so
@@ -13,14 +15,19 @@
 //! in the facade layer.  `parking_lot::Mutex` has no poisoning semantics, so
 //! lock guards are obtained without `.unwrap()`.
 
-use crate::error::EmbeddingDimensionMismatch;
+use super::cache::{ReadCache, DEFAULT_READ_CACHE_BYTES};
+use super::spatial::backfill_node_coordinates;
+use crate::error::{EmbeddingDimensionMismatch, Result};
+use crate::events::{GraphEvent, GRAPH_EVENT_CAPACITY};
+use crate::progress::{NoProgress, Progress, ProgressSink};
 use crate::schema::SchemaDefinition;
-use crate::types::{ChunkType, ObjectId, ObjectMetadata};
-use anyhow::{Context, Result};
+use crate::types::{ChunkType, EdgeId, Lifecycle, ObjectId, ObjectMetadata};
+use anyhow::Context;
 use rusqlite::{params, Connection, OptionalExtension};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use parking_lot::Mutex;
 use std::sync::{Arc, Once};
+use tokio::sync::broadcast;
 use tracing::warn;
 
 
@@ -29,6 +36,7 @@ use tracing::warn;
 pub(super) const SQL_SCHEMA: &str = r#"
 PRAGMA journal_mode=WAL;
 PRAGMA foreign_keys=ON;
+PRAGMA mmap_size=268435456;
 
 CREATE TABLE IF NOT EXISTS nodes (
     id          TEXT PRIMARY KEY,
@@ -37,7 +45,8 @@ CREATE TABLE IF NOT EXISTS nodes (
     name        TEXT NOT NULL,
     properties  TEXT NOT NULL DEFAULT '{}',
     created_at  TEXT NOT NULL,
-    updated_at  TEXT NOT NULL
+    updated_at  TEXT NOT NULL,
+    lifecycle   TEXT NOT NULL DEFAULT 'canon'
 );
 
 CREATE TABLE IF NOT EXISTS edges (
@@ -47,6 +56,7 @@ CREATE TABLE IF NOT EXISTS edges (
     weight     REAL NOT NULL DEFAULT 1.0,
     metadata   TEXT NOT NULL DEFAULT '{}',
     created_at TEXT NOT NULL,
+    id         TEXT,
     UNIQUE(source_id, target_id, edge_type)
 );
 
@@ -70,6 +80,20 @@ CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
     content_rowid='rowid'
 );
 
+-- Trigram twin of chunks_fts for scripts written without spaces (Chinese,
+-- Japanese, Thai, …), where unicode61 indexes a whole sentence as one word.
+-- Queries containing such characters are routed here; see fts.rs.
+CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts_trigram USING fts5(
+    content,
+    content='chunks',
+    content_rowid='rowid',
+    tokenize='trigram'
+);
+
+-- Read-only view of the terms in chunks_fts; lets query spell-correction ask
+-- "does this word occur anywhere?" without scanning chunk text.
+CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts_vocab USING fts5vocab(chunks_fts, 'row');
+
 CREATE INDEX IF NOT EXISTS idx_nodes_type      ON nodes(object_type);
 CREATE INDEX IF NOT EXISTS idx_nodes_name      ON nodes(object_type, name);
 CREATE INDEX IF NOT EXISTS idx_nodes_name_only ON nodes(name);
@@ -87,6 +111,18 @@ CREATE TRIGGER IF NOT EXISTS chunks_au AFTER UPDATE ON chunks BEGIN
     INSERT INTO chunks_fts(chunks_fts, rowid, content) VALUES('delete', old.rowid, old.content);
     INSERT INTO chunks_fts(rowid, content) VALUES (new.rowid, new.content);
 END;
+CREATE TRIGGER IF NOT EXISTS chunks_trigram_ai AFTER INSERT ON chunks BEGIN
+    INSERT INTO chunks_fts_trigram(rowid, content) VALUES (new.rowid, new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_trigram_ad AFTER DELETE ON chunks BEGIN
+    INSERT INTO chunks_fts_trigram(chunks_fts_trigram, rowid, content)
+        VALUES('delete', old.rowid, old.content);
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_trigram_au AFTER UPDATE ON chunks BEGIN
+    INSERT INTO chunks_fts_trigram(chunks_fts_trigram, rowid, content)
+        VALUES('delete', old.rowid, old.content);
+    INSERT INTO chunks_fts_trigram(rowid, content) VALUES (new.rowid, new.content);
+END;
 
 -- ── ANN vector search (sqlite-vec) ────────────────────────────────────────────
 -- Each row maps a chunk rowid → its 256-dim embedding (cosine distance).
@@ -122,6 +158,334 @@ CREATE TRIGGER IF NOT EXISTS chunks_vec_hq_ad AFTER DELETE ON chunks BEGIN
     DELETE FROM chunks_vec_hq WHERE rowid = old.rowid;
 END;
 
+-- ── Object profile embeddings ─────────────────────────────────────────────────
+-- One short "profile text" per node (name, type, tags, scalar properties) so
+-- objects with structured data but no prose are still reachable by semantic
+-- search.  Kept in sync by upsert_node() / set_node_property(); a change in
+-- content_hash drops the stale vector so the next embedding pass refreshes it.
+CREATE TABLE IF NOT EXISTS node_profiles (
+    object_id    TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    content      TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    updated_at   TEXT NOT NULL
+);
+
+-- Keyed by node_profiles rowid, same 768-dim space as chunks_vec.
+CREATE VIRTUAL TABLE IF NOT EXISTS node_profiles_vec USING vec0(
+    embedding float[768] distance_metric=cosine
+);
+
+CREATE TRIGGER IF NOT EXISTS node_profiles_vec_ad AFTER DELETE ON node_profiles BEGIN
+    DELETE FROM node_profiles_vec WHERE rowid = old.rowid;
+END;
+
+-- ── Spatial index ─────────────────────────────────────────────────────────────
+-- One row per coordinate-shaped property (`{"map": …, "x": …, "y": …}`) so
+-- find_objects_near() can range-scan instead of reading every node.  `map_id`
+-- is '' for coordinates not tied to a map.  Kept in sync by upsert_node() /
+-- set_node_property(); cascades with the node.
+CREATE TABLE IF NOT EXISTS node_coordinates (
+    node_id  TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    property TEXT NOT NULL,
+    map_id   TEXT NOT NULL,
+    x        REAL NOT NULL,
+    y        REAL NOT NULL,
+    PRIMARY KEY (node_id, property)
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_coordinates_xy ON node_coordinates(map_id, x, y);
+
+-- ── Chunk edit history ────────────────────────────────────────────────────────
+-- Every content edit made through update_chunk_content() appends a row here.
+-- The first edit of a chunk also records its original content, so the latest
+-- row always matches chunks.content.  Cascades with the chunk.
+CREATE TABLE IF NOT EXISTS chunk_revisions (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    chunk_id     TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    content      TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    author       TEXT,
+    revised_at   TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_chunk_revisions_chunk ON chunk_revisions(chunk_id, id);
+
+-- ── Node and edge history ──────────────────────────────────────────────────────
+-- Append-only snapshots written by triggers (see HISTORY_TRIGGERS) on every
+-- insert, update, and delete of nodes and edges — including cascaded edge
+-- deletes.  `deleted = 1` rows mark removal.  No foreign keys: history must
+-- outlive the rows it describes.  Read by get_node_as_of() / get_edges_as_of().
+CREATE TABLE IF NOT EXISTS node_history (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    node_id     TEXT NOT NULL,
+    object_type TEXT NOT NULL,
+    schema_name TEXT,
+    name        TEXT NOT NULL,
+    properties  TEXT NOT NULL,
+    created_at  TEXT NOT NULL,
+    updated_at  TEXT NOT NULL,
+    lifecycle   TEXT NOT NULL,
+    deleted     INTEGER NOT NULL DEFAULT 0,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_history_node ON node_history(node_id, id);
+
+CREATE TABLE IF NOT EXISTS edge_history (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    edge_id     TEXT,
+    source_id   TEXT NOT NULL,
+    target_id   TEXT NOT NULL,
+    edge_type   TEXT NOT NULL,
+    weight      REAL NOT NULL,
+    metadata    TEXT NOT NULL,
+    created_at  TEXT NOT NULL,
+    deleted     INTEGER NOT NULL DEFAULT 0,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_edge_history_source ON edge_history(source_id);
+CREATE INDEX IF NOT EXISTS idx_edge_history_target ON edge_history(target_id);
+
+-- ── AI proposal review queue ───────────────────────────────────────────────────
+-- LLM-generated objects, edges, and property changes wait here until a user
+-- accepts or rejects them.  Nothing in this table is visible to graph queries;
+-- accepting a proposal applies its change to nodes/edges and marks the row.
+CREATE TABLE IF NOT EXISTS proposals (
+    id          TEXT PRIMARY KEY,
+    kind        TEXT NOT NULL,
+    change      TEXT NOT NULL,
+    status      TEXT NOT NULL DEFAULT 'pending',
+    source      TEXT,
+    created_at  TEXT NOT NULL,
+    resolved_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposals_status ON proposals(status, created_at);
+
+-- ── Consistency warnings ───────────────────────────────────────────────────────
+-- Pairs of chunks on the same object that the LLM flagged as contradicting each
+-- other.  Cascades from both chunks so edits that remove a statement also
+-- remove the warning.
+CREATE TABLE IF NOT EXISTS consistency_warnings (
+    id           TEXT PRIMARY KEY,
+    object_id    TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    first_chunk  TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    second_chunk TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    explanation  TEXT NOT NULL,
+    dismissed    INTEGER NOT NULL DEFAULT 0,
+    created_at   TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_consistency_object ON consistency_warnings(object_id);
+
+-- ── Glossary ──────────────────────────────────────────────────────────────────
+-- In-world synonyms and abbreviations ("the Company" → "Fellowship of the
+-- Ring").  Terms are unique case-insensitively; a linked object is unlinked,
+-- not deleted with it.
+CREATE TABLE IF NOT EXISTS glossary (
+    term       TEXT PRIMARY KEY COLLATE NOCASE,
+    expansion  TEXT NOT NULL,
+    object_id  TEXT REFERENCES nodes(id) ON DELETE SET NULL,
+    note       TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- ── Speaker mappings ──────────────────────────────────────────────────────────
+-- Transcript speaker labels ("SPEAKER_01", "Dave") and the object each one
+-- voices.  Labels are unique case-insensitively; a mapping goes with its
+-- object.
+CREATE TABLE IF NOT EXISTS speaker_mappings (
+    speaker   TEXT PRIMARY KEY COLLATE NOCASE,
+    object_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE
+);
+
+-- ── Branches ──────────────────────────────────────────────────────────────────
+-- Alternate timelines of the project (see src/branches.rs).  Each branch is a
+-- copy-on-write overlay over the main graph: `layer` is a JSON StagingLayer
+-- holding only what the branch changed, `bases` the main-graph `updated_at`
+-- of each object when the branch first diverged on it.
+CREATE TABLE IF NOT EXISTS branches (
+    name       TEXT PRIMARY KEY COLLATE NOCASE,
+    created_at TEXT NOT NULL,
+    layer      TEXT NOT NULL,
+    bases      TEXT NOT NULL
+);
+
+-- ── Search telemetry ──────────────────────────────────────────────────────────
+-- Opt-in, local-only log of searches (see src/search/telemetry.rs): the query,
+-- its normalised form for grouping, and how many results it returned.  Clicks
+-- on a result cascade with their search.  Object ids are not foreign keys, so
+-- deleting an object keeps the history of what was searched for.  Not touched
+-- by clear_all(): the log describes use of the project, not its contents.
+CREATE TABLE IF NOT EXISTS search_log (
+    id           TEXT PRIMARY KEY,
+    query        TEXT NOT NULL,
+    normalized   TEXT NOT NULL,
+    result_count INTEGER NOT NULL,
+    searched_at  TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_log_normalized ON search_log(normalized, result_count);
+CREATE INDEX IF NOT EXISTS idx_search_log_searched_at ON search_log(searched_at);
+
+CREATE TABLE IF NOT EXISTS search_clicks (
+    search_id  TEXT NOT NULL REFERENCES search_log(id) ON DELETE CASCADE,
+    object_id  TEXT NOT NULL,
+    rank       INTEGER NOT NULL,
+    clicked_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_clicks_search ON search_clicks(search_id);
+
+-- ── Intent log ──────────────────────────────────────────────────────────────
+-- Cross-index work (re-chunking, re-embedding) owed to an object after a
+-- write, recorded so it can be rolled forward if the process dies before the
+-- work finishes.  Rows are appended per request and deleted up to the seq a
+-- completed run started from, so a request made mid-run survives.
+CREATE TABLE IF NOT EXISTS intents (
+    seq         INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind        TEXT NOT NULL,
+    object_id   TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_intents_object ON intents(kind, object_id);
+
+-- ── Embedding queue ─────────────────────────────────────────────────────────
+-- Chunks a sweep has set out to embed, per target index, with the hash of
+-- the content that was queued.  Rows are deleted as each vector is stored,
+-- so what is left after a crash is the unfinished work to resume first.
+CREATE TABLE IF NOT EXISTS embed_queue (
+    chunk_id     TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    target       TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    queued_at    TEXT NOT NULL,
+    PRIMARY KEY (chunk_id, target)
+);
+
+-- ── Chunk embedding provenance ──────────────────────────────────────────────
+-- Which model produced a chunk's vector in each index, when, and from what
+-- content (see src/embedding_status.rs).  Replaced whenever a vector is
+-- written, so a vector stored without provenance has no row.
+CREATE TABLE IF NOT EXISTS chunk_embeddings (
+    chunk_id     TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    target       TEXT NOT NULL,
+    model        TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    embedded_at  TEXT NOT NULL,
+    PRIMARY KEY (chunk_id, target)
+);
+
+-- ── Background jobs ─────────────────────────────────────────────────────────
+-- Jobs submitted to the JobManager (see src/jobs.rs), one JSON record per
+-- job, so queued work and recent history survive a restart.
+CREATE TABLE IF NOT EXISTS jobs (
+    id         TEXT PRIMARY KEY,
+    state      TEXT NOT NULL,
+    record     TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- ── Ledger ──────────────────────────────────────────────────────────────────────
+-- Credits and debits of treasuries (see src/economy.rs), in units of the
+-- smallest denomination.  `balance` is the treasury's balance after the
+-- entry; both sides of a transfer share a `transfer_id`.  Entries go with
+-- their treasury.
+CREATE TABLE IF NOT EXISTS ledger (
+    id          TEXT PRIMARY KEY,
+    treasury_id TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    amount      INTEGER NOT NULL,
+    balance     INTEGER NOT NULL,
+    memo        TEXT NOT NULL,
+    transfer_id TEXT,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_treasury ON ledger(treasury_id, recorded_at);
+
+-- ── Reveals ─────────────────────────────────────────────────────────────────────
+-- What each player has been shown (see src/reveals.rs): a whole object, or one
+-- chunk of it, and the session it happened in.  Reveals go with the player or
+-- object; a deleted session only loses the link.  `chunk_id` is not a foreign
+-- key, so re-chunking an object keeps the record that it was revealed.
+CREATE TABLE IF NOT EXISTS reveals (
+    id          TEXT PRIMARY KEY,
+    player_id   TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    object_id   TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    chunk_id    TEXT,
+    session_id  TEXT REFERENCES nodes(id) ON DELETE SET NULL,
+    revealed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reveals_player ON reveals(player_id, revealed_at);
+CREATE INDEX IF NOT EXISTS idx_reveals_object ON reveals(object_id);
+
+-- ── Cross-project references ──────────────────────────────────────────────────
+-- Links from an object here to an object in another project (see
+-- src/crosslinks.rs), by that project's `project_id` setting and the object's
+-- id.  The target is resolved only when read, through the ProjectManager;
+-- `label` is the target's name when linked, shown while that project is not
+-- open.  References go with their source object.
+CREATE TABLE IF NOT EXISTS external_refs (
+    id          TEXT PRIMARY KEY,
+    source_id   TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    edge_type   TEXT NOT NULL,
+    project_id  TEXT NOT NULL,
+    object_id   TEXT NOT NULL,
+    label       TEXT NOT NULL,
+    created_at  TEXT NOT NULL,
+    UNIQUE(source_id, edge_type, project_id, object_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_external_refs_target ON external_refs(project_id, object_id);
+
+-- ── Archive ─────────────────────────────────────────────────────────────────────
+-- Objects set aside from default search and graph views (see src/archive.rs).
+-- The object itself is untouched; unarchiving deletes the row.
+CREATE TABLE IF NOT EXISTS archived_objects (
+    object_id   TEXT PRIMARY KEY REFERENCES nodes(id) ON DELETE CASCADE,
+    archived_at TEXT NOT NULL
+);
+
+-- ── Inline entity links ──────────────────────────────────────────────────────
+-- `[[Name]]` / `@Name` spans parsed from chunk text (see entity_links.rs), by
+-- byte offset.  `object_id` is NULL while the name resolves to nothing, and
+-- keeps pointing at the object when it is renamed.
+CREATE TABLE IF NOT EXISTS chunk_links (
+    chunk_id   TEXT NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    start_byte INTEGER NOT NULL,
+    end_byte   INTEGER NOT NULL,
+    target     TEXT NOT NULL,
+    object_id  TEXT REFERENCES nodes(id) ON DELETE SET NULL,
+    PRIMARY KEY (chunk_id, start_byte)
+);
+CREATE INDEX IF NOT EXISTS idx_chunk_links_object ON chunk_links(object_id);
+
+-- ── Flashcard reviews ───────────────────────────────────────────────────────
+-- SM-2 review state of generated flashcards (see src/flashcards.rs), keyed by
+-- the card's stable id.  Cards are regenerated from the graph on demand; only
+-- the schedule is stored, and it goes with the card's subject object.
+CREATE TABLE IF NOT EXISTS flashcard_reviews (
+    card_id          TEXT PRIMARY KEY,
+    object_id        TEXT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    easiness         REAL NOT NULL,
+    interval_days    INTEGER NOT NULL,
+    repetitions      INTEGER NOT NULL,
+    lapses           INTEGER NOT NULL,
+    due_at           TEXT NOT NULL,
+    last_reviewed_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_flashcard_reviews_due ON flashcard_reviews(due_at);
+
+-- ── Project settings ──────────────────────────────────────────────────────────
+-- Free-form per-project key/value settings (e.g. `default_language`).  Not
+-- touched by clear_all(): settings describe the project, not its contents.
+CREATE TABLE IF NOT EXISTS project_settings (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
 -- ── Embedding schema metadata ─────────────────────────────────────────────────
 -- Records the dimensionality baked into each vec0 virtual table at creation
 -- time.  On open, KnowledgeGraphStorage compares these stored values against
@@ -131,6 +495,35 @@ CREATE TABLE IF NOT EXISTS schema_metadata (
     key   TEXT PRIMARY KEY,
     value TEXT NOT NULL
 );
+
+-- ── Read-cache epoch ──────────────────────────────────────────────────────────
+-- One counter bumped by every write to `nodes` or `chunks`, from any
+-- connection.  The in-memory read cache (graph/cache.rs) empties itself when
+-- it sees a new value.
+CREATE TABLE IF NOT EXISTS storage_epoch (
+    id    INTEGER PRIMARY KEY CHECK (id = 0),
+    value INTEGER NOT NULL
+);
+INSERT OR IGNORE INTO storage_epoch (id, value) VALUES (0, 0);
+
+CREATE TRIGGER IF NOT EXISTS nodes_epoch_ai AFTER INSERT ON nodes BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
+CREATE TRIGGER IF NOT EXISTS nodes_epoch_au AFTER UPDATE ON nodes BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
+CREATE TRIGGER IF NOT EXISTS nodes_epoch_ad AFTER DELETE ON nodes BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_epoch_ai AFTER INSERT ON chunks BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_epoch_au AFTER UPDATE ON chunks BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
+CREATE TRIGGER IF NOT EXISTS chunks_epoch_ad AFTER DELETE ON chunks BEGIN
+    UPDATE storage_epoch SET value = value + 1 WHERE id = 0;
+END;
 "#;
 
 // ─── Constants & process-level init ───────────────────────────────────────────
@@ -144,25 +537,7 @@ CREATE TABLE IF NOT EXISTS schema_metadata (
 /// database — `vec0` virtual tables cannot be `ALTER`ed after creation.
 pub const EMBEDDING_DIMENSIONS: usize = 768;
 
-/// Default context window size (in tokens) assumed for the active embedding model.
-///
-/// Used to derive [`MAX_CHUNK_TOKENS`].  The standard embedding model
-/// (`embed-gemma-300m-FLM` / `embeddinggemma-300M-GGUF`) supports a 2048-token
-/// context window.
-pub const DEFAULT_EMBEDDING_CONTEXT_TOKENS: usize = 2048;
-
-/// Maximum number of tokens per stored text chunk.
-///
-/// Chunks larger than this are split at word boundaries by
-/// [`KnowledgeGraph::add_text_chunk`] before being written to storage.
-///
-/// Set to half of [`DEFAULT_EMBEDDING_CONTEXT_TOKENS`] to give a safe margin
-/// against the `len/4` character heuristic — which tends to *under*count real
-/// tokens for dense prose by 30–50%.  At 2048 heuristic tokens, a 50%
-/// undercount would yield ~3072 real tokens, still well within the 4096 context
-/// window.  Nodes are now embedded as a single flattened document, so this
-/// budget needs to accommodate an entire node's metadata in one chunk.
-pub const MAX_CHUNK_TOKENS: usize = DEFAULT_EMBEDDING_CONTEXT_TOKENS / 2;
+pub use crate::text::{DEFAULT_EMBEDDING_CONTEXT_TOKENS, MAX_CHUNK_TOKENS};
 
 /// Number of dimensions produced by high-quality embedding models
 /// (e.g. `Qwen3-Embedding-8B-GGUF`).
@@ -188,6 +563,11 @@ pub(super) static SQLITE_VEC_INIT: Once = Once::new();
 /// the struct is cheaply cloneable and safe to share across threads.
 pub struct KnowledgeGraphStorage {
     pub(super) conn: Arc<Mutex<Connection>>,
+    /// `<db_path>/knowledge.db`, for opening extra read-only connections.
+    pub(super) db_file: PathBuf,
+    pub(super) cache: ReadCache,
+    /// Change notifications; see [`crate::events`].
+    pub(super) events: broadcast::Sender<GraphEvent>,
 }
 
 /// Aggregate statistics about the knowledge graph.
@@ -237,9 +617,21 @@ pub(super) fn str_to_chunk_type(s: &str) -> ChunkType {
     }
 }
 
-/// Build an `ObjectMetadata` from the seven column values returned by every
+/// Deserialise a `Lifecycle` from its stored snake_case string.
+///
+/// Unknown values fall back to `Lifecycle::Canon` with a warning, matching
+/// [`str_to_chunk_type`].
+pub(super) fn str_to_lifecycle(s: &str) -> Lifecycle {
+    Lifecycle::parse(s).unwrap_or_else(|| {
+        warn!(value = s, "Unknown lifecycle in database; defaulting to Canon");
+        Lifecycle::Canon
+    })
+}
+
+/// Build an `ObjectMetadata` from the eight column values returned by every
 /// `SELECT … FROM nodes` query.  Centralising this avoids repeating
 /// fallible parsing logic across multiple methods.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn row_to_metadata(
     id_str: String,
     object_type: String,
@@ -248,6 +640,7 @@ pub(super) fn row_to_metadata(
     props_str: String,
     created_at_str: String,
     updated_at_str: String,
+    lifecycle_str: String,
 ) -> Result<ObjectMetadata> {
     Ok(ObjectMetadata {
         id: ObjectId::parse_str(&id_str)
@@ -263,18 +656,129 @@ pub(super) fn row_to_metadata(
         updated_at: chrono::DateTime::parse_from_rfc3339(&updated_at_str)
             .with_context(|| format!("Invalid updated_at timestamp: '{updated_at_str}'"))?
             .with_timezone(&chrono::Utc),
+        lifecycle: Some(str_to_lifecycle(&lifecycle_str)),
     })
 }
 
+/// History triggers and the one-time backfill of rows that predate them.
+///
+/// Applied after `ensure_column` because the node triggers read `lifecycle`
+/// and the edge triggers write `edge_history.edge_id`, which older databases
+/// only gain there.  The backfill records every node
+/// and edge that has no history yet as of its last known timestamp, so
+/// as-of queries on upgraded databases see the pre-upgrade state.
+const HISTORY_TRIGGERS: &str = "
+CREATE TRIGGER IF NOT EXISTS nodes_history_ai AFTER INSERT ON nodes BEGIN
+    INSERT INTO node_history (node_id, object_type, schema_name, name, properties,
+                              created_at, updated_at, lifecycle, deleted, recorded_at)
+    VALUES (new.id, new.object_type, new.schema_name, new.name, new.properties,
+            new.created_at, new.updated_at, new.lifecycle, 0,
+            strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS nodes_history_au AFTER UPDATE ON nodes BEGIN
+    INSERT INTO node_history (node_id, object_type, schema_name, name, properties,
+                              created_at, updated_at, lifecycle, deleted, recorded_at)
+    VALUES (new.id, new.object_type, new.schema_name, new.name, new.properties,
+            new.created_at, new.updated_at, new.lifecycle, 0,
+            strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS nodes_history_ad AFTER DELETE ON nodes BEGIN
+    INSERT INTO node_history (node_id, object_type, schema_name, name, properties,
+                              created_at, updated_at, lifecycle, deleted, recorded_at)
+    VALUES (old.id, old.object_type, old.schema_name, old.name, old.properties,
+            old.created_at, old.updated_at, old.lifecycle, 1,
+            strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+-- Edge triggers are recreated on every open: older databases have versions
+-- that did not record the edge id.
+DROP TRIGGER IF EXISTS edges_history_ai;
+DROP TRIGGER IF EXISTS edges_history_au;
+DROP TRIGGER IF EXISTS edges_history_ad;
+
+CREATE TRIGGER edges_history_ai AFTER INSERT ON edges BEGIN
+    INSERT INTO edge_history (edge_id, source_id, target_id, edge_type, weight, metadata,
+                              created_at, deleted, recorded_at)
+    VALUES (new.id, new.source_id, new.target_id, new.edge_type, new.weight, new.metadata,
+            new.created_at, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER edges_history_au AFTER UPDATE ON edges BEGIN
+    INSERT INTO edge_history (edge_id, source_id, target_id, edge_type, weight, metadata,
+                              created_at, deleted, recorded_at)
+    VALUES (new.id, new.source_id, new.target_id, new.edge_type, new.weight, new.metadata,
+            new.created_at, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER edges_history_ad AFTER DELETE ON edges BEGIN
+    INSERT INTO edge_history (edge_id, source_id, target_id, edge_type, weight, metadata,
+                              created_at, deleted, recorded_at)
+    VALUES (old.id, old.source_id, old.target_id, old.edge_type, old.weight, old.metadata,
+            old.created_at, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+INSERT INTO node_history (node_id, object_type, schema_name, name, properties,
+                          created_at, updated_at, lifecycle, deleted, recorded_at)
+SELECT id, object_type, schema_name, name, properties,
+       created_at, updated_at, lifecycle, 0, updated_at
+FROM nodes
+WHERE id NOT IN (SELECT node_id FROM node_history);
+
+INSERT INTO edge_history (edge_id, source_id, target_id, edge_type, weight, metadata,
+                          created_at, deleted, recorded_at)
+SELECT e.id, e.source_id, e.target_id, e.edge_type, e.weight, e.metadata,
+       e.created_at, 0, e.created_at
+FROM edges e
+WHERE NOT EXISTS (
+    SELECT 1 FROM edge_history h
+    WHERE h.source_id = e.source_id
+      AND h.target_id = e.target_id
+      AND h.edge_type = e.edge_type
+);
+
+-- History recorded before rows carried the edge id: attribute it to the
+-- current edge with the same endpoints and type, if there is one.
+UPDATE edge_history
+SET edge_id = (
+    SELECT e.id FROM edges e
+    WHERE e.source_id = edge_history.source_id
+      AND e.target_id = edge_history.target_id
+      AND e.edge_type = edge_history.edge_type
+)
+WHERE edge_id IS NULL;
+";
+
 // ─── Internal helpers ─────────────────────────────────────────────────────────
 
+/// Register sqlite-vec as a process-wide SQLite auto-extension so that every
+/// subsequent `Connection::open` automatically gets the vec0 virtual-table
+/// module.  The Once guard ensures the unsafe FFI call fires exactly once per
+/// process — safe even in multi-threaded tests that each create their own
+/// KnowledgeGraphStorage on a TempDir.
+pub(super) fn register_sqlite_vec() {
+    SQLITE_VEC_INIT.call_once(|| unsafe {
+        use rusqlite::ffi::sqlite3_auto_extension;
+        use sqlite_vec::sqlite3_vec_init;
+        sqlite3_auto_extension(Some(std::mem::transmute::<
+            *const (),
+            unsafe extern "C" fn(
+                *mut rusqlite::ffi::sqlite3,
+                *mut *mut i8,
+                *const rusqlite::ffi::sqlite3_api_routines,
+            ) -> i32,
+        >(sqlite3_vec_init as *const ())));
+    });
+}
+
 /// Verify — or initialise — the embedding dimension records in `schema_metadata`.
 ///
 /// For each `(table_name, expected_dims)` pair:
 /// * If no record exists → insert `expected_dims` (first open after schema creation).
 /// * If a record exists and matches → OK.
 /// * If a record exists but differs → return [`EmbeddingDimensionMismatch`].
-fn check_or_init_embedding_dims(
+pub(super) fn check_or_init_embedding_dims(
     conn: &Connection,
     checks: &[(&str, usize)],
 ) -> Result<()> {
@@ -315,6 +819,68 @@ fn check_or_init_embedding_dims(
     Ok(())
 }
 
+/// Add `column` to `table` if an older database was created without it.
+///
+/// `CREATE TABLE IF NOT EXISTS` never alters an existing table, so columns
+/// introduced after the initial schema are added here on open.
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({table})"))
+        .with_context(|| format!("Failed to inspect table {table}"))?;
+    let exists = stmt
+        .query_map([], |r| r.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|c| c == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl};"))
+            .with_context(|| format!("Failed to add column {table}.{column}"))?;
+    }
+    Ok(())
+}
+
+/// Give every edge stored before edges had ids one of its own.
+fn assign_edge_ids(conn: &Connection) -> Result<()> {
+    let rowids = conn
+        .prepare("SELECT rowid FROM edges WHERE id IS NULL")?
+        .query_map([], |r| r.get::<_, i64>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Failed to find edges without ids")?;
+    for rowid in rowids {
+        conn.execute(
+            "UPDATE edges SET id = ?1 WHERE rowid = ?2",
+            params![EdgeId::new_v4().hyphenated().to_string(), rowid],
+        )
+        .context("Failed to assign edge id")?;
+    }
+    Ok(())
+}
+
+/// Fill `chunks_fts_trigram` for chunks stored before it existed.
+///
+/// The triggers only index chunks written after the table was created, so
+/// a database from an older version has an empty trigram index.  Every
+/// indexed chunk has a `_docsize` row; when the counts differ the index is
+/// rebuilt from `chunks`.
+fn rebuild_trigram_index(conn: &Connection) -> Result<()> {
+    let (indexed, chunks): (i64, i64) = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM chunks_fts_trigram_docsize),
+                    (SELECT COUNT(*) FROM chunks)",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .context("Failed to count trigram index rows")?;
+    if indexed != chunks {
+        conn.execute(
+            "INSERT INTO chunks_fts_trigram(chunks_fts_trigram) VALUES ('rebuild')",
+            [],
+        )
+        .context("Failed to rebuild trigram index")?;
+    }
+    Ok(())
+}
+
 // ─── Implementation ───────────────────────────────────────────────────────────
 
 impl KnowledgeGraphStorage {
@@ -327,25 +893,24 @@ impl KnowledgeGraphStorage {
     /// virtual table, and triggers) is applied on every open via
     /// `CREATE … IF NOT EXISTS`, so this method is idempotent.
     pub fn new(db_path: &Path) -> Result<Self> {
+        Self::open_with_progress(db_path, &NoProgress)
+    }
+
+    /// Like [`new`](Self::new), reporting stage `"migrate"` to `progress`
+    /// once per migration step.  Every step is idempotent, so cancelling
+    /// fails with [`UForgeError::Cancelled`](crate::UForgeError::Cancelled)
+    /// and the next open finishes the remaining steps.
+    pub fn open_with_progress(db_path: &Path, progress: &dyn ProgressSink) -> Result<Self> {
+        const STEPS: usize = 7;
+        let step = |done: usize, name: &str| -> Result<()> {
+            progress.check_cancelled()?;
+            progress.report(&Progress::new("migrate", done, Some(STEPS)).with_message(name));
+            Ok(())
+        };
+
         std::fs::create_dir_all(db_path).context("Failed to create database directory")?;
 
-        // Register sqlite-vec as a process-wide SQLite auto-extension so that
-        // every subsequent Connection::open automatically gets the vec0
-        // virtual-table module.  The Once guard ensures the unsafe FFI call
-        // fires exactly once per process — safe even in multi-threaded tests
-        // that each create their own KnowledgeGraphStorage on a TempDir.
-        SQLITE_VEC_INIT.call_once(|| unsafe {
-            use rusqlite::ffi::sqlite3_auto_extension;
-            use sqlite_vec::sqlite3_vec_init;
-            sqlite3_auto_extension(Some(std::mem::transmute::<
-                *const (),
-                unsafe extern "C" fn(
-                    *mut rusqlite::ffi::sqlite3,
-                    *mut *mut i8,
-                    *const rusqlite::ffi::sqlite3_api_routines,
-                ) -> i32,
-            >(sqlite3_vec_init as *const ())));
-        });
+        register_sqlite_vec();
 
         let db_file = db_path.join("knowledge.db");
         let conn = Connection::open(&db_file)
@@ -354,25 +919,80 @@ impl KnowledgeGraphStorage {
         // Apply WAL mode, FK enforcement, DDL, indexes, FTS triggers, and the
         // chunks_vec vec0 virtual table in one batch.  `execute_batch` uses
         // sqlite3_exec internally and ignores result rows from PRAGMA statements.
+        step(0, "schema")?;
         conn.execute_batch(SQL_SCHEMA)
             .context("Failed to initialise database schema")?;
 
+        // Columns added after the initial schema, plus indexes that depend on them.
+        step(1, "columns")?;
+        ensure_column(&conn, "nodes", "lifecycle", "TEXT NOT NULL DEFAULT 'canon'")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_nodes_lifecycle ON nodes(lifecycle);",
+        )
+        .context("Failed to create lifecycle index")?;
+        ensure_column(&conn, "chunks", "language", "TEXT")?;
+        ensure_column(&conn, "chunks", "start_ms", "INTEGER")?;
+        ensure_column(&conn, "chunks", "end_ms", "INTEGER")?;
+        ensure_column(&conn, "edges", "id", "TEXT")?;
+        step(2, "edge ids")?;
+        assign_edge_ids(&conn)?;
+        conn.execute_batch("CREATE UNIQUE INDEX IF NOT EXISTS idx_edges_id ON edges(id);")
+            .context("Failed to create edge id index")?;
+        step(3, "history")?;
+        ensure_column(&conn, "edge_history", "edge_id", "TEXT")?;
+        conn.execute_batch(HISTORY_TRIGGERS)
+            .context("Failed to initialise node/edge history")?;
+        step(4, "coordinates")?;
+        backfill_node_coordinates(&conn)?;
+        step(5, "full-text index")?;
+        rebuild_trigram_index(&conn)?;
+
         // Verify (or record) the embedding dimensions baked into each vec0 table.
         // Returns EmbeddingDimensionMismatch if the model was changed without
         // recreating the database.
+        step(6, "embedding dimensions")?;
         check_or_init_embedding_dims(
             &conn,
             &[
                 ("chunks_vec", EMBEDDING_DIMENSIONS),
                 ("chunks_vec_hq", HIGH_QUALITY_EMBEDDING_DIMENSIONS),
+                ("node_profiles_vec", EMBEDDING_DIMENSIONS),
             ],
         )?;
+        step(STEPS, "done")?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
+            db_file,
+            cache: ReadCache::new(DEFAULT_READ_CACHE_BYTES),
+            events: broadcast::channel(GRAPH_EVENT_CAPACITY).0,
         })
     }
 
+    /// Receive every [`GraphEvent`] sent from now on.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<GraphEvent> {
+        self.events.subscribe()
+    }
+
+    /// Send `event` to current subscribers; dropped when there are none.
+    pub fn emit(&self, event: GraphEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// The directory holding `knowledge.db` and its sidecar files.
+    pub fn db_dir(&self) -> &Path {
+        self.db_file.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Fold the WAL back into `knowledge.db` and truncate it, so the
+    /// directory can be copied without the connection's help.
+    pub fn checkpoint(&self) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .context("Failed to checkpoint WAL")?;
+        Ok(())
+    }
+
     // ── Bulk operations ───────────────────────────────────────────────────────
 
     /// Delete all data from the knowledge graph, leaving an empty database.
@@ -384,64 +1004,37 @@ impl KnowledgeGraphStorage {
         let conn = self.conn.lock();
         conn.execute_batch(
             "DELETE FROM nodes;
+             DELETE FROM node_history;
+             DELETE FROM edge_history;
              DELETE FROM schemas;
+             DELETE FROM proposals;
+             DELETE FROM glossary;
+             DELETE FROM speaker_mappings;
+             DELETE FROM branches;
              DELETE FROM chunks_vec;
-             DELETE FROM chunks_vec_hq;",
+             DELETE FROM chunks_vec_hq;
+             DELETE FROM node_profiles_vec;",
         )
-        .context("Failed to clear knowledge graph")
+        .context("Failed to clear knowledge graph")?;
+        Ok(())
     }
 
-    /// Delete all node data (nodes, edges via cascade, chunks, vectors) but leave schemas intact.
+    /// Delete all node data (nodes, edges via cascade, chunks, vectors, pending
+    /// proposals) but leave schemas intact.
     pub fn clear_data_only(&self) -> Result<()> {
         let conn = self.conn.lock();
         conn.execute_batch(
             "DELETE FROM nodes;
+             DELETE FROM node_history;
+             DELETE FROM edge_history;
+             DELETE FROM proposals;
+             DELETE FROM branches;
              DELETE FROM chunks_vec;
-             DELETE FROM chunks_vec_hq;",
+             DELETE FROM chunks_vec_hq;
+             DELETE FROM node_profiles_vec;",
         )
-        .context("Failed to clear node data")
-    }
-
-    // ── Statistics ────────────────────────────────────────────────────────────
-
-    /// Return aggregate graph statistics.
-    ///
-    /// All four queries use indexed `COUNT(*)` or `SUM(…)` — effectively O(1)
-    /// regardless of graph size.
-    pub fn get_stats(&self) -> Result<GraphStats> {
-        let conn = self.conn.lock();
-
-        let node_count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM nodes", [], |r| r.get(0))
-            .context("Failed to count nodes")?;
-        let edge_count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM edges", [], |r| r.get(0))
-            .context("Failed to count edges")?;
-        let chunk_count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM chunks", [], |r| r.get(0))
-            .context("Failed to count chunks")?;
-        let total_tokens: i64 = conn
-            .query_row(
-                "SELECT COALESCE(SUM(token_count), 0) FROM chunks",
-                [],
-                |r| r.get(0),
-            )
-            .context("Failed to sum token_count")?;
-        let embedded_count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM chunks_vec", [], |r| r.get(0))
-            .context("Failed to count chunks_vec")?;
-        let embedded_hq_count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM chunks_vec_hq", [], |r| r.get(0))
-            .context("Failed to count chunks_vec_hq")?;
-
-        Ok(GraphStats {
-            node_count: node_count as usize,
-            edge_count: edge_count as usize,
-            chunk_count: chunk_count as usize,
-            total_tokens: total_tokens as usize,
-            embedded_count: embedded_count as usize,
-            embedded_hq_count: embedded_hq_count as usize,
-        })
+        .context("Failed to clear node data")?;
+        Ok(())
     }
 
     // ── Schemas ───────────────────────────────────────────────────────────────
@@ -510,7 +1103,8 @@ impl KnowledgeGraphStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{ChunkId, ChunkType, Edge, EdgeType, ObjectId, TextChunk};
+    use crate::error::UForgeError;
+    use crate::types::{ChunkId, ChunkType, Edge, EdgeChanges, EdgeType, ObjectId, TextChunk};
     use std::collections::HashSet;
     use tempfile::TempDir;
 
@@ -624,6 +1218,59 @@ mod tests {
         assert!(storage.get_neighbors(sam.id).unwrap().is_empty());
     }
 
+    #[test]
+    fn test_edge_ids_update_and_delete() {
+        let (storage, _dir) = create_test_storage();
+
+        let gandalf = ObjectMetadata::new("character".to_string(), "Gandalf".to_string());
+        let frodo = ObjectMetadata::new("character".to_string(), "Frodo".to_string());
+        let sam = ObjectMetadata::new("character".to_string(), "Sam".to_string());
+        for node in [&gandalf, &frodo, &sam] {
+            storage.upsert_node(node.clone()).unwrap();
+        }
+
+        storage
+            .upsert_edge(Edge::new(gandalf.id, frodo.id, EdgeType::new("knows")))
+            .unwrap();
+        let id = storage.get_edges(gandalf.id).unwrap()[0].id;
+
+        // Re-writing the same triplet keeps the stored id.
+        storage
+            .upsert_edge(Edge::new(gandalf.id, frodo.id, EdgeType::new("knows")).with_weight(0.5))
+            .unwrap();
+        let edge = storage.get_edge(id).unwrap().unwrap();
+        assert_eq!(edge.weight, 0.5);
+
+        // Retarget in place.
+        let changes = EdgeChanges {
+            to: Some(sam.id),
+            edge_type: Some(EdgeType::new("mentors")),
+            ..Default::default()
+        };
+        let updated = storage.update_edge(id, &changes).unwrap().unwrap();
+        assert_eq!((updated.id, updated.to), (id, sam.id));
+        assert!(storage.get_edges(frodo.id).unwrap().is_empty());
+        assert_eq!(storage.get_edges(sam.id).unwrap()[0].id, id);
+
+        // Colliding with an existing triplet fails and changes nothing.
+        storage
+            .upsert_edge(Edge::new(gandalf.id, frodo.id, EdgeType::new("knows")))
+            .unwrap();
+        let collide = EdgeChanges {
+            to: Some(frodo.id),
+            edge_type: Some(EdgeType::new("knows")),
+            ..Default::default()
+        };
+        assert!(storage.update_edge(id, &collide).is_err());
+        assert_eq!(storage.get_edge(id).unwrap().unwrap().to, sam.id);
+
+        assert!(storage.delete_edge_by_id(id).unwrap());
+        assert!(!storage.delete_edge_by_id(id).unwrap());
+        assert!(storage.get_edge(id).unwrap().is_none());
+        assert!(storage.update_edge(id, &EdgeChanges::default()).unwrap().is_none());
+        assert_eq!(storage.get_edges(gandalf.id).unwrap().len(), 1);
+    }
+
     // ── Cascade delete ────────────────────────────────────────────────────────
 
     #[test]
@@ -751,6 +1398,38 @@ mod tests {
 
     // ── FTS5 full-text search ─────────────────────────────────────────────────
 
+    #[test]
+    fn test_search_chunks_bm25_matches_any_term_and_ranks_rare_terms_first() {
+        let (storage, _dir) = create_test_storage();
+        let mut ids = Vec::new();
+        for (name, text) in [
+            ("Tower", "The old tower stands north of the river."),
+            ("Keep", "A tower keep guarded by the north watch."),
+            ("Ixildar", "Ixildar, the ruined city, lies beyond the tower."),
+        ] {
+            let node = ObjectMetadata::new("location".to_string(), name.to_string());
+            storage.upsert_node(node.clone()).unwrap();
+            storage
+                .upsert_chunk(TextChunk::new(node.id, text.to_string(), ChunkType::Description))
+                .unwrap();
+            ids.push(node.id);
+        }
+
+        // "vraskgut" matches nothing; AND-semantics FTS would return no rows.
+        let terms = ["ixildar", "tower", "vraskgut"].map(String::from);
+        assert!(storage.search_chunks_fts("ixildar tower vraskgut", 10).unwrap().is_empty());
+        let results = storage.search_chunks_bm25(&terms, 10).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].1, ids[2], "the rare proper noun should rank first");
+        assert!(results[0].3 > results[1].3);
+
+        assert!(storage.search_chunks_bm25(&[], 10).unwrap().is_empty());
+        // Quotes and FTS5 operators in a term are matched literally.
+        assert!(storage
+            .search_chunks_bm25(&["\"tower OR".to_string()], 10)
+            .is_ok());
+    }
+
     #[test]
     fn test_search_chunks_fts() {
         let (storage, _dir) = create_test_storage();
@@ -805,6 +1484,45 @@ mod tests {
         assert_eq!(prefix.len(), 1, "prefix 'wiz*' should match 'wizard'");
     }
 
+    #[test]
+    fn test_search_chunks_fts_unspaced_scripts() {
+        let (storage, dir) = create_test_storage();
+        let node = ObjectMetadata::new("character".to_string(), "Merlin".to_string());
+        storage.upsert_node(node.clone()).unwrap();
+        storage
+            .upsert_chunk(TextChunk::new(
+                node.id,
+                "古い塔に住む魔法使いは竜を恐れない。".to_string(),
+                ChunkType::Description,
+            ))
+            .unwrap();
+
+        // unicode61 indexes the whole sentence as one token; the trigram
+        // index finds a word inside it.
+        let results = storage.search_chunks_fts("魔法使い", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1, node.id);
+        let results = storage
+            .search_chunks_bm25(&["魔法使い".to_string()], 10)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // A database from before the trigram index gets it rebuilt on open.
+        storage
+            .conn
+            .lock()
+            .execute_batch(
+                "DROP TRIGGER chunks_trigram_ai;
+                 DROP TRIGGER chunks_trigram_ad;
+                 DROP TRIGGER chunks_trigram_au;
+                 DROP TABLE chunks_fts_trigram;",
+            )
+            .unwrap();
+        drop(storage);
+        let storage = KnowledgeGraphStorage::new(dir.path()).unwrap();
+        assert_eq!(storage.search_chunks_fts("魔法使い", 10).unwrap().len(), 1);
+    }
+
     // ── BFS subgraph expansion ────────────────────────────────────────────────
 
     #[test]
@@ -945,6 +1663,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_similar_and_duplicate_chunks() {
+        let (storage, _dir) = create_test_storage();
+        let node = ObjectMetadata::new("session".to_string(), "Session 3".to_string());
+        storage.upsert_node(node.clone()).unwrap();
+
+        let mut ids = Vec::new();
+        for (text, dim) in [("Notes A", 0), ("Notes A again", 0), ("Other", 1)] {
+            let chunk = TextChunk::new(node.id, text.to_string(), ChunkType::SessionNote);
+            ids.push(chunk.id);
+            storage.upsert_chunk(chunk).unwrap();
+            storage
+                .upsert_chunk_embedding(ids[ids.len() - 1], &one_hot(dim, EMBEDDING_DIMENSIONS))
+                .unwrap();
+        }
+
+        let similar = storage.find_similar_chunks(ids[0], 0.05).unwrap();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].chunk_id, ids[1]);
+        assert!(storage.find_similar_chunks(ids[2], 0.05).unwrap().is_empty());
+
+        let groups = storage.find_duplicate_chunks(0.05).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].chunk_ids.len(), 2);
+        assert!(groups[0].chunk_ids.contains(&ids[0]) && groups[0].chunk_ids.contains(&ids[1]));
+        assert_eq!(groups[0].object_ids, vec![node.id]);
+
+        assert!(storage.delete_chunk(groups[0].chunk_ids[1]).unwrap());
+        assert!(storage.find_duplicate_chunks(0.05).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_chunks_into_keeps_duplicate_history() {
+        let (storage, _dir) = create_test_storage();
+        let node = ObjectMetadata::new("session".to_string(), "Session 3".to_string());
+        storage.upsert_node(node.clone()).unwrap();
+
+        let keeper = TextChunk::new(node.id, "Notes A".to_string(), ChunkType::SessionNote);
+        let copy = TextChunk::new(node.id, "Notes A draft".to_string(), ChunkType::SessionNote)
+            .with_time_range(0, 5_000);
+        let (keep_id, copy_id) = (keeper.id, copy.id);
+        storage.upsert_chunk(keeper).unwrap();
+        storage.upsert_chunk(copy).unwrap();
+        storage
+            .update_chunk_content(copy_id, "Notes A again", Some("gm"))
+            .unwrap();
+
+        assert_eq!(storage.merge_chunks_into(keep_id, &[copy_id]).unwrap(), 1);
+
+        let history = storage.get_chunk_history(keep_id).unwrap();
+        let contents: Vec<&str> = history.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, vec!["Notes A draft", "Notes A again", "Notes A"]);
+        let chunks = storage.get_chunks_for_node(node.id).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].id, keep_id);
+        assert_eq!(chunks[0].time_range, Some((0, 5_000)));
+    }
+
     #[test]
     fn test_semantic_search_ranking() {
         let (storage, _dir) = create_test_storage();
@@ -1141,6 +1917,7 @@ mod tests {
             err.to_string().contains("dimension mismatch"),
             "error should mention dimension mismatch, got: {err}"
         );
+        assert_eq!(err.kind(), crate::error::ErrorKind::EmbeddingDimensionMismatch);
     }
 
     #[test]
@@ -1303,10 +2080,29 @@ mod tests {
             msg.contains("chunks_vec") && msg.contains("999"),
             "error must name the table and stored dim, got: {msg}"
         );
-        // Confirm it downcasts to the structured error type.
+        // Confirm it surfaces as the structured error type.
         assert!(
-            err.downcast_ref::<EmbeddingDimensionMismatch>().is_some(),
+            matches!(err, UForgeError::EmbeddingDimensionMismatch(_)),
             "error must be EmbeddingDimensionMismatch"
         );
     }
+
+    #[test]
+    fn test_open_with_progress_reports_migration_steps() {
+        let dir = TempDir::new().unwrap();
+        let stages = parking_lot::Mutex::new(Vec::new());
+        let sink = |p: &Progress| stages.lock().push((p.done, p.message.clone()));
+        KnowledgeGraphStorage::open_with_progress(dir.path(), &sink).unwrap();
+        let stages = stages.into_inner();
+        assert_eq!(stages.first(), Some(&(0, Some("schema".to_string()))));
+        assert_eq!(stages.last(), Some(&(7, Some("done".to_string()))));
+
+        let cancel = crate::progress::CancellationToken::new();
+        cancel.cancel();
+        let cancelled = crate::progress::LatestProgress::new().with_cancellation(cancel);
+        let err = KnowledgeGraphStorage::open_with_progress(dir.path(), &cancelled)
+            .err()
+            .unwrap();
+        assert!(matches!(err, UForgeError::Cancelled));
+    }
 }
@@ -1,9 +1,9 @@
 //! Graph traversal methods for KnowledgeGraphStorage.
 
 use super::storage::*;
-use anyhow::Result;
 
-use crate::types::{ObjectId, QueryResult};
+use crate::error::Result;
+use crate::types::{Edge, ObjectId, ObjectMetadata, QueryResult, TextChunk};
 use std::collections::HashSet;
 use tracing::warn;
 
@@ -23,6 +23,26 @@ impl KnowledgeGraphStorage {
     /// The loop runs for `max_hops + 1` iterations: iteration 0 processes the
     /// start node, iteration 1 its direct neighbours, and so on.
     pub fn query_subgraph(&self, start: ObjectId, max_hops: usize) -> Result<QueryResult> {
+        self.bfs_subgraph(
+            start,
+            max_hops,
+            |id| self.get_node(id),
+            |id| self.get_edges(id),
+            |id| self.get_chunks_for_node(id),
+        )
+    }
+
+    /// The BFS behind [`query_subgraph`](Self::query_subgraph), with the node,
+    /// edge, and chunk lookups supplied by the caller so the same walk can run
+    /// against current or historical state.
+    pub(crate) fn bfs_subgraph(
+        &self,
+        start: ObjectId,
+        max_hops: usize,
+        get_node: impl Fn(ObjectId) -> Result<Option<ObjectMetadata>>,
+        get_edges: impl Fn(ObjectId) -> Result<Vec<Edge>>,
+        get_chunks: impl Fn(ObjectId) -> Result<Vec<TextChunk>>,
+    ) -> Result<QueryResult> {
         let mut result = QueryResult::new();
         let mut visited: HashSet<ObjectId> = HashSet::new();
         let mut seen_edges: HashSet<(ObjectId, ObjectId, String)> = HashSet::new();
@@ -41,7 +61,7 @@ impl KnowledgeGraphStorage {
                 visited.insert(node_id);
 
                 // ── node metadata ─────────────────────────────────────────────
-                match self.get_node(node_id)? {
+                match get_node(node_id)? {
                     Some(meta) => result.add_object(meta),
                     None => {
                         warn!(
@@ -53,7 +73,7 @@ impl KnowledgeGraphStorage {
                 }
 
                 // ── edges (deduplicated) ──────────────────────────────────────
-                for edge in self.get_edges(node_id)? {
+                for edge in get_edges(node_id)? {
                     let key = (edge.from, edge.to, edge.edge_type.as_str().to_string());
                     if seen_edges.insert(key) {
                         result.add_edge(edge.clone());
@@ -70,7 +90,7 @@ impl KnowledgeGraphStorage {
                 }
 
                 // ── text chunks ───────────────────────────────────────────────
-                for chunk in self.get_chunks_for_node(node_id)? {
+                for chunk in get_chunks(node_id)? {
                     result.add_chunk(chunk);
                 }
             }
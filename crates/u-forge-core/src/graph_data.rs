@@ -0,0 +1,1137 @@
+//! Scoped graph data for visualisation.
+//!
+//! Drawing every node of a 20 000-node world produces an unreadable hairball
+//! and a slow frame.  [`KnowledgeGraph::get_graph_data`] returns just the part
+//! of the graph a view needs — the neighbourhood of a focus node, or the
+//! nodes matching a [`NodeFilter`] — capped at a node and edge budget, along
+//! with the totals the UI needs to say "showing 500 of 20 113".
+//!
+//! Pruning happens here rather than in the renderer: nodes nearest the focus
+//! (or with the most connections, for filters) are kept first, only edges
+//! between kept nodes are returned, and when those exceed the edge budget the
+//! heaviest edges win.
+//!
+//! For the first look at a whole world, an [`Aggregation`] collapses the
+//! selection into [`Cluster`] super-nodes — one per object type, tag, or
+//! detected community — joined by [`ClusterEdge`]s that count the edges
+//! between them.  Listing a cluster's key in [`GraphDataRequest::expand`]
+//! returns its members as ordinary nodes instead.
+//!
+//! [`KnowledgeGraph::get_relevant_neighborhood`] grows a neighbourhood by
+//! relevance rather than hop count: the heaviest, most recent edge leaving
+//! the nodes gathered so far is followed next, until the budget is spent.
+//! The focus view uses it through [`GraphScope::Relevant`].
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::styles::NodeStyle;
+use crate::types::{Edge, EdgeId, Lifecycle, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Default node budget for [`GraphDataRequest`].
+pub const DEFAULT_MAX_NODES: usize = 500;
+
+/// Default edge budget for [`GraphDataRequest`].
+pub const DEFAULT_MAX_EDGES: usize = 2000;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Which nodes a [`GraphDataRequest`] selects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GraphScope {
+    /// The whole graph (subject to the budget).
+    All,
+    /// `focus` and everything within `depth` hops of it, in either direction.
+    Neighborhood { focus: ObjectId, depth: usize },
+    /// Nodes matching the filter.
+    Filter(NodeFilter),
+    /// The most relevant nodes around `focus`; see
+    /// [`KnowledgeGraph::get_relevant_neighborhood`].
+    Relevant { focus: ObjectId },
+    /// Everything within `depth` hops of any of the `focus` nodes that also
+    /// matches `filter`.  The focus nodes themselves are always kept.
+    Focus {
+        focus: Vec<ObjectId>,
+        depth: usize,
+        #[serde(default)]
+        filter: NodeFilter,
+    },
+}
+
+/// Node predicate for [`GraphScope::Filter`].  Empty fields match anything;
+/// non-empty ones must all match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeFilter {
+    /// Any of these object types.
+    #[serde(default)]
+    pub object_types: Vec<String>,
+    /// At least one of these tags (case-insensitive).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Any of these lifecycles.
+    #[serde(default)]
+    pub lifecycles: Vec<Lifecycle>,
+    /// Case-insensitive substring of the name.
+    #[serde(default)]
+    pub name_contains: Option<String>,
+}
+
+impl NodeFilter {
+    pub fn matches(&self, object: &ObjectMetadata) -> bool {
+        if !self.object_types.is_empty()
+            && !self
+                .object_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&object.object_type))
+        {
+            return false;
+        }
+        if !self.lifecycles.is_empty()
+            && !self
+                .lifecycles
+                .contains(&object.lifecycle.unwrap_or_default())
+        {
+            return false;
+        }
+        if let Some(needle) = &self.name_contains {
+            if !object.name.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = object_tags(object);
+            if !self
+                .tags
+                .iter()
+                .any(|t| tags.iter().any(|o| o.eq_ignore_ascii_case(t)))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// How [`GraphDataRequest::aggregate`] groups nodes into clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    /// One cluster per object type (key `type:<object_type>`).
+    Type,
+    /// One cluster per first tag, lower-cased (key `tag:<tag>`); untagged
+    /// nodes share the key `tag:`.
+    Tag,
+    /// Densely connected groups found by label propagation (key
+    /// `community:<n>`, 1 being the largest).
+    Community,
+}
+
+/// A scoped, budgeted request for graph data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphDataRequest {
+    pub scope: GraphScope,
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+    #[serde(default = "default_max_edges")]
+    pub max_edges: usize,
+    /// Collapse the selected nodes into clusters.
+    #[serde(default)]
+    pub aggregate: Option<Aggregation>,
+    /// Keys of clusters to return as individual nodes.
+    #[serde(default)]
+    pub expand: Vec<String>,
+    /// Edge types to return; empty returns every type.  Hidden edges are
+    /// still walked when gathering a neighbourhood.
+    #[serde(default)]
+    pub edge_types: Vec<String>,
+    /// Also show [archived](crate::archive) objects, which are left out by
+    /// default.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+fn default_max_nodes() -> usize {
+    DEFAULT_MAX_NODES
+}
+
+fn default_max_edges() -> usize {
+    DEFAULT_MAX_EDGES
+}
+
+impl GraphDataRequest {
+    pub fn new(scope: GraphScope) -> Self {
+        Self {
+            scope,
+            max_nodes: DEFAULT_MAX_NODES,
+            max_edges: DEFAULT_MAX_EDGES,
+            aggregate: None,
+            expand: Vec::new(),
+            edge_types: Vec::new(),
+            include_archived: false,
+        }
+    }
+
+    pub fn neighborhood(focus: ObjectId, depth: usize) -> Self {
+        Self::new(GraphScope::Neighborhood { focus, depth })
+    }
+
+    pub fn filter(filter: NodeFilter) -> Self {
+        Self::new(GraphScope::Filter(filter))
+    }
+
+    pub fn relevant(focus: ObjectId) -> Self {
+        Self::new(GraphScope::Relevant { focus })
+    }
+
+    pub fn with_budget(mut self, max_nodes: usize, max_edges: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self.max_edges = max_edges;
+        self
+    }
+
+    pub fn aggregated(mut self, aggregation: Aggregation) -> Self {
+        self.aggregate = Some(aggregation);
+        self
+    }
+
+    /// Return the members of cluster `key` as individual nodes.
+    pub fn expanding(mut self, key: impl Into<String>) -> Self {
+        self.expand.push(key.into());
+        self
+    }
+
+    /// Return only edges of these types.
+    pub fn with_edge_types<S: Into<String>>(mut self, types: impl IntoIterator<Item = S>) -> Self {
+        self.edge_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether `edge` passes [`edge_types`](Self::edge_types).
+    pub fn shows_edge(&self, edge: &Edge) -> bool {
+        self.edge_types.is_empty()
+            || self
+                .edge_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(edge.edge_type.as_str()))
+    }
+}
+
+/// Node and edge limits for [`KnowledgeGraph::get_relevant_neighborhood`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NeighborhoodBudget {
+    pub max_nodes: usize,
+    pub max_edges: usize,
+}
+
+impl NeighborhoodBudget {
+    pub fn new(max_nodes: usize, max_edges: usize) -> Self {
+        Self {
+            max_nodes,
+            max_edges,
+        }
+    }
+}
+
+impl Default for NeighborhoodBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_NODES, DEFAULT_MAX_EDGES)
+    }
+}
+
+/// An edge leading out of the gathered nodes, ordered heaviest then newest
+/// (then by id, so expansion is deterministic).
+struct Candidate {
+    edge: Edge,
+    next: ObjectId,
+}
+
+impl Candidate {
+    fn cmp_key(&self) -> (f32, chrono::DateTime<chrono::Utc>, uuid::Uuid) {
+        (self.edge.weight, self.edge.created_at, self.edge.id.0)
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.cmp_key(), other.cmp_key());
+        a.0.total_cmp(&b.0)
+            .then_with(|| a.1.cmp(&b.1))
+            // Reversed so the smaller id wins a full tie.
+            .then_with(|| b.2.cmp(&a.2))
+    }
+}
+
+/// A super-node standing for a group of collapsed nodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cluster {
+    /// Stable within one aggregation; pass to [`GraphDataRequest::expanding`].
+    pub key: String,
+    pub label: String,
+    /// Number of member nodes.
+    pub size: usize,
+    /// Edges with both ends in this cluster.
+    pub internal_edges: usize,
+    /// Names of up to five best-connected members, for tooltips.
+    pub sample: Vec<String>,
+}
+
+/// One end of a [`ClusterEdge`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum GraphNodeRef {
+    Object(ObjectId),
+    Cluster(String),
+}
+
+impl GraphNodeRef {
+    /// Objects before clusters, then by id or key.
+    fn sort_key(&self) -> (bool, String) {
+        match self {
+            GraphNodeRef::Object(id) => (false, id.to_string()),
+            GraphNodeRef::Cluster(key) => (true, key.clone()),
+        }
+    }
+}
+
+/// All edges between a cluster and another cluster or an expanded node.
+/// Undirected: `from` sorts before `to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterEdge {
+    pub from: GraphNodeRef,
+    pub to: GraphNodeRef,
+    /// Number of underlying edges.
+    pub count: usize,
+    /// Sum of their weights.
+    pub weight: f32,
+}
+
+/// The result of [`KnowledgeGraph::get_graph_data`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphData {
+    pub objects: Vec<ObjectMetadata>,
+    /// Edges whose endpoints are both in `objects`.
+    pub edges: Vec<Edge>,
+    /// Nodes in the whole graph.
+    pub total_nodes: usize,
+    /// Edges in the whole graph.
+    pub total_edges: usize,
+    /// Nodes the scope selected before the node budget was applied.
+    pub matched_nodes: usize,
+    /// Edges between returned nodes before the edge budget was applied.
+    pub matched_edges: usize,
+    /// Collapsed groups, when the request asked for an [`Aggregation`].
+    #[serde(default)]
+    pub clusters: Vec<Cluster>,
+    /// Edges touching a collapsed cluster, aggregated.
+    #[serde(default)]
+    pub cluster_edges: Vec<ClusterEdge>,
+    /// Effective style of every object type in `objects` (and of each
+    /// `type:` cluster), keyed by type.  See [`crate::styles`].
+    #[serde(default)]
+    pub styles: HashMap<String, NodeStyle>,
+}
+
+impl GraphData {
+    /// Whether the budget cut anything the scope selected.
+    pub fn truncated(&self) -> bool {
+        self.objects.len() < self.matched_nodes || self.edges.len() < self.matched_edges
+    }
+}
+
+/// `properties["tags"]` as strings.
+pub(crate) fn object_tags(object: &ObjectMetadata) -> Vec<&str> {
+    object
+        .get_json_property("tags")
+        .and_then(|v| v.as_array())
+        .map(|tags| tags.iter().filter_map(|t| t.as_str()).collect())
+        .unwrap_or_default()
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// The nodes and edges selected by `request`, within its budget.
+    ///
+    /// Neighbourhoods keep the nodes closest to the focus; filters and
+    /// [`GraphScope::All`] keep the best-connected nodes.  An unknown focus
+    /// yields no nodes.
+    pub fn get_graph_data(&self, request: &GraphDataRequest) -> Result<GraphData> {
+        let stats = self.storage.get_stats()?;
+        let mut data = GraphData {
+            total_nodes: stats.node_count,
+            total_edges: stats.edge_count,
+            ..Default::default()
+        };
+        let archived = if request.include_archived {
+            HashSet::new()
+        } else {
+            self.archived_ids()?
+        };
+        let shown = |id: &ObjectId| !archived.contains(id);
+
+        if let Some(aggregation) = request.aggregate {
+            let (mut objects, mut edges) = self.scope_selection(&request.scope)?;
+            objects.retain(|o| shown(&o.id));
+            edges.retain(|e| request.shows_edge(e) && shown(&e.from) && shown(&e.to));
+            data.matched_nodes = objects.len();
+            aggregate(&mut data, objects, edges, aggregation, request);
+            apply_edge_budget(&mut data, request.max_edges);
+            self.attach_styles(&mut data);
+            return Ok(data);
+        }
+
+        match &request.scope {
+            GraphScope::Relevant { focus } => {
+                let budget = NeighborhoodBudget::new(request.max_nodes, request.max_edges);
+                let mut data = self.get_relevant_neighborhood(*focus, budget)?;
+                data.objects.retain(|o| shown(&o.id));
+                let before = data.edges.len();
+                data.edges
+                    .retain(|e| request.shows_edge(e) && shown(&e.from) && shown(&e.to));
+                data.matched_edges -= before - data.edges.len();
+                return Ok(data);
+            }
+            GraphScope::Neighborhood { .. } | GraphScope::Focus { .. } => {
+                let (mut objects, matched) =
+                    self.focused_objects(&request.scope, request.max_nodes)?;
+                objects.retain(|o| shown(&o.id));
+                data.matched_nodes = matched;
+                data.objects = objects;
+                let kept: HashSet<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+                let mut seen: HashSet<EdgeId> = HashSet::new();
+                for id in &kept {
+                    for edge in self.storage.get_edges(*id)? {
+                        if kept.contains(&edge.from)
+                            && kept.contains(&edge.to)
+                            && request.shows_edge(&edge)
+                            && seen.insert(edge.id)
+                        {
+                            data.edges.push(edge);
+                        }
+                    }
+                }
+            }
+            GraphScope::All | GraphScope::Filter(_) => {
+                let mut objects = self.storage.get_all_objects()?;
+                objects.retain(|o| shown(&o.id));
+                if let GraphScope::Filter(filter) = &request.scope {
+                    objects.retain(|o| filter.matches(o));
+                }
+                data.matched_nodes = objects.len();
+                let mut all_edges = self.storage.get_all_edges()?;
+                all_edges.retain(|e| request.shows_edge(e));
+
+                if objects.len() > request.max_nodes {
+                    // Keep the best-connected nodes, counting only edges
+                    // within the selection.
+                    let selected: HashSet<ObjectId> = objects.iter().map(|o| o.id).collect();
+                    let mut degree: HashMap<ObjectId, usize> = HashMap::new();
+                    for edge in &all_edges {
+                        if selected.contains(&edge.from) && selected.contains(&edge.to) {
+                            *degree.entry(edge.from).or_default() += 1;
+                            *degree.entry(edge.to).or_default() += 1;
+                        }
+                    }
+                    objects.sort_by(|a, b| {
+                        let (da, db) = (degree.get(&a.id), degree.get(&b.id));
+                        db.cmp(&da).then_with(|| a.name.cmp(&b.name))
+                    });
+                    objects.truncate(request.max_nodes);
+                }
+
+                let kept: HashSet<ObjectId> = objects.iter().map(|o| o.id).collect();
+                data.edges = all_edges
+                    .into_iter()
+                    .filter(|e| kept.contains(&e.from) && kept.contains(&e.to))
+                    .collect();
+                data.objects = objects;
+            }
+        }
+
+        apply_edge_budget(&mut data, request.max_edges);
+        self.attach_styles(&mut data);
+        Ok(data)
+    }
+
+    /// Fill [`GraphData::styles`] for the types present in `data`.
+    fn attach_styles(&self, data: &mut GraphData) {
+        let cluster_types = data
+            .clusters
+            .iter()
+            .filter_map(|c| c.key.strip_prefix("type:"));
+        let types: HashSet<&str> = data
+            .objects
+            .iter()
+            .map(|o| o.object_type.as_str())
+            .chain(cluster_types)
+            .collect();
+        data.styles = self.type_styles(types);
+    }
+
+    /// Every node `scope` selects, with the edges among them, unbudgeted.
+    fn scope_selection(&self, scope: &GraphScope) -> Result<(Vec<ObjectMetadata>, Vec<Edge>)> {
+        let objects = match scope {
+            GraphScope::Neighborhood { .. } | GraphScope::Focus { .. } => {
+                self.focused_objects(scope, usize::MAX)?.0
+            }
+            GraphScope::Relevant { focus } => {
+                let unlimited = NeighborhoodBudget::new(usize::MAX, usize::MAX);
+                self.get_relevant_neighborhood(*focus, unlimited)?.objects
+            }
+            GraphScope::All => self.storage.get_all_objects()?,
+            GraphScope::Filter(filter) => {
+                let mut objects = self.storage.get_all_objects()?;
+                objects.retain(|o| filter.matches(o));
+                objects
+            }
+        };
+        let selected: HashSet<ObjectId> = objects.iter().map(|o| o.id).collect();
+        let edges = self
+            .storage
+            .get_all_edges()?
+            .into_iter()
+            .filter(|e| selected.contains(&e.from) && selected.contains(&e.to))
+            .collect();
+        Ok((objects, edges))
+    }
+
+    /// The part of the graph around `object_id` most worth showing or
+    /// feeding to a model, within `budget`.
+    ///
+    /// Starting from the focus, the heaviest edge leading to a node not yet
+    /// gathered is followed next, newer edges winning ties, so a strong
+    /// relationship two hops away beats a weak one next door.  Edges between
+    /// gathered nodes are included as they are found.  Expansion stops when
+    /// either budget is reached or nothing is left to reach.
+    ///
+    /// `objects` are in the order they were gathered, focus first.
+    /// `matched_nodes` and `matched_edges` count everything seen along the
+    /// way, including the frontier left unexplored, so
+    /// [`GraphData::truncated`] reports whether the budget cut anything.
+    /// An unknown `object_id` yields empty data.
+    pub fn get_relevant_neighborhood(
+        &self,
+        object_id: ObjectId,
+        budget: NeighborhoodBudget,
+    ) -> Result<GraphData> {
+        let stats = self.storage.get_stats()?;
+        let mut data = GraphData {
+            total_nodes: stats.node_count,
+            total_edges: stats.edge_count,
+            ..Default::default()
+        };
+        let Some(focus) = self.storage.get_node(object_id)? else {
+            return Ok(data);
+        };
+        if budget.max_nodes == 0 {
+            data.matched_nodes = 1;
+            return Ok(data);
+        }
+
+        let mut kept: HashSet<ObjectId> = HashSet::new();
+        let mut reached: HashSet<ObjectId> = HashSet::from([object_id]);
+        let mut seen_edges: HashSet<EdgeId> = HashSet::new();
+        let mut frontier: BinaryHeap<Candidate> = BinaryHeap::new();
+        let mut next = Some(focus);
+
+        while let Some(object) = next.take() {
+            let id = object.id;
+            kept.insert(id);
+            data.objects.push(object);
+            for edge in self.storage.get_edges(id)? {
+                if !seen_edges.insert(edge.id) {
+                    continue;
+                }
+                let other = if edge.from == id { edge.to } else { edge.from };
+                if kept.contains(&other) {
+                    if data.edges.len() < budget.max_edges {
+                        data.edges.push(edge);
+                    }
+                } else {
+                    reached.insert(other);
+                    frontier.push(Candidate { edge, next: other });
+                }
+            }
+
+            if data.objects.len() >= budget.max_nodes {
+                break;
+            }
+            while data.edges.len() < budget.max_edges {
+                let Some(candidate) = frontier.pop() else {
+                    break;
+                };
+                if kept.contains(&candidate.next) {
+                    // Both ends were gathered after it was queued.
+                    data.edges.push(candidate.edge);
+                } else if let Some(object) = self.storage.get_node(candidate.next)? {
+                    data.edges.push(candidate.edge);
+                    next = Some(object);
+                    break;
+                }
+            }
+        }
+
+        // Queued edges whose far end was gathered later still belong.
+        for candidate in frontier.into_sorted_vec().into_iter().rev() {
+            if kept.contains(&candidate.next) && data.edges.len() < budget.max_edges {
+                data.edges.push(candidate.edge);
+            }
+        }
+        data.matched_nodes = reached.len();
+        data.matched_edges = seen_edges.len();
+        self.attach_styles(&mut data);
+        Ok(data)
+    }
+
+    /// The objects a [`GraphScope::Neighborhood`] or [`GraphScope::Focus`]
+    /// selects, nearest first, capped at `max_nodes`.  Also returns how many
+    /// the scope selects in full.
+    fn focused_objects(
+        &self,
+        scope: &GraphScope,
+        max_nodes: usize,
+    ) -> Result<(Vec<ObjectMetadata>, usize)> {
+        let (ids, matched) = match scope {
+            GraphScope::Neighborhood { focus, depth } => {
+                self.neighborhood_ids(&[*focus], *depth, max_nodes)?
+            }
+            GraphScope::Focus {
+                focus,
+                depth,
+                filter,
+            } => {
+                let mut ids = Vec::new();
+                for id in self.neighborhood_ids(focus, *depth, usize::MAX)?.0 {
+                    if focus.contains(&id) {
+                        ids.push(id);
+                    } else if let Some(object) = self.storage.get_node(id)? {
+                        if filter.matches(&object) {
+                            ids.push(id);
+                        }
+                    }
+                }
+                let matched = ids.len();
+                ids.truncate(max_nodes);
+                (ids, matched)
+            }
+            _ => return Ok((Vec::new(), 0)),
+        };
+        let mut objects = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(object) = self.storage.get_node(id)? {
+                objects.push(object);
+            }
+        }
+        Ok((objects, matched))
+    }
+
+    /// Breadth-first ids around the `focus` nodes, nearest first, capped at
+    /// `max_nodes`.  Also returns how many nodes the full walk would reach.
+    /// Unknown focus ids are skipped.
+    fn neighborhood_ids(
+        &self,
+        focus: &[ObjectId],
+        depth: usize,
+        max_nodes: usize,
+    ) -> Result<(Vec<ObjectId>, usize)> {
+        let mut order = Vec::new();
+        let mut visited: HashSet<ObjectId> = HashSet::new();
+        for id in focus {
+            if self.storage.get_node(*id)?.is_some() && visited.insert(*id) {
+                order.push(*id);
+            }
+        }
+        let mut frontier = order.clone();
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for id in frontier {
+                for neighbour in self.storage.get_neighbors(id)? {
+                    if visited.insert(neighbour) {
+                        order.push(neighbour);
+                        next.push(neighbour);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        let matched = order.len();
+        order.truncate(max_nodes);
+        Ok((order, matched))
+    }
+}
+
+/// Record `matched_edges` and keep the `max_edges` heaviest edges.
+fn apply_edge_budget(data: &mut GraphData, max_edges: usize) {
+    data.matched_edges = data.edges.len();
+    if data.edges.len() > max_edges {
+        data.edges.sort_by(|a, b| {
+            b.weight
+                .partial_cmp(&a.weight)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.created_at.cmp(&a.created_at))
+        });
+        data.edges.truncate(max_edges);
+    }
+}
+
+/// Edges per node, counting only `edges`.
+fn degrees(edges: &[Edge]) -> HashMap<ObjectId, usize> {
+    let mut degree: HashMap<ObjectId, usize> = HashMap::new();
+    for edge in edges {
+        *degree.entry(edge.from).or_default() += 1;
+        *degree.entry(edge.to).or_default() += 1;
+    }
+    degree
+}
+
+/// Fill `data` with the clusters, expanded nodes, and edges for `objects`
+/// grouped by `aggregation`.
+fn aggregate(
+    data: &mut GraphData,
+    objects: Vec<ObjectMetadata>,
+    edges: Vec<Edge>,
+    aggregation: Aggregation,
+    request: &GraphDataRequest,
+) {
+    let keys: HashMap<ObjectId, (String, String)> = match aggregation {
+        Aggregation::Type => objects
+            .iter()
+            .map(|o| {
+                (
+                    o.id,
+                    (format!("type:{}", o.object_type), o.object_type.clone()),
+                )
+            })
+            .collect(),
+        Aggregation::Tag => objects
+            .iter()
+            .map(|o| {
+                let tag = object_tags(o)
+                    .first()
+                    .map(|t| t.to_lowercase())
+                    .unwrap_or_default();
+                let label = if tag.is_empty() {
+                    "(untagged)".to_string()
+                } else {
+                    tag.clone()
+                };
+                (o.id, (format!("tag:{tag}"), label))
+            })
+            .collect(),
+        Aggregation::Community => communities(&objects, &edges)
+            .into_iter()
+            .map(|(id, n)| (id, (format!("community:{n}"), format!("Community {n}"))))
+            .collect(),
+    };
+    let expanded: HashSet<&str> = request.expand.iter().map(String::as_str).collect();
+    let degree = degrees(&edges);
+    let node_ref = |id: ObjectId| -> GraphNodeRef {
+        let key = &keys[&id].0;
+        if expanded.contains(key.as_str()) {
+            GraphNodeRef::Object(id)
+        } else {
+            GraphNodeRef::Cluster(key.clone())
+        }
+    };
+
+    // Clusters, and members of expanded clusters as individual nodes.
+    let mut members: HashMap<String, Vec<&ObjectMetadata>> = HashMap::new();
+    let mut individual: Vec<ObjectMetadata> = Vec::new();
+    for object in &objects {
+        let key = &keys[&object.id].0;
+        if expanded.contains(key.as_str()) {
+            individual.push(object.clone());
+        } else {
+            members.entry(key.clone()).or_default().push(object);
+        }
+    }
+    individual.sort_by(|a, b| {
+        let (da, db) = (degree.get(&a.id), degree.get(&b.id));
+        db.cmp(&da).then_with(|| a.name.cmp(&b.name))
+    });
+    individual.truncate(request.max_nodes);
+    let kept: HashSet<ObjectId> = individual.iter().map(|o| o.id).collect();
+
+    for (key, mut group) in members {
+        group.sort_by(|a, b| {
+            let (da, db) = (degree.get(&a.id), degree.get(&b.id));
+            db.cmp(&da).then_with(|| a.name.cmp(&b.name))
+        });
+        data.clusters.push(Cluster {
+            label: keys[&group[0].id].1.clone(),
+            size: group.len(),
+            internal_edges: 0,
+            sample: group.iter().take(5).map(|o| o.name.clone()).collect(),
+            key,
+        });
+    }
+    data.clusters
+        .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.key.cmp(&b.key)));
+
+    // Edges: kept between individual nodes, aggregated when a cluster is
+    // involved, dropped when they lead to an individual node over budget.
+    let mut aggregated: HashMap<(GraphNodeRef, GraphNodeRef), (usize, f32)> = HashMap::new();
+    for edge in edges {
+        let (from, to) = (node_ref(edge.from), node_ref(edge.to));
+        match (&from, &to) {
+            (GraphNodeRef::Object(a), GraphNodeRef::Object(b)) => {
+                if kept.contains(a) && kept.contains(b) {
+                    data.edges.push(edge);
+                }
+            }
+            (GraphNodeRef::Cluster(a), GraphNodeRef::Cluster(b)) if a == b => {
+                if let Some(cluster) = data.clusters.iter_mut().find(|c| &c.key == a) {
+                    cluster.internal_edges += 1;
+                }
+            }
+            _ => {
+                let dropped =
+                    |r: &GraphNodeRef| matches!(r, GraphNodeRef::Object(id) if !kept.contains(id));
+                if dropped(&from) || dropped(&to) {
+                    continue;
+                }
+                let pair = if from.sort_key() <= to.sort_key() {
+                    (from, to)
+                } else {
+                    (to, from)
+                };
+                let entry = aggregated.entry(pair).or_default();
+                entry.0 += 1;
+                entry.1 += edge.weight;
+            }
+        }
+    }
+    data.cluster_edges = aggregated
+        .into_iter()
+        .map(|((from, to), (count, weight))| ClusterEdge {
+            from,
+            to,
+            count,
+            weight,
+        })
+        .collect();
+    data.cluster_edges
+        .sort_by_key(|e| (e.from.sort_key(), e.to.sort_key()));
+    data.objects = individual;
+}
+
+/// Community number (1 = largest) for every object, by weighted label
+/// propagation: each node repeatedly adopts the label carrying the most edge
+/// weight among its neighbours.  Deterministic — nodes are visited in id
+/// order and ties go to the smallest label.
+fn communities(objects: &[ObjectMetadata], edges: &[Edge]) -> HashMap<ObjectId, usize> {
+    let mut ids: Vec<ObjectId> = objects.iter().map(|o| o.id).collect();
+    ids.sort_by_key(|id| id.0);
+    let index: HashMap<ObjectId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let mut adjacency: Vec<Vec<(usize, f32)>> = vec![Vec::new(); ids.len()];
+    for edge in edges {
+        if let (Some(&a), Some(&b)) = (index.get(&edge.from), index.get(&edge.to)) {
+            if a != b {
+                adjacency[a].push((b, edge.weight));
+                adjacency[b].push((a, edge.weight));
+            }
+        }
+    }
+
+    let mut labels: Vec<usize> = (0..ids.len()).collect();
+    for _ in 0..20 {
+        let mut changed = false;
+        for node in 0..ids.len() {
+            let mut votes: HashMap<usize, f32> = HashMap::new();
+            for &(neighbour, weight) in &adjacency[node] {
+                *votes.entry(labels[neighbour]).or_default() += weight;
+            }
+            let best = votes.into_iter().max_by(|a, b| {
+                a.1.partial_cmp(&b.1)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| b.0.cmp(&a.0))
+            });
+            if let Some((label, _)) = best {
+                if label != labels[node] {
+                    labels[node] = label;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Renumber by size, largest first.
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &label in &labels {
+        *sizes.entry(label).or_default() += 1;
+    }
+    let mut order: Vec<(usize, usize)> = sizes.into_iter().collect();
+    order.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let number: HashMap<usize, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, (label, _))| (*label, i + 1))
+        .collect();
+    ids.iter()
+        .zip(labels)
+        .map(|(id, label)| (*id, number[&label]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CreateRelationshipRequest;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_neighborhood_scope_and_budget() {
+        let (graph, _tmp) = create_test_graph();
+        // A chain hub - a - b - c, plus three leaves on the hub.
+        let hub = ObjectBuilder::location("Hub".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let a = ObjectBuilder::character("A".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let b = ObjectBuilder::character("B".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let c = ObjectBuilder::character("C".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(a, hub, "located_in").unwrap();
+        graph
+            .connect_objects_weighted_str(b, a, "knows", 0.5)
+            .unwrap();
+        graph.connect_objects_str(c, b, "knows").unwrap();
+        for name in ["L1", "L2", "L3"] {
+            let leaf = ObjectBuilder::item(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap();
+            graph
+                .connect_objects_weighted_str(leaf, hub, "located_in", 0.2)
+                .unwrap();
+        }
+
+        let data = graph
+            .get_graph_data(&GraphDataRequest::neighborhood(a, 1))
+            .unwrap();
+        let ids: HashSet<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, HashSet::from([a, hub, b]));
+        assert_eq!(data.edges.len(), 2);
+        assert_eq!((data.total_nodes, data.total_edges), (7, 6));
+        assert!(!data.truncated());
+
+        // Budget keeps the nearest nodes and the heaviest edges.
+        let data = graph
+            .get_graph_data(&GraphDataRequest::neighborhood(a, 2).with_budget(3, 1))
+            .unwrap();
+        assert_eq!(data.matched_nodes, 7);
+        let ids: HashSet<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, HashSet::from([a, hub, b]));
+        assert_eq!(data.matched_edges, 2);
+        assert_eq!(data.edges.len(), 1);
+        assert_eq!((data.edges[0].from, data.edges[0].to), (a, hub));
+        assert!(data.truncated());
+
+        let none = graph
+            .get_graph_data(&GraphDataRequest::neighborhood(ObjectId::new_v4(), 2))
+            .unwrap();
+        assert!(none.objects.is_empty());
+    }
+
+    #[test]
+    fn test_filter_scope_keeps_best_connected() {
+        let (graph, _tmp) = create_test_graph();
+        let frodo = ObjectBuilder::character("Frodo".to_string())
+            .with_tag("hobbit".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let sam = ObjectBuilder::character("Sam".to_string())
+            .with_tag("Hobbit".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let gandalf = ObjectBuilder::character("Gandalf".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let shire = ObjectBuilder::location("Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(frodo, sam, "knows").unwrap();
+        graph.connect_objects_str(frodo, gandalf, "knows").unwrap();
+        graph
+            .connect_objects_str(frodo, shire, "located_in")
+            .unwrap();
+
+        let hobbits = NodeFilter {
+            tags: vec!["HOBBIT".to_string()],
+            ..Default::default()
+        };
+        let data = graph
+            .get_graph_data(&GraphDataRequest::filter(hobbits))
+            .unwrap();
+        assert_eq!(data.objects.len(), 2);
+        assert_eq!(data.edges.len(), 1);
+
+        let characters = NodeFilter {
+            object_types: vec!["character".to_string()],
+            ..Default::default()
+        };
+        let data = graph
+            .get_graph_data(&GraphDataRequest::filter(characters).with_budget(2, 10))
+            .unwrap();
+        assert_eq!(data.matched_nodes, 3);
+        assert_eq!(data.objects[0].id, frodo);
+        assert_eq!(data.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_relevant_neighborhood_follows_heavy_edges() {
+        let (graph, _tmp) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (focus, weak, strong, far) = (add("Focus"), add("Weak"), add("Strong"), add("Far"));
+        let connect = |from, to, weight| {
+            graph
+                .create_relationship(
+                    CreateRelationshipRequest::new(from, to, "knows").with_weight(weight),
+                )
+                .unwrap();
+        };
+        connect(focus, weak, 0.2);
+        connect(focus, strong, 0.9);
+        connect(strong, far, 0.8);
+
+        // The strong chain is followed before the weak neighbour.
+        let data = graph
+            .get_relevant_neighborhood(focus, NeighborhoodBudget::new(3, 10))
+            .unwrap();
+        let ids: Vec<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![focus, strong, far]);
+        assert_eq!(data.edges.len(), 2);
+        assert!(data.truncated());
+
+        // The edge budget stops expansion too.
+        let data = graph
+            .get_relevant_neighborhood(focus, NeighborhoodBudget::new(10, 1))
+            .unwrap();
+        assert_eq!(data.objects.len(), 2);
+        assert_eq!(data.edges.len(), 1);
+
+        let data = graph
+            .get_graph_data(&GraphDataRequest::relevant(focus))
+            .unwrap();
+        assert_eq!(data.objects.len(), 4);
+        assert!(!data.truncated());
+    }
+
+    #[test]
+    fn test_aggregation_by_type_and_expand() {
+        let (graph, _tmp) = create_test_graph();
+        let frodo = ObjectBuilder::character("Frodo".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let sam = ObjectBuilder::character("Sam".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let shire = ObjectBuilder::location("Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let bree = ObjectBuilder::location("Bree".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(frodo, sam, "knows").unwrap();
+        graph
+            .connect_objects_str(frodo, shire, "located_in")
+            .unwrap();
+        graph.connect_objects_str(sam, shire, "located_in").unwrap();
+        graph
+            .connect_objects_str(shire, bree, "related_to")
+            .unwrap();
+
+        let request = GraphDataRequest::new(GraphScope::All).aggregated(Aggregation::Type);
+        let data = graph.get_graph_data(&request).unwrap();
+        assert!(data.objects.is_empty());
+        assert_eq!(data.clusters.len(), 2);
+        let characters = data
+            .clusters
+            .iter()
+            .find(|c| c.key == "type:character")
+            .unwrap();
+        assert_eq!((characters.size, characters.internal_edges), (2, 1));
+        assert_eq!(data.cluster_edges.len(), 1);
+        assert_eq!(data.cluster_edges[0].count, 2);
+        assert_eq!(data.styles.len(), 2);
+        assert_eq!(data.styles["character"], graph.type_style("character"));
+
+        // Expanding the locations shows them as nodes linked to the
+        // character cluster.
+        let data = graph
+            .get_graph_data(&request.expanding("type:location"))
+            .unwrap();
+        assert_eq!(data.clusters.len(), 1);
+        assert_eq!(data.objects.len(), 2);
+        assert_eq!(data.edges.len(), 1);
+        let to_shire = data
+            .cluster_edges
+            .iter()
+            .find(|e| e.from == GraphNodeRef::Object(shire) || e.to == GraphNodeRef::Object(shire))
+            .unwrap();
+        assert_eq!(to_shire.count, 2);
+    }
+
+    #[test]
+    fn test_community_aggregation_separates_components() {
+        let (graph, _tmp) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (a, b, c) = (add("A"), add("B"), add("C"));
+        let (x, y) = (add("X"), add("Y"));
+        graph.connect_objects_str(a, b, "knows").unwrap();
+        graph.connect_objects_str(b, c, "knows").unwrap();
+        graph.connect_objects_str(c, a, "knows").unwrap();
+        graph.connect_objects_str(x, y, "knows").unwrap();
+
+        let data = graph
+            .get_graph_data(
+                &GraphDataRequest::new(GraphScope::All).aggregated(Aggregation::Community),
+            )
+            .unwrap();
+        assert_eq!(data.clusters.len(), 2);
+        assert_eq!(data.clusters[0].key, "community:1");
+        assert_eq!(
+            (data.clusters[0].size, data.clusters[0].internal_edges),
+            (3, 3)
+        );
+        assert_eq!(data.clusters[1].size, 2);
+        assert!(data.cluster_edges.is_empty());
+    }
+}
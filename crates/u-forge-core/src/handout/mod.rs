@@ -0,0 +1,488 @@
+//! Player handouts — item cards, NPC dossiers, and location briefs as PDF.
+//!
+//! [`KnowledgeGraph::export_handouts`] renders one handout per selected
+//! object (an object whose content overflows continues on further pages),
+//! choosing a [`HandoutLayout`] by [`HandoutKind`].  Only what players may
+//! see is printed:
+//!
+//! - objects whose `visibility` property is `"gm"` and
+//!   [`Lifecycle::Draft`](crate::types::Lifecycle::Draft) objects are
+//!   withheld entirely;
+//! - properties hidden by the rules in [`crate::visibility`] — schema
+//!   metadata, the object's own `_gm_properties` list, the always-private
+//!   [`GM_ONLY_PROPERTIES`], and internal `_`-prefixed properties — are left
+//!   out;
+//! - relationships are listed only when the other object is visible too.
+//!
+//! Text chunks are never printed: they hold notes and imports rather than
+//! player-facing prose.
+//!
+//! Styling lives in a [`HandoutStyle`] — page size, fonts, colours, and one
+//! [`HandoutLayout`] per kind — which serialises to JSON so communities can
+//! share styles as files.  Layout text fields are templates where
+//! `{{name}}`, `{{type}}`, and `{{property.<key>}}` are substituted.
+
+mod pdf;
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Result;
+use crate::progress::{Progress, ProgressSink};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+use pdf::{PageGeometry, PdfWriter};
+pub use pdf::{PdfFont, Rgb};
+pub use crate::visibility::{GM_ONLY_PROPERTIES, VISIBILITY_KEY};
+
+// ── Templates ─────────────────────────────────────────────────────────────────
+
+/// Which layout a handout uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoutKind {
+    ItemCard,
+    NpcDossier,
+    LocationBrief,
+    Generic,
+}
+
+impl HandoutKind {
+    /// Kind used for objects of `object_type`.
+    pub fn for_type(object_type: &str) -> Self {
+        match object_type {
+            "item" | "artifact" | "currency" | "inventory" | "transportation" => Self::ItemCard,
+            "character" | "npc" | "player_character" => Self::NpcDossier,
+            "location" => Self::LocationBrief,
+            _ => Self::Generic,
+        }
+    }
+}
+
+/// What one kind of handout shows, top to bottom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandoutLayout {
+    /// Title template.
+    pub title: String,
+    /// Line under the title; empty for none.
+    pub subtitle: String,
+    pub show_description: bool,
+    /// Properties to print, in order.  Empty prints every visible property
+    /// in name order.
+    pub properties: Vec<String>,
+    /// Heading for the visible relationships; empty hides them.
+    pub relationships_heading: String,
+    /// Template printed at the end of the handout; empty for none.
+    pub footer: String,
+}
+
+impl Default for HandoutLayout {
+    fn default() -> Self {
+        Self {
+            title: "{{name}}".to_string(),
+            subtitle: "{{type}}".to_string(),
+            show_description: true,
+            properties: Vec::new(),
+            relationships_heading: "Connections".to_string(),
+            footer: String::new(),
+        }
+    }
+}
+
+/// Page setup, typography, and per-kind layouts for a set of handouts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandoutStyle {
+    pub name: String,
+    /// Page width in points (1/72 inch).
+    pub page_width: f32,
+    pub page_height: f32,
+    pub margin: f32,
+    pub title_font: PdfFont,
+    pub title_size: f32,
+    pub heading_font: PdfFont,
+    pub body_font: PdfFont,
+    pub body_size: f32,
+    /// Colour of the band above the title, the title, headings, and rules.
+    pub accent: Rgb,
+    pub text_color: Rgb,
+    /// Colour the band above the title with the object type's display
+    /// colour (see [`crate::styles`]) instead of `accent`.
+    pub type_colored_band: bool,
+    /// Layouts by kind; a missing kind uses [`HandoutKind::Generic`], then
+    /// [`HandoutLayout::default`].
+    pub layouts: HashMap<HandoutKind, HandoutLayout>,
+}
+
+impl Default for HandoutStyle {
+    /// "Classic": A5 pages, serif body, dark red accent.
+    fn default() -> Self {
+        let layout = |title: &str, subtitle: &str, heading: &str| HandoutLayout {
+            title: title.to_string(),
+            subtitle: subtitle.to_string(),
+            relationships_heading: heading.to_string(),
+            ..Default::default()
+        };
+        Self {
+            name: "classic".to_string(),
+            page_width: 419.5,
+            page_height: 595.3,
+            margin: 36.0,
+            title_font: PdfFont::TimesBold,
+            title_size: 20.0,
+            heading_font: PdfFont::HelveticaBold,
+            body_font: PdfFont::Times,
+            body_size: 11.0,
+            accent: [0.45, 0.08, 0.08],
+            text_color: [0.1, 0.1, 0.1],
+            type_colored_band: false,
+            layouts: HashMap::from([
+                (HandoutKind::ItemCard, layout("{{name}}", "Item", "")),
+                (
+                    HandoutKind::NpcDossier,
+                    layout("Dossier: {{name}}", "{{type}}", "Known associates"),
+                ),
+                (
+                    HandoutKind::LocationBrief,
+                    layout("{{name}}", "Location brief", "Nearby"),
+                ),
+                (HandoutKind::Generic, HandoutLayout::default()),
+            ]),
+        }
+    }
+}
+
+impl HandoutStyle {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json).context("Failed to parse handout style")?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self).context("Failed to serialize handout style")?)
+    }
+
+    fn layout(&self, kind: HandoutKind) -> HandoutLayout {
+        self.layouts
+            .get(&kind)
+            .or_else(|| self.layouts.get(&HandoutKind::Generic))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Fill `{{name}}`, `{{type}}`, and `{{property.<key>}}` placeholders from
+/// `object`.  Unknown placeholders become empty.
+pub fn render_template(template: &str, object: &ObjectMetadata) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let key = rest[start + 2..start + end].trim();
+        match key {
+            "name" => out.push_str(&object.name),
+            "type" => out.push_str(&object.object_type),
+            _ => {
+                if let Some(value) = key
+                    .strip_prefix("property.")
+                    .and_then(|k| object.get_json_property(k))
+                {
+                    out.push_str(&display_value(value));
+                }
+            }
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ── Export ────────────────────────────────────────────────────────────────────
+
+/// Result of [`KnowledgeGraph::export_handouts`].
+#[derive(Debug, Clone)]
+pub struct HandoutExport {
+    pub pdf: Vec<u8>,
+    /// Objects printed, in order.
+    pub included: Vec<ObjectId>,
+    /// Objects skipped because players may not see them (or they no longer
+    /// exist).
+    pub withheld: Vec<ObjectId>,
+}
+
+impl KnowledgeGraph {
+    /// Render the player-visible objects among `ids` as one PDF, one handout
+    /// per object in the given order.
+    ///
+    /// Reports stage `"handouts"` to `progress` once per object.  Cancelling
+    /// fails with [`UForgeError::Cancelled`](crate::UForgeError::Cancelled)
+    /// and discards the partial PDF.
+    pub fn export_handouts(
+        &self,
+        ids: &[ObjectId],
+        style: &HandoutStyle,
+        progress: &dyn ProgressSink,
+    ) -> Result<HandoutExport> {
+        let mut pdf = PdfWriter::new(PageGeometry {
+            width: style.page_width,
+            height: style.page_height,
+            margin: style.margin,
+        });
+        let mut included = Vec::new();
+        let mut withheld = Vec::new();
+        for (done, &id) in ids.iter().enumerate() {
+            progress.check_cancelled()?;
+            progress.report(&Progress::new("handouts", done, Some(ids.len())));
+            match self.get_object(id)? {
+                Some(object) if self.is_player_visible(&object) => {
+                    self.render_handout(&mut pdf, &object, style)?;
+                    included.push(id);
+                }
+                _ => withheld.push(id),
+            }
+        }
+        progress.report(&Progress::new("handouts", ids.len(), Some(ids.len())));
+        Ok(HandoutExport {
+            pdf: pdf.finish(),
+            included,
+            withheld,
+        })
+    }
+
+    fn render_handout(
+        &self,
+        pdf: &mut PdfWriter,
+        object: &ObjectMetadata,
+        style: &HandoutStyle,
+    ) -> Result<()> {
+        let layout = style.layout(HandoutKind::for_type(&object.object_type));
+        let body = |pdf: &mut PdfWriter, text: &str, indent: f32| {
+            pdf.paragraph(
+                text,
+                style.body_font,
+                style.body_size,
+                style.text_color,
+                indent,
+            )
+        };
+        let heading = |pdf: &mut PdfWriter, text: &str| {
+            pdf.space(style.body_size * 0.6);
+            pdf.paragraph(text, style.heading_font, style.body_size, style.accent, 0.0);
+            pdf.rule(style.accent);
+        };
+
+        pdf.new_page();
+        let band = if style.type_colored_band {
+            self.object_style(object).color.map(|c| f32::from(c) / 255.0)
+        } else {
+            style.accent
+        };
+        pdf.band(band, style.title_size * 0.4);
+        pdf.paragraph(
+            &render_template(&layout.title, object),
+            style.title_font,
+            style.title_size,
+            style.accent,
+            0.0,
+        );
+        let subtitle = render_template(&layout.subtitle, object);
+        if !subtitle.is_empty() {
+            pdf.paragraph(
+                &subtitle,
+                PdfFont::TimesItalic,
+                style.body_size,
+                style.text_color,
+                0.0,
+            );
+        }
+        pdf.rule(style.accent);
+
+        let properties = self.player_visible_properties(object);
+        let description = properties
+            .iter()
+            .find(|(key, _)| key == "description")
+            .map(|(_, value)| display_value(value));
+        if layout.show_description {
+            if let Some(description) = description.filter(|d| !d.trim().is_empty()) {
+                pdf.space(style.body_size * 0.4);
+                body(pdf, &description, 0.0);
+            }
+        }
+
+        let listed: Vec<&(String, Value)> = if layout.properties.is_empty() {
+            properties
+                .iter()
+                .filter(|(key, _)| key != "description")
+                .collect()
+        } else {
+            layout
+                .properties
+                .iter()
+                .filter_map(|wanted| properties.iter().find(|(key, _)| key == wanted))
+                .collect()
+        };
+        let listed: Vec<_> = listed
+            .into_iter()
+            .map(|(key, value)| (key, display_value(value)))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        if !listed.is_empty() {
+            heading(pdf, "Details");
+            for (key, value) in listed {
+                body(pdf, &format!("{}: {value}", humanize(key)), 0.0);
+            }
+        }
+
+        if !layout.relationships_heading.is_empty() {
+            let mut lines = Vec::new();
+            for edge in self.get_relationships(object.id)? {
+                let other_id = if edge.from == object.id {
+                    edge.to
+                } else {
+                    edge.from
+                };
+                let Some(other) = self.get_object(other_id)? else {
+                    continue;
+                };
+                if self.is_player_visible(&other) {
+                    lines.push(format!(
+                        "{} — {}",
+                        humanize(edge.edge_type.as_str()),
+                        other.name
+                    ));
+                }
+            }
+            if !lines.is_empty() {
+                heading(pdf, &layout.relationships_heading);
+                for line in lines {
+                    body(pdf, &format!("• {line}"), 6.0);
+                }
+            }
+        }
+
+        let footer = render_template(&layout.footer, object);
+        if !footer.is_empty() {
+            pdf.space(style.body_size);
+            pdf.paragraph(
+                &footer,
+                PdfFont::TimesItalic,
+                style.body_size * 0.85,
+                style.text_color,
+                0.0,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Property value as handout text: strings as-is, arrays comma-joined.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// `"current_location"` / `"currentLocation"` → `"Current location"`.
+fn humanize(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if c == '_' {
+            out.push(' ');
+        } else if c.is_uppercase() && i > 0 {
+            out.push(' ');
+            out.extend(c.to_lowercase());
+        } else if i == 0 {
+            out.extend(c.to_uppercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_handouts_respect_visibility() {
+        let (graph, _temp_dir) = create_test_graph();
+        let hideout = ObjectBuilder::location("Cragmaw Hideout".to_string())
+            .with_property(VISIBILITY_KEY.to_string(), "gm".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let town = ObjectBuilder::location("Phandalin".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let sildar = ObjectBuilder::custom("npc".to_string(), "Sildar Hallwinter".to_string())
+            .with_description("A veteran of the Lords' Alliance.".to_string())
+            .with_property("faction".to_string(), "Lords' Alliance".to_string())
+            .with_json_property(
+                "secrets".to_string(),
+                serde_json::json!(["Knows the hideout's back door"]),
+            )
+            .located_in(town)
+            .with_relationship("escaped_from", hideout)
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let export = graph
+            .export_handouts(
+                &[sildar, hideout, town],
+                &HandoutStyle::default(),
+                &crate::progress::NoProgress,
+            )
+            .unwrap();
+        assert_eq!(export.included, vec![sildar, town]);
+        assert_eq!(export.withheld, vec![hideout]);
+
+        let text = String::from_utf8_lossy(&export.pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("(Dossier: Sildar Hallwinter) Tj"));
+        assert!(text.contains("(Faction: Lords' Alliance) Tj"));
+        assert!(text.contains("Located in"));
+        assert!(!text.contains("back door"));
+        assert!(!text.contains("Cragmaw"));
+    }
+
+    #[test]
+    fn test_style_json_and_templates() {
+        let mut style = HandoutStyle::default();
+        style
+            .layouts
+            .get_mut(&HandoutKind::ItemCard)
+            .unwrap()
+            .footer = "Value: {{property.value}} {{missing}}".to_string();
+        let parsed = HandoutStyle::from_json(&style.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, style);
+        // Partial styles fill the rest from the defaults.
+        let partial = HandoutStyle::from_json(r#"{"name": "minimal", "body_size": 9}"#).unwrap();
+        assert_eq!(partial.body_size, 9.0);
+        assert_eq!(partial.margin, style.margin);
+
+        let sword = ObjectMetadata::new("artifact".to_string(), "Talon".to_string())
+            .with_property("value".to_string(), "500 gp".to_string());
+        let footer = &parsed
+            .layout(HandoutKind::for_type(&sword.object_type))
+            .footer;
+        assert_eq!(render_template(footer, &sword), "Value: 500 gp ");
+        assert_eq!(humanize("currentLocation"), "Current location");
+    }
+}
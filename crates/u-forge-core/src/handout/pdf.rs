@@ -0,0 +1,339 @@
+//! Minimal PDF writer for handouts.
+//!
+//! Produces uncompressed PDF 1.4 using only the standard Type 1 fonts, which
+//! every viewer ships, so no font files or PDF crates are needed.  Text is
+//! encoded as WinAnsi; characters outside it print as `?`.  Line widths use
+//! an average glyph width per font family, which is close enough for
+//! wrapping prose.
+
+use serde::{Deserialize, Serialize};
+
+/// One of the standard Type 1 fonts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfFont {
+    Helvetica,
+    HelveticaBold,
+    Times,
+    TimesBold,
+    TimesItalic,
+    Courier,
+    CourierBold,
+}
+
+impl PdfFont {
+    const ALL: [PdfFont; 7] = [
+        PdfFont::Helvetica,
+        PdfFont::HelveticaBold,
+        PdfFont::Times,
+        PdfFont::TimesBold,
+        PdfFont::TimesItalic,
+        PdfFont::Courier,
+        PdfFont::CourierBold,
+    ];
+
+    fn base_font(self) -> &'static str {
+        match self {
+            PdfFont::Helvetica => "Helvetica",
+            PdfFont::HelveticaBold => "Helvetica-Bold",
+            PdfFont::Times => "Times-Roman",
+            PdfFont::TimesBold => "Times-Bold",
+            PdfFont::TimesItalic => "Times-Italic",
+            PdfFont::Courier => "Courier",
+            PdfFont::CourierBold => "Courier-Bold",
+        }
+    }
+
+    /// Resource name used in content streams (`/F1` …).
+    fn resource(self) -> usize {
+        Self::ALL.iter().position(|f| *f == self).unwrap_or(0) + 1
+    }
+
+    /// Average glyph advance as a fraction of the font size.
+    fn average_width(self) -> f32 {
+        match self {
+            PdfFont::Helvetica | PdfFont::HelveticaBold => 0.52,
+            PdfFont::Times | PdfFont::TimesBold | PdfFont::TimesItalic => 0.47,
+            PdfFont::Courier | PdfFont::CourierBold => 0.6,
+        }
+    }
+}
+
+/// RGB colour with components in `0.0..=1.0`.
+pub type Rgb = [f32; 3];
+
+/// Page dimensions and margin, in points (1/72 inch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct PageGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+}
+
+/// Builds a multi-page document top to bottom, starting new pages as the
+/// cursor runs out of room.
+pub(super) struct PdfWriter {
+    geometry: PageGeometry,
+    pages: Vec<Vec<u8>>,
+    /// Baseline of the next line, measured from the bottom of the page.
+    y: f32,
+}
+
+impl PdfWriter {
+    pub fn new(geometry: PageGeometry) -> Self {
+        Self {
+            geometry,
+            pages: Vec::new(),
+            y: 0.0,
+        }
+    }
+
+    /// Start a new page with the cursor at the top margin.
+    pub fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = self.geometry.height - self.geometry.margin;
+    }
+
+    fn content(&mut self) -> &mut Vec<u8> {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+        self.pages.last_mut().expect("page exists")
+    }
+
+    fn text_width(&self) -> f32 {
+        self.geometry.width - 2.0 * self.geometry.margin
+    }
+
+    /// Make room for `height` points, breaking the page if needed.
+    fn reserve(&mut self, height: f32) {
+        if self.pages.is_empty() || self.y - height < self.geometry.margin {
+            self.new_page();
+        }
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// A filled band across the text width, `height` points tall, with the
+    /// cursor moved below it.
+    pub fn band(&mut self, color: Rgb, height: f32) {
+        self.reserve(height);
+        let (x, w) = (self.geometry.margin, self.text_width());
+        let y = self.y - height;
+        let op = format!(
+            "q {} {} {} rg {x:.2} {y:.2} {w:.2} {height:.2} re f Q\n",
+            color[0], color[1], color[2]
+        );
+        self.content().extend_from_slice(op.as_bytes());
+        self.y = y;
+    }
+
+    /// A horizontal rule across the text width.
+    pub fn rule(&mut self, color: Rgb) {
+        self.reserve(6.0);
+        let (x1, x2) = (
+            self.geometry.margin,
+            self.geometry.width - self.geometry.margin,
+        );
+        let y = self.y - 3.0;
+        let op = format!(
+            "q {} {} {} RG 0.75 w {x1:.2} {y:.2} m {x2:.2} {y:.2} l S Q\n",
+            color[0], color[1], color[2]
+        );
+        self.content().extend_from_slice(op.as_bytes());
+        self.y -= 6.0;
+    }
+
+    /// Write `text` word-wrapped to the text width, `indent` points in from
+    /// the left margin.  Explicit newlines start new lines.
+    pub fn paragraph(&mut self, text: &str, font: PdfFont, size: f32, color: Rgb, indent: f32) {
+        let max_chars =
+            ((self.text_width() - indent) / (size * font.average_width())).max(1.0) as usize;
+        let leading = size * 1.3;
+        for line in text.lines().flat_map(|l| wrap(l, max_chars)) {
+            self.reserve(leading);
+            self.y -= leading;
+            let x = self.geometry.margin + indent;
+            let y = self.y;
+            let mut op = format!(
+                "BT /F{} {size:.1} Tf {} {} {} rg {x:.2} {y:.2} Td (",
+                font.resource(),
+                color[0],
+                color[1],
+                color[2]
+            )
+            .into_bytes();
+            op.extend(encode_text(&line));
+            op.extend_from_slice(b") Tj ET\n");
+            self.content().extend(op);
+        }
+    }
+
+    /// Serialise all pages into a PDF file.
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+        let PageGeometry { width, height, .. } = self.geometry;
+        let font_count = PdfFont::ALL.len();
+        // Objects: 1 catalog, 2 pages, fonts, then (page, content) pairs.
+        let first_page = 3 + font_count;
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| first_page + 2 * i).collect();
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{id} 0 R")).collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_ids.len()
+            )
+            .into_bytes(),
+        );
+        for font in PdfFont::ALL {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font.base_font()
+                )
+                .into_bytes(),
+            );
+        }
+        let font_resources: Vec<String> = (0..font_count)
+            .map(|i| format!("/F{} {} 0 R", i + 1, i + 3))
+            .collect();
+        for (page_id, content) in page_ids.iter().zip(&self.pages) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width:.2} {height:.2}] \
+                     /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                    font_resources.join(" "),
+                    page_id + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            out.extend(format!("{offset:010} 00000 n \n").into_bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .into_bytes(),
+        );
+        out
+    }
+}
+
+/// Greedy word wrap to at most `max_chars` characters per line.  Words
+/// longer than a line are split.
+fn wrap(line: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        let word_len = word.len();
+        let word: String = word.into_iter().collect();
+        let needed = current.chars().count() + usize::from(!current.is_empty()) + word_len;
+        if needed > max_chars && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Encode `text` as a WinAnsi PDF string body, escaping delimiters.
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let byte = match c {
+            '\\' | '(' | ')' => {
+                out.push(b'\\');
+                c as u8
+            }
+            '\u{20}'..='\u{7e}' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '…' => 0x85,
+            _ => b'?',
+        };
+        out.push(byte);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_encode() {
+        assert_eq!(
+            wrap("the quick brown fox", 9),
+            vec!["the quick", "brown fox"]
+        );
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(encode_text("a(b)—é✓"), b"a\\(b\\)\x97\xe9?".to_vec());
+    }
+
+    #[test]
+    fn test_writer_produces_xref_for_every_object() {
+        let mut pdf = PdfWriter::new(PageGeometry {
+            width: 200.0,
+            height: 100.0,
+            margin: 10.0,
+        });
+        for _ in 0..20 {
+            pdf.paragraph("Line", PdfFont::Helvetica, 12.0, [0.0; 3], 0.0);
+        }
+        let bytes = pdf.finish();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        let pages = text.matches("/Type /Page ").count();
+        assert!(pages > 1, "20 lines should not fit on one short page");
+        // catalog + pages + 7 fonts + 2 objects per page, plus the free entry.
+        assert!(text.contains(&format!("xref\n0 {}\n", 2 + 7 + 2 * pages + 1)));
+    }
+}
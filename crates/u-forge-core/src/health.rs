@@ -0,0 +1,244 @@
+//! Structured health/status report for the desktop app.
+//!
+//! [`KnowledgeGraph::health`] gathers everything a "search isn't working"
+//! report needs in one call: whether the database answers, how far the FTS
+//! and vector indexes lag behind the stored chunks, which embedding workers
+//! the [`InferenceQueue`] has, its pending job counts, and the most recent
+//! errors swallowed by search and embedding fallbacks.
+//!
+//! Those errors are kept in a small process-wide ring buffer
+//! ([`record_error`] / [`recent_errors`]) because they surface in worker
+//! tasks and fallback paths that have no graph handle to report to.
+
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::graph::IndexHealth;
+use crate::queue::{InferenceQueue, QueueStats};
+use crate::KnowledgeGraph;
+
+/// Number of errors kept by [`record_error`]; older entries are dropped.
+pub const RECENT_ERROR_CAPACITY: usize = 32;
+
+static RECENT_ERRORS: LazyLock<Mutex<VecDeque<RecentError>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(RECENT_ERROR_CAPACITY)));
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Overall verdict of a [`HealthReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Everything checked is working.
+    Ok,
+    /// The graph works, but some search paths are incomplete or failing —
+    /// see [`HealthReport::issues`].
+    Degraded,
+    /// The database cannot be queried.
+    Unavailable,
+}
+
+/// An error recorded by [`record_error`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    /// Subsystem that hit the error, e.g. `"search"` or `"embedding"`.
+    pub component: String,
+    pub message: String,
+}
+
+/// Database connection state.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageHealth {
+    pub open: bool,
+    /// `None` for an in-memory database.
+    pub path: Option<String>,
+    /// Why the database could not be queried, when `open` is `false`.
+    pub error: Option<String>,
+}
+
+/// Embedding provider availability, from the [`InferenceQueue`] passed to
+/// [`KnowledgeGraph::health`].
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingHealth {
+    pub available: bool,
+    /// Registered embedding workers (`"<recipe>/<model>"`).
+    pub workers: Vec<String>,
+    pub reranking: bool,
+    pub text_generation: bool,
+}
+
+/// Snapshot returned by [`KnowledgeGraph::health`].
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checked_at: DateTime<Utc>,
+    pub storage: StorageHealth,
+    /// `None` when the database could not be queried.
+    pub index: Option<IndexHealth>,
+    /// `None` when no queue was passed.
+    pub embeddings: Option<EmbeddingHealth>,
+    /// `None` when no queue was passed.
+    pub queues: Option<QueueStats>,
+    /// Most recent first.
+    pub recent_errors: Vec<RecentError>,
+    /// One line per problem that made the status less than [`HealthStatus::Ok`].
+    pub issues: Vec<String>,
+}
+
+// ── Error ring buffer ─────────────────────────────────────────────────────────
+
+/// Remember an error for [`HealthReport::recent_errors`].
+///
+/// Meant for failures that are logged and then recovered from (a search
+/// falling back to FTS, a chunk left unembedded), which otherwise only show
+/// up in the log.
+pub fn record_error(component: &str, message: impl Into<String>) {
+    let mut errors = RECENT_ERRORS.lock();
+    if errors.len() == RECENT_ERROR_CAPACITY {
+        errors.pop_front();
+    }
+    errors.push_back(RecentError {
+        at: Utc::now(),
+        component: component.to_string(),
+        message: message.into(),
+    });
+}
+
+/// Errors recorded by [`record_error`], most recent first.
+pub fn recent_errors() -> Vec<RecentError> {
+    RECENT_ERRORS.lock().iter().rev().cloned().collect()
+}
+
+/// Forget all recorded errors (e.g. after the user has read them).
+pub fn clear_recent_errors() {
+    RECENT_ERRORS.lock().clear();
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Check storage, indexes, and (when given) the inference queue.
+    ///
+    /// Never fails: problems are reported in the returned
+    /// [`HealthReport`] rather than as an error.  Reading index state scans
+    /// the FTS index, so call this on demand rather than every frame.
+    pub fn health(&self, queue: Option<&InferenceQueue>) -> HealthReport {
+        let mut issues = Vec::new();
+
+        let ping = self.storage.ping();
+        let storage = StorageHealth {
+            open: ping.is_ok(),
+            path: self.storage.database_path(),
+            error: ping.err().map(|e| format!("{e:#}")),
+        };
+        let index = if storage.open {
+            match self.storage.index_health() {
+                Ok(index) => Some(index),
+                Err(e) => {
+                    issues.push(format!("Could not read index state: {e:#}"));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(index) = &index {
+            if !index.fts_consistent {
+                issues.push("Full-text index is out of sync with stored chunks".to_string());
+            }
+            if index.unembedded_chunks > 0 {
+                issues.push(format!(
+                    "{} of {} chunks have no embedding; semantic search will miss them",
+                    index.unembedded_chunks, index.chunk_count
+                ));
+            }
+            if index.unembedded_profiles > 0 {
+                issues.push(format!(
+                    "{} object profiles have no current embedding",
+                    index.unembedded_profiles
+                ));
+            }
+        }
+
+        let embeddings = queue.map(|q| EmbeddingHealth {
+            available: q.has_embedding(),
+            workers: q.embedding_worker_names(),
+            reranking: q.has_reranking(),
+            text_generation: q.has_text_generation(),
+        });
+        if embeddings.as_ref().is_some_and(|e| !e.available) {
+            issues.push("No embedding provider; search is keyword-only".to_string());
+        }
+
+        let status = if !storage.open {
+            HealthStatus::Unavailable
+        } else if issues.is_empty() {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Degraded
+        };
+        if let Some(error) = &storage.error {
+            issues.insert(0, format!("Database unavailable: {error}"));
+        }
+
+        HealthReport {
+            status,
+            checked_at: Utc::now(),
+            storage,
+            index,
+            embeddings,
+            queues: queue.map(InferenceQueue::stats),
+            recent_errors: recent_errors(),
+            issues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_health_reports_unembedded_chunks() {
+        let (graph, _temp_dir) = create_test_graph();
+
+        let report = graph.health(None);
+        assert_eq!(report.status, HealthStatus::Ok);
+        assert!(report.storage.open);
+        assert!(report.storage.path.is_some());
+        assert!(report.embeddings.is_none());
+
+        let id = ObjectBuilder::character("Gandalf".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .add_text_chunk(id, "A wizard.".to_string(), crate::ChunkType::Description)
+            .unwrap();
+        let report = graph.health(None);
+        assert_eq!(report.status, HealthStatus::Degraded);
+        let index = report.index.unwrap();
+        assert!(index.fts_consistent);
+        assert_eq!(index.chunk_count, 1);
+        assert_eq!(index.unembedded_chunks, 1);
+        assert!(report.issues.iter().any(|i| i.contains("no embedding")));
+    }
+
+    #[test]
+    fn test_recent_errors_ring_buffer() {
+        for i in 0..RECENT_ERROR_CAPACITY + 5 {
+            record_error("health-test", format!("failure {i}"));
+        }
+        let errors = recent_errors();
+        assert!(errors.len() <= RECENT_ERROR_CAPACITY);
+        let last = format!("failure {}", RECENT_ERROR_CAPACITY + 4);
+        assert!(errors.iter().any(|e| e.message == last));
+        assert!(!errors.iter().any(|e| e.message == "failure 0"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Automation hooks — run a script or call a URL when the world changes.
+//!
+//! An [`AutomationHook`] names the [`HookTrigger`]s it reacts to and a
+//! [`HookAction`]:
+//!
+//! - **Script** — a local program started with the [`HookPayload`] JSON on
+//!   stdin and the trigger in `UFORGE_EVENT`.  Useful for anything the
+//!   payload needs reshaping for, e.g. posting a session recap to Discord.
+//! - **Http** — a `POST` of the payload JSON to a URL, with optional extra
+//!   headers (an auth token, say).
+//!
+//! Hooks are defined in the local `u-forge.toml` (`[[hooks]]` tables, see
+//! [`AppConfig::hooks`](crate::config::AppConfig::hooks)), never in the
+//! project: a project shared by someone else cannot make this machine run
+//! programs or send requests.  [`HookRunner::spawn`] validates them,
+//! subscribes to [`GraphEvent`]s and fires every enabled hook whose triggers
+//! match, each in its own task so a slow endpoint does not hold back the
+//! next event.  A failing hook is logged and otherwise ignored: the change
+//! that triggered it is saved already.
+//!
+//! ```toml
+//! [[hooks]]
+//! name = "Discord recap"
+//! triggers = ["session_ended"]
+//! action = { kind = "script", program = "./scripts/discord-recap.sh" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::error::UForgeError;
+use crate::events::GraphEvent;
+use crate::KnowledgeGraph;
+
+/// How long a script or HTTP call may take before it counts as failed.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variable carrying the trigger name to scripts.
+pub const HOOK_EVENT_ENV: &str = "UFORGE_EVENT";
+
+/// Which [`GraphEvent`]s a hook reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookTrigger {
+    ObjectCreated,
+    QuestCompleted,
+    SessionEnded,
+    ClockChanged,
+    ScheduledEventFired,
+    WorldTimeAdvanced,
+}
+
+impl HookTrigger {
+    /// The trigger `event` fires.
+    pub fn of(event: &GraphEvent) -> Self {
+        match event {
+            GraphEvent::ObjectCreated { .. } => Self::ObjectCreated,
+            GraphEvent::QuestCompleted { .. } => Self::QuestCompleted,
+            GraphEvent::SessionEnded { .. } => Self::SessionEnded,
+            GraphEvent::ClockChanged(_) => Self::ClockChanged,
+            GraphEvent::ScheduledEventFired(_) => Self::ScheduledEventFired,
+            GraphEvent::WorldTimeAdvanced { .. } => Self::WorldTimeAdvanced,
+        }
+    }
+
+    /// The snake_case name used in config and `UFORGE_EVENT`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ObjectCreated => "object_created",
+            Self::QuestCompleted => "quest_completed",
+            Self::SessionEnded => "session_ended",
+            Self::ClockChanged => "clock_changed",
+            Self::ScheduledEventFired => "scheduled_event_fired",
+            Self::WorldTimeAdvanced => "world_time_advanced",
+        }
+    }
+}
+
+/// What a hook does when it fires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Run `program` with `args`, the payload on stdin.
+    Script {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// `POST` the payload to `url`.
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+/// A named reaction to graph events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutomationHook {
+    /// Names the hook in payloads and logs.
+    pub name: String,
+    pub triggers: Vec<HookTrigger>,
+    pub action: HookAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl AutomationHook {
+    pub fn new(
+        name: impl Into<String>,
+        triggers: impl IntoIterator<Item = HookTrigger>,
+        action: HookAction,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            triggers: triggers.into_iter().collect(),
+            action,
+            enabled: true,
+        }
+    }
+
+    /// Whether this hook fires for `event`.
+    pub fn matches(&self, event: &GraphEvent) -> bool {
+        self.enabled && self.triggers.contains(&HookTrigger::of(event))
+    }
+
+    /// Fails with [`UForgeError::ValidationFailed`] for a blank name, no
+    /// triggers, an empty program or a non-HTTP URL.
+    pub fn validate(&self) -> Result<()> {
+        let problem = if self.name.trim().is_empty() {
+            Some("Hook name cannot be blank".to_string())
+        } else if self.triggers.is_empty() {
+            Some(format!("Hook '{}' has no triggers", self.name))
+        } else {
+            match &self.action {
+                HookAction::Script { program, .. } if program.trim().is_empty() => {
+                    Some(format!("Hook '{}' has no program", self.name))
+                }
+                HookAction::Http { url, .. }
+                    if !(url.starts_with("http://") || url.starts_with("https://")) =>
+                {
+                    Some(format!("Hook '{}' needs an http(s) URL, got '{url}'", self.name))
+                }
+                _ => None,
+            }
+        };
+        match problem {
+            Some(problem) => Err(UForgeError::ValidationFailed(problem).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The JSON a hook receives.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookPayload<'a> {
+    pub hook: &'a str,
+    pub trigger: HookTrigger,
+    pub sent_at: DateTime<Utc>,
+    /// The [`GraphEvent`] itself, tagged by `kind`.
+    pub event: &'a GraphEvent,
+}
+
+/// Run `hook` once for `event`, waiting at most [`HOOK_TIMEOUT`].  Fails
+/// when the script exits non-zero or the endpoint answers with an error
+/// status.
+pub async fn fire_hook(hook: &AutomationHook, event: &GraphEvent) -> Result<()> {
+    let trigger = HookTrigger::of(event);
+    let payload = serde_json::to_vec(&HookPayload {
+        hook: &hook.name,
+        trigger,
+        sent_at: Utc::now(),
+        event,
+    })?;
+    let run = async {
+        match &hook.action {
+            HookAction::Script { program, args } => {
+                run_script(program, args, trigger, &payload).await
+            }
+            HookAction::Http { url, headers } => post_payload(url, headers, payload).await,
+        }
+    };
+    tokio::time::timeout(HOOK_TIMEOUT, run)
+        .await
+        .with_context(|| format!("Hook '{}' timed out", hook.name))?
+        .with_context(|| format!("Hook '{}' failed", hook.name))
+}
+
+async fn run_script(
+    program: &str,
+    args: &[String],
+    trigger: HookTrigger,
+    payload: &[u8],
+) -> Result<()> {
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .env(HOOK_EVENT_ENV, trigger.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start '{program}'"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A script that ignores its input may exit before reading it.
+        let _ = stdin.write_all(payload).await;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "'{program}' exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+async fn post_payload(
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    payload: Vec<u8>,
+) -> Result<()> {
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Background task firing hooks for a graph's events; stops when dropped.
+pub struct HookRunner {
+    task: JoinHandle<()>,
+}
+
+impl HookRunner {
+    /// Start firing `hooks` for `graph`'s events.  Must be called from
+    /// within a Tokio runtime.  Fails with [`UForgeError::ValidationFailed`]
+    /// if any hook is invalid, before anything runs.
+    pub fn spawn(graph: Arc<KnowledgeGraph>, hooks: Vec<AutomationHook>) -> Result<Self> {
+        for hook in &hooks {
+            hook.validate()?;
+        }
+        let hooks: Vec<AutomationHook> = hooks.into_iter().filter(|h| h.enabled).collect();
+        let mut events = graph.subscribe_events();
+        let task = tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "Automation hooks fell behind; events skipped");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                for hook in hooks.iter().filter(|h| h.matches(&event)) {
+                    let (hook, event) = (hook.clone(), event.clone());
+                    tokio::spawn(async move {
+                        if let Err(e) = fire_hook(&hook, &event).await {
+                            warn!(hook = %hook.name, "Automation hook failed: {e:#}");
+                        }
+                    });
+                }
+            }
+        });
+        Ok(Self { task })
+    }
+}
+
+impl Drop for HookRunner {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+
+    #[tokio::test]
+    async fn test_hooks_fire_on_graph_events() {
+        let (graph, temp_dir) = create_test_graph();
+        let graph = Arc::new(graph);
+        let out = temp_dir.path().join("recap.json");
+        let script = HookAction::Script {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), format!("cat > '{}'", out.display())],
+        };
+        let blank = AutomationHook::new(" ", [HookTrigger::SessionEnded], script.clone());
+        let err = HookRunner::spawn(graph.clone(), vec![blank]).err().unwrap();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::ValidationFailed
+        );
+
+        // Hooks come from the local config.
+        let config: crate::config::AppConfig = toml::from_str(
+            r#"
+            [[hooks]]
+            name = "Discord recap"
+            triggers = ["session_ended"]
+            action = { kind = "script", program = "sh" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.hooks.len(), 1);
+        let mut recap = config.hooks[0].clone();
+        recap.action = script;
+
+        let mut events = graph.subscribe_events();
+        let _runner = HookRunner::spawn(graph.clone(), vec![recap]).unwrap();
+        let session = graph
+            .add_object(ObjectMetadata::new("session".to_string(), "Session 4".to_string()))
+            .unwrap();
+        let mut quest = ObjectMetadata::new("quest".to_string(), "Find Cragmaw".to_string());
+        let quest_id = graph.add_object(quest.clone()).unwrap();
+        quest.set_property("status".to_string(), "Completed".to_string());
+        graph.update_object(quest.clone()).unwrap();
+        graph.update_object(quest).unwrap();
+        graph.end_session(session, Some("The party reached Phandalin.")).unwrap();
+
+        let triggers: Vec<HookTrigger> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|e| HookTrigger::of(&e))
+            .collect();
+        assert_eq!(
+            triggers,
+            [
+                HookTrigger::ObjectCreated,
+                HookTrigger::ObjectCreated,
+                HookTrigger::QuestCompleted,
+                HookTrigger::SessionEnded,
+            ]
+        );
+        assert!(graph.end_session(quest_id, None).is_err());
+
+        // Only the session hook fires; wait for its script to finish.
+        let mut payload = None;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            payload = std::fs::read_to_string(&out)
+                .ok()
+                .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+            if payload.is_some() {
+                break;
+            }
+        }
+        let payload = payload.expect("hook script did not run");
+        assert_eq!(payload["hook"], "Discord recap");
+        assert_eq!(payload["event"]["kind"], "session_ended");
+        assert_eq!(payload["event"]["summary"], "The party reached Phandalin.");
+    }
+}
@@ -15,7 +15,19 @@
 //! - `properties`   — typed JSON object; arrays stay arrays, strings stay strings
 //!
 //! Dedup: nodes are matched first by `_source_id`, then by `(nodetype, name)`.
-
+//! With [`DataIngestion::with_id_seed`], object ids are derived from the seed,
+//! type, and name ([`ObjectId::deterministic`]), and a node whose id already
+//! exists is updated in place instead of skipped.
+//!
+//! Type mapping: a node whose `nodetype` has a [`TypeMapping`] — from
+//! [`DataIngestion::with_type_mapping`] or stored in the `imported_schemas` /
+//! `default` schema — is rewritten to the mapped type and properties before
+//! any of the above, so data authored against another schema lands in this
+//! project's types.
+
+use crate::diagnostics::trace_operation;
+use crate::progress::{NoProgress, Progress, ProgressSink};
+use crate::schema::TypeMapping;
 use crate::types::*;
 use crate::KnowledgeGraph;
 use anyhow::{Context, Result};
@@ -48,13 +60,20 @@ pub enum JsonEntry {
 #[derive(Debug)]
 pub struct IngestionStats {
     pub objects_created: usize,
+    /// Objects overwritten because their deterministic id already existed.
+    pub objects_updated: usize,
     pub relationships_created: usize,
     pub parse_errors: usize,
+    /// Nodes rewritten through a [`TypeMapping`].
+    pub objects_mapped: usize,
 }
 
 pub struct DataIngestion<'a> {
     graph: &'a KnowledgeGraph,
     stats: IngestionStats,
+    progress: &'a dyn ProgressSink,
+    id_seed: Option<String>,
+    type_mappings: HashMap<String, TypeMapping>,
 }
 
 impl<'a> DataIngestion<'a> {
@@ -63,16 +82,47 @@ impl<'a> DataIngestion<'a> {
             graph,
             stats: IngestionStats {
                 objects_created: 0,
+                objects_updated: 0,
                 relationships_created: 0,
                 parse_errors: 0,
+                objects_mapped: 0,
             },
+            progress: &NoProgress,
+            id_seed: None,
+            type_mappings: HashMap::new(),
         }
     }
 
+    /// Derive object ids with [`ObjectId::deterministic`] from `seed` (e.g.
+    /// the project name) instead of generating random ones, so importing the
+    /// same file again updates the objects it created rather than
+    /// duplicating or skipping them.
+    pub fn with_id_seed(mut self, seed: impl Into<String>) -> Self {
+        self.id_seed = Some(seed.into());
+        self
+    }
+
+    /// Report stages `"objects"` and `"relationships"` to `progress`, and
+    /// stop with [`UForgeError::Cancelled`](crate::UForgeError::Cancelled)
+    /// when it is cancelled.
+    pub fn with_progress(mut self, progress: &'a dyn ProgressSink) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Map incoming `mapping.source_type` nodes with `mapping`, taking
+    /// precedence over any mapping stored in the project's schemas.
+    pub fn with_type_mapping(mut self, mapping: TypeMapping) -> Self {
+        self.type_mappings
+            .insert(mapping.source_type.clone(), mapping);
+        self
+    }
+
     /// Import JSONL data from a file into the knowledge graph.
     pub async fn import_json_data<P: AsRef<Path>>(&mut self, data_file: P) -> Result<()> {
         let data_file = data_file.as_ref();
         info!("Loading JSON data from: {:?}", data_file);
+        let mut op = trace_operation("ingest", "import_json_data");
 
         let file_content = fs::read_to_string(data_file)
             .with_context(|| format!("Failed to read file: {:?}", data_file))?;
@@ -112,10 +162,15 @@ impl<'a> DataIngestion<'a> {
             edges.len()
         );
 
+        self.load_schema_type_mappings().await;
+
         let mut name_to_id = HashMap::new();
         self.create_objects(nodes, &mut name_to_id).await?;
         self.create_relationships(edges, &name_to_id).await?;
 
+        op.set_items(
+            self.stats.objects_created + self.stats.objects_updated + self.stats.relationships_created,
+        );
         Ok(())
     }
 
@@ -123,6 +178,27 @@ impl<'a> DataIngestion<'a> {
         &self.stats
     }
 
+    /// Add the type mappings stored in the `imported_schemas` and `default`
+    /// schemas (in that order of precedence) without overriding explicit ones.
+    async fn load_schema_type_mappings(&mut self) {
+        let schema_manager = self.graph.get_schema_manager();
+        let Ok(schemas) = schema_manager.list_schemas() else {
+            return;
+        };
+        for schema_name in ["imported_schemas", "default"] {
+            if !schemas.contains(&schema_name.to_string()) {
+                continue;
+            }
+            if let Ok(schema) = schema_manager.load_schema(schema_name).await {
+                for (source_type, mapping) in &schema.type_mappings {
+                    self.type_mappings
+                        .entry(source_type.clone())
+                        .or_insert_with(|| mapping.clone());
+                }
+            }
+        }
+    }
+
     async fn create_objects(
         &mut self,
         nodes: Vec<JsonEntry>,
@@ -130,13 +206,24 @@ impl<'a> DataIngestion<'a> {
     ) -> Result<()> {
         info!("Creating {} objects...", nodes.len());
 
-        for entry in nodes {
+        let total = nodes.len();
+        for (done, entry) in nodes.into_iter().enumerate() {
+            self.progress.check_cancelled()?;
+            self.progress.report(&Progress::new("objects", done, Some(total)));
             if let JsonEntry::Node {
                 id: source_id,
                 node_type,
                 properties,
             } = entry
             {
+                let (node_type, properties) = match self.type_mappings.get(&node_type) {
+                    Some(mapping) => {
+                        self.stats.objects_mapped += 1;
+                        (mapping.target_type.clone(), mapping.apply(&properties))
+                    }
+                    None => (node_type, properties),
+                };
+
                 let name = match properties
                     .get("name")
                     .and_then(|v| v.as_str())
@@ -152,6 +239,27 @@ impl<'a> DataIngestion<'a> {
                     }
                 };
 
+                let mut object_metadata = self
+                    .create_object_by_type(&source_id, &node_type, &properties)
+                    .await?;
+
+                if let Some(seed) = &self.id_seed {
+                    let id = ObjectId::deterministic(seed, &object_metadata.object_type, &name);
+                    object_metadata.id = id;
+                    if let Some(existing) = self.graph.get_object(id)? {
+                        object_metadata.created_at = existing.created_at;
+                        object_metadata.lifecycle = existing.lifecycle;
+                        match self.graph.update_object(object_metadata) {
+                            Ok(()) => {
+                                name_to_id.insert(name, id);
+                                self.stats.objects_updated += 1;
+                            }
+                            Err(e) => error!("Failed to update object '{}': {}", name, e),
+                        }
+                        continue;
+                    }
+                }
+
                 // Dedup: check by source_id first, then by (type, name).
                 let existing_id = self.find_existing(&source_id, &node_type, &name);
                 if let Some(existing) = existing_id {
@@ -163,10 +271,6 @@ impl<'a> DataIngestion<'a> {
                     continue;
                 }
 
-                let object_metadata = self
-                    .create_object_by_type(&source_id, &node_type, &properties)
-                    .await?;
-
                 match self.graph.add_object(object_metadata) {
                     Ok(id) => {
                         name_to_id.insert(name, id);
@@ -179,7 +283,11 @@ impl<'a> DataIngestion<'a> {
             }
         }
 
-        info!("Created {} objects total", self.stats.objects_created);
+        self.progress.report(&Progress::new("objects", total, Some(total)));
+        info!(
+            "Created {} objects total ({} updated)",
+            self.stats.objects_created, self.stats.objects_updated
+        );
         Ok(())
     }
 
@@ -207,7 +315,10 @@ impl<'a> DataIngestion<'a> {
     ) -> Result<()> {
         info!("Creating {} relationships...", edges.len());
 
-        for entry in edges {
+        let total = edges.len();
+        for (done, entry) in edges.into_iter().enumerate() {
+            self.progress.check_cancelled()?;
+            self.progress.report(&Progress::new("relationships", done, Some(total)));
             if let JsonEntry::Edge {
                 from,
                 to,
@@ -231,6 +342,7 @@ impl<'a> DataIngestion<'a> {
             }
         }
 
+        self.progress.report(&Progress::new("relationships", total, Some(total)));
         info!(
             "Created {} relationships total",
             self.stats.relationships_created
@@ -351,15 +463,10 @@ impl<'a> DataIngestion<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_helpers::create_test_graph;
     use serde_json::json;
     use tempfile::TempDir;
 
-    fn create_test_graph() -> (TempDir, KnowledgeGraph) {
-        let temp_dir = TempDir::new().unwrap();
-        let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
-        (temp_dir, graph)
-    }
-
     #[tokio::test]
     async fn test_json_parsing() {
         let json_data = r#"{"entitytype":"node","id":"00000000-0000-0000-0000-000000000001","nodetype":"location","properties":{"name":"Test Location","description":"A place"}}
@@ -383,7 +490,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_properties_parsing() {
-        let (_temp_dir, graph) = create_test_graph();
+        let (graph, _temp_dir) = create_test_graph();
         let ingestion = DataIngestion::new(&graph);
 
         let mut props = Map::new();
@@ -411,7 +518,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_import_roundtrip() {
-        let (_temp_dir, graph) = create_test_graph();
+        let (graph, _temp_dir) = create_test_graph();
         let mut ingestion = DataIngestion::new(&graph);
 
         let jsonl = r#"{"entitytype":"node","id":"00000000-0000-0000-0000-000000000001","nodetype":"location","properties":{"name":"Terminus","description":"A frontier world","tags":["planet","foundation"]}}
@@ -429,4 +536,75 @@ mod tests {
         assert_eq!(stats.relationships_created, 1);
         assert_eq!(stats.parse_errors, 0);
     }
+
+    #[tokio::test]
+    async fn test_seeded_reimport_updates_in_place() {
+        let (graph, _temp_dir) = create_test_graph();
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("test.jsonl");
+        std::fs::write(
+            &file,
+            r#"{"entitytype":"node","id":"a","nodetype":"location","properties":{"name":"Terminus","description":"A frontier world"}}"#,
+        )
+        .unwrap();
+
+        let mut first = DataIngestion::new(&graph).with_id_seed("foundation");
+        first.import_json_data(&file).await.unwrap();
+        assert_eq!(first.get_stats().objects_created, 1);
+
+        std::fs::write(
+            &file,
+            r#"{"entitytype":"node","id":"a","nodetype":"location","properties":{"name":"Terminus","description":"Seat of the Foundation"}}"#,
+        )
+        .unwrap();
+        let mut second = DataIngestion::new(&graph).with_id_seed("foundation");
+        second.import_json_data(&file).await.unwrap();
+        assert_eq!(second.get_stats().objects_created, 0);
+        assert_eq!(second.get_stats().objects_updated, 1);
+
+        let id = ObjectId::deterministic("foundation", "location", "Terminus");
+        let terminus = graph.get_object(id).unwrap().unwrap();
+        assert_eq!(
+            terminus.get_property("description").as_deref(),
+            Some("Seat of the Foundation")
+        );
+        assert_eq!(graph.get_all_objects().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_type_mappings_applied_on_import() {
+        let (graph, _temp_dir) = create_test_graph();
+        let manager = graph.get_schema_manager();
+        let mut schema = (*manager.load_schema("default").await.unwrap()).clone();
+        schema.add_type_mapping(
+            TypeMapping::new("monster", "character")
+                .with_property("hp", "hit_points")
+                .with_set_property("species", json!("monster")),
+        );
+        manager.save_schema(&schema).await.unwrap();
+
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("test.jsonl");
+        std::fs::write(
+            &file,
+            r#"{"entitytype":"node","id":"1","nodetype":"monster","properties":{"name":"Ogre","hp":59}}
+{"entitytype":"node","id":"2","nodetype":"relic","properties":{"title":"Sunblade"}}
+{"entitytype":"edge","from":"Ogre","to":"Sunblade","edgeType":"related_to"}"#,
+        )
+        .unwrap();
+
+        let mut ingestion = DataIngestion::new(&graph)
+            .with_type_mapping(TypeMapping::new("relic", "item").with_property("title", "name"));
+        ingestion.import_json_data(&file).await.unwrap();
+        let stats = ingestion.get_stats();
+        assert_eq!(stats.objects_mapped, 2);
+        assert_eq!(stats.objects_created, 2);
+        assert_eq!(stats.relationships_created, 1);
+
+        let ogre = graph.find_by_name("character", "Ogre").unwrap();
+        assert_eq!(ogre.len(), 1);
+        assert_eq!(ogre[0].properties["hit_points"], json!(59));
+        assert_eq!(ogre[0].get_property("species").as_deref(), Some("monster"));
+        assert_eq!(graph.find_by_name("item", "Sunblade").unwrap().len(), 1);
+    }
 }
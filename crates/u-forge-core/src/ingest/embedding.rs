@@ -7,6 +7,15 @@
 //! [`build_hq_embed_queue`] is a convenience constructor that builds a
 //! single-worker [`InferenceQueue`] for the first high-quality embedding model
 //! selected by [`ModelSelector`] from a live [`LemonadeServerCatalog`].
+//!
+//! [`embed_all_profiles`] does the same for object profile texts (see
+//! [`ObjectMetadata::profile_text`](crate::types::ObjectMetadata::profile_text)).
+//!
+//! [`rechunk_and_embed`] completes [`IntentKind::Reindex`] intents, and an
+//! [`EmbeddingPlan::embed_all`] sweep rolls forward any left pending by a
+//! crash or cancellation before it embeds (see [`crate::intents`]).
+//! [`embed_all_chunks`] persists its work list and resumes what an earlier
+//! run left queued (see [`crate::embed_queue`]).
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -15,15 +24,22 @@ use anyhow::Result;
 use tracing::{info, warn};
 
 use crate::config::AppConfig;
+use crate::diagnostics::trace_operation;
+use crate::error::UForgeError;
+use crate::health::record_error;
+use crate::embedding_mode::EmbeddingMode;
+use crate::intents::IntentKind;
 use crate::lemonade::catalog::LemonadeServerCatalog;
 use crate::lemonade::provider_factory::{BuiltProvider, Capability, ProviderFactory};
 use crate::lemonade::selector::{ModelSelector, QualityTier};
+use crate::progress::{Progress, ProgressSink};
 use crate::queue::{InferenceQueue, InferenceQueueBuilder};
+use crate::text::content_hash;
 use crate::KnowledgeGraph;
 use crate::HIGH_QUALITY_EMBEDDING_DIMENSIONS;
 
 /// Which embedding index to target.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EmbeddingTarget {
     /// Standard 768-dim embeddings (`chunks_vec`).
     Standard,
@@ -31,6 +47,21 @@ pub enum EmbeddingTarget {
     HighQuality,
 }
 
+impl EmbeddingTarget {
+    /// Stored form, as used by the embedding queue.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingTarget::Standard => "standard",
+            EmbeddingTarget::HighQuality => "high_quality",
+        }
+    }
+}
+
+/// Chunks per `embed_many` call in [`embed_all_chunks`].  Each batch's
+/// vectors are stored and dequeued before the next starts, so a crash loses
+/// at most one batch of work.
+const EMBED_BATCH_SIZE: usize = 64;
+
 /// Outcome of an [`embed_all_chunks`] call.
 #[derive(Debug)]
 pub struct EmbeddingResult {
@@ -51,13 +82,8 @@ pub struct EmbeddingOutcome {
     pub skipped: usize,
     /// Chunks successfully embedded at high quality (0 when no HQ queue).
     pub hq_stored: usize,
-}
-
-/// Progress event emitted by [`EmbeddingPlan::execute`].
-#[derive(Debug, Clone)]
-pub enum EmbeddingProgress {
-    /// Rechunking plan: node `done` of `total` nodes processed.
-    Rechunking { done: usize, total: usize },
+    /// Object profiles successfully embedded.
+    pub profiles_stored: usize,
 }
 
 enum EmbeddingTask {
@@ -105,17 +131,19 @@ impl EmbeddingPlan {
         }
     }
 
-    /// Execute the plan, emitting [`EmbeddingProgress`] events via `on_progress`
-    /// as work proceeds.  Returns an [`EmbeddingOutcome`] when complete.
+    /// Execute the plan, reporting to `progress` as work proceeds.  Returns an
+    /// [`EmbeddingOutcome`] when complete.
     ///
-    /// `on_progress` is called synchronously from within the async task — keep it
-    /// cheap (e.g. write to an `Arc<Mutex<_>>`).
+    /// A rechunk plan reports stage `"rechunk"` once per node; a sweep reports
+    /// `"chunks"`, `"hq_chunks"`, and `"profiles"` as it starts each pass.
+    /// Cancellation is checked between nodes and passes; a cancelled plan
+    /// returns what it stored so far.
     pub async fn execute(
         self,
         graph: &KnowledgeGraph,
         queue: &InferenceQueue,
         hq_queue: Option<&InferenceQueue>,
-        on_progress: impl Fn(EmbeddingProgress) + Send,
+        progress: &dyn ProgressSink,
     ) -> EmbeddingOutcome {
         let t0 = std::time::Instant::now();
         let inflight = Arc::new(AtomicUsize::new(0));
@@ -127,20 +155,22 @@ impl EmbeddingPlan {
                 let mut stored = 0usize;
                 let mut skipped = 0usize;
                 for (done, oid) in node_ids.iter().enumerate() {
+                    if progress.is_cancelled() {
+                        info!(done, total, "EmbeddingPlan::Rechunk cancelled");
+                        break;
+                    }
                     let cur = inflight.fetch_add(1, Ordering::Relaxed) + 1;
                     max_inflight.fetch_max(cur, Ordering::Relaxed);
                     match rechunk_and_embed(graph, queue, hq_queue, *oid).await {
                         Ok(n) => stored += n,
                         Err(e) => {
                             warn!(object_id = %oid, %e, "rechunk_and_embed failed");
+                            record_error("embedding", format!("rechunk_and_embed failed: {e}"));
                             skipped += 1;
                         }
                     }
                     inflight.fetch_sub(1, Ordering::Relaxed);
-                    on_progress(EmbeddingProgress::Rechunking {
-                        done: done + 1,
-                        total,
-                    });
+                    progress.report(&Progress::new("rechunk", done + 1, Some(total)));
                 }
                 let peak = max_inflight.load(Ordering::Relaxed);
                 let duration_ms = t0.elapsed().as_millis() as u64;
@@ -155,15 +185,34 @@ impl EmbeddingPlan {
                     stored,
                     skipped,
                     hq_stored: 0,
+                    profiles_stored: 0,
                 }
             }
             EmbeddingTask::EmbedAll => {
+                let (rolled_forward, roll_forward_failed) =
+                    roll_forward_intents(graph, queue, hq_queue, progress).await;
+                let mode = graph.embedding_mode().unwrap_or_else(|e| {
+                    warn!(%e, "Failed to read embedding mode");
+                    EmbeddingMode::default()
+                });
+                if !mode.allows_embeddings() {
+                    info!("Embeddings are off for this project — skipping sweep");
+                    return EmbeddingOutcome {
+                        stored: rolled_forward,
+                        skipped: roll_forward_failed,
+                        hq_stored: 0,
+                        profiles_stored: 0,
+                    };
+                }
+                progress.report(&Progress::new("chunks", 0, Some(3)));
                 let std_result =
                     embed_all_chunks(graph, queue, EmbeddingTarget::Standard).await;
-                let hq_result = if let Some(hq) = hq_queue {
-                    Some(embed_all_chunks(graph, hq, EmbeddingTarget::HighQuality).await)
-                } else {
-                    None
+                let hq_result = match hq_queue {
+                    Some(hq) if !progress.is_cancelled() => {
+                        progress.report(&Progress::new("hq_chunks", 1, Some(3)));
+                        Some(embed_all_chunks(graph, hq, EmbeddingTarget::HighQuality).await)
+                    }
+                    _ => None,
                 };
 
                 let (stored, skipped, total_jobs) = match std_result {
@@ -177,6 +226,19 @@ impl EmbeddingPlan {
                     .and_then(|r| r.ok())
                     .map(|r| r.stored)
                     .unwrap_or(0);
+                let profiles_stored = if progress.is_cancelled() {
+                    0
+                } else {
+                    progress.report(&Progress::new("profiles", 2, Some(3)));
+                    match embed_all_profiles(graph, queue).await {
+                        Ok(r) => r.stored,
+                        Err(e) => {
+                            warn!(%e, "embed_all_profiles failed");
+                            0
+                        }
+                    }
+                };
+                progress.report(&Progress::new("profiles", 3, Some(3)));
 
                 // embed_many uses (workers * 2).max(4) as its concurrency cap.
                 let concurrency_cap = (queue.embedding_worker_count() * 2).max(4);
@@ -191,90 +253,283 @@ impl EmbeddingPlan {
                 );
 
                 EmbeddingOutcome {
-                    stored,
-                    skipped,
+                    stored: stored + rolled_forward,
+                    skipped: skipped + roll_forward_failed,
                     hq_stored,
+                    profiles_stored,
                 }
             }
         }
     }
 }
 
+/// Re-index every object with a pending [`IntentKind::Reindex`] intent —
+/// work an earlier run recorded but never finished.  Reports stage
+/// `"intents"` per object.  Returns chunks stored and objects that failed;
+/// failures stay pending for the next sweep.
+async fn roll_forward_intents(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    hq_queue: Option<&InferenceQueue>,
+    progress: &dyn ProgressSink,
+) -> (usize, usize) {
+    let pending = match graph.pending_intents(IntentKind::Reindex) {
+        Ok(pending) => pending,
+        Err(e) => {
+            warn!(%e, "Failed to read pending intents");
+            return (0, 0);
+        }
+    };
+    if pending.is_empty() {
+        return (0, 0);
+    }
+    info!(count = pending.len(), "Rolling forward unfinished re-index intents");
+    let (mut stored, mut failed) = (0, 0);
+    for (done, oid) in pending.iter().enumerate() {
+        if progress.is_cancelled() {
+            break;
+        }
+        match rechunk_and_embed(graph, queue, hq_queue, *oid).await {
+            Ok(n) => stored += n,
+            Err(e) => {
+                warn!(object_id = %oid, %e, "Rolling forward re-index intent failed");
+                record_error("embedding", format!("re-index roll-forward failed: {e}"));
+                failed += 1;
+            }
+        }
+        progress.report(&Progress::new("intents", done + 1, Some(pending.len())));
+    }
+    (stored, failed)
+}
+
 /// Re-chunk a single object and embed all its chunks, waiting until complete.
 ///
 /// This is the per-node analogue of the bulk [`embed_all_chunks`] pipeline:
 /// 1. Load the node's metadata and resolve edge display lines.
-/// 2. Delete all existing chunks for the node (triggers clean up FTS5 + vector indexes).
-/// 3. Flatten the node into embedding text via [`ObjectMetadata::flatten_for_embedding`].
-/// 4. Create new chunk(s) via [`KnowledgeGraph::add_text_chunk`].
-/// 5. Embed every chunk with `queue` (standard 768-dim).
-/// 6. If `hq_queue` is provided, also embed every chunk at high quality (4096-dim).
+/// 2. Flatten the node into embedding text via [`ObjectMetadata::flatten_for_embedding`].
+/// 3. Keep the existing chunks whose content hash matches a flattened piece.
+/// 4. Write the remaining pieces over the remaining chunks via
+///    [`KnowledgeGraph::update_text_chunk`], so chunk ids and their edit
+///    history survive; add chunks for extra pieces and delete extra chunks.
+/// 5. Embed the node's chunks that lack a vector with `queue` (standard 768-dim).
+/// 6. If `hq_queue` is provided, also embed those lacking a high-quality
+///    (4096-dim) vector.
+/// 7. Embed the node's profile text if it changed since it was last embedded.
 ///
-/// Returns the number of chunks created (and embedded).
+/// Steps 5–7 are skipped when `queue` has no embedding worker or the
+/// project's [`EmbeddingMode`] is `Off`, so the chunks and FTS index still
+/// follow the node.
+/// 8. Complete the node's [`IntentKind::Reindex`] intents that were pending
+///    when the call started.
+///
+/// Returns the number of chunks the node now has.
 ///
 /// # Errors
 /// - Node not found.
-/// - Embedding queue has no workers.
 /// - Any individual embed or upsert call fails.
 pub async fn rechunk_and_embed(
     graph: &KnowledgeGraph,
     queue: &InferenceQueue,
     hq_queue: Option<&InferenceQueue>,
     object_id: crate::types::ObjectId,
+) -> Result<usize> {
+    let marker = graph.intent_marker(IntentKind::Reindex, object_id)?;
+    let chunks = reindex_node(graph, queue, hq_queue, object_id).await?;
+    if let Some(marker) = marker {
+        graph.complete_intent(IntentKind::Reindex, object_id, marker)?;
+    }
+    Ok(chunks)
+}
+
+/// Steps 1–7 of [`rechunk_and_embed`].
+async fn reindex_node(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    hq_queue: Option<&InferenceQueue>,
+    object_id: crate::types::ObjectId,
 ) -> Result<usize> {
     use crate::types::ChunkType;
 
     let meta = graph
         .get_object(object_id)?
-        .ok_or_else(|| anyhow::anyhow!("Node {object_id} not found"))?;
+        .ok_or_else(|| UForgeError::NotFound(format!("Node {object_id} not found")))?;
+
+    // Chunks — and with them the FTS index — are refreshed either way;
+    // vectors only when the project allows embeddings and a worker exists.
+    let embed = queue.has_embedding() && graph.embedding_mode()?.allows_embeddings();
+    let hq_queue = hq_queue.filter(|q| embed && q.has_embedding());
+    if embed {
+        embed_stale_profile(graph, queue, object_id).await?;
+    }
 
     let edge_lines = graph.edge_display_lines(&meta);
     let flat_text = meta.flatten_for_embedding(&edge_lines);
 
-    // Remove stale chunks (triggers clean up FTS5 + vector tables).
-    let deleted = graph.delete_chunks_for_node(object_id)?;
-    if deleted > 0 {
-        tracing::debug!(object_id = %object_id, deleted, "Deleted old chunks");
+    // Bring the stored chunks in line with the new text without deleting
+    // them: a chunk's id keys its edit history, and deleting it would
+    // cascade that history away.  Pieces whose text is already stored keep
+    // their chunk and vectors (a save that only moved the node changes
+    // nothing); the rest are written over the remaining chunks in storage
+    // order, which records a revision and drops their stale vectors.
+    // Surplus pieces become new chunks and surplus chunks are removed.
+    let existing = graph.get_text_chunks(object_id)?;
+    let mut spare: Vec<_> = existing.iter().collect();
+    let mut changed = Vec::new();
+    for piece in graph.split_chunk_text(&flat_text)? {
+        let hash = content_hash(&piece);
+        match spare.iter().position(|c| content_hash(&c.content) == hash) {
+            Some(i) => {
+                spare.remove(i);
+            }
+            None => changed.push(piece),
+        }
     }
-
-    // Create fresh chunks from the flattened text.
-    let chunk_ids = graph.add_text_chunk(object_id, flat_text, ChunkType::Description)?;
-    if chunk_ids.is_empty() {
-        return Ok(0);
+    let mut spare = spare.into_iter();
+    let (mut updated, mut added) = (0, 0);
+    for piece in changed {
+        match spare.next() {
+            Some(chunk) => {
+                graph.update_text_chunk(chunk.id, &piece, None)?;
+                updated += 1;
+            }
+            None => {
+                added += graph
+                    .add_text_chunk(object_id, piece, ChunkType::Description)?
+                    .len();
+            }
+        }
+    }
+    let mut removed = 0;
+    for chunk in spare {
+        removed += usize::from(graph.delete_text_chunk(chunk.id)?);
     }
 
-    // Retrieve the newly created chunks so we have their content for embedding.
-    let chunks = graph.get_text_chunks(object_id)?;
-
-    // Embed every chunk with the standard queue.
-    for chunk in &chunks {
+    // Embed whatever of this node's chunks has no vector yet: edited and
+    // new chunks, plus any an earlier pass missed.
+    let missing = if embed {
+        graph.get_unembedded_chunks_for_node(object_id)?
+    } else {
+        Vec::new()
+    };
+    for chunk in &missing {
         let vec = queue.embed(&chunk.content).await?;
-        graph.upsert_chunk_embedding(chunk.id, &vec)?;
+        store_chunk_vector(graph, queue, EmbeddingTarget::Standard, chunk, &vec)?;
     }
-
-    // Embed with the HQ queue if available.
     if let Some(hq) = hq_queue {
-        if hq.has_embedding() {
-            for chunk in &chunks {
-                let hq_vec = hq.embed(&chunk.content).await?;
-                graph.upsert_chunk_embedding_hq(chunk.id, &hq_vec)?;
-            }
+        for chunk in graph.get_unembedded_chunks_hq_for_node(object_id)? {
+            let hq_vec = hq.embed(&chunk.content).await?;
+            store_chunk_vector(graph, hq, EmbeddingTarget::HighQuality, &chunk, &hq_vec)?;
         }
     }
 
+    let chunks = graph.get_text_chunks(object_id)?.len();
     tracing::info!(
         object_id = %object_id,
         name = %meta.name,
-        chunks = chunks.len(),
-        hq = hq_queue.is_some_and(|q| q.has_embedding()),
+        chunks,
+        updated,
+        added,
+        removed,
+        embedded = missing.len(),
+        hq = hq_queue.is_some(),
         "Rechunked and embedded node"
     );
 
-    Ok(chunks.len())
+    Ok(chunks)
+}
+
+/// Embed `object_id`'s profile if it has no vector (new object, or the
+/// profile text changed on the last write).  No-op without an embedding worker.
+async fn embed_stale_profile(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    object_id: crate::types::ObjectId,
+) -> Result<()> {
+    if !queue.has_embedding() {
+        return Ok(());
+    }
+    let stale = graph
+        .get_unembedded_profiles()?
+        .into_iter()
+        .find(|(id, _)| *id == object_id);
+    if let Some((_, text)) = stale {
+        let vec = queue.embed(&text).await?;
+        graph.upsert_profile_embedding(object_id, &vec)?;
+    }
+    Ok(())
+}
+
+/// Embed every object profile that has no vector yet.
+///
+/// Backfills missing profiles first via
+/// [`KnowledgeGraph::sync_object_profiles`], so this also covers databases
+/// created before profiles existed.  Returns `total == 0` when the queue has
+/// no embedding worker.
+pub async fn embed_all_profiles(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+) -> Result<EmbeddingResult> {
+    if !queue.has_embedding() {
+        return Ok(EmbeddingResult {
+            stored: 0,
+            skipped: 0,
+            total: 0,
+        });
+    }
+
+    graph.sync_object_profiles()?;
+    let profiles = graph.get_unembedded_profiles()?;
+    let total = profiles.len();
+    if total == 0 {
+        return Ok(EmbeddingResult {
+            stored: 0,
+            skipped: 0,
+            total: 0,
+        });
+    }
+
+    let texts: Vec<String> = profiles.iter().map(|(_, text)| text.clone()).collect();
+    let vecs = match queue.embed_many(texts).await {
+        Ok(vecs) => vecs,
+        Err(e) => {
+            warn!(%e, "Profile embedding failed");
+            record_error("embedding", format!("Profile embedding failed: {e}"));
+            return Ok(EmbeddingResult {
+                stored: 0,
+                skipped: total,
+                total,
+            });
+        }
+    };
+
+    let mut stored = 0usize;
+    let mut skipped = 0usize;
+    for ((object_id, _), vec) in profiles.iter().zip(vecs.iter()) {
+        match graph.upsert_profile_embedding(*object_id, vec) {
+            Ok(()) => stored += 1,
+            Err(e) => {
+                warn!(object_id = %object_id, %e, "Could not store profile embedding");
+                record_error("embedding", format!("Could not store profile embedding: {e}"));
+                skipped += 1;
+            }
+        }
+    }
+    info!(stored, skipped, total, "Profile embedding complete");
+    Ok(EmbeddingResult {
+        stored,
+        skipped,
+        total,
+    })
 }
 
 /// Embed all un-embedded chunks in `graph` using `queue`.
 ///
+/// The work list is persisted to the embedding queue first (see
+/// [`crate::embed_queue`]); chunks left queued by an earlier run go first.
+/// Chunks are embedded in batches of [`EMBED_BATCH_SIZE`], each stored and
+/// dequeued before the next, so a crash or failed batch leaves only the
+/// unfinished chunks queued for the next sweep.
+///
 /// Returns `Ok(EmbeddingResult)` with `total == 0` when:
 /// - the queue has no embedding worker, or
 /// - all chunks are already embedded for the given `target`.
@@ -309,48 +564,78 @@ pub async fn embed_all_chunks(
     }
 
     info!(target = ?target, "Embedding chunks");
+    let mut op = trace_operation("embedding", target.as_str());
 
-    let chunks_to_embed = match target {
+    let candidates = match target {
         EmbeddingTarget::Standard => graph.get_unembedded_chunks()?,
         EmbeddingTarget::HighQuality => graph.get_unembedded_chunks_hq()?,
     };
+    let (chunks_to_embed, resumed) = graph.resume_embedding_queue(target, candidates)?;
+    if resumed > 0 {
+        info!(resumed, target = ?target, "Resuming queued embeddings from an earlier run");
+    }
 
     let total = chunks_to_embed.len();
-    let texts: Vec<String> = chunks_to_embed.iter().map(|c| c.content.clone()).collect();
-
-    match queue.embed_many(texts).await {
-        Err(e) => {
-            warn!(%e, target = ?target, "Embedding failed");
-            Ok(EmbeddingResult {
-                stored: 0,
-                skipped: total,
-                total,
-            })
-        }
-        Ok(vecs) => {
-            let mut stored = 0usize;
-            let mut skipped = 0usize;
-            for (chunk, vec) in chunks_to_embed.iter().zip(vecs.iter()) {
-                let result = match target {
-                    EmbeddingTarget::Standard => graph.upsert_chunk_embedding(chunk.id, vec),
-                    EmbeddingTarget::HighQuality => graph.upsert_chunk_embedding_hq(chunk.id, vec),
-                };
-                match result {
-                    Ok(()) => stored += 1,
-                    Err(e) => {
-                        warn!(chunk_id = %chunk.id, %e, "Could not store embedding");
-                        skipped += 1;
-                    }
+    let mut stored = 0usize;
+    let mut skipped = 0usize;
+    for (batch_no, batch) in chunks_to_embed.chunks(EMBED_BATCH_SIZE).enumerate() {
+        let texts: Vec<String> = batch.iter().map(|c| c.content.clone()).collect();
+        let vecs = match queue.embed_many(texts).await {
+            Ok(vecs) => vecs,
+            Err(e) => {
+                // The rest stays queued for the next sweep.
+                warn!(%e, target = ?target, "Embedding failed");
+                record_error("embedding", format!("Embedding failed: {e}"));
+                skipped += total - batch_no * EMBED_BATCH_SIZE;
+                break;
+            }
+        };
+        let mut done = Vec::with_capacity(batch.len());
+        for (chunk, vec) in batch.iter().zip(vecs.iter()) {
+            match store_chunk_vector(graph, queue, target, chunk, vec) {
+                Ok(()) => {
+                    stored += 1;
+                    done.push(chunk.id);
+                }
+                Err(e) => {
+                    warn!(chunk_id = %chunk.id, %e, "Could not store embedding");
+                    record_error("embedding", format!("Could not store embedding: {e}"));
+                    skipped += 1;
                 }
             }
-            info!(stored, skipped, total, target = ?target, "Embedding complete");
-            Ok(EmbeddingResult {
-                stored,
-                skipped,
-                total,
-            })
+        }
+        if let Err(e) = graph.dequeue_embeddings(target, &done) {
+            warn!(%e, "Could not dequeue stored embeddings");
         }
     }
+    info!(stored, skipped, total, target = ?target, "Embedding complete");
+    op.set_items(stored);
+    Ok(EmbeddingResult {
+        stored,
+        skipped,
+        total,
+    })
+}
+
+/// Store `vec` as `chunk`'s `target` vector and record that `queue`'s
+/// embedding model produced it (see [`crate::embedding_status`]).
+fn store_chunk_vector(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    target: EmbeddingTarget,
+    chunk: &crate::types::TextChunk,
+    vec: &[f32],
+) -> Result<()> {
+    match target {
+        EmbeddingTarget::Standard => graph.upsert_chunk_embedding(chunk.id, vec)?,
+        EmbeddingTarget::HighQuality => graph.upsert_chunk_embedding_hq(chunk.id, vec)?,
+    }
+    let model = queue
+        .embedding_worker_names()
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "unknown".to_string());
+    Ok(graph.record_chunk_embeddings(target, &model, std::slice::from_ref(chunk))?)
 }
 
 /// Build a single-worker [`InferenceQueue`] for the high-quality (4096-dim)
@@ -397,6 +682,7 @@ pub async fn build_hq_embed_queue(
     {
         Err(e) => {
             warn!(%e, model = %hq_model_id, "HQ embedding model load failed");
+            record_error("embedding", format!("HQ embedding model load failed: {e}"));
             return None;
         }
         Ok(p) => p,
@@ -433,13 +719,13 @@ mod tests {
     use std::sync::Arc;
 
     use async_trait::async_trait;
-    use tempfile::TempDir;
 
     use crate::ai::embeddings::{EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType};
     use crate::lemonade::{BuiltProvider, Capability, ProviderSlot};
     use crate::queue::InferenceQueueBuilder;
+    use crate::test_helpers::create_test_graph;
     use crate::types::ChunkType;
-    use crate::{KnowledgeGraph, ObjectBuilder};
+    use crate::ObjectBuilder;
 
     use super::*;
 
@@ -488,17 +774,11 @@ mod tests {
         InferenceQueueBuilder::new().with_provider(built).build()
     }
 
-    fn make_graph() -> (KnowledgeGraph, TempDir) {
-        let tmp = TempDir::new().unwrap();
-        let graph = KnowledgeGraph::new(tmp.path()).unwrap();
-        (graph, tmp)
-    }
-
     /// Verify that `embed_all_chunks` is incremental: after an initial full
     /// embedding pass, only newly added chunks are embedded on the next call.
     #[tokio::test]
     async fn test_embed_all_chunks_is_incremental() {
-        let (graph, _tmp) = make_graph();
+        let (graph, _tmp) = create_test_graph();
         let queue = make_embed_queue();
 
         // Add 10 objects, each with one text chunk.
@@ -576,4 +856,40 @@ mod tests {
             "All 12 chunks should now be embedded"
         );
     }
+
+    /// Re-chunking after an edit writes over the node's chunk instead of
+    /// replacing it, so its edit history survives repeated edits.
+    #[tokio::test]
+    async fn test_rechunk_keeps_chunk_history_across_edits() {
+        let (graph, _tmp) = create_test_graph();
+        let queue = make_embed_queue();
+        let oid = ObjectBuilder::character("Sildar Hallwinter".to_string())
+            .with_property(
+                "description".to_string(),
+                "A knight of the Lords' Alliance.".to_string(),
+            )
+            .add_to_graph(&graph)
+            .unwrap();
+        assert_eq!(rechunk_and_embed(&graph, &queue, None, oid).await.unwrap(), 1);
+        let chunk_id = graph.get_text_chunks(oid).unwrap()[0].id;
+
+        for description in [
+            "A wounded knight rescued from the Cragmaw hideout.",
+            "A knight searching Phandalin for Iarno Albrek.",
+        ] {
+            let mut meta = graph.get_object(oid).unwrap().unwrap();
+            meta.set_property("description".to_string(), description.to_string());
+            graph.update_object(meta).unwrap();
+            assert_eq!(rechunk_and_embed(&graph, &queue, None, oid).await.unwrap(), 1);
+        }
+
+        let chunks = graph.get_text_chunks(oid).unwrap();
+        assert_eq!(chunks[0].id, chunk_id);
+        assert!(chunks[0].content.contains("Iarno Albrek"));
+        let history = graph.get_chunk_history(chunk_id).unwrap();
+        assert_eq!(history.len(), 3, "baseline plus one revision per edit");
+        assert!(history[0].content.contains("Lords' Alliance"));
+        assert_eq!(history[2].content, chunks[0].content);
+        assert!(graph.get_unembedded_chunks_for_node(oid).unwrap().is_empty());
+    }
 }
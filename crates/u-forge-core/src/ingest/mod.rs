@@ -7,15 +7,34 @@
 //!
 //! # Modules
 //! * [`data`] — low-level JSON import via [`DataIngestion`]
+//! * [`roll20`] — Roll20 campaign exports via [`Roll20Import`]
+//! * [`session_log`] — `.txt`/`.docx` session logs via [`import_session_log`],
+//!   with [`propose_session_links`] for reviewable links and
+//!   [`draft_session_recap`] for recaps
+//! * [`transcript`] — SRT/VTT/Whisper transcripts via [`import_transcript`]
 //! * [`pipeline`] — high-level orchestration: [`setup_and_index`]
-//! * [`embedding`] — batch embedding: [`embed_all_chunks`], [`build_hq_embed_queue`]
+//! * [`embedding`] — batch embedding: [`embed_all_chunks`], [`embed_all_profiles`],
+//!   [`build_hq_embed_queue`]
 pub mod data;
 pub mod embedding;
 pub mod pipeline;
+pub mod roll20;
+pub mod session_log;
+pub mod transcript;
 
 pub use data::{DataIngestion, IngestionStats, JsonEntry};
 pub use embedding::{
-    build_hq_embed_queue, embed_all_chunks, rechunk_and_embed, EmbeddingOutcome, EmbeddingPlan,
-    EmbeddingProgress, EmbeddingResult, EmbeddingTarget,
+    build_hq_embed_queue, embed_all_chunks, embed_all_profiles, rechunk_and_embed, EmbeddingOutcome,
+    EmbeddingPlan, EmbeddingResult, EmbeddingTarget,
 };
 pub use pipeline::{import_data_only, setup_and_index, SetupResult};
+pub use roll20::{Roll20Import, Roll20ImportStats, HANDOUT_TYPE};
+pub use session_log::{
+    draft_session_recap, import_session_log, import_session_log_dir, import_session_text,
+    propose_session_links, read_session_log, session_recap_context, SessionLinkReport,
+    SessionLogImport,
+};
+pub use transcript::{
+    import_transcript, import_transcript_segments, parse_transcript, TranscriptFormat,
+    TranscriptImport, TranscriptSegment,
+};
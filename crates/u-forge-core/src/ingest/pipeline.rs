@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use tracing::{info, warn};
 
 use crate::ingest::DataIngestion;
+use crate::progress::{Progress, ProgressSink};
 use crate::schema::SchemaIngestion;
 use crate::types::{ChunkType, ObjectId};
 use crate::KnowledgeGraph;
@@ -30,36 +31,32 @@ pub struct SetupResult {
 /// Use this when schemas are already present. Unlike [`setup_and_index`] this
 /// always runs (no `node_count > 0` guard) so the caller controls whether to
 /// clear first.
-pub async fn import_data_only(graph: &KnowledgeGraph, data_file: &str) -> Result<SetupResult> {
+///
+/// With `id_seed`, objects get deterministic ids and a re-import of the same
+/// file updates them in place (see [`DataIngestion::with_id_seed`]).  Either
+/// way each object's `Imported` chunk is replaced, not duplicated.
+///
+/// Reports stages `"objects"`, `"relationships"`, and `"index"` to
+/// `progress`.  Cancelling fails with [`UForgeError::Cancelled`](crate::UForgeError::Cancelled)
+/// and keeps whatever was already written.
+pub async fn import_data_only(
+    graph: &KnowledgeGraph,
+    data_file: &str,
+    id_seed: Option<&str>,
+    progress: &dyn ProgressSink,
+) -> Result<SetupResult> {
     info!(data_file, "Importing data (schema-independent)");
-    let mut ingestion = DataIngestion::new(graph);
+    let mut ingestion = DataIngestion::new(graph).with_progress(progress);
+    if let Some(seed) = id_seed {
+        ingestion = ingestion.with_id_seed(seed);
+    }
     ingestion.import_json_data(data_file).await?;
     let stats = ingestion.get_stats();
     let objects_created = stats.objects_created;
     let relationships_created = stats.relationships_created;
     info!(objects_created, relationships_created, "Data imported");
 
-    info!("Indexing text for full-text search");
-    let all_objects = graph.get_all_objects()?;
-    let id_to_name: HashMap<ObjectId, String> =
-        all_objects.iter().map(|o| (o.id, o.name.clone())).collect();
-    let mut chunks_indexed = 0usize;
-    for obj in &all_objects {
-        let edges = graph.get_relationships(obj.id).unwrap_or_default();
-        let edge_lines: Vec<String> = edges
-            .iter()
-            .filter_map(|e| {
-                let from_name = id_to_name.get(&e.from)?;
-                let to_name = id_to_name.get(&e.to)?;
-                Some(format!("{} {} {}", from_name, e.edge_type.as_str(), to_name))
-            })
-            .collect();
-        let text = obj.flatten_for_embedding(&edge_lines);
-        chunks_indexed += graph
-            .add_text_chunk(obj.id, text, ChunkType::Imported)?
-            .len();
-    }
-    info!(chunks_indexed, "FTS5 indexing complete");
+    let chunks_indexed = index_objects(graph, progress)?;
 
     Ok(SetupResult {
         fresh_import: true,
@@ -78,11 +75,13 @@ pub async fn import_data_only(graph: &KnowledgeGraph, data_file: &str) -> Result
 /// and `SetupResult::fresh_import` is `false`.
 ///
 /// Schema load failures are logged as warnings and do not abort the pipeline.
-/// Data import failures propagate as errors.
+/// Data import failures propagate as errors.  Progress is reported as for
+/// [`import_data_only`].
 pub async fn setup_and_index(
     graph: &KnowledgeGraph,
     schema_dir: &str,
     data_file: &str,
+    progress: &dyn ProgressSink,
 ) -> Result<SetupResult> {
     let pre_stats = graph.get_stats()?;
     if pre_stats.node_count > 0 {
@@ -122,7 +121,7 @@ pub async fn setup_and_index(
     // ── Data import ─────────────────────────────────────────────────────────
 
     info!(data_file, "Importing data");
-    let mut ingestion = DataIngestion::new(graph);
+    let mut ingestion = DataIngestion::new(graph).with_progress(progress);
     ingestion.import_json_data(data_file).await?;
     let stats = ingestion.get_stats();
     let objects_created = stats.objects_created;
@@ -136,13 +135,28 @@ pub async fn setup_and_index(
     // edge endpoint resolution is O(1) per edge rather than O(N) get_object
     // calls.
 
+    let chunks_indexed = index_objects(graph, progress)?;
+
+    Ok(SetupResult {
+        fresh_import: true,
+        objects_created,
+        relationships_created,
+        chunks_indexed,
+    })
+}
+
+/// Flatten every object (plus its edge labels) into an `Imported` text chunk,
+/// replacing any the object already has, and report stage `"index"` per
+/// object.  Returns the number of chunks added.
+fn index_objects(graph: &KnowledgeGraph, progress: &dyn ProgressSink) -> Result<usize> {
     info!("Indexing text for full-text search");
     let all_objects = graph.get_all_objects()?;
     let id_to_name: HashMap<ObjectId, String> =
         all_objects.iter().map(|o| (o.id, o.name.clone())).collect();
     let mut chunks_indexed = 0usize;
 
-    for obj in &all_objects {
+    for (done, obj) in all_objects.iter().enumerate() {
+        progress.check_cancelled()?;
         let edges = graph.get_relationships(obj.id).unwrap_or_default();
         let edge_lines: Vec<String> = edges
             .iter()
@@ -153,16 +167,64 @@ pub async fn setup_and_index(
             })
             .collect();
         let text = obj.flatten_for_embedding(&edge_lines);
+        for chunk in graph.get_text_chunks(obj.id)? {
+            if matches!(chunk.chunk_type, ChunkType::Imported) {
+                graph.delete_text_chunk(chunk.id)?;
+            }
+        }
         chunks_indexed += graph
             .add_text_chunk(obj.id, text, ChunkType::Imported)?
             .len();
+        progress.report(&Progress::new("index", done + 1, Some(all_objects.len())));
     }
     info!(chunks_indexed, "FTS5 indexing complete");
+    Ok(chunks_indexed)
+}
 
-    Ok(SetupResult {
-        fresh_import: true,
-        objects_created,
-        relationships_created,
-        chunks_indexed,
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::test_helpers::create_test_graph;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_seeded_reimport_replaces_imported_chunks() {
+        let (graph, _temp_dir) = create_test_graph();
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("data.jsonl");
+        let path = file.to_str().unwrap();
+        let write = |description: &str| {
+            std::fs::write(
+                &file,
+                format!(
+                    r#"{{"entitytype":"node","id":"a","nodetype":"location","properties":{{"name":"Terminus","description":"{description}"}}}}"#
+                ),
+            )
+            .unwrap();
+        };
+
+        write("A frontier world");
+        let first = import_data_only(&graph, path, Some("foundation"), &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(first.objects_created, 1);
+
+        write("Seat of the Foundation");
+        let second = import_data_only(&graph, path, Some("foundation"), &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(second.objects_created, 0);
+
+        let id = ObjectId::deterministic("foundation", "location", "Terminus");
+        let imported: Vec<_> = graph
+            .get_text_chunks(id)
+            .unwrap()
+            .into_iter()
+            .filter(|c| matches!(c.chunk_type, ChunkType::Imported))
+            .collect();
+        assert_eq!(imported.len(), 1);
+        assert!(imported[0].content.contains("Seat of the Foundation"));
+        assert_eq!(graph.get_all_objects().unwrap().len(), 1);
+    }
 }
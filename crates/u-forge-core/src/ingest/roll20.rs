@@ -0,0 +1,403 @@
+//! Roll20 campaign import.
+//!
+//! Reads the `campaign.json` written by Roll20 campaign exporters:
+//!
+//! ```json
+//! {"characters":[{"id":"-Mabc","name":"Meepo","bio":"<p>A kobold…</p>",
+//!                 "gmnotes":"","controlledby":""}],
+//!  "handouts":[{"id":"-Mdef","name":"Sunless Citadel","notes":"<p>…</p>",
+//!               "gmnotes":""}],
+//!  "journal":[{"n":"NPCs","i":["-Mabc"]},"-Mdef"]}
+//! ```
+//!
+//! Mapping:
+//! - characters become `character` objects, handouts become `handout`
+//!   objects; the Roll20 id is kept as `_source_id` (prefixed `roll20:`)
+//! - `journal` folder names become tags on the entries filed under them
+//! - the first paragraph of a bio or handout's notes becomes its
+//!   `description`; the full text, HTML stripped by [`html_to_text`], is
+//!   archived as [`ChunkType::Imported`] chunks, GM notes in their own chunk
+//! - journal links in the HTML (`http://journal.roll20.net/handout/<id>` and
+//!   `…/character/<id>`) and `[Entry Name]` links become `related_to` edges
+//!
+//! Character sheet attributes and abilities are not imported.  Entries whose
+//! `(type, name)` already exists are reused rather than duplicated, so
+//! importing the same export twice adds nothing.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::progress::{NoProgress, Progress, ProgressSink};
+use crate::text::html_to_text;
+use crate::types::{ChunkType, ObjectId};
+use crate::{KnowledgeGraph, ObjectBuilder};
+
+/// Object type given to imported handouts.
+pub const HANDOUT_TYPE: &str = "handout";
+
+/// Edge type created for journal links.
+const LINK_EDGE: &str = "related_to";
+
+static JOURNAL_LINK: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"journal\.roll20\.net/(?:handout|character)/([-_A-Za-z0-9]+)").unwrap()
+});
+
+static NAME_LINK: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\[([^\[\]]+)\]").unwrap());
+
+#[derive(Debug, Default, Deserialize)]
+struct Roll20Campaign {
+    #[serde(default)]
+    characters: Vec<Roll20Entry>,
+    #[serde(default)]
+    handouts: Vec<Roll20Entry>,
+    /// Folder tree: each item is an entry id or `{"n": name, "i": [items]}`.
+    #[serde(default)]
+    journal: Vec<Value>,
+}
+
+/// A character or handout.  Characters carry `bio`, handouts `notes`.
+#[derive(Debug, Default, Deserialize)]
+struct Roll20Entry {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    bio: String,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    gmnotes: String,
+    #[serde(default)]
+    controlledby: String,
+    #[serde(default)]
+    archived: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Roll20ImportStats {
+    pub objects_created: usize,
+    /// Entries matching an existing object, reused instead of created.
+    pub objects_reused: usize,
+    pub relationships_created: usize,
+    pub chunks_created: usize,
+    /// Links whose target is not in the export or the graph.
+    pub unresolved_links: usize,
+}
+
+pub struct Roll20Import<'a> {
+    graph: &'a KnowledgeGraph,
+    stats: Roll20ImportStats,
+    progress: &'a dyn ProgressSink,
+}
+
+impl<'a> Roll20Import<'a> {
+    pub fn new(graph: &'a KnowledgeGraph) -> Self {
+        Self {
+            graph,
+            stats: Roll20ImportStats::default(),
+            progress: &NoProgress,
+        }
+    }
+
+    /// Report stages `"objects"` and `"content"` to `progress`, and stop with
+    /// [`UForgeError::Cancelled`](crate::UForgeError::Cancelled) when it is
+    /// cancelled.
+    pub fn with_progress(mut self, progress: &'a dyn ProgressSink) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Import a Roll20 `campaign.json` file.
+    pub fn import_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        info!("Loading Roll20 campaign from: {:?}", path);
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+        self.import_str(&content)
+    }
+
+    /// Import a Roll20 campaign export held in memory.
+    pub fn import_str(&mut self, json: &str) -> Result<()> {
+        let campaign: Roll20Campaign =
+            serde_json::from_str(json).context("Failed to parse Roll20 campaign export")?;
+        info!(
+            "Parsed {} characters and {} handouts from Roll20",
+            campaign.characters.len(),
+            campaign.handouts.len()
+        );
+
+        let mut folders: HashMap<String, Vec<String>> = HashMap::new();
+        collect_folders(&campaign.journal, &mut Vec::new(), &mut folders);
+
+        let entries: Vec<(&str, &Roll20Entry)> = campaign
+            .characters
+            .iter()
+            .map(|c| ("character", c))
+            .chain(campaign.handouts.iter().map(|h| (HANDOUT_TYPE, h)))
+            .filter(|(_, e)| !e.name.trim().is_empty())
+            .collect();
+
+        // Objects first, so links can resolve to entries later in the file.
+        let mut by_source: HashMap<&str, ObjectId> = HashMap::new();
+        let mut by_name: HashMap<String, ObjectId> = HashMap::new();
+        let mut created: Vec<(ObjectId, &Roll20Entry)> = Vec::new();
+        for (done, &(object_type, entry)) in entries.iter().enumerate() {
+            self.progress.check_cancelled()?;
+            self.progress.report(
+                &Progress::new("objects", done, Some(entries.len()))
+                    .with_message(entry.name.trim()),
+            );
+            let id = match self
+                .graph
+                .find_by_name(object_type, entry.name.trim())?
+                .first()
+            {
+                Some(existing) => {
+                    self.stats.objects_reused += 1;
+                    existing.id
+                }
+                None => {
+                    let id = self.create_object(object_type, entry, &folders)?;
+                    created.push((id, entry));
+                    id
+                }
+            };
+            by_source.insert(entry.id.as_str(), id);
+            by_name.insert(entry.name.trim().to_lowercase(), id);
+        }
+
+        // Text chunks and links, only for the objects this import created.
+        let total = created.len();
+        for (done, (id, entry)) in created.into_iter().enumerate() {
+            self.progress.check_cancelled()?;
+            self.progress
+                .report(&Progress::new("content", done, Some(total)));
+            let body = html_to_text(entry_html(entry));
+            if !body.is_empty() {
+                self.stats.chunks_created += self
+                    .graph
+                    .add_text_chunk(id, body, ChunkType::Imported)?
+                    .len();
+            }
+            let gm_notes = html_to_text(&entry.gmnotes);
+            if !gm_notes.is_empty() {
+                self.stats.chunks_created += self
+                    .graph
+                    .add_text_chunk(id, format!("GM notes: {gm_notes}"), ChunkType::Imported)?
+                    .len();
+            }
+            self.link(id, entry, &by_source, &by_name)?;
+        }
+        self.progress
+            .report(&Progress::new("content", total, Some(total)));
+
+        info!(
+            "Roll20 import: {} objects created, {} reused, {} relationships",
+            self.stats.objects_created, self.stats.objects_reused, self.stats.relationships_created
+        );
+        Ok(())
+    }
+
+    pub fn get_stats(&self) -> &Roll20ImportStats {
+        &self.stats
+    }
+
+    fn create_object(
+        &mut self,
+        object_type: &str,
+        entry: &Roll20Entry,
+        folders: &HashMap<String, Vec<String>>,
+    ) -> Result<ObjectId> {
+        let name = entry.name.trim().to_string();
+        let mut builder = if object_type == "character" {
+            ObjectBuilder::character(name)
+        } else {
+            ObjectBuilder::custom(object_type.to_string(), name)
+        };
+        builder = builder.with_property("_source_id".to_string(), format!("roll20:{}", entry.id));
+        let text = html_to_text(entry_html(entry));
+        if let Some(first) = text.lines().next() {
+            builder = builder.with_description(first.to_string());
+        }
+        for folder in folders.get(&entry.id).into_iter().flatten() {
+            builder = builder.with_tag(folder.clone());
+        }
+        if !entry.controlledby.trim().is_empty() {
+            builder = builder.with_tag("player character".to_string());
+        }
+        if entry.archived {
+            builder = builder.with_tag("archived".to_string());
+        }
+        let id = self.graph.add_object(builder.build())?;
+        self.stats.objects_created += 1;
+        Ok(id)
+    }
+
+    /// Create a `related_to` edge from `id` to every entry its HTML links to.
+    fn link(
+        &mut self,
+        id: ObjectId,
+        entry: &Roll20Entry,
+        by_source: &HashMap<&str, ObjectId>,
+        by_name: &HashMap<String, ObjectId>,
+    ) -> Result<()> {
+        let mut targets: HashSet<ObjectId> = HashSet::new();
+        for html in [entry_html(entry), entry.gmnotes.as_str()] {
+            for caps in JOURNAL_LINK.captures_iter(html) {
+                match by_source.get(&caps[1]) {
+                    Some(&target) => {
+                        targets.insert(target);
+                    }
+                    None => self.stats.unresolved_links += 1,
+                }
+            }
+            for caps in NAME_LINK.captures_iter(&html_to_text(html)) {
+                let name = caps[1].trim();
+                let target = match by_name.get(&name.to_lowercase()) {
+                    Some(&target) => Some(target),
+                    None => self.graph.find_by_name_only(name)?.first().map(|o| o.id),
+                };
+                match target {
+                    Some(target) => {
+                        targets.insert(target);
+                    }
+                    None => self.stats.unresolved_links += 1,
+                }
+            }
+        }
+        targets.remove(&id);
+
+        let mut targets: Vec<ObjectId> = targets.into_iter().collect();
+        targets.sort_by_key(|t| t.0);
+        for target in targets {
+            match self.graph.connect_objects_str(id, target, LINK_EDGE) {
+                Ok(()) => self.stats.relationships_created += 1,
+                Err(e) => warn!("Failed to link '{}' to {}: {}", entry.name, target, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A character's bio or a handout's notes.
+fn entry_html(entry: &Roll20Entry) -> &str {
+    if entry.bio.is_empty() {
+        &entry.notes
+    } else {
+        &entry.bio
+    }
+}
+
+/// Map each entry id in the journal tree to the names of its folders,
+/// outermost first.
+fn collect_folders(
+    items: &[Value],
+    path: &mut Vec<String>,
+    out: &mut HashMap<String, Vec<String>>,
+) {
+    for item in items {
+        match item {
+            Value::String(id) if !path.is_empty() => {
+                out.insert(id.clone(), path.clone());
+            }
+            Value::Object(folder) => {
+                let name = folder.get("n").and_then(Value::as_str).unwrap_or("").trim();
+                let children = folder.get("i").and_then(Value::as_array);
+                if let Some(children) = children {
+                    if !name.is_empty() {
+                        path.push(name.to_string());
+                    }
+                    collect_folders(children, path, out);
+                    if !name.is_empty() {
+                        path.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+
+    const CAMPAIGN: &str = r#"{
+        "characters": [
+            {"id": "-Mmeepo", "name": "Meepo",
+             "bio": "<p>Kobold keeper of dragons.</p><p>Lost <a href=\"http://journal.roll20.net/handout/-Mcalcryx\">Calcryx</a>.</p>",
+             "gmnotes": "<p>Knows the way to [Yusdrayl].</p>", "controlledby": ""},
+            {"id": "-Myus", "name": "Yusdrayl", "bio": "<p>Kobold queen.</p>", "controlledby": ""},
+            {"id": "-Mhero", "name": "Talia", "bio": "", "controlledby": "-Mplayer1"}
+        ],
+        "handouts": [
+            {"id": "-Mcalcryx", "name": "Calcryx", "notes": "<p>A white dragon wyrmling.<script>x()</script></p>"},
+            {"id": "-Mblank", "name": "  "}
+        ],
+        "journal": [{"n": "NPCs", "i": [{"n": "Kobolds", "i": ["-Mmeepo", "-Myus"]}]}, "-Mhero"]
+    }"#;
+
+    #[test]
+    fn test_roll20_import_maps_entries_links_and_chunks() {
+        let (graph, _tmp) = create_test_graph();
+        let mut import = Roll20Import::new(&graph);
+        import.import_str(CAMPAIGN).unwrap();
+
+        let stats = import.get_stats();
+        assert_eq!(stats.objects_created, 4);
+        assert_eq!(stats.relationships_created, 2);
+        assert_eq!(stats.unresolved_links, 0);
+
+        let meepo = graph.find_by_name("character", "Meepo").unwrap().remove(0);
+        assert_eq!(
+            meepo.get_property("description").as_deref(),
+            Some("Kobold keeper of dragons.")
+        );
+        assert_eq!(
+            meepo.get_property("_source_id").as_deref(),
+            Some("roll20:-Mmeepo")
+        );
+        let tags = meepo.get_json_property("tags").unwrap();
+        assert_eq!(tags, &serde_json::json!(["NPCs", "Kobolds"]));
+
+        let linked: HashSet<ObjectId> = graph
+            .get_relationships(meepo.id)
+            .unwrap()
+            .iter()
+            .map(|e| e.to)
+            .collect();
+        let calcryx = graph
+            .find_by_name(HANDOUT_TYPE, "Calcryx")
+            .unwrap()
+            .remove(0);
+        let yusdrayl = graph
+            .find_by_name("character", "Yusdrayl")
+            .unwrap()
+            .remove(0);
+        assert_eq!(linked, HashSet::from([calcryx.id, yusdrayl.id]));
+
+        let chunks = graph.get_text_chunks(calcryx.id).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "A white dragon wyrmling.");
+        let chunks = graph.get_text_chunks(meepo.id).unwrap();
+        assert!(chunks
+            .iter()
+            .any(|c| c.content == "GM notes: Knows the way to [Yusdrayl]."));
+
+        // Importing again reuses everything.
+        let mut again = Roll20Import::new(&graph);
+        again.import_str(CAMPAIGN).unwrap();
+        assert_eq!(again.get_stats().objects_created, 0);
+        assert_eq!(again.get_stats().objects_reused, 4);
+        assert_eq!(graph.get_stats().unwrap().node_count, 4);
+    }
+}
@@ -0,0 +1,625 @@
+//! Session log import.
+//!
+//! GMs keep years of game logs as plain text or Word documents.
+//! [`import_session_log`] reads one (`.txt`, `.md`, or `.docx`), creates a
+//! `session` object named after the file, and attaches the log as
+//! [`ChunkType::SessionNote`] chunks so it is searchable and embeddable.
+//! [`import_session_log_dir`] does the same for every log in a directory.
+//!
+//! Linking the session to the objects it mentions is a separate, reviewable
+//! step.  [`propose_session_links`] finds mentions of existing objects
+//! (names and glossary terms, via
+//! [`KnowledgeGraph::detect_mentions`]) and, when given an
+//! [`InferenceQueue`] with a text-generation worker, also asks the LLM for
+//! the named entities in the log.  Every link — and every entity the graph
+//! does not know yet — becomes a pending [`Proposal`](crate::Proposal);
+//! nothing is written to the graph until the GM accepts it.
+//!
+//! [`draft_session_recap`] asks the LLM for a recap of the session from its
+//! log and linked objects, packed into a token budget by
+//! [`build_context`](crate::context_builder::build_context).
+//!
+//! ```text
+//! import_session_log(graph, "2019-03-02 Sunless Citadel.docx")
+//!     → session object + SessionNote chunks
+//! propose_session_links(graph, Some(queue), session_id)
+//!     → detect_mentions + LLM entity list → edge / object proposals
+//! draft_session_recap(graph, queue, session_id, config)
+//!     → session_recap_context → build_rag_messages → recap text
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::context_builder::{build_context, AssembledContext, ContextBuilderConfig};
+use crate::error::UForgeError;
+use crate::interrogate::hydrate;
+use crate::lemonade::{ChatMessage, ChatRequest};
+use crate::progress::{Progress, ProgressSink};
+use crate::proposals::{ProposalId, ProposedChange};
+use crate::queue::InferenceQueue;
+use crate::rag::{build_rag_messages, RagContext};
+use crate::text::html_to_text;
+use crate::types::{ChunkType, Edge, EdgeType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Object type of the objects session logs are attached to.
+pub const SESSION_TYPE: &str = "session";
+
+/// Edge type proposed from a session to what it mentions.
+pub const SESSION_LINK_EDGE: &str = "includes";
+
+/// `source` recorded on proposals made by [`propose_session_links`].
+const PROPOSAL_SOURCE: &str = "session_log";
+
+/// Maximum number of chunks sent to the LLM in one extraction call.
+const CHUNKS_PER_PROMPT: usize = 8;
+
+/// Object types the LLM may assign to new entities; entities of any other
+/// type are dropped.
+const ENTITY_TYPES: [&str; 5] = ["character", "location", "faction", "item", "event"];
+
+const SYSTEM_PROMPT: &str = "You extract named entities from tabletop RPG session logs. \
+List every named character, location, faction, item, and event in the text. \
+Respond with ONLY a JSON array, no prose: \
+[{\"name\": \"<name as written>\", \"type\": \"character|location|faction|item|event\"}]. \
+Respond with [] when nothing is named.";
+
+const RECAP_PROMPT: &str = "You write the recap read out at the start of the next \
+tabletop RPG session. Using only the session log and notes below, summarise in a few short \
+paragraphs what the party did, who they met, and what was left unresolved. Do not invent \
+details.";
+
+static ISO_DATE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\b(\d{4}-\d{2}-\d{2})\b").unwrap());
+
+static DOCX_BREAK: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"</w:p>|<w:br\s*/>|<w:cr\s*/>").unwrap());
+
+static DOCX_TAB: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"<w:tab\s*/>").unwrap());
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Outcome of importing one log.
+#[derive(Debug, Clone)]
+pub struct SessionLogImport {
+    pub session_id: ObjectId,
+    /// Chunks attached; 0 when the session already had its log.
+    pub chunks_created: usize,
+    /// The session existed with text chunks, so nothing was added.
+    pub already_imported: bool,
+}
+
+/// Proposals queued by [`propose_session_links`].
+#[derive(Debug, Clone, Default)]
+pub struct SessionLinkReport {
+    /// Edge proposals from the session to objects it mentions.
+    pub link_proposals: Vec<ProposalId>,
+    /// Object proposals for entities the LLM named that the graph lacks.
+    pub object_proposals: Vec<ProposalId>,
+}
+
+/// One entity as emitted by the model.
+#[derive(Debug, Deserialize)]
+struct RawEntity {
+    name: String,
+    #[serde(default, rename = "type")]
+    object_type: String,
+}
+
+// ── Reading ───────────────────────────────────────────────────────────────────
+
+/// The text of a session log: `.txt`, `.md`, and `.log` are read as UTF-8,
+/// `.docx` has its paragraphs extracted from `word/document.xml`.
+pub fn read_session_log<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "md" | "log" => {
+            fs::read_to_string(path).with_context(|| format!("Failed to read file: {:?}", path))
+        }
+        "docx" => {
+            let bytes =
+                fs::read(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+            docx_text(&bytes).with_context(|| format!("Failed to read Word document: {:?}", path))
+        }
+        _ => Err(anyhow!("Unsupported session log format: {:?}", path)),
+    }
+}
+
+/// Paragraph text of a `.docx` file.
+fn docx_text(bytes: &[u8]) -> Result<String> {
+    let xml = zip_entry(bytes, "word/document.xml", MAX_DOCX_ENTRY_BYTES)?;
+    let xml = String::from_utf8(xml).context("word/document.xml is not UTF-8")?;
+    let xml = DOCX_BREAK.replace_all(&xml, "\n");
+    let xml = DOCX_TAB.replace_all(&xml, " ");
+    Ok(html_to_text(&xml))
+}
+
+/// Largest `.docx` part [`zip_entry`] will inflate.  Real documents are a
+/// few megabytes at most; anything past this is treated as a zip bomb.
+const MAX_DOCX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// The uncompressed contents of `name` in the zip archive `bytes`, refusing
+/// to inflate more than `limit` bytes.
+fn zip_entry(bytes: &[u8], name: &str, limit: u64) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).context("Not a zip archive")?;
+    let entry = archive
+        .by_name(name)
+        .with_context(|| format!("'{name}' not found in archive"))?;
+    // The declared size is only a hint; `take` enforces the limit on what
+    // actually inflates.
+    if entry.size() > limit {
+        return Err(anyhow!("'{name}' is larger than {limit} bytes"));
+    }
+    let mut out = Vec::new();
+    entry
+        .take(limit + 1)
+        .read_to_end(&mut out)
+        .with_context(|| format!("Failed to inflate '{name}'"))?;
+    if out.len() as u64 > limit {
+        return Err(anyhow!("'{name}' is larger than {limit} bytes"));
+    }
+    Ok(out)
+}
+
+// ── Import ────────────────────────────────────────────────────────────────────
+
+/// Import one session log; the session is named after the file stem.
+pub fn import_session_log<P: AsRef<Path>>(
+    graph: &KnowledgeGraph,
+    path: P,
+) -> Result<SessionLogImport> {
+    let path = path.as_ref();
+    let text = read_session_log(path)?;
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Session log has no file name: {:?}", path))?;
+    import_session_text(graph, name, &text)
+}
+
+/// Attach `text` to the session called `name`, creating it if needed.
+///
+/// An ISO date (`2019-03-02`) in the name becomes the session's `date`.
+/// When the session already has text chunks the log is assumed imported
+/// and nothing is added, so re-running a bulk import is safe.
+pub fn import_session_text(
+    graph: &KnowledgeGraph,
+    name: &str,
+    text: &str,
+) -> Result<SessionLogImport> {
+    let name = name.trim();
+    let (session_id, has_chunks) = session_object(graph, name)?;
+    if has_chunks {
+        return Ok(SessionLogImport {
+            session_id,
+            chunks_created: 0,
+            already_imported: true,
+        });
+    }
+
+    let chunks_created = if text.trim().is_empty() {
+        0
+    } else {
+        graph
+            .add_text_chunk(session_id, text.trim().to_string(), ChunkType::SessionNote)?
+            .len()
+    };
+    info!(session = name, chunks_created, "Session log imported");
+    Ok(SessionLogImport {
+        session_id,
+        chunks_created,
+        already_imported: false,
+    })
+}
+
+/// The session called `name`, created if needed (an ISO date in the name
+/// becomes its `date`), and whether it already has text chunks.
+pub(super) fn session_object(graph: &KnowledgeGraph, name: &str) -> Result<(ObjectId, bool)> {
+    if name.is_empty() {
+        return Err(anyhow!("Session name must not be empty"));
+    }
+    if let Some(existing) = graph.find_by_name(SESSION_TYPE, name)?.first() {
+        return Ok((existing.id, !graph.get_text_chunks(existing.id)?.is_empty()));
+    }
+    let mut session = ObjectMetadata::new(SESSION_TYPE.to_string(), name.to_string());
+    if let Some(date) = ISO_DATE.captures(name) {
+        session.set_property("date".to_string(), date[1].to_string());
+    }
+    Ok((graph.add_object(session)?, false))
+}
+
+/// Import every `.txt`, `.md`, `.log`, and `.docx` file in `dir`, in file
+/// name order.  A file that fails is logged and skipped.
+///
+/// Reports stage `"session_logs"` to `progress` once per file.  Cancelling
+/// fails with [`UForgeError::Cancelled`](crate::UForgeError::Cancelled) and
+/// keeps the logs already imported.
+pub fn import_session_log_dir<P: AsRef<Path>>(
+    graph: &KnowledgeGraph,
+    dir: P,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<SessionLogImport>> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+                ["txt", "md", "log", "docx"].contains(&e.to_ascii_lowercase().as_str())
+            })
+        })
+        .collect();
+    paths.sort();
+
+    let total = paths.len();
+    let mut imported = Vec::new();
+    for (done, path) in paths.iter().enumerate() {
+        progress.check_cancelled()?;
+        let mut report = Progress::new("session_logs", done, Some(total));
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            report = report.with_message(name);
+        }
+        progress.report(&report);
+        match import_session_log(graph, path) {
+            Ok(result) => imported.push(result),
+            Err(e) => warn!("Skipping session log {:?}: {e:#}", path),
+        }
+    }
+    progress.report(&Progress::new("session_logs", total, Some(total)));
+    Ok(imported)
+}
+
+// ── Link proposals ────────────────────────────────────────────────────────────
+
+/// Queue proposals linking session `session_id` to what its log mentions.
+///
+/// Objects already named in the log (or referred to by a glossary term) get
+/// a [`SESSION_LINK_EDGE`] edge proposal.  With a `queue` that can generate
+/// text, the LLM's entity list is matched too; entities the graph lacks get
+/// an object proposal plus an edge proposal to it (accept the object first).
+/// Existing links are not proposed again.
+pub async fn propose_session_links(
+    graph: &KnowledgeGraph,
+    queue: Option<&InferenceQueue>,
+    session_id: ObjectId,
+) -> Result<SessionLinkReport> {
+    let session = graph
+        .get_object(session_id)?
+        .ok_or_else(|| anyhow!("No session with id {session_id}"))?;
+    let chunks = graph.get_text_chunks(session_id)?;
+    let mut linked: HashSet<ObjectId> = graph
+        .get_relationships(session_id)?
+        .into_iter()
+        .filter(|e| e.from == session_id && e.edge_type.as_str() == SESSION_LINK_EDGE)
+        .map(|e| e.to)
+        .collect();
+    linked.insert(session_id);
+
+    let mut report = SessionLinkReport::default();
+    for chunk in &chunks {
+        for mention in graph.detect_mentions(&chunk.content)? {
+            if linked.insert(mention.object_id) {
+                report
+                    .link_proposals
+                    .push(propose_link(graph, session_id, mention.object_id)?);
+            }
+        }
+    }
+
+    let Some(queue) = queue.filter(|q| q.has_text_generation()) else {
+        return Ok(report);
+    };
+    let glossary = graph.glossary()?;
+    let mut system = SYSTEM_PROMPT.to_string();
+    if !glossary.is_empty() {
+        system.push_str("\n\n");
+        system.push_str(&glossary.prompt_section());
+    }
+    let mut proposed_names: HashSet<String> = HashSet::new();
+    for window in chunks.chunks(CHUNKS_PER_PROMPT) {
+        let text: Vec<&str> = window.iter().map(|c| c.content.trim()).collect();
+        let request = ChatRequest::new(vec![
+            ChatMessage::system(system.as_str()),
+            ChatMessage::user(format!(
+                "Session \"{}\":\n\n{}",
+                session.name,
+                text.join("\n\n")
+            )),
+        ])
+        .with_temperature(0.0);
+        let response = queue.generate(request).await?;
+        for entity in parse_entities(response.first_content().unwrap_or_default()) {
+            let existing = match graph.find_by_name_only(&entity.name)?.first() {
+                Some(object) => Some(object.id),
+                None => glossary.lookup(&entity.name).and_then(|e| e.object_id),
+            };
+            let target =
+                match existing {
+                    Some(id) => id,
+                    None if proposed_names.insert(entity.name.to_lowercase()) => {
+                        let object = ObjectMetadata::new(entity.object_type, entity.name);
+                        let id = object.id;
+                        report.object_proposals.push(graph.propose_change(
+                            ProposedChange::Object(object),
+                            Some(PROPOSAL_SOURCE),
+                        )?);
+                        id
+                    }
+                    None => continue,
+                };
+            if linked.insert(target) {
+                report
+                    .link_proposals
+                    .push(propose_link(graph, session_id, target)?);
+            }
+        }
+    }
+    Ok(report)
+}
+
+// ── Recaps ───────────────────────────────────────────────────────────────────
+
+/// The token-budgeted context [`draft_session_recap`] sends to the model:
+/// the session's log, then the objects it links to with
+/// [`SESSION_LINK_EDGE`] edges.
+pub fn session_recap_context(
+    graph: &KnowledgeGraph,
+    session_id: ObjectId,
+    config: &ContextBuilderConfig,
+) -> Result<AssembledContext> {
+    let session = graph
+        .get_object(session_id)?
+        .ok_or_else(|| UForgeError::NotFound(format!("No session with id {session_id}")))?;
+    let session = hydrate(graph, session, 1.0)?;
+    let linked: Vec<ObjectId> = session
+        .edges
+        .iter()
+        .filter(|e| e.from == session_id && e.edge_type.as_str() == SESSION_LINK_EDGE)
+        .map(|e| e.to)
+        .collect();
+    let mut scope = vec![session];
+    for id in linked {
+        if let Some(node) = graph.get_object(id)? {
+            scope.push(hydrate(graph, node, 0.5)?);
+        }
+    }
+    Ok(build_context("", &scope, config))
+}
+
+/// Ask the LLM for a recap of session `session_id`, ready to pass to
+/// [`KnowledgeGraph::end_session`].
+///
+/// Errors with [`UForgeError::NotFound`] when the session does not exist and
+/// [`UForgeError::InferenceUnavailable`] when the queue has no
+/// text-generation worker.
+pub async fn draft_session_recap(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    session_id: ObjectId,
+    config: &ContextBuilderConfig,
+) -> Result<String> {
+    let context = session_recap_context(graph, session_id, config)?;
+    if !queue.has_text_generation() {
+        return Err(UForgeError::InferenceUnavailable(
+            "Drafting a session recap requires a text-generation worker".to_string(),
+        )
+        .into());
+    }
+    let messages = build_rag_messages(
+        RECAP_PROMPT,
+        &RagContext::from(context),
+        &[],
+        0,
+        "Recap this session.",
+    );
+    let response = queue
+        .generate(ChatRequest::new(messages).with_temperature(0.3))
+        .await?;
+    response
+        .first_content()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| anyhow!("LLM response contained no choices"))
+}
+
+fn propose_link(
+    graph: &KnowledgeGraph,
+    session_id: ObjectId,
+    target: ObjectId,
+) -> Result<ProposalId> {
+    let edge = Edge::new(session_id, target, EdgeType::new(SESSION_LINK_EDGE));
+    Ok(graph.propose_change(ProposedChange::Edge(edge), Some(PROPOSAL_SOURCE))?)
+}
+
+/// Extract entities from the model's reply.
+///
+/// Tolerates surrounding prose by parsing the outermost `[...]` span.
+/// Blank names and types outside [`ENTITY_TYPES`] are dropped.
+fn parse_entities(text: &str) -> Vec<RawEntity> {
+    let (Some(start), Some(end)) = (text.find('['), text.rfind(']')) else {
+        warn!("LLM reply contained no JSON array; treating as no entities");
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let entities: Vec<RawEntity> = match serde_json::from_str(&text[start..=end]) {
+        Ok(e) => e,
+        Err(e) => {
+            warn!("Failed to parse extracted entities: {e}");
+            return Vec::new();
+        }
+    };
+    entities
+        .into_iter()
+        .filter_map(|mut e| {
+            e.name = e.name.trim().to_string();
+            e.object_type = e.object_type.trim().to_ascii_lowercase();
+            (!e.name.is_empty() && ENTITY_TYPES.contains(&e.object_type.as_str())).then_some(e)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorKind, UForgeError};
+    use crate::progress::{CancellationToken, LatestProgress, NoProgress};
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+    use std::io::Write;
+
+    /// A zip archive with each entry deflated.
+    fn zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, content) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_docx_paragraphs_are_extracted() {
+        let document = r#"<?xml version="1.0"?><w:document><w:body>
+            <w:p><w:r><w:t>The party reached </w:t></w:r><w:r><w:t>Thundertree.</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Reidoth</w:t><w:tab/><w:t>warned them &amp; left.</w:t></w:r></w:p>
+            </w:body></w:document>"#;
+        let bytes = zip(&[
+            ("[Content_Types].xml", "<Types/>"),
+            ("word/document.xml", document),
+        ]);
+        assert_eq!(
+            docx_text(&bytes).unwrap(),
+            "The party reached Thundertree.\nReidoth warned them & left."
+        );
+        assert!(docx_text(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_zip_entry_refuses_oversized_parts() {
+        let bytes = zip(&[("word/document.xml", &"a".repeat(4096))]);
+        assert_eq!(
+            zip_entry(&bytes, "word/document.xml", 4096).unwrap().len(),
+            4096
+        );
+        assert!(zip_entry(&bytes, "word/document.xml", 1024).is_err());
+        assert!(zip_entry(&bytes, "word/missing.xml", 4096).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_log_import_and_mention_proposals() {
+        let (graph, tmp) = create_test_graph();
+        let reidoth = ObjectBuilder::character("Reidoth".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let logs = tmp.path().join("logs");
+        fs::create_dir(&logs).unwrap();
+        fs::write(
+            logs.join("2019-03-02 Thundertree.txt"),
+            "Reidoth met the party.",
+        )
+        .unwrap();
+        fs::write(logs.join("notes.pdf"), "ignored").unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = LatestProgress::new().with_cancellation(token);
+        let err = import_session_log_dir(&graph, &logs, &cancelled).unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::Cancelled);
+
+        let imported = import_session_log_dir(&graph, &logs, &NoProgress).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].chunks_created, 1);
+        let session = graph.get_object(imported[0].session_id).unwrap().unwrap();
+        assert_eq!(session.object_type, SESSION_TYPE);
+        assert_eq!(session.get_property("date").as_deref(), Some("2019-03-02"));
+
+        // Re-importing adds nothing.
+        let again = import_session_log_dir(&graph, &logs, &NoProgress).unwrap();
+        assert!(again[0].already_imported);
+        assert_eq!(graph.get_text_chunks(session.id).unwrap().len(), 1);
+
+        let report = propose_session_links(&graph, None, session.id)
+            .await
+            .unwrap();
+        assert_eq!(report.link_proposals.len(), 1);
+        assert!(report.object_proposals.is_empty());
+        graph.accept_proposal(report.link_proposals[0]).unwrap();
+        assert!(graph
+            .get_relationships(session.id)
+            .unwrap()
+            .iter()
+            .any(|e| e.to == reidoth && e.edge_type.as_str() == SESSION_LINK_EDGE));
+
+        // Accepted links are not proposed again.
+        let report = propose_session_links(&graph, None, session.id)
+            .await
+            .unwrap();
+        assert!(report.link_proposals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_recap_covers_log_and_linked_objects() {
+        let (graph, _tmp) = create_test_graph();
+        let reidoth = ObjectBuilder::character("Reidoth".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .add_text_chunk(
+                reidoth,
+                "A druid of Thundertree.".to_string(),
+                ChunkType::Description,
+            )
+            .unwrap();
+        let session = import_session_text(&graph, "Thundertree", "Reidoth met the party.")
+            .unwrap()
+            .session_id;
+        graph
+            .connect_objects_str(session, reidoth, SESSION_LINK_EDGE)
+            .unwrap();
+
+        let ctx = session_recap_context(&graph, session, &ContextBuilderConfig::default()).unwrap();
+        assert_eq!(ctx.object_count, 2);
+        assert_eq!(ctx.citations[0].object_id, session);
+        assert!(ctx.text.contains("Reidoth met the party."));
+        assert!(ctx.text.contains("A druid of Thundertree."));
+
+        let queue = crate::queue::InferenceQueueBuilder::new().build();
+        let config = ContextBuilderConfig::default();
+        let err = draft_session_recap(&graph, &queue, ObjectId::new_v4(), &config)
+            .await
+            .unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::NotFound);
+        let err = draft_session_recap(&graph, &queue, session, &config)
+            .await
+            .unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::InferenceUnavailable);
+    }
+
+    #[test]
+    fn test_parse_entities_filters_bad_rows() {
+        let reply = r#"Sure: [{"name": " Venomfang ", "type": "Character"},
+            {"name": "", "type": "location"}, {"name": "Phandalin", "type": "planet"}]"#;
+        let entities = parse_entities(reply);
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "Venomfang");
+        assert_eq!(entities[0].object_type, "character");
+        assert!(parse_entities("no entities").is_empty());
+    }
+}
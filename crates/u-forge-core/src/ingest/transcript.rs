@@ -0,0 +1,488 @@
+//! Recorded-session transcript import.
+//!
+//! Reads SRT, WebVTT, and Whisper JSON transcripts into a `session` object,
+//! the same one [`import_session_log`](super::import_session_log) would use
+//! for a file of that name.  Consecutive segments are packed into
+//! [`ChunkType::SessionNote`] chunks that keep the span of the recording they
+//! cover ([`TextChunk::time_range`](crate::TextChunk::time_range)), one line
+//! per segment:
+//!
+//! ```text
+//! [01:02:03] Meepo: We must find Calcryx!
+//! ```
+//!
+//! Speaker labels come from VTT voice tags (`<v Dave>`), a Whisper
+//! segment's `speaker` field, or a `Name:` / `[Name]` prefix.  The project's
+//! speaker mapping table ([`KnowledgeGraph::set_speaker_mapping`]) ties a
+//! label such as `SPEAKER_01` or a player's name to the character they
+//! voice: mapped lines show the character's name, and the session gets an
+//! `includes` edge to each mapped character heard in it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use tracing::info;
+
+use super::session_log::{session_object, SESSION_LINK_EDGE};
+use crate::error::{Result, UForgeError};
+use crate::graph::MAX_CHUNK_TOKENS;
+use crate::progress::{Progress, ProgressSink};
+use crate::text::{count_chunk_tokens, html_to_text};
+use crate::types::{ChunkType, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Longest text accepted as a `Name:` speaker prefix.
+const MAX_SPEAKER_LEN: usize = 40;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Transcript file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Srt,
+    Vtt,
+    /// `{"segments": [{"start": 1.5, "end": 3.0, "text": "…", "speaker": "…"}]}`
+    /// as written by Whisper and WhisperX.
+    WhisperJson,
+}
+
+impl TranscriptFormat {
+    /// The format implied by a file extension (`srt`, `vtt`, `json`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::Vtt),
+            "json" => Some(Self::WhisperJson),
+            _ => None,
+        }
+    }
+}
+
+/// One timed utterance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Outcome of [`import_transcript`].
+#[derive(Debug, Clone)]
+pub struct TranscriptImport {
+    pub session_id: ObjectId,
+    pub segments: usize,
+    pub chunks_created: usize,
+    /// Mapped objects heard in the transcript, each linked from the session.
+    pub speakers_linked: Vec<ObjectId>,
+    /// Speaker labels with no mapping, sorted.
+    pub unmapped_speakers: Vec<String>,
+    /// The session already had text chunks, so nothing was added.
+    pub already_imported: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperTranscript {
+    #[serde(default)]
+    segments: Vec<WhisperSegment>,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    speaker: Option<String>,
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+/// Parse transcript `content` in `format` into segments, in file order.
+pub fn parse_transcript(content: &str, format: TranscriptFormat) -> Result<Vec<TranscriptSegment>> {
+    match format {
+        TranscriptFormat::Srt | TranscriptFormat::Vtt => Ok(parse_cues(content)),
+        TranscriptFormat::WhisperJson => {
+            let transcript: WhisperTranscript =
+                serde_json::from_str(content).context("Failed to parse Whisper JSON transcript")?;
+            if transcript.segments.is_empty() && !transcript.text.trim().is_empty() {
+                return Ok(vec![segment(0, 0, None, &transcript.text)]);
+            }
+            Ok(transcript
+                .segments
+                .iter()
+                .filter(|s| !s.text.trim().is_empty())
+                .map(|s| {
+                    segment(
+                        seconds_to_ms(s.start),
+                        seconds_to_ms(s.end),
+                        s.speaker.clone(),
+                        &s.text,
+                    )
+                })
+                .collect())
+        }
+    }
+}
+
+/// SRT and VTT share a cue layout: an optional id line, a
+/// `start --> end` timing line, then text lines up to a blank line.
+fn parse_cues(content: &str) -> Vec<TranscriptSegment> {
+    let content = content.replace("\r\n", "\n");
+    let mut out = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue; // header, NOTE, or STYLE block
+        };
+        let (Some(start), Some(end)) = timing
+            .split_once("-->")
+            .map(|(a, b)| (parse_timestamp(a), parse_timestamp(b)))
+            .unwrap_or((None, None))
+        else {
+            continue;
+        };
+
+        let raw: Vec<&str> = lines.collect();
+        let raw = raw.join(" ");
+        let voice = raw
+            .split_once("<v ")
+            .and_then(|(_, rest)| rest.split_once('>'))
+            .map(|(name, _)| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let text = html_to_text(&raw).replace('\n', " ");
+        if !text.trim().is_empty() {
+            out.push(segment(start, end, voice, &text));
+        }
+    }
+    out
+}
+
+/// `HH:MM:SS,mmm`, `HH:MM:SS.mmm`, or `MM:SS.mmm` (cue settings after the
+/// time are ignored).
+fn parse_timestamp(text: &str) -> Option<u64> {
+    let time = text.split_whitespace().next()?;
+    let (clock, millis) = time.split_once([',', '.']).unwrap_or((time, "0"));
+    let mut seconds = 0u64;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    let millis: u64 = format!("{millis:0<3}").get(..3)?.parse().ok()?;
+    Some(seconds * 1000 + millis)
+}
+
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+/// A segment, taking the speaker from a `Name:` or `[Name]` prefix when none
+/// is given.
+fn segment(start_ms: u64, end_ms: u64, speaker: Option<String>, text: &str) -> TranscriptSegment {
+    let text = text.trim();
+    let prefixed = text
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .or_else(|| text.split_once(':'))
+        .filter(|(name, _)| {
+            let name = name.trim();
+            !name.is_empty()
+                && name.len() <= MAX_SPEAKER_LEN
+                && name.split_whitespace().count() <= 3
+                && !name.contains(['.', '!', '?', ','])
+        });
+    let (speaker, text) = match (speaker, prefixed) {
+        (Some(speaker), _) => (Some(speaker), text.to_string()),
+        (None, Some((name, rest))) => (Some(name.trim().to_string()), rest.trim().to_string()),
+        (None, None) => (None, text.to_string()),
+    };
+    TranscriptSegment {
+        start_ms,
+        end_ms,
+        speaker,
+        text,
+    }
+}
+
+fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+// ── Import ────────────────────────────────────────────────────────────────────
+
+/// Import a transcript file into the session named after its file stem.
+/// The format comes from the extension (see [`TranscriptFormat::from_path`]).
+/// Progress is reported as for [`import_transcript_segments`].
+pub fn import_transcript<P: AsRef<Path>>(
+    graph: &KnowledgeGraph,
+    path: P,
+    progress: &dyn ProgressSink,
+) -> Result<TranscriptImport> {
+    let path = path.as_ref();
+    let format = TranscriptFormat::from_path(path)
+        .ok_or_else(|| anyhow!("Unsupported transcript format: {:?}", path))?;
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Transcript has no file name: {:?}", path))?;
+    import_transcript_segments(graph, name, &parse_transcript(&content, format)?, progress)
+}
+
+/// Attach `segments` to the session called `name` (created if needed), as
+/// timed chunks, and link the session to every mapped speaker.
+///
+/// Like [`import_session_text`](super::import_session_text), a session that
+/// already has text chunks is left alone.
+///
+/// Reports stage `"transcript"` to `progress` once per segment.  Cancelling
+/// fails with [`UForgeError::Cancelled`] and keeps the chunks already
+/// stored.
+pub fn import_transcript_segments(
+    graph: &KnowledgeGraph,
+    name: &str,
+    segments: &[TranscriptSegment],
+    progress: &dyn ProgressSink,
+) -> Result<TranscriptImport> {
+    let (session_id, has_chunks) = session_object(graph, name.trim())?;
+    let mut result = TranscriptImport {
+        session_id,
+        segments: segments.len(),
+        chunks_created: 0,
+        speakers_linked: Vec::new(),
+        unmapped_speakers: Vec::new(),
+        already_imported: has_chunks,
+    };
+    if has_chunks {
+        return Ok(result);
+    }
+
+    let mappings: HashMap<String, ObjectId> = graph
+        .speaker_mappings()?
+        .into_iter()
+        .map(|(speaker, id)| (speaker.to_lowercase(), id))
+        .collect();
+    let mut names: HashMap<ObjectId, String> = HashMap::new();
+    let mut heard: Vec<ObjectId> = Vec::new();
+    let mut unmapped: BTreeSet<String> = BTreeSet::new();
+
+    // Pack lines into chunks that stay under the chunk token limit.
+    let mut pending: Vec<String> = Vec::new();
+    let mut pending_tokens = 0;
+    let mut span = (0, 0);
+    for (done, segment) in segments.iter().enumerate() {
+        progress.check_cancelled()?;
+        progress.report(&Progress::new("transcript", done, Some(segments.len())));
+        let speaker = match &segment.speaker {
+            Some(label) => match mappings.get(&label.to_lowercase()) {
+                Some(&id) => {
+                    if !heard.contains(&id) {
+                        heard.push(id);
+                        let name = graph.get_object(id)?.map(|o| o.name);
+                        names.insert(id, name.unwrap_or_else(|| label.clone()));
+                    }
+                    Some(names[&id].clone())
+                }
+                None => {
+                    unmapped.insert(label.clone());
+                    Some(label.clone())
+                }
+            },
+            None => None,
+        };
+        let line = match speaker {
+            Some(speaker) => format!(
+                "[{}] {speaker}: {}",
+                format_timestamp(segment.start_ms),
+                segment.text
+            ),
+            None => format!("[{}] {}", format_timestamp(segment.start_ms), segment.text),
+        };
+        let tokens = count_chunk_tokens(&line) + 1;
+        if !pending.is_empty() && pending_tokens + tokens > MAX_CHUNK_TOKENS {
+            result.chunks_created += flush(graph, session_id, &mut pending, span)?;
+            pending_tokens = 0;
+        }
+        if pending.is_empty() {
+            span.0 = segment.start_ms;
+        }
+        span.1 = span.1.max(segment.end_ms);
+        pending.push(line);
+        pending_tokens += tokens;
+    }
+    result.chunks_created += flush(graph, session_id, &mut pending, span)?;
+    progress.report(&Progress::new(
+        "transcript",
+        segments.len(),
+        Some(segments.len()),
+    ));
+
+    let existing: Vec<ObjectId> = graph
+        .get_relationships(session_id)?
+        .into_iter()
+        .filter(|e| e.from == session_id && e.edge_type.as_str() == SESSION_LINK_EDGE)
+        .map(|e| e.to)
+        .collect();
+    for id in heard {
+        if !existing.contains(&id) {
+            graph.connect_objects_str(session_id, id, SESSION_LINK_EDGE)?;
+        }
+        result.speakers_linked.push(id);
+    }
+    result.unmapped_speakers = unmapped.into_iter().collect();
+    info!(
+        session = name,
+        segments = segments.len(),
+        chunks_created = result.chunks_created,
+        "Transcript imported"
+    );
+    Ok(result)
+}
+
+/// Store `lines` as one timed chunk and clear them.
+fn flush(
+    graph: &KnowledgeGraph,
+    session_id: ObjectId,
+    lines: &mut Vec<String>,
+    (start_ms, end_ms): (u64, u64),
+) -> Result<usize> {
+    if lines.is_empty() {
+        return Ok(0);
+    }
+    let content = lines.join("\n");
+    lines.clear();
+    Ok(graph
+        .add_timed_text_chunk(
+            session_id,
+            content,
+            ChunkType::SessionNote,
+            start_ms,
+            end_ms,
+        )?
+        .len())
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Map transcript speaker label `speaker` (case-insensitive) to the
+    /// object it voices, replacing any previous mapping.
+    pub fn set_speaker_mapping(&self, speaker: &str, object_id: ObjectId) -> Result<()> {
+        if speaker.trim().is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "Speaker label must not be empty".to_string(),
+            ));
+        }
+        if self.get_object(object_id)?.is_none() {
+            return Err(UForgeError::NotFound(format!(
+                "Speaker mapping refers to unknown object {object_id}"
+            )));
+        }
+        self.storage
+            .upsert_speaker_mapping(speaker.trim(), object_id)
+    }
+
+    /// Every speaker mapping, ordered by label.
+    pub fn speaker_mappings(&self) -> Result<Vec<(String, ObjectId)>> {
+        self.storage.list_speaker_mappings()
+    }
+
+    /// Remove the mapping for `speaker`.  Returns `false` if there was none.
+    pub fn remove_speaker_mapping(&self, speaker: &str) -> Result<bool> {
+        self.storage.delete_speaker_mapping(speaker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_parse_srt_vtt_and_whisper() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:04,000\r\nDave: We open the door.\r\n\r\n\
+                   2\r\n01:02:03,000 --> 01:02:05,250\r\n<i>The dragon roars.</i>\r\n";
+        let segments = parse_transcript(srt, TranscriptFormat::Srt).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1500, 4000));
+        assert_eq!(segments[0].speaker.as_deref(), Some("Dave"));
+        assert_eq!(segments[0].text, "We open the door.");
+        assert_eq!(segments[1].start_ms, 3_723_000);
+        assert_eq!(segments[1].speaker, None);
+        assert_eq!(segments[1].text, "The dragon roars.");
+
+        let vtt = "WEBVTT\n\nNOTE recorded live\n\nintro\n00:05.000 --> 00:07.5 align:start\n\
+                   <v Sara>Wait, what?</v>\n";
+        let segments = parse_transcript(vtt, TranscriptFormat::Vtt).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (5000, 7500));
+        assert_eq!(segments[0].speaker.as_deref(), Some("Sara"));
+        assert_eq!(segments[0].text, "Wait, what?");
+
+        let json = r#"{"segments": [
+            {"start": 0.0, "end": 2.25, "text": " Roll initiative.", "speaker": "SPEAKER_00"},
+            {"start": 2.25, "end": 3.0, "text": "  "}]}"#;
+        let segments = parse_transcript(json, TranscriptFormat::WhisperJson).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].end_ms, 2250);
+        assert_eq!(segments[0].speaker.as_deref(), Some("SPEAKER_00"));
+    }
+
+    #[test]
+    fn test_transcript_import_maps_speakers() {
+        let (graph, tmp) = create_test_graph();
+        let meepo = ObjectBuilder::character("Meepo".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.set_speaker_mapping("speaker_01", meepo).unwrap();
+        assert!(graph.set_speaker_mapping("x", ObjectId::new_v4()).is_err());
+
+        let path = tmp.path().join("Session 4.json");
+        fs::write(
+            &path,
+            r#"{"segments": [
+                {"start": 61.0, "end": 63.0, "text": "We must find Calcryx!", "speaker": "SPEAKER_01"},
+                {"start": 64.0, "end": 66.5, "text": "Who took her?", "speaker": "SPEAKER_02"}]}"#,
+        )
+        .unwrap();
+        let result = import_transcript(&graph, &path, &NoProgress).unwrap();
+        assert_eq!(result.chunks_created, 1);
+        assert_eq!(result.speakers_linked, vec![meepo]);
+        assert_eq!(result.unmapped_speakers, vec!["SPEAKER_02".to_string()]);
+
+        let chunks = graph.get_text_chunks(result.session_id).unwrap();
+        assert_eq!(chunks[0].time_range, Some((61_000, 66_500)));
+        assert_eq!(
+            chunks[0].content,
+            "[00:01:01] Meepo: We must find Calcryx!\n[00:01:04] SPEAKER_02: Who took her?"
+        );
+        assert!(graph
+            .get_relationships(result.session_id)
+            .unwrap()
+            .iter()
+            .any(|e| e.to == meepo && e.edge_type.as_str() == SESSION_LINK_EDGE));
+
+        assert!(
+            import_transcript(&graph, &path, &NoProgress)
+                .unwrap()
+                .already_imported
+        );
+
+        // Mappings go with their object.
+        graph.delete_object(meepo).unwrap();
+        assert!(graph.speaker_mappings().unwrap().is_empty());
+    }
+}
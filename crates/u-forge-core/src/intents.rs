@@ -0,0 +1,127 @@
+//! Write-ahead intents for work that spans indexes.
+//!
+//! Saving an object commits the node row at once, but refreshing its
+//! description chunks, their FTS rows, and their embeddings happens later in
+//! [`rechunk_and_embed`](crate::ingest::rechunk_and_embed), which needs the
+//! embedding server and can be cancelled by a newer save.  If the app exits
+//! or crashes in between, the object is left half-indexed: search keeps
+//! finding the old text, and the embedding sweep only fills in chunks that
+//! have no vector, so nothing ever repairs it.
+//!
+//! Callers therefore record an [`IntentKind::Reindex`] intent for every saved
+//! object.  `rechunk_and_embed` completes the intents that were pending when
+//! it started once it succeeds, and an
+//! [`EmbeddingPlan::embed_all`](crate::ingest::EmbeddingPlan::embed_all)
+//! sweep — run at startup — first rolls every still-pending intent forward.
+//! Intents for deleted objects are dropped with the object.
+
+use crate::error::Result;
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Cross-index work owed to an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntentKind {
+    /// Re-chunk the object's flattened text and embed the result.
+    Reindex,
+}
+
+impl IntentKind {
+    /// Stored form.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntentKind::Reindex => "reindex",
+        }
+    }
+}
+
+impl KnowledgeGraph {
+    /// Record that `kind` work is owed to each of `object_ids`.  Call right
+    /// after the write that made it necessary.
+    pub fn record_intent(&self, kind: IntentKind, object_ids: &[ObjectId]) -> Result<()> {
+        if object_ids.is_empty() {
+            return Ok(());
+        }
+        self.storage.record_intents(kind.as_str(), object_ids)
+    }
+
+    /// Objects with `kind` work still outstanding, oldest request first.
+    pub fn pending_intents(&self, kind: IntentKind) -> Result<Vec<ObjectId>> {
+        self.storage.pending_intent_objects(kind.as_str())
+    }
+
+    /// The newest pending `kind` intent for `object_id`, to pass to
+    /// [`complete_intent`](Self::complete_intent) once the work that started
+    /// now has finished.  `None` when nothing is pending.
+    pub fn intent_marker(&self, kind: IntentKind, object_id: ObjectId) -> Result<Option<i64>> {
+        self.storage.latest_intent_seq(kind.as_str(), object_id)
+    }
+
+    /// Mark `object_id`'s `kind` intents up to `marker` done.  Intents
+    /// recorded after the marker was taken stay pending.
+    pub fn complete_intent(
+        &self,
+        kind: IntentKind,
+        object_id: ObjectId,
+        marker: i64,
+    ) -> Result<()> {
+        self.storage
+            .complete_intents(kind.as_str(), object_id, marker)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ObjectMetadata;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_intents_survive_reopen_and_complete_up_to_marker() {
+        let temp_dir = TempDir::new().unwrap();
+        let (a, b) = {
+            let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
+            let a = graph
+                .add_object(ObjectMetadata::new(
+                    "npc".to_string(),
+                    "Gundren".to_string(),
+                ))
+                .unwrap();
+            let b = graph
+                .add_object(ObjectMetadata::new("npc".to_string(), "Nundro".to_string()))
+                .unwrap();
+            graph.record_intent(IntentKind::Reindex, &[a, b]).unwrap();
+            (a, b)
+        };
+
+        // A "restart" still sees the work.
+        let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
+        assert_eq!(graph.pending_intents(IntentKind::Reindex).unwrap(), [a, b]);
+
+        // A save during the run keeps a newer intent pending.
+        let marker = graph
+            .intent_marker(IntentKind::Reindex, a)
+            .unwrap()
+            .unwrap();
+        graph.record_intent(IntentKind::Reindex, &[a]).unwrap();
+        graph
+            .complete_intent(IntentKind::Reindex, a, marker)
+            .unwrap();
+        assert_eq!(graph.pending_intents(IntentKind::Reindex).unwrap(), [b, a]);
+        let marker = graph
+            .intent_marker(IntentKind::Reindex, a)
+            .unwrap()
+            .unwrap();
+        graph
+            .complete_intent(IntentKind::Reindex, a, marker)
+            .unwrap();
+
+        graph.delete_object(b).unwrap();
+        assert!(graph
+            .pending_intents(IntentKind::Reindex)
+            .unwrap()
+            .is_empty());
+        assert_eq!(graph.intent_marker(IntentKind::Reindex, a).unwrap(), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Interactions — relationships between more than two objects.
+//!
+//! An edge joins exactly two objects, but a battle between three factions at
+//! a river ford during the Long Winter involves five.  An [`Interaction`] is
+//! reified as a node of type [`INTERACTION_TYPE`] with one
+//! [`PARTICIPANT_EDGE`] edge to each participant; the participant's part in
+//! it (`"attacker"`, `"site"`, `"during"`, …) is the edge's `role` metadata.
+//!
+//! The participant edges are managed here: [`KnowledgeGraph::add_interaction`]
+//! and [`KnowledgeGraph::set_participants`] write the node and its edges in
+//! one transaction, and deleting the node removes the edges with it.
+//! Because the interaction is an ordinary node it is searchable, versioned,
+//! and visible in the graph view like any other object.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::staging::StagingLayer;
+use crate::types::{Edge, EdgeType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Object type of interaction nodes.
+pub const INTERACTION_TYPE: &str = "interaction";
+
+/// Edge type from an interaction node to each participant.
+pub const PARTICIPANT_EDGE: &str = "involves";
+
+/// Edge metadata key holding a participant's role.
+pub const ROLE_KEY: &str = "role";
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// One object taking part in an interaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Participant {
+    pub object_id: ObjectId,
+    /// The participant's part in the interaction, e.g. `"attacker"`.
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+impl Participant {
+    pub fn new(object_id: ObjectId, role: Option<&str>) -> Self {
+        Self {
+            object_id,
+            role: role.map(str::to_string),
+        }
+    }
+}
+
+/// A relationship among any number of objects, stored as an
+/// [`INTERACTION_TYPE`] node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub id: ObjectId,
+    pub name: String,
+    /// What kind of interaction this is, e.g. `"battle"`, `"treaty"`;
+    /// stored as the node's `kind` property.
+    #[serde(default)]
+    pub kind: Option<String>,
+    pub participants: Vec<Participant>,
+}
+
+impl Interaction {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: ObjectId::new_v4(),
+            name: name.into(),
+            kind: None,
+            participants: Vec::new(),
+        }
+    }
+
+    pub fn with_kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    pub fn with_participant(mut self, object_id: ObjectId, role: &str) -> Self {
+        self.participants.push(Participant::new(object_id, Some(role)));
+        self
+    }
+
+    /// The role `object_id` plays, if it participates with one.
+    pub fn role_of(&self, object_id: ObjectId) -> Option<&str> {
+        self.participants
+            .iter()
+            .find(|p| p.object_id == object_id)
+            .and_then(|p| p.role.as_deref())
+    }
+
+    /// Participants whose role is `role` (case-insensitive).
+    pub fn participants_with_role(&self, role: &str) -> Vec<ObjectId> {
+        self.participants
+            .iter()
+            .filter(|p| p.role.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(role)))
+            .map(|p| p.object_id)
+            .collect()
+    }
+
+    pub fn involves(&self, object_id: ObjectId) -> bool {
+        self.participants.iter().any(|p| p.object_id == object_id)
+    }
+}
+
+fn participant_edge(interaction: ObjectId, participant: &Participant) -> Edge {
+    let mut edge = Edge::new(interaction, participant.object_id, EdgeType::new(PARTICIPANT_EDGE));
+    if let Some(role) = &participant.role {
+        edge.metadata.insert(ROLE_KEY.to_string(), role.clone());
+    }
+    edge
+}
+
+fn check_distinct(participants: &[Participant]) -> Result<()> {
+    let mut seen = HashSet::new();
+    for p in participants {
+        if !seen.insert(p.object_id) {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Object {} is listed more than once as a participant",
+                p.object_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Store `interaction` as a node plus one participant edge per
+    /// participant, in one transaction.  Returns the node's id.
+    ///
+    /// Errors — and writes nothing — when a participant does not exist or
+    /// is listed twice.
+    pub fn add_interaction(&self, interaction: &Interaction) -> Result<ObjectId> {
+        check_distinct(&interaction.participants)?;
+        let mut metadata =
+            ObjectMetadata::new(INTERACTION_TYPE.to_string(), interaction.name.clone());
+        metadata.id = interaction.id;
+        if let Some(kind) = &interaction.kind {
+            metadata.set_property("kind".to_string(), kind.clone());
+        }
+
+        let mut layer = StagingLayer::new("interaction");
+        layer.add_object(metadata);
+        for participant in &interaction.participants {
+            layer.add_edge(participant_edge(interaction.id, participant));
+        }
+        self.commit_staging(layer)?;
+        Ok(interaction.id)
+    }
+
+    /// The interaction stored as node `id`, or `None` if there is no such
+    /// node or it is not an interaction.
+    pub fn get_interaction(&self, id: ObjectId) -> Result<Option<Interaction>> {
+        match self.get_object(id)? {
+            Some(node) if node.object_type == INTERACTION_TYPE => {
+                self.interaction_from_node(node).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the participants of interaction `id`: edges to objects no
+    /// longer listed are removed, the rest are written with their new roles,
+    /// all in one transaction.
+    pub fn set_participants(&self, id: ObjectId, participants: &[Participant]) -> Result<()> {
+        check_distinct(participants)?;
+        let current = self
+            .get_interaction(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("No interaction with id {id}")))?;
+
+        let mut layer = StagingLayer::new("interaction");
+        for old in &current.participants {
+            if !participants.iter().any(|p| p.object_id == old.object_id) {
+                layer.delete_edge(id, old.object_id, PARTICIPANT_EDGE);
+            }
+        }
+        for participant in participants {
+            layer.add_edge(participant_edge(id, participant));
+        }
+        self.commit_staging(layer)?;
+        Ok(())
+    }
+
+    /// Add `object_id` to interaction `id` (or change its role if it already
+    /// takes part).
+    pub fn add_participant(&self, id: ObjectId, object_id: ObjectId, role: Option<&str>) -> Result<()> {
+        let mut interaction = self
+            .get_interaction(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("No interaction with id {id}")))?;
+        interaction.participants.retain(|p| p.object_id != object_id);
+        interaction.participants.push(Participant::new(object_id, role));
+        self.set_participants(id, &interaction.participants)
+    }
+
+    /// Remove `object_id` from interaction `id`.  Returns whether it took part.
+    pub fn remove_participant(&self, id: ObjectId, object_id: ObjectId) -> Result<bool> {
+        let mut interaction = self
+            .get_interaction(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("No interaction with id {id}")))?;
+        let before = interaction.participants.len();
+        interaction.participants.retain(|p| p.object_id != object_id);
+        if interaction.participants.len() == before {
+            return Ok(false);
+        }
+        self.set_participants(id, &interaction.participants)?;
+        Ok(true)
+    }
+
+    /// Every interaction `object_id` takes part in, ordered by name.
+    pub fn interactions_involving(&self, object_id: ObjectId) -> Result<Vec<Interaction>> {
+        let mut out = Vec::new();
+        for edge in self.get_relationships(object_id)? {
+            if edge.to != object_id || edge.edge_type.as_str() != PARTICIPANT_EDGE {
+                continue;
+            }
+            if let Some(interaction) = self.get_interaction(edge.from)? {
+                out.push(interaction);
+            }
+        }
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(out)
+    }
+
+    /// Interactions in which every one of `objects` takes part, e.g. every
+    /// battle fought between two factions.
+    pub fn interactions_between(&self, objects: &[ObjectId]) -> Result<Vec<Interaction>> {
+        let Some((first, rest)) = objects.split_first() else {
+            return Ok(Vec::new());
+        };
+        Ok(self
+            .interactions_involving(*first)?
+            .into_iter()
+            .filter(|i| rest.iter().all(|o| i.involves(*o)))
+            .collect())
+    }
+
+    fn interaction_from_node(&self, node: ObjectMetadata) -> Result<Interaction> {
+        let mut participants: Vec<Participant> = self
+            .get_relationships(node.id)?
+            .into_iter()
+            .filter(|e| e.from == node.id && e.edge_type.as_str() == PARTICIPANT_EDGE)
+            .map(|e| Participant {
+                object_id: e.to,
+                role: e.metadata.get(ROLE_KEY).cloned(),
+            })
+            .collect();
+        // Stable order for callers that compare or display participants.
+        participants.sort_by(|a, b| {
+            (a.role.as_deref(), a.object_id.0).cmp(&(b.role.as_deref(), b.object_id.0))
+        });
+        Ok(Interaction {
+            kind: node.get_property("kind"),
+            id: node.id,
+            name: node.name,
+            participants,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_interaction_round_trip_and_queries() {
+        let (graph, _tmp) = create_test_graph();
+        let gondor = ObjectBuilder::faction("Gondor".to_string()).add_to_graph(&graph).unwrap();
+        let rohan = ObjectBuilder::faction("Rohan".to_string()).add_to_graph(&graph).unwrap();
+        let mordor = ObjectBuilder::faction("Mordor".to_string()).add_to_graph(&graph).unwrap();
+        let fields = ObjectBuilder::location("Pelennor Fields".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let battle = Interaction::new("Battle of the Pelennor Fields")
+            .with_kind("battle")
+            .with_participant(gondor, "defender")
+            .with_participant(rohan, "defender")
+            .with_participant(mordor, "attacker")
+            .with_participant(fields, "site");
+        let id = graph.add_interaction(&battle).unwrap();
+
+        let stored = graph.get_interaction(id).unwrap().unwrap();
+        assert_eq!(stored.kind.as_deref(), Some("battle"));
+        assert_eq!(stored.participants.len(), 4);
+        assert_eq!(stored.role_of(mordor), Some("attacker"));
+        assert_eq!(stored.participants_with_role("Defender").len(), 2);
+
+        assert_eq!(graph.interactions_involving(fields).unwrap().len(), 1);
+        assert_eq!(graph.interactions_between(&[gondor, mordor]).unwrap().len(), 1);
+        assert!(graph.get_interaction(gondor).unwrap().is_none());
+
+        // Participant edges are managed with the interaction.
+        graph.add_participant(id, rohan, Some("reinforcement")).unwrap();
+        assert!(graph.remove_participant(id, fields).unwrap());
+        assert!(!graph.remove_participant(id, fields).unwrap());
+        let stored = graph.get_interaction(id).unwrap().unwrap();
+        assert_eq!(stored.role_of(rohan), Some("reinforcement"));
+        assert!(graph.interactions_involving(fields).unwrap().is_empty());
+
+        graph.delete_object(id).unwrap();
+        assert!(graph.interactions_involving(gondor).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interaction_rejects_bad_participants() {
+        let (graph, _tmp) = create_test_graph();
+        let gondor = ObjectBuilder::faction("Gondor".to_string()).add_to_graph(&graph).unwrap();
+
+        let twice = Interaction::new("Council")
+            .with_participant(gondor, "host")
+            .with_participant(gondor, "guest");
+        assert!(graph.add_interaction(&twice).is_err());
+
+        let missing = Interaction::new("Parley")
+            .with_participant(gondor, "host")
+            .with_participant(ObjectId::new_v4(), "guest");
+        let id = missing.id;
+        assert!(graph.add_interaction(&missing).is_err());
+        assert!(graph.get_object(id).unwrap().is_none());
+    }
+}
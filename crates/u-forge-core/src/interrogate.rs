@@ -0,0 +1,380 @@
+//! Question answering scoped to one object ("interrogate this NPC's file").
+//!
+//! A world-wide RAG turn searches the whole graph, so "what does Director
+//! Chen know about the artifact?" can come back with the artifact's lore
+//! and three unrelated scientists.  [`ask_about`] instead answers from a
+//! fixed scope: the object's own chunks, a card of its properties and
+//! relationships, and its immediate neighbours.  Neighbours the question
+//! names are ranked first, so "the artifact" is pulled in when it is linked
+//! to Chen and ignored when it is not.
+//!
+//! ```text
+//! ask_about(graph, queue, object_id, question, config)
+//!     → object_context(...)          // scope → build_context → AssembledContext
+//!     → build_rag_messages(...)      // file-only system prompt
+//!     → InferenceQueue::generate
+//! ```
+//!
+//! Like [`detect_contradictions`](crate::consistency::detect_contradictions),
+//! the queue is a parameter — [`KnowledgeGraph`] itself has no AI dependency.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::context_builder::{
+    build_context, AssembledContext, ContextBuilderConfig, ContextCitation,
+};
+use crate::error::UForgeError;
+use crate::lemonade::ChatRequest;
+use crate::queue::InferenceQueue;
+use crate::rag::{build_rag_messages, RagContext};
+use crate::search::{ConnectedNode, NodeSearchResult, SearchSources};
+use crate::types::{ChunkType, Edge, ObjectId, ObjectMetadata, TextChunk};
+use crate::KnowledgeGraph;
+
+const SYSTEM_PROMPT: &str = "You answer questions about one entity in a tabletop RPG \
+campaign, using only its file below: the entity's own notes first, then notes on the \
+things directly connected to it. When asked what the entity knows, believes, or wants, \
+answer from its point of view as the file describes it. If the file does not say, reply \
+that the file does not say — do not invent details. Cite statements with their [n] markers.";
+
+/// Tuning knobs for [`ask_about`] and [`object_context`].
+#[derive(Debug, Clone)]
+pub struct AskAboutConfig {
+    /// Budget and ranking for the assembled file.  The subject is scored as
+    /// the best hit, so its chunks are preferred over its neighbours'.
+    pub context: ContextBuilderConfig,
+
+    /// Upper bound on neighbours brought into scope.  Neighbours named in
+    /// the question are taken first, then the rest by name.
+    pub max_neighbors: usize,
+
+    /// Answer a player rather than the GM: the scope is passed through
+    /// [`KnowledgeGraph::redact_search_results`], and a subject players may
+    /// not see is reported as not found.
+    pub player_view: bool,
+}
+
+impl Default for AskAboutConfig {
+    fn default() -> Self {
+        Self {
+            context: ContextBuilderConfig {
+                max_chunks_per_object: 6,
+                ..ContextBuilderConfig::default()
+            },
+            max_neighbors: 12,
+            player_view: false,
+        }
+    }
+}
+
+/// Reply from [`ask_about`].
+#[derive(Debug, Clone)]
+pub struct ObjectAnswer {
+    pub object_id: ObjectId,
+    pub answer: String,
+    /// Sources for the `[n]` markers in `answer`.
+    pub citations: Vec<ContextCitation>,
+    /// Objects (subject included) that contributed context.
+    pub object_count: usize,
+}
+
+/// Answer `question` from `object_id`'s file and its immediate
+/// neighbourhood.
+///
+/// Errors with [`UForgeError::NotFound`] when the object does not exist and
+/// [`UForgeError::InferenceUnavailable`] when the queue has no
+/// text-generation worker.
+pub async fn ask_about(
+    graph: &KnowledgeGraph,
+    queue: &InferenceQueue,
+    object_id: ObjectId,
+    question: &str,
+    config: &AskAboutConfig,
+) -> Result<ObjectAnswer> {
+    let context = object_context(graph, object_id, question, config)?;
+    if !queue.has_text_generation() {
+        return Err(UForgeError::InferenceUnavailable(
+            "Asking about an object requires a text-generation worker".to_string(),
+        )
+        .into());
+    }
+
+    let citations = context.citations.clone();
+    let object_count = context.object_count;
+    let messages = build_rag_messages(SYSTEM_PROMPT, &RagContext::from(context), &[], 0, question);
+    let response = queue
+        .generate(ChatRequest::new(messages).with_temperature(0.2))
+        .await?;
+    let answer = response
+        .first_content()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| anyhow!("LLM response contained no choices"))?;
+
+    Ok(ObjectAnswer {
+        object_id,
+        answer,
+        citations,
+        object_count,
+    })
+}
+
+/// The token-budgeted file [`ask_about`] sends to the model: the subject's
+/// chunks and property card, then its neighbours' chunks.
+pub fn object_context(
+    graph: &KnowledgeGraph,
+    object_id: ObjectId,
+    question: &str,
+    config: &AskAboutConfig,
+) -> Result<AssembledContext> {
+    let scope = object_scope(
+        graph,
+        object_id,
+        question,
+        config.max_neighbors,
+        config.player_view,
+    )?;
+    Ok(build_context(question, &scope, &config.context))
+}
+
+/// The subject (score 1.0) followed by up to `max_neighbors` neighbours —
+/// those named in `question` at 0.8, the rest at 0.4 — as search results.
+/// With `player_view`, everything is redacted for players.
+fn object_scope(
+    graph: &KnowledgeGraph,
+    object_id: ObjectId,
+    question: &str,
+    max_neighbors: usize,
+    player_view: bool,
+) -> Result<Vec<NodeSearchResult>> {
+    let not_found = || UForgeError::NotFound(format!("Object {object_id} not found"));
+    let subject = graph.get_object(object_id)?.ok_or_else(not_found)?;
+
+    let mut subject_result = hydrate(graph, subject, 1.0)?;
+    if player_view {
+        subject_result = graph
+            .redact_search_results(vec![subject_result])?
+            .pop()
+            .ok_or_else(not_found)?;
+    }
+    // The property card goes first so the model always sees the structured
+    // fields, even when the description chunks predate the last edit.
+    let card = TextChunk::new(
+        object_id,
+        subject_result
+            .node
+            .flatten_for_embedding(&edge_lines(&subject_result)),
+        ChunkType::Description,
+    );
+    subject_result.chunks.insert(0, card);
+
+    let question_lower = question.to_lowercase();
+    let mut neighbours: Vec<(bool, ObjectId)> = Vec::new();
+    for (id, connected) in &subject_result.connected_node_names {
+        let named = question_lower.contains(&connected.name.to_lowercase());
+        neighbours.push((named, *id));
+    }
+    // Named first, then by name so the cut is deterministic.
+    neighbours.sort_by(|a, b| {
+        b.0.cmp(&a.0).then_with(|| {
+            subject_result.connected_node_names[&a.1]
+                .name
+                .cmp(&subject_result.connected_node_names[&b.1].name)
+        })
+    });
+    neighbours.truncate(max_neighbors);
+
+    let mut scope = Vec::with_capacity(neighbours.len() + 1);
+    for (named, id) in neighbours {
+        if let Some(node) = graph.get_object(id)? {
+            scope.push(hydrate(graph, node, if named { 0.8 } else { 0.4 })?);
+        }
+    }
+    if player_view {
+        scope = graph.redact_search_results(scope)?;
+    }
+    scope.insert(0, subject_result);
+    Ok(scope)
+}
+
+/// Load chunks, edges, and connected-node names for `node`.
+pub(crate) fn hydrate(
+    graph: &KnowledgeGraph,
+    node: ObjectMetadata,
+    score: f32,
+) -> Result<NodeSearchResult> {
+    let chunks = graph.get_text_chunks(node.id)?;
+    let edges = graph.get_relationships(node.id)?;
+    let mut connected_node_names = HashMap::new();
+    for edge in &edges {
+        let other = if edge.from == node.id {
+            edge.to
+        } else {
+            edge.from
+        };
+        if connected_node_names.contains_key(&other) {
+            continue;
+        }
+        if let Some(meta) = graph.get_object(other)? {
+            connected_node_names.insert(
+                other,
+                ConnectedNode {
+                    name: meta.name,
+                    object_type: meta.object_type,
+                },
+            );
+        }
+    }
+    Ok(NodeSearchResult {
+        node,
+        chunks,
+        edges,
+        connected_node_names,
+        score,
+        sources: SearchSources::default(),
+    })
+}
+
+/// `"<from> <edge_type> <to>"` lines for the subject's property card.
+fn edge_lines(result: &NodeSearchResult) -> Vec<String> {
+    let name = |id: ObjectId| -> Option<String> {
+        if id == result.node.id {
+            Some(result.node.name.clone())
+        } else {
+            result.connected_node_names.get(&id).map(|c| c.name.clone())
+        }
+    };
+    result
+        .edges
+        .iter()
+        .filter_map(|e: &Edge| {
+            Some(format!(
+                "{} {} {}",
+                name(e.from)?,
+                e.edge_type.as_str(),
+                name(e.to)?
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::queue::InferenceQueueBuilder;
+    use crate::test_helpers::create_test_graph;
+    use crate::VISIBILITY_KEY;
+
+    fn npc(graph: &KnowledgeGraph, name: &str, note: &str) -> ObjectId {
+        let id = graph
+            .add_object(
+                ObjectMetadata::new("npc".to_string(), name.to_string())
+                    .with_property("rank".to_string(), "Director".to_string()),
+            )
+            .unwrap();
+        graph
+            .add_text_chunk(id, note.to_string(), ChunkType::Description)
+            .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_object_context_covers_subject_and_named_neighbours() {
+        let (graph, _temp_dir) = create_test_graph();
+        let chen = npc(
+            &graph,
+            "Director Chen",
+            "Chen has read the excavation reports.",
+        );
+        let artifact = npc(&graph, "The Artifact", "A humming obsidian disc.");
+        let stranger = npc(&graph, "Stranger", "Unconnected to Chen.");
+        graph
+            .connect_objects_str(chen, artifact, "studies")
+            .unwrap();
+
+        let config = AskAboutConfig::default();
+        let ctx = object_context(
+            &graph,
+            chen,
+            "What does Chen know about the artifact?",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(ctx.object_count, 2);
+        assert_eq!(ctx.citations[0].object_id, chen);
+        assert!(ctx.text.contains("rank: Director"));
+        assert!(ctx.text.contains("Director Chen studies The Artifact"));
+        assert!(ctx.text.contains("obsidian disc"));
+        assert!(ctx.citations.iter().all(|c| c.object_id != stranger));
+
+        let no_neighbours = AskAboutConfig {
+            max_neighbors: 0,
+            ..AskAboutConfig::default()
+        };
+        let ctx = object_context(&graph, chen, "anything", &no_neighbours).unwrap();
+        assert_eq!(ctx.object_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ask_about_errors() {
+        let (graph, _temp_dir) = create_test_graph();
+        let queue = InferenceQueueBuilder::new().build();
+        let config = AskAboutConfig::default();
+
+        let err = ask_about(&graph, &queue, ObjectId::new_v4(), "who?", &config)
+            .await
+            .unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::NotFound);
+
+        let chen = npc(&graph, "Director Chen", "Chen runs Site 9.");
+        let err = ask_about(&graph, &queue, chen, "who?", &config)
+            .await
+            .unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::InferenceUnavailable);
+    }
+
+    #[test]
+    fn test_object_context_player_view_redacts() {
+        let (graph, _temp_dir) = create_test_graph();
+        let chen = graph
+            .add_object(
+                ObjectMetadata::new("npc".to_string(), "Director Chen".to_string())
+                    .with_property("rank".to_string(), "Director".to_string())
+                    .with_property("secrets".to_string(), "Chen is a cultist.".to_string()),
+            )
+            .unwrap();
+        graph
+            .add_text_chunk(
+                chen,
+                "GM: Chen betrays the party.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap();
+        let mole = graph
+            .add_object(
+                ObjectMetadata::new("npc".to_string(), "The Mole".to_string())
+                    .with_property(VISIBILITY_KEY.to_string(), "gm".to_string()),
+            )
+            .unwrap();
+        graph.connect_objects_str(mole, chen, "reports_to").unwrap();
+
+        let gm = object_context(&graph, chen, "Who is Chen?", &AskAboutConfig::default()).unwrap();
+        assert!(gm.text.contains("betrays") && gm.text.contains("The Mole"));
+
+        let config = AskAboutConfig {
+            player_view: true,
+            ..AskAboutConfig::default()
+        };
+        let ctx = object_context(&graph, chen, "Who is Chen?", &config).unwrap();
+        assert_eq!(ctx.object_count, 1);
+        assert!(ctx.text.contains("rank: Director"));
+        for leak in ["cultist", "betrays", "The Mole"] {
+            assert!(!ctx.text.contains(leak), "leaked {leak}: {}", ctx.text);
+        }
+
+        let err = object_context(&graph, mole, "Who?", &config).unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::NotFound);
+    }
+}
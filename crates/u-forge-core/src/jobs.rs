@@ -0,0 +1,523 @@
+//! Background jobs — one queue for embedding, re-indexing, imports, exports,
+//! and analysis.
+//!
+//! A [`JobManager`] owns every job the app has been asked to run.  Callers
+//! [`submit`](JobManager::submit) a [`JobSpec`] (kind, label, priority, and a
+//! JSON payload describing the work); a runner loop takes the next job with
+//! [`next_job`](JobManager::next_job) or
+//! [`wait_for_job`](JobManager::wait_for_job), matches on its [`JobKind`],
+//! and executes it with the returned [`JobHandle`] as its [`ProgressSink`] —
+//! for embedding that means passing it to
+//! [`EmbeddingPlan::execute`](crate::ingest::EmbeddingPlan::execute).
+//! [`JobHandle::finish`] records the outcome.
+//!
+//! Higher priorities run first; within a priority, older jobs first.
+//! [`cancel`](JobManager::cancel) drops a queued job at once and fires the
+//! cancellation token of a running one, which the work observes between
+//! items.
+//!
+//! Every state change is saved to the `jobs` table, so queued work survives a
+//! restart.  A job that was running when the app stopped is queued again on
+//! the next [`JobManager::new`].  All changes, including progress, are sent
+//! as [`Job`] snapshots on one broadcast channel
+//! ([`subscribe`](JobManager::subscribe)) for a jobs panel to render.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Notify};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::{ErrorKind, UForgeError};
+use crate::progress::{CancellationToken, Progress, ProgressEvent, ProgressSink};
+use crate::KnowledgeGraph;
+
+/// Capacity of the [`JobManager`] event channel.
+const JOB_EVENT_CAPACITY: usize = 256;
+
+/// Identifier of a submitted job.
+pub type JobId = Uuid;
+
+/// What a job does; the runner dispatches on this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Embed chunks and profiles (an [`EmbeddingPlan`](crate::ingest::EmbeddingPlan)).
+    Embedding,
+    /// Re-chunk and re-embed specific nodes.
+    Reindex,
+    /// Import a data file.
+    Import,
+    /// Export the project.
+    Export,
+    /// Long-running analysis, e.g. a consistency or link-prediction pass.
+    Analysis,
+}
+
+/// Scheduling priority; higher runs first.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Background fill-in work.
+    Low,
+    #[default]
+    Normal,
+    /// Work the user is waiting on.
+    High,
+}
+
+/// Where a job is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    /// Stored form.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Completed => "completed",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the job will not run (again).
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+/// A job to submit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobSpec {
+    pub kind: JobKind,
+    /// Shown in the jobs panel, e.g. `"Import lore.json"`.
+    pub label: String,
+    pub priority: JobPriority,
+    /// Kind-specific parameters for the runner, e.g. node ids or a path.
+    pub payload: serde_json::Value,
+}
+
+impl JobSpec {
+    pub fn new(kind: JobKind, label: impl Into<String>) -> Self {
+        Self {
+            kind,
+            label: label.into(),
+            priority: JobPriority::default(),
+            payload: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: JobPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+}
+
+/// A submitted job and its latest state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub label: String,
+    pub priority: JobPriority,
+    pub payload: serde_json::Value,
+    pub state: JobState,
+    /// Latest progress report of a running job.  Not persisted.
+    #[serde(skip_deserializing)]
+    pub progress: Option<ProgressEvent>,
+    /// Why a failed job failed.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+struct Entry {
+    job: Job,
+    cancel: CancellationToken,
+    /// Whole percent of the last progress event sent, to throttle events.
+    last_percent: Option<(String, u32)>,
+}
+
+struct Shared {
+    graph: Arc<KnowledgeGraph>,
+    jobs: Mutex<HashMap<JobId, Entry>>,
+    notify: Notify,
+    events: broadcast::Sender<Job>,
+}
+
+/// The app's background job queue.  Clones share the same queue.
+#[derive(Clone)]
+pub struct JobManager {
+    shared: Arc<Shared>,
+}
+
+impl JobManager {
+    /// Load the jobs saved in `graph`.  Jobs that were running when the app
+    /// stopped are queued again.
+    pub fn new(graph: Arc<KnowledgeGraph>) -> Result<Self> {
+        let mut jobs = HashMap::new();
+        let mut requeued = Vec::new();
+        for record in graph.storage.load_jobs()? {
+            let mut job: Job = match serde_json::from_str(&record) {
+                Ok(job) => job,
+                Err(e) => {
+                    warn!(%e, "Skipping unreadable job record");
+                    continue;
+                }
+            };
+            if job.state == JobState::Running {
+                job.state = JobState::Queued;
+                requeued.push(job.clone());
+            }
+            jobs.insert(
+                job.id,
+                Entry {
+                    job,
+                    cancel: CancellationToken::new(),
+                    last_percent: None,
+                },
+            );
+        }
+        let (events, _) = broadcast::channel(JOB_EVENT_CAPACITY);
+        let manager = Self {
+            shared: Arc::new(Shared {
+                graph,
+                jobs: Mutex::new(jobs),
+                notify: Notify::new(),
+                events,
+            }),
+        };
+        for job in &requeued {
+            manager.save(job)?;
+        }
+        Ok(manager)
+    }
+
+    /// Queue `spec`.  Returns the new job's id.
+    pub fn submit(&self, spec: JobSpec) -> Result<JobId> {
+        let job = Job {
+            id: Uuid::new_v4(),
+            kind: spec.kind,
+            label: spec.label,
+            priority: spec.priority,
+            payload: spec.payload,
+            state: JobState::Queued,
+            progress: None,
+            error: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        self.save(&job)?;
+        self.shared.jobs.lock().insert(
+            job.id,
+            Entry {
+                job: job.clone(),
+                cancel: CancellationToken::new(),
+                last_percent: None,
+            },
+        );
+        self.emit(job.clone());
+        self.shared.notify.notify_one();
+        Ok(job.id)
+    }
+
+    /// Every known job: running first, then queued in run order, then
+    /// finished ones newest first.
+    pub fn jobs(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .shared
+            .jobs
+            .lock()
+            .values()
+            .map(|e| e.job.clone())
+            .collect();
+        jobs.sort_by(|a, b| {
+            let rank = |job: &Job| match job.state {
+                JobState::Running => 0,
+                JobState::Queued => 1,
+                _ => 2,
+            };
+            rank(a).cmp(&rank(b)).then_with(|| {
+                if a.state.is_finished() {
+                    b.finished_at.cmp(&a.finished_at)
+                } else {
+                    run_order(a, b)
+                }
+            })
+        });
+        jobs
+    }
+
+    pub fn job(&self, id: JobId) -> Option<Job> {
+        self.shared.jobs.lock().get(&id).map(|e| e.job.clone())
+    }
+
+    /// Cancel job `id`: a queued job is marked cancelled at once, a running
+    /// one is asked to stop.  Returns `false` when the job had already
+    /// finished.  Fails with [`UForgeError::NotFound`] for an unknown id.
+    pub fn cancel(&self, id: JobId) -> Result<bool> {
+        let job = {
+            let mut jobs = self.shared.jobs.lock();
+            let entry = jobs
+                .get_mut(&id)
+                .ok_or_else(|| UForgeError::NotFound(format!("Unknown job {id}")))?;
+            match entry.job.state {
+                JobState::Queued => {
+                    entry.job.state = JobState::Cancelled;
+                    entry.job.finished_at = Some(Utc::now());
+                    entry.job.clone()
+                }
+                JobState::Running => {
+                    entry.cancel.cancel();
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            }
+        };
+        self.save(&job)?;
+        self.emit(job);
+        Ok(true)
+    }
+
+    /// Forget every finished job.  Returns how many were removed.
+    pub fn clear_finished(&self) -> Result<usize> {
+        self.shared
+            .jobs
+            .lock()
+            .retain(|_, e| !e.job.state.is_finished());
+        Ok(self.shared.graph.storage.delete_jobs_in_states(&[
+            JobState::Completed.as_str(),
+            JobState::Failed.as_str(),
+            JobState::Cancelled.as_str(),
+        ])?)
+    }
+
+    /// Receive a [`Job`] snapshot on every submission, state change, and
+    /// progress step (throttled to one per whole percent per stage).
+    /// Receivers that fall more than 256 events behind get
+    /// [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<Job> {
+        self.shared.events.subscribe()
+    }
+
+    /// Start the next queued job, if any, marking it running.
+    pub fn next_job(&self) -> Result<Option<JobHandle>> {
+        let started = {
+            let mut jobs = self.shared.jobs.lock();
+            let next = jobs
+                .values()
+                .filter(|e| e.job.state == JobState::Queued)
+                .min_by(|a, b| run_order(&a.job, &b.job))
+                .map(|e| e.job.id);
+            next.and_then(|id| jobs.get_mut(&id)).map(|entry| {
+                entry.job.state = JobState::Running;
+                (entry.job.clone(), entry.cancel.clone())
+            })
+        };
+        let Some((job, cancel)) = started else {
+            return Ok(None);
+        };
+        self.save(&job)?;
+        self.emit(job.clone());
+        Ok(Some(JobHandle {
+            manager: self.clone(),
+            job,
+            cancel,
+        }))
+    }
+
+    /// Wait until a job is queued, then start it.
+    pub async fn wait_for_job(&self) -> Result<JobHandle> {
+        loop {
+            let notified = self.shared.notify.notified();
+            if let Some(handle) = self.next_job()? {
+                return Ok(handle);
+            }
+            notified.await;
+        }
+    }
+
+    fn save(&self, job: &Job) -> Result<()> {
+        let record = serde_json::to_string(job)?;
+        Ok(self.shared.graph.storage.save_job(
+            &job.id.to_string(),
+            job.state.as_str(),
+            &record,
+            &job.created_at.to_rfc3339(),
+        )?)
+    }
+
+    fn emit(&self, job: Job) {
+        // No subscribers is fine: the state was still saved.
+        let _ = self.shared.events.send(job);
+    }
+}
+
+/// Queued jobs in the order they run: higher priority, then older.
+fn run_order(a: &Job, b: &Job) -> std::cmp::Ordering {
+    b.priority
+        .cmp(&a.priority)
+        .then_with(|| a.created_at.cmp(&b.created_at))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// A running job, handed to the code executing it.  Reports progress to the
+/// manager and carries the job's cancellation token.
+pub struct JobHandle {
+    manager: JobManager,
+    job: Job,
+    cancel: CancellationToken,
+}
+
+impl JobHandle {
+    /// The job as it was when it started.
+    pub fn job(&self) -> &Job {
+        &self.job
+    }
+
+    /// Record how the job ended.  An [`UForgeError::Cancelled`] error, or
+    /// any outcome after cancellation was requested, counts as cancelled.
+    pub fn finish(self, outcome: Result<()>) -> Result<Job> {
+        let job = {
+            let mut jobs = self.manager.shared.jobs.lock();
+            let entry = jobs
+                .get_mut(&self.job.id)
+                .ok_or_else(|| UForgeError::NotFound(format!("Unknown job {}", self.job.id)))?;
+            let (state, error) = match outcome {
+                _ if self.cancel.is_cancelled() => (JobState::Cancelled, None),
+                Ok(()) => (JobState::Completed, None),
+                Err(e) if UForgeError::kind_of(&e) == ErrorKind::Cancelled => {
+                    (JobState::Cancelled, None)
+                }
+                Err(e) => (JobState::Failed, Some(format!("{e:#}"))),
+            };
+            entry.job.state = state;
+            entry.job.error = error;
+            entry.job.finished_at = Some(Utc::now());
+            entry.job.clone()
+        };
+        self.manager.save(&job)?;
+        self.manager.emit(job.clone());
+        Ok(job)
+    }
+}
+
+impl ProgressSink for JobHandle {
+    fn report(&self, progress: &Progress) {
+        let event = ProgressEvent::from(progress);
+        let job = {
+            let mut jobs = self.manager.shared.jobs.lock();
+            let Some(entry) = jobs.get_mut(&self.job.id) else {
+                return;
+            };
+            if let Some(percent) = event.percent {
+                let key = (event.stage.clone(), percent.floor() as u32);
+                if entry.last_percent.as_ref() == Some(&key) {
+                    return;
+                }
+                entry.last_percent = Some(key);
+            }
+            entry.job.progress = Some(event);
+            entry.job.clone()
+        };
+        self.manager.emit(job);
+    }
+
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        Some(&self.cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_jobs_run_by_priority_and_survive_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let (import, reindex) = {
+            let graph = Arc::new(KnowledgeGraph::new(temp_dir.path()).unwrap());
+            let manager = JobManager::new(graph).unwrap();
+            let mut events = manager.subscribe();
+            let embed = manager
+                .submit(JobSpec::new(JobKind::Embedding, "Embedding…").with_priority(JobPriority::Low))
+                .unwrap();
+            let import = manager
+                .submit(
+                    JobSpec::new(JobKind::Import, "Import lore.json")
+                        .with_priority(JobPriority::High)
+                        .with_payload(serde_json::json!({ "path": "lore.json" })),
+                )
+                .unwrap();
+            let reindex = manager
+                .submit(JobSpec::new(JobKind::Reindex, "Re-embedding 2 node(s)…"))
+                .unwrap();
+
+            let handle = manager.next_job().unwrap().unwrap();
+            assert_eq!(handle.job().id, import);
+            handle.report(&Progress::new("objects", 1, Some(2)));
+            assert_eq!(
+                manager.job(import).unwrap().progress.unwrap().percent,
+                Some(50.0)
+            );
+
+            // A queued job is dropped at once; the running one is asked to stop.
+            assert!(manager.cancel(embed).unwrap());
+            assert_eq!(manager.job(embed).unwrap().state, JobState::Cancelled);
+            assert!(manager.cancel(import).unwrap());
+            assert!(handle.is_cancelled());
+            assert_eq!(manager.jobs()[0].id, import);
+
+            // Three submissions, a start, a progress step, a cancellation.
+            let mut seen = 0;
+            while events.try_recv().is_ok() {
+                seen += 1;
+            }
+            assert_eq!(seen, 6);
+            (import, reindex)
+        };
+
+        // The "crash" left the import running and the re-index queued.
+        let graph = Arc::new(KnowledgeGraph::new(temp_dir.path()).unwrap());
+        let manager = JobManager::new(graph).unwrap();
+        assert_eq!(manager.job(import).unwrap().state, JobState::Queued);
+        let handle = manager.next_job().unwrap().unwrap();
+        assert_eq!(handle.job().id, import);
+        let finished = handle.finish(Ok(())).unwrap();
+        assert_eq!(finished.state, JobState::Completed);
+
+        let handle = manager.next_job().unwrap().unwrap();
+        assert_eq!(handle.job().id, reindex);
+        let failed = handle.finish(Err(anyhow::anyhow!("server gone"))).unwrap();
+        assert_eq!(failed.error.as_deref(), Some("server gone"));
+        assert!(manager.next_job().unwrap().is_none());
+
+        assert_eq!(manager.clear_finished().unwrap(), 3);
+        assert!(manager.jobs().is_empty());
+    }
+}
@@ -7,6 +7,7 @@
 //! | [`LemonadeServerCatalog`]    | —        | Discovers all models        |
 //! | [`ModelSelector`]            | —        | Selects models by capability|
 //! | [`ProviderFactory`]          | —        | Builds live providers       |
+//! | [`ModelManager`]             | —        | Downloads / deletes models  |
 //! | [`LemonadeTtsProvider`]      | CPU      | `kokoro-v1`                 |
 //! | [`LemonadeSttProvider`]      | GPU      | `Whisper-Large-v3-Turbo`    |
 //! | [`LemonadeChatProvider`]     | GPU/NPU  | llamacpp / FLM models       |
@@ -29,6 +30,7 @@ pub mod embedding;
 pub mod gpu_manager;
 pub mod health;
 pub mod load;
+pub mod models;
 pub mod provider_factory;
 pub mod rerank;
 pub mod selector;
@@ -54,6 +56,7 @@ pub use transcription::LemonadeTranscriptionProvider;
 pub use health::{LemonadeHealth, LoadedModelEntry};
 pub use gpu_manager::{GpuResourceManager, GpuWorkload, LlmGuard, SttGuard};
 pub use load::{load_model, ModelLoadOptions};
+pub use models::{ManagedModel, ModelManager, ModelState, DEFAULT_DOWNLOAD_ATTEMPTS};
 pub use rerank::{LemonadeRerankProvider, RerankDocument};
 pub use stt::{LemonadeSttProvider, TranscriptionResult};
 pub use system_info::{RecipeBackendInfo, SystemDeviceInfo, SystemInfo};
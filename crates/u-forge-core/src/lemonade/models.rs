@@ -0,0 +1,381 @@
+//! Explicit model acquisition through Lemonade Server.
+//!
+//! The provider factory and [`ModelSelector`](super::ModelSelector) only use
+//! models that are already downloaded; getting weights onto the machine was
+//! left to `lemonade-server pull` on the command line.  [`ModelManager`]
+//! exposes that step to the app:
+//!
+//! * [`list`](ModelManager::list) — every registered model with its
+//!   [`ModelState`] (available, downloaded, or loaded).
+//! * [`download`](ModelManager::download) — `POST /pull` with `stream: true`,
+//!   forwarding the server's per-file byte counts to a
+//!   [`ProgressSink`].  An interrupted stream is retried; the server keeps
+//!   partially downloaded files and continues from them, so a retry resumes
+//!   rather than restarts.  The download is verified before returning: every
+//!   file must have reported all of its bytes, and the server must list the
+//!   model as downloaded afterwards (its downloader checks the repository's
+//!   file hashes before marking a file complete).
+//! * [`delete`](ModelManager::delete) — `POST /delete`, freeing the disk space.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::catalog::{CatalogModel, LemonadeServerCatalog};
+use super::client::LemonadeHttpClient;
+use crate::error::UForgeError;
+use crate::progress::{Progress, ProgressSink};
+
+/// Attempts made by [`ModelManager::download`] before giving up on a
+/// download whose stream keeps breaking.
+pub const DEFAULT_DOWNLOAD_ATTEMPTS: usize = 3;
+
+/// Progress stage name used for download reports.
+pub const DOWNLOAD_STAGE: &str = "download";
+
+/// Where a model is in its lifecycle on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelState {
+    /// Registered with the server but not downloaded.
+    Available,
+    /// Weights are on disk.
+    Downloaded,
+    /// Downloaded and currently serving requests.
+    Loaded,
+}
+
+/// One entry of [`ModelManager::list`].
+#[derive(Debug, Clone)]
+pub struct ManagedModel {
+    pub model: CatalogModel,
+    pub state: ModelState,
+}
+
+// ── Wire format ───────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+struct PullRequest<'a> {
+    model_name: &'a str,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct DeleteRequest<'a> {
+    model_name: &'a str,
+}
+
+/// Payload of a `progress` or `complete` event from `POST /pull`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct PullProgress {
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    file_index: usize,
+    #[serde(default)]
+    total_files: usize,
+    #[serde(default)]
+    bytes_downloaded: u64,
+    #[serde(default)]
+    bytes_total: u64,
+}
+
+/// One server-sent event from `POST /pull`.
+#[derive(Debug, Clone, PartialEq)]
+enum PullEvent {
+    Progress(PullProgress),
+    Complete,
+    Error(String),
+}
+
+/// Parse one SSE event (its `event:` name and `data:` payload).  Unknown
+/// event names are ignored.
+fn parse_pull_event(event: &str, data: &str) -> Option<PullEvent> {
+    match event {
+        "progress" => serde_json::from_str(data).ok().map(PullEvent::Progress),
+        "complete" => Some(PullEvent::Complete),
+        "error" => Some(PullEvent::Error(
+            serde_json::from_str::<serde_json::Value>(data)
+                .ok()
+                .and_then(|v| v.get("error")?.as_str().map(String::from))
+                .unwrap_or_else(|| data.to_string()),
+        )),
+        _ => None,
+    }
+}
+
+/// How one `/pull` stream ended.
+enum PullOutcome {
+    Complete,
+    /// The connection dropped before a `complete` event.
+    Interrupted(anyhow::Error),
+}
+
+// ── ModelManager ──────────────────────────────────────────────────────────────
+
+/// Lists, downloads, and deletes Lemonade Server models.
+#[derive(Debug, Clone)]
+pub struct ModelManager {
+    client: LemonadeHttpClient,
+    /// Separate client without an overall timeout — a multi-gigabyte pull
+    /// outlives the 30 s request timeout of [`LemonadeHttpClient`].
+    pull_client: reqwest::Client,
+    attempts: usize,
+}
+
+impl ModelManager {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: LemonadeHttpClient::new(base_url),
+            pull_client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            attempts: DEFAULT_DOWNLOAD_ATTEMPTS,
+        }
+    }
+
+    /// Set how many times a broken download stream is retried (at least one
+    /// attempt is always made).
+    pub fn with_attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Every model the server knows about, with its state.
+    pub async fn list(&self) -> Result<Vec<ManagedModel>> {
+        let catalog = LemonadeServerCatalog::discover(&self.client.base_url).await?;
+        Ok(catalog
+            .models
+            .iter()
+            .map(|model| ManagedModel {
+                state: if catalog.is_model_loaded(&model.id) {
+                    ModelState::Loaded
+                } else if model.downloaded {
+                    ModelState::Downloaded
+                } else {
+                    ModelState::Available
+                },
+                model: model.clone(),
+            })
+            .collect())
+    }
+
+    /// The state of `model_id`, or [`UForgeError::NotFound`] when the
+    /// server does not know it.
+    pub async fn state(&self, model_id: &str) -> Result<ModelState> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|m| m.model.id == model_id)
+            .map(|m| m.state)
+            .ok_or_else(|| {
+                UForgeError::NotFound(format!("Model '{model_id}' is not registered")).into()
+            })
+    }
+
+    /// Download `model_id`, reporting bytes as they arrive.  Returns at once
+    /// when it is already downloaded.  Cancelling `progress` stops the
+    /// download with [`UForgeError::Cancelled`]; the partial files are kept
+    /// and a later call resumes from them.
+    pub async fn download(&self, model_id: &str, progress: &dyn ProgressSink) -> Result<()> {
+        if self.state(model_id).await? != ModelState::Available {
+            return Ok(());
+        }
+
+        let mut last_err = None;
+        for attempt in 1..=self.attempts {
+            progress.check_cancelled()?;
+            if attempt > 1 {
+                progress.report(
+                    &Progress::new(DOWNLOAD_STAGE, 0, None)
+                        .with_message(format!("Resuming {model_id} (attempt {attempt})")),
+                );
+                tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            }
+            match self.pull_once(model_id, progress).await? {
+                PullOutcome::Complete => return self.verify(model_id).await,
+                PullOutcome::Interrupted(e) => {
+                    tracing::warn!(
+                        model = model_id,
+                        attempt,
+                        "Model download interrupted: {e:#}"
+                    );
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| anyhow::anyhow!("No download attempt was made"))
+            .context(format!("Failed to download model '{model_id}'")))
+    }
+
+    /// Delete `model_id`'s weights from the server.
+    pub async fn delete(&self, model_id: &str) -> Result<()> {
+        let _: serde_json::Value = self
+            .client
+            .post_json(
+                "/delete",
+                &DeleteRequest {
+                    model_name: model_id,
+                },
+            )
+            .await
+            .with_context(|| format!("Failed to delete model '{model_id}'"))?;
+        Ok(())
+    }
+
+    // ── Private helpers ───────────────────────────────────────────────────────
+
+    /// Run one streaming `/pull`.  Server-reported errors and cancellation
+    /// are returned as `Err`; a dropped connection as
+    /// [`PullOutcome::Interrupted`] so the caller can retry.
+    async fn pull_once(&self, model_id: &str, progress: &dyn ProgressSink) -> Result<PullOutcome> {
+        let url = format!("{}/pull", self.client.base_url);
+        let response = match self
+            .pull_client
+            .post(&url)
+            .header("Authorization", "Bearer lemonade")
+            .json(&PullRequest {
+                model_name: model_id,
+                stream: true,
+            })
+            .send()
+            .await
+        {
+            Ok(r) => r
+                .error_for_status()
+                .with_context(|| format!("POST {url} returned an error status"))?,
+            Err(e) => {
+                return Ok(PullOutcome::Interrupted(
+                    anyhow::Error::new(e).context(format!("POST {url} failed")),
+                ))
+            }
+        };
+
+        let mut line_buf = String::new();
+        let mut event = String::new();
+        // Latest (downloaded, total) bytes per file index.
+        let mut files: BTreeMap<usize, (u64, u64)> = BTreeMap::new();
+        let mut byte_stream = response.bytes_stream();
+        while let Some(chunk) = byte_stream.next().await {
+            progress.check_cancelled()?;
+            let bytes = match chunk {
+                Ok(b) => b,
+                Err(e) => {
+                    return Ok(PullOutcome::Interrupted(
+                        anyhow::Error::new(e).context("Download stream read error"),
+                    ))
+                }
+            };
+            line_buf.push_str(&String::from_utf8_lossy(&bytes));
+
+            while let Some(nl) = line_buf.find('\n') {
+                let line = line_buf[..nl].trim_end_matches('\r').to_string();
+                line_buf.drain(..=nl);
+
+                if let Some(name) = line.strip_prefix("event:") {
+                    event = name.trim().to_string();
+                    continue;
+                }
+                let Some(data) = line.strip_prefix("data:") else {
+                    continue;
+                };
+                match parse_pull_event(&event, data.trim()) {
+                    Some(PullEvent::Progress(p)) => {
+                        files.insert(p.file_index, (p.bytes_downloaded, p.bytes_total));
+                        let mut report = Progress::new(
+                            DOWNLOAD_STAGE,
+                            p.bytes_downloaded as usize,
+                            (p.bytes_total > 0).then_some(p.bytes_total as usize),
+                        );
+                        if let Some(file) = &p.file {
+                            report = report.with_message(format!(
+                                "{file} ({}/{})",
+                                p.file_index, p.total_files
+                            ));
+                        }
+                        progress.report(&report);
+                    }
+                    Some(PullEvent::Complete) => {
+                        if let Some((index, (done, total))) =
+                            files.iter().find(|(_, (done, total))| done < total)
+                        {
+                            anyhow::bail!(
+                                "Download of '{model_id}' reported complete with file {index} \
+                                 at {done} of {total} bytes"
+                            );
+                        }
+                        return Ok(PullOutcome::Complete);
+                    }
+                    Some(PullEvent::Error(message)) => {
+                        anyhow::bail!("Lemonade Server failed to download '{model_id}': {message}")
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(PullOutcome::Interrupted(anyhow::anyhow!(
+            "Download stream ended before completion"
+        )))
+    }
+
+    /// Confirm the server now lists `model_id` as downloaded.
+    async fn verify(&self, model_id: &str) -> Result<()> {
+        match self.state(model_id).await? {
+            ModelState::Available => Err(UForgeError::Internal(anyhow::anyhow!(
+                "Download of '{model_id}' finished but the server does not list it as downloaded"
+            ))
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+
+    #[test]
+    fn test_parse_pull_events() {
+        let event = parse_pull_event(
+            "progress",
+            r#"{"file":"model.gguf","file_index":1,"total_files":2,"bytes_downloaded":10,"bytes_total":40,"percent":25}"#,
+        );
+        assert_eq!(
+            event,
+            Some(PullEvent::Progress(PullProgress {
+                file: Some("model.gguf".to_string()),
+                file_index: 1,
+                total_files: 2,
+                bytes_downloaded: 10,
+                bytes_total: 40,
+            }))
+        );
+        assert_eq!(
+            parse_pull_event("complete", r#"{"percent":100}"#),
+            Some(PullEvent::Complete)
+        );
+        assert_eq!(
+            parse_pull_event("error", r#"{"error":"disk full"}"#),
+            Some(PullEvent::Error("disk full".to_string()))
+        );
+        assert_eq!(parse_pull_event("heartbeat", "{}"), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_fails_on_unreachable_server() {
+        let manager = ModelManager::new("http://127.0.0.1:19999/api/v1").with_attempts(1);
+        assert!(manager
+            .download("embed-gemma-300m-FLM", &NoProgress)
+            .await
+            .is_err());
+        assert!(manager.delete("embed-gemma-300m-FLM").await.is_err());
+    }
+}
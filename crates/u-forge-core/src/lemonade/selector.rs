@@ -14,6 +14,7 @@ use std::collections::HashSet;
 use crate::config::{EmbeddingDeviceConfig, ModelConfig};
 use crate::lemonade::catalog::{CatalogModel, LemonadeServerCatalog};
 use crate::lemonade::load::ModelLoadOptions;
+use crate::text::is_english;
 
 // ── Public types ──────────────────────────────────────────────────────────────
 
@@ -81,6 +82,9 @@ pub struct ModelSelector<'a> {
     catalog: &'a LemonadeServerCatalog,
     config: &'a ModelConfig,
     embedding: &'a EmbeddingDeviceConfig,
+    /// Project content language (BCP 47); non-English prefers multilingual
+    /// embedding models.
+    language: Option<String>,
 }
 
 impl<'a> ModelSelector<'a> {
@@ -95,7 +99,17 @@ impl<'a> ModelSelector<'a> {
         config: &'a ModelConfig,
         embedding: &'a EmbeddingDeviceConfig,
     ) -> Self {
-        Self { catalog, config, embedding }
+        Self { catalog, config, embedding, language: None }
+    }
+
+    /// Set the project's content language.
+    ///
+    /// When it is anything other than English, [`select_embedding_models`](Self::select_embedding_models)
+    /// only considers models listed in `ModelConfig::multilingual_embedding_models`
+    /// (falling back to every embedding model if none of those is downloaded).
+    pub fn with_language(mut self, language: Option<&str>) -> Self {
+        self.language = language.map(str::to_string);
+        self
     }
 
     /// Returns embedding models to register as workers, ordered by priority.
@@ -112,8 +126,26 @@ impl<'a> ModelSelector<'a> {
     ///   all later candidates for the same slot are dropped.  This prevents
     ///   spawning multiple NPU workers or mixing incompatible model families
     ///   (e.g. embedgemma + nomic) in the same embedding index.
+    /// - For non-English projects (see [`with_language`](Self::with_language)),
+    ///   restricted to `ModelConfig::multilingual_embedding_models` when any
+    ///   of them is downloaded.
     pub fn select_embedding_models(&self) -> Vec<SelectedModel> {
-        let candidates = self.catalog.downloaded_models_with_label("embeddings");
+        let mut candidates = self.catalog.downloaded_models_with_label("embeddings");
+        if let Some(lang) = self.language.as_deref().filter(|l| !is_english(l)) {
+            let multilingual: Vec<&CatalogModel> = candidates
+                .iter()
+                .copied()
+                .filter(|m| self.config.multilingual_embedding_models.contains(&m.id))
+                .collect();
+            if multilingual.is_empty() {
+                tracing::warn!(
+                    language = lang,
+                    "No multilingual embedding model downloaded; search quality may suffer"
+                );
+            } else {
+                candidates = multilingual;
+            }
+        }
         let ordered = self.apply_preference_order(&candidates, &self.config.embedding_model_preferences);
 
         let mut result: Vec<SelectedModel> = ordered
@@ -509,6 +541,39 @@ mod tests {
         assert_eq!(std_m.quality_tier, QualityTier::Standard);
     }
 
+    #[test]
+    fn test_select_embedding_prefers_multilingual_for_non_english() {
+        let catalog = catalog_with(
+            vec![
+                model("embed-en", "llamacpp", &["embeddings"]),
+                model("embed-multi", "llamacpp", &["embeddings"]),
+            ],
+            vec![installed_backend("llamacpp", "rocm", &["amd_igpu"])],
+        );
+        let cfg = ModelConfig {
+            embedding_model_preferences: vec!["embed-en".to_string()],
+            multilingual_embedding_models: vec!["embed-multi".to_string()],
+            ..Default::default()
+        };
+        let emb = default_embedding_cfg();
+
+        let english = ModelSelector::new(&catalog, &cfg, &emb).with_language(Some("en-US"));
+        assert_eq!(english.select_embedding_models()[0].model_id, "embed-en");
+
+        let german = ModelSelector::new(&catalog, &cfg, &emb).with_language(Some("de"));
+        let results = german.select_embedding_models();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].model_id, "embed-multi");
+
+        // No multilingual model downloaded → fall back to the full list.
+        let cfg = ModelConfig {
+            multilingual_embedding_models: vec!["missing".to_string()],
+            ..cfg
+        };
+        let german = ModelSelector::new(&catalog, &cfg, &emb).with_language(Some("de"));
+        assert_eq!(german.select_embedding_models()[0].model_id, "embed-en");
+    }
+
     #[test]
     fn test_select_embedding_preference_picks_winner_for_slot() {
         // Two models competing for the same GPU slot — only the preferred one wins.
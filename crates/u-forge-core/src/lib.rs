@@ -13,67 +13,333 @@
 //! storage layer, making it straightforward to use the graph without a running Lemonade
 //! Server (e.g. in tests).  AI capabilities are opt-in via [`InferenceQueue`].
 
-#[cfg(test)]
+/// Declare items that need the `native` feature — SQLite storage, the
+/// tokio runtime and the Lemonade clients.  Everything else builds for
+/// `wasm32-unknown-unknown` too.
+macro_rules! cfg_native {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "native")]
+            $item
+        )*
+    };
+}
+
+#[cfg(all(test, feature = "native"))]
 pub(crate) mod test_helpers;
 
-pub mod ai;
-pub mod builder;
-pub mod config;
+#[cfg(all(feature = "native", any(test, feature = "test-fixtures")))]
+pub mod test_fixtures;
+#[cfg(all(feature = "native", any(test, feature = "test-fixtures")))]
+pub use test_fixtures::{
+    generate_world, GeneratedChunk, GeneratedEdge, GeneratedWorld, SizeProfile, WorldStats,
+};
+
 pub mod error;
-pub mod graph;
-pub mod ingest;
-pub mod lemonade;
-pub mod queue;
-pub mod rag;
+pub mod memory;
 pub mod schema;
-pub mod search;
 pub(crate) mod text;
 pub mod types;
 
+cfg_native! {
+    pub mod actor;
+    pub mod ai;
+    pub mod archive;
+    pub mod async_graph;
+    pub mod autocomplete;
+    pub mod branches;
+    pub mod builder;
+    pub mod calendar;
+    pub mod canonical;
+    pub mod clocks;
+    pub mod config;
+    pub mod consistency;
+    pub mod content_pack;
+    pub mod crosslinks;
+    pub mod context_builder;
+    pub mod diagnostics;
+    pub mod diff;
+    pub mod duplicates;
+    pub mod economy;
+    pub mod embed_queue;
+    pub mod embedding_mode;
+    pub mod embedding_status;
+    pub mod encounters;
+    pub mod entity_links;
+    pub mod events;
+    pub mod export;
+    pub mod flashcards;
+    pub mod geo;
+    pub mod glossary;
+    pub mod graph;
+    pub mod graph_data;
+    pub mod handout;
+    pub mod health;
+    pub mod hooks;
+    pub mod ingest;
+    pub mod intents;
+    pub mod interactions;
+    pub mod interrogate;
+    pub mod jobs;
+    pub mod lemonade;
+    pub mod lineage;
+    pub mod lint;
+    pub mod link_prediction;
+    pub mod maintenance;
+    pub mod markdown;
+    pub mod orgs;
+    pub mod persona;
+    pub mod pins;
+    pub mod players;
+    pub mod prep;
+    pub mod presence;
+    pub mod progress;
+    pub mod project_lock;
+    pub mod projects;
+    pub mod proposals;
+    pub mod queue;
+    pub mod quests;
+    pub mod rag;
+    pub mod replica;
+    pub mod reveals;
+    pub mod routes;
+    pub mod rumors;
+    pub mod schedule;
+    pub mod search;
+    pub mod similar;
+    pub mod staging;
+    pub mod statblocks;
+    pub mod styles;
+    pub mod text_stats;
+    pub mod validation;
+    pub mod views;
+    pub mod visibility;
+}
+
 // ── Re-exports ────────────────────────────────────────────────────────────────
 
-pub use ai::embeddings::{
-    EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType, LemonadeProvider,
-};
-pub use error::EmbeddingDimensionMismatch;
-pub use builder::ObjectBuilder;
-pub use config::{
-    AppConfig, ChatConfig, ChatDevice, ChatDeviceConfig, DataConfig, EmbeddingDeviceConfig,
-    ModelConfig, ModelLoadParams, StorageConfig, UiConfig,
-};
-pub use graph::{
-    GraphStats, KnowledgeGraphStorage, DEFAULT_EMBEDDING_CONTEXT_TOKENS, EMBEDDING_DIMENSIONS,
-    HIGH_QUALITY_EMBEDDING_DIMENSIONS, MAX_CHUNK_TOKENS,
-};
-pub use ingest::{
-    build_hq_embed_queue, embed_all_chunks, rechunk_and_embed, setup_and_index, DataIngestion,
-    EmbeddingOutcome, EmbeddingPlan, EmbeddingProgress, EmbeddingResult, EmbeddingTarget,
-    IngestionStats, SetupResult,
-};
-pub use lemonade::{
-    load_model, ChatChoice, ChatCompletionResponse, ChatMessage, ChatRequest, ChatUsage,
-    GpuResourceManager, GpuWorkload, KokoroVoice, LemonadeChatProvider, LemonadeHealth,
-    LemonadeSttProvider, LemonadeTtsProvider, LlmGuard, LoadedModelEntry, ModelLoadOptions,
-    StreamToken, SttGuard, TranscriptionResult,
-};
-pub use rag::{build_rag_messages, format_search_context, RagContext};
+pub use error::{EmbeddingDimensionMismatch, ErrorKind, UForgeError};
+pub use memory::{MemoryGraph, MemoryHit, MemorySnapshot};
 pub use schema::{
-    EdgeTypeSchema, ObjectTypeSchema, PropertyIssue, PropertySchema, PropertyType,
-    SchemaDefinition, SchemaIngestion, SchemaManager, SchemaStats, ValidationResult,
+    format_currency, parse_currency, ComputedExpression, Denomination, DiceExpression,
+    EdgeTypeSchema, FormField, FormLayout, FormSection, ObjectTypeSchema, PropertySchema,
+    PropertyType, PropertyUi, SchemaDefinition, TypeMapping, ValidationFix, ValidationResult,
+    Widget,
 };
-pub use search::{
-    search_hybrid, ConnectedNode, HybridSearchConfig, NodeSearchResult, SearchSources,
+pub use text::{
+    count_tokens, html_to_text, DEFAULT_EMBEDDING_CONTEXT_TOKENS, DEFAULT_TOKENIZER_MODEL,
+    MAX_CHUNK_TOKENS,
 };
 pub use types::*;
 
+cfg_native! {
+    pub use actor::{
+        ChangeFilter, ChangeKind, ChangeSubscription, GraphActor, GraphChange, WindowHandle, WindowId,
+        CHANGE_CHANNEL_CAPACITY,
+    };
+    pub use ai::embeddings::{
+        EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType, LemonadeProvider,
+    };
+    pub use ai::fallback::{EmbeddingChain, TaggedEmbedding};
+    pub use archive::ArchivedObject;
+    pub use autocomplete::{
+        LinkTargetSuggestion, NameMatch, NEIGHBOR_BOOST, RECENCY_BOOST, RECENCY_HALF_LIFE_DAYS,
+        SUGGESTION_LIMIT,
+    };
+    pub use economy::{
+        LedgerEntry, CURRENCY_SETTING, TREASURY_BALANCE_KEY, TREASURY_OWNER_EDGE, TREASURY_TYPE,
+    };
+    pub use diagnostics::{
+        operation_stats, operation_tracing_enabled, reset_operation_stats, set_operation_tracing,
+        trace_operation, DiagnosticsTrace, OperationGuard, OperationStats,
+    };
+    pub use diff::{diff_words, DiffHunk, DiffOp};
+    pub use duplicates::{
+        normalize_object_name, CheckedAdd, DuplicateCandidate, DuplicateMatch, DuplicateWarning,
+        EMBEDDING_DUPLICATE_DISTANCE, FUZZY_NAME_SIMILARITY,
+    };
+    pub use embed_queue::QueuedEmbedding;
+    pub use embedding_mode::{EmbeddingMode, EMBEDDING_MODE_SETTING};
+    pub use embedding_status::{ChunkEmbeddingStatus, EmbeddingCoverage};
+    pub use encounters::{
+        encounter_budget, BudgetAssessment, Combatant, DifficultyThresholds, Dnd5eBudget,
+        EncounterBudget, EncounterDifficulty, EncounterEstimate, SwnBudget, ENCOUNTER_FOE_EDGE,
+        FOE_COUNT_KEY, PARTY_MEMBER_EDGE, TPK_FACTOR,
+    };
+    pub use entity_links::{
+        parse_entity_links, ChunkLink, LinkFormat, LinkSpan, LinkSyntax, INLINE_LINK_KEY, MENTIONS_EDGE,
+    };
+    pub use events::{GraphEvent, SESSION_ENDED_AT_KEY, SESSION_SUMMARY_KEY};
+    pub use export::{
+        ExportFile, ExportFilter, ExportFormat, ExportManifest, SelectionExport, EXPORT_ARCHIVE_FILE,
+        EXPORT_DATA_FILE,
+    };
+    pub use flashcards::{
+        schedule_review, CardSource, Flashcard, ReviewState, DEFAULT_EASINESS, MAX_GRADE,
+        MAX_PROPERTY_ANSWER_CHARS, MIN_EASINESS, PASSING_GRADE,
+    };
+    pub use async_graph::{KnowledgeGraphAsync, DEFAULT_STORAGE_THREADS};
+    pub use branches::{Branch, BranchConflict, BranchMerge, MAIN_BRANCH};
+    pub use builder::{ObjectBuilder, RelationshipTarget};
+    pub use calendar::{CalendarMonth, TimelineEntry, WorldCalendar, WorldDate};
+    pub use canonical::{
+        negotiate_canonical_version, CanonicalGraph, CanonicalImport, CanonicalOptions,
+        CANONICAL_FORMAT, CANONICAL_VERSION, SUPPORTED_CANONICAL_VERSIONS,
+    };
+    pub use clocks::{ClockEvent, ProgressClock, CLOCKS_KEY};
+    pub use consistency::{
+        detect_contradictions, scan_for_contradictions, ConsistencyReport, ConsistencyWarning,
+        ConsistencyWarningId,
+    };
+    pub use content_pack::{
+        InstalledPack, ObjectTemplate, PackInstall, PackManifest, RandomTable, RandomTableEntry,
+    };
+    pub use context_builder::{
+        build_context, AssembledContext, ContextBuilderConfig, ContextCitation,
+    };
+    pub use config::{
+        AppConfig, ChatConfig, ChatDevice, ChatDeviceConfig, DataConfig, EmbeddingDeviceConfig,
+        ModelConfig, ModelLoadParams, StorageConfig, UiConfig,
+    };
+    pub use crosslinks::{ExternalRef, ResolvedRef, PROJECT_ID_SETTING};
+    pub use geo::{Coordinates, NearbyObject, Point};
+    pub use glossary::{Glossary, GlossaryEntry, Mention};
+    pub use graph::{
+        ChunkTypeStats, CompactionReport, ExtendedStats, GraphStats, IndexHealth, KnowledgeGraphStorage,
+        ReadCacheStats, DEFAULT_READ_CACHE_BYTES, EMBEDDING_DIMENSIONS,
+        HIGH_QUALITY_EMBEDDING_DIMENSIONS,
+    };
+    pub use handout::{
+        render_template, HandoutExport, HandoutKind, HandoutLayout, HandoutStyle, PdfFont,
+    };
+    pub use health::{
+        clear_recent_errors, recent_errors, record_error, EmbeddingHealth, HealthReport,
+        HealthStatus, RecentError, StorageHealth,
+    };
+    pub use hooks::{
+        fire_hook, AutomationHook, HookAction, HookPayload, HookRunner, HookTrigger, HOOK_EVENT_ENV,
+        HOOK_TIMEOUT,
+    };
+    pub use ingest::{
+        build_hq_embed_queue, draft_session_recap, embed_all_chunks, embed_all_profiles,
+        import_session_log, import_session_log_dir, import_transcript, propose_session_links,
+        rechunk_and_embed, setup_and_index, DataIngestion, EmbeddingOutcome, EmbeddingPlan,
+        EmbeddingResult, EmbeddingTarget, IngestionStats, Roll20Import, Roll20ImportStats,
+        SessionLinkReport, SessionLogImport, SetupResult, TranscriptFormat, TranscriptImport,
+        TranscriptSegment,
+    };
+    pub use intents::IntentKind;
+    pub use jobs::{Job, JobHandle, JobId, JobKind, JobManager, JobPriority, JobSpec, JobState};
+    pub use graph_data::{
+        Aggregation, Cluster, ClusterEdge, GraphData, GraphDataRequest, GraphNodeRef, GraphScope,
+        NeighborhoodBudget, NodeFilter,
+    };
+    pub use interactions::{Interaction, Participant, INTERACTION_TYPE, PARTICIPANT_EDGE};
+    pub use interrogate::{ask_about, object_context, AskAboutConfig, ObjectAnswer};
+    pub use lemonade::{
+        load_model, ChatChoice, ChatCompletionResponse, ChatMessage, ChatRequest, ChatUsage,
+        GpuResourceManager, GpuWorkload, KokoroVoice, LemonadeChatProvider, LemonadeHealth,
+        LemonadeSttProvider, LemonadeTtsProvider, LlmGuard, LoadedModelEntry, ModelLoadOptions,
+        ModelManager, ModelState, StreamToken, SttGuard, TranscriptionResult,
+    };
+    pub use lineage::{
+        FamilyGeneration, FamilyMember, FamilyTree, CHILD_OF_EDGE, MARRIED_TO_EDGE, PARENT_OF_EDGE,
+        SIBLING_OF_EDGE,
+    };
+    pub use lint::{
+        default_lint_rules, LintCheck, LintFinding, LintReport, LintRule, LintSeverity,
+        ANY_OBJECT_TYPE, LINT_RULES_SETTING,
+    };
+    pub use link_prediction::{
+        LinkPredictionConfig, LinkSuggestion, DEFAULT_SUGGESTED_EDGE, LINK_PREDICTION_SOURCE,
+    };
+    pub use maintenance::{RetentionPolicy, RETENTION_SETTING};
+    pub use markdown::{markdown_file_name, MarkdownDocument, MarkdownExport, MarkdownImport};
+    pub use orgs::{
+        OrgChart, OrgIssue, OrgPosition, OrgUnit, LED_BY_EDGE, MEMBER_OF_EDGE, ORG_ROLE_KEY,
+        REPORTS_TO_EDGE, SINGLE_SUPERIOR_KEY, SUBFACTION_OF_EDGE,
+    };
+    pub use persona::{NpcPersona, PersonaEvent, PersonaRelationship, PERSONA_RECENT_EVENTS};
+    pub use pins::Pin;
+    pub use players::{ATTENDED_EDGE, PLAYER_TYPE, PLAYS_EDGE, PLOT_TYPE, VISIBLE_TO_KEY};
+    pub use prep::{PrepItem, PrepSheet, PrepSheetOptions};
+    pub use presence::{
+        Presence, PresenceConflict, PRESENT_FROM_KEY, PRESENT_IN_EDGE, PRESENT_UNTIL_KEY,
+    };
+    pub use progress::{
+        CancellationToken, EventBridge, LatestProgress, NoProgress, Progress, ProgressEvent, ProgressSink,
+    };
+    pub use project_lock::{
+        inspect_lock, LockOwner, LockState, ProjectLock, LOCK_FILE, LOCK_HEARTBEAT_INTERVAL,
+        LOCK_STALE_AFTER,
+    };
+    pub use projects::{ProjectManager, ProjectSearchResult, PROJECT_PATH_SETTING};
+    pub use proposals::{Proposal, ProposalId, ProposalStatus, ProposedChange};
+    pub use quests::{
+        QuestAvailability, QuestGraph, QuestNode, QuestState, BLOCKED_BY_EDGE, QUEST_STATUS_KEY,
+        QUEST_TYPE, REQUIRES_EDGE,
+    };
+    pub use rag::{build_rag_messages, format_search_context, RagContext};
+    pub use replica::{PrimaryFollower, PrimaryUpdate, DEFAULT_FOLLOW_INTERVAL};
+    pub use reveals::{Reveal, RevealTarget};
+    pub use routes::{
+        Route, RouteLeg, TravelLeg, TravelMode, Waypoint, DIFFICULTY_KEY, DISTANCE_KEY, MODES_KEY,
+        ONE_WAY_KEY,
+    };
+    pub use rumors::{
+        Knowledge, RumorReach, DETAIL_KEY, KNOWS_ABOUT_EDGE, MIN_RUMOR_CERTAINTY, SOCIAL_EDGES,
+    };
+    pub use schedule::{
+        FiredEvent, ScheduledEvent, AFFECTS_EDGE, FIRED_EVENT_TYPE, SCHEDULE_SETTING, WORLD_DATE_SETTING,
+    };
+    pub use schema::{
+        PropertyIssue, SchemaIngestion, SchemaManager, SchemaReloadEvent, SchemaStats, SchemaWatcher,
+    };
+    pub use search::{
+        normalize_query, preprocess_query, search_hybrid, ClickedObject, ConnectedNode,
+        HybridSearchConfig, NodeSearchResult, PreparedQuery, QueryPreprocessing, SearchReport,
+        SearchSources, ZeroResultQuery, SEARCH_TELEMETRY_SETTING,
+    };
+    pub use similar::{SimilarObject, SimilarityWeights, EMBEDDING_CANDIDATES};
+    pub use staging::{StagedGraph, StagingCommit, StagingLayer};
+    pub use statblocks::{
+        StatBlock, StatBlockLayout, StatField, StatFieldType, StatSection, STAT_BLOCK_KEY,
+        STAT_BLOCK_LAYOUTS_SETTING, STAT_BLOCK_SCHEMA_KEY,
+    };
+    pub use styles::{default_type_color, parse_hex_color, NodeShape, NodeStyle};
+    pub use text_stats::{
+        count_words, flesch_reading_ease, reading_minutes, CompletenessReport, ObjectTextStats,
+        TypeTextStats, BASE_EXPECTED_WORDS, MAX_EXPECTED_WORDS, THIN_COVERAGE, WORDS_PER_CONNECTION,
+        WORDS_PER_MINUTE,
+    };
+    pub use validation::{
+        ObjectValidationIssue, TypeValidationSummary, ValidationReport, WARNING_KIND,
+    };
+    pub use views::{GraphView, ViewLayout, DEFAULT_VIEW_DEPTH, GRAPH_VIEWS_SETTING};
+    pub use visibility::{GM_ONLY_PROPERTIES, GM_PROPERTIES_KEY, VISIBILITY_KEY};
+}
+
 // ── Facade ────────────────────────────────────────────────────────────────────
 
-use anyhow::Result;
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Arc;
+cfg_native! {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use crate::error::Result;
+
+    use text::split_text_for_language;
 
-use text::split_text;
+    /// `project_settings` key holding the project's default content language.
+    const DEFAULT_LANGUAGE_SETTING: &str = "default_language";
+
+    /// `project_settings` key; `"true"` turns on strict edge mode.
+    const STRICT_EDGES_SETTING: &str = "strict_edges";
+
+    /// Edge metadata key marking an edge deliberately written against the schema
+    /// in strict mode.
+    pub const SCHEMA_EXCEPTION_KEY: &str = "schema_exception";
+}
 
 /// Central knowledge graph interface.
 ///
@@ -94,12 +360,19 @@ use text::split_text;
 // KnowledgeGraph is Send + Sync:
 //   - KnowledgeGraphStorage wraps rusqlite::Connection in Arc<parking_lot::Mutex<Connection>> (see graph/storage.rs)
 //   - SchemaManager holds Arc<KnowledgeGraphStorage> + DashMap (both Send + Sync)
-// This means Arc<KnowledgeGraph> is a valid axum State<T> type for Phase 3.
+//   - the active branch is cached behind a parking_lot::RwLock
+// This means Arc<KnowledgeGraph> is a valid axum State<T> type for Phase 3;
+// async handlers should call it through KnowledgeGraphAsync (async_graph.rs).
+#[cfg(feature = "native")]
 pub struct KnowledgeGraph {
     storage: Arc<KnowledgeGraphStorage>,
     schema_manager: Arc<SchemaManager>,
+    /// The active [branch](branches), `None` on the main graph.  Object and
+    /// edge reads and writes go through its overlay.
+    branch: parking_lot::RwLock<Option<Branch>>,
 }
 
+#[cfg(feature = "native")]
 impl KnowledgeGraph {
     /// Open (or create) a knowledge graph at `db_path`.
     ///
@@ -107,51 +380,237 @@ impl KnowledgeGraph {
     /// `<db_path>/knowledge.db`.
     pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
         let storage = Arc::new(KnowledgeGraphStorage::new(db_path.as_ref())?);
+        Self::from_parts(storage)
+    }
+
+    /// Like [`new`](Self::new), reporting the storage migrations to
+    /// `progress`; see [`KnowledgeGraphStorage::open_with_progress`].
+    pub fn open_with_progress<P: AsRef<Path>>(
+        db_path: P,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let storage = Arc::new(KnowledgeGraphStorage::open_with_progress(
+            db_path.as_ref(),
+            progress,
+        )?);
+        Self::from_parts(storage)
+    }
+
+    /// Wrap opened storage with its schema manager and load the active
+    /// branch.
+    fn from_parts(storage: Arc<KnowledgeGraphStorage>) -> Result<Self> {
         let schema_manager = Arc::new(SchemaManager::new(storage.clone()));
-        Ok(Self {
+        let graph = Self {
             storage,
             schema_manager,
-        })
+            branch: parking_lot::RwLock::new(None),
+        };
+        graph.reload_active_branch()?;
+        Ok(graph)
+    }
+
+    /// The project directory this graph was opened from.
+    pub fn db_path(&self) -> &Path {
+        self.storage.db_dir()
     }
 
     // ── Node / object operations ──────────────────────────────────────────────
 
     /// Persist a new object, returning its [`ObjectId`].
-    pub fn add_object(&self, metadata: ObjectMetadata) -> Result<ObjectId> {
+    ///
+    /// When `metadata.lifecycle` is `None`, the type's schema
+    /// `default_lifecycle` is applied (if the schema is loaded), otherwise
+    /// the object is stored as [`Lifecycle::Canon`].  Properties missing
+    /// from `metadata` are filled from the schema's `default_value`s.
+    pub fn add_object(&self, mut metadata: ObjectMetadata) -> Result<ObjectId> {
+        if metadata.lifecycle.is_none() {
+            metadata.lifecycle = self.default_lifecycle_for(&metadata);
+        }
+        self.apply_schema_defaults(&mut metadata);
         let id = metadata.id;
-        self.storage.upsert_node(metadata)?;
+        if self.on_branch() {
+            self.edit_active_branch(|layer| layer.add_object(metadata))?;
+        } else {
+            self.storage.upsert_node(metadata)?;
+        }
         Ok(id)
     }
 
+    /// Start an [`ObjectBuilder`] for `object_type` with the schema's
+    /// property `default_value`s already filled in, so required fields that
+    /// have defaults are valid from the start.
+    pub fn new_object(&self, object_type: &str, name: &str) -> ObjectBuilder {
+        let mut metadata = ObjectMetadata::new(object_type.to_string(), name.to_string());
+        self.apply_schema_defaults(&mut metadata);
+        ObjectBuilder::from_metadata(metadata)
+    }
+
+    /// Fill `metadata`'s missing properties from its type's schema defaults.
+    /// Returns the property names filled.
+    pub fn apply_schema_defaults(&self, metadata: &mut ObjectMetadata) -> Vec<String> {
+        let Some(type_schema) = self.object_type_schema_for(metadata) else {
+            return Vec::new();
+        };
+        match metadata.properties.as_object_mut() {
+            Some(props) => type_schema.apply_defaults(props),
+            None => Vec::new(),
+        }
+    }
+
+    /// Values of `metadata`'s computed properties (schema `computed`
+    /// expressions), evaluated against its stored properties.  Properties
+    /// whose inputs are missing are left out.
+    pub fn computed_properties(
+        &self,
+        metadata: &ObjectMetadata,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        match (self.object_type_schema_for(metadata), metadata.properties.as_object()) {
+            (Some(type_schema), Some(props)) => type_schema.computed_values(props),
+            _ => serde_json::Map::new(),
+        }
+    }
+
+    /// `metadata` with its computed properties filled in, replacing any
+    /// stale stored values.
+    pub fn with_computed_properties(&self, mut metadata: ObjectMetadata) -> ObjectMetadata {
+        let computed = self.computed_properties(&metadata);
+        if let Some(props) = metadata.properties.as_object_mut() {
+            props.extend(computed);
+        }
+        metadata
+    }
+
+    /// Retrieve an object with its computed properties evaluated.
+    pub fn get_object_with_computed(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
+        Ok(self.get_object(id)?.map(|o| self.with_computed_properties(o)))
+    }
+
+    /// Schema-level default lifecycle for `metadata`'s type, if one is set.
+    fn default_lifecycle_for(&self, metadata: &ObjectMetadata) -> Option<Lifecycle> {
+        self.object_type_schema_for(metadata).and_then(|t| t.default_lifecycle)
+    }
+
+    /// The type schema for `metadata`'s object type, if registered.
+    ///
+    /// Checks the schema cache first and falls back to the stored schema, so
+    /// a type registered moments ago (which invalidates the cache) is still
+    /// found.
+    fn object_type_schema_for(&self, metadata: &ObjectMetadata) -> Option<ObjectTypeSchema> {
+        let schema_name = metadata.schema_name.as_deref().unwrap_or("default");
+        self.object_type_schema(schema_name, &metadata.object_type)
+    }
+
+    /// The type schema for `object_type` in `schema_name`, if registered.
+    /// Same cache-then-storage lookup as
+    /// [`object_type_schema_for`](Self::object_type_schema_for).
+    fn object_type_schema(&self, schema_name: &str, object_type: &str) -> Option<ObjectTypeSchema> {
+        self.schema_manager
+            .get_object_type_schema(schema_name, object_type)
+            .or_else(|| {
+                self.storage
+                    .get_schema(schema_name)
+                    .ok()
+                    .flatten()
+                    .and_then(|s| s.object_types.get(object_type).cloned())
+            })
+    }
+
+    /// Return every object whose lifecycle is one of `lifecycles`, ordered by name.
+    ///
+    /// Pass `&[Lifecycle::Canon]` for authoritative lookups that must not
+    /// see drafts or rumors.
+    pub fn get_objects_by_lifecycle(&self, lifecycles: &[Lifecycle]) -> Result<Vec<ObjectMetadata>> {
+        self.storage.get_nodes_by_lifecycle(lifecycles)
+    }
+
+    /// Move an object between lifecycle states (e.g. promote a draft to canon).
+    /// Fails with [`UForgeError::NotFound`] for an unknown id.
+    pub fn set_object_lifecycle(&self, id: ObjectId, lifecycle: Lifecycle) -> Result<()> {
+        self.storage.set_node_lifecycle(id, lifecycle)
+    }
+
+    /// Current lifecycle of an object, or `None` if it does not exist.
+    pub fn get_object_lifecycle(&self, id: ObjectId) -> Result<Option<Lifecycle>> {
+        self.storage.get_node_lifecycle(id)
+    }
+
     /// Retrieve an object by its [`ObjectId`], or `None` if it does not exist.
     pub fn get_object(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
+        if let Some(branch) = self.branch.read().as_ref() {
+            return self.staged(&branch.layer).get_object(id);
+        }
         self.storage.get_node(id)
     }
 
+    /// An object as it was at `timestamp`, or `None` if it did not exist then.
+    ///
+    /// Reads the trigger-maintained `node_history` table, so retcons, property
+    /// edits, and deletions after `timestamp` are all rolled back.
+    pub fn get_object_as_of(
+        &self,
+        id: ObjectId,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<ObjectMetadata>> {
+        self.storage.get_node_as_of(id, timestamp)
+    }
+
     /// Return every object stored in the graph.
     pub fn get_all_objects(&self) -> Result<Vec<ObjectMetadata>> {
+        if let Some(branch) = self.branch.read().as_ref() {
+            return self.staged(&branch.layer).get_all_objects();
+        }
         self.storage.get_all_objects()
     }
 
     /// Overwrite an existing object's metadata (updates `updated_at`).
     pub fn update_object(&self, mut metadata: ObjectMetadata) -> Result<()> {
         metadata.touch();
-        self.storage.upsert_node(metadata)
+        if self.on_branch() {
+            self.edit_active_branch(|layer| layer.add_object(metadata))?;
+            return Ok(());
+        }
+        let completed = metadata.object_type == quests::QUEST_TYPE
+            && quests::quest_status(&metadata).as_deref() == Some("completed")
+            && self
+                .storage
+                .get_node(metadata.id)?
+                .is_none_or(|old| quests::quest_status(&old).as_deref() != Some("completed"));
+        let event = completed.then(|| GraphEvent::QuestCompleted {
+            quest_id: metadata.id,
+            name: metadata.name.clone(),
+        });
+        self.storage.upsert_node(metadata)?;
+        if let Some(event) = event {
+            self.emit(event);
+        }
+        Ok(())
     }
 
     /// Delete an object and, via `ON DELETE CASCADE`, all its edges and chunks.
     pub fn delete_object(&self, id: ObjectId) -> Result<()> {
+        if self.on_branch() {
+            let stored = self.storage.get_node(id)?.is_some();
+            return self.edit_active_branch(|layer| {
+                if stored {
+                    layer.delete_object(id);
+                } else {
+                    layer.remove_object(id);
+                }
+            });
+        }
         self.storage.delete_node(id)
     }
 
     /// Delete all data from the graph (nodes, edges, chunks, schemas, vectors).
     pub fn clear_all(&self) -> Result<()> {
-        self.storage.clear_all()
+        self.storage.clear_all()?;
+        self.reload_active_branch()
     }
 
     /// Delete node data only (nodes, edges, chunks, vectors) — schemas are preserved.
     pub fn clear_data(&self) -> Result<()> {
-        self.storage.clear_data_only()
+        self.storage.clear_data_only()?;
+        self.reload_active_branch()
     }
 
     /// Delete all schemas from the graph — node data is preserved.
@@ -167,13 +626,12 @@ impl KnowledgeGraph {
 
     /// Create a typed relationship between two objects.
     pub fn connect_objects(&self, from: ObjectId, to: ObjectId, edge_type: EdgeType) -> Result<()> {
-        self.storage.upsert_edge(Edge::new(from, to, edge_type))
+        self.add_edge(Edge::new(from, to, edge_type), false)
     }
 
     /// Create a relationship using a plain string edge type.
     pub fn connect_objects_str(&self, from: ObjectId, to: ObjectId, edge_type: &str) -> Result<()> {
-        self.storage
-            .upsert_edge(Edge::new(from, to, EdgeType::new(edge_type)))
+        self.add_edge(Edge::new(from, to, EdgeType::new(edge_type)), false)
     }
 
     /// Create a weighted relationship.
@@ -184,8 +642,7 @@ impl KnowledgeGraph {
         edge_type: EdgeType,
         weight: f32,
     ) -> Result<()> {
-        self.storage
-            .upsert_edge(Edge::new(from, to, edge_type).with_weight(weight))
+        self.add_edge(Edge::new(from, to, edge_type).with_weight(weight), false)
     }
 
     /// Create a weighted relationship using a plain string edge type.
@@ -196,12 +653,202 @@ impl KnowledgeGraph {
         edge_type: &str,
         weight: f32,
     ) -> Result<()> {
+        self.add_edge(
+            Edge::new(from, to, EdgeType::new(edge_type)).with_weight(weight),
+            false,
+        )
+    }
+
+    /// Create a relationship with typed edge properties and return the
+    /// stored edge.
+    ///
+    /// Properties are checked against the edge type in the source object's
+    /// schema and stored in canonical form; unknown properties or values of
+    /// the wrong type fail the whole request.  Strict edge mode applies as
+    /// for [`add_edge`](Self::add_edge).  The returned edge's
+    /// `(from, to, edge_type)` identifies it for later edits.
+    pub fn create_relationship(&self, request: CreateRelationshipRequest) -> Result<Edge> {
+        let CreateRelationshipRequest { from, to, edge_type, weight, mut properties } = request;
+        let source = self
+            .get_object(from)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown source object {from}")))?;
+        if self.get_object(to)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Unknown target object {to}")));
+        }
+        self.check_edge_properties(&source, &edge_type, &mut properties)?;
+
+        let mut edge = Edge::new(from, to, EdgeType::new(edge_type));
+        if let Some(weight) = weight {
+            edge = edge.with_weight(weight);
+        }
+        edge.metadata = properties;
+        self.add_edge(edge.clone(), false)?;
+
+        let stored = self
+            .storage
+            .get_edges(from)?
+            .into_iter()
+            .find(|e| e.from == from && e.to == to && e.edge_type == edge.edge_type);
+        Ok(stored.unwrap_or(edge))
+    }
+
+    /// The edge with id `id`, if it exists.
+    pub fn get_edge(&self, id: EdgeId) -> Result<Option<Edge>> {
+        self.storage.get_edge(id)
+    }
+
+    /// Change an existing edge in place — its type, endpoints, weight, or
+    /// metadata — keeping its id, and return the updated edge.
+    ///
+    /// New metadata is checked like the properties of
+    /// [`create_relationship`](Self::create_relationship), and strict edge
+    /// mode applies to the result.
+    pub fn update_edge(&self, id: EdgeId, mut changes: EdgeChanges) -> Result<Edge> {
+        let current = self
+            .storage
+            .get_edge(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown edge {id}")))?;
+        let mut updated = changes.apply_to(&current);
+        let source = self
+            .get_object(updated.from)?
+            .ok_or_else(|| {
+                UForgeError::NotFound(format!("Unknown source object {}", updated.from))
+            })?;
+        if self.get_object(updated.to)?.is_none() {
+            let message = format!("Unknown target object {}", updated.to);
+            return Err(UForgeError::NotFound(message));
+        }
+        if changes.metadata.is_some() || changes.edge_type.is_some() {
+            // Exceptions recorded by strict mode are not schema properties.
+            let exception = updated.metadata.remove(SCHEMA_EXCEPTION_KEY);
+            self.check_edge_properties(&source, updated.edge_type.as_str(), &mut updated.metadata)?;
+            if let Some(flag) = exception {
+                updated.metadata.insert(SCHEMA_EXCEPTION_KEY.to_string(), flag);
+            }
+        }
+        self.check_strict_edge(&mut updated, false)?;
+        changes.metadata = Some(updated.metadata);
         self.storage
-            .upsert_edge(Edge::new(from, to, EdgeType::new(edge_type)).with_weight(weight))
+            .update_edge(id, &changes)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown edge {id}")))
+    }
+
+    /// Delete the edge with id `id`.  Returns whether an edge was removed.
+    pub fn delete_edge_by_id(&self, id: EdgeId) -> Result<bool> {
+        self.storage.delete_edge_by_id(id)
+    }
+
+    /// Validate and normalise `properties` for an `edge_type` edge from
+    /// `source`, against the source object's schema.
+    fn check_edge_properties(
+        &self,
+        source: &ObjectMetadata,
+        edge_type: &str,
+        properties: &mut HashMap<String, String>,
+    ) -> Result<()> {
+        let schema_name = source.schema_name.as_deref().unwrap_or("default");
+        let Some(schema) = self.schema_manager.current_schema(schema_name)? else {
+            return Ok(());
+        };
+        let errors = self
+            .schema_manager
+            .validate_edge_properties(edge_type, properties, &schema);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+        Err(UForgeError::ValidationFailed(format!(
+            "Invalid properties for '{edge_type}' edge: {}",
+            messages.join("; ")
+        )))
+    }
+
+    /// Write a fully-specified edge.
+    ///
+    /// In strict edge mode (see [`set_strict_edges`](Self::set_strict_edges))
+    /// an edge that breaks its schema's allowed source/target types or
+    /// cardinality is rejected, unless `allow_violation` is set; such an
+    /// intentional exception is stored with [`SCHEMA_EXCEPTION_KEY`] set to
+    /// `"true"` in its metadata.
+    pub fn add_edge(&self, mut edge: Edge, allow_violation: bool) -> Result<()> {
+        self.check_strict_edge(&mut edge, allow_violation)?;
+        if self.on_branch() {
+            return self.edit_active_branch(|layer| layer.add_edge(edge));
+        }
+        self.storage.upsert_edge(edge)
+    }
+
+    /// The strict-mode check behind [`add_edge`](Self::add_edge): errors on a
+    /// violation, or tags `edge` as an exception when `allow_violation`.
+    fn check_strict_edge(&self, edge: &mut Edge, allow_violation: bool) -> Result<()> {
+        if self.strict_edges()? {
+            let source = self
+                .get_object(edge.from)?
+                .ok_or_else(|| {
+                    UForgeError::NotFound(format!("Unknown source object {}", edge.from))
+                })?;
+            let target = self
+                .get_object(edge.to)?
+                .ok_or_else(|| {
+                    UForgeError::NotFound(format!("Unknown target object {}", edge.to))
+                })?;
+            let violations = self.edge_schema_violations(edge, &source, &target, &[])?;
+            if !violations.is_empty() {
+                if !allow_violation {
+                    return Err(UForgeError::SchemaConflict(format!(
+                        "Edge rejected by strict mode: {}",
+                        violations.join("; ")
+                    )));
+                }
+                edge.metadata.insert(SCHEMA_EXCEPTION_KEY.to_string(), "true".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether strict edge mode is on.
+    pub fn strict_edges(&self) -> Result<bool> {
+        Ok(self.storage.get_setting(STRICT_EDGES_SETTING)?.as_deref() == Some("true"))
+    }
+
+    /// Turn strict edge mode on or off for this project.
+    ///
+    /// When on, every edge write — `connect_objects*`, [`add_edge`](Self::add_edge),
+    /// and staged edges in [`commit_staging`](Self::commit_staging) — is
+    /// checked against the schema instead of only when validation is asked for.
+    pub fn set_strict_edges(&self, strict: bool) -> Result<()> {
+        if strict {
+            self.storage.set_setting(STRICT_EDGES_SETTING, "true")
+        } else {
+            self.storage.delete_setting(STRICT_EDGES_SETTING).map(|_| ())
+        }
+    }
+
+    /// Schema errors for writing `edge` between `source` and `target`, as
+    /// messages.  Uses the source object's schema; `staged` edges written in
+    /// the same batch count towards cardinality.
+    pub(crate) fn edge_schema_violations(
+        &self,
+        edge: &Edge,
+        source: &ObjectMetadata,
+        target: &ObjectMetadata,
+        staged: &[Edge],
+    ) -> Result<Vec<String>> {
+        let schema_name = source.schema_name.as_deref().unwrap_or("default");
+        let Some(schema) = self.schema_manager.current_schema(schema_name)? else {
+            return Ok(Vec::new());
+        };
+        let result = self
+            .schema_manager
+            .validate_staged_edge_with_schema(edge, source, target, &schema, staged)?;
+        Ok(result.errors.into_iter().map(|e| e.message).collect())
     }
 
     /// All edges incident to `id` (both outgoing and incoming).
     pub fn get_relationships(&self, id: ObjectId) -> Result<Vec<Edge>> {
+        if let Some(branch) = self.branch.read().as_ref() {
+            return self.staged(&branch.layer).get_relationships(id);
+        }
         self.storage.get_edges(id)
     }
 
@@ -234,6 +881,9 @@ impl KnowledgeGraph {
     /// Prefer this over repeated `get_relationships()` calls when building a
     /// full graph snapshot.
     pub fn get_all_edges(&self) -> Result<Vec<Edge>> {
+        if let Some(branch) = self.branch.read().as_ref() {
+            return self.staged(&branch.layer).get_all_edges();
+        }
         self.storage.get_all_edges()
     }
 
@@ -241,6 +891,9 @@ impl KnowledgeGraph {
     ///
     /// This is idempotent — deleting a non-existent edge succeeds silently.
     pub fn delete_edge(&self, from: ObjectId, to: ObjectId, edge_type: &str) -> Result<()> {
+        if self.on_branch() {
+            return self.edit_active_branch(|layer| layer.delete_edge(from, to, edge_type));
+        }
         self.storage.delete_edge(from, to, edge_type)
     }
 
@@ -253,6 +906,16 @@ impl KnowledgeGraph {
 
     /// IDs of every object directly connected to `id` (1-hop neighbours).
     pub fn get_neighbors(&self, id: ObjectId) -> Result<Vec<ObjectId>> {
+        if self.on_branch() {
+            let mut neighbors: Vec<ObjectId> = Vec::new();
+            for edge in self.get_relationships(id)? {
+                let other = if edge.from == id { edge.to } else { edge.from };
+                if !neighbors.contains(&other) {
+                    neighbors.push(other);
+                }
+            }
+            return Ok(neighbors);
+        }
         self.storage.get_neighbors(id)
     }
 
@@ -272,12 +935,48 @@ impl KnowledgeGraph {
         content: String,
         chunk_type: ChunkType,
     ) -> Result<Vec<ChunkId>> {
-        let pieces = split_text(&content);
+        let language = self.get_default_language()?;
+        let pieces = split_text_for_language(&content, language.as_deref());
         let mut ids = Vec::with_capacity(pieces.len());
         for piece in pieces {
-            let chunk = TextChunk::new(object_id, piece, chunk_type.clone());
-            ids.push(chunk.id);
+            let links = entity_links::has_link_syntax(&piece);
+            let chunk = TextChunk::new(object_id, piece, chunk_type.clone())
+                .with_language(language.clone());
+            let chunk_id = chunk.id;
+            ids.push(chunk_id);
             self.storage.upsert_chunk(chunk)?;
+            if links {
+                self.index_chunk_links(chunk_id)?;
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Like [`add_text_chunk`](Self::add_text_chunk), recording the span of
+    /// a recording the text was transcribed from (milliseconds from its
+    /// start) on every resulting chunk.
+    pub fn add_timed_text_chunk(
+        &self,
+        object_id: ObjectId,
+        content: String,
+        chunk_type: ChunkType,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<ChunkId>> {
+        let language = self.get_default_language()?;
+        let pieces = split_text_for_language(&content, language.as_deref());
+        let mut ids = Vec::with_capacity(pieces.len());
+        for piece in pieces {
+            let links = entity_links::has_link_syntax(&piece);
+            let chunk = TextChunk::new(object_id, piece, chunk_type.clone())
+                .with_language(language.clone())
+                .with_time_range(start_ms, end_ms);
+            let chunk_id = chunk.id;
+            ids.push(chunk_id);
+            self.storage.upsert_chunk(chunk)?;
+            if links {
+                self.index_chunk_links(chunk_id)?;
+            }
         }
         Ok(ids)
     }
@@ -299,18 +998,19 @@ impl KnowledgeGraph {
         chunk_type: ChunkType,
         embedding: &[f32],
     ) -> Result<ChunkId> {
-        let pieces = split_text(&content);
+        let language = self.get_default_language()?;
+        let pieces = split_text_for_language(&content, language.as_deref());
         if pieces.len() > 1 {
-            return Err(anyhow::anyhow!(
+            return Err(UForgeError::ValidationFailed(format!(
                 "add_text_chunk_with_embedding: content splits into {} chunks \
                  (max tokens per chunk: {}). Use add_text_chunk + upsert_chunk_embedding \
                  for long content.",
                 pieces.len(),
                 MAX_CHUNK_TOKENS,
-            ));
+            )));
         }
         let text = pieces.into_iter().next().unwrap_or_default();
-        let chunk = TextChunk::new(object_id, text, chunk_type);
+        let chunk = TextChunk::new(object_id, text, chunk_type).with_language(language);
         let chunk_id = chunk.id;
         self.storage.upsert_chunk(chunk)?;
         self.storage.upsert_chunk_embedding(chunk_id, embedding)?;
@@ -344,6 +1044,16 @@ impl KnowledgeGraph {
         self.storage.get_unembedded_chunks_hq()
     }
 
+    /// `object_id`'s chunks that have no 768-dim embedding yet.
+    pub fn get_unembedded_chunks_for_node(&self, object_id: ObjectId) -> Result<Vec<TextChunk>> {
+        self.storage.get_unembedded_chunks_for_node(object_id)
+    }
+
+    /// `object_id`'s chunks that have no 4096-dim embedding yet.
+    pub fn get_unembedded_chunks_hq_for_node(&self, object_id: ObjectId) -> Result<Vec<TextChunk>> {
+        self.storage.get_unembedded_chunks_hq_for_node(object_id)
+    }
+
     /// Delete all text chunks belonging to `object_id`.
     ///
     /// Triggers on `chunks` automatically clean up FTS5 and vector-index rows.
@@ -352,10 +1062,151 @@ impl KnowledgeGraph {
         self.storage.delete_chunks_for_node(object_id)
     }
 
+    /// Delete a single chunk.  Returns `false` if it did not exist.
+    pub fn delete_text_chunk(&self, chunk_id: ChunkId) -> Result<bool> {
+        self.storage.delete_chunk(chunk_id)
+    }
+
+    /// Override the language tag of a single chunk (e.g. a German quote in an
+    /// otherwise English campaign).  Returns `false` if the chunk does not exist.
+    pub fn set_chunk_language(&self, chunk_id: ChunkId, language: Option<&str>) -> Result<bool> {
+        self.storage.set_chunk_language(chunk_id, language)
+    }
+
+    /// Split `content` into chunk-sized pieces using the project's default
+    /// language — the same split [`add_text_chunk`](Self::add_text_chunk) applies.
+    pub(crate) fn split_chunk_text(&self, content: &str) -> Result<Vec<String>> {
+        let language = self.get_default_language()?;
+        Ok(split_text_for_language(content, language.as_deref()))
+    }
+
+    // ── Project settings ─────────────────────────────────────────────────────
+
+    /// The project's default content language as a BCP 47 tag (`"de"`,
+    /// `"ja"`, …), or `None` when unset (treated as English).
+    ///
+    /// New chunks are tagged with it, chunk splitting uses it to pick
+    /// sentence boundaries for unspaced scripts, and
+    /// [`ModelSelector::with_language`](lemonade::ModelSelector::with_language)
+    /// uses it to prefer multilingual embedding models.
+    pub fn get_default_language(&self) -> Result<Option<String>> {
+        self.storage.get_setting(DEFAULT_LANGUAGE_SETTING)
+    }
+
+    /// Set or clear the project's default content language.  Existing chunks
+    /// keep their tag until they are re-chunked.
+    pub fn set_default_language(&self, language: Option<&str>) -> Result<()> {
+        match language.map(str::trim).filter(|l| !l.is_empty()) {
+            Some(lang) => self.storage.set_setting(DEFAULT_LANGUAGE_SETTING, lang),
+            None => self.storage.delete_setting(DEFAULT_LANGUAGE_SETTING).map(|_| ()),
+        }
+    }
+
+    // ── Near-duplicate chunks ────────────────────────────────────────────────
+
+    /// Chunks whose 768-dim embeddings lie within `threshold` cosine distance
+    /// of `chunk_id`'s, closest first (the chunk itself excluded).
+    ///
+    /// Empty when `chunk_id` has not been embedded.  See
+    /// [`DEFAULT_DUPLICATE_THRESHOLD`] for a value suited to repeated pastes.
+    pub fn find_similar_chunks(&self, chunk_id: ChunkId, threshold: f32) -> Result<Vec<SimilarChunk>> {
+        self.storage.find_similar_chunks(chunk_id, threshold)
+    }
+
+    /// Report every group of embedded chunks that are near-duplicates of each
+    /// other (e.g. the same session notes imported twice), largest first.
+    pub fn find_duplicate_chunks(&self, threshold: f32) -> Result<Vec<DuplicateChunkGroup>> {
+        self.storage.find_duplicate_chunks(threshold)
+    }
+
+    /// Collapse a duplicate group onto its oldest chunk so the copies no
+    /// longer count twice in search.  The copies' edit history, consistency
+    /// warnings and missing metadata are folded into the kept chunk before
+    /// they are deleted.  Returns the number deleted.
+    pub fn merge_duplicate_chunks(&self, group: &DuplicateChunkGroup) -> Result<usize> {
+        let Some((&keeper, duplicates)) = group.chunk_ids.split_first() else {
+            return Ok(0);
+        };
+        self.storage.merge_chunks_into(keeper, duplicates)
+    }
+
+    /// Edit a chunk's text in place, keeping the previous version in its history.
+    ///
+    /// `author` is an optional tag recorded with the revision.  Returns
+    /// `Ok(false)` when the content is unchanged (hash compare) — no revision
+    /// is written and the existing embeddings are kept.  On a real change the
+    /// chunk's embeddings are dropped so the next embedding pass re-embeds it.
+    ///
+    /// Returns an error if `content` would split into more than one chunk
+    /// (see [`MAX_CHUNK_TOKENS`]).
+    pub fn update_text_chunk(
+        &self,
+        chunk_id: ChunkId,
+        content: &str,
+        author: Option<&str>,
+    ) -> Result<bool> {
+        let pieces = self.split_chunk_text(content)?;
+        if pieces.len() > 1 {
+            return Err(UForgeError::ValidationFailed(format!(
+                "update_text_chunk: content splits into {} chunks (max tokens per chunk: {})",
+                pieces.len(),
+                MAX_CHUNK_TOKENS,
+            )));
+        }
+        let text = pieces.into_iter().next().unwrap_or_default();
+        let changed = self.storage.update_chunk_content(chunk_id, &text, author)?;
+        if changed {
+            // Also clears the links of text that no longer has any.
+            self.index_chunk_links(chunk_id)?;
+        }
+        Ok(changed)
+    }
+
+    /// Every recorded revision of a chunk, oldest first.
+    ///
+    /// The last entry matches the chunk's current content.  Empty for chunks
+    /// that have never been edited through [`update_text_chunk`](Self::update_text_chunk).
+    pub fn get_chunk_history(&self, chunk_id: ChunkId) -> Result<Vec<ChunkRevision>> {
+        self.storage.get_chunk_history(chunk_id)
+    }
+
+    /// Restore a chunk to the content of an earlier revision.
+    ///
+    /// The revert is itself recorded as a new revision, so history is never
+    /// rewritten.  Returns `Ok(false)` if the chunk already has that content.
+    pub fn revert_chunk(
+        &self,
+        chunk_id: ChunkId,
+        revision: i64,
+        author: Option<&str>,
+    ) -> Result<bool> {
+        let target = self
+            .storage
+            .get_chunk_revision(revision)?
+            .filter(|r| r.chunk_id == chunk_id)
+            .ok_or_else(|| {
+                UForgeError::NotFound(format!(
+                    "Revision {revision} does not belong to chunk {chunk_id}"
+                ))
+            })?;
+        let changed = self
+            .storage
+            .update_chunk_content(chunk_id, &target.content, author)?;
+        if changed {
+            self.index_chunk_links(chunk_id)?;
+        }
+        Ok(changed)
+    }
+
     // ── Search ────────────────────────────────────────────────────────────────
 
     /// Exact name lookup scoped to a single object type.
     pub fn find_by_name(&self, object_type: &str, name: &str) -> Result<Vec<ObjectMetadata>> {
+        if self.on_branch() {
+            let mut found = self.find_by_name_only(name)?;
+            found.retain(|o| o.object_type == object_type);
+            return Ok(found);
+        }
         self.storage.find_nodes_by_name(object_type, name)
     }
 
@@ -365,6 +1216,9 @@ impl KnowledgeGraph {
     /// [`find_by_name`](Self::find_by_name) but useful when the type is unknown
     /// (e.g. cross-session edge resolution, BUG-7 fix).
     pub fn find_by_name_only(&self, name: &str) -> Result<Vec<ObjectMetadata>> {
+        if let Some(branch) = self.branch.read().as_ref() {
+            return self.staged(&branch.layer).find_by_name_only(name);
+        }
         self.storage.find_nodes_by_name_only(name)
     }
 
@@ -381,6 +1235,26 @@ impl KnowledgeGraph {
         self.storage.search_chunks_fts(query, limit)
     }
 
+    /// BM25-ranked search for chunks containing any of `terms`, as
+    /// `(chunk_id, object_id, content, score)` with the best match first.
+    pub fn search_chunks_bm25(
+        &self,
+        terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<(ChunkId, ObjectId, String, f32)>> {
+        self.storage.search_chunks_bm25(terms, limit)
+    }
+
+    /// `true` when `term` (one lowercase word) occurs in at least one chunk.
+    pub fn is_indexed_term(&self, term: &str) -> Result<bool> {
+        self.storage.is_indexed_term(term)
+    }
+
+    /// Distinct lowercase words appearing in node names, sorted.
+    pub fn name_vocabulary(&self) -> Result<Vec<String>> {
+        self.storage.name_vocabulary()
+    }
+
     /// Approximate nearest-neighbour search over stored chunk embeddings.
     ///
     /// Queries the `chunks_vec` sqlite-vec virtual table for the `limit` closest
@@ -425,6 +1299,46 @@ impl KnowledgeGraph {
             .search_chunks_semantic_hq(query_embedding, limit)
     }
 
+    // ── Object profile embeddings ────────────────────────────────────────────
+
+    /// The synthesized profile text indexed for `object_id` (see
+    /// [`ObjectMetadata::profile_text`]), or `None` if the object does not exist.
+    ///
+    /// Profiles are refreshed automatically on every object write.
+    pub fn get_object_profile(&self, object_id: ObjectId) -> Result<Option<String>> {
+        self.storage.get_node_profile(object_id)
+    }
+
+    /// Rebuild every object's profile, returning how many changed.
+    ///
+    /// Only needed for databases created before profiles existed; regular
+    /// writes keep profiles current.
+    pub fn sync_object_profiles(&self) -> Result<usize> {
+        self.storage.sync_node_profiles()
+    }
+
+    /// Profiles with no 768-dim embedding yet, as `(object_id, profile_text)`.
+    pub fn get_unembedded_profiles(&self) -> Result<Vec<(ObjectId, String)>> {
+        self.storage.get_unembedded_profiles()
+    }
+
+    /// Store or update the 768-dim embedding of an object's profile.
+    pub fn upsert_profile_embedding(&self, object_id: ObjectId, embedding: &[f32]) -> Result<()> {
+        self.storage.upsert_profile_embedding(object_id, embedding)
+    }
+
+    /// Approximate nearest-neighbour search over object profile embeddings.
+    ///
+    /// Returns `(object_id, profile_text, distance)` tuples ordered by
+    /// ascending cosine distance.
+    pub fn search_profiles_semantic(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(ObjectId, String, f32)>> {
+        self.storage.search_profiles_semantic(query_embedding, limit)
+    }
+
     // ── Graph traversal ───────────────────────────────────────────────────────
 
     /// BFS subgraph rooted at `start`, expanding up to `max_hops` hops.
@@ -432,13 +1346,46 @@ impl KnowledgeGraph {
         self.storage.query_subgraph(start, max_hops)
     }
 
+    /// [`query_subgraph`](Self::query_subgraph) against the world as it was
+    /// at `timestamp`.
+    ///
+    /// Objects and relationships come from their recorded history.  Chunks are
+    /// the object's current chunks that already existed at `timestamp`, with
+    /// content rolled back through their edit history.
+    pub fn query_subgraph_as_of(
+        &self,
+        start: ObjectId,
+        max_hops: usize,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<QueryResult> {
+        self.storage.query_subgraph_as_of(start, max_hops, timestamp)
+    }
+
     // ── Statistics ────────────────────────────────────────────────────────────
 
-    /// Counts of nodes, edges, chunks, and total tokens.  O(1) via SQL aggregates.
+    /// Counts of nodes, edges, chunks, and total tokens.  The aggregates run
+    /// in parallel on separate read-only connections.
     pub fn get_stats(&self) -> Result<GraphStats> {
         self.storage.get_stats()
     }
 
+    /// [`get_stats`](Self::get_stats) with per-type node, edge, lifecycle,
+    /// and chunk breakdowns, for the stats dashboard.
+    pub fn get_extended_stats(&self) -> Result<ExtendedStats> {
+        self.storage.get_extended_stats()
+    }
+
+    /// Hit/miss counters and size of the storage read cache.
+    pub fn read_cache_stats(&self) -> ReadCacheStats {
+        self.storage.read_cache_stats()
+    }
+
+    /// Set the storage read cache's byte budget
+    /// (default [`DEFAULT_READ_CACHE_BYTES`]); `0` disables it.
+    pub fn set_read_cache_capacity(&self, bytes: usize) {
+        self.storage.set_read_cache_capacity(bytes)
+    }
+
     // ── Layout persistence ────────────────────────────────────────────────────
 
     /// Persist canvas positions for the graph-view UI.
@@ -465,7 +1412,7 @@ impl KnowledgeGraph {
 
     /// Validate `object` against its registered schema.
     pub async fn validate_object(&self, object: &ObjectMetadata) -> Result<ValidationResult> {
-        self.schema_manager.validate_object(object).await
+        Ok(self.schema_manager.validate_object(object).await?)
     }
 
     /// Validate and coerce `properties` for `object_type` against the cached schema.
@@ -480,18 +1427,22 @@ impl KnowledgeGraph {
             .validate_and_coerce_properties(object_type, properties)
     }
 
-    /// Persist `metadata` only if it passes schema validation.
-    pub async fn add_object_validated(&self, metadata: ObjectMetadata) -> Result<ObjectId> {
+    /// Persist `metadata` only if it passes schema validation.  Defaults
+    /// (property values and lifecycle) are applied as by
+    /// [`add_object`](Self::add_object).
+    pub async fn add_object_validated(&self, mut metadata: ObjectMetadata) -> Result<ObjectId> {
+        if metadata.lifecycle.is_none() {
+            metadata.lifecycle = self.default_lifecycle_for(&metadata);
+        }
+        self.apply_schema_defaults(&mut metadata);
         let result = self.validate_object(&metadata).await?;
         if !result.valid {
-            return Err(anyhow::anyhow!(
+            return Err(UForgeError::ValidationFailed(format!(
                 "Object validation failed: {:?}",
                 result.errors
-            ));
+            )));
         }
-        let id = metadata.id;
-        self.storage.upsert_node(metadata)?;
-        Ok(id)
+        self.add_object(metadata)
     }
 
     /// Register a new object type in the `"default"` schema.
@@ -500,9 +1451,10 @@ impl KnowledgeGraph {
         type_name: &str,
         type_schema: ObjectTypeSchema,
     ) -> Result<()> {
-        self.schema_manager
+        Ok(self
+            .schema_manager
             .register_object_type("default", type_name, type_schema)
-            .await
+            .await?)
     }
 
     /// Register a new edge type in the `"default"` schema.
@@ -511,19 +1463,20 @@ impl KnowledgeGraph {
         edge_name: &str,
         edge_schema: EdgeTypeSchema,
     ) -> Result<()> {
-        self.schema_manager
+        Ok(self
+            .schema_manager
             .register_edge_type("default", edge_name, edge_schema)
-            .await
+            .await?)
     }
 
     /// Schema-level statistics for the named schema.
     pub async fn get_schema_stats(&self, schema_name: &str) -> Result<SchemaStats> {
-        self.schema_manager.get_schema_stats(schema_name).await
+        Ok(self.schema_manager.get_schema_stats(schema_name).await?)
     }
 
     /// Names of all schemas currently persisted.
     pub fn list_schemas(&self) -> Result<Vec<String>> {
-        self.schema_manager.list_schemas()
+        Ok(self.schema_manager.list_schemas()?)
     }
 
     /// Return a compact, LLM-readable summary of **all** persisted schemas,
@@ -560,6 +1513,9 @@ impl KnowledgeGraph {
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "native"))]
 #[path = "lib_tests.rs"]
 mod tests;
+
+#[cfg(all(test, feature = "native"))]
+mod proptests;
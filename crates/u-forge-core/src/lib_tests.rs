@@ -1,21 +1,12 @@
 // Integration tests for KnowledgeGraph facade and ObjectBuilder.
 // This file is included from lib.rs via #[cfg(test)] #[path = "lib_tests.rs"] mod tests;
 
-use tempfile::TempDir;
-
-use crate::graph::MAX_CHUNK_TOKENS;
-use crate::types::{ChunkType, EdgeType};
-use crate::{KnowledgeGraph, ObjectBuilder, ObjectTypeSchema, PropertySchema};
-
-fn create_test_graph() -> (KnowledgeGraph, TempDir) {
-    let temp_dir = TempDir::new().unwrap();
-    let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
-    (graph, temp_dir)
-}
-
-async fn create_test_graph_async() -> (KnowledgeGraph, TempDir) {
-    create_test_graph()
-}
+use crate::graph::{EMBEDDING_DIMENSIONS, MAX_CHUNK_TOKENS};
+use crate::test_helpers::create_test_graph;
+use crate::types::{
+    ChunkType, CreateRelationshipRequest, Edge, EdgeChanges, EdgeType, Lifecycle, ObjectId,
+};
+use crate::{EdgeTypeSchema, ObjectBuilder, ObjectTypeSchema, PropertySchema};
 
 // ── Basic CRUD ────────────────────────────────────────────────────────────
 
@@ -87,6 +78,68 @@ fn test_basic_graph_operations() {
     assert!(stats.total_tokens > 0);
 }
 
+#[test]
+fn test_builder_relationships_resolve_names() {
+    let (graph, _tmp) = create_test_graph();
+    let shire = ObjectBuilder::location("The Shire".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let fellowship = ObjectBuilder::faction("Fellowship".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+
+    let frodo = ObjectBuilder::character("Frodo".to_string())
+        .located_in("The Shire")
+        .member_of(fellowship)
+        .with_relationship("knows", "Fellowship")
+        .add_to_graph(&graph)
+        .unwrap();
+
+    let mut rels: Vec<(ObjectId, String)> = graph
+        .get_relationships(frodo)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.to, e.edge_type.into_inner()))
+        .collect();
+    rels.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        rels,
+        vec![
+            (fellowship, "knows".to_string()),
+            (shire, "located_in".to_string()),
+            (fellowship, "member_of".to_string()),
+        ]
+    );
+
+    // Unknown names fail before anything is written.
+    let result = ObjectBuilder::character("Sam".to_string())
+        .located_in("Mordor")
+        .add_to_graph(&graph);
+    assert!(result.is_err());
+    assert!(graph.find_by_name_only("Sam").unwrap().is_empty());
+
+    // A rejected edge removes the object again.
+    let result = ObjectBuilder::character("Sam".to_string())
+        .located_in(ObjectId::new_v4())
+        .add_to_graph(&graph);
+    assert!(result.is_err());
+    assert!(graph.find_by_name_only("Sam").unwrap().is_empty());
+
+    // Created like add_object: the event fires and schema defaults apply.
+    let mut events = graph.subscribe_events();
+    let sam = ObjectBuilder::character("Sam".to_string())
+        .located_in(shire)
+        .add_to_graph(&graph)
+        .unwrap();
+    assert!(
+        std::iter::from_fn(|| events.try_recv().ok()).any(|e| matches!(
+            e,
+            crate::GraphEvent::ObjectCreated { object_id, .. } if object_id == sam
+        ))
+    );
+    assert_eq!(graph.get_relationships(sam).unwrap().len(), 1);
+}
+
 #[test]
 fn test_find_by_name() {
     let (graph, _tmp) = create_test_graph();
@@ -193,6 +246,106 @@ fn test_fts_search() {
     assert!(empty.is_empty());
 }
 
+// ── Lifecycle ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_lifecycle_defaults_and_filtering() {
+    let (graph, _tmp) = create_test_graph();
+
+    let canon = ObjectBuilder::character("Aragorn".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let draft = ObjectBuilder::character("Unnamed Ranger".to_string())
+        .with_lifecycle(Lifecycle::Draft)
+        .add_to_graph(&graph)
+        .unwrap();
+
+    assert_eq!(
+        graph.get_object(canon).unwrap().unwrap().lifecycle,
+        Some(Lifecycle::Canon)
+    );
+    assert_eq!(graph.get_object_lifecycle(draft).unwrap(), Some(Lifecycle::Draft));
+
+    let canon_only = graph.get_objects_by_lifecycle(&[Lifecycle::Canon]).unwrap();
+    assert_eq!(canon_only.len(), 1);
+    assert_eq!(canon_only[0].id, canon);
+
+    // Updating with lifecycle = None preserves the stored state.
+    let mut meta = graph.get_object(draft).unwrap().unwrap();
+    meta.lifecycle = None;
+    meta.set_property("occupation".to_string(), "Scout".to_string());
+    graph.update_object(meta).unwrap();
+    assert_eq!(graph.get_object_lifecycle(draft).unwrap(), Some(Lifecycle::Draft));
+
+    graph.set_object_lifecycle(draft, Lifecycle::Canon).unwrap();
+    assert_eq!(
+        graph
+            .get_objects_by_lifecycle(&[Lifecycle::Canon])
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let err = graph
+        .set_object_lifecycle(ObjectId::new_v4(), Lifecycle::Canon)
+        .unwrap_err();
+    assert_eq!(err.kind(), crate::ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn test_schema_default_lifecycle_applied_on_add() {
+    let (graph, _tmp) = create_test_graph();
+
+    let idea = ObjectTypeSchema::new("idea".to_string(), "A brainstormed idea".to_string())
+        .with_default_lifecycle(Lifecycle::Draft);
+    graph.register_object_type("idea", idea).await.unwrap();
+
+    let id = ObjectBuilder::custom("idea".to_string(), "Sky pirates".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    assert_eq!(graph.get_object_lifecycle(id).unwrap(), Some(Lifecycle::Draft));
+
+    // An explicit lifecycle wins over the schema default.
+    let id = ObjectBuilder::custom("idea".to_string(), "Dragon council".to_string())
+        .with_lifecycle(Lifecycle::Canon)
+        .add_to_graph(&graph)
+        .unwrap();
+    assert_eq!(graph.get_object_lifecycle(id).unwrap(), Some(Lifecycle::Canon));
+
+    // Validated adds get the default too.
+    let meta = crate::types::ObjectMetadata::new("idea".to_string(), "Living city".to_string());
+    let id = graph.add_object_validated(meta).await.unwrap();
+    assert_eq!(graph.get_object_lifecycle(id).unwrap(), Some(Lifecycle::Draft));
+}
+
+#[tokio::test]
+async fn test_computed_properties_evaluated_on_read() {
+    let (graph, _tmp) = create_test_graph();
+
+    let monster = ObjectTypeSchema::new("monster".to_string(), "A monster".to_string())
+        .with_property("strength".to_string(), PropertySchema::number("STR score"))
+        .with_property(
+            "str_mod".to_string(),
+            PropertySchema::number("STR modifier").with_computed("floor((strength - 10) / 2)"),
+        );
+    graph.register_object_type("monster", monster).await.unwrap();
+
+    // A stale stored value is replaced by the computed one.
+    let id = ObjectBuilder::custom("monster".to_string(), "Ogre".to_string())
+        .with_json_property("strength".to_string(), serde_json::json!(19))
+        .with_json_property("str_mod".to_string(), serde_json::json!(0))
+        .add_to_graph(&graph)
+        .unwrap();
+    let ogre = graph.get_object_with_computed(id).unwrap().unwrap();
+    assert_eq!(ogre.properties["str_mod"], serde_json::json!(4));
+
+    let id = ObjectBuilder::custom("monster".to_string(), "Blob".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let blob = graph.get_object(id).unwrap().unwrap();
+    assert!(graph.computed_properties(&blob).is_empty());
+}
+
 // ── split_text (via add_text_chunk) ──────────────────────────────────────
 
 #[test]
@@ -240,11 +393,225 @@ fn test_add_text_chunk_long_content_stored_as_multiple_chunks() {
     );
 }
 
+#[test]
+fn test_default_language_tags_new_chunks() {
+    let (graph, _tmp) = create_test_graph();
+    let obj_id = ObjectBuilder::character("Aiko".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    assert_eq!(graph.get_default_language().unwrap(), None);
+
+    let before = graph
+        .add_text_chunk(obj_id, "Born in the capital.".to_string(), ChunkType::Description)
+        .unwrap()[0];
+
+    graph.set_default_language(Some("ja")).unwrap();
+    assert_eq!(graph.get_default_language().unwrap().as_deref(), Some("ja"));
+    let after = graph
+        .add_text_chunk(obj_id, "都で生まれた。".to_string(), ChunkType::Description)
+        .unwrap()[0];
+
+    let chunks = graph.get_text_chunks(obj_id).unwrap();
+    let lang_of = |id| chunks.iter().find(|c| c.id == id).unwrap().language.clone();
+    assert_eq!(lang_of(before), None);
+    assert_eq!(lang_of(after).as_deref(), Some("ja"));
+
+    assert!(graph.set_chunk_language(before, Some("en")).unwrap());
+    let chunks = graph.get_text_chunks(obj_id).unwrap();
+    let tagged = chunks.iter().find(|c| c.id == before).unwrap();
+    assert_eq!(tagged.language.as_deref(), Some("en"));
+
+    graph.set_default_language(None).unwrap();
+    assert_eq!(graph.get_default_language().unwrap(), None);
+}
+
+// ── Chunk edit history ───────────────────────────────────────────────────
+
+#[test]
+fn test_chunk_history_and_revert() {
+    let (graph, _tmp) = create_test_graph();
+    let obj_id = ObjectBuilder::character("Boromir".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let chunk_id = graph
+        .add_text_chunk(obj_id, "Boromir died at Amon Hen.".to_string(), ChunkType::UserNote)
+        .unwrap()[0];
+    graph
+        .upsert_chunk_embedding(chunk_id, &vec![0.1; EMBEDDING_DIMENSIONS])
+        .unwrap();
+
+    // Identical content is a no-op and keeps the embedding.
+    assert!(!graph
+        .update_text_chunk(chunk_id, "Boromir died at Amon Hen.", Some("gm"))
+        .unwrap());
+    assert!(graph.get_chunk_history(chunk_id).unwrap().is_empty());
+    assert!(graph.get_unembedded_chunks().unwrap().is_empty());
+
+    // A real edit records baseline + new revision and drops the stale vector.
+    assert!(graph
+        .update_text_chunk(chunk_id, "Boromir died defending the hobbits.", Some("gm"))
+        .unwrap());
+    let history = graph.get_chunk_history(chunk_id).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].content, "Boromir died at Amon Hen.");
+    assert_eq!(history[0].author, None);
+    assert_eq!(history[1].author.as_deref(), Some("gm"));
+    assert_eq!(graph.get_unembedded_chunks().unwrap().len(), 1);
+    assert_eq!(graph.search_chunks_fts("defending", 10).unwrap().len(), 1);
+
+    // Revert restores old content as a new revision.
+    assert!(graph
+        .revert_chunk(chunk_id, history[0].revision, Some("gm"))
+        .unwrap());
+    let history = graph.get_chunk_history(chunk_id).unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(
+        graph.get_text_chunks(obj_id).unwrap()[0].content,
+        "Boromir died at Amon Hen."
+    );
+
+    // A revision from another chunk is refused.
+    let other = graph
+        .add_text_chunk(obj_id, "Son of Denethor.".to_string(), ChunkType::UserNote)
+        .unwrap()[0];
+    assert!(graph.revert_chunk(other, history[0].revision, None).is_err());
+}
+
+// ── Time-travel queries ──────────────────────────────────────────────────
+
+#[test]
+fn test_object_and_subgraph_as_of() {
+    let pause = || std::thread::sleep(std::time::Duration::from_millis(5));
+    let (graph, _tmp) = create_test_graph();
+    let boromir = ObjectBuilder::character("Boromir".to_string())
+        .with_property("status".to_string(), "alive".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let gondor = ObjectBuilder::location("Gondor".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    graph.connect_objects_str(boromir, gondor, "defends").unwrap();
+    pause();
+    let before_retcon = chrono::Utc::now();
+    pause();
+
+    // Retcon: Boromir dies, Faramir appears, the edge is removed.
+    let mut meta = graph.get_object(boromir).unwrap().unwrap();
+    meta.set_property("status".to_string(), "dead".to_string());
+    graph.update_object(meta).unwrap();
+    let faramir = ObjectBuilder::character("Faramir".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    graph.connect_objects_str(faramir, gondor, "defends").unwrap();
+    graph.delete_edge(boromir, gondor, "defends").unwrap();
+
+    let then = graph.get_object_as_of(boromir, before_retcon).unwrap().unwrap();
+    assert_eq!(then.get_property("status"), Some("alive".to_string()));
+    assert!(graph.get_object_as_of(faramir, before_retcon).unwrap().is_none());
+
+    let past = graph.query_subgraph_as_of(gondor, 1, before_retcon).unwrap();
+    let names: Vec<&str> = past.objects.iter().map(|o| o.name.as_str()).collect();
+    assert_eq!(past.objects.len(), 2);
+    assert!(names.contains(&"Boromir") && !names.contains(&"Faramir"));
+    assert_eq!(past.edges.len(), 1);
+    assert_eq!(past.edges[0].from, boromir);
+
+    let now = graph.query_subgraph(gondor, 1).unwrap();
+    assert!(now.objects.iter().any(|o| o.name == "Faramir"));
+
+    // Deleted objects stay visible in the past but not after deletion.
+    graph.delete_object(boromir).unwrap();
+    assert!(graph.get_object_as_of(boromir, before_retcon).unwrap().is_some());
+    assert!(graph
+        .get_object_as_of(boromir, chrono::Utc::now())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_edges_as_of_keep_stored_ids() {
+    let pause = || std::thread::sleep(std::time::Duration::from_millis(5));
+    let (graph, _tmp) = create_test_graph();
+    let aragorn = ObjectBuilder::character("Aragorn".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let gondor = ObjectBuilder::location("Gondor".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    graph.connect_objects_str(aragorn, gondor, "visits").unwrap();
+    let edge = graph.get_relationships(aragorn).unwrap()[0].clone();
+    pause();
+    let before_retype = chrono::Utc::now();
+    pause();
+    graph
+        .update_edge(
+            edge.id,
+            EdgeChanges { edge_type: Some(EdgeType::new("rules")), ..Default::default() },
+        )
+        .unwrap();
+
+    // Repeated reads report the stored id, and the retyped edge is one edge.
+    let past = graph.query_subgraph_as_of(aragorn, 1, before_retype).unwrap();
+    assert_eq!(past.edges.len(), 1);
+    assert_eq!(past.edges[0].id, edge.id);
+    assert_eq!(past.edges[0].edge_type.as_str(), "visits");
+    let now = graph.query_subgraph_as_of(aragorn, 1, chrono::Utc::now()).unwrap();
+    assert_eq!(now.edges.len(), 1);
+    assert_eq!(now.edges[0].id, edge.id);
+    assert_eq!(now.edges[0].edge_type.as_str(), "rules");
+}
+
+// ── Object profile embeddings ────────────────────────────────────────────
+
+#[test]
+fn test_object_profile_tracks_updates() {
+    let (graph, _tmp) = create_test_graph();
+    let obj_id = ObjectBuilder::character("Elrond".to_string())
+        .with_property("title".to_string(), "Lord of Rivendell".to_string())
+        .with_description("x".repeat(500))
+        .add_to_graph(&graph)
+        .unwrap();
+
+    // New objects get a profile without any chunks; long prose is left out.
+    assert!(graph.get_text_chunks(obj_id).unwrap().is_empty());
+    let profile = graph.get_object_profile(obj_id).unwrap().unwrap();
+    assert!(profile.contains("Name: Elrond"));
+    assert!(profile.contains("title: Lord of Rivendell"));
+    assert!(!profile.contains("description"));
+    assert_eq!(graph.get_unembedded_profiles().unwrap().len(), 1);
+
+    let mut hot = vec![0.0; EMBEDDING_DIMENSIONS];
+    hot[0] = 1.0;
+    graph.upsert_profile_embedding(obj_id, &hot).unwrap();
+    assert!(graph.get_unembedded_profiles().unwrap().is_empty());
+    let hits = graph.search_profiles_semantic(&hot, 5).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].0, obj_id);
+
+    // Updates that leave the profile unchanged keep the vector...
+    let meta = graph.get_object(obj_id).unwrap().unwrap();
+    graph.update_object(meta).unwrap();
+    assert!(graph.get_unembedded_profiles().unwrap().is_empty());
+
+    // ...while a changed property drops it for re-embedding.
+    graph
+        .storage
+        .set_node_property(obj_id, "title", &serde_json::json!("Half-elven"))
+        .unwrap();
+    assert!(graph
+        .get_object_profile(obj_id)
+        .unwrap()
+        .unwrap()
+        .contains("title: Half-elven"));
+    assert_eq!(graph.get_unembedded_profiles().unwrap().len(), 1);
+    assert!(graph.search_profiles_semantic(&hot, 5).unwrap().is_empty());
+}
+
 // ── Schema integration ────────────────────────────────────────────────────
 
 #[tokio::test]
 async fn test_schema_integration() {
-    let (graph, _tmp) = create_test_graph_async().await;
+    let (graph, _tmp) = create_test_graph();
 
     let spell_schema = ObjectTypeSchema::new("spell".to_string(), "A magical spell".to_string())
         .with_property("level".to_string(), PropertySchema::number("Spell level"))
@@ -288,7 +655,7 @@ async fn test_schema_integration() {
 
 #[tokio::test]
 async fn test_validation_failure() {
-    let (graph, _tmp) = create_test_graph_async().await;
+    let (graph, _tmp) = create_test_graph();
 
     use crate::types::ObjectMetadata;
     let bad = ObjectMetadata::new("unknown_type_xyz".to_string(), "Test".to_string());
@@ -299,3 +666,256 @@ async fn test_validation_failure() {
     let insert_result = graph.add_object_validated(bad).await;
     assert!(insert_result.is_err());
 }
+
+#[tokio::test]
+async fn test_schema_defaults_fill_new_objects() {
+    let (graph, _tmp) = create_test_graph();
+
+    let spell_schema = ObjectTypeSchema::new("spell".to_string(), "A magical spell".to_string())
+        .with_property(
+            "level".to_string(),
+            PropertySchema::number("Spell level").with_default(serde_json::json!(1)),
+        )
+        .with_property(
+            "school".to_string(),
+            PropertySchema::string("School of magic").with_default(serde_json::json!("Evocation")),
+        )
+        .with_required_property("level".to_string());
+    graph
+        .register_object_type("spell", spell_schema)
+        .await
+        .unwrap();
+
+    // new_object starts valid even though "level" is required.
+    let draft = graph
+        .new_object("spell", "Magic Missile")
+        .with_json_property("school".to_string(), serde_json::json!("Abjuration"))
+        .build();
+    assert_eq!(draft.properties["level"], serde_json::json!(1));
+    assert_eq!(draft.properties["school"], serde_json::json!("Abjuration"));
+    assert!(graph.validate_object(&draft).await.unwrap().valid);
+
+    // add_object fills defaults for objects built without them.
+    let id = ObjectBuilder::custom("spell".to_string(), "Fireball".to_string())
+        .with_json_property("level".to_string(), serde_json::json!(3))
+        .add_to_graph(&graph)
+        .unwrap();
+    let stored = graph.get_object(id).unwrap().unwrap();
+    assert_eq!(stored.properties["level"], serde_json::json!(3));
+    assert_eq!(stored.properties["school"], serde_json::json!("Evocation"));
+}
+
+#[tokio::test]
+async fn test_strict_edges_reject_schema_violations() {
+    use crate::schema::{Cardinality, RelationshipDefinition};
+
+    let (graph, _tmp) = create_test_graph();
+    let frodo = ObjectBuilder::character("Frodo".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let shire = ObjectBuilder::location("The Shire".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let fellowship = ObjectBuilder::faction("Fellowship".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+
+    // Off by default: anything goes.
+    assert!(!graph.strict_edges().unwrap());
+    graph.connect_objects_str(shire, fellowship, "member_of").unwrap();
+    graph.delete_edge(shire, fellowship, "member_of").unwrap();
+
+    graph.set_strict_edges(true).unwrap();
+    graph.connect_objects_str(frodo, fellowship, "member_of").unwrap();
+    assert!(graph.connect_objects_str(shire, fellowship, "member_of").is_err());
+
+    // Intentional exception.
+    graph.add_edge(Edge::new(shire, fellowship, EdgeType::new("member_of")), true).unwrap();
+    let exception = graph
+        .get_relationships(shire)
+        .unwrap()
+        .into_iter()
+        .find(|e| e.edge_type.as_str() == "member_of")
+        .unwrap();
+    assert_eq!(exception.metadata[crate::SCHEMA_EXCEPTION_KEY], "true");
+
+    // Cardinality: each ship has one captain.
+    let captain = RelationshipDefinition::new("captained_by".to_string(), "Captain".to_string())
+        .with_cardinality(Cardinality::ManyToOne);
+    let ship_schema = ObjectTypeSchema::new("ship".to_string(), "A vessel".to_string())
+        .with_property(
+            "captain".to_string(),
+            PropertySchema::string("Captain").with_relationship(captain),
+        );
+    graph.register_object_type("ship", ship_schema).await.unwrap();
+    let ship = ObjectBuilder::custom("ship".to_string(), "Grey Ship".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    graph.connect_objects_str(ship, frodo, "captained_by").unwrap();
+    // Re-writing the same edge is fine; a second captain is not.
+    graph.connect_objects_str(ship, frodo, "captained_by").unwrap();
+    let sam = ObjectBuilder::character("Sam".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    assert!(graph.connect_objects_str(ship, sam, "captained_by").is_err());
+}
+
+#[tokio::test]
+async fn test_create_relationship_with_properties() {
+    let (graph, _tmp) = create_test_graph();
+    let frodo = ObjectBuilder::character("Frodo".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let fellowship = ObjectBuilder::faction("Fellowship".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+
+    let edge = graph
+        .create_relationship(
+            CreateRelationshipRequest::new(frodo, fellowship, "member_of")
+                .with_property("role", "Ring-bearer")
+                .with_weight(0.9),
+        )
+        .unwrap();
+    assert_eq!((edge.from, edge.to), (frodo, fellowship));
+    assert_eq!(edge.metadata["role"], "Ring-bearer");
+    assert_eq!(edge.weight, 0.9);
+
+    // Undeclared properties are rejected and nothing is written.
+    let err = graph
+        .create_relationship(
+            CreateRelationshipRequest::new(frodo, fellowship, "enemy_of")
+                .with_property("role", "Spy"),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("role"));
+    assert_eq!(graph.get_relationships(frodo).unwrap().len(), 1);
+
+    // Typed properties are parsed and stored in canonical form.
+    graph
+        .register_edge_type(
+            "sworn_to",
+            EdgeTypeSchema::new("sworn_to".to_string(), "Oath".to_string())
+                .with_property("oath_year".to_string(), PropertySchema::number("Year sworn"))
+                .with_property("binding".to_string(), PropertySchema::boolean("Magically binding")),
+        )
+        .await
+        .unwrap();
+    let oath = graph
+        .create_relationship(
+            CreateRelationshipRequest::new(frodo, fellowship, "sworn_to")
+                .with_property("oath_year", " 3018 ")
+                .with_property("binding", "yes"),
+        )
+        .unwrap();
+    assert_eq!(oath.metadata["oath_year"], "3018");
+    assert_eq!(oath.metadata["binding"], "true");
+    assert!(graph
+        .create_relationship(
+            CreateRelationshipRequest::new(frodo, fellowship, "sworn_to")
+                .with_property("oath_year", "long ago"),
+        )
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_update_edge_by_id() {
+    let (graph, _tmp) = create_test_graph();
+    let frodo = ObjectBuilder::character("Frodo".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let fellowship = ObjectBuilder::faction("Fellowship".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let shire = ObjectBuilder::location("The Shire".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+
+    let edge = graph
+        .create_relationship(CreateRelationshipRequest::new(frodo, fellowship, "member_of"))
+        .unwrap();
+    assert_eq!(graph.get_edge(edge.id).unwrap().unwrap(), edge);
+
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert("role".to_string(), "Ring-bearer".to_string());
+    let updated = graph
+        .update_edge(
+            edge.id,
+            EdgeChanges { weight: Some(0.7), metadata: Some(metadata), ..Default::default() },
+        )
+        .unwrap();
+    assert_eq!(updated.id, edge.id);
+    assert_eq!(updated.metadata["role"], "Ring-bearer");
+
+    // Changes are validated like new relationships.
+    let mut bad = std::collections::HashMap::new();
+    bad.insert("motto".to_string(), "Go home".to_string());
+    assert!(graph
+        .update_edge(edge.id, EdgeChanges { metadata: Some(bad), ..Default::default() })
+        .is_err());
+    graph.set_strict_edges(true).unwrap();
+    assert!(graph
+        .update_edge(edge.id, EdgeChanges { from: Some(shire), ..Default::default() })
+        .is_err());
+    assert_eq!(graph.get_edge(edge.id).unwrap().unwrap().from, frodo);
+
+    assert!(graph.delete_edge_by_id(edge.id).unwrap());
+    assert!(graph.get_relationships(frodo).unwrap().is_empty());
+    assert!(graph.update_edge(edge.id, EdgeChanges::default()).is_err());
+}
+
+#[test]
+fn test_errors_carry_their_kind() {
+    use crate::{EdgeId, ErrorKind, UForgeError};
+    use anyhow::Context;
+
+    let (graph, _tmp) = create_test_graph();
+    let frodo = ObjectBuilder::character("Frodo".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+    let shire = ObjectBuilder::location("The Shire".to_string())
+        .add_to_graph(&graph)
+        .unwrap();
+
+    let err = graph
+        .update_edge(EdgeId::new_v4(), EdgeChanges::default())
+        .context("Failed to edit edge")
+        .unwrap_err();
+    assert_eq!(UForgeError::kind_of(&err), ErrorKind::NotFound);
+    assert!(matches!(UForgeError::from(err), UForgeError::NotFound(_)));
+
+    graph.set_strict_edges(true).unwrap();
+    let err = graph.connect_objects_str(shire, frodo, "member_of").unwrap_err();
+    let err = err;
+    assert_eq!(err.kind(), ErrorKind::SchemaConflict);
+    assert!(err.to_string().contains("strict mode"));
+
+    let err = anyhow::anyhow!("something else");
+    assert_eq!(UForgeError::from(err).kind(), ErrorKind::Internal);
+}
+
+#[tokio::test]
+async fn test_object_created_is_emitted_once_per_new_object() {
+    use crate::{GraphEvent, ObjectMetadata, StagingLayer};
+
+    let (graph, _tmp) = create_test_graph();
+    let mut events = graph.subscribe_events();
+
+    let frodo = ObjectMetadata::new("character".to_string(), "Frodo".to_string());
+    graph.add_object(frodo.clone()).unwrap();
+    graph.add_object(frodo.clone()).unwrap();
+    let sam = ObjectMetadata::new("character".to_string(), "Sam".to_string());
+    let sam_id = graph.add_object_validated(sam).await.unwrap();
+    let mut layer = StagingLayer::new("shire");
+    let shire = layer.add_object(ObjectBuilder::location("The Shire".to_string()).build());
+    layer.add_object(frodo.clone());
+    graph.commit_staging(layer).unwrap();
+
+    let created: Vec<ObjectId> = std::iter::from_fn(|| events.try_recv().ok())
+        .filter_map(|e| match e {
+            GraphEvent::ObjectCreated { object_id, .. } => Some(object_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(created, [frodo.id, sam_id, shire]);
+}
@@ -0,0 +1,436 @@
+//! Family trees — lineage edges between characters.
+//!
+//! Lineage is stored as ordinary edges, always in matched pairs so either
+//! side of a relationship can be read from one object's edges:
+//!
+//! - [`PARENT_OF_EDGE`] from parent to child, with [`CHILD_OF_EDGE`] back;
+//! - [`SIBLING_OF_EDGE`] in both directions;
+//! - [`MARRIED_TO_EDGE`] in both directions.
+//!
+//! [`KnowledgeGraph::add_parent`] also links the child as a sibling of the
+//! parent's other children.  Edges written some other way (an import, the
+//! raw edge API) are brought back into shape by
+//! [`KnowledgeGraph::repair_lineage`].  [`KnowledgeGraph::get_family_tree`]
+//! lays a character's family out by generation for tree rendering.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::staging::StagingLayer;
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Edge type from a parent to a child.
+pub const PARENT_OF_EDGE: &str = "parent_of";
+
+/// Edge type from a child to a parent; the inverse of [`PARENT_OF_EDGE`].
+pub const CHILD_OF_EDGE: &str = "child_of";
+
+/// Edge type between siblings, stored in both directions.
+pub const SIBLING_OF_EDGE: &str = "sibling_of";
+
+/// Edge type between spouses, stored in both directions.
+pub const MARRIED_TO_EDGE: &str = "married_to";
+
+/// One person in a [`FamilyTree`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FamilyMember {
+    pub id: ObjectId,
+    pub name: String,
+    pub object_type: String,
+    /// Parents that are also in the tree.
+    pub parents: Vec<ObjectId>,
+    /// Spouses that are also in the tree.
+    pub spouses: Vec<ObjectId>,
+}
+
+/// The members of one generation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FamilyGeneration {
+    /// Relative to the root: `0` is the root's own generation, `-1` its
+    /// parents, `1` its children.
+    pub level: i32,
+    /// By name.
+    pub members: Vec<FamilyMember>,
+}
+
+/// The result of [`KnowledgeGraph::get_family_tree`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FamilyTree {
+    pub root: ObjectId,
+    /// Oldest generation first.
+    pub generations: Vec<FamilyGeneration>,
+}
+
+impl FamilyTree {
+    /// The generation `id` sits in, if it is in the tree.
+    pub fn level_of(&self, id: ObjectId) -> Option<i32> {
+        self.generations
+            .iter()
+            .find(|g| g.members.iter().any(|m| m.id == id))
+            .map(|g| g.level)
+    }
+}
+
+/// Lineage relations read from one object's edges, whichever side of each
+/// pair was written.
+#[derive(Default)]
+struct Kin {
+    parents: Vec<ObjectId>,
+    children: Vec<ObjectId>,
+    siblings: Vec<ObjectId>,
+    spouses: Vec<ObjectId>,
+}
+
+fn push_unique(list: &mut Vec<ObjectId>, id: ObjectId) {
+    if !list.contains(&id) {
+        list.push(id);
+    }
+}
+
+impl KnowledgeGraph {
+    /// Record `parent` as a parent of `child`, writing the inverse edge and
+    /// sibling edges to the parent's other children in one commit.  Fails
+    /// with [`UForgeError::NotFound`] when either is missing and
+    /// [`UForgeError::ValidationFailed`] when the link would make someone
+    /// their own ancestor.
+    pub fn add_parent(&self, parent: ObjectId, child: ObjectId) -> Result<()> {
+        self.require_kin(&[parent, child])?;
+        if parent == child || self.ancestors(parent)?.contains(&child) {
+            return Err(UForgeError::ValidationFailed(format!(
+                "{parent} cannot be a parent of its own ancestor {child}"
+            )));
+        }
+        let mut layer = StagingLayer::new("lineage");
+        self.stage_pair(&mut layer, parent, child, PARENT_OF_EDGE, CHILD_OF_EDGE)?;
+        for sibling in self.kin(parent)?.children {
+            if sibling != child {
+                self.stage_pair(&mut layer, child, sibling, SIBLING_OF_EDGE, SIBLING_OF_EDGE)?;
+            }
+        }
+        self.commit_staging(layer).map(|_| ())
+    }
+
+    /// Remove the parent link between `parent` and `child` (both
+    /// directions).  Sibling edges are left alone.
+    pub fn remove_parent(&self, parent: ObjectId, child: ObjectId) -> Result<()> {
+        self.delete_edge(parent, child, PARENT_OF_EDGE)?;
+        self.delete_edge(child, parent, CHILD_OF_EDGE)
+    }
+
+    /// Record `a` and `b` as siblings, in both directions.
+    pub fn add_sibling(&self, a: ObjectId, b: ObjectId) -> Result<()> {
+        self.link_symmetric(a, b, SIBLING_OF_EDGE)
+    }
+
+    /// Record `a` and `b` as married, in both directions.
+    pub fn add_spouse(&self, a: ObjectId, b: ObjectId) -> Result<()> {
+        self.link_symmetric(a, b, MARRIED_TO_EDGE)
+    }
+
+    /// Remove the marriage between `a` and `b` (both directions).
+    pub fn remove_spouse(&self, a: ObjectId, b: ObjectId) -> Result<()> {
+        self.delete_edge(a, b, MARRIED_TO_EDGE)?;
+        self.delete_edge(b, a, MARRIED_TO_EDGE)
+    }
+
+    /// Parents of `id`, by name.
+    pub fn parents(&self, id: ObjectId) -> Result<Vec<ObjectId>> {
+        self.sorted_by_name(self.kin(id)?.parents)
+    }
+
+    /// Children of `id`, by name.
+    pub fn children(&self, id: ObjectId) -> Result<Vec<ObjectId>> {
+        self.sorted_by_name(self.kin(id)?.children)
+    }
+
+    /// Add every missing half of a lineage pair: the `child_of` for each
+    /// `parent_of` and vice versa, the reverse of each `sibling_of` and
+    /// `married_to`, and sibling edges between children sharing a parent.
+    /// Returns how many edges were written.
+    pub fn repair_lineage(&self) -> Result<usize> {
+        let edges = self.get_all_edges()?;
+        let existing: HashSet<(ObjectId, ObjectId, &str)> = edges
+            .iter()
+            .map(|e| (e.from, e.to, e.edge_type.as_str()))
+            .collect();
+        let mut wanted: Vec<(ObjectId, ObjectId, &str)> = Vec::new();
+        let mut children: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        for edge in &edges {
+            let (parent, child) = match edge.edge_type.as_str() {
+                PARENT_OF_EDGE => (edge.from, edge.to),
+                CHILD_OF_EDGE => (edge.to, edge.from),
+                SIBLING_OF_EDGE | MARRIED_TO_EDGE => {
+                    wanted.push((edge.to, edge.from, edge.edge_type.as_str()));
+                    continue;
+                }
+                _ => continue,
+            };
+            wanted.push((parent, child, PARENT_OF_EDGE));
+            wanted.push((child, parent, CHILD_OF_EDGE));
+            push_unique(children.entry(parent).or_default(), child);
+        }
+        for siblings in children.values() {
+            for a in siblings {
+                for b in siblings {
+                    if a != b {
+                        wanted.push((*a, *b, SIBLING_OF_EDGE));
+                    }
+                }
+            }
+        }
+
+        let mut layer = StagingLayer::new("lineage");
+        let mut staged = HashSet::new();
+        for (from, to, edge_type) in wanted {
+            if from != to
+                && !existing.contains(&(from, to, edge_type))
+                && staged.insert((from, to, edge_type))
+            {
+                layer.connect_str(from, to, edge_type);
+            }
+        }
+        let added = staged.len();
+        if added > 0 {
+            self.commit_staging(layer)?;
+        }
+        Ok(added)
+    }
+
+    /// `id`'s family, up to `generations` levels above and below it.
+    ///
+    /// The root's own generation holds the root, its siblings and spouses.
+    /// Each older generation holds the parents of the one below it; each
+    /// younger one the children of the root's line and of the root's
+    /// spouses.  Everyone appears once, in the generation nearest the root.
+    /// Fails with [`UForgeError::NotFound`] for an unknown `id`.
+    pub fn get_family_tree(&self, id: ObjectId, generations: usize) -> Result<FamilyTree> {
+        self.require_kin(&[id])?;
+        let mut placed: HashSet<ObjectId> = HashSet::from([id]);
+        let mut levels: BTreeMap<i32, Vec<ObjectId>> = BTreeMap::new();
+
+        let root_kin = self.kin(id)?;
+        let mut own = vec![id];
+        for other in root_kin.siblings.iter().chain(&root_kin.spouses) {
+            if placed.insert(*other) {
+                own.push(*other);
+            }
+        }
+
+        let mut frontier = vec![id];
+        for level in 1..=generations as i32 {
+            let mut next = Vec::new();
+            for member in &frontier {
+                for parent in self.kin(*member)?.parents {
+                    if placed.insert(parent) {
+                        next.push(parent);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            levels.insert(-level, next.clone());
+            frontier = next;
+        }
+
+        let mut frontier: Vec<ObjectId> = std::iter::once(id)
+            .chain(root_kin.spouses.iter().copied())
+            .collect();
+        for level in 1..=generations as i32 {
+            let mut next = Vec::new();
+            for member in &frontier {
+                for child in self.kin(*member)?.children {
+                    if placed.insert(child) {
+                        next.push(child);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            levels.insert(level, next.clone());
+            frontier = next;
+        }
+        levels.insert(0, own);
+
+        let mut tree = FamilyTree {
+            root: id,
+            generations: Vec::with_capacity(levels.len()),
+        };
+        for (level, ids) in levels {
+            let mut members = Vec::with_capacity(ids.len());
+            for member in ids {
+                let Some(object) = self.get_object(member)? else {
+                    continue;
+                };
+                let kin = self.kin(member)?;
+                members.push(FamilyMember {
+                    id: member,
+                    name: object.name,
+                    object_type: object.object_type,
+                    parents: kin.parents.into_iter().filter(|p| placed.contains(p)).collect(),
+                    spouses: kin.spouses.into_iter().filter(|s| placed.contains(s)).collect(),
+                });
+            }
+            members.sort_by(|a, b| a.name.cmp(&b.name));
+            tree.generations.push(FamilyGeneration { level, members });
+        }
+        Ok(tree)
+    }
+
+    /// Lineage relations of `id`.
+    fn kin(&self, id: ObjectId) -> Result<Kin> {
+        let mut kin = Kin::default();
+        for edge in self.get_relationships(id)? {
+            let (from, to) = (edge.from, edge.to);
+            let other = if from == id { to } else { from };
+            if other == id {
+                continue;
+            }
+            let list = match (edge.edge_type.as_str(), from == id) {
+                (PARENT_OF_EDGE, true) | (CHILD_OF_EDGE, false) => &mut kin.children,
+                (PARENT_OF_EDGE, false) | (CHILD_OF_EDGE, true) => &mut kin.parents,
+                (SIBLING_OF_EDGE, _) => &mut kin.siblings,
+                (MARRIED_TO_EDGE, _) => &mut kin.spouses,
+                _ => continue,
+            };
+            push_unique(list, other);
+        }
+        Ok(kin)
+    }
+
+    /// Every ancestor of `id`.
+    fn ancestors(&self, id: ObjectId) -> Result<HashSet<ObjectId>> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(next) = stack.pop() {
+            for parent in self.kin(next)?.parents {
+                if seen.insert(parent) {
+                    stack.push(parent);
+                }
+            }
+        }
+        Ok(seen)
+    }
+
+    fn link_symmetric(&self, a: ObjectId, b: ObjectId, edge_type: &str) -> Result<()> {
+        self.require_kin(&[a, b])?;
+        if a == b {
+            return Err(UForgeError::ValidationFailed(format!(
+                "{a} cannot be linked to itself by {edge_type}"
+            )));
+        }
+        let mut layer = StagingLayer::new("lineage");
+        self.stage_pair(&mut layer, a, b, edge_type, edge_type)?;
+        self.commit_staging(layer).map(|_| ())
+    }
+
+    /// Stage `from -forward-> to` and `to -inverse-> from`, skipping halves
+    /// that already exist.
+    fn stage_pair(
+        &self,
+        layer: &mut StagingLayer,
+        from: ObjectId,
+        to: ObjectId,
+        forward: &str,
+        inverse: &str,
+    ) -> Result<()> {
+        let edges = self.get_relationships(from)?;
+        let has = |a: ObjectId, b: ObjectId, edge_type: &str| {
+            edges
+                .iter()
+                .any(|e| e.from == a && e.to == b && e.edge_type.as_str() == edge_type)
+        };
+        if !has(from, to, forward) {
+            layer.connect_str(from, to, forward);
+        }
+        if !has(to, from, inverse) {
+            layer.connect_str(to, from, inverse);
+        }
+        Ok(())
+    }
+
+    fn require_kin(&self, ids: &[ObjectId]) -> Result<()> {
+        for id in ids {
+            if self.get_object(*id)?.is_none() {
+                return Err(UForgeError::NotFound(format!("Unknown object {id}")));
+            }
+        }
+        Ok(())
+    }
+
+    fn sorted_by_name(&self, ids: Vec<ObjectId>) -> Result<Vec<ObjectId>> {
+        let mut named = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(object) = self.get_object(id)? {
+                named.push((object.name, id));
+            }
+        }
+        named.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(named.into_iter().map(|(_, id)| id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_lineage_edges_and_family_tree() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (grandma, king, queen) = (add("Grandma"), add("King"), add("Queen"));
+        let (prince, princess, baby) = (add("Prince"), add("Princess"), add("Baby"));
+
+        graph.add_parent(grandma, king).unwrap();
+        graph.add_spouse(king, queen).unwrap();
+        graph.add_parent(king, prince).unwrap();
+        graph.add_parent(king, princess).unwrap();
+        graph.add_parent(prince, baby).unwrap();
+
+        // Inverse and sibling edges were written.
+        assert_eq!(graph.parents(princess).unwrap(), vec![king]);
+        assert_eq!(graph.children(king).unwrap(), vec![prince, princess]);
+        let edges = graph.get_relationships(prince).unwrap();
+        assert!(edges.iter().any(|e| e.from == prince
+            && e.to == princess
+            && e.edge_type.as_str() == SIBLING_OF_EDGE));
+        assert!(edges
+            .iter()
+            .any(|e| e.from == prince && e.to == king && e.edge_type.as_str() == CHILD_OF_EDGE));
+
+        let err = graph.add_parent(baby, grandma).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let tree = graph.get_family_tree(prince, 1).unwrap();
+        let levels: Vec<i32> = tree.generations.iter().map(|g| g.level).collect();
+        assert_eq!(levels, vec![-1, 0, 1]);
+        assert_eq!(tree.level_of(king), Some(-1));
+        assert_eq!(tree.level_of(princess), Some(0));
+        assert_eq!(tree.level_of(baby), Some(1));
+        assert_eq!(tree.level_of(grandma), None);
+        let baby_member = &tree.generations[2].members[0];
+        assert_eq!(baby_member.parents, vec![prince]);
+
+        let tree = graph.get_family_tree(prince, 2).unwrap();
+        assert_eq!(tree.level_of(grandma), Some(-2));
+
+        // Edges written without the helpers are repaired.
+        let bastard = add("Bastard");
+        graph.connect_objects_str(king, bastard, PARENT_OF_EDGE).unwrap();
+        // child_of back, plus sibling_of both ways with the prince and princess.
+        assert_eq!(graph.repair_lineage().unwrap(), 5);
+        assert_eq!(graph.repair_lineage().unwrap(), 0);
+        assert_eq!(graph.parents(bastard).unwrap(), vec![king]);
+    }
+}
@@ -0,0 +1,324 @@
+//! Link prediction — "you might want to connect X and Y".
+//!
+//! Imported graphs tend to be sparse: the notes mention that two NPCs share
+//! a patron, but nobody drew the edge between them.
+//! [`KnowledgeGraph::suggest_links`] ranks unconnected pairs by
+//!
+//! - **Adamic–Adar** over shared neighbours: each neighbour `w` both objects
+//!   connect to adds `1 / ln(degree(w))`, so a shared tavern everyone visits
+//!   counts for less than a shared secret society.  The sum is squashed into
+//!   `0..1` as `aa / (1 + aa)`.
+//! - **Embedding similarity** between object profiles, for each object's
+//!   nearest [`LinkPredictionConfig::embedding_candidates`] profiles.
+//!
+//! Each suggestion carries an edge type guessed from the types most often
+//! used between objects of the same two types.
+//! [`KnowledgeGraph::propose_links`] files the best suggestions as edge
+//! [`Proposal`](crate::proposals::Proposal)s, so the GM accepts, edits or
+//! rejects them in the usual review queue; pairs already proposed are not
+//! proposed again.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::proposals::{ProposalId, ProposedChange};
+use crate::types::{Edge, EdgeType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Proposal `source` of [`KnowledgeGraph::propose_links`].
+pub const LINK_PREDICTION_SOURCE: &str = "link_prediction";
+
+/// Edge type suggested when no existing edge joins the two object types.
+pub const DEFAULT_SUGGESTED_EDGE: &str = "related_to";
+
+/// Tuning for [`KnowledgeGraph::suggest_links`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinkPredictionConfig {
+    /// Most suggestions returned.
+    pub limit: usize,
+    /// Suggestions scoring below this are dropped.
+    pub min_score: f32,
+    /// Share of the score taken by embedding similarity (`0..=1`); the rest
+    /// is Adamic–Adar.
+    pub embedding_weight: f32,
+    /// Nearest profiles compared per object; 0 skips embeddings.
+    pub embedding_candidates: usize,
+}
+
+impl Default for LinkPredictionConfig {
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            min_score: 0.1,
+            embedding_weight: 0.4,
+            embedding_candidates: 10,
+        }
+    }
+}
+
+/// One suggested edge.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkSuggestion {
+    pub from: ObjectId,
+    pub to: ObjectId,
+    pub from_name: String,
+    pub to_name: String,
+    pub edge_type: String,
+    /// Blended score in `0..=1`.
+    pub score: f32,
+    pub adamic_adar: f32,
+    /// `None` when the pair was not compared by embedding.
+    pub embedding_similarity: Option<f32>,
+    pub common_neighbors: Vec<ObjectId>,
+}
+
+impl LinkSuggestion {
+    /// The edge this suggestion would create.
+    pub fn to_edge(&self) -> Edge {
+        Edge::new(self.from, self.to, EdgeType::new(self.edge_type.clone()))
+    }
+}
+
+/// Unordered pair key.
+fn pair(a: ObjectId, b: ObjectId) -> (ObjectId, ObjectId) {
+    if a.0 <= b.0 {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[derive(Default)]
+struct PairEvidence {
+    adamic_adar: f32,
+    common: Vec<ObjectId>,
+    similarity: Option<f32>,
+}
+
+impl KnowledgeGraph {
+    /// Rank unconnected object pairs; see the module docs.  Best first.
+    pub fn suggest_links(&self, config: &LinkPredictionConfig) -> Result<Vec<LinkSuggestion>> {
+        let objects: HashMap<ObjectId, ObjectMetadata> = self
+            .get_all_objects()?
+            .into_iter()
+            .map(|o| (o.id, o))
+            .collect();
+        let edges = self.get_all_edges()?;
+
+        let mut adjacency: HashMap<ObjectId, HashSet<ObjectId>> = HashMap::new();
+        // (source type, target type) -> edge type -> count
+        let mut type_usage: HashMap<(&str, &str), HashMap<&str, usize>> = HashMap::new();
+        for edge in &edges {
+            if edge.from == edge.to {
+                continue;
+            }
+            adjacency.entry(edge.from).or_default().insert(edge.to);
+            adjacency.entry(edge.to).or_default().insert(edge.from);
+            if let (Some(from), Some(to)) = (objects.get(&edge.from), objects.get(&edge.to)) {
+                *type_usage
+                    .entry((from.object_type.as_str(), to.object_type.as_str()))
+                    .or_default()
+                    .entry(edge.edge_type.as_str())
+                    .or_default() += 1;
+            }
+        }
+        let connected =
+            |a: ObjectId, b: ObjectId| adjacency.get(&a).is_some_and(|n| n.contains(&b));
+
+        let mut evidence: HashMap<(ObjectId, ObjectId), PairEvidence> = HashMap::new();
+        for (&middle, neighbors) in &adjacency {
+            if neighbors.len() < 2 {
+                continue;
+            }
+            let weight = 1.0 / (neighbors.len() as f32).ln();
+            let neighbors: Vec<ObjectId> = neighbors.iter().copied().collect();
+            for (i, &a) in neighbors.iter().enumerate() {
+                for &b in &neighbors[i + 1..] {
+                    if connected(a, b) {
+                        continue;
+                    }
+                    let entry = evidence.entry(pair(a, b)).or_default();
+                    entry.adamic_adar += weight;
+                    entry.common.push(middle);
+                }
+            }
+        }
+        if config.embedding_candidates > 0 {
+            for &id in objects.keys() {
+                let Some(similar) = self
+                    .storage
+                    .similar_profiles(id, config.embedding_candidates)?
+                else {
+                    continue;
+                };
+                for (other, distance) in similar {
+                    if connected(id, other) || !objects.contains_key(&other) {
+                        continue;
+                    }
+                    let entry = evidence.entry(pair(id, other)).or_default();
+                    entry.similarity = Some((1.0 - distance).clamp(0.0, 1.0));
+                }
+            }
+        }
+
+        let embedding_weight = config.embedding_weight.clamp(0.0, 1.0);
+        let mut out = Vec::new();
+        for ((a, b), mut found) in evidence {
+            let (Some(oa), Some(ob)) = (objects.get(&a), objects.get(&b)) else {
+                continue;
+            };
+            let structural = found.adamic_adar / (1.0 + found.adamic_adar);
+            let score = (1.0 - embedding_weight) * structural
+                + embedding_weight * found.similarity.unwrap_or(0.0);
+            if score < config.min_score || score <= 0.0 {
+                continue;
+            }
+
+            // Orient the pair the way edges between these types usually run.
+            let best = |from: &ObjectMetadata, to: &ObjectMetadata| {
+                type_usage
+                    .get(&(from.object_type.as_str(), to.object_type.as_str()))
+                    .and_then(|types| {
+                        types
+                            .iter()
+                            .max_by(|x, y| x.1.cmp(y.1).then_with(|| y.0.cmp(x.0)))
+                    })
+                    .map(|(edge_type, &count)| (edge_type.to_string(), count))
+            };
+            let (from, to, edge_type) = match (best(oa, ob), best(ob, oa)) {
+                (Some((t, n)), Some((_, m))) if n >= m => (oa, ob, t),
+                (_, Some((t, _))) => (ob, oa, t),
+                (Some((t, _)), None) => (oa, ob, t),
+                (None, None) => (oa, ob, DEFAULT_SUGGESTED_EDGE.to_string()),
+            };
+            found.common.sort_by_key(|id| id.0);
+            out.push(LinkSuggestion {
+                from: from.id,
+                to: to.id,
+                from_name: from.name.clone(),
+                to_name: to.name.clone(),
+                edge_type,
+                score,
+                adamic_adar: found.adamic_adar,
+                embedding_similarity: found.similarity,
+                common_neighbors: found.common,
+            });
+        }
+        out.sort_by(|x, y| {
+            y.score
+                .total_cmp(&x.score)
+                .then_with(|| x.from_name.cmp(&y.from_name))
+                .then_with(|| x.to_name.cmp(&y.to_name))
+        });
+        out.truncate(config.limit);
+        Ok(out)
+    }
+
+    /// File the suggestions from [`suggest_links`](Self::suggest_links) as
+    /// edge proposals with source [`LINK_PREDICTION_SOURCE`], skipping pairs
+    /// this pass has proposed before (pending, accepted or rejected).
+    /// Returns the new proposals, best first.
+    pub fn propose_links(&self, config: &LinkPredictionConfig) -> Result<Vec<ProposalId>> {
+        let mut proposed: HashSet<(ObjectId, ObjectId)> = self
+            .list_proposals(None)?
+            .into_iter()
+            .filter(|p| p.source.as_deref() == Some(LINK_PREDICTION_SOURCE))
+            .filter_map(|p| match p.change {
+                ProposedChange::Edge(edge) => Some(pair(edge.from, edge.to)),
+                _ => None,
+            })
+            .collect();
+        let mut ids = Vec::new();
+        for suggestion in self.suggest_links(config)? {
+            if !proposed.insert(pair(suggestion.from, suggestion.to)) {
+                continue;
+            }
+            ids.push(self.propose_change(
+                ProposedChange::Edge(suggestion.to_edge()),
+                Some(LINK_PREDICTION_SOURCE),
+            )?);
+        }
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EMBEDDING_DIMENSIONS;
+    use crate::test_helpers::create_test_graph;
+
+    fn npc(graph: &KnowledgeGraph, name: &str) -> ObjectId {
+        graph
+            .add_object(ObjectMetadata::new("npc".to_string(), name.to_string()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_suggest_links_closes_squares() {
+        let (graph, _temp_dir) = create_test_graph();
+        let [a, b, c, d, e] = ["Aldo", "Bree", "Cass", "Dorn", "Edda"].map(|n| npc(&graph, n));
+        for (from, to) in [(a, b), (a, c), (b, d), (c, d), (e, b)] {
+            graph.connect_objects_str(from, to, "knows").unwrap();
+        }
+
+        let suggestions = graph
+            .suggest_links(&LinkPredictionConfig::default())
+            .unwrap();
+        let pairs: Vec<_> = suggestions.iter().map(|s| pair(s.from, s.to)).collect();
+        // B–C share two degree-2 neighbours; A–D share one of degree 3.
+        assert_eq!(pairs[0], pair(b, c));
+        assert_eq!(pairs[1], pair(a, d));
+        assert!(pairs.contains(&pair(a, e)));
+        assert!(!pairs.contains(&pair(a, b)));
+        assert_eq!(suggestions[0].edge_type, "knows");
+        assert_eq!(suggestions[0].common_neighbors.len(), 2);
+        assert!(suggestions[0].embedding_similarity.is_none());
+
+        let first = graph
+            .propose_links(&LinkPredictionConfig {
+                limit: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        graph.reject_proposal(first[0]).unwrap();
+        graph.accept_proposal(first[1]).unwrap();
+        assert!(graph.get_neighbors(a).unwrap().contains(&d));
+
+        // Rejected and accepted pairs are not proposed again.
+        let again = graph
+            .propose_links(&LinkPredictionConfig {
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        for id in again {
+            let ProposedChange::Edge(edge) = graph.get_proposal(id).unwrap().unwrap().change else {
+                panic!("expected an edge proposal");
+            };
+            assert_ne!(pair(edge.from, edge.to), pair(b, c));
+        }
+    }
+
+    #[test]
+    fn test_suggest_links_uses_embeddings() {
+        let (graph, _temp_dir) = create_test_graph();
+        let twin_a = npc(&graph, "Twin A");
+        let twin_b = npc(&graph, "Twin B");
+        let mut v = vec![0.0; EMBEDDING_DIMENSIONS];
+        v[0] = 1.0;
+        graph.upsert_profile_embedding(twin_a, &v).unwrap();
+        graph.upsert_profile_embedding(twin_b, &v).unwrap();
+
+        let suggestions = graph
+            .suggest_links(&LinkPredictionConfig::default())
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].edge_type, DEFAULT_SUGGESTED_EDGE);
+        assert!((suggestions[0].embedding_similarity.unwrap() - 1.0).abs() < 1e-4);
+        assert!((suggestions[0].score - 0.4).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,410 @@
+//! World linter — configurable structural rules, like clippy for a setting.
+//!
+//! Where [`crate::validation`] checks objects against their schema and
+//! [`crate::consistency`] asks an LLM about contradicting text, the linter
+//! applies declarative [`LintRule`]s to the graph's shape:
+//!
+//! - [`LintCheck::RequiresEdge`] — "every faction must have a leader edge";
+//! - [`LintCheck::RequiresProperty`] — "quests need a status";
+//! - [`LintCheck::GmOnlyIfProperty`] — "NPCs with secrets must be GM-only":
+//!   the object, or every listed property it fills, hidden from players.
+//!
+//! [`default_lint_rules`] ships a starting set.  Project rules are stored as
+//! JSON in the `lint_rules` setting; one with a default rule's id replaces
+//! it (to change its severity or switch it off), others are added.
+//! [`KnowledgeGraph::lint_world`] runs every enabled rule and returns a
+//! [`LintReport`], errors first.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the project's lint rules.
+pub const LINT_RULES_SETTING: &str = "lint_rules";
+
+/// `object_type` matching every type.
+pub const ANY_OBJECT_TYPE: &str = "*";
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// What a rule checks on each object of its type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum LintCheck {
+    /// At least one outgoing edge of a type in `outgoing` or incoming edge
+    /// of a type in `incoming`.
+    RequiresEdge {
+        #[serde(default)]
+        outgoing: Vec<String>,
+        #[serde(default)]
+        incoming: Vec<String>,
+    },
+    /// A non-empty `property`.
+    RequiresProperty { property: String },
+    /// When any of `properties` is filled, the object or each filled
+    /// property is hidden from players.
+    GmOnlyIfProperty { properties: Vec<String> },
+}
+
+/// One configurable check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintRule {
+    /// Stable id, e.g. `"faction-leader"`.
+    pub id: String,
+    /// Shown with each finding.
+    pub message: String,
+    /// Object type checked, or [`ANY_OBJECT_TYPE`].
+    pub object_type: String,
+    pub severity: LintSeverity,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub check: LintCheck,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl LintRule {
+    pub fn new(
+        id: impl Into<String>,
+        object_type: impl Into<String>,
+        severity: LintSeverity,
+        message: impl Into<String>,
+        check: LintCheck,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            message: message.into(),
+            object_type: object_type.into(),
+            severity,
+            enabled: true,
+            check,
+        }
+    }
+
+    fn applies_to(&self, object: &ObjectMetadata) -> bool {
+        self.object_type == ANY_OBJECT_TYPE || self.object_type == object.object_type
+    }
+
+    fn validate(&self) -> Result<()> {
+        let problem = if self.id.trim().is_empty() {
+            Some("Lint rule id cannot be blank".to_string())
+        } else {
+            match &self.check {
+                LintCheck::RequiresEdge { outgoing, incoming }
+                    if outgoing.is_empty() && incoming.is_empty() =>
+                {
+                    Some(format!("Lint rule '{}' names no edge types", self.id))
+                }
+                LintCheck::RequiresProperty { property } if property.trim().is_empty() => {
+                    Some(format!("Lint rule '{}' names no property", self.id))
+                }
+                LintCheck::GmOnlyIfProperty { properties } if properties.is_empty() => {
+                    Some(format!("Lint rule '{}' names no properties", self.id))
+                }
+                _ => None,
+            }
+        };
+        match problem {
+            Some(problem) => Err(UForgeError::ValidationFailed(problem)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The rules every project starts with.
+pub fn default_lint_rules() -> Vec<LintRule> {
+    vec![
+        LintRule::new(
+            "faction-leader",
+            "faction",
+            LintSeverity::Warning,
+            "Faction has no leader",
+            LintCheck::RequiresEdge {
+                outgoing: vec!["led_by".to_string()],
+                incoming: vec!["leads".to_string()],
+            },
+        ),
+        LintRule::new(
+            "location-parent",
+            "location",
+            LintSeverity::Info,
+            "Location is not inside another location",
+            LintCheck::RequiresEdge {
+                outgoing: vec!["located_in".to_string(), "part_of".to_string()],
+                incoming: vec!["contains".to_string()],
+            },
+        ),
+        LintRule::new(
+            "npc-secret-gm-only",
+            "character",
+            LintSeverity::Error,
+            "Character's secret is visible to players",
+            LintCheck::GmOnlyIfProperty {
+                properties: vec!["secret".to_string(), "true_name".to_string()],
+            },
+        ),
+        LintRule::new(
+            "quest-status",
+            "quest",
+            LintSeverity::Info,
+            "Quest has no status",
+            LintCheck::RequiresProperty {
+                property: "status".to_string(),
+            },
+        ),
+    ]
+}
+
+/// One rule broken by one object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintFinding {
+    pub rule_id: String,
+    pub severity: LintSeverity,
+    pub object_id: ObjectId,
+    pub object_name: String,
+    pub object_type: String,
+    pub message: String,
+}
+
+/// Result of [`KnowledgeGraph::lint_world`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LintReport {
+    pub rules_run: usize,
+    pub objects_checked: usize,
+    /// Most severe first, then by object name.
+    pub findings: Vec<LintFinding>,
+    /// Finding counts by severity.
+    pub counts: BTreeMap<LintSeverity, usize>,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Whether any finding is an [`LintSeverity::Error`].
+    pub fn has_errors(&self) -> bool {
+        self.counts.get(&LintSeverity::Error).is_some_and(|&n| n > 0)
+    }
+}
+
+fn is_filled(object: &ObjectMetadata, property: &str) -> bool {
+    object
+        .get_json_property(property)
+        .is_some_and(|v| match v {
+            serde_json::Value::Null => false,
+            serde_json::Value::String(s) => !s.trim().is_empty(),
+            serde_json::Value::Array(a) => !a.is_empty(),
+            serde_json::Value::Object(o) => !o.is_empty(),
+            _ => true,
+        })
+}
+
+/// Whether `id` has an edge of one of `types` in `edges`.
+fn has_edge_of(
+    edges: &HashMap<ObjectId, HashSet<String>>,
+    id: ObjectId,
+    types: &[String],
+) -> bool {
+    edges
+        .get(&id)
+        .is_some_and(|present| types.iter().any(|t| present.contains(t)))
+}
+
+impl KnowledgeGraph {
+    /// Rules saved for this project, without the defaults.
+    pub fn project_lint_rules(&self) -> Result<Vec<LintRule>> {
+        match self.storage.get_setting(LINT_RULES_SETTING)? {
+            Some(json) => Ok(serde_json::from_str(&json).context("Failed to parse lint rules")?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The rules [`lint_world`](Self::lint_world) runs: the defaults with
+    /// project rules replacing those of the same id, then the remaining
+    /// project rules.
+    pub fn lint_rules(&self) -> Result<Vec<LintRule>> {
+        let mut project: Vec<LintRule> = self.project_lint_rules()?;
+        let mut rules: Vec<LintRule> = default_lint_rules()
+            .into_iter()
+            .map(|rule| match project.iter().position(|r| r.id == rule.id) {
+                Some(i) => project.remove(i),
+                None => rule,
+            })
+            .collect();
+        rules.extend(project);
+        Ok(rules)
+    }
+
+    /// Save `rule` for this project, replacing the project rule of the same
+    /// id (and overriding a default one).  Fails with
+    /// [`UForgeError::ValidationFailed`] for a blank id or a check naming
+    /// nothing.
+    pub fn save_lint_rule(&self, rule: LintRule) -> Result<()> {
+        rule.validate()?;
+        let mut rules = self.project_lint_rules()?;
+        rules.retain(|r| r.id != rule.id);
+        rules.push(rule);
+        self.storage
+            .set_setting(LINT_RULES_SETTING, &serde_json::to_string(&rules)?)
+    }
+
+    /// Delete the project rule `id`, restoring the default of that id if
+    /// there is one.  Returns whether a project rule was removed.
+    pub fn remove_lint_rule(&self, id: &str) -> Result<bool> {
+        let mut rules = self.project_lint_rules()?;
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        if rules.len() == before {
+            return Ok(false);
+        }
+        self.storage
+            .set_setting(LINT_RULES_SETTING, &serde_json::to_string(&rules)?)?;
+        Ok(true)
+    }
+
+    /// Run every enabled rule over every object.
+    pub fn lint_world(&self) -> Result<LintReport> {
+        let rules: Vec<LintRule> = self.lint_rules()?.into_iter().filter(|r| r.enabled).collect();
+        let objects = self.get_all_objects()?;
+
+        let mut outgoing: HashMap<ObjectId, HashSet<String>> = HashMap::new();
+        let mut incoming: HashMap<ObjectId, HashSet<String>> = HashMap::new();
+        for edge in self.get_all_edges()? {
+            let edge_type = edge.edge_type.as_str();
+            outgoing.entry(edge.from).or_default().insert(edge_type.to_string());
+            incoming.entry(edge.to).or_default().insert(edge_type.to_string());
+        }
+
+        let mut report = LintReport {
+            rules_run: rules.len(),
+            objects_checked: objects.len(),
+            ..Default::default()
+        };
+        for object in &objects {
+            for rule in rules.iter().filter(|r| r.applies_to(object)) {
+                let broken = match &rule.check {
+                    LintCheck::RequiresEdge {
+                        outgoing: out_types,
+                        incoming: in_types,
+                    } => {
+                        !has_edge_of(&outgoing, object.id, out_types)
+                            && !has_edge_of(&incoming, object.id, in_types)
+                    }
+                    LintCheck::RequiresProperty { property } => !is_filled(object, property),
+                    LintCheck::GmOnlyIfProperty { properties } => {
+                        self.is_player_visible(object)
+                            && properties.iter().any(|p| {
+                                is_filled(object, p) && self.is_property_player_visible(object, p)
+                            })
+                    }
+                };
+                if broken {
+                    report.findings.push(LintFinding {
+                        rule_id: rule.id.clone(),
+                        severity: rule.severity,
+                        object_id: object.id,
+                        object_name: object.name.clone(),
+                        object_type: object.object_type.clone(),
+                        message: rule.message.clone(),
+                    });
+                }
+            }
+        }
+
+        report.findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.object_name.cmp(&b.object_name))
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+        });
+        for finding in &report.findings {
+            *report.counts.entry(finding.severity).or_default() += 1;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_lint_world_with_default_and_project_rules() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |builder: ObjectBuilder| builder.add_to_graph(&graph).unwrap();
+        let traders = add(ObjectBuilder::faction("Free Traders Alliance".to_string()));
+        let zhent = add(ObjectBuilder::faction("Zhentarim".to_string()));
+        let halia = add(
+            ObjectBuilder::character("Halia Thornton".to_string())
+                .with_property("secret".to_string(), "Zhentarim agent".to_string()),
+        );
+        add(ObjectBuilder::character("Sildar Hallwinter".to_string()));
+        graph.connect_objects_str(zhent, halia, "led_by").unwrap();
+
+        let report = graph.lint_world().unwrap();
+        assert!(report.has_errors());
+        let found: Vec<(&str, ObjectId)> = report
+            .findings
+            .iter()
+            .map(|f| (f.rule_id.as_str(), f.object_id))
+            .collect();
+        assert_eq!(
+            found,
+            [("npc-secret-gm-only", halia), ("faction-leader", traders)]
+        );
+
+        // Hiding the secret clears the error.
+        graph.set_property_gm_only(halia, "secret", true).unwrap();
+        assert!(!graph.lint_world().unwrap().has_errors());
+
+        // A project rule overrides a default and adds its own check.
+        let mut leader = default_lint_rules().remove(0);
+        leader.enabled = false;
+        graph.save_lint_rule(leader).unwrap();
+        graph
+            .save_lint_rule(LintRule::new(
+                "character-race",
+                "character",
+                LintSeverity::Warning,
+                "Character has no race",
+                LintCheck::RequiresProperty {
+                    property: "race".to_string(),
+                },
+            ))
+            .unwrap();
+        let report = graph.lint_world().unwrap();
+        assert_eq!(report.findings.len(), 2);
+        assert!(report.findings.iter().all(|f| f.rule_id == "character-race"));
+        assert_eq!(report.counts[&LintSeverity::Warning], 2);
+
+        let blank = LintRule::new(
+            "empty",
+            ANY_OBJECT_TYPE,
+            LintSeverity::Info,
+            "",
+            LintCheck::GmOnlyIfProperty { properties: vec![] },
+        );
+        let err = graph.save_lint_rule(blank).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ValidationFailed);
+        assert!(graph.remove_lint_rule("faction-leader").unwrap());
+        assert_eq!(graph.lint_rules().unwrap().len(), default_lint_rules().len() + 1);
+    }
+}
@@ -0,0 +1,203 @@
+//! Compaction and history retention.
+//!
+//! A long-running project accumulates dead weight: vectors left behind by
+//! chunks removed outside the delete triggers, every revision of every note,
+//! a snapshot of every node and edge write, and free pages SQLite never
+//! returns on its own.  [`KnowledgeGraph::compact`] prunes the orphaned
+//! vectors, trims history according to the project's [`RetentionPolicy`],
+//! merges the FTS index and vacuums the database, returning a
+//! [`CompactionReport`] of what it removed and how much space came back.
+//!
+//! The default policy keeps all history; compaction then only prunes and
+//! vacuums.  Trimmed history limits how far back
+//! [`KnowledgeGraph::get_object_as_of`] and chunk diffs can look.
+//!
+//! [`KnowledgeGraph::backup_to`] copies the live database into another
+//! project directory without closing it.
+
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{Result, UForgeError};
+use crate::graph::CompactionReport;
+use crate::progress::ProgressSink;
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the [`RetentionPolicy`] as JSON.
+pub const RETENTION_SETTING: &str = "retention";
+
+/// How much history [`KnowledgeGraph::compact`] keeps.  `None` keeps
+/// everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Newest revisions kept per chunk (at least 1).
+    #[serde(default)]
+    pub revisions_per_chunk: Option<usize>,
+    /// Chunk revisions older than this many days are dropped.
+    #[serde(default)]
+    pub revision_max_age_days: Option<u32>,
+    /// Node and edge history older than this many days is dropped.
+    #[serde(default)]
+    pub history_max_age_days: Option<u32>,
+}
+
+impl KnowledgeGraph {
+    /// The project's retention policy; keep-everything when unset.
+    pub fn retention_policy(&self) -> Result<RetentionPolicy> {
+        match self.storage.get_setting(RETENTION_SETTING)? {
+            Some(json) => {
+                Ok(serde_json::from_str(&json).context("Failed to parse retention policy")?)
+            }
+            None => Ok(RetentionPolicy::default()),
+        }
+    }
+
+    /// Store `policy` for later compactions.  Fails with
+    /// [`UForgeError::ValidationFailed`] when `revisions_per_chunk` is 0.
+    pub fn set_retention_policy(&self, policy: &RetentionPolicy) -> Result<()> {
+        if policy.revisions_per_chunk == Some(0) {
+            return Err(UForgeError::ValidationFailed(
+                "Retention must keep at least one revision per chunk".to_string(),
+            ));
+        }
+        let json = serde_json::to_string(policy).context("Failed to serialize retention policy")?;
+        self.storage.set_setting(RETENTION_SETTING, &json)
+    }
+
+    /// Compact under the project's [`RetentionPolicy`]; see the module docs.
+    pub fn compact(&self) -> Result<CompactionReport> {
+        self.compact_with(&self.retention_policy()?)
+    }
+
+    /// Compact under `policy` instead of the stored one.  Holds the
+    /// connection for the whole run; call it from a maintenance action, not
+    /// a hot path.
+    pub fn compact_with(&self, policy: &RetentionPolicy) -> Result<CompactionReport> {
+        let cutoff = |days: Option<u32>| days.map(|d| Utc::now() - Duration::days(i64::from(d)));
+        self.storage.compact(
+            policy.revisions_per_chunk,
+            cutoff(policy.revision_max_age_days),
+            cutoff(policy.history_max_age_days),
+        )
+    }
+
+    /// Copy the database to `<dest_dir>/knowledge.db` while the graph stays
+    /// open; `dest_dir` then opens as a project of its own.  Reports stage
+    /// `"backup"` to `progress` in pages.  Fails with
+    /// [`UForgeError::Conflict`] when the destination already holds a
+    /// database; cancelling fails with [`UForgeError::Cancelled`] and
+    /// leaves no partial file.
+    pub fn backup_to<P: AsRef<Path>>(
+        &self,
+        dest_dir: P,
+        progress: &dyn ProgressSink,
+    ) -> Result<()> {
+        let dest_dir = dest_dir.as_ref();
+        std::fs::create_dir_all(dest_dir)
+            .with_context(|| format!("Failed to create {dest_dir:?}"))?;
+        self.storage
+            .backup_to(&dest_dir.join("knowledge.db"), progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::progress::{CancellationToken, LatestProgress, NoProgress};
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{ChunkType, ObjectMetadata};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compact_trims_revisions_and_reports_space() {
+        let (graph, _temp_dir) = create_test_graph();
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Iarno".to_string()))
+            .unwrap();
+        let chunk = graph
+            .add_text_chunk(id, "Draft 0".to_string(), ChunkType::UserNote)
+            .unwrap()[0];
+        for i in 1..=5 {
+            let text = format!("Draft {i} {}", "padding ".repeat(500));
+            graph.update_text_chunk(chunk, &text, None).unwrap();
+        }
+        assert_eq!(graph.get_chunk_history(chunk).unwrap().len(), 6);
+
+        // The default policy keeps everything.
+        let report = graph.compact().unwrap();
+        assert_eq!(report.revisions, 0);
+        assert_eq!(graph.get_chunk_history(chunk).unwrap().len(), 6);
+
+        graph
+            .set_retention_policy(&RetentionPolicy {
+                revisions_per_chunk: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        let report = graph.compact().unwrap();
+        assert_eq!(report.revisions, 4);
+        assert!(report.bytes_after <= report.bytes_before);
+        assert_eq!(
+            report.reclaimed_bytes(),
+            report.bytes_before - report.bytes_after
+        );
+        let history = graph.get_chunk_history(chunk).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[1].content.starts_with("Draft 5"));
+
+        // Age-based trimming still keeps the newest revision and snapshot.
+        let report = graph
+            .compact_with(&RetentionPolicy {
+                revision_max_age_days: Some(0),
+                history_max_age_days: Some(0),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(report.revisions, 1);
+        assert_eq!(graph.get_chunk_history(chunk).unwrap().len(), 1);
+        assert!(graph.get_object_as_of(id, Utc::now()).unwrap().is_some());
+        assert_eq!(graph.search_chunks_fts("Draft", 5).unwrap().len(), 1);
+
+        let err = graph
+            .set_retention_policy(&RetentionPolicy {
+                revisions_per_chunk: Some(0),
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    }
+
+    #[test]
+    fn test_backup_copies_live_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let graph = KnowledgeGraph::new(temp_dir.path().join("live")).unwrap();
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Iarno".to_string()))
+            .unwrap();
+
+        let dest = temp_dir.path().join("backup");
+        let progress = LatestProgress::new();
+        graph.backup_to(&dest, &progress).unwrap();
+        let last = progress.latest().unwrap();
+        assert_eq!(last.stage, "backup");
+        assert_eq!(Some(last.done), last.total);
+
+        let copy = KnowledgeGraph::new(&dest).unwrap();
+        assert_eq!(copy.get_object(id).unwrap().unwrap().name, "Iarno");
+        drop(copy);
+
+        let err = graph.backup_to(&dest, &NoProgress).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let cancelled = LatestProgress::new().with_cancellation(cancel);
+        let other = temp_dir.path().join("cancelled");
+        let err = graph.backup_to(&other, &cancelled).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Cancelled);
+        assert!(!other.join("knowledge.db").exists());
+    }
+}
@@ -0,0 +1,579 @@
+//! Markdown export and re-import of objects, for editing outside the app.
+//!
+//! [`KnowledgeGraph::export_markdown`] renders each selected object as one
+//! [`MarkdownDocument`]:
+//!
+//! ```markdown
+//! ---
+//! id: "3f0c…"
+//! type: "character"
+//! name: "Frodo"
+//! lifecycle: "canon"
+//! properties: {"race":"Hobbit","tags":["ringbearer"]}
+//! ---
+//!
+//! # Frodo
+//!
+//! A brave hobbit.            ← the `description` property
+//!
+//! ## Relationships
+//!
+//! - located_in: [The Shire](the-shire-1a2b3c4d.md)
+//!
+//! ## Referenced by
+//!
+//! - member_of ← [Sam](sam-9e8d7c6b.md)
+//!
+//! ## Notes
+//!
+//! <!-- chunk: UserNote -->
+//! Owes Sam an apology.
+//! <!-- /chunk -->
+//! ```
+//!
+//! Front-matter values are JSON, which is also valid YAML, so any front-matter
+//! aware editor reads them.  Links point at the file names of the other
+//! documents, so an exported directory browses like a wiki.  `Description`
+//! chunks are not exported: they are regenerated from the object itself.
+//!
+//! [`KnowledgeGraph::import_markdown`] reads the same format back.  The
+//! object with the front-matter `id` is updated (or created with that id),
+//! listed relationships missing from the graph are added, and the `Notes`
+//! chunks replace the object's non-`Description` chunks when they differ.
+//! Edges are never deleted by an import, and `Referenced by` is ignored —
+//! each edge is owned by the document of its source object.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::error::{Result, UForgeError};
+use crate::graph_data::NodeFilter;
+use crate::progress::{Progress, ProgressSink};
+use crate::types::{ChunkType, Lifecycle, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Relationship line: `- <edge_type>: [<name>](<file>)`.
+static RELATIONSHIP_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^- ([\w-]+): \[(.+)\]\(([^)]*)\)\s*$").unwrap());
+
+/// A chunk block in the `Notes` section.
+static CHUNK_BLOCK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!-- chunk: (\w+) -->\n(.*?)\n<!-- /chunk -->").unwrap());
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// What [`KnowledgeGraph::export_markdown`] exports.
+#[derive(Debug, Clone)]
+pub enum MarkdownExport {
+    /// One object.
+    Object(ObjectId),
+    /// An object and everything within `depth` edges of it.
+    Subgraph { root: ObjectId, depth: usize },
+    /// Every object matching the filter.
+    Filter(NodeFilter),
+}
+
+/// One exported object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownDocument {
+    pub object_id: ObjectId,
+    /// `<slugified-name>-<first 8 id hex digits>.md`; other documents link
+    /// to this name.
+    pub file_name: String,
+    pub content: String,
+}
+
+impl MarkdownDocument {
+    /// Write the document into `dir` under its [`file_name`](Self::file_name).
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(&self.file_name);
+        std::fs::write(&path, &self.content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Outcome of [`KnowledgeGraph::import_markdown`].
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownImport {
+    pub object_id: Option<ObjectId>,
+    /// `true` if the object did not exist before.
+    pub created: bool,
+    pub edges_created: usize,
+    /// `true` if the `Notes` chunks differed and were replaced.
+    pub chunks_replaced: bool,
+    /// Link names that matched no object (or several), so no edge was made.
+    pub unresolved_links: Vec<String>,
+}
+
+// ── Export ────────────────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Render the selected objects as Markdown documents, ordered by name.
+    ///
+    /// Reports stage `"markdown"` to `progress` once per document.
+    /// Cancelling fails with [`UForgeError::Cancelled`].
+    pub fn export_markdown(
+        &self,
+        selection: &MarkdownExport,
+        progress: &dyn ProgressSink,
+    ) -> Result<Vec<MarkdownDocument>> {
+        let mut objects = match selection {
+            MarkdownExport::Object(id) => vec![self.require_object(*id)?],
+            MarkdownExport::Subgraph { root, depth } => {
+                let mut seen = HashSet::from([*root]);
+                let mut objects = vec![self.require_object(*root)?];
+                let mut frontier = VecDeque::from([(*root, 0)]);
+                while let Some((id, dist)) = frontier.pop_front() {
+                    if dist == *depth {
+                        continue;
+                    }
+                    for next in self.get_neighbors(id)? {
+                        if seen.insert(next) {
+                            if let Some(object) = self.get_object(next)? {
+                                objects.push(object);
+                            }
+                            frontier.push_back((next, dist + 1));
+                        }
+                    }
+                }
+                objects
+            }
+            MarkdownExport::Filter(filter) => self
+                .get_all_objects()?
+                .into_iter()
+                .filter(|o| filter.matches(o))
+                .collect(),
+        };
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        self.render_markdown_all(&objects, false, progress)
+    }
+
+    /// [`render_markdown`](Self::render_markdown) for each of `objects`,
+    /// reporting stage `"markdown"` to `progress`.
+    pub(crate) fn render_markdown_all(
+        &self,
+        objects: &[ObjectMetadata],
+        player_view: bool,
+        progress: &dyn ProgressSink,
+    ) -> Result<Vec<MarkdownDocument>> {
+        let total = objects.len();
+        let mut documents = Vec::with_capacity(total);
+        for (done, object) in objects.iter().enumerate() {
+            progress.check_cancelled()?;
+            progress.report(
+                &Progress::new("markdown", done, Some(total)).with_message(object.name.clone()),
+            );
+            documents.push(self.render_markdown(object, player_view)?);
+        }
+        progress.report(&Progress::new("markdown", total, Some(total)));
+        Ok(documents)
+    }
+
+    fn require_object(&self, id: ObjectId) -> Result<ObjectMetadata> {
+        self.get_object(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}")))
+    }
+
+    /// Render one document.  With `player_view`, relationships to objects
+    /// hidden from players and the `Notes` section are left out; the caller
+    /// passes an already redacted `object`.
+    pub(crate) fn render_markdown(
+        &self,
+        object: &ObjectMetadata,
+        player_view: bool,
+    ) -> Result<MarkdownDocument> {
+        let mut properties = object.properties.clone();
+        // Only prose descriptions move into the body; anything else stays in
+        // the front-matter so it survives the round trip.
+        let description = properties.as_object_mut().and_then(|p| {
+            let prose = p
+                .get("description")
+                .and_then(Value::as_str)
+                .is_some_and(|text| !text.trim().is_empty());
+            if prose {
+                p.remove("description")
+            } else {
+                None
+            }
+        });
+
+        let mut out = String::from("---\n");
+        front_matter_line(&mut out, "id", &Value::String(object.id.to_string()));
+        front_matter_line(&mut out, "type", &Value::String(object.object_type.clone()));
+        front_matter_line(&mut out, "name", &Value::String(object.name.clone()));
+        if let Some(lifecycle) = object.lifecycle {
+            front_matter_line(&mut out, "lifecycle", &lifecycle.as_str().into());
+        }
+        if let Some(schema) = &object.schema_name {
+            front_matter_line(&mut out, "schema", &Value::String(schema.clone()));
+        }
+        front_matter_line(&mut out, "properties", &properties);
+        out.push_str("---\n\n");
+
+        out.push_str(&format!("# {}\n", object.name));
+        match description {
+            Some(Value::String(text)) if !text.trim().is_empty() => {
+                out.push_str(&format!("\n{}\n", text.trim()));
+            }
+            _ => {}
+        }
+
+        let mut outgoing = Vec::new();
+        let mut incoming = Vec::new();
+        for edge in self.get_relationships(object.id)? {
+            let (other, list) = if edge.from == object.id {
+                (edge.to, &mut outgoing)
+            } else {
+                (edge.from, &mut incoming)
+            };
+            if let Some(other) = self.get_object(other)? {
+                if player_view && !self.is_player_visible(&other) {
+                    continue;
+                }
+                list.push((edge.edge_type.as_str().to_string(), other));
+            }
+        }
+        if !outgoing.is_empty() {
+            out.push_str("\n## Relationships\n\n");
+            for (edge_type, other) in &outgoing {
+                out.push_str(&format!(
+                    "- {edge_type}: [{}]({})\n",
+                    other.name,
+                    markdown_file_name(other)
+                ));
+            }
+        }
+        if !incoming.is_empty() {
+            out.push_str("\n## Referenced by\n\n");
+            for (edge_type, other) in &incoming {
+                out.push_str(&format!(
+                    "- {edge_type} ← [{}]({})\n",
+                    other.name,
+                    markdown_file_name(other)
+                ));
+            }
+        }
+
+        // Notes hold GM material, not player-facing prose.
+        let notes: Vec<_> = if player_view {
+            Vec::new()
+        } else {
+            self.get_text_chunks(object.id)?
+                .into_iter()
+                .filter(|c| !matches!(c.chunk_type, ChunkType::Description))
+                .collect()
+        };
+        if !notes.is_empty() {
+            out.push_str("\n## Notes\n");
+            for chunk in &notes {
+                out.push_str(&format!(
+                    "\n<!-- chunk: {} -->\n{}\n<!-- /chunk -->\n",
+                    chunk_type_name(&chunk.chunk_type),
+                    chunk.content.trim_end()
+                ));
+            }
+        }
+
+        Ok(MarkdownDocument {
+            object_id: object.id,
+            file_name: markdown_file_name(object),
+            content: out,
+        })
+    }
+
+    // ── Import ────────────────────────────────────────────────────────────────
+
+    /// Create or update an object from a document in the
+    /// [`export_markdown`](Self::export_markdown) format.
+    ///
+    /// Fails with [`UForgeError::ValidationFailed`] if the front-matter is
+    /// missing or lacks `type` / `name`.
+    pub fn import_markdown(&self, content: &str) -> Result<MarkdownImport> {
+        let (front, body) = split_front_matter(content).ok_or_else(|| {
+            UForgeError::ValidationFailed("Markdown document has no front-matter".to_string())
+        })?;
+        let field = |key: &str| front.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let text_field = |key: &str| field(key).and_then(Value::as_str).map(str::to_string);
+        let (Some(object_type), Some(name)) = (text_field("type"), text_field("name")) else {
+            return Err(UForgeError::ValidationFailed(
+                "Markdown front-matter needs `type` and `name`".to_string(),
+            ));
+        };
+        let id = text_field("id").and_then(|s| ObjectId::parse_str(&s).ok());
+
+        let existing = match id {
+            Some(id) => self.get_object(id)?,
+            None => None,
+        };
+        let mut object = ObjectMetadata::new(object_type, name);
+        if let Some(id) = id {
+            object.id = id;
+        }
+        if let Some(existing) = &existing {
+            object.created_at = existing.created_at;
+        }
+        object.lifecycle = text_field("lifecycle").and_then(|s| Lifecycle::parse(&s));
+        object.schema_name = text_field("schema");
+        if let Some(Value::Object(props)) = field("properties") {
+            object.properties = Value::Object(props.clone());
+        }
+        let sections = split_sections(body);
+        let description = sections
+            .iter()
+            .find(|(title, _)| title.is_empty())
+            .map(|(_, text)| text.trim())
+            .unwrap_or("");
+        if !description.is_empty() {
+            object.set_property("description".to_string(), description.to_string());
+        }
+
+        let mut result = MarkdownImport {
+            object_id: Some(object.id),
+            created: existing.is_none(),
+            ..Default::default()
+        };
+        let object_id = object.id;
+        if existing.is_some() {
+            self.update_object(object)?;
+        } else {
+            self.add_object(object)?;
+        }
+
+        let section = |name: &str| {
+            sections
+                .iter()
+                .find(|(title, _)| title == name)
+                .map_or("", |(_, text)| text.as_str())
+        };
+
+        let current: HashSet<(String, ObjectId)> = self
+            .get_relationships(object_id)?
+            .into_iter()
+            .filter(|e| e.from == object_id)
+            .map(|e| (e.edge_type.as_str().to_string(), e.to))
+            .collect();
+        for line in section("Relationships").lines() {
+            let Some(caps) = RELATIONSHIP_LINE.captures(line.trim()) else {
+                continue;
+            };
+            let Some(target) = self.resolve_markdown_link(&caps[2], &caps[3])? else {
+                result.unresolved_links.push(caps[2].to_string());
+                continue;
+            };
+            if !current.contains(&(caps[1].to_string(), target)) {
+                self.connect_objects_str(object_id, target, &caps[1])?;
+                result.edges_created += 1;
+            }
+        }
+
+        let notes: Vec<(String, String)> = CHUNK_BLOCK
+            .captures_iter(section("Notes"))
+            .map(|c| (c[1].to_string(), c[2].trim_end().to_string()))
+            .collect();
+        let old_notes: Vec<_> = self
+            .get_text_chunks(object_id)?
+            .into_iter()
+            .filter(|c| !matches!(c.chunk_type, ChunkType::Description))
+            .collect();
+        let unchanged = old_notes.len() == notes.len()
+            && old_notes.iter().zip(&notes).all(|(old, (kind, text))| {
+                chunk_type_name(&old.chunk_type) == *kind && old.content.trim_end() == text
+            });
+        if !unchanged {
+            for chunk in &old_notes {
+                self.delete_text_chunk(chunk.id)?;
+            }
+            for (kind, text) in notes {
+                let chunk_type = serde_json::from_value(Value::String(kind.clone()))
+                    .with_context(|| format!("Unknown chunk type '{kind}'"))?;
+                self.add_text_chunk(object_id, text, chunk_type)?;
+            }
+            result.chunks_replaced = true;
+        }
+
+        Ok(result)
+    }
+
+    /// Resolve a relationship link by name, using the id prefix in the file
+    /// name to choose between objects that share the name.
+    fn resolve_markdown_link(&self, name: &str, file: &str) -> Result<Option<ObjectId>> {
+        let matches = self.find_by_name_only(name)?;
+        if let [only] = matches.as_slice() {
+            return Ok(Some(only.id));
+        }
+        let prefix = file
+            .trim_end_matches(".md")
+            .rsplit('-')
+            .next()
+            .unwrap_or_default();
+        let mut by_prefix = matches
+            .iter()
+            .filter(|o| !prefix.is_empty() && o.id.to_string().starts_with(prefix));
+        match (by_prefix.next(), by_prefix.next()) {
+            (Some(object), None) => Ok(Some(object.id)),
+            _ => Ok(None),
+        }
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// File name used for `object`'s document and for links to it.
+pub fn markdown_file_name(object: &ObjectMetadata) -> String {
+    let slug: String = object
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let id = object.id.to_string();
+    if slug.is_empty() {
+        format!("{}.md", &id[..8])
+    } else {
+        format!("{slug}-{}.md", &id[..8])
+    }
+}
+
+fn front_matter_line(out: &mut String, key: &str, value: &Value) {
+    out.push_str(&format!("{key}: {value}\n"));
+}
+
+/// Split `content` into parsed front-matter fields and the body after it.
+fn split_front_matter(content: &str) -> Option<(Vec<(String, Value)>, &str)> {
+    let rest = content.strip_prefix("---\n")?;
+    let end = rest.find("\n---\n")?;
+    let fields = rest[..end]
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(": ")?;
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| Value::String(value.trim().to_string()));
+            Some((key.trim().to_string(), value))
+        })
+        .collect();
+    Some((fields, &rest[end + 5..]))
+}
+
+/// Split the body into `(section title, text)` pairs.  Text between the
+/// `# title` line and the first `## ` heading has an empty title.
+fn split_sections(body: &str) -> Vec<(String, String)> {
+    let mut sections = vec![(String::new(), String::new())];
+    for line in body.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            sections.push((title.trim().to_string(), String::new()));
+        } else if line.starts_with("# ") && sections.len() == 1 {
+            continue;
+        } else if let Some((_, text)) = sections.last_mut() {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    sections
+}
+
+/// `ChunkType` variant name as written in `<!-- chunk: … -->` markers.
+fn chunk_type_name(chunk_type: &ChunkType) -> String {
+    serde_json::to_value(chunk_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let (graph, _temp_dir) = create_test_graph();
+        let shire = ObjectBuilder::location("The Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let frodo = ObjectBuilder::character("Frodo".to_string())
+            .with_description("A brave hobbit.".to_string())
+            .with_property("race".to_string(), "Hobbit".to_string())
+            .located_in(shire)
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .add_text_chunk(
+                frodo,
+                "Owes Sam an apology.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap();
+
+        let docs = graph
+            .export_markdown(
+                &MarkdownExport::Subgraph {
+                    root: frodo,
+                    depth: 1,
+                },
+                &NoProgress,
+            )
+            .unwrap();
+        assert_eq!(docs.len(), 2);
+        let doc = docs.iter().find(|d| d.object_id == frodo).unwrap();
+        assert!(doc.file_name.starts_with("frodo-"));
+        assert!(doc.content.contains("\nA brave hobbit.\n"));
+        assert!(doc.content.contains("- located_in: [The Shire](the-shire-"));
+        assert!(doc
+            .content
+            .contains("<!-- chunk: UserNote -->\nOwes Sam an apology.\n"));
+
+        // Unchanged re-import is a no-op.
+        let unchanged = graph.import_markdown(&doc.content).unwrap();
+        assert!(!unchanged.created);
+        assert_eq!(unchanged.edges_created, 0);
+        assert!(!unchanged.chunks_replaced);
+
+        // Edit in an "external editor" and import again.
+        let edited = doc
+            .content
+            .replace("A brave hobbit.", "The Ring-bearer.")
+            .replace("Owes Sam an apology.", "Trusts Sam completely.")
+            .replace(
+                "## Relationships\n\n",
+                "## Relationships\n\n- knows: [Gandalf](gandalf.md)\n",
+            );
+        let result = graph.import_markdown(&edited).unwrap();
+        assert!(result.chunks_replaced);
+        assert_eq!(result.unresolved_links, vec!["Gandalf".to_string()]);
+        let frodo_obj = graph.get_object(frodo).unwrap().unwrap();
+        assert_eq!(
+            frodo_obj.get_property("description").as_deref(),
+            Some("The Ring-bearer.")
+        );
+        assert_eq!(frodo_obj.get_property("race").as_deref(), Some("Hobbit"));
+        let chunks = graph.get_text_chunks(frodo).unwrap();
+        assert!(chunks.iter().any(|c| c.content == "Trusts Sam completely."));
+
+        // A document for an object that does not exist yet creates it.
+        graph.delete_object(frodo).unwrap();
+        let recreated = graph.import_markdown(&edited).unwrap();
+        assert!(recreated.created);
+        assert_eq!(recreated.edges_created, 1);
+        assert_eq!(recreated.object_id, Some(frodo));
+    }
+
+    #[test]
+    fn test_import_requires_front_matter() {
+        let (graph, _temp_dir) = create_test_graph();
+        let err = graph.import_markdown("# Frodo\n").unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ValidationFailed);
+    }
+}
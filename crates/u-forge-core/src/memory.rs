@@ -0,0 +1,379 @@
+//! In-memory graph for builds without the `native` feature.
+//!
+//! A browser viewer compiled to `wasm32-unknown-unknown` has no SQLite, no
+//! tokio and no local Lemonade Server.  [`MemoryGraph`] keeps objects, edges,
+//! chunks and chunk embeddings in plain collections and offers the read side
+//! of the `KnowledgeGraph` API over them: lookups, neighbours,
+//! subgraph traversal, substring text search and brute-force semantic
+//! search.
+//!
+//! Data moves in and out as a [`MemorySnapshot`] — JSON the host page can
+//! keep in IndexedDB, or a [`QueryResult`] exported from a native project.
+//! Embeddings are computed elsewhere: the host calls a remote embedding
+//! endpoint and passes vectors to [`MemoryGraph::set_chunk_embedding`] and
+//! [`MemoryGraph::search_semantic`].
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ChunkId, Edge, ObjectId, ObjectMetadata, QueryResult, TextChunk};
+
+/// Serialisable contents of a [`MemoryGraph`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    pub objects: Vec<ObjectMetadata>,
+    pub edges: Vec<Edge>,
+    pub chunks: Vec<TextChunk>,
+    #[serde(default)]
+    pub embeddings: Vec<(ChunkId, Vec<f32>)>,
+}
+
+/// One chunk found by [`MemoryGraph::search_text`] or
+/// [`MemoryGraph::search_semantic`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryHit {
+    pub object_id: ObjectId,
+    pub chunk_id: ChunkId,
+    pub content: String,
+    /// Term occurrences for text search, cosine similarity for semantic.
+    pub score: f32,
+}
+
+/// Objects, edges and chunks held in memory; see the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryGraph {
+    objects: HashMap<ObjectId, ObjectMetadata>,
+    edges: Vec<Edge>,
+    chunks: Vec<TextChunk>,
+    embeddings: HashMap<ChunkId, Vec<f32>>,
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 {
+        0.0
+    } else {
+        dot / denom
+    }
+}
+
+impl MemoryGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_snapshot(snapshot: MemorySnapshot) -> Self {
+        let mut graph = Self::new();
+        for object in snapshot.objects {
+            graph.add_object(object);
+        }
+        for edge in snapshot.edges {
+            graph.add_edge(edge);
+        }
+        for chunk in snapshot.chunks {
+            graph.add_chunk(chunk);
+        }
+        graph.embeddings = snapshot.embeddings.into_iter().collect();
+        graph
+    }
+
+    /// Everything in the graph; objects by name, chunks in insertion order.
+    pub fn to_snapshot(&self) -> MemorySnapshot {
+        let mut objects: Vec<ObjectMetadata> = self.objects.values().cloned().collect();
+        objects.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.0.cmp(&b.id.0)));
+        let mut embeddings: Vec<(ChunkId, Vec<f32>)> = self
+            .embeddings
+            .iter()
+            .map(|(id, v)| (*id, v.clone()))
+            .collect();
+        embeddings.sort_by_key(|(id, _)| id.0);
+        MemorySnapshot {
+            objects,
+            edges: self.edges.clone(),
+            chunks: self.chunks.clone(),
+            embeddings,
+        }
+    }
+
+    /// Load a traversal or export result from a native project.
+    pub fn from_query_result(result: QueryResult) -> Self {
+        Self::from_snapshot(MemorySnapshot {
+            objects: result.objects,
+            edges: result.edges,
+            chunks: result.chunks,
+            embeddings: Vec::new(),
+        })
+    }
+
+    // ── Writes ────────────────────────────────────────────────────────────────
+
+    /// Insert or replace an object.
+    pub fn add_object(&mut self, object: ObjectMetadata) -> ObjectId {
+        let id = object.id;
+        self.objects.insert(id, object);
+        id
+    }
+
+    /// Remove an object with its edges, chunks and their embeddings.
+    /// Returns `false` for an unknown id.
+    pub fn remove_object(&mut self, id: ObjectId) -> bool {
+        if self.objects.remove(&id).is_none() {
+            return false;
+        }
+        self.edges.retain(|e| e.from != id && e.to != id);
+        let embeddings = &mut self.embeddings;
+        self.chunks.retain(|c| {
+            let keep = c.object_id != id;
+            if !keep {
+                embeddings.remove(&c.id);
+            }
+            keep
+        });
+        true
+    }
+
+    /// Insert an edge, replacing one with the same endpoints and type.
+    pub fn add_edge(&mut self, edge: Edge) {
+        match self
+            .edges
+            .iter_mut()
+            .find(|e| e.from == edge.from && e.to == edge.to && e.edge_type == edge.edge_type)
+        {
+            Some(existing) => *existing = edge,
+            None => self.edges.push(edge),
+        }
+    }
+
+    /// Insert or replace a chunk.
+    pub fn add_chunk(&mut self, chunk: TextChunk) -> ChunkId {
+        let id = chunk.id;
+        match self.chunks.iter_mut().find(|c| c.id == id) {
+            Some(existing) => *existing = chunk,
+            None => self.chunks.push(chunk),
+        }
+        id
+    }
+
+    pub fn set_chunk_embedding(&mut self, chunk_id: ChunkId, embedding: Vec<f32>) {
+        self.embeddings.insert(chunk_id, embedding);
+    }
+
+    // ── Reads ─────────────────────────────────────────────────────────────────
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn get_object(&self, id: ObjectId) -> Option<&ObjectMetadata> {
+        self.objects.get(&id)
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &ObjectMetadata> {
+        self.objects.values()
+    }
+
+    /// Objects of `object_type`, by name.
+    pub fn objects_of_type(&self, object_type: &str) -> Vec<&ObjectMetadata> {
+        let mut out: Vec<&ObjectMetadata> = self
+            .objects
+            .values()
+            .filter(|o| o.object_type == object_type)
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        out
+    }
+
+    /// Objects named `name`, ignoring case.
+    pub fn find_by_name_only(&self, name: &str) -> Vec<&ObjectMetadata> {
+        let name = name.trim().to_lowercase();
+        let mut out: Vec<&ObjectMetadata> = self
+            .objects
+            .values()
+            .filter(|o| o.name.to_lowercase() == name)
+            .collect();
+        out.sort_by_key(|o| o.id.0);
+        out
+    }
+
+    /// Every edge incident on `id`, in either direction.
+    pub fn get_relationships(&self, id: ObjectId) -> Vec<&Edge> {
+        self.edges
+            .iter()
+            .filter(|e| e.from == id || e.to == id)
+            .collect()
+    }
+
+    /// IDs of every object directly connected to `id`.
+    pub fn get_neighbors(&self, id: ObjectId) -> Vec<ObjectId> {
+        let mut seen = HashSet::new();
+        self.get_relationships(id)
+            .into_iter()
+            .map(|e| if e.from == id { e.to } else { e.from })
+            .filter(|other| seen.insert(*other))
+            .collect()
+    }
+
+    pub fn get_text_chunks(&self, id: ObjectId) -> Vec<&TextChunk> {
+        self.chunks.iter().filter(|c| c.object_id == id).collect()
+    }
+
+    /// Objects within `max_hops` of `start`, the edges between them and
+    /// their chunks — the in-memory `query_subgraph`.
+    pub fn query_subgraph(&self, start: ObjectId, max_hops: usize) -> QueryResult {
+        let mut result = QueryResult::new();
+        if !self.objects.contains_key(&start) {
+            return result;
+        }
+        let mut visited: HashSet<ObjectId> = HashSet::from([start]);
+        let mut order = vec![start];
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((id, depth)) = queue.pop_front() {
+            if depth == max_hops {
+                continue;
+            }
+            for neighbor in self.get_neighbors(id) {
+                if self.objects.contains_key(&neighbor) && visited.insert(neighbor) {
+                    order.push(neighbor);
+                    queue.push_back((neighbor, depth + 1));
+                }
+            }
+        }
+        for id in &order {
+            result.add_object(self.objects[id].clone());
+        }
+        for edge in &self.edges {
+            if visited.contains(&edge.from) && visited.contains(&edge.to) {
+                result.add_edge(edge.clone());
+            }
+        }
+        for chunk in &self.chunks {
+            if visited.contains(&chunk.object_id) {
+                result.add_chunk(chunk.clone());
+            }
+        }
+        result
+    }
+
+    /// Chunks containing every word of `query`, ignoring case, most
+    /// occurrences first.
+    pub fn search_text(&self, query: &str, limit: usize) -> Vec<MemoryHit> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        let mut hits: Vec<MemoryHit> = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| {
+                let content = chunk.content.to_lowercase();
+                let mut occurrences = 0;
+                for term in &terms {
+                    let n = content.matches(term.as_str()).count();
+                    if n == 0 {
+                        return None;
+                    }
+                    occurrences += n;
+                }
+                Some(Self::hit(chunk, occurrences as f32))
+            })
+            .collect();
+        Self::rank(&mut hits, limit);
+        hits
+    }
+
+    /// Embedded chunks most similar to `query_embedding` by cosine
+    /// similarity.  Chunks without an embedding are skipped.
+    pub fn search_semantic(&self, query_embedding: &[f32], limit: usize) -> Vec<MemoryHit> {
+        let mut hits: Vec<MemoryHit> = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| {
+                let embedding = self.embeddings.get(&chunk.id)?;
+                Some(Self::hit(chunk, cosine(query_embedding, embedding)))
+            })
+            .collect();
+        Self::rank(&mut hits, limit);
+        hits
+    }
+
+    fn hit(chunk: &TextChunk, score: f32) -> MemoryHit {
+        MemoryHit {
+            object_id: chunk.object_id,
+            chunk_id: chunk.id,
+            content: chunk.content.clone(),
+            score,
+        }
+    }
+
+    fn rank(hits: &mut Vec<MemoryHit>, limit: usize) {
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.chunk_id.0.cmp(&b.chunk_id.0))
+        });
+        hits.truncate(limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChunkType, EdgeType};
+
+    #[test]
+    fn test_memory_graph_queries_and_snapshot() {
+        let mut graph = MemoryGraph::new();
+        let mut add = |ty: &str, name: &str| {
+            graph.add_object(ObjectMetadata::new(ty.to_string(), name.to_string()))
+        };
+        let town = add("location", "Phandalin");
+        let inn = add("location", "Stonehill Inn");
+        let toblen = add("character", "Toblen");
+        graph.add_edge(Edge::new(inn, town, EdgeType::new("located_in")));
+        graph.add_edge(Edge::new(toblen, inn, EdgeType::new("works_at")));
+        graph.add_edge(Edge::new(toblen, inn, EdgeType::new("works_at")));
+        let chunk = graph.add_chunk(TextChunk::new(
+            toblen,
+            "Toblen runs the inn with his wife Trilena.".to_string(),
+            ChunkType::Description,
+        ));
+        graph.add_chunk(TextChunk::new(
+            town,
+            "A frontier town rebuilt on old ruins.".to_string(),
+            ChunkType::Description,
+        ));
+        graph.set_chunk_embedding(chunk, vec![1.0, 0.0]);
+
+        assert_eq!(graph.get_relationships(inn).len(), 2);
+        assert_eq!(graph.find_by_name_only("toblen")[0].id, toblen);
+        assert_eq!(graph.query_subgraph(toblen, 1).objects.len(), 2);
+        let all = graph.query_subgraph(toblen, 2);
+        assert_eq!((all.objects.len(), all.edges.len(), all.chunks.len()), (3, 2, 2));
+
+        assert_eq!(graph.search_text("INN toblen", 5)[0].chunk_id, chunk);
+        assert!(graph.search_text("dragon", 5).is_empty());
+        assert_eq!(graph.search_semantic(&[0.9, 0.1], 5)[0].object_id, toblen);
+
+        // Round trip through JSON, as stored by the host page.
+        let json = serde_json::to_string(&graph.to_snapshot()).unwrap();
+        let restored = MemoryGraph::from_snapshot(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored.search_semantic(&[1.0, 0.0], 1)[0].chunk_id, chunk);
+
+        assert!(graph.remove_object(inn));
+        assert!(graph.get_relationships(toblen).is_empty());
+    }
+}
@@ -0,0 +1,363 @@
+//! Organisation hierarchies — who answers to whom in a faction.
+//!
+//! An org chart is read from existing edges:
+//!
+//! - `faction -led_by-> person` makes the person a leader of the faction;
+//! - `person -member_of-> faction` makes them a member;
+//! - `faction -subfaction_of-> parent` nests one unit under another;
+//! - `person -reports_to-> person` names a member's direct superior
+//!   explicitly.
+//!
+//! The `role` in a `led_by` or `member_of` edge's metadata becomes the
+//! position's title.  Without a `reports_to` edge a member's superior is
+//! their unit's leader, and a unit leader's superior is the leader of the
+//! parent unit.  A faction type whose schema metadata sets
+//! [`SINGLE_SUPERIOR_KEY`] to `true` requires every person in its units to
+//! have at most one direct superior; [`KnowledgeGraph::get_org_chart`]
+//! reports each violation as an [`OrgIssue`].
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::{Edge, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Edge type from a faction to its leader.
+pub const LED_BY_EDGE: &str = "led_by";
+
+/// Edge type from a person to a faction they belong to.
+pub const MEMBER_OF_EDGE: &str = "member_of";
+
+/// Edge type from a faction to the faction it is part of.
+pub const SUBFACTION_OF_EDGE: &str = "subfaction_of";
+
+/// Edge type from a person to their direct superior.
+pub const REPORTS_TO_EDGE: &str = "reports_to";
+
+/// `led_by` / `member_of` edge metadata key holding the position title.
+pub const ORG_ROLE_KEY: &str = "role";
+
+/// `ObjectTypeSchema::metadata` key that, set to `true` on a faction type,
+/// limits its people to one direct superior each.
+pub const SINGLE_SUPERIOR_KEY: &str = "single_superior";
+
+/// A person's place in an [`OrgUnit`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgPosition {
+    pub id: ObjectId,
+    pub name: String,
+    /// From the edge's [`ORG_ROLE_KEY`] metadata.
+    pub role: Option<String>,
+    /// Direct superiors within the chart.
+    pub superiors: Vec<ObjectId>,
+}
+
+/// One faction in an [`OrgChart`], with its sub-factions nested below.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgUnit {
+    pub faction: ObjectId,
+    pub name: String,
+    /// By name.
+    pub leaders: Vec<OrgPosition>,
+    /// By name; leaders are not repeated here.
+    pub members: Vec<OrgPosition>,
+    /// By name.
+    pub subunits: Vec<OrgUnit>,
+}
+
+/// A person with more direct superiors than their faction's schema allows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgIssue {
+    pub person: ObjectId,
+    /// The faction whose schema sets [`SINGLE_SUPERIOR_KEY`].
+    pub faction: ObjectId,
+    pub superiors: Vec<ObjectId>,
+    pub message: String,
+}
+
+/// The result of [`KnowledgeGraph::get_org_chart`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgChart {
+    pub root: OrgUnit,
+    pub issues: Vec<OrgIssue>,
+}
+
+impl OrgChart {
+    /// Every unit, depth first from the root.
+    pub fn units(&self) -> Vec<&OrgUnit> {
+        let mut units = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(unit) = stack.pop() {
+            units.push(unit);
+            stack.extend(unit.subunits.iter().rev());
+        }
+        units
+    }
+
+    /// `id`'s positions, one per unit they hold a place in.
+    pub fn positions_of(&self, id: ObjectId) -> Vec<&OrgPosition> {
+        self.units()
+            .into_iter()
+            .flat_map(|u| u.leaders.iter().chain(&u.members))
+            .filter(|p| p.id == id)
+            .collect()
+    }
+}
+
+impl KnowledgeGraph {
+    /// `faction`'s hierarchy: its leaders and members, its sub-factions
+    /// nested below, and each person's direct superiors (see the module
+    /// docs).  Fails with [`UForgeError::NotFound`] for an unknown faction.
+    pub fn get_org_chart(&self, faction: ObjectId) -> Result<OrgChart> {
+        let object = self
+            .get_object(faction)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown faction {faction}")))?;
+        let mut visited = HashSet::from([faction]);
+        let mut root = self.org_unit(object, &mut visited)?;
+        let mut people = HashSet::new();
+        collect_people(&root, &mut people);
+        self.assign_superiors(&mut root, &[], &people)?;
+
+        let mut chart = OrgChart {
+            root,
+            issues: Vec::new(),
+        };
+        let mut issues = Vec::new();
+        for unit in chart.units() {
+            if !self.requires_single_superior(unit.faction)? {
+                continue;
+            }
+            for position in unit.leaders.iter().chain(&unit.members) {
+                let mut superiors: Vec<ObjectId> = Vec::new();
+                for held in chart.positions_of(position.id) {
+                    for superior in &held.superiors {
+                        if !superiors.contains(superior) {
+                            superiors.push(*superior);
+                        }
+                    }
+                }
+                if superiors.len() > 1 && !issues.iter().any(|i: &OrgIssue| i.person == position.id)
+                {
+                    issues.push(OrgIssue {
+                        person: position.id,
+                        faction: unit.faction,
+                        message: format!(
+                            "{} has {} direct superiors, but {} allows one",
+                            position.name,
+                            superiors.len(),
+                            unit.name
+                        ),
+                        superiors,
+                    });
+                }
+            }
+        }
+        chart.issues = issues;
+        Ok(chart)
+    }
+
+    /// Whether `faction`'s type schema sets [`SINGLE_SUPERIOR_KEY`].
+    fn requires_single_superior(&self, faction: ObjectId) -> Result<bool> {
+        let Some(object) = self.get_object(faction)? else {
+            return Ok(false);
+        };
+        Ok(self
+            .object_type_schema_for(&object)
+            .and_then(|t| t.metadata.get(SINGLE_SUPERIOR_KEY).cloned())
+            .is_some_and(|v| v.eq_ignore_ascii_case("true")))
+    }
+
+    /// `faction`'s unit and, recursively, its sub-factions not yet in
+    /// `visited`.  Superiors are filled in later.
+    fn org_unit(&self, faction: ObjectMetadata, visited: &mut HashSet<ObjectId>) -> Result<OrgUnit> {
+        let mut unit = OrgUnit {
+            faction: faction.id,
+            name: faction.name,
+            leaders: Vec::new(),
+            members: Vec::new(),
+            subunits: Vec::new(),
+        };
+        let edges = self.get_relationships(unit.faction)?;
+        let mut subfactions = Vec::new();
+        for edge in &edges {
+            match edge.edge_type.as_str() {
+                LED_BY_EDGE if edge.from == unit.faction => {
+                    push_position(&mut unit.leaders, self, edge.to, edge)?;
+                }
+                SUBFACTION_OF_EDGE if edge.to == unit.faction && visited.insert(edge.from) => {
+                    subfactions.extend(self.get_object(edge.from)?);
+                }
+                _ => {}
+            }
+        }
+        let leaders: HashSet<ObjectId> = unit.leaders.iter().map(|p| p.id).collect();
+        for edge in &edges {
+            if edge.edge_type.as_str() == MEMBER_OF_EDGE
+                && edge.to == unit.faction
+                && !leaders.contains(&edge.from)
+            {
+                push_position(&mut unit.members, self, edge.from, edge)?;
+            }
+        }
+        for subfaction in subfactions {
+            unit.subunits.push(self.org_unit(subfaction, visited)?);
+        }
+        unit.leaders.sort_by(|a, b| a.name.cmp(&b.name));
+        unit.members.sort_by(|a, b| a.name.cmp(&b.name));
+        unit.subunits.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(unit)
+    }
+
+    /// Fill each position's superiors: explicit `reports_to` targets within
+    /// the chart, otherwise the unit's leaders (for members) or
+    /// `parent_leaders` (for leaders).
+    fn assign_superiors(
+        &self,
+        unit: &mut OrgUnit,
+        parent_leaders: &[ObjectId],
+        people: &HashSet<ObjectId>,
+    ) -> Result<()> {
+        let leaders: Vec<ObjectId> = unit.leaders.iter().map(|p| p.id).collect();
+        for position in &mut unit.leaders {
+            position.superiors = self.reports_to(position.id, people)?;
+            if position.superiors.is_empty() {
+                position.superiors = parent_leaders.to_vec();
+            }
+        }
+        for position in &mut unit.members {
+            position.superiors = self.reports_to(position.id, people)?;
+            if position.superiors.is_empty() {
+                position.superiors = leaders.clone();
+            }
+        }
+        for subunit in &mut unit.subunits {
+            let parent = if leaders.is_empty() {
+                parent_leaders.to_vec()
+            } else {
+                leaders.clone()
+            };
+            self.assign_superiors(subunit, &parent, people)?;
+        }
+        Ok(())
+    }
+
+    /// Targets of `person`'s `reports_to` edges that are in `people`.
+    fn reports_to(&self, person: ObjectId, people: &HashSet<ObjectId>) -> Result<Vec<ObjectId>> {
+        Ok(self
+            .get_relationships(person)?
+            .into_iter()
+            .filter(|e| {
+                e.from == person
+                    && e.edge_type.as_str() == REPORTS_TO_EDGE
+                    && people.contains(&e.to)
+            })
+            .map(|e| e.to)
+            .collect())
+    }
+}
+
+/// Everyone holding a position in `unit` or below it.
+fn collect_people(unit: &OrgUnit, people: &mut HashSet<ObjectId>) {
+    people.extend(unit.leaders.iter().chain(&unit.members).map(|p| p.id));
+    for subunit in &unit.subunits {
+        collect_people(subunit, people);
+    }
+}
+
+/// Add `person` to `positions` with the role from `edge`, once.
+fn push_position(
+    positions: &mut Vec<OrgPosition>,
+    graph: &KnowledgeGraph,
+    person: ObjectId,
+    edge: &Edge,
+) -> Result<()> {
+    if positions.iter().any(|p| p.id == person) {
+        return Ok(());
+    }
+    let Some(object) = graph.get_object(person)? else {
+        return Ok(());
+    };
+    positions.push(OrgPosition {
+        id: person,
+        name: object.name,
+        role: edge.metadata.get(ORG_ROLE_KEY).cloned(),
+        superiors: Vec::new(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::ObjectTypeSchema;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::CreateRelationshipRequest;
+    use crate::ObjectBuilder;
+
+    #[tokio::test]
+    async fn test_org_chart_hierarchy_and_single_superior() {
+        let (graph, _temp_dir) = create_test_graph();
+        let mut order_type = ObjectTypeSchema::new("order".to_string(), "A strict order".to_string());
+        order_type
+            .metadata
+            .insert(SINGLE_SUPERIOR_KEY.to_string(), "true".to_string());
+        graph.register_object_type("order", order_type).await.unwrap();
+
+        let faction = |name: &str| {
+            ObjectBuilder::custom("order".to_string(), name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let person = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let link = |from, to, edge_type: &str, role: Option<&str>| {
+            let mut request = CreateRelationshipRequest::new(from, to, edge_type);
+            if let Some(role) = role {
+                request = request.with_property(ORG_ROLE_KEY, role);
+            }
+            graph.create_relationship(request).unwrap();
+        };
+        let (guild, thieves) = (faction("Guild"), faction("Thieves"));
+        let (master, captain, cutpurse, spy) = (
+            person("Guildmaster"),
+            person("Captain"),
+            person("Cutpurse"),
+            person("Spy"),
+        );
+        link(thieves, guild, SUBFACTION_OF_EDGE, None);
+        link(guild, master, LED_BY_EDGE, Some("Guildmaster"));
+        link(thieves, captain, LED_BY_EDGE, Some("Captain"));
+        link(cutpurse, thieves, MEMBER_OF_EDGE, Some("Pickpocket"));
+        link(spy, thieves, MEMBER_OF_EDGE, None);
+        link(spy, guild, MEMBER_OF_EDGE, None);
+
+        let chart = graph.get_org_chart(guild).unwrap();
+        assert_eq!(chart.root.leaders[0].id, master);
+        assert_eq!(chart.root.leaders[0].role.as_deref(), Some("Guildmaster"));
+        let sub = &chart.root.subunits[0];
+        assert_eq!(sub.faction, thieves);
+        assert_eq!(sub.leaders[0].superiors, vec![master]);
+        let pickpocket = sub.members.iter().find(|p| p.id == cutpurse).unwrap();
+        assert_eq!(pickpocket.role.as_deref(), Some("Pickpocket"));
+        assert_eq!(pickpocket.superiors, vec![captain]);
+
+        // The spy answers to both the guildmaster and the captain.
+        assert_eq!(chart.issues.len(), 1);
+        assert_eq!(chart.issues[0].person, spy);
+        assert_eq!(chart.issues[0].superiors.len(), 2);
+
+        // An explicit superior settles it.
+        link(spy, captain, REPORTS_TO_EDGE, None);
+        let chart = graph.get_org_chart(guild).unwrap();
+        assert!(chart.issues.is_empty());
+        assert!(chart
+            .positions_of(spy)
+            .iter()
+            .all(|p| p.superiors == vec![captain]));
+    }
+}
@@ -0,0 +1,327 @@
+//! Persona packets for letting an LLM play an NPC.
+//!
+//! [`KnowledgeGraph::build_npc_persona`] gathers what a roleplaying model
+//! needs about one character into a [`NpcPersona`]: who they are, their
+//! traits and goals, how they talk, who they know, and what happened to
+//! them lately.  Properties players may not see (see
+//! [`crate::visibility`]) and `secret`/`secrets` fields are collected
+//! separately in [`NpcPersona::secrets`] so a frontend can show them to the
+//! GM and leave them out of the prompt.
+//!
+//! The packet is serialisable for a "talk to NPC" panel;
+//! [`NpcPersona::to_system_prompt`] renders it as a system message.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Result, UForgeError};
+use crate::interactions::PARTICIPANT_EDGE;
+use crate::types::{ChunkType, ObjectId};
+use crate::visibility::VISIBILITY_KEY;
+use crate::KnowledgeGraph;
+
+/// Session notes and interactions included in [`NpcPersona::recent_events`].
+pub const PERSONA_RECENT_EVENTS: usize = 5;
+
+/// Properties read as personality traits.
+const TRAIT_KEYS: [&str; 4] = ["traits", "personality", "conditions", "alignment"];
+/// Properties read as goals.
+const GOAL_KEYS: [&str; 2] = ["goals", "motivation"];
+/// Properties read as speech style, first non-empty wins.
+const SPEECH_KEYS: [&str; 4] = ["speech_style", "speech", "voice", "mannerisms"];
+/// Properties always treated as secrets, whatever their visibility.
+const SECRET_KEYS: [&str; 2] = ["secret", "secrets"];
+
+/// One relationship of the NPC, from the NPC's side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PersonaRelationship {
+    pub edge_type: String,
+    /// `true` when the NPC is the edge's source ("Chen *leads* Site 9"),
+    /// `false` when it is the target ("Site 9 *employs* Chen").
+    pub outgoing: bool,
+    pub other_id: ObjectId,
+    pub other_name: String,
+    pub other_type: String,
+}
+
+/// A recent session note or interaction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PersonaEvent {
+    pub text: String,
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Everything an LLM needs to roleplay one NPC.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NpcPersona {
+    pub object_id: ObjectId,
+    pub name: String,
+    pub object_type: String,
+    /// The `description` property.
+    pub summary: Option<String>,
+    pub traits: Vec<String>,
+    pub goals: Vec<String>,
+    pub speech_style: Option<String>,
+    /// Remaining player-visible properties as `(key, value)`, by key.
+    pub facts: Vec<(String, String)>,
+    pub relationships: Vec<PersonaRelationship>,
+    /// Newest first, at most [`PERSONA_RECENT_EVENTS`].
+    pub recent_events: Vec<PersonaEvent>,
+    /// GM-only properties as `(key, value)`.  Excluded from
+    /// [`to_system_prompt`](Self::to_system_prompt) unless asked for.
+    pub secrets: Vec<(String, String)>,
+}
+
+impl NpcPersona {
+    /// Render the packet as a roleplay system prompt.  With
+    /// `include_secrets` the secrets are added with an instruction to let
+    /// them shape behaviour without stating them.
+    pub fn to_system_prompt(&self, include_secrets: bool) -> String {
+        let mut out = format!(
+            "You are {}, a character in a tabletop RPG campaign. Stay in character and \
+             speak only as {} would. Do not invent major facts beyond what is given here.\n",
+            self.name, self.name
+        );
+        if let Some(summary) = &self.summary {
+            out.push_str(&format!("\n## Who you are\n{summary}\n"));
+        }
+        push_list(&mut out, "Traits", &self.traits);
+        push_list(&mut out, "Goals", &self.goals);
+        if let Some(style) = &self.speech_style {
+            out.push_str(&format!("\n## How you speak\n{style}\n"));
+        }
+        let facts: Vec<String> = self
+            .facts
+            .iter()
+            .map(|(k, v)| format!("{k}: {v}"))
+            .collect();
+        push_list(&mut out, "Facts", &facts);
+        let relationships: Vec<String> = self
+            .relationships
+            .iter()
+            .map(|r| {
+                if r.outgoing {
+                    format!("You {} {} ({})", r.edge_type, r.other_name, r.other_type)
+                } else {
+                    format!("{} ({}) {} you", r.other_name, r.other_type, r.edge_type)
+                }
+            })
+            .collect();
+        push_list(&mut out, "People and places you know", &relationships);
+        let events: Vec<String> = self.recent_events.iter().map(|e| e.text.clone()).collect();
+        push_list(&mut out, "Recent events", &events);
+        if include_secrets {
+            let secrets: Vec<String> = self
+                .secrets
+                .iter()
+                .map(|(k, v)| format!("{k}: {v}"))
+                .collect();
+            push_list(
+                &mut out,
+                "Secrets (never state these outright; let them shape what you say and avoid)",
+                &secrets,
+            );
+        }
+        out
+    }
+}
+
+fn push_list(out: &mut String, heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n## {heading}\n"));
+    for item in items {
+        out.push_str(&format!("- {item}\n"));
+    }
+}
+
+/// A property value as display strings: one per array item, otherwise one.
+fn value_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => vec![s.trim().to_string()],
+        Value::Number(n) => vec![n.to_string()],
+        Value::Bool(b) => vec![b.to_string()],
+        Value::Array(items) => items.iter().flat_map(value_strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl KnowledgeGraph {
+    /// Assemble the [`NpcPersona`] for `object_id`.  Works for any object
+    /// type; missing properties leave their sections empty.
+    pub fn build_npc_persona(&self, object_id: ObjectId) -> Result<NpcPersona> {
+        let object = self
+            .get_object(object_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Object {object_id} not found")))?;
+
+        let mut persona = NpcPersona {
+            object_id,
+            name: object.name.clone(),
+            object_type: object.object_type.clone(),
+            summary: object
+                .get_property("description")
+                .filter(|s| !s.trim().is_empty()),
+            traits: Vec::new(),
+            goals: Vec::new(),
+            speech_style: None,
+            facts: Vec::new(),
+            relationships: Vec::new(),
+            recent_events: Vec::new(),
+            secrets: Vec::new(),
+        };
+
+        let mut properties: Vec<(String, Value)> = object
+            .properties
+            .as_object()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        properties.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, value) in properties {
+            if key.starts_with('_') || key == VISIBILITY_KEY || key == "description" {
+                continue;
+            }
+            let values = value_strings(&value);
+            if values.is_empty() {
+                continue;
+            }
+            if SECRET_KEYS.contains(&key.as_str())
+                || !self.is_property_player_visible(&object, &key)
+            {
+                persona.secrets.push((key, values.join(", ")));
+            } else if TRAIT_KEYS.contains(&key.as_str()) {
+                persona.traits.extend(values);
+            } else if GOAL_KEYS.contains(&key.as_str()) {
+                persona.goals.extend(values);
+            } else if SPEECH_KEYS.contains(&key.as_str()) {
+                if persona.speech_style.is_none() {
+                    persona.speech_style = Some(values.join(" "));
+                }
+            } else {
+                persona.facts.push((key, values.join(", ")));
+            }
+        }
+
+        for edge in self.get_relationships(object_id)? {
+            if edge.edge_type.as_str() == PARTICIPANT_EDGE {
+                continue; // covered by recent_events
+            }
+            let outgoing = edge.from == object_id;
+            let other_id = if outgoing { edge.to } else { edge.from };
+            let Some(other) = self.get_object(other_id)? else {
+                continue;
+            };
+            persona.relationships.push(PersonaRelationship {
+                edge_type: edge.edge_type.as_str().to_string(),
+                outgoing,
+                other_id,
+                other_name: other.name,
+                other_type: other.object_type,
+            });
+        }
+        persona.relationships.sort_by(|a, b| {
+            a.other_name
+                .cmp(&b.other_name)
+                .then(a.edge_type.cmp(&b.edge_type))
+        });
+
+        let mut notes: Vec<_> = self
+            .get_text_chunks(object_id)?
+            .into_iter()
+            .filter(|c| matches!(c.chunk_type, ChunkType::SessionNote))
+            .collect();
+        notes.sort_by_key(|n| std::cmp::Reverse(n.created_at));
+        persona.recent_events = notes
+            .into_iter()
+            .map(|c| PersonaEvent {
+                text: c.content.trim().to_string(),
+                at: Some(c.created_at),
+            })
+            .collect();
+        for interaction in self.interactions_involving(object_id)? {
+            let role = interaction
+                .role_of(object_id)
+                .map(|r| format!(" as {r}"))
+                .unwrap_or_default();
+            persona.recent_events.push(PersonaEvent {
+                text: format!("Took part in {}{role}", interaction.name),
+                at: None,
+            });
+        }
+        persona.recent_events.truncate(PERSONA_RECENT_EVENTS);
+
+        Ok(persona)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::interactions::Interaction;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_npc_persona() {
+        let (graph, _temp_dir) = create_test_graph();
+
+        let mut chen = ObjectMetadata::new("npc".to_string(), "Director Chen".to_string())
+            .with_property("description".to_string(), "Runs Site 9.".to_string())
+            .with_property("speech_style".to_string(), "Clipped, formal.".to_string())
+            .with_property(
+                "secret".to_string(),
+                "Sold the artifact's location.".to_string(),
+            )
+            .with_property("role".to_string(), "Director".to_string());
+        chen.properties["traits"] = json!(["paranoid", "loyal to the board"]);
+        chen.properties["goals"] = json!(["Keep the dig quiet"]);
+        let chen = graph.add_object(chen).unwrap();
+        let site = graph
+            .add_object(ObjectMetadata::new(
+                "location".to_string(),
+                "Site 9".to_string(),
+            ))
+            .unwrap();
+        graph.connect_objects_str(chen, site, "leads").unwrap();
+        graph
+            .add_text_chunk(
+                chen,
+                "Chen met the party at the gate.".to_string(),
+                ChunkType::SessionNote,
+            )
+            .unwrap();
+        graph
+            .add_interaction(&Interaction::new("Board hearing").with_participant(chen, "witness"))
+            .unwrap();
+
+        let persona = graph.build_npc_persona(chen).unwrap();
+        assert_eq!(persona.summary.as_deref(), Some("Runs Site 9."));
+        assert_eq!(persona.traits, ["paranoid", "loyal to the board"]);
+        assert_eq!(persona.goals, ["Keep the dig quiet"]);
+        assert_eq!(persona.speech_style.as_deref(), Some("Clipped, formal."));
+        assert_eq!(
+            persona.facts,
+            [("role".to_string(), "Director".to_string())]
+        );
+        assert_eq!(persona.secrets.len(), 1);
+        assert_eq!(persona.relationships.len(), 1);
+        assert!(persona.relationships[0].outgoing);
+        assert_eq!(persona.recent_events.len(), 2);
+        assert_eq!(
+            persona.recent_events[1].text,
+            "Took part in Board hearing as witness"
+        );
+
+        let prompt = persona.to_system_prompt(false);
+        assert!(prompt.contains("- You leads Site 9 (location)"));
+        assert!(!prompt.contains("Sold the artifact"));
+        assert!(persona.to_system_prompt(true).contains("Sold the artifact"));
+
+        let err = graph.build_npc_persona(ObjectId::new_v4()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,197 @@
+//! Per-user pinboards — the "stuff I need at the table tonight" list.
+//!
+//! Each user's pinboard is an ordered list of [`Pin`]s, stored as JSON in
+//! `project_settings` under `pinboard:<user>`, so it survives
+//! [`KnowledgeGraph::clear_data`] like the other project settings.  A pin can
+//! additionally be marked as a favorite.
+//!
+//! Search boosts pinned objects when [`HybridSearchConfig::pinboard`] names
+//! a user (see [`crate::search`]); results then carry
+//! [`SearchSources::pinned`].
+//!
+//! [`HybridSearchConfig::pinboard`]: crate::HybridSearchConfig::pinboard
+//! [`SearchSources::pinned`]: crate::SearchSources::pinned
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Prefix of the `project_settings` key holding a user's pinboard.
+const PINBOARD_SETTING_PREFIX: &str = "pinboard:";
+
+/// One entry on a pinboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pin {
+    pub object_id: ObjectId,
+    #[serde(default)]
+    pub favorite: bool,
+    pub pinned_at: DateTime<Utc>,
+}
+
+impl KnowledgeGraph {
+    /// `user`'s pins in display order.  Pins whose object has since been
+    /// deleted are left out.
+    pub fn pinboard(&self, user: &str) -> Result<Vec<Pin>> {
+        let pins = self.load_pinboard(user)?;
+        let mut kept = Vec::with_capacity(pins.len());
+        for pin in pins {
+            if self.get_object(pin.object_id)?.is_some() {
+                kept.push(pin);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Ids on `user`'s pinboard, for membership checks.
+    pub fn pinned_ids(&self, user: &str) -> Result<HashSet<ObjectId>> {
+        Ok(self
+            .load_pinboard(user)?
+            .into_iter()
+            .map(|p| p.object_id)
+            .collect())
+    }
+
+    /// Append `id` to the end of `user`'s pinboard.  Returns `false` if it
+    /// was already pinned.
+    pub fn pin_object(&self, user: &str, id: ObjectId) -> Result<bool> {
+        if self.get_object(id)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Unknown object {id}")));
+        }
+        let mut pins = self.load_pinboard(user)?;
+        if pins.iter().any(|p| p.object_id == id) {
+            return Ok(false);
+        }
+        pins.push(Pin {
+            object_id: id,
+            favorite: false,
+            pinned_at: Utc::now(),
+        });
+        self.save_pinboard(user, &pins)?;
+        Ok(true)
+    }
+
+    /// Remove `id` from `user`'s pinboard.  Returns `false` if it was not
+    /// pinned.
+    pub fn unpin_object(&self, user: &str, id: ObjectId) -> Result<bool> {
+        let mut pins = self.load_pinboard(user)?;
+        let before = pins.len();
+        pins.retain(|p| p.object_id != id);
+        if pins.len() == before {
+            return Ok(false);
+        }
+        self.save_pinboard(user, &pins)?;
+        Ok(true)
+    }
+
+    /// Mark or unmark a pin as a favorite, pinning the object first if
+    /// needed.
+    pub fn set_pin_favorite(&self, user: &str, id: ObjectId, favorite: bool) -> Result<()> {
+        self.pin_object(user, id)?;
+        let mut pins = self.load_pinboard(user)?;
+        if let Some(pin) = pins.iter_mut().find(|p| p.object_id == id) {
+            pin.favorite = favorite;
+        }
+        self.save_pinboard(user, &pins)
+    }
+
+    /// Move a pinned object to `position` (0 = top; clamped to the end).
+    pub fn move_pin(&self, user: &str, id: ObjectId, position: usize) -> Result<()> {
+        let mut pins = self.pinboard(user)?;
+        let Some(from) = pins.iter().position(|p| p.object_id == id) else {
+            return Err(UForgeError::NotFound(format!("Object {id} is not pinned")));
+        };
+        let pin = pins.remove(from);
+        pins.insert(position.min(pins.len()), pin);
+        self.save_pinboard(user, &pins)
+    }
+
+    /// Replace the pinboard order with `order`, which must list every pinned
+    /// object (as returned by [`pinboard`](Self::pinboard)) exactly once.
+    pub fn reorder_pinboard(&self, user: &str, order: &[ObjectId]) -> Result<()> {
+        let mut pins = self.pinboard(user)?;
+        let distinct: HashSet<ObjectId> = order.iter().copied().collect();
+        if distinct.len() != order.len()
+            || order.len() != pins.len()
+            || pins.iter().any(|p| !distinct.contains(&p.object_id))
+        {
+            return Err(UForgeError::ValidationFailed(
+                "New pinboard order must list every pinned object exactly once".to_string(),
+            ));
+        }
+        pins.sort_by_key(|p| order.iter().position(|id| *id == p.object_id));
+        self.save_pinboard(user, &pins)
+    }
+
+    fn load_pinboard(&self, user: &str) -> Result<Vec<Pin>> {
+        let key = format!("{PINBOARD_SETTING_PREFIX}{user}");
+        match self.storage.get_setting(&key)? {
+            Some(json) => Ok(serde_json::from_str(&json)
+                .with_context(|| format!("Failed to parse pinboard for '{user}'"))?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn save_pinboard(&self, user: &str, pins: &[Pin]) -> Result<()> {
+        let key = format!("{PINBOARD_SETTING_PREFIX}{user}");
+        if pins.is_empty() {
+            return self.storage.delete_setting(&key).map(|_| ());
+        }
+        let json = serde_json::to_string(pins).context("Failed to serialize pinboard")?;
+        self.storage.set_setting(&key, &json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_pin_favorite_and_reorder() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (frodo, sam, gollum) = (add("Frodo"), add("Sam"), add("Gollum"));
+
+        assert!(graph.pin_object("gm", frodo).unwrap());
+        assert!(graph.pin_object("gm", sam).unwrap());
+        assert!(!graph.pin_object("gm", frodo).unwrap());
+        graph.set_pin_favorite("gm", gollum, true).unwrap();
+        assert!(graph.pinboard("player").unwrap().is_empty());
+
+        graph.move_pin("gm", gollum, 0).unwrap();
+        let order: Vec<_> = graph
+            .pinboard("gm")
+            .unwrap()
+            .iter()
+            .map(|p| p.object_id)
+            .collect();
+        assert_eq!(order, vec![gollum, frodo, sam]);
+        assert!(graph.pinboard("gm").unwrap()[0].favorite);
+
+        graph.reorder_pinboard("gm", &[sam, frodo, gollum]).unwrap();
+        let err = graph.reorder_pinboard("gm", &[sam, frodo]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        graph.delete_object(frodo).unwrap();
+        let order: Vec<_> = graph
+            .pinboard("gm")
+            .unwrap()
+            .iter()
+            .map(|p| p.object_id)
+            .collect();
+        assert_eq!(order, vec![sam, gollum]);
+        assert!(graph.unpin_object("gm", sam).unwrap());
+        assert!(!graph.unpin_object("gm", sam).unwrap());
+    }
+}
@@ -0,0 +1,325 @@
+//! Players — the real people at the table — as distinct from their
+//! characters.
+//!
+//! A player is a `player` object.  It owns player characters through
+//! [`PLAYS_EDGE`] edges (one owner per character) and attends sessions
+//! through [`ATTENDED_EDGE`] edges, so questions like "which plots involve
+//! Sarah's characters" or "who was at session 12" are graph queries.
+//!
+//! Ownership also routes visibility: an object whose [`VISIBLE_TO_KEY`]
+//! lists player or character ids is shown only to those players (and the
+//! owners of those characters), on top of the usual rules in
+//! [`crate::visibility`].  [`KnowledgeGraph::redact_for_player`] is what a
+//! player-facing view calls per object.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Object type of player records.
+pub const PLAYER_TYPE: &str = "player";
+
+/// Edge type from a player to each character they own.
+pub const PLAYS_EDGE: &str = "plays";
+
+/// Edge type from a player to each session they attended.
+pub const ATTENDED_EDGE: &str = "attended";
+
+/// Object type whose neighbours count as plots in
+/// [`KnowledgeGraph::plots_involving_player`].
+pub const PLOT_TYPE: &str = "quest";
+
+/// Object property listing the player and character ids allowed to see
+/// it.  Internal (`_`-prefixed), so players never see the list itself.
+pub const VISIBLE_TO_KEY: &str = "_visible_to";
+
+impl KnowledgeGraph {
+    /// Create a `player` record named `name`.
+    pub fn create_player(&self, name: &str) -> Result<ObjectId> {
+        self.add_object(ObjectMetadata::new(
+            PLAYER_TYPE.to_string(),
+            name.to_string(),
+        ))
+    }
+
+    /// Every player record, by name.
+    pub fn players(&self) -> Result<Vec<ObjectMetadata>> {
+        let mut players: Vec<ObjectMetadata> = self
+            .get_all_objects()?
+            .into_iter()
+            .filter(|o| o.object_type == PLAYER_TYPE)
+            .collect();
+        players.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(players)
+    }
+
+    /// Make `player` the owner of `character`, replacing any previous
+    /// owner.  Fails with [`UForgeError::NotFound`] when either is missing
+    /// and [`UForgeError::ValidationFailed`] when `player` is not a player.
+    pub fn set_character_owner(&self, character: ObjectId, player: ObjectId) -> Result<()> {
+        self.require_player(player)?;
+        if self.get_object(character)?.is_none() {
+            return Err(UForgeError::NotFound(format!(
+                "Unknown character {character}"
+            )));
+        }
+        if let Some(previous) = self.character_owner(character)? {
+            if previous == player {
+                return Ok(());
+            }
+            self.delete_edge(previous, character, PLAYS_EDGE)?;
+        }
+        self.connect_objects_str(player, character, PLAYS_EDGE)
+    }
+
+    /// The player who owns `character`, if any.
+    pub fn character_owner(&self, character: ObjectId) -> Result<Option<ObjectId>> {
+        Ok(self
+            .get_relationships(character)?
+            .into_iter()
+            .find(|e| e.to == character && e.edge_type.as_str() == PLAYS_EDGE)
+            .map(|e| e.from))
+    }
+
+    /// Characters owned by `player`, by name.
+    pub fn player_characters(&self, player: ObjectId) -> Result<Vec<ObjectMetadata>> {
+        self.outgoing_objects(player, PLAYS_EDGE)
+    }
+
+    /// Record that `player` attended `session`.  Recording twice is a
+    /// no-op.
+    pub fn record_attendance(&self, session: ObjectId, player: ObjectId) -> Result<()> {
+        self.require_player(player)?;
+        if self.get_object(session)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Unknown session {session}")));
+        }
+        if self.attended(session, player)? {
+            return Ok(());
+        }
+        self.connect_objects_str(player, session, ATTENDED_EDGE)
+    }
+
+    /// Remove `player`'s attendance at `session`.  Returns whether it was
+    /// recorded.
+    pub fn remove_attendance(&self, session: ObjectId, player: ObjectId) -> Result<bool> {
+        if !self.attended(session, player)? {
+            return Ok(false);
+        }
+        self.delete_edge(player, session, ATTENDED_EDGE)?;
+        Ok(true)
+    }
+
+    /// Whether `player` attended `session`.
+    pub fn attended(&self, session: ObjectId, player: ObjectId) -> Result<bool> {
+        Ok(self
+            .get_relationships(player)?
+            .iter()
+            .any(|e| e.from == player && e.to == session && e.edge_type.as_str() == ATTENDED_EDGE))
+    }
+
+    /// Players who attended `session`, by name.
+    pub fn session_attendance(&self, session: ObjectId) -> Result<Vec<ObjectMetadata>> {
+        self.incoming_objects(session, ATTENDED_EDGE)
+    }
+
+    /// Sessions `player` attended, by name.
+    pub fn attended_sessions(&self, player: ObjectId) -> Result<Vec<ObjectMetadata>> {
+        self.outgoing_objects(player, ATTENDED_EDGE)
+    }
+
+    /// Quests connected by any edge to a character `player` owns, by name.
+    pub fn plots_involving_player(&self, player: ObjectId) -> Result<Vec<ObjectMetadata>> {
+        let mut seen = HashSet::new();
+        let mut plots = Vec::new();
+        for character in self.player_characters(player)? {
+            for neighbor in self.get_neighbors(character.id)? {
+                if !seen.insert(neighbor) {
+                    continue;
+                }
+                if let Some(object) = self.get_object(neighbor)? {
+                    if object.object_type == PLOT_TYPE {
+                        plots.push(object);
+                    }
+                }
+            }
+        }
+        plots.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(plots)
+    }
+
+    /// Restrict `object` to `viewers` (players or characters); an empty
+    /// list lifts the restriction.
+    pub fn set_visible_to(&self, object: ObjectId, viewers: &[ObjectId]) -> Result<()> {
+        if self.get_object(object)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Unknown object {object}")));
+        }
+        let ids: Vec<Value> = viewers.iter().map(|id| id.to_string().into()).collect();
+        self.storage
+            .set_node_property(object, VISIBLE_TO_KEY, &Value::from(ids))
+    }
+
+    /// Whether `player` may see `object`: it must be visible to players at
+    /// all ([`is_player_visible`](Self::is_player_visible)) and, when it has
+    /// a [`VISIBLE_TO_KEY`] list, name the player or one of their
+    /// characters.  A player always sees their own characters.
+    pub fn is_visible_to_player(&self, object: &ObjectMetadata, player: ObjectId) -> Result<bool> {
+        if self.character_owner(object.id)? == Some(player) {
+            return Ok(true);
+        }
+        if !self.is_player_visible(object) {
+            return Ok(false);
+        }
+        let Some(viewers) = object
+            .get_json_property(VISIBLE_TO_KEY)
+            .and_then(Value::as_array)
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(true);
+        };
+        let mut allowed: HashSet<String> = HashSet::from([player.to_string()]);
+        allowed.extend(
+            self.player_characters(player)?
+                .into_iter()
+                .map(|c| c.id.to_string()),
+        );
+        Ok(viewers
+            .iter()
+            .any(|v| v.as_str().is_some_and(|id| allowed.contains(id))))
+    }
+
+    /// `object` as `player` may see it: `None` when hidden from them,
+    /// otherwise redacted like
+    /// [`redact_for_players`](Self::redact_for_players).  Owners see their
+    /// own characters unredacted.
+    pub fn redact_for_player(
+        &self,
+        object: &ObjectMetadata,
+        player: ObjectId,
+    ) -> Result<Option<ObjectMetadata>> {
+        if self.character_owner(object.id)? == Some(player) {
+            return Ok(Some(object.clone()));
+        }
+        if !self.is_visible_to_player(object, player)? {
+            return Ok(None);
+        }
+        Ok(self.redact_for_players(object))
+    }
+
+    pub(crate) fn require_player(&self, player: ObjectId) -> Result<()> {
+        match self.get_object(player)? {
+            None => Err(UForgeError::NotFound(format!("Unknown player {player}"))),
+            Some(o) if o.object_type != PLAYER_TYPE => Err(UForgeError::ValidationFailed(format!(
+                "'{}' is a {}, not a player",
+                o.name, o.object_type
+            ))),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Targets of `id`'s outgoing `edge_type` edges, by name.
+    fn outgoing_objects(&self, id: ObjectId, edge_type: &str) -> Result<Vec<ObjectMetadata>> {
+        let mut objects = Vec::new();
+        for edge in self.get_relationships(id)? {
+            if edge.from == id && edge.edge_type.as_str() == edge_type {
+                objects.extend(self.get_object(edge.to)?);
+            }
+        }
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(objects)
+    }
+
+    /// Sources of `id`'s incoming `edge_type` edges, by name.
+    fn incoming_objects(&self, id: ObjectId, edge_type: &str) -> Result<Vec<ObjectMetadata>> {
+        let mut objects = Vec::new();
+        for edge in self.get_relationships(id)? {
+            if edge.to == id && edge.edge_type.as_str() == edge_type {
+                objects.extend(self.get_object(edge.from)?);
+            }
+        }
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::visibility::VISIBILITY_KEY;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_players_own_characters_and_attend_sessions() {
+        let (graph, _temp_dir) = create_test_graph();
+        let sarah = graph.create_player("Sarah").unwrap();
+        let tom = graph.create_player("Tom").unwrap();
+        let ayla = ObjectBuilder::custom("player_character".to_string(), "Ayla".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let rescue = ObjectBuilder::custom("quest".to_string(), "Rescue Gundren".to_string())
+            .with_relationship("involves", ayla)
+            .add_to_graph(&graph)
+            .unwrap();
+        let session = ObjectBuilder::session("Session 12".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        graph.set_character_owner(ayla, tom).unwrap();
+        graph.set_character_owner(ayla, sarah).unwrap();
+        assert_eq!(graph.character_owner(ayla).unwrap(), Some(sarah));
+        assert!(graph.player_characters(tom).unwrap().is_empty());
+        let err = graph.set_character_owner(ayla, rescue).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let plots = graph.plots_involving_player(sarah).unwrap();
+        assert_eq!(plots.len(), 1);
+        assert_eq!(plots[0].id, rescue);
+
+        graph.record_attendance(session, sarah).unwrap();
+        graph.record_attendance(session, sarah).unwrap();
+        graph.record_attendance(session, tom).unwrap();
+        let names: Vec<String> = graph
+            .session_attendance(session)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["Sarah", "Tom"]);
+        assert!(graph.remove_attendance(session, tom).unwrap());
+        assert_eq!(graph.attended_sessions(sarah).unwrap()[0].id, session);
+        assert!(graph.attended_sessions(tom).unwrap().is_empty());
+        assert_eq!(graph.players().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_visibility_routed_by_player() {
+        let (graph, _temp_dir) = create_test_graph();
+        let sarah = graph.create_player("Sarah").unwrap();
+        let tom = graph.create_player("Tom").unwrap();
+        let ayla = ObjectBuilder::custom("player_character".to_string(), "Ayla".to_string())
+            .with_property(VISIBILITY_KEY.to_string(), "gm".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.set_character_owner(ayla, sarah).unwrap();
+        let letter = ObjectBuilder::custom("item".to_string(), "Sealed letter".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.set_visible_to(letter, &[ayla]).unwrap();
+
+        let letter = graph.get_object(letter).unwrap().unwrap();
+        assert!(graph.is_visible_to_player(&letter, sarah).unwrap());
+        assert!(!graph.is_visible_to_player(&letter, tom).unwrap());
+        let redacted = graph.redact_for_player(&letter, sarah).unwrap().unwrap();
+        assert!(redacted.get_json_property(VISIBLE_TO_KEY).is_none());
+        assert!(graph.redact_for_player(&letter, tom).unwrap().is_none());
+
+        let ayla = graph.get_object(ayla).unwrap().unwrap();
+        assert!(graph.redact_for_player(&ayla, sarah).unwrap().is_some());
+        assert!(graph.redact_for_player(&ayla, tom).unwrap().is_none());
+    }
+}
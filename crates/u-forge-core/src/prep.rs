@@ -0,0 +1,416 @@
+//! Session prep sheets — the graph pulled together for one upcoming session.
+//!
+//! [`KnowledgeGraph::generate_prep_sheet`] takes a session object and
+//! collects what a GM wants in front of them at the table:
+//!
+//! - **Locations** — `location` objects linked to the session (e.g. by the
+//!   session's `includes` edges), treated as where play is planned.
+//! - **NPCs** — objects with a `located_in` / `present_in` edge to one of
+//!   those locations.
+//! - **Encounters** — `encounter` objects linked to the session, each
+//!   with its difficulty for [`PrepSheetOptions::party`] when one is given
+//!   (see [`crate::encounters`]), so likely TPKs stand out.
+//! - **Active quests** — `quest` objects whose `status` is `Active`.
+//! - **Plot threads** — quests still open but not active (hooks, rumors,
+//!   quests without a status) and objects in the [`Lifecycle::Rumor`] state.
+//! - **Progress clocks** — every [`ProgressClock`](crate::ProgressClock) in
+//!   the project, with its fill and trigger.
+//! - **Pinned** — the pinboard named by [`PrepSheetOptions::pinboard`].
+//! - **Recent changes** — objects updated since
+//!   [`PrepSheetOptions::changes_since`].
+//!
+//! [`PrepSheet::to_markdown`] renders it as a checklist.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::error::{Result, UForgeError};
+use crate::quests::{quest_status, CLOSED_QUEST_STATUSES, QUEST_TYPE};
+use crate::types::{Lifecycle, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Edge types that place an object at a location.
+const PRESENCE_EDGES: [&str; 2] = ["located_in", "present_in"];
+
+/// Recent-change window used when [`PrepSheetOptions::changes_since`] is unset.
+const DEFAULT_CHANGE_WINDOW_DAYS: i64 = 7;
+
+/// Summaries longer than this are cut at a word boundary.
+const SUMMARY_MAX_CHARS: usize = 160;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Options for [`KnowledgeGraph::generate_prep_sheet`].
+#[derive(Debug, Clone)]
+pub struct PrepSheetOptions {
+    /// User whose pinboard fills [`PrepSheet::pinned`].  `None` leaves it
+    /// empty.
+    pub pinboard: Option<String>,
+    /// Start of the recent-changes window.  `None` means the last seven days.
+    pub changes_since: Option<DateTime<Utc>>,
+    /// Maximum number of recent changes listed (newest first).
+    pub max_recent_changes: usize,
+    /// Party whose members [`PrepSheet::encounters`] are estimated
+    /// against.  `None` lists encounters without an estimate.
+    pub party: Option<ObjectId>,
+}
+
+impl Default for PrepSheetOptions {
+    fn default() -> Self {
+        Self {
+            pinboard: None,
+            changes_since: None,
+            max_recent_changes: 20,
+            party: None,
+        }
+    }
+}
+
+/// One line of a [`PrepSheet`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrepItem {
+    pub id: ObjectId,
+    pub name: String,
+    pub object_type: String,
+    /// First sentence of the object's description, if it has one.
+    pub summary: Option<String>,
+    /// Why the object is listed, e.g. `"at Phandalin"` or `"status: Hook"`.
+    pub note: Option<String>,
+}
+
+impl PrepItem {
+    fn new(object: &ObjectMetadata) -> Self {
+        Self {
+            id: object.id,
+            name: object.name.clone(),
+            object_type: object.object_type.clone(),
+            summary: object
+                .get_property("description")
+                .as_deref()
+                .and_then(summarize),
+            note: None,
+        }
+    }
+
+    fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// Prep document returned by [`KnowledgeGraph::generate_prep_sheet`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PrepSheet {
+    pub session_id: ObjectId,
+    pub session_name: String,
+    pub generated_at: DateTime<Utc>,
+    pub locations: Vec<PrepItem>,
+    pub npcs: Vec<PrepItem>,
+    /// Noted with the difficulty estimate when a party was given.
+    pub encounters: Vec<PrepItem>,
+    pub active_quests: Vec<PrepItem>,
+    pub plot_threads: Vec<PrepItem>,
+    /// One item per clock, listed under the object it is attached to.
+    pub clocks: Vec<PrepItem>,
+    pub pinned: Vec<PrepItem>,
+    /// Start of the window covered by `recent_changes`.
+    pub changes_since: DateTime<Utc>,
+    pub recent_changes: Vec<PrepItem>,
+}
+
+impl PrepSheet {
+    /// Render the sheet as a Markdown checklist, one section per category.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Session prep: {}\n\n_Generated {}_\n",
+            self.session_name,
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        let changes = format!(
+            "Recent changes (since {})",
+            self.changes_since.format("%Y-%m-%d")
+        );
+        for (title, items) in [
+            ("Locations", &self.locations),
+            ("NPCs at planned locations", &self.npcs),
+            ("Encounters", &self.encounters),
+            ("Active quests", &self.active_quests),
+            ("Unresolved plot threads", &self.plot_threads),
+            ("Progress clocks", &self.clocks),
+            ("Pinned", &self.pinned),
+            (changes.as_str(), &self.recent_changes),
+        ] {
+            let _ = write!(out, "\n## {title}\n\n");
+            if items.is_empty() {
+                out.push_str("_None._\n");
+            }
+            for item in items {
+                let _ = write!(out, "- [ ] **{}** ({})", item.name, item.object_type);
+                if let Some(note) = &item.note {
+                    let _ = write!(out, " — {note}");
+                }
+                if let Some(summary) = &item.summary {
+                    let _ = write!(out, ": {summary}");
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Assemble a [`PrepSheet`] for the session object `session_id`.
+    ///
+    /// Fails with [`UForgeError::NotFound`] if the session does not exist.
+    /// Reads every object once, so it is meant for on-demand use.
+    pub fn generate_prep_sheet(
+        &self,
+        session_id: ObjectId,
+        options: &PrepSheetOptions,
+    ) -> Result<PrepSheet> {
+        let Some(session) = self.get_object(session_id)? else {
+            return Err(UForgeError::NotFound(format!(
+                "Unknown session {session_id}"
+            )));
+        };
+
+        let mut locations = Vec::new();
+        let mut npcs = Vec::new();
+        let mut encounters = Vec::new();
+        let mut seen_npcs: HashSet<ObjectId> = HashSet::from([session_id]);
+        for neighbor in self.get_neighbors(session_id)? {
+            let Some(location) = self.get_object(neighbor)? else {
+                continue;
+            };
+            if location.object_type == "encounter" {
+                encounters.push(self.encounter_item(&location, options.party));
+                continue;
+            }
+            if location.object_type != "location" {
+                continue;
+            }
+            for edge in self.get_relationships(location.id)? {
+                if edge.to != location.id || !PRESENCE_EDGES.contains(&edge.edge_type.as_str()) {
+                    continue;
+                }
+                if !seen_npcs.insert(edge.from) {
+                    continue;
+                }
+                if let Some(npc) = self.get_object(edge.from)? {
+                    npcs.push(PrepItem::new(&npc).with_note(format!("at {}", location.name)));
+                }
+            }
+            locations.push(PrepItem::new(&location));
+        }
+
+        let all_objects = self.get_all_objects()?;
+        let mut active_quests = Vec::new();
+        let mut plot_threads = Vec::new();
+        for object in &all_objects {
+            if object.object_type == QUEST_TYPE {
+                let status = object.get_property("status");
+                match quest_status(object).as_deref() {
+                    Some("active") => active_quests.push(PrepItem::new(object)),
+                    Some(s) if CLOSED_QUEST_STATUSES.contains(&s) => {}
+                    _ => plot_threads.push(PrepItem::new(object).with_note(match &status {
+                        Some(status) => format!("status: {status}"),
+                        None => "no status".to_string(),
+                    })),
+                }
+            } else if object.lifecycle == Some(Lifecycle::Rumor) {
+                plot_threads.push(PrepItem::new(object).with_note("rumor"));
+            }
+        }
+
+        let mut clocks = Vec::new();
+        for (object, object_clocks) in self.all_clocks()? {
+            for clock in object_clocks {
+                let mut note = format!("{} {}", clock.name, clock.progress_label());
+                if let Some(trigger) = &clock.trigger {
+                    let _ = write!(note, ", then: {trigger}");
+                }
+                clocks.push(PrepItem::new(&object).with_note(note));
+            }
+        }
+
+        let pinned = match &options.pinboard {
+            Some(user) => {
+                let mut pinned = Vec::new();
+                for pin in self.pinboard(user)? {
+                    if let Some(object) = self.get_object(pin.object_id)? {
+                        let item = PrepItem::new(&object);
+                        pinned.push(if pin.favorite {
+                            item.with_note("favorite")
+                        } else {
+                            item
+                        });
+                    }
+                }
+                pinned
+            }
+            None => Vec::new(),
+        };
+
+        let changes_since = options
+            .changes_since
+            .unwrap_or_else(|| Utc::now() - Duration::days(DEFAULT_CHANGE_WINDOW_DAYS));
+        let mut changed: Vec<&ObjectMetadata> = all_objects
+            .iter()
+            .filter(|o| o.id != session_id && o.updated_at >= changes_since)
+            .collect();
+        changed.sort_by_key(|o| std::cmp::Reverse(o.updated_at));
+        let recent_changes = changed
+            .into_iter()
+            .take(options.max_recent_changes)
+            .map(|o| {
+                let verb = if o.created_at >= changes_since {
+                    "added"
+                } else {
+                    "updated"
+                };
+                PrepItem::new(o).with_note(format!("{verb} {}", o.updated_at.format("%Y-%m-%d")))
+            })
+            .collect();
+
+        Ok(PrepSheet {
+            session_id,
+            session_name: session.name,
+            generated_at: Utc::now(),
+            locations,
+            npcs,
+            encounters,
+            active_quests,
+            plot_threads,
+            clocks,
+            pinned,
+            changes_since,
+            recent_changes,
+        })
+    }
+
+    /// `encounter` as a prep item, noted with its estimate against `party`.
+    fn encounter_item(&self, encounter: &ObjectMetadata, party: Option<ObjectId>) -> PrepItem {
+        let item = PrepItem::new(encounter);
+        let Some(party) = party else {
+            return item;
+        };
+        match self.estimate_encounter_difficulty(encounter.id, party) {
+            Ok(estimate) => item.with_note(estimate.summary()),
+            Err(e) => item.with_note(format!("no estimate: {e}")),
+        }
+    }
+}
+
+/// First sentence of `text`, cut to [`SUMMARY_MAX_CHARS`].
+fn summarize(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let sentence = match text.find(". ") {
+        Some(end) => &text[..=end],
+        None => text,
+    };
+    if sentence.chars().count() <= SUMMARY_MAX_CHARS {
+        return Some(sentence.to_string());
+    }
+    let cut: String = sentence.chars().take(SUMMARY_MAX_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    Some(format!("{cut}…"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_prep_sheet_collects_sections() {
+        let (graph, _temp_dir) = create_test_graph();
+        let phandalin = ObjectBuilder::location("Phandalin".to_string())
+            .with_description("A frontier town. Rebuilt on old ruins.".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let session = ObjectBuilder::session("Session 4".to_string())
+            .with_relationship("includes", phandalin)
+            .add_to_graph(&graph)
+            .unwrap();
+        let sildar = ObjectBuilder::custom("npc".to_string(), "Sildar".to_string())
+            .located_in("Phandalin")
+            .add_to_graph(&graph)
+            .unwrap();
+        let quest = |name: &str, status: &str| {
+            ObjectBuilder::custom("quest".to_string(), name.to_string())
+                .with_property("status".to_string(), status.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let rescue = quest("Rescue Gundren", "Active");
+        quest("Clear the hideout", "Completed");
+        let hook = quest("Dragon sighting", "Hook");
+        graph.pin_object("gm", sildar).unwrap();
+        graph
+            .add_clock(
+                hook,
+                crate::ProgressClock::new("Dragon wakes", 6).with_trigger("Cragmaw burns"),
+            )
+            .unwrap();
+
+        let party = ObjectBuilder::custom("party".to_string(), "The Party".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let ambush = ObjectBuilder::custom("encounter".to_string(), "Ambush".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(session, ambush, "includes").unwrap();
+
+        let options = PrepSheetOptions {
+            pinboard: Some("gm".to_string()),
+            party: Some(party),
+            ..Default::default()
+        };
+        let sheet = graph.generate_prep_sheet(session, &options).unwrap();
+        assert_eq!(sheet.locations[0].id, phandalin);
+        assert_eq!(
+            sheet.locations[0].summary.as_deref(),
+            Some("A frontier town.")
+        );
+        assert_eq!(sheet.npcs.len(), 1);
+        assert_eq!(sheet.npcs[0].note.as_deref(), Some("at Phandalin"));
+        assert_eq!(sheet.encounters.len(), 1);
+        assert_eq!(sheet.encounters[0].id, ambush);
+        assert!(sheet.encounters[0]
+            .note
+            .as_deref()
+            .is_some_and(|n| n.starts_with("no estimate:")));
+        assert_eq!(sheet.active_quests.len(), 1);
+        assert_eq!(sheet.active_quests[0].id, rescue);
+        assert_eq!(sheet.plot_threads.len(), 1);
+        assert_eq!(sheet.plot_threads[0].id, hook);
+        assert_eq!(sheet.pinned[0].id, sildar);
+        assert_eq!(sheet.clocks.len(), 1);
+        assert_eq!(
+            sheet.clocks[0].note.as_deref(),
+            Some("Dragon wakes 0/6, then: Cragmaw burns")
+        );
+        assert!(sheet.recent_changes.iter().all(|c| c.id != session));
+        assert_eq!(sheet.recent_changes.len(), 7);
+
+        let markdown = sheet.to_markdown();
+        assert!(markdown.starts_with("# Session prep: Session 4"));
+        assert!(markdown.contains("- [ ] **Sildar** (npc) — at Phandalin\n"));
+        assert!(markdown.contains("- [ ] **Rescue Gundren** (quest)"));
+
+        let missing = graph
+            .generate_prep_sheet(ObjectId::new_v4(), &options)
+            .unwrap_err();
+        assert_eq!(missing.kind(), crate::error::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,304 @@
+//! Timeline conflicts — the same character in two places at once.
+//!
+//! A [`PRESENT_IN_EDGE`] edge from a character to a location can carry the
+//! in-world span of the stay in its metadata: [`PRESENT_FROM_KEY`] and
+//! [`PRESENT_UNTIL_KEY`], each a date in the project calendar (see
+//! [`crate::calendar`]).  A missing end leaves that side open, so a stay
+//! with only `from` lasts until further notice; an edge with neither is
+//! undated and never conflicts.
+//!
+//! [`KnowledgeGraph::find_presence_conflicts`] flags every pair of dated
+//! stays of one entity whose spans overlap at two distant locations.  Two
+//! locations are not distant when they are the same or one lies inside the
+//! other through `a_part_of` / `located_in` (child to parent) or `contains`
+//! (parent to child) edges — a character in the Castle Ward is also in
+//! Waterdeep.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::calendar::{date_cmp, WorldCalendar, WorldDate};
+use crate::error::{Result, UForgeError};
+use crate::types::{Edge, EdgeChanges, EdgeId, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Edge type from an entity to a location it was at.
+pub const PRESENT_IN_EDGE: &str = "present_in";
+
+/// `present_in` edge metadata key holding the first day of the stay.
+pub const PRESENT_FROM_KEY: &str = "from";
+
+/// `present_in` edge metadata key holding the last day of the stay.
+pub const PRESENT_UNTIL_KEY: &str = "until";
+
+/// Edge types from a place to the place containing it.
+const PART_OF_EDGES: [&str; 2] = ["a_part_of", "located_in"];
+
+/// Edge type from a place to a place inside it.
+const CONTAINS_EDGE: &str = "contains";
+
+/// One dated stay read from a `present_in` edge.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Presence {
+    pub edge: EdgeId,
+    pub location: ObjectId,
+    pub location_name: String,
+    /// `None` means since before records began.
+    pub from: Option<WorldDate>,
+    /// `None` means still there.
+    pub until: Option<WorldDate>,
+}
+
+impl Presence {
+    /// The stay on `edge`, or `None` when it carries no usable date.
+    /// Dates that do not parse in `calendar` count as missing.
+    pub fn from_edge(edge: &Edge, calendar: &WorldCalendar) -> Option<Self> {
+        let date = |key: &str| {
+            edge.metadata
+                .get(key)
+                .and_then(|text| calendar.parse_date(text).ok())
+        };
+        let (from, until) = (date(PRESENT_FROM_KEY), date(PRESENT_UNTIL_KEY));
+        if from.is_none() && until.is_none() {
+            return None;
+        }
+        Some(Self {
+            edge: edge.id,
+            location: edge.to,
+            location_name: String::new(),
+            from,
+            until,
+        })
+    }
+
+    /// Whether the two spans share at least one moment.  Bounds without a
+    /// time cover their whole day.
+    pub fn overlaps(&self, other: &Presence) -> bool {
+        starts_by(&self.from, &other.until) && starts_by(&other.from, &self.until)
+    }
+}
+
+/// Whether a span starting at `start` has begun by `end`; open ends always
+/// have.
+fn starts_by(start: &Option<WorldDate>, end: &Option<WorldDate>) -> bool {
+    match (start, end) {
+        (Some(start), Some(end)) => date_cmp(start, end).is_le(),
+        _ => true,
+    }
+}
+
+/// An entity recorded at two distant locations during overlapping spans.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresenceConflict {
+    pub entity: ObjectId,
+    pub entity_name: String,
+    /// The stay that starts first.
+    pub first: Presence,
+    pub second: Presence,
+}
+
+impl KnowledgeGraph {
+    /// Store the span of the `present_in` edge `edge_id`, keeping its other
+    /// metadata; `None` clears that end.  Fails with
+    /// [`UForgeError::ValidationFailed`] when the edge is not `present_in`
+    /// or `until` comes before `from`.
+    pub fn set_presence_span(
+        &self,
+        edge_id: EdgeId,
+        from: Option<WorldDate>,
+        until: Option<WorldDate>,
+    ) -> Result<Edge> {
+        let edge = self
+            .get_edge(edge_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown edge {edge_id}")))?;
+        if edge.edge_type.as_str() != PRESENT_IN_EDGE {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Edge {edge_id} is {}, not {PRESENT_IN_EDGE}",
+                edge.edge_type.as_str()
+            )));
+        }
+        if let (Some(from), Some(until)) = (&from, &until) {
+            if until < from {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "Stay ends ({until}) before it starts ({from})"
+                )));
+            }
+        }
+        let mut metadata = edge.metadata;
+        for (key, date) in [(PRESENT_FROM_KEY, from), (PRESENT_UNTIL_KEY, until)] {
+            match date {
+                Some(date) => metadata.insert(key.to_string(), date.to_string()),
+                None => metadata.remove(key),
+            };
+        }
+        self.update_edge(
+            edge_id,
+            EdgeChanges {
+                metadata: Some(metadata),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// `entity`'s dated stays, earliest first.
+    pub fn presences(&self, entity: ObjectId) -> Result<Vec<Presence>> {
+        let calendar = self.calendar();
+        let edges: Vec<Edge> = self
+            .get_relationships(entity)?
+            .into_iter()
+            .filter(|e| e.from == entity && e.edge_type.as_str() == PRESENT_IN_EDGE)
+            .collect();
+        self.named_presences(&edges, &calendar)
+    }
+
+    /// Every pair of overlapping stays of one entity at distant locations
+    /// (see the module docs), by entity name and then start date.
+    pub fn find_presence_conflicts(&self) -> Result<Vec<PresenceConflict>> {
+        let calendar = self.calendar();
+        let all_edges = self.get_all_edges()?;
+        let mut parents: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        let mut by_entity: HashMap<ObjectId, Vec<Edge>> = HashMap::new();
+        for edge in all_edges {
+            let edge_type = edge.edge_type.as_str();
+            if PART_OF_EDGES.contains(&edge_type) {
+                parents.entry(edge.from).or_default().push(edge.to);
+            } else if edge_type == CONTAINS_EDGE {
+                parents.entry(edge.to).or_default().push(edge.from);
+            }
+            if edge_type == PRESENT_IN_EDGE {
+                by_entity.entry(edge.from).or_default().push(edge);
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (entity, edges) in by_entity {
+            if edges.len() < 2 {
+                continue;
+            }
+            let Some(object) = self.get_object(entity)? else {
+                continue;
+            };
+            let stays = self.named_presences(&edges, &calendar)?;
+            for (i, first) in stays.iter().enumerate() {
+                for second in &stays[i + 1..] {
+                    if first.overlaps(second)
+                        && !nested(first.location, second.location, &parents)
+                    {
+                        conflicts.push(PresenceConflict {
+                            entity,
+                            entity_name: object.name.clone(),
+                            first: first.clone(),
+                            second: second.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        conflicts.sort_by(|a, b| {
+            a.entity_name
+                .cmp(&b.entity_name)
+                .then_with(|| a.first.from.cmp(&b.first.from))
+                .then_with(|| a.second.from.cmp(&b.second.from))
+        });
+        Ok(conflicts)
+    }
+
+    /// The dated stays among `edges` with location names filled in,
+    /// earliest first (open starts before everything).
+    fn named_presences(&self, edges: &[Edge], calendar: &WorldCalendar) -> Result<Vec<Presence>> {
+        let mut stays = Vec::new();
+        for edge in edges {
+            let Some(mut stay) = Presence::from_edge(edge, calendar) else {
+                continue;
+            };
+            let Some(location) = self.get_object(stay.location)? else {
+                continue;
+            };
+            stay.location_name = location.name;
+            stays.push(stay);
+        }
+        stays.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.until.cmp(&b.until)));
+        Ok(stays)
+    }
+}
+
+/// Whether `a` and `b` are the same place or one contains the other.
+fn nested(a: ObjectId, b: ObjectId, parents: &HashMap<ObjectId, Vec<ObjectId>>) -> bool {
+    a == b || ancestors(a, parents).contains(&b) || ancestors(b, parents).contains(&a)
+}
+
+/// Every place containing `place`, however indirectly.
+fn ancestors(place: ObjectId, parents: &HashMap<ObjectId, Vec<ObjectId>>) -> HashSet<ObjectId> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![place];
+    while let Some(next) = stack.pop() {
+        for parent in parents.get(&next).into_iter().flatten() {
+            if seen.insert(*parent) {
+                stack.push(*parent);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::CreateRelationshipRequest;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_presence_conflicts() {
+        let (graph, _temp_dir) = create_test_graph();
+        let place = |name: &str| {
+            ObjectBuilder::location(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (waterdeep, ward, gate) = (
+            place("Waterdeep"),
+            place("Castle Ward"),
+            place("Baldur's Gate"),
+        );
+        graph.connect_objects_str(ward, waterdeep, "a_part_of").unwrap();
+        let aria = ObjectBuilder::character("Aria".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let stay = |location, from: &str, until: Option<&str>| {
+            let mut request = CreateRelationshipRequest::new(aria, location, PRESENT_IN_EDGE)
+                .with_property(PRESENT_FROM_KEY, from);
+            if let Some(until) = until {
+                request = request.with_property(PRESENT_UNTIL_KEY, until);
+            }
+            graph.create_relationship(request).unwrap()
+        };
+        stay(waterdeep, "1492-01-01", Some("1492-03-01"));
+        stay(ward, "1492-02-01", Some("1492-02-10"));
+        assert!(graph.find_presence_conflicts().unwrap().is_empty());
+
+        let gate_edge = stay(gate, "1492-02-20", None);
+        let conflicts = graph.find_presence_conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].entity, aria);
+        assert_eq!(conflicts[0].first.location, waterdeep);
+        assert_eq!(conflicts[0].second.location_name, "Baldur's Gate");
+
+        // Moving the arrival past the Waterdeep stay resolves it.
+        graph
+            .set_presence_span(gate_edge.id, Some(WorldDate::new(1492, 3, 2)), None)
+            .unwrap();
+        assert!(graph.find_presence_conflicts().unwrap().is_empty());
+        assert_eq!(graph.presences(aria).unwrap().len(), 3);
+
+        let err = graph
+            .set_presence_span(
+                gate_edge.id,
+                Some(WorldDate::new(1492, 3, 2)),
+                Some(WorldDate::new(1492, 3, 1)),
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ValidationFailed);
+    }
+}
@@ -0,0 +1,293 @@
+//! Progress reporting and cancellation for long-running operations.
+//!
+//! Imports, re-indexing, and the other bulk jobs take a
+//! `&dyn` [`ProgressSink`] and call [`ProgressSink::report`] with a
+//! [`Progress`] (stage, done/total, optional message) as they go.  Between
+//! items they call [`ProgressSink::check_cancelled`], which fails with
+//! [`UForgeError::Cancelled`] once the sink's [`CancellationToken`] fires.
+//!
+//! Sinks provided here:
+//! * [`NoProgress`] — ignore everything (the default for callers that do
+//!   not care).
+//! * any `Fn(&Progress) + Send + Sync` closure.
+//! * [`LatestProgress`] — keep the most recent report for a UI poller.
+//! * [`EventBridge`] — forward [`ProgressEvent`]s to an app event emitter
+//!   such as Tauri's `AppHandle::emit`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::error::UForgeError;
+
+// ── Progress ──────────────────────────────────────────────────────────────────
+
+/// One progress report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Short stage name, e.g. `"objects"` or `"embedding"`.
+    pub stage: String,
+    /// Items finished in this stage.
+    pub done: usize,
+    /// Items in this stage, when known.
+    pub total: Option<usize>,
+    /// Human-readable detail, e.g. the item being processed.
+    pub message: Option<String>,
+}
+
+impl Progress {
+    pub fn new(stage: impl Into<String>, done: usize, total: Option<usize>) -> Self {
+        Self {
+            stage: stage.into(),
+            done,
+            total,
+            message: None,
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Completion of the stage in `0.0..=100.0`, or `None` when the total is
+    /// unknown.  An empty stage counts as complete.
+    pub fn percent(&self) -> Option<f32> {
+        self.total.map(|total| {
+            if total == 0 {
+                100.0
+            } else {
+                (self.done.min(total) as f32 / total as f32) * 100.0
+            }
+        })
+    }
+}
+
+// ── Cancellation ──────────────────────────────────────────────────────────────
+
+/// Shared cancel flag.  Clones observe the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    /// Wrap an existing cancel flag.
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+// ── ProgressSink ──────────────────────────────────────────────────────────────
+
+/// Receiver for [`Progress`] reports from a long-running operation.
+///
+/// `report` is called synchronously from the worker — keep it cheap.
+pub trait ProgressSink: Send + Sync {
+    fn report(&self, progress: &Progress);
+
+    /// The token that cancels the operation, if it can be cancelled.
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// `Err(UForgeError::Cancelled)` once cancellation was requested.
+    fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(UForgeError::Cancelled.into());
+        }
+        Ok(())
+    }
+}
+
+impl<F: Fn(&Progress) + Send + Sync> ProgressSink for F {
+    fn report(&self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// A sink that discards every report and is never cancelled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn report(&self, _progress: &Progress) {}
+}
+
+/// Keeps the most recent report, for UIs that poll on a timer.
+#[derive(Debug, Clone, Default)]
+pub struct LatestProgress {
+    latest: Arc<Mutex<Option<Progress>>>,
+    cancel: Option<CancellationToken>,
+}
+
+impl LatestProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// The last report, if any.  Clones share the same slot.
+    pub fn latest(&self) -> Option<Progress> {
+        self.latest.lock().clone()
+    }
+}
+
+impl ProgressSink for LatestProgress {
+    fn report(&self, progress: &Progress) {
+        *self.latest.lock() = Some(progress.clone());
+    }
+
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+}
+
+// ── Event bridge ──────────────────────────────────────────────────────────────
+
+/// Serialisable form of a [`Progress`] report, as sent by [`EventBridge`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    pub stage: String,
+    pub done: usize,
+    pub total: Option<usize>,
+    pub percent: Option<f32>,
+    pub message: Option<String>,
+}
+
+impl From<&Progress> for ProgressEvent {
+    fn from(progress: &Progress) -> Self {
+        Self {
+            stage: progress.stage.clone(),
+            done: progress.done,
+            total: progress.total,
+            percent: progress.percent(),
+            message: progress.message.clone(),
+        }
+    }
+}
+
+/// Forwards reports to an app event channel as [`ProgressEvent`]s.
+///
+/// `emit(event_name, event)` is called at most once per whole percent per
+/// stage (every report when the total is unknown), so a frontend is not
+/// flooded by per-item updates:
+///
+/// ```ignore
+/// let bridge = EventBridge::new("import-progress", move |name, event| {
+///     let _ = app_handle.emit(name, event);
+/// });
+/// ```
+pub struct EventBridge<F> {
+    event_name: String,
+    emit: F,
+    cancel: Option<CancellationToken>,
+    /// `(stage, whole percent)` of the last emitted event.
+    last: Mutex<Option<(String, u32)>>,
+}
+
+impl<F: Fn(&str, &ProgressEvent) + Send + Sync> EventBridge<F> {
+    pub fn new(event_name: impl Into<String>, emit: F) -> Self {
+        Self {
+            event_name: event_name.into(),
+            emit,
+            cancel: None,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+impl<F: Fn(&str, &ProgressEvent) + Send + Sync> ProgressSink for EventBridge<F> {
+    fn report(&self, progress: &Progress) {
+        let event = ProgressEvent::from(progress);
+        if let Some(percent) = event.percent {
+            let key = (event.stage.clone(), percent.floor() as u32);
+            let mut last = self.last.lock();
+            if last.as_ref() == Some(&key) {
+                return;
+            }
+            *last = Some(key);
+        }
+        (self.emit)(&self.event_name, &event);
+    }
+
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_event_bridge_throttles_and_cancels() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let token = CancellationToken::new();
+        let bridge = EventBridge::new("progress", move |name: &str, event: &ProgressEvent| {
+            assert_eq!(name, "progress");
+            sink.lock().push(event.clone());
+        })
+        .with_cancellation(token.clone());
+
+        for done in 0..=1000 {
+            bridge.report(&Progress::new("objects", done, Some(1000)));
+        }
+        bridge.report(&Progress::new("links", 3, None).with_message("Calcryx"));
+        let events = events.lock();
+        assert_eq!(events.len(), 102);
+        assert_eq!(events[100].percent, Some(100.0));
+        assert_eq!(events[101].message.as_deref(), Some("Calcryx"));
+
+        assert!(bridge.check_cancelled().is_ok());
+        token.cancel();
+        let err = bridge.check_cancelled().unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::Cancelled);
+    }
+
+    #[test]
+    fn test_latest_progress_and_closures() {
+        let latest = LatestProgress::new();
+        let reader = latest.clone();
+        latest.report(&Progress::new("embedding", 2, Some(4)));
+        assert_eq!(reader.latest().unwrap().percent(), Some(50.0));
+        assert!(!latest.is_cancelled());
+
+        let count = Mutex::new(0);
+        let closure = |_: &Progress| *count.lock() += 1;
+        closure.report(&Progress::new("x", 0, None));
+        assert_eq!(*count.lock(), 1);
+        assert!(NoProgress.check_cancelled().is_ok());
+    }
+}
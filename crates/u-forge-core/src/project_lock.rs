@@ -0,0 +1,285 @@
+//! Owner lock for a project directory, and recovery after a crash.
+//!
+//! SQLite itself copes with two processes on one file, but two u-forge
+//! instances editing the same project fight over caches, watchers and
+//! background jobs.  [`ProjectLock::acquire`] creates `u-forge.lock` in the
+//! project directory — the owner's pid, when it took the lock and a
+//! heartbeat refreshed by [`ProjectLock::heartbeat`] — and removes it again
+//! when dropped.  The file is created with `create_new`, so of two processes
+//! racing for a free project exactly one wins; a lock already held by this
+//! process counts as held too, so one process never locks a project twice.
+//!
+//! A process that crashes leaves its lock behind.  [`inspect_lock`] calls
+//! such a lock stale when its pid is no longer running (checked on Linux)
+//! or its heartbeat is older than [`LOCK_STALE_AFTER`], and `acquire` asks
+//! its `confirm` callback — the app's "recover this project?" prompt —
+//! before taking it over: the new lock is written to a temporary file,
+//! renamed into place and read back, and only a read-back naming this lock
+//! counts as owning the project.  A live lock, or a stale one the user
+//! declines, fails with a [`UForgeError::Conflict`] naming the holder,
+//! instead of a bare "failed to open database".
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::UForgeError;
+
+/// File name of the lock inside a project directory.
+pub const LOCK_FILE: &str = "u-forge.lock";
+
+/// How often an open project should refresh its heartbeat.
+pub const LOCK_HEARTBEAT_INTERVAL: Duration = Duration::seconds(30);
+
+/// Heartbeat age after which a lock counts as stale.
+pub const LOCK_STALE_AFTER: Duration = Duration::minutes(2);
+
+/// Contents of a lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+    pub heartbeat_at: DateTime<Utc>,
+}
+
+/// What [`inspect_lock`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockState {
+    /// No lock.
+    Free,
+    /// Held by a live process, possibly this one.
+    Held(LockOwner),
+    /// Left behind by a process that is gone.
+    Stale(LockOwner),
+}
+
+/// Whether `pid` is running, where that can be checked.
+fn pid_alive(pid: u32) -> Option<bool> {
+    if cfg!(target_os = "linux") {
+        Some(Path::new(&format!("/proc/{pid}")).exists())
+    } else {
+        None
+    }
+}
+
+/// The state of the lock in project directory `dir`.  An unreadable lock
+/// file counts as stale.
+pub fn inspect_lock(dir: &Path) -> Result<LockState> {
+    let path = dir.join(LOCK_FILE);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(LockState::Free),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+    };
+    let Ok(owner) = serde_json::from_str::<LockOwner>(&text) else {
+        warn!(?path, "Unreadable project lock — treating it as stale");
+        let now = Utc::now();
+        return Ok(LockState::Stale(LockOwner {
+            pid: 0,
+            acquired_at: now,
+            heartbeat_at: now,
+        }));
+    };
+    if owner.pid == std::process::id() {
+        return Ok(LockState::Held(owner));
+    }
+    let dead = pid_alive(owner.pid) == Some(false);
+    if dead || Utc::now() - owner.heartbeat_at > LOCK_STALE_AFTER {
+        Ok(LockState::Stale(owner))
+    } else {
+        Ok(LockState::Held(owner))
+    }
+}
+
+/// This process's claim on a project directory; released on drop.
+#[derive(Debug)]
+pub struct ProjectLock {
+    path: PathBuf,
+    owner: LockOwner,
+}
+
+/// Attempts at creating the lock file when it keeps vanishing between
+/// checks.
+const ACQUIRE_ATTEMPTS: usize = 3;
+
+fn held_error(dir: &Path, owner: &LockOwner) -> anyhow::Error {
+    let holder = if owner.pid == std::process::id() {
+        "this u-forge process".to_string()
+    } else {
+        format!("another u-forge process (pid {})", owner.pid)
+    };
+    UForgeError::Conflict(format!("Project {dir:?} is already open in {holder}")).into()
+}
+
+impl ProjectLock {
+    /// Lock project directory `dir`, asking `confirm` before taking over a
+    /// stale lock; see the module docs.
+    pub fn acquire(dir: &Path, confirm: impl FnOnce(&LockOwner) -> bool) -> Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {dir:?}"))?;
+        let now = Utc::now();
+        let lock = Self {
+            path: dir.join(LOCK_FILE),
+            owner: LockOwner {
+                pid: std::process::id(),
+                acquired_at: now,
+                heartbeat_at: now,
+            },
+        };
+        for _ in 0..ACQUIRE_ATTEMPTS {
+            if lock.create()? {
+                return Ok(lock);
+            }
+            match inspect_lock(dir)? {
+                // Released since `create` saw it; try again.
+                LockState::Free => continue,
+                LockState::Held(owner) => return Err(held_error(dir, &owner)),
+                LockState::Stale(owner) => {
+                    if !confirm(&owner) {
+                        return Err(UForgeError::Conflict(format!(
+                            "Project {dir:?} was left locked by a process that exited (pid {}); \
+                             recover it to open",
+                            owner.pid
+                        ))
+                        .into());
+                    }
+                    warn!(?dir, pid = owner.pid, "Recovering project from a stale lock");
+                    lock.replace()?;
+                    // Another process recovering at the same time may have
+                    // renamed its lock over ours; the last rename wins.
+                    return match lock.read_back() {
+                        Some(owner) if owner == lock.owner => Ok(lock),
+                        Some(owner) => Err(held_error(dir, &owner)),
+                        None => Err(UForgeError::Conflict(format!(
+                            "Lost the lock on project {dir:?} while recovering it"
+                        ))
+                        .into()),
+                    };
+                }
+            }
+        }
+        Err(UForgeError::Conflict(format!("Could not lock project {dir:?}")).into())
+    }
+
+    pub fn owner(&self) -> &LockOwner {
+        &self.owner
+    }
+
+    /// Refresh the heartbeat; call every [`LOCK_HEARTBEAT_INTERVAL`].
+    pub fn heartbeat(&mut self) -> Result<()> {
+        self.owner.heartbeat_at = Utc::now();
+        self.replace()
+    }
+
+    fn json(&self) -> Result<String> {
+        serde_json::to_string(&self.owner).context("Failed to serialize lock")
+    }
+
+    /// Create the lock file if there is none.  Returns `false` when one
+    /// exists already.
+    fn create(&self) -> Result<bool> {
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&self.path)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(e).with_context(|| format!("Failed to create {:?}", self.path)),
+        };
+        file.write_all(self.json()?.as_bytes())
+            .with_context(|| format!("Failed to write {:?}", self.path))?;
+        Ok(true)
+    }
+
+    /// Atomically replace the lock file with this lock.
+    fn replace(&self) -> Result<()> {
+        let temp = self
+            .path
+            .with_extension(format!("lock.{}.tmp", self.owner.pid));
+        std::fs::write(&temp, self.json()?)
+            .with_context(|| format!("Failed to write {temp:?}"))?;
+        std::fs::rename(&temp, &self.path)
+            .with_context(|| format!("Failed to replace {:?}", self.path))
+    }
+
+    /// The owner recorded in the lock file, if readable.
+    fn read_back(&self) -> Option<LockOwner> {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        // Only remove the file while it is still ours.
+        let ours = self.read_back().is_some_and(|owner| {
+            owner.pid == self.owner.pid && owner.acquired_at == self.owner.acquired_at
+        });
+        if ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_lock(dir: &Path, pid: u32, heartbeat_at: DateTime<Utc>) {
+        let owner = LockOwner {
+            pid,
+            acquired_at: heartbeat_at,
+            heartbeat_at,
+        };
+        std::fs::write(dir.join(LOCK_FILE), serde_json::to_string(&owner).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_recovered_only_with_confirmation() {
+        let dir = TempDir::new().unwrap();
+        let lock = ProjectLock::acquire(dir.path(), |_| false).unwrap();
+        assert_eq!(lock.owner().pid, std::process::id());
+        // This process already holds it: a second lock is refused and the
+        // first one's file survives.
+        assert_eq!(
+            inspect_lock(dir.path()).unwrap(),
+            LockState::Held(lock.owner().clone())
+        );
+        let err = ProjectLock::acquire(dir.path(), |_| true).unwrap_err();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::Conflict
+        );
+        assert!(dir.path().join(LOCK_FILE).exists());
+        drop(lock);
+        assert!(!dir.path().join(LOCK_FILE).exists());
+
+        // A live holder with a fresh heartbeat.
+        write_lock(dir.path(), 1, Utc::now());
+        assert!(matches!(inspect_lock(dir.path()).unwrap(), LockState::Held(_)));
+        let err = ProjectLock::acquire(dir.path(), |_| true).unwrap_err();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::Conflict
+        );
+
+        // A crashed holder: its heartbeat stopped long ago.
+        write_lock(dir.path(), 1, Utc::now() - Duration::hours(1));
+        assert!(matches!(inspect_lock(dir.path()).unwrap(), LockState::Stale(_)));
+        assert!(ProjectLock::acquire(dir.path(), |_| false).is_err());
+        let mut lock = ProjectLock::acquire(dir.path(), |owner| owner.pid == 1).unwrap();
+        lock.heartbeat().unwrap();
+        assert_eq!(
+            inspect_lock(dir.path()).unwrap(),
+            LockState::Held(lock.owner().clone())
+        );
+        drop(lock);
+        assert_eq!(inspect_lock(dir.path()).unwrap(), LockState::Free);
+    }
+}
@@ -0,0 +1,543 @@
+//! Several projects open at once, and search across all of them.
+//!
+//! GMs often keep setting lore and each campaign's notes as separate
+//! projects.  A [`ProjectManager`] holds every open project's
+//! [`KnowledgeGraph`] under a name, sharing one [`InferenceQueue`], and
+//! [`search_all`](ProjectManager::search_all) runs [`search_hybrid`] on each
+//! project concurrently and merges the hits by score, tagging each with the
+//! project it came from.  Hybrid scores are rank-based (RRF) or come from the
+//! same reranker, so they compare across projects.  A project whose search
+//! fails is logged and left out rather than failing the whole search.
+//!
+//! The manager is also where [cross-project references](crate::crosslinks)
+//! are made ([`link_across`](ProjectManager::link_across)) and resolved
+//! against whichever projects are open.
+//!
+//! [`move_project`](ProjectManager::move_project) relocates a project's
+//! directory — the SQLite file, its WAL and any sidecar files — and reopens
+//! it in place, undoing the move if any step fails.  Moving folders by hand
+//! while the app has them open is what breaks projects.
+//!
+//! Projects opened through the manager hold a [`ProjectLock`], so a second
+//! u-forge process cannot open them too;
+//! [`open_with_recovery`](ProjectManager::open_with_recovery) takes over a
+//! lock left by a crash once the user confirms.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use parking_lot::{Mutex, RwLock};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::crosslinks::{ExternalRef, ResolvedRef};
+use crate::error::UForgeError;
+use crate::health::record_error;
+use crate::project_lock::{LockOwner, ProjectLock};
+use crate::queue::InferenceQueue;
+use crate::search::{search_hybrid, HybridSearchConfig, NodeSearchResult};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Setting holding the directory a project was last moved to by
+/// [`ProjectManager::move_project`].
+pub const PROJECT_PATH_SETTING: &str = "project_path";
+
+/// One hit from [`ProjectManager::search_all`].
+#[derive(Debug, Clone)]
+pub struct ProjectSearchResult {
+    /// Name the project is registered under.
+    pub project: String,
+    pub result: NodeSearchResult,
+}
+
+/// The open projects, by name.
+pub struct ProjectManager {
+    projects: RwLock<BTreeMap<String, Arc<KnowledgeGraph>>>,
+    /// Owner locks of the projects opened here, by name.
+    locks: Mutex<BTreeMap<String, ProjectLock>>,
+    queue: InferenceQueue,
+    hq_queue: Option<InferenceQueue>,
+}
+
+impl ProjectManager {
+    /// A manager with no projects whose searches embed with `queue`.
+    pub fn new(queue: InferenceQueue) -> Self {
+        Self {
+            projects: RwLock::new(BTreeMap::new()),
+            locks: Mutex::new(BTreeMap::new()),
+            queue,
+            hq_queue: None,
+        }
+    }
+
+    pub fn with_hq_queue(mut self, hq_queue: InferenceQueue) -> Self {
+        self.hq_queue = Some(hq_queue);
+        self
+    }
+
+    /// Open the project at `db_path` under `name`.  Fails with
+    /// [`UForgeError::Conflict`] when the name is taken or another process
+    /// holds the project's lock, stale or not.
+    pub fn open<P: AsRef<Path>>(&self, name: &str, db_path: P) -> Result<Arc<KnowledgeGraph>> {
+        self.open_with_recovery(name, db_path, |_| false)
+    }
+
+    /// [`open`](Self::open), taking over a lock left by a crashed process
+    /// when `confirm` agrees.
+    pub fn open_with_recovery<P: AsRef<Path>>(
+        &self,
+        name: &str,
+        db_path: P,
+        confirm: impl FnOnce(&LockOwner) -> bool,
+    ) -> Result<Arc<KnowledgeGraph>> {
+        self.ensure_free(name)?;
+        let lock = ProjectLock::acquire(db_path.as_ref(), confirm)?;
+        let graph = Arc::new(KnowledgeGraph::new(db_path)?);
+        self.register(name, graph.clone())?;
+        self.locks.lock().insert(name.to_string(), lock);
+        Ok(graph)
+    }
+
+    /// Add an already open project under `name`.  Fails with
+    /// [`UForgeError::Conflict`] when the name is taken.
+    pub fn register(&self, name: &str, graph: Arc<KnowledgeGraph>) -> Result<()> {
+        let mut projects = self.projects.write();
+        if projects.contains_key(name) {
+            return Err(name_taken(name));
+        }
+        projects.insert(name.to_string(), graph);
+        Ok(())
+    }
+
+    /// Forget project `name`.  Returns `false` when it was not open.
+    pub fn close(&self, name: &str) -> bool {
+        self.locks.lock().remove(name);
+        self.projects.write().remove(name).is_some()
+    }
+
+    /// Refresh the lock heartbeat of every project opened here; call every
+    /// [`LOCK_HEARTBEAT_INTERVAL`](crate::project_lock::LOCK_HEARTBEAT_INTERVAL).
+    pub fn heartbeat(&self) -> Result<()> {
+        for lock in self.locks.lock().values_mut() {
+            lock.heartbeat()?;
+        }
+        Ok(())
+    }
+
+    pub fn project(&self, name: &str) -> Option<Arc<KnowledgeGraph>> {
+        self.projects.read().get(name).cloned()
+    }
+
+    /// Names of the open projects, sorted.
+    pub fn project_names(&self) -> Vec<String> {
+        self.projects.read().keys().cloned().collect()
+    }
+
+    /// [`search_all_with`](Self::search_all_with) using the default
+    /// [`HybridSearchConfig`].
+    pub async fn search_all(&self, query: &str) -> Result<Vec<ProjectSearchResult>> {
+        self.search_all_with(query, &HybridSearchConfig::default())
+            .await
+    }
+
+    /// Search every open project for `query` and merge the hits, best
+    /// first; `config.limit` applies to the merged list.
+    pub async fn search_all_with(
+        &self,
+        query: &str,
+        config: &HybridSearchConfig,
+    ) -> Result<Vec<ProjectSearchResult>> {
+        // Snapshot so a project opened or closed mid-search does not block.
+        let projects: Vec<(String, Arc<KnowledgeGraph>)> = self
+            .projects
+            .read()
+            .iter()
+            .map(|(name, graph)| (name.clone(), graph.clone()))
+            .collect();
+        let searches = projects.iter().map(|(name, graph)| async move {
+            let results =
+                search_hybrid(graph, &self.queue, self.hq_queue.as_ref(), query, config).await;
+            (name, results)
+        });
+
+        let mut merged = Vec::new();
+        for (name, results) in futures::future::join_all(searches).await {
+            match results {
+                Ok(results) => merged.extend(results.into_iter().map(|result| {
+                    ProjectSearchResult {
+                        project: name.clone(),
+                        result,
+                    }
+                })),
+                Err(e) => {
+                    warn!(project = %name, %e, "Project search failed — leaving it out");
+                    record_error("search", format!("Search in project '{name}' failed: {e}"));
+                }
+            }
+        }
+        merged.sort_by(|a, b| {
+            b.result
+                .score
+                .total_cmp(&a.result.score)
+                .then_with(|| a.project.cmp(&b.project))
+                .then_with(|| a.result.node.name.cmp(&b.result.node.name))
+        });
+        merged.truncate(config.limit);
+        Ok(merged)
+    }
+
+    /// The open project whose [`KnowledgeGraph::project_id`] is `id`, with
+    /// the name it is open under.
+    pub fn project_by_id(&self, id: Uuid) -> Result<Option<(String, Arc<KnowledgeGraph>)>> {
+        for (name, graph) in self.projects.read().iter() {
+            if graph.project_id()? == id {
+                return Ok(Some((name.clone(), graph.clone())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reference `target` in open project `to_project` from `source` in open
+    /// project `from_project`, labelled with the target's current name.
+    ///
+    /// Fails with [`UForgeError::NotFound`] when either project is not open
+    /// or either object does not exist.
+    pub fn link_across(
+        &self,
+        from_project: &str,
+        source: ObjectId,
+        edge_type: &str,
+        to_project: &str,
+        target: ObjectId,
+    ) -> Result<ExternalRef> {
+        let from = self.require_project(from_project)?;
+        let to = self.require_project(to_project)?;
+        let object = to.get_object(target)?.ok_or_else(|| {
+            UForgeError::NotFound(format!(
+                "Object {target} not found in project '{to_project}'"
+            ))
+        })?;
+        Ok(from.add_external_ref(source, edge_type, to.project_id()?, target, &object.name)?)
+    }
+
+    /// Look `reference`'s target up in the open projects.
+    pub fn resolve(&self, reference: &ExternalRef) -> Result<ResolvedRef> {
+        let Some((project, graph)) = self.project_by_id(reference.project_id)? else {
+            return Ok(ResolvedRef::ProjectClosed);
+        };
+        Ok(match graph.get_object(reference.object_id)? {
+            Some(object) => ResolvedRef::Resolved { project, object },
+            None => ResolvedRef::Missing,
+        })
+    }
+
+    /// Every reference of `source` in open project `project`, each with what
+    /// it resolves to — the object plus the foreign objects it pulls in.
+    pub fn transclude(
+        &self,
+        project: &str,
+        source: ObjectId,
+    ) -> Result<Vec<(ExternalRef, ResolvedRef)>> {
+        let graph = self.require_project(project)?;
+        graph
+            .external_refs(source)?
+            .into_iter()
+            .map(|reference| {
+                let resolved = self.resolve(&reference)?;
+                Ok((reference, resolved))
+            })
+            .collect()
+    }
+
+    /// Move the project directory `old_path` to `new_path` as one
+    /// operation.
+    ///
+    /// A project open from `old_path` is closed, moved, and reopened from
+    /// `new_path` under the same name, with [`PROJECT_PATH_SETTING`]
+    /// recording the new location.  The directory is renamed, or copied and
+    /// then deleted when `new_path` is on another filesystem.  If any step
+    /// fails the directory is put back and the project reopened where it
+    /// was.
+    ///
+    /// Fails with [`UForgeError::NotFound`] when `old_path` holds no
+    /// project, [`UForgeError::Conflict`] when `new_path` exists or the open
+    /// project is still shared elsewhere, and
+    /// [`UForgeError::ValidationFailed`] when `new_path` is inside
+    /// `old_path`.
+    pub fn move_project<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        old_path: P,
+        new_path: Q,
+    ) -> Result<()> {
+        let (old_path, new_path) = (old_path.as_ref(), new_path.as_ref());
+        if !old_path.join("knowledge.db").is_file() {
+            return Err(UForgeError::NotFound(format!("No project at {old_path:?}")).into());
+        }
+        if new_path.exists() {
+            return Err(UForgeError::Conflict(format!("{new_path:?} already exists")).into());
+        }
+        let canonical = std::fs::canonicalize(old_path)
+            .with_context(|| format!("Failed to resolve {old_path:?}"))?;
+        if std::path::absolute(new_path)?.starts_with(&canonical) {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Cannot move a project into itself ({new_path:?})"
+            ))
+            .into());
+        }
+
+        // Held throughout so nothing opens or registers the project mid-move.
+        let mut projects = self.projects.write();
+        let name = projects
+            .iter()
+            .find(|(_, graph)| {
+                std::fs::canonicalize(graph.db_path()).is_ok_and(|p| p == canonical)
+            })
+            .map(|(name, _)| name.clone());
+        if let Some(name) = &name {
+            projects[name].storage.checkpoint()?;
+            let graph = projects.remove(name).expect("project found above");
+            // The connection must close before the files move.
+            if let Err(graph) = Arc::try_unwrap(graph) {
+                projects.insert(name.clone(), graph);
+                return Err(UForgeError::Conflict(format!(
+                    "Project '{name}' is still in use; close it elsewhere before moving"
+                ))
+                .into());
+            }
+        }
+        // The lock moves with the project; re-taken at the new path below.
+        let locked = name
+            .as_ref()
+            .is_some_and(|name| self.locks.lock().remove(name).is_some());
+        let reopen = |projects: &mut BTreeMap<String, Arc<KnowledgeGraph>>,
+                      name: Option<String>,
+                      path: &Path,
+                      graph: Option<KnowledgeGraph>|
+         -> Result<()> {
+            let Some(name) = name else {
+                return Ok(());
+            };
+            if locked {
+                let lock = ProjectLock::acquire(path, |_| false)?;
+                self.locks.lock().insert(name.clone(), lock);
+            }
+            let graph = match graph {
+                Some(graph) => graph,
+                None => KnowledgeGraph::new(path)?,
+            };
+            projects.insert(name, Arc::new(graph));
+            Ok(())
+        };
+
+        if let Err(e) = relocate_dir(old_path, new_path) {
+            reopen(&mut projects, name, old_path, None)?;
+            return Err(e);
+        }
+        let moved = KnowledgeGraph::new(new_path).and_then(|graph| {
+            graph
+                .storage
+                .set_setting(PROJECT_PATH_SETTING, &new_path.display().to_string())?;
+            Ok(graph)
+        });
+        match moved {
+            Ok(graph) => reopen(&mut projects, name, new_path, Some(graph)),
+            Err(e) => {
+                warn!(
+                    from = ?old_path,
+                    to = ?new_path,
+                    %e,
+                    "Moved project failed to open — moving it back"
+                );
+                relocate_dir(new_path, old_path).context("Failed to roll back project move")?;
+                reopen(&mut projects, name, old_path, None)?;
+                Err(e.into())
+            }
+        }
+    }
+
+    fn require_project(&self, name: &str) -> Result<Arc<KnowledgeGraph>> {
+        self.project(name).ok_or_else(|| {
+            UForgeError::NotFound(format!("No project named '{name}' is open")).into()
+        })
+    }
+
+    fn ensure_free(&self, name: &str) -> Result<()> {
+        if self.projects.read().contains_key(name) {
+            return Err(name_taken(name));
+        }
+        Ok(())
+    }
+}
+
+fn name_taken(name: &str) -> anyhow::Error {
+    UForgeError::Conflict(format!("A project named '{name}' is already open")).into()
+}
+
+/// Rename `from` to `to`, falling back to copy-then-delete across
+/// filesystems.  A failed copy removes what it wrote, leaving `from` as it
+/// was.
+fn relocate_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {parent:?}"))?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = copy_dir(from, to) {
+        let _ = std::fs::remove_dir_all(to);
+        return Err(e);
+    }
+    std::fs::remove_dir_all(from).with_context(|| format!("Failed to remove {from:?}"))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir(to).with_context(|| format!("Failed to create {to:?}"))?;
+    for entry in std::fs::read_dir(from).with_context(|| format!("Failed to read {from:?}"))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {:?}", entry.path()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::InferenceQueueBuilder;
+    use crate::types::{ChunkType, ObjectMetadata};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_search_all_merges_projects_with_attribution() {
+        let (lore_dir, campaign_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let manager = ProjectManager::new(InferenceQueueBuilder::new().build());
+        for (name, dir, object, text) in [
+            ("lore", &lore_dir, "Klauth", "An ancient red dragon of the Sword Mountains."),
+            ("campaign", &campaign_dir, "Session 4", "The party fled from a young dragon."),
+        ] {
+            let graph = manager.open(name, dir.path()).unwrap();
+            let id = graph
+                .add_object(ObjectMetadata::new("session".to_string(), object.to_string()))
+                .unwrap();
+            graph
+                .add_text_chunk(id, text.to_string(), ChunkType::UserNote)
+                .unwrap();
+        }
+        let err = manager.open("lore", lore_dir.path()).err().unwrap();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::Conflict
+        );
+
+        let config = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            limit: 10,
+            ..Default::default()
+        };
+        let results = manager.search_all_with("dragon", &config).await.unwrap();
+        let mut found: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.project.as_str(), r.result.node.name.as_str()))
+            .collect();
+        found.sort();
+        assert_eq!(found, [("campaign", "Session 4"), ("lore", "Klauth")]);
+
+        assert!(manager.close("campaign"));
+        let results = manager.search_all_with("dragon", &config).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(manager.project_names(), ["lore"]);
+    }
+
+    #[tokio::test]
+    async fn test_move_project_reopens_in_place() {
+        let root = TempDir::new().unwrap();
+        let old_path = root.path().join("campaign");
+        let new_path = root.path().join("moved/campaign");
+        let manager = ProjectManager::new(InferenceQueueBuilder::new().build());
+        let graph = manager.open("campaign", &old_path).unwrap();
+        let id = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Sildar".to_string()))
+            .unwrap();
+
+        // Still shared: refused, and the project stays open where it was.
+        let err = manager.move_project(&old_path, &new_path).unwrap_err();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::Conflict
+        );
+        assert!(manager.project("campaign").is_some());
+        drop(graph);
+
+        manager.move_project(&old_path, &new_path).unwrap();
+        assert!(!old_path.exists());
+        assert!(new_path.join(crate::project_lock::LOCK_FILE).exists());
+        let moved = manager.project("campaign").unwrap();
+        assert_eq!(moved.db_path(), new_path.as_path());
+        assert!(moved.get_object(id).unwrap().is_some());
+        assert_eq!(
+            moved.storage.get_setting(PROJECT_PATH_SETTING).unwrap(),
+            Some(new_path.display().to_string())
+        );
+
+        let err = manager.move_project(&old_path, root.path()).unwrap_err();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::NotFound
+        );
+        drop(moved);
+        let err = manager.move_project(&new_path, root.path()).unwrap_err();
+        assert_eq!(
+            UForgeError::kind_of(&err),
+            crate::error::ErrorKind::Conflict
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cross_project_refs_resolve_lazily() {
+        let (bible_dir, campaign_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let manager = ProjectManager::new(InferenceQueueBuilder::new().build());
+        let bible = manager.open("bible", bible_dir.path()).unwrap();
+        let campaign = manager.open("campaign", campaign_dir.path()).unwrap();
+        let waterdeep = bible
+            .add_object(ObjectMetadata::new("location".to_string(), "Waterdeep".to_string()))
+            .unwrap();
+        let session = campaign
+            .add_object(ObjectMetadata::new("session".to_string(), "Session 1".to_string()))
+            .unwrap();
+
+        let reference = manager
+            .link_across("campaign", session, "set_in", "bible", waterdeep)
+            .unwrap();
+        assert_eq!(reference.label, "Waterdeep");
+        assert_eq!(reference.project_id, bible.project_id().unwrap());
+        // Linking again keeps the one reference.
+        manager
+            .link_across("campaign", session, "set_in", "bible", waterdeep)
+            .unwrap();
+        assert_eq!(campaign.external_refs(session).unwrap().len(), 1);
+
+        let resolved = manager.transclude("campaign", session).unwrap();
+        assert!(matches!(
+            &resolved[0].1,
+            ResolvedRef::Resolved { project, object } if project == "bible" && object.id == waterdeep
+        ));
+
+        bible.delete_object(waterdeep).unwrap();
+        assert!(matches!(manager.resolve(&reference).unwrap(), ResolvedRef::Missing));
+        manager.close("bible");
+        assert!(matches!(manager.resolve(&reference).unwrap(), ResolvedRef::ProjectClosed));
+
+        assert!(campaign.remove_external_ref(reference.id).unwrap());
+        assert!(campaign.external_refs(session).unwrap().is_empty());
+    }
+}
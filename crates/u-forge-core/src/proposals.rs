@@ -0,0 +1,468 @@
+//! AI proposal review queue.
+//!
+//! LLM output is never written straight into the canon graph.  Instead, each
+//! generated object, edge, or property change is stored as a pending
+//! [`Proposal`] in the `proposals` table (see `graph/proposals.rs`).  A user
+//! then lists the queue and either accepts a proposal (applying it to the
+//! graph), edits it before accepting, or rejects it.
+//!
+//! Accepting goes through the same checks as
+//! [`KnowledgeGraph::commit_staging`]: new objects get their schema defaults
+//! and lifecycle, edges must join existing objects and pass strict edge mode,
+//! and new objects are announced as
+//! [`GraphEvent::ObjectCreated`](crate::events::GraphEvent::ObjectCreated).  A
+//! proposed object's description is added as an
+//! [`AiGenerated`](ChunkType::AiGenerated) chunk, so its inline entity links
+//! are indexed.  Proposals are canon changes: they apply to the main graph
+//! even while a [branch](crate::branches) is active.
+//!
+//! Pending proposals are invisible to every graph query, FTS5, and ANN search —
+//! they live in their own table and only touch `nodes` / `edges` on accept.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::staging::StagingLayer;
+use crate::types::{ChunkType, Edge, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+// ── Identifiers ───────────────────────────────────────────────────────────────
+
+/// Unique identifier for a queued proposal.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProposalId(pub uuid::Uuid);
+
+impl ProposalId {
+    pub fn new_v4() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+
+    pub fn parse_str(s: &str) -> Result<Self, uuid::Error> {
+        uuid::Uuid::parse_str(s).map(Self)
+    }
+
+    /// Return the inner UUID formatted with hyphens (e.g. for SQL params).
+    pub fn hyphenated(&self) -> uuid::fmt::Hyphenated {
+        self.0.hyphenated()
+    }
+}
+
+impl std::fmt::Display for ProposalId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// A single change an AI component would like to make to the graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum ProposedChange {
+    /// Create (or overwrite) an object.
+    Object(ObjectMetadata),
+    /// Create (or overwrite) an edge between two existing objects.
+    Edge(Edge),
+    /// Set a single property on an existing object.
+    Property {
+        object_id: ObjectId,
+        key: String,
+        value: serde_json::Value,
+    },
+}
+
+impl ProposedChange {
+    /// Storage discriminant — matches the `kind` column of the `proposals` table.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProposedChange::Object(_) => "object",
+            ProposedChange::Edge(_) => "edge",
+            ProposedChange::Property { .. } => "property",
+        }
+    }
+}
+
+/// Review state of a [`Proposal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    /// Waiting for a user decision.
+    Pending,
+    /// Applied to the graph.
+    Accepted,
+    /// Discarded without touching the graph.
+    Rejected,
+}
+
+impl ProposalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStatus::Pending => "pending",
+            ProposalStatus::Accepted => "accepted",
+            ProposalStatus::Rejected => "rejected",
+        }
+    }
+
+    /// Parse the stored snake_case string.  Returns `None` for unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(ProposalStatus::Pending),
+            "accepted" => Some(ProposalStatus::Accepted),
+            "rejected" => Some(ProposalStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// A queued AI-generated change awaiting review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub id: ProposalId,
+    pub change: ProposedChange,
+    pub status: ProposalStatus,
+    /// Free-form label for what produced the proposal (e.g. `"agent"`, `"extraction"`).
+    pub source: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Set when the proposal is accepted or rejected.
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Proposal {
+    pub fn new(change: ProposedChange) -> Self {
+        Self {
+            id: ProposalId::new_v4(),
+            change,
+            status: ProposalStatus::Pending,
+            source: None,
+            created_at: chrono::Utc::now(),
+            resolved_at: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+}
+
+// ── Facade ────────────────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// Queue `change` for review without touching the graph.
+    ///
+    /// `source` labels the producer (e.g. `"agent"`) for display in the review UI.
+    pub fn propose_change(
+        &self,
+        change: ProposedChange,
+        source: Option<&str>,
+    ) -> Result<ProposalId> {
+        let mut proposal = Proposal::new(change);
+        proposal.source = source.map(str::to_string);
+        let id = proposal.id;
+        self.storage.insert_proposal(&proposal)?;
+        Ok(id)
+    }
+
+    /// Retrieve a proposal by ID, or `None` if it does not exist.
+    pub fn get_proposal(&self, id: ProposalId) -> Result<Option<Proposal>> {
+        self.storage.get_proposal(id)
+    }
+
+    /// List proposals, oldest first.  `None` returns every status.
+    pub fn list_proposals(&self, status: Option<ProposalStatus>) -> Result<Vec<Proposal>> {
+        self.storage.list_proposals(status)
+    }
+
+    /// Apply a pending proposal to the graph exactly as it was proposed.
+    ///
+    /// Applying and marking it accepted happen in one transaction, so a
+    /// proposal accepted or rejected meanwhile fails with
+    /// [`UForgeError::Conflict`] and is never applied twice.
+    pub fn accept_proposal(&self, id: ProposalId) -> Result<()> {
+        let proposal = self.pending_proposal(id)?;
+        self.apply_proposal(id, proposal.change, None)
+    }
+
+    /// Replace a pending proposal's change with `edited`, then apply it, in
+    /// one transaction like [`accept_proposal`](Self::accept_proposal).
+    ///
+    /// `edited` must be of the same kind as the original (an edge proposal
+    /// cannot be edited into an object proposal).  The stored proposal keeps
+    /// the edited version so the review history shows what was applied.
+    pub fn accept_proposal_edited(&self, id: ProposalId, edited: ProposedChange) -> Result<()> {
+        let proposal = self.pending_proposal(id)?;
+        if proposal.change.kind() != edited.kind() {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Cannot edit a '{}' proposal into a '{}' change",
+                proposal.change.kind(),
+                edited.kind()
+            )));
+        }
+        self.apply_proposal(id, edited.clone(), Some(&edited))
+    }
+
+    /// Discard a pending proposal without touching the graph.
+    pub fn reject_proposal(&self, id: ProposalId) -> Result<()> {
+        self.storage
+            .resolve_proposal(id, ProposalStatus::Rejected, None)
+    }
+
+    /// Validate `change` like a one-item [`commit_staging`](Self::commit_staging)
+    /// against the main graph, then apply it and mark `id` accepted in one
+    /// transaction.  A property change naming a missing object fails with
+    /// [`UForgeError::NotFound`] and leaves the proposal pending.
+    fn apply_proposal(
+        &self,
+        id: ProposalId,
+        change: ProposedChange,
+        edited: Option<&ProposedChange>,
+    ) -> Result<()> {
+        let main = StagingLayer::default();
+        let base = self.staged(&main);
+        let mut layer = StagingLayer::new(format!("proposal {id}"));
+        match change {
+            ProposedChange::Object(meta) => {
+                layer.add_object(meta);
+            }
+            ProposedChange::Edge(edge) => layer.add_edge(edge),
+            ProposedChange::Property {
+                object_id,
+                key,
+                value,
+            } => {
+                let mut meta = base.get_object(object_id)?.ok_or_else(|| {
+                    UForgeError::NotFound(format!(
+                        "Proposed property change references missing object {object_id}"
+                    ))
+                })?;
+                meta.set_json_property(key, value);
+                layer.add_object(meta);
+            }
+        }
+
+        let (layer, commit) = self.prepare_commit(layer, &base)?;
+        self.storage
+            .accept_proposal(id, layer.objects(), layer.edges(), edited)?;
+
+        for meta in layer.objects() {
+            let description = meta.get_property("description").unwrap_or_default();
+            if commit.created.contains(&meta.id) && !description.trim().is_empty() {
+                self.add_text_chunk(meta.id, description, ChunkType::AiGenerated)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load `id` and ensure it is still awaiting review.
+    fn pending_proposal(&self, id: ProposalId) -> Result<Proposal> {
+        let proposal = self
+            .storage
+            .get_proposal(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Proposal {id} not found")))?;
+        if proposal.status != ProposalStatus::Pending {
+            return Err(UForgeError::Conflict(format!(
+                "Proposal {id} is already {}",
+                proposal.status.as_str()
+            )));
+        }
+        Ok(proposal)
+    }
+}
+
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EdgeType;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_pending_object_is_not_in_graph_until_accepted() {
+        let (graph, _tmp) = create_test_graph();
+
+        let meta = ObjectBuilder::character("Mysterious Stranger".to_string()).build();
+        let object_id = meta.id;
+        let pid = graph
+            .propose_change(ProposedChange::Object(meta), Some("agent"))
+            .unwrap();
+
+        assert!(graph.get_object(object_id).unwrap().is_none());
+        let pending = graph.list_proposals(Some(ProposalStatus::Pending)).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].source.as_deref(), Some("agent"));
+
+        graph.accept_proposal(pid).unwrap();
+        assert!(graph.get_object(object_id).unwrap().is_some());
+        assert!(graph
+            .list_proposals(Some(ProposalStatus::Pending))
+            .unwrap()
+            .is_empty());
+
+        let resolved = graph.get_proposal(pid).unwrap().unwrap();
+        assert_eq!(resolved.status, ProposalStatus::Accepted);
+        assert!(resolved.resolved_at.is_some());
+
+        // A resolved proposal cannot be accepted twice.
+        assert!(graph.accept_proposal(pid).is_err());
+    }
+
+    #[test]
+    fn test_reject_leaves_graph_untouched() {
+        let (graph, _tmp) = create_test_graph();
+        let a = ObjectBuilder::character("Alice".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let b = ObjectBuilder::character("Bob".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let pid = graph
+            .propose_change(
+                ProposedChange::Edge(Edge::new(a, b, EdgeType::new("betrayed"))),
+                None,
+            )
+            .unwrap();
+        graph.reject_proposal(pid).unwrap();
+
+        assert!(graph.get_relationships(a).unwrap().is_empty());
+        assert_eq!(
+            graph.get_proposal(pid).unwrap().unwrap().status,
+            ProposalStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn test_accept_racing_a_reject_applies_nothing() {
+        let (graph, _tmp) = create_test_graph();
+        let meta = ObjectBuilder::character("Doppelganger".to_string()).build();
+        let object_id = meta.id;
+        let change = ProposedChange::Object(meta);
+        let pid = graph.propose_change(change.clone(), None).unwrap();
+
+        // A reviewer loaded the pending proposal, then someone rejected it
+        // before the accept landed.
+        let loaded = graph.pending_proposal(pid).unwrap();
+        graph.reject_proposal(pid).unwrap();
+        let err = graph.apply_proposal(pid, loaded.change, None).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::Conflict);
+        assert!(graph.get_object(object_id).unwrap().is_none());
+        assert_eq!(
+            graph.get_proposal(pid).unwrap().unwrap().status,
+            ProposalStatus::Rejected
+        );
+        let err = graph.reject_proposal(pid).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::Conflict);
+    }
+
+    #[test]
+    fn test_edit_then_accept_property_change() {
+        let (graph, _tmp) = create_test_graph();
+        let id = ObjectBuilder::character("Gandalf".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let pid = graph
+            .propose_change(
+                ProposedChange::Property {
+                    object_id: id,
+                    key: "occupation".to_string(),
+                    value: serde_json::json!("Sorcerer"),
+                },
+                Some("extraction"),
+            )
+            .unwrap();
+
+        // Kind mismatch is refused.
+        let wrong_kind = ProposedChange::Object(ObjectBuilder::item("Staff".to_string()).build());
+        assert!(graph.accept_proposal_edited(pid, wrong_kind).is_err());
+
+        graph
+            .accept_proposal_edited(
+                pid,
+                ProposedChange::Property {
+                    object_id: id,
+                    key: "occupation".to_string(),
+                    value: serde_json::json!("Wizard"),
+                },
+            )
+            .unwrap();
+
+        let obj = graph.get_object(id).unwrap().unwrap();
+        assert_eq!(obj.get_property("occupation"), Some("Wizard".to_string()));
+
+        let stored = graph.get_proposal(pid).unwrap().unwrap();
+        match stored.change {
+            ProposedChange::Property { value, .. } => assert_eq!(value, "Wizard"),
+            other => panic!("unexpected change kind: {}", other.kind()),
+        }
+    }
+
+    #[test]
+    fn test_accept_runs_commit_checks_and_indexes_description() {
+        let (graph, _tmp) = create_test_graph();
+        let shire = ObjectBuilder::location("The Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let fellowship = ObjectBuilder::faction("Fellowship".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let mut events = graph.subscribe_events();
+
+        graph.set_strict_edges(true).unwrap();
+        let pid = graph
+            .propose_change(
+                ProposedChange::Edge(Edge::new(shire, fellowship, EdgeType::new("member_of"))),
+                None,
+            )
+            .unwrap();
+        let err = graph.accept_proposal(pid).unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::SchemaConflict);
+        assert_eq!(
+            graph.get_proposal(pid).unwrap().unwrap().status,
+            ProposalStatus::Pending
+        );
+
+        let meta = ObjectBuilder::character("Sam".to_string())
+            .with_description("Gardener from [[The Shire]].".to_string())
+            .build();
+        let sam = meta.id;
+        let pid = graph
+            .propose_change(ProposedChange::Object(meta), Some("agent"))
+            .unwrap();
+        graph.accept_proposal(pid).unwrap();
+
+        match events.try_recv().unwrap() {
+            crate::GraphEvent::ObjectCreated { object_id, .. } => assert_eq!(object_id, sam),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        let chunks = graph.get_text_chunks(sam).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(matches!(chunks[0].chunk_type, ChunkType::AiGenerated));
+        assert_eq!(graph.backlinks(shire).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_accepting_edge_with_missing_endpoint_fails() {
+        let (graph, _tmp) = create_test_graph();
+        let a = ObjectBuilder::character("Alice".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let pid = graph
+            .propose_change(
+                ProposedChange::Edge(Edge::new(a, ObjectId::new_v4(), EdgeType::new("knows"))),
+                None,
+            )
+            .unwrap();
+
+        assert!(graph.accept_proposal(pid).is_err());
+        // Still pending so the user can edit or reject it.
+        assert_eq!(
+            graph.get_proposal(pid).unwrap().unwrap().status,
+            ProposalStatus::Pending
+        );
+    }
+}
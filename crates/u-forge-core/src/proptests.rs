@@ -0,0 +1,143 @@
+//! Property-based round-trip tests for objects, edges and chunks.
+//!
+//! Generates arbitrary [`ObjectMetadata`], [`Edge`] and [`TextChunk`] values
+//! — any Unicode in names and tags, property values up to several kilobytes,
+//! odd keys — and checks that serde, SQLite storage and JSONL export hand
+//! back exactly what went in.  Compiled only for tests.
+
+use std::collections::HashMap;
+
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::export::{ExportFilter, ExportFormat};
+use crate::graph_data::NodeFilter;
+use crate::ingest::data::JsonEntry;
+use crate::progress::NoProgress;
+use crate::test_helpers::create_test_graph;
+use crate::types::{ChunkType, Edge, EdgeType, ObjectMetadata, TextChunk};
+
+/// Any non-control character, including astral-plane ones and RTL marks.
+const ANY_TEXT: &str = "\\PC{1,40}";
+
+fn property_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        "\\PC{0,4000}".prop_map(Value::String),
+        any::<i64>().prop_map(Value::from),
+        any::<bool>().prop_map(Value::Bool),
+        Just(Value::Null),
+        prop::collection::vec("\\PC{0,20}", 0..5).prop_map(Value::from),
+    ]
+}
+
+prop_compose! {
+    fn arb_object()(
+        // Never a schema type, so no defaults are filled in.
+        object_type in "t_[a-z_]{0,14}",
+        name in ANY_TEXT,
+        tags in prop::collection::vec("\\PC{0,24}", 0..8),
+        // Prefixed so they never collide with keys the graph reads itself
+        // (`name`, `tags`, coordinates, …).
+        properties in prop::collection::btree_map("p_\\PC{0,12}", property_value(), 0..8),
+    ) -> ObjectMetadata {
+        let mut object = ObjectMetadata::new(object_type, name);
+        let mut map: Map<String, Value> = properties.into_iter().collect();
+        map.insert("tags".to_string(), Value::from(tags));
+        object.properties = Value::Object(map);
+        object
+    }
+}
+
+prop_compose! {
+    fn arb_edge_parts()(
+        edge_type in "[a-z_]{1,20}",
+        weight in 0.0f32..=1.0,
+        metadata in prop::collection::hash_map("\\PC{1,12}", "\\PC{0,40}", 0..4),
+    ) -> (String, f32, HashMap<String, String>) {
+        (edge_type, weight, metadata)
+    }
+}
+
+fn config() -> ProptestConfig {
+    // Each case opens a fresh SQLite project.
+    ProptestConfig::with_cases(32)
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn prop_object_serde_round_trip(object in arb_object()) {
+        let json = serde_json::to_string(&object).unwrap();
+        let back: ObjectMetadata = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(back.id, object.id);
+        prop_assert_eq!(&back.name, &object.name);
+        prop_assert_eq!(&back.properties, &object.properties);
+        prop_assert_eq!(back.created_at, object.created_at);
+    }
+
+    #[test]
+    fn prop_storage_round_trip(
+        object in arb_object(),
+        other in arb_object(),
+        (edge_type, weight, metadata) in arb_edge_parts(),
+        content in "\\PC{1,2000}",
+    ) {
+        let (graph, _temp_dir) = create_test_graph();
+        let id = graph.add_object(object.clone()).unwrap();
+        let other_id = graph.add_object(other).unwrap();
+
+        // Straight from SQLite, not the read cache.
+        let stored = graph
+            .get_all_objects()
+            .unwrap()
+            .into_iter()
+            .find(|o| o.id == id)
+            .unwrap();
+        prop_assert_eq!(&stored.name, &object.name);
+        prop_assert_eq!(&stored.object_type, &object.object_type);
+        prop_assert_eq!(&stored.properties, &object.properties);
+        prop_assert_eq!(stored.created_at, object.created_at);
+
+        let mut edge = Edge::new(id, other_id, EdgeType::new(edge_type.as_str()));
+        edge.weight = weight;
+        edge.metadata = metadata.clone();
+        graph.add_edge(edge, true).unwrap();
+        let edges = graph.get_relationships(id).unwrap();
+        prop_assert_eq!(edges.len(), 1);
+        prop_assert_eq!(edges[0].edge_type.as_str(), edge_type.as_str());
+        prop_assert_eq!(edges[0].weight, weight);
+        prop_assert_eq!(&edges[0].metadata, &metadata);
+
+        let chunk = TextChunk::new(id, content.clone(), ChunkType::Imported);
+        graph.storage.upsert_chunk(chunk.clone()).unwrap();
+        let chunks = graph.get_text_chunks(id).unwrap();
+        prop_assert_eq!(chunks.len(), 1);
+        prop_assert_eq!(chunks[0].id, chunk.id);
+        prop_assert_eq!(&chunks[0].content, &content);
+        prop_assert_eq!(chunks[0].token_count, chunk.token_count);
+    }
+
+    #[test]
+    fn prop_export_round_trip(object in arb_object()) {
+        let (graph, _temp_dir) = create_test_graph();
+        graph.add_object(object.clone()).unwrap();
+
+        let filter = ExportFilter::new(NodeFilter::default());
+        let export = graph
+            .export_selection(&filter, ExportFormat::Json, &NoProgress)
+            .unwrap();
+        let jsonl = String::from_utf8(export.files[0].content.clone()).unwrap();
+        let entries: Vec<JsonEntry> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        prop_assert_eq!(entries.len(), 1);
+        let Some(JsonEntry::Node { node_type, mut properties, .. }) = entries.into_iter().next() else {
+            panic!("expected a node entry");
+        };
+        prop_assert_eq!(&node_type, &object.object_type);
+        prop_assert_eq!(properties.remove("name"), Some(Value::String(object.name.clone())));
+        prop_assert_eq!(Value::Object(properties), object.properties);
+    }
+}
@@ -0,0 +1,420 @@
+//! Quest dependencies — which quests the party can take on right now.
+//!
+//! Prerequisites are edges between `quest` objects:
+//!
+//! - [`REQUIRES_EDGE`] (`quest → prerequisite`): the quest unlocks once the
+//!   prerequisite is completed.  If the prerequisite failed, the quest can
+//!   no longer unlock.
+//! - [`BLOCKED_BY_EDGE`] (`quest → blocker`): the quest unlocks once the
+//!   blocker is resolved either way, completed or failed.
+//!
+//! [`KnowledgeGraph::quest_dependencies`] evaluates every quest against a
+//! [`QuestState`] — by default read from each quest's `status` property via
+//! [`KnowledgeGraph::quest_state`], but callers can mark quests done to ask
+//! "what opens up if they finish this?".  Quests come back in topological
+//! order, prerequisites first.  Prerequisite cycles are authoring errors:
+//! each is reported in [`QuestGraph::cycles`] and its open quests are
+//! [`QuestAvailability::InCycle`].
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Object type of quests.
+pub const QUEST_TYPE: &str = "quest";
+
+/// Edge type from a quest to a quest that must be completed first.
+pub const REQUIRES_EDGE: &str = "requires";
+
+/// Edge type from a quest to a quest that must be resolved first.
+pub const BLOCKED_BY_EDGE: &str = "blocked_by";
+
+/// Quest property holding its status (`active`, `completed`, …).
+pub const QUEST_STATUS_KEY: &str = "status";
+
+/// Quest `status` values that close a quest.
+pub(crate) const CLOSED_QUEST_STATUSES: [&str; 2] = ["completed", "failed"];
+
+/// Which quests are resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestState {
+    #[serde(default)]
+    pub completed: HashSet<ObjectId>,
+    #[serde(default)]
+    pub failed: HashSet<ObjectId>,
+}
+
+impl QuestState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `quest` completed.
+    pub fn completing(mut self, quest: ObjectId) -> Self {
+        self.failed.remove(&quest);
+        self.completed.insert(quest);
+        self
+    }
+
+    /// Mark `quest` failed.
+    pub fn failing(mut self, quest: ObjectId) -> Self {
+        self.completed.remove(&quest);
+        self.failed.insert(quest);
+        self
+    }
+
+    fn is_resolved(&self, quest: &ObjectId) -> bool {
+        self.completed.contains(quest) || self.failed.contains(quest)
+    }
+}
+
+/// Where a quest stands under a [`QuestState`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum QuestAvailability {
+    /// Every prerequisite is met.
+    Available,
+    /// Waiting on these prerequisites.
+    Locked { waiting_on: Vec<ObjectId> },
+    /// A required quest failed, so this one can never unlock.
+    Unreachable { failed: Vec<ObjectId> },
+    /// Part of a prerequisite cycle.
+    InCycle,
+    Completed,
+    Failed,
+}
+
+/// One quest with its prerequisites and availability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestNode {
+    pub id: ObjectId,
+    pub name: String,
+    /// Targets of its [`REQUIRES_EDGE`] edges.
+    pub requires: Vec<ObjectId>,
+    /// Targets of its [`BLOCKED_BY_EDGE`] edges.
+    pub blocked_by: Vec<ObjectId>,
+    pub availability: QuestAvailability,
+}
+
+/// The result of [`KnowledgeGraph::quest_dependencies`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuestGraph {
+    /// Every quest, prerequisites before the quests that need them; quests
+    /// in or behind a cycle come last, by name.
+    pub quests: Vec<QuestNode>,
+    /// Each prerequisite cycle, by quest name.
+    pub cycles: Vec<Vec<ObjectId>>,
+}
+
+impl QuestGraph {
+    /// Ids of the quests that are [`QuestAvailability::Available`].
+    pub fn available(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.quests
+            .iter()
+            .filter(|q| q.availability == QuestAvailability::Available)
+            .map(|q| q.id)
+    }
+}
+
+impl KnowledgeGraph {
+    /// Completed and failed quests according to their `status` property.
+    pub fn quest_state(&self) -> Result<QuestState> {
+        let mut state = QuestState::new();
+        for quest in self.quests()? {
+            match quest_status(&quest).as_deref() {
+                Some("completed") => {
+                    state.completed.insert(quest.id);
+                }
+                Some("failed") => {
+                    state.failed.insert(quest.id);
+                }
+                _ => {}
+            }
+        }
+        Ok(state)
+    }
+
+    /// Every quest with its availability under `state` (see the module
+    /// docs).  Prerequisite edges to objects that are not quests are
+    /// ignored.
+    pub fn quest_dependencies(&self, state: &QuestState) -> Result<QuestGraph> {
+        let quests = self.quests()?;
+        let ids: HashSet<ObjectId> = quests.iter().map(|q| q.id).collect();
+        let mut requires: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        let mut blocked_by: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+        for edge in self.get_all_edges()? {
+            if !ids.contains(&edge.from) || !ids.contains(&edge.to) {
+                continue;
+            }
+            let list = match edge.edge_type.as_str() {
+                REQUIRES_EDGE => requires.entry(edge.from).or_default(),
+                BLOCKED_BY_EDGE => blocked_by.entry(edge.from).or_default(),
+                _ => continue,
+            };
+            if !list.contains(&edge.to) {
+                list.push(edge.to);
+            }
+        }
+
+        let prerequisites = |id: &ObjectId| -> Vec<ObjectId> {
+            let mut all = requires.get(id).cloned().unwrap_or_default();
+            for blocker in blocked_by.get(id).into_iter().flatten() {
+                if !all.contains(blocker) {
+                    all.push(*blocker);
+                }
+            }
+            all
+        };
+        let edges: HashMap<ObjectId, Vec<ObjectId>> =
+            quests.iter().map(|q| (q.id, prerequisites(&q.id))).collect();
+        let cycles = find_cycles(&quests, &edges);
+        let in_cycle: HashSet<ObjectId> = cycles.iter().flatten().copied().collect();
+
+        let mut nodes = Vec::with_capacity(quests.len());
+        for id in topological_order(&quests, &edges) {
+            let quest = quests.iter().find(|q| q.id == id).expect("ordered id is a quest");
+            let requires = requires.remove(&id).unwrap_or_default();
+            let blocked_by = blocked_by.remove(&id).unwrap_or_default();
+            let availability = if state.completed.contains(&id) {
+                QuestAvailability::Completed
+            } else if state.failed.contains(&id) {
+                QuestAvailability::Failed
+            } else if in_cycle.contains(&id) {
+                QuestAvailability::InCycle
+            } else {
+                let failed: Vec<ObjectId> = requires
+                    .iter()
+                    .filter(|r| state.failed.contains(r))
+                    .copied()
+                    .collect();
+                let waiting_on: Vec<ObjectId> = requires
+                    .iter()
+                    .filter(|r| !state.is_resolved(r))
+                    .chain(blocked_by.iter().filter(|b| !state.is_resolved(b)))
+                    .copied()
+                    .collect();
+                if !failed.is_empty() {
+                    QuestAvailability::Unreachable { failed }
+                } else if !waiting_on.is_empty() {
+                    QuestAvailability::Locked { waiting_on }
+                } else {
+                    QuestAvailability::Available
+                }
+            };
+            nodes.push(QuestNode {
+                id,
+                name: quest.name.clone(),
+                requires,
+                blocked_by,
+                availability,
+            });
+        }
+        Ok(QuestGraph {
+            quests: nodes,
+            cycles,
+        })
+    }
+
+    /// Open quests whose prerequisites are all met under `state`, in
+    /// topological order.
+    pub fn get_available_quests(&self, state: &QuestState) -> Result<Vec<ObjectMetadata>> {
+        let graph = self.quest_dependencies(state)?;
+        let mut available = Vec::new();
+        for id in graph.available() {
+            if let Some(quest) = self.get_object(id)? {
+                available.push(quest);
+            }
+        }
+        Ok(available)
+    }
+
+    /// Every `quest` object, by name.
+    fn quests(&self) -> Result<Vec<ObjectMetadata>> {
+        let mut quests: Vec<ObjectMetadata> = self
+            .get_all_objects()?
+            .into_iter()
+            .filter(|o| o.object_type == QUEST_TYPE)
+            .collect();
+        quests.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.0.cmp(&b.id.0)));
+        Ok(quests)
+    }
+}
+
+/// A quest's `status`, lower-cased.
+pub(crate) fn quest_status(quest: &ObjectMetadata) -> Option<String> {
+    quest
+        .get_property(QUEST_STATUS_KEY)
+        .map(|s| s.to_lowercase())
+}
+
+/// Kahn's algorithm over `prerequisites`, picking the earliest quest in
+/// `quests` order among the ready ones.  Quests never freed (in or behind a
+/// cycle) follow in `quests` order.
+fn topological_order(
+    quests: &[ObjectMetadata],
+    prerequisites: &HashMap<ObjectId, Vec<ObjectId>>,
+) -> Vec<ObjectId> {
+    let mut pending: HashMap<ObjectId, usize> = quests
+        .iter()
+        .map(|q| (q.id, prerequisites[&q.id].len()))
+        .collect();
+    let mut order = Vec::with_capacity(quests.len());
+    let mut placed: HashSet<ObjectId> = HashSet::new();
+    while let Some(next) = quests
+        .iter()
+        .find(|q| !placed.contains(&q.id) && pending[&q.id] == 0)
+    {
+        placed.insert(next.id);
+        order.push(next.id);
+        for quest in quests {
+            if prerequisites[&quest.id].contains(&next.id) {
+                *pending.get_mut(&quest.id).expect("every quest is counted") -= 1;
+            }
+        }
+    }
+    order.extend(quests.iter().map(|q| q.id).filter(|id| !placed.contains(id)));
+    order
+}
+
+/// Strongly connected components of more than one quest, plus quests that
+/// require themselves (Tarjan's algorithm).
+fn find_cycles(
+    quests: &[ObjectMetadata],
+    prerequisites: &HashMap<ObjectId, Vec<ObjectId>>,
+) -> Vec<Vec<ObjectId>> {
+    struct Tarjan<'a> {
+        prerequisites: &'a HashMap<ObjectId, Vec<ObjectId>>,
+        index: HashMap<ObjectId, usize>,
+        low: HashMap<ObjectId, usize>,
+        stack: Vec<ObjectId>,
+        on_stack: HashSet<ObjectId>,
+        components: Vec<Vec<ObjectId>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, id: ObjectId) {
+            let n = self.index.len();
+            self.index.insert(id, n);
+            self.low.insert(id, n);
+            self.stack.push(id);
+            self.on_stack.insert(id);
+            for next in self.prerequisites[&id].clone() {
+                if !self.index.contains_key(&next) {
+                    self.visit(next);
+                    let low = self.low[&id].min(self.low[&next]);
+                    self.low.insert(id, low);
+                } else if self.on_stack.contains(&next) {
+                    let low = self.low[&id].min(self.index[&next]);
+                    self.low.insert(id, low);
+                }
+            }
+            if self.low[&id] == self.index[&id] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    component.push(member);
+                    if member == id {
+                        break;
+                    }
+                }
+                let self_loop = self.prerequisites[&id].contains(&id);
+                if component.len() > 1 || self_loop {
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        prerequisites,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for quest in quests {
+        if !tarjan.index.contains_key(&quest.id) {
+            tarjan.visit(quest.id);
+        }
+    }
+    let position: HashMap<ObjectId, usize> =
+        quests.iter().enumerate().map(|(i, q)| (q.id, i)).collect();
+    let mut cycles = tarjan.components;
+    for cycle in &mut cycles {
+        cycle.sort_by_key(|id| position[id]);
+    }
+    cycles.sort_by_key(|c| position[&c[0]]);
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_available_quests_and_cycles() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::custom(QUEST_TYPE.to_string(), name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (map, vault, heist, escape) = (
+            add("Find the Map"),
+            add("Open the Vault"),
+            add("The Heist"),
+            add("Escape"),
+        );
+        let (loop_a, loop_b) = (add("Loop A"), add("Loop B"));
+        graph.connect_objects_str(vault, map, REQUIRES_EDGE).unwrap();
+        graph.connect_objects_str(heist, vault, REQUIRES_EDGE).unwrap();
+        graph
+            .connect_objects_str(escape, heist, BLOCKED_BY_EDGE)
+            .unwrap();
+        graph.connect_objects_str(loop_a, loop_b, REQUIRES_EDGE).unwrap();
+        graph.connect_objects_str(loop_b, loop_a, REQUIRES_EDGE).unwrap();
+
+        let names = |state: &QuestState| -> Vec<String> {
+            graph
+                .get_available_quests(state)
+                .unwrap()
+                .into_iter()
+                .map(|q| q.name)
+                .collect()
+        };
+        assert_eq!(names(&QuestState::new()), vec!["Find the Map"]);
+
+        // Read from status properties.
+        let mut quest = graph.get_object(map).unwrap().unwrap();
+        quest.set_property("status".to_string(), "Completed".to_string());
+        graph.update_object(quest).unwrap();
+        let state = graph.quest_state().unwrap();
+        assert_eq!(names(&state), vec!["Open the Vault"]);
+
+        // Failing the vault strands the heist; escape only needs it resolved.
+        let state = state.failing(vault);
+        let deps = graph.quest_dependencies(&state).unwrap();
+        let heist_node = deps.quests.iter().find(|q| q.id == heist).unwrap();
+        assert_eq!(
+            heist_node.availability,
+            QuestAvailability::Unreachable {
+                failed: vec![vault]
+            }
+        );
+        let state = state.failing(heist);
+        assert_eq!(names(&state), vec!["Escape"]);
+
+        let order: Vec<ObjectId> = deps.quests.iter().map(|q| q.id).collect();
+        assert_eq!(&order[..4], &[map, vault, heist, escape]);
+        assert_eq!(deps.cycles, vec![vec![loop_a, loop_b]]);
+        let loop_node = deps.quests.iter().find(|q| q.id == loop_a).unwrap();
+        assert_eq!(loop_node.availability, QuestAvailability::InCycle);
+    }
+}
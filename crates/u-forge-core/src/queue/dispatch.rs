@@ -6,6 +6,7 @@ use anyhow::{anyhow, Result};
 use tokio::sync::{mpsc, oneshot};
 use tracing::instrument;
 
+use crate::error::UForgeError;
 use crate::lemonade::{ChatCompletionResponse, ChatRequest, KokoroVoice, LemonadeChatProvider, RerankDocument, StreamToken};
 
 use super::jobs::{EmbedJob, GenerateJob, RerankJob, SynthesizeJob, TranscribeJob, WorkQueue};
@@ -14,7 +15,7 @@ use super::weighted::WeightedEmbedDispatcher;
 // ── Public queue state exposed via QueueStats ─────────────────────────────────
 
 /// Snapshot of the queue's current pending job counts.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct QueueStats {
     /// Jobs waiting to be picked up by an embedding worker.
     pub pending_embeddings: usize,
@@ -75,11 +76,13 @@ impl InferenceQueue {
     #[instrument(skip(self, text), fields(text_len, pending_jobs, selected_worker_id, duration_us))]
     pub async fn embed(&self, text: impl Into<String>) -> Result<Vec<f32>> {
         if self.embedding_workers == 0 {
-            return Err(anyhow!(
+            return Err(UForgeError::EmbeddingUnavailable(
                 "InferenceQueue: no embedding-capable provider is registered. \
                  Build an embedding provider with ProviderFactory::build() and register \
                  it via InferenceQueueBuilder::with_provider() before calling embed()."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         let text = text.into();
@@ -105,9 +108,10 @@ impl InferenceQueue {
     /// returned in input order.
     pub async fn embed_many(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
         if self.embedding_workers == 0 {
-            return Err(anyhow!(
-                "InferenceQueue: no embedding-capable device is registered"
-            ));
+            return Err(UForgeError::EmbeddingUnavailable(
+                "InferenceQueue: no embedding-capable device is registered".to_string(),
+            )
+            .into());
         }
 
         use futures::{StreamExt, TryStreamExt};
@@ -146,11 +150,13 @@ impl InferenceQueue {
         filename: impl Into<String>,
     ) -> Result<String> {
         if self.transcription_workers == 0 {
-            return Err(anyhow!(
+            return Err(UForgeError::InferenceUnavailable(
                 "InferenceQueue: no transcription-capable provider is registered. \
                  Build a transcription provider with ProviderFactory::build() and register \
                  it via InferenceQueueBuilder::with_provider() before calling transcribe()."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         let filename = filename.into();
@@ -190,10 +196,12 @@ impl InferenceQueue {
         voice: Option<KokoroVoice>,
     ) -> Result<Vec<u8>> {
         if self.tts_workers == 0 {
-            return Err(anyhow!(
+            return Err(UForgeError::InferenceUnavailable(
                 "InferenceQueue: no TTS-capable device is registered. \
                  Add a CpuDevice::new() to the builder before calling synthesize()."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         let text = text.into();
@@ -227,11 +235,13 @@ impl InferenceQueue {
     #[instrument(skip(self, request), fields(model, n_messages))]
     pub async fn generate(&self, request: ChatRequest) -> Result<ChatCompletionResponse> {
         if self.llm_workers == 0 {
-            return Err(anyhow!(
+            return Err(UForgeError::InferenceUnavailable(
                 "InferenceQueue: no LLM-capable provider is registered. \
                  Build a TextGeneration provider with ProviderFactory::build() and register \
                  it via InferenceQueueBuilder::with_provider() before calling generate()."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         tracing::Span::current().record("n_messages", request.messages.len());
@@ -258,9 +268,10 @@ impl InferenceQueue {
     /// Stream-level errors are sent as `Err(_)` items through the receiver.
     pub fn generate_stream(&self, request: ChatRequest) -> Result<mpsc::Receiver<Result<StreamToken>>> {
         if self.llm_workers == 0 {
-            return Err(anyhow!(
-                "InferenceQueue: no LLM-capable device is registered."
-            ));
+            return Err(UForgeError::InferenceUnavailable(
+                "InferenceQueue: no LLM-capable device is registered.".to_string(),
+            )
+            .into());
         }
         let provider = self
             .chat_providers
@@ -304,11 +315,13 @@ impl InferenceQueue {
         top_n: Option<usize>,
     ) -> Result<Vec<RerankDocument>> {
         if self.reranking_workers == 0 {
-            return Err(anyhow!(
+            return Err(UForgeError::InferenceUnavailable(
                 "InferenceQueue: no reranking-capable device is registered. \
                  Ensure a reranker model is available in the Lemonade registry \
                  and add it via the builder before calling rerank()."
-            ));
+                    .to_string(),
+            )
+            .into());
         }
 
         let query = query.into();
@@ -372,6 +385,11 @@ impl InferenceQueue {
         self.embedding_workers
     }
 
+    /// Names of the registered embedding workers (`"<recipe>/<model>"`).
+    pub fn embedding_worker_names(&self) -> Vec<String> {
+        self.embed_dispatcher.worker_names()
+    }
+
     /// Number of background worker tasks registered for transcription.
     pub fn transcription_worker_count(&self) -> usize {
         self.transcription_workers
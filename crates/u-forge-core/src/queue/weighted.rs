@@ -167,6 +167,11 @@ impl WeightedEmbedDispatcher {
         target.queue.try_pop()
     }
 
+    /// Names of the registered workers, in registration order.
+    pub(super) fn worker_names(&self) -> Vec<String> {
+        self.workers.iter().map(|w| w.name.clone()).collect()
+    }
+
     /// Total pending embedding jobs across all worker queues.
     pub(super) fn pending(&self) -> usize {
         self.workers.iter().map(|w| w.queue.pending()).sum()
@@ -10,11 +10,13 @@
 //! ```text
 //! search_hybrid(graph, queue, ...)
 //!     → Vec<NodeSearchResult>
-//!     → format_search_context(&results)   // renders nodes into a text block
+//!     → build_context(query, &results, &config)   // token-budgeted text block
+//!     → RagContext::from(assembled)
 //!     → build_rag_messages(system, ctx, history, max_turns, query)
 //!     → InferenceQueue::generate(ChatRequest::new(messages))
 //! ```
 
+use crate::context_builder::{build_context, ContextBuilderConfig};
 use crate::lemonade::ChatMessage;
 use crate::search::NodeSearchResult;
 
@@ -22,12 +24,13 @@ use crate::search::NodeSearchResult;
 
 /// Formatted context block ready to be injected into an LLM system prompt.
 ///
-/// Produced by [`format_search_context`] from a slice of [`NodeSearchResult`]s.
+/// Produced by [`format_search_context`] or converted from an
+/// [`AssembledContext`](crate::context_builder::AssembledContext).
 pub struct RagContext {
     /// The rendered text block, suitable for embedding in a system message.
     ///
-    /// Contains each node's name, type, chunk text, and connected node names,
-    /// separated by clear delimiters.
+    /// Contains each node's name, type, connected node names, and `[n]`-marked
+    /// chunk text, separated by clear delimiters.
     pub formatted_context: String,
 
     /// Number of source nodes that contributed to the context.
@@ -38,59 +41,29 @@ pub struct RagContext {
 
 /// Render a slice of [`NodeSearchResult`] items into an LLM-ready context block.
 ///
-/// Each node is formatted as:
+/// This is [`build_context`] with no question and no budget: every distinct
+/// chunk is kept, grouped under its node's card:
 ///
 /// ```text
 /// ### <name> (<type>)
-/// <chunk text 1>
-/// <chunk text 2>
 /// Connected: <node a>, <node b>, …
+/// [1] <chunk text 1>
+/// [2] <chunk text 2>
 /// ```
 ///
-/// The resulting [`RagContext`] is intended for injection into a system message
-/// via [`build_rag_messages`].  When `results` is empty, `formatted_context`
-/// is an empty string and `source_count` is 0.
+/// Call [`build_context`] directly to fit a token budget or rank chunks
+/// against the question.  The resulting [`RagContext`] is intended for
+/// injection into a system message via [`build_rag_messages`].  When
+/// `results` is empty, `formatted_context` is an empty string and
+/// `source_count` is 0.
 pub fn format_search_context(results: &[NodeSearchResult]) -> RagContext {
-    if results.is_empty() {
-        return RagContext {
-            formatted_context: String::new(),
-            source_count: 0,
-        };
-    }
-
-    let mut parts = Vec::with_capacity(results.len());
-
-    for result in results {
-        let node = &result.node;
-        let mut node_text = format!("### {} ({})\n", node.name, node.object_type);
-
-        for chunk in &result.chunks {
-            node_text.push_str(&chunk.content);
-            node_text.push('\n');
-        }
-
-        // Collect names of directly connected nodes (de-duplicated, sorted for
-        // deterministic output).
-        if !result.connected_node_names.is_empty() {
-            let mut connected: Vec<&str> = result
-                .connected_node_names
-                .values()
-                .map(|c| c.name.as_str())
-                .collect();
-            connected.sort_unstable();
-            connected.dedup();
-            node_text.push_str("Connected: ");
-            node_text.push_str(&connected.join(", "));
-            node_text.push('\n');
-        }
-
-        parts.push(node_text);
-    }
-
-    RagContext {
-        formatted_context: parts.join("\n"),
-        source_count: results.len(),
-    }
+    let config = ContextBuilderConfig {
+        max_tokens: usize::MAX,
+        recency_weight: 0.0,
+        max_chunks_per_object: usize::MAX,
+        ..ContextBuilderConfig::default()
+    };
+    build_context("", results, &config).into()
 }
 
 /// Assemble the full [`ChatMessage`] array for a single RAG conversation turn.
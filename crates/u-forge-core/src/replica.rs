@@ -0,0 +1,176 @@
+//! Following a live project from a second process.
+//!
+//! [`KnowledgeGraph::open_secondary`] opens a project read-only next to the
+//! primary that owns it.  Reads always see the primary's latest committed
+//! data; what a secondary has to refresh is its own cache of compiled
+//! schemas.  [`KnowledgeGraph::catch_up`] does that on demand, and
+//! [`KnowledgeGraph::follow_primary`] polls in the background, catching up
+//! and broadcasting a [`PrimaryUpdate`] whenever the primary has committed.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::error::Result;
+use crate::graph::KnowledgeGraphStorage;
+use crate::KnowledgeGraph;
+
+/// Poll interval used by [`KnowledgeGraph::follow_primary`] when none is
+/// given.
+pub const DEFAULT_FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The primary committed changes and the secondary has caught up with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimaryUpdate {
+    /// SQLite `data_version` observed after the change.
+    pub data_version: i64,
+}
+
+/// A running background follow.  Dropping it (or calling
+/// [`stop`](Self::stop)) ends it.
+pub struct PrimaryFollower {
+    sender: broadcast::Sender<PrimaryUpdate>,
+    task: JoinHandle<()>,
+}
+
+impl PrimaryFollower {
+    /// Subscribe to updates.  Receivers that fall more than 16 updates behind
+    /// get [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<PrimaryUpdate> {
+        self.sender.subscribe()
+    }
+
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for PrimaryFollower {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl KnowledgeGraph {
+    /// Open the project at `db_path` read-only alongside its primary.  See
+    /// [`KnowledgeGraphStorage::open_secondary`].
+    pub fn open_secondary<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        let storage = Arc::new(KnowledgeGraphStorage::open_secondary(db_path.as_ref())?);
+        Self::from_parts(storage)
+    }
+
+    /// Drop cached schemas so the next lookup reads what the primary last
+    /// saved.  Returns the current `data_version`.
+    pub fn catch_up(&self) -> Result<i64> {
+        self.schema_manager.clear_cache();
+        self.storage.data_version()
+    }
+
+    /// Catch up every `interval` (default [`DEFAULT_FOLLOW_INTERVAL`]) on
+    /// which the primary has committed since the last check.  Must be called
+    /// from within a Tokio runtime.
+    pub fn follow_primary(&self, interval: Option<Duration>) -> PrimaryFollower {
+        let storage = self.storage.clone();
+        let schema_manager = self.schema_manager.clone();
+        let (sender, _) = broadcast::channel(16);
+        let updates = sender.clone();
+        let mut ticker = tokio::time::interval(interval.unwrap_or(DEFAULT_FOLLOW_INTERVAL));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let task = tokio::spawn(async move {
+            let mut last = storage.data_version().ok();
+            loop {
+                ticker.tick().await;
+                let version = match storage.data_version() {
+                    Ok(version) => version,
+                    Err(e) => {
+                        warn!(error = %e, "Failed to poll primary for changes");
+                        continue;
+                    }
+                };
+                if last == Some(version) {
+                    continue;
+                }
+                last = Some(version);
+                schema_manager.clear_cache();
+                // No subscribers is fine: the cache was still refreshed.
+                let _ = updates.send(PrimaryUpdate {
+                    data_version: version,
+                });
+            }
+        });
+        PrimaryFollower { sender, task }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::schema::ObjectTypeSchema;
+    use crate::types::ObjectMetadata;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_secondary_follows_primary() {
+        let temp_dir = TempDir::new().unwrap();
+        let err = KnowledgeGraph::open_secondary(temp_dir.path())
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let primary = KnowledgeGraph::new(temp_dir.path()).unwrap();
+        let secondary = KnowledgeGraph::open_secondary(temp_dir.path()).unwrap();
+        assert!(secondary.storage.is_read_only());
+        assert!(!primary.storage.is_read_only());
+
+        let id = primary
+            .add_object(ObjectMetadata::new("npc".to_string(), "Toblen".to_string()))
+            .unwrap();
+        assert_eq!(secondary.get_object(id).unwrap().unwrap().name, "Toblen");
+        assert!(secondary
+            .add_object(ObjectMetadata::new("npc".to_string(), "Nope".to_string()))
+            .is_err());
+
+        // Cache the current schema on the secondary so catching up matters.
+        primary
+            .register_object_type(
+                "inn",
+                ObjectTypeSchema::new("inn".to_string(), "An inn".to_string()),
+            )
+            .await
+            .unwrap();
+        secondary
+            .get_schema_manager()
+            .load_schema("default")
+            .await
+            .unwrap();
+
+        let follower = secondary.follow_primary(Some(Duration::from_millis(20)));
+        let mut updates = follower.subscribe();
+        // Let the follower record the starting version before writing.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        primary
+            .register_object_type(
+                "tavern",
+                ObjectTypeSchema::new("tavern".to_string(), "A tavern".to_string()),
+            )
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .expect("follower saw no update")
+            .unwrap();
+        assert!(secondary
+            .get_schema_manager()
+            .load_schema("default")
+            .await
+            .unwrap()
+            .object_types
+            .contains_key("tavern"));
+        follower.stop();
+    }
+}
@@ -0,0 +1,300 @@
+//! Reveal tracking — what each player has actually been told.
+//!
+//! [`KnowledgeGraph::reveal`] records that an object, or one chunk of it
+//! (a single note, a paragraph of lore), was shown to some players,
+//! optionally during a session.  The log answers "have I told them about
+//! the cult yet?" ([`KnowledgeGraph::revealed_to`]) and feeds the player
+//! view: [`KnowledgeGraph::revealed_objects`] is everything a player has
+//! been shown, redacted for them.  [`KnowledgeGraph::deliver_handouts`]
+//! prints handouts and records them as revealed in one call.
+//!
+//! Reveals are stored in the `reveals` table; revealing the same thing
+//! twice keeps both records, so the log shows when it came up again.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{Result, UForgeError};
+use crate::handout::{HandoutExport, HandoutStyle};
+use crate::progress::NoProgress;
+use crate::types::{ChunkId, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// What was revealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum RevealTarget {
+    Object(ObjectId),
+    /// One chunk; the reveal is also filed under the chunk's object.
+    Chunk(ChunkId),
+}
+
+impl From<ObjectId> for RevealTarget {
+    fn from(id: ObjectId) -> Self {
+        Self::Object(id)
+    }
+}
+
+impl From<ChunkId> for RevealTarget {
+    fn from(id: ChunkId) -> Self {
+        Self::Chunk(id)
+    }
+}
+
+/// One thing shown to one player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reveal {
+    pub id: Uuid,
+    pub player_id: ObjectId,
+    pub object_id: ObjectId,
+    /// Set when only this chunk of the object was revealed.
+    pub chunk_id: Option<ChunkId>,
+    pub session_id: Option<ObjectId>,
+    pub revealed_at: DateTime<Utc>,
+}
+
+impl KnowledgeGraph {
+    /// Record that `target` was shown to each of `players`, during
+    /// `session` when given.  Returns one [`Reveal`] per player.
+    ///
+    /// Fails with [`UForgeError::NotFound`] when the target, a player or
+    /// the session does not exist, and [`UForgeError::ValidationFailed`]
+    /// when `players` is empty or names something that is not a player.
+    pub fn reveal(
+        &self,
+        target: impl Into<RevealTarget>,
+        players: &[ObjectId],
+        session: Option<ObjectId>,
+    ) -> Result<Vec<Reveal>> {
+        if players.is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "A reveal needs at least one player".to_string(),
+            ));
+        }
+        for &player in players {
+            self.require_player(player)?;
+        }
+        if let Some(session) = session {
+            if self.get_object(session)?.is_none() {
+                return Err(UForgeError::NotFound(format!("Unknown session {session}")));
+            }
+        }
+        let (object_id, chunk_id) = match target.into() {
+            RevealTarget::Object(id) => {
+                if self.get_object(id)?.is_none() {
+                    return Err(UForgeError::NotFound(format!("Unknown object {id}")));
+                }
+                (id, None)
+            }
+            RevealTarget::Chunk(chunk) => {
+                let object_id = self
+                    .storage
+                    .chunk_object_id(chunk)?
+                    .ok_or_else(|| UForgeError::NotFound(format!("Unknown chunk {chunk}")))?;
+                (object_id, Some(chunk))
+            }
+        };
+        self.storage
+            .insert_reveals(object_id, chunk_id, players, session)
+    }
+
+    /// Everything shown to `player`, newest first.
+    pub fn reveals_to_player(&self, player: ObjectId) -> Result<Vec<Reveal>> {
+        self.storage.list_reveals(Some(player), None, None)
+    }
+
+    /// Every reveal of `object` or its chunks, newest first.
+    pub fn reveals_of(&self, object: ObjectId) -> Result<Vec<Reveal>> {
+        self.storage.list_reveals(None, Some(object), None)
+    }
+
+    /// Everything revealed during `session`, newest first.
+    pub fn reveals_in_session(&self, session: ObjectId) -> Result<Vec<Reveal>> {
+        self.storage.list_reveals(None, None, Some(session))
+    }
+
+    /// Players who have been shown `object` or any chunk of it, in the
+    /// order they first saw it.
+    pub fn revealed_to(&self, object: ObjectId) -> Result<Vec<ObjectId>> {
+        let mut seen = HashSet::new();
+        let mut players: Vec<ObjectId> = self
+            .reveals_of(object)?
+            .into_iter()
+            .rev()
+            .map(|r| r.player_id)
+            .collect();
+        players.retain(|p| seen.insert(*p));
+        Ok(players)
+    }
+
+    /// Whether `player` has been shown the whole of `object`.  A reveal of
+    /// one chunk does not count; see
+    /// [`revealed_chunks`](Self::revealed_chunks).
+    pub fn is_revealed_to(&self, object: ObjectId, player: ObjectId) -> Result<bool> {
+        Ok(self
+            .storage
+            .list_reveals(Some(player), Some(object), None)?
+            .iter()
+            .any(|r| r.chunk_id.is_none()))
+    }
+
+    /// Chunks of `object` shown to `player` on their own.
+    pub fn revealed_chunks(&self, object: ObjectId, player: ObjectId) -> Result<Vec<ChunkId>> {
+        let mut seen = HashSet::new();
+        Ok(self
+            .storage
+            .list_reveals(Some(player), Some(object), None)?
+            .into_iter()
+            .filter_map(|r| r.chunk_id)
+            .filter(|c| seen.insert(*c))
+            .collect())
+    }
+
+    /// Objects `player` has been shown in whole or part, most recently
+    /// revealed first, each redacted as
+    /// [`redact_for_player`](Self::redact_for_player) would.  Objects since
+    /// hidden from the player are left out.
+    pub fn revealed_objects(&self, player: ObjectId) -> Result<Vec<ObjectMetadata>> {
+        let mut seen = HashSet::new();
+        let mut objects = Vec::new();
+        for reveal in self.reveals_to_player(player)? {
+            if !seen.insert(reveal.object_id) {
+                continue;
+            }
+            let Some(object) = self.get_object(reveal.object_id)? else {
+                continue;
+            };
+            objects.extend(self.redact_for_player(&object, player)?);
+        }
+        Ok(objects)
+    }
+
+    /// Delete reveal `id`, e.g. one recorded by mistake.  Returns whether it
+    /// existed.
+    pub fn retract_reveal(&self, id: Uuid) -> Result<bool> {
+        self.storage.delete_reveal(id)
+    }
+
+    /// Print handouts for `ids` and record each printed object as revealed
+    /// to `players`.  Objects that some recipient may not see (see
+    /// [`is_visible_to_player`](Self::is_visible_to_player)) are withheld
+    /// from the whole delivery.
+    pub fn deliver_handouts(
+        &self,
+        ids: &[ObjectId],
+        style: &HandoutStyle,
+        players: &[ObjectId],
+        session: Option<ObjectId>,
+    ) -> Result<HandoutExport> {
+        let mut deliverable = Vec::new();
+        let mut withheld = Vec::new();
+        for &id in ids {
+            let visible = match self.get_object(id)? {
+                Some(object) => {
+                    let mut visible = true;
+                    for &player in players {
+                        visible &= self.is_visible_to_player(&object, player)?;
+                    }
+                    visible
+                }
+                None => false,
+            };
+            if visible {
+                deliverable.push(id);
+            } else {
+                withheld.push(id);
+            }
+        }
+
+        let mut export = self.export_handouts(&deliverable, style, &NoProgress)?;
+        for &id in &export.included {
+            self.reveal(id, players, session)?;
+        }
+        withheld.append(&mut export.withheld);
+        export.withheld = withheld;
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ChunkType;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_reveal_objects_and_chunks() {
+        let (graph, _temp_dir) = create_test_graph();
+        let sarah = graph.create_player("Sarah").unwrap();
+        let tom = graph.create_player("Tom").unwrap();
+        let session = ObjectBuilder::session("Session 3".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let cult = ObjectBuilder::custom("faction".to_string(), "Cult of the Dragon".to_string())
+            .with_property("gm_notes".to_string(), "Led by Severin".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let chunk_id = graph
+            .add_text_chunk(
+                cult,
+                "They meet at the old mill.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap()[0];
+
+        assert!(graph.revealed_to(cult).unwrap().is_empty());
+        graph.reveal(chunk_id, &[tom], Some(session)).unwrap();
+        assert!(!graph.is_revealed_to(cult, tom).unwrap());
+        assert_eq!(graph.revealed_chunks(cult, tom).unwrap(), vec![chunk_id]);
+
+        graph.reveal(cult, &[sarah, tom], Some(session)).unwrap();
+        assert!(graph.is_revealed_to(cult, sarah).unwrap());
+        assert_eq!(graph.revealed_to(cult).unwrap(), vec![tom, sarah]);
+        assert_eq!(graph.reveals_in_session(session).unwrap().len(), 3);
+
+        let view = graph.revealed_objects(sarah).unwrap();
+        assert_eq!(view.len(), 1);
+        assert!(view[0].get_property("gm_notes").is_none());
+
+        let err = graph.reveal(cult, &[cult], None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+        let err = graph.reveal(ChunkId::new_v4(), &[sarah], None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let first = graph.reveals_to_player(tom).unwrap().pop().unwrap();
+        assert!(graph.retract_reveal(first.id).unwrap());
+        assert!(graph.revealed_chunks(cult, tom).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deliver_handouts_records_reveals() {
+        let (graph, _temp_dir) = create_test_graph();
+        let sarah = graph.create_player("Sarah").unwrap();
+        let tom = graph.create_player("Tom").unwrap();
+        let map = ObjectBuilder::custom("item".to_string(), "Treasure map".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let letter = ObjectBuilder::custom("item".to_string(), "Private letter".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.set_visible_to(letter, &[sarah]).unwrap();
+
+        let export = graph
+            .deliver_handouts(
+                &[map, letter],
+                &HandoutStyle::default(),
+                &[sarah, tom],
+                None,
+            )
+            .unwrap();
+        assert_eq!(export.included, vec![map]);
+        assert_eq!(export.withheld, vec![letter]);
+        assert!(graph.is_revealed_to(map, tom).unwrap());
+        assert!(!graph.is_revealed_to(letter, sarah).unwrap());
+    }
+}
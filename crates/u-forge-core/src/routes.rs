@@ -0,0 +1,444 @@
+//! Travel routes between locations.
+//!
+//! Any edge can be a leg of travel once it carries a [`TravelLeg`] in its
+//! metadata: a `distance` (in whatever unit the campaign uses — miles,
+//! leagues, hexes), an optional `difficulty` multiplier for rough terrain,
+//! the travel `modes` it allows (empty means any; a sea lane might allow
+//! only `"ship"`), and `one_way` for rivers and portals.  Legs are walked in
+//! both directions unless one-way.
+//!
+//! [`KnowledgeGraph::compute_route`] runs Dijkstra over those legs for a
+//! [`TravelMode`] and returns the fastest [`Route`]: the path, each leg's
+//! travel time, and the day the party reaches each waypoint.  Times are in
+//! travel days at the mode's pace; [`Route::arrival`] turns a departure date
+//! into an arrival date in the world calendar.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use serde::Serialize;
+
+use crate::calendar::{WorldCalendar, WorldDate};
+use crate::error::{Result, UForgeError};
+use crate::types::{Edge, EdgeChanges, EdgeId, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Edge metadata key: length of the leg.
+pub const DISTANCE_KEY: &str = "distance";
+
+/// Edge metadata key: time multiplier for terrain (1 = open road).
+pub const DIFFICULTY_KEY: &str = "difficulty";
+
+/// Edge metadata key: comma-separated travel modes the leg allows.
+pub const MODES_KEY: &str = "modes";
+
+/// Edge metadata key: `"true"` when the leg only runs from source to target.
+pub const ONE_WAY_KEY: &str = "one_way";
+
+/// Travel annotations of one edge.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TravelLeg {
+    pub distance: f64,
+    pub difficulty: f64,
+    /// Allowed [`TravelMode::name`]s; empty allows every mode.
+    pub modes: Vec<String>,
+    pub one_way: bool,
+}
+
+impl TravelLeg {
+    pub fn new(distance: f64) -> Self {
+        Self {
+            distance,
+            difficulty: 1.0,
+            modes: Vec::new(),
+            one_way: false,
+        }
+    }
+
+    pub fn with_difficulty(mut self, difficulty: f64) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: impl Into<String>) -> Self {
+        self.modes.push(mode.into());
+        self
+    }
+
+    pub fn one_way(mut self) -> Self {
+        self.one_way = true;
+        self
+    }
+
+    /// The leg stored on `edge`, if it has a usable `distance`.
+    pub fn from_edge(edge: &Edge) -> Option<Self> {
+        let distance: f64 = edge.metadata.get(DISTANCE_KEY)?.trim().parse().ok()?;
+        if !distance.is_finite() || distance < 0.0 {
+            return None;
+        }
+        let difficulty = edge
+            .metadata
+            .get(DIFFICULTY_KEY)
+            .and_then(|d| d.trim().parse::<f64>().ok())
+            .filter(|d| d.is_finite() && *d > 0.0)
+            .unwrap_or(1.0);
+        let modes = edge
+            .metadata
+            .get(MODES_KEY)
+            .map(|m| {
+                m.split(',')
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .map(str::to_lowercase)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let one_way = edge
+            .metadata
+            .get(ONE_WAY_KEY)
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+        Some(Self {
+            distance,
+            difficulty,
+            modes,
+            one_way,
+        })
+    }
+
+    /// Whether `mode` may use this leg.
+    pub fn allows(&self, mode: &TravelMode) -> bool {
+        self.modes.is_empty()
+            || self
+                .modes
+                .iter()
+                .any(|m| m.eq_ignore_ascii_case(&mode.name))
+    }
+
+    fn write_to(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert(DISTANCE_KEY.to_string(), self.distance.to_string());
+        metadata.insert(DIFFICULTY_KEY.to_string(), self.difficulty.to_string());
+        if self.modes.is_empty() {
+            metadata.remove(MODES_KEY);
+        } else {
+            metadata.insert(MODES_KEY.to_string(), self.modes.join(","));
+        }
+        if self.one_way {
+            metadata.insert(ONE_WAY_KEY.to_string(), "true".to_string());
+        } else {
+            metadata.remove(ONE_WAY_KEY);
+        }
+    }
+}
+
+/// How the party travels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TravelMode {
+    /// Matched against [`TravelLeg::modes`], case-insensitively.
+    pub name: String,
+    /// Distance covered per travel day on difficulty-1 terrain.
+    pub distance_per_day: f64,
+}
+
+impl TravelMode {
+    pub fn new(name: impl Into<String>, distance_per_day: f64) -> Self {
+        Self {
+            name: name.into(),
+            distance_per_day,
+        }
+    }
+
+    /// 24 miles a day.
+    pub fn on_foot() -> Self {
+        Self::new("foot", 24.0)
+    }
+
+    /// 40 miles a day.
+    pub fn mounted() -> Self {
+        Self::new("mounted", 40.0)
+    }
+
+    /// 72 miles a day.
+    pub fn by_ship() -> Self {
+        Self::new("ship", 72.0)
+    }
+}
+
+/// One leg of a [`Route`], in travel order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteLeg {
+    pub edge_id: EdgeId,
+    pub from: ObjectId,
+    pub to: ObjectId,
+    pub distance: f64,
+    /// Travel days for this leg.
+    pub days: f64,
+}
+
+/// A stop on a [`Route`], start and destination included.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Waypoint {
+    pub object_id: ObjectId,
+    pub name: String,
+    /// Travel days from the start when the party arrives.
+    pub day: f64,
+}
+
+/// The fastest route found by [`KnowledgeGraph::compute_route`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Route {
+    pub mode: TravelMode,
+    pub legs: Vec<RouteLeg>,
+    pub waypoints: Vec<Waypoint>,
+    pub total_distance: f64,
+    pub total_days: f64,
+}
+
+impl Route {
+    /// Object ids from start to destination.
+    pub fn path(&self) -> Vec<ObjectId> {
+        self.waypoints.iter().map(|w| w.object_id).collect()
+    }
+
+    /// The date the party arrives when leaving on `depart`, counting a
+    /// started travel day as a whole one.
+    pub fn arrival(&self, calendar: &WorldCalendar, depart: &WorldDate) -> WorldDate {
+        calendar.add_days(depart, self.total_days.ceil() as i64)
+    }
+}
+
+/// A node on the Dijkstra frontier; ordered so the heap pops the
+/// shortest time first.
+struct Frontier {
+    days: f64,
+    node: ObjectId,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .days
+            .total_cmp(&self.days)
+            .then_with(|| other.node.0.cmp(&self.node.0))
+    }
+}
+
+impl KnowledgeGraph {
+    /// Store `leg` on edge `edge_id`, keeping its other metadata.
+    pub fn set_travel_leg(&self, edge_id: EdgeId, leg: &TravelLeg) -> Result<Edge> {
+        if !leg.distance.is_finite() || leg.distance < 0.0 || leg.difficulty <= 0.0 {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Travel legs need a non-negative distance and positive difficulty, got {} and {}",
+                leg.distance, leg.difficulty
+            )));
+        }
+        let edge = self
+            .get_edge(edge_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown edge {edge_id}")))?;
+        let mut metadata = edge.metadata;
+        leg.write_to(&mut metadata);
+        self.update_edge(
+            edge_id,
+            EdgeChanges {
+                metadata: Some(metadata),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// The fastest route from `from` to `to` for `mode`, or `None` when no
+    /// chain of travel legs the mode may use connects them.  Fails with
+    /// [`UForgeError::NotFound`] when either object does not exist.
+    pub fn compute_route(
+        &self,
+        from: ObjectId,
+        to: ObjectId,
+        mode: &TravelMode,
+    ) -> Result<Option<Route>> {
+        for id in [from, to] {
+            if self.get_object(id)?.is_none() {
+                return Err(UForgeError::NotFound(format!("Unknown object {id}")));
+            }
+        }
+        if mode.distance_per_day <= 0.0 {
+            return Err(UForgeError::ValidationFailed(format!(
+                "Travel mode '{}' must cover some distance per day",
+                mode.name
+            )));
+        }
+
+        let mut best: HashMap<ObjectId, f64> = HashMap::from([(from, 0.0)]);
+        let mut previous: HashMap<ObjectId, RouteLeg> = HashMap::new();
+        let mut frontier = BinaryHeap::from([Frontier {
+            days: 0.0,
+            node: from,
+        }]);
+        while let Some(Frontier { days, node }) = frontier.pop() {
+            if node == to {
+                break;
+            }
+            if best.get(&node).is_some_and(|b| days > *b) {
+                continue; // stale entry
+            }
+            for edge in self.get_relationships(node)? {
+                let Some(leg) = TravelLeg::from_edge(&edge) else {
+                    continue;
+                };
+                if !leg.allows(mode) || (leg.one_way && edge.from != node) {
+                    continue;
+                }
+                let next = if edge.from == node {
+                    edge.to
+                } else {
+                    edge.from
+                };
+                let leg_days = leg.distance * leg.difficulty / mode.distance_per_day;
+                let arrival = days + leg_days;
+                if best.get(&next).is_some_and(|b| arrival >= *b) {
+                    continue;
+                }
+                best.insert(next, arrival);
+                previous.insert(
+                    next,
+                    RouteLeg {
+                        edge_id: edge.id,
+                        from: node,
+                        to: next,
+                        distance: leg.distance,
+                        days: leg_days,
+                    },
+                );
+                frontier.push(Frontier {
+                    days: arrival,
+                    node: next,
+                });
+            }
+        }
+        let Some(&total_days) = best.get(&to) else {
+            return Ok(None);
+        };
+
+        let mut legs = Vec::new();
+        let mut at = to;
+        while at != from {
+            let leg = previous[&at].clone();
+            at = leg.from;
+            legs.push(leg);
+        }
+        legs.reverse();
+
+        let mut waypoints = Vec::with_capacity(legs.len() + 1);
+        for id in std::iter::once(from).chain(legs.iter().map(|l| l.to)) {
+            let name = self.get_object(id)?.map(|o| o.name).unwrap_or_default();
+            waypoints.push(Waypoint {
+                object_id: id,
+                name,
+                day: best[&id],
+            });
+        }
+        Ok(Some(Route {
+            mode: mode.clone(),
+            total_distance: legs.iter().map(|l| l.distance).sum(),
+            legs,
+            waypoints,
+            total_days,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::{EdgeType, ObjectMetadata};
+
+    fn location(graph: &KnowledgeGraph, name: &str) -> ObjectId {
+        graph
+            .add_object(ObjectMetadata::new(
+                "location".to_string(),
+                name.to_string(),
+            ))
+            .unwrap()
+    }
+
+    fn road(graph: &KnowledgeGraph, from: ObjectId, to: ObjectId, leg: TravelLeg) {
+        let edge = Edge::new(from, to, EdgeType::new("road_to"));
+        let id = edge.id;
+        graph.add_edge(edge, false).unwrap();
+        graph.set_travel_leg(id, &leg).unwrap();
+    }
+
+    #[test]
+    fn test_compute_route_prefers_fastest_legs() {
+        let (graph, _temp_dir) = create_test_graph();
+        let neverwinter = location(&graph, "Neverwinter");
+        let phandalin = location(&graph, "Phandalin");
+        let triboar = location(&graph, "Triboar");
+        let leilon = location(&graph, "Leilon");
+        // Direct but through the hills: 48 miles at difficulty 2.
+        road(
+            &graph,
+            neverwinter,
+            phandalin,
+            TravelLeg::new(48.0).with_difficulty(2.0),
+        );
+        // Around by road: 24 + 24 miles.
+        road(&graph, neverwinter, leilon, TravelLeg::new(24.0));
+        road(&graph, leilon, phandalin, TravelLeg::new(24.0));
+        // Ship-only lane and a one-way river.
+        road(
+            &graph,
+            neverwinter,
+            triboar,
+            TravelLeg::new(10.0).with_mode("ship"),
+        );
+        road(&graph, triboar, phandalin, TravelLeg::new(1.0).one_way());
+
+        let route = graph
+            .compute_route(neverwinter, phandalin, &TravelMode::on_foot())
+            .unwrap()
+            .unwrap();
+        assert_eq!(route.path(), [neverwinter, leilon, phandalin]);
+        assert!((route.total_days - 2.0).abs() < 1e-9);
+        assert!((route.total_distance - 48.0).abs() < 1e-9);
+        assert_eq!(route.waypoints[1].name, "Leilon");
+        assert!((route.waypoints[1].day - 1.0).abs() < 1e-9);
+
+        let calendar = WorldCalendar::default();
+        assert_eq!(
+            route.arrival(&calendar, &WorldDate::new(1492, 1, 31)),
+            WorldDate::new(1492, 2, 2)
+        );
+
+        let by_ship = graph
+            .compute_route(neverwinter, phandalin, &TravelMode::by_ship())
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_ship.path(), [neverwinter, triboar, phandalin]);
+
+        // The river does not run upstream.
+        let back = graph
+            .compute_route(phandalin, triboar, &TravelMode::on_foot())
+            .unwrap();
+        assert!(back.is_none());
+
+        let err = graph
+            .compute_route(neverwinter, ObjectId::new_v4(), &TravelMode::on_foot())
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
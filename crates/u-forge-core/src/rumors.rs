@@ -0,0 +1,315 @@
+//! Rumours — who has heard what.
+//!
+//! "Does the Thieves' Guild know the party burned the granary?"  A
+//! [`KNOWS_ABOUT_EDGE`] edge runs from a knower (an NPC, a faction) to the
+//! subject it has heard about (the party, an event node), carrying the
+//! heard `detail` in its metadata and the knower's certainty (0–1) as its
+//! weight.  There is one such edge per knower and subject; hearing the same
+//! thing again only ever raises the certainty.
+//!
+//! [`KnowledgeGraph::spread_rumor`] simulates word of mouth: starting from
+//! one knower it walks [`SOCIAL_EDGES`] in either direction for a number of
+//! hops, multiplying the certainty by `decay` at each hop, and records a
+//! knows-about edge for everyone reached — all in one transaction.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::staging::StagingLayer;
+use crate::types::{Edge, EdgeType, ObjectId};
+use crate::KnowledgeGraph;
+
+/// Edge type from a knower to the subject it knows about.
+pub const KNOWS_ABOUT_EDGE: &str = "knows_about";
+
+/// Edge metadata key holding what the knower has heard.
+pub const DETAIL_KEY: &str = "detail";
+
+/// Edge types word travels along, in either direction.
+pub const SOCIAL_EDGES: [&str; 12] = [
+    "knows",
+    "member_of",
+    "led_by",
+    "is_led_by",
+    "leads",
+    "subfaction_of",
+    "trades_with",
+    "allied_with",
+    "employs",
+    "mentors",
+    "related_to",
+    "provides_information_for",
+];
+
+/// Rumours fading below this certainty stop spreading.
+pub const MIN_RUMOR_CERTAINTY: f32 = 0.05;
+
+/// One knows-about edge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Knowledge {
+    pub knower: ObjectId,
+    pub subject: ObjectId,
+    pub detail: String,
+    /// 0 (barely a whisper) to 1 (witnessed first-hand).
+    pub certainty: f32,
+}
+
+impl Knowledge {
+    fn from_edge(edge: &Edge) -> Self {
+        Self {
+            knower: edge.from,
+            subject: edge.to,
+            detail: edge.metadata.get(DETAIL_KEY).cloned().unwrap_or_default(),
+            certainty: edge.weight,
+        }
+    }
+
+    fn to_edge(&self) -> Edge {
+        Edge::new(self.knower, self.subject, EdgeType::new(KNOWS_ABOUT_EDGE))
+            .with_weight(self.certainty)
+            .with_metadata(DETAIL_KEY.to_string(), self.detail.clone())
+    }
+}
+
+/// Someone a rumour reached in [`KnowledgeGraph::spread_rumor`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RumorReach {
+    pub object_id: ObjectId,
+    /// Social hops from the origin.
+    pub hops: usize,
+    /// Certainty after decay.  The stored certainty may be higher when the
+    /// object already knew more.
+    pub certainty: f32,
+}
+
+fn check_unit(name: &str, value: f32) -> Result<()> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err(UForgeError::ValidationFailed(format!(
+            "{name} must be between 0 and 1, got {value}"
+        )));
+    }
+    Ok(())
+}
+
+impl KnowledgeGraph {
+    /// Record that `knower` has heard `detail` about `subject`.  An existing
+    /// edge is replaced only when `certainty` is higher than the stored one.
+    pub fn knows_about(
+        &self,
+        knower: ObjectId,
+        subject: ObjectId,
+        detail: &str,
+        certainty: f32,
+    ) -> Result<()> {
+        check_unit("certainty", certainty)?;
+        if let Some(existing) = self.knowledge(knower, subject)? {
+            if existing.certainty >= certainty {
+                return Ok(());
+            }
+        }
+        let knowledge = Knowledge {
+            knower,
+            subject,
+            detail: detail.to_string(),
+            certainty,
+        };
+        self.add_edge(knowledge.to_edge(), false)
+    }
+
+    /// What `knower` has heard about `subject`, if anything.
+    pub fn knowledge(&self, knower: ObjectId, subject: ObjectId) -> Result<Option<Knowledge>> {
+        Ok(self
+            .get_relationships(knower)?
+            .iter()
+            .find(|e| {
+                e.from == knower && e.to == subject && e.edge_type.as_str() == KNOWS_ABOUT_EDGE
+            })
+            .map(Knowledge::from_edge))
+    }
+
+    /// Everyone who has heard about `subject`, most certain first.
+    pub fn knowers_of(&self, subject: ObjectId) -> Result<Vec<Knowledge>> {
+        let mut out: Vec<Knowledge> = self
+            .get_relationships(subject)?
+            .iter()
+            .filter(|e| e.to == subject && e.edge_type.as_str() == KNOWS_ABOUT_EDGE)
+            .map(Knowledge::from_edge)
+            .collect();
+        out.sort_by(|a, b| b.certainty.total_cmp(&a.certainty));
+        Ok(out)
+    }
+
+    /// Spread what `from` knows about `subject` up to `hops` social edges
+    /// away, multiplying the certainty by `decay` per hop.
+    ///
+    /// `from` is recorded as knowing `detail` with certainty 1 unless it
+    /// already knows about `subject`, in which case its stored certainty is
+    /// the starting point.  Everyone reached (excluding `from`) is returned
+    /// nearest first; objects whose certainty would fall below
+    /// [`MIN_RUMOR_CERTAINTY`] are not reached.  The subject itself never
+    /// "hears" about itself.
+    pub fn spread_rumor(
+        &self,
+        from: ObjectId,
+        subject: ObjectId,
+        detail: &str,
+        hops: usize,
+        decay: f32,
+    ) -> Result<Vec<RumorReach>> {
+        check_unit("decay", decay)?;
+        if self.get_object(from)?.is_none() {
+            return Err(UForgeError::NotFound(format!("Object {from} not found")));
+        }
+
+        let start = match self.knowledge(from, subject)? {
+            Some(k) => k.certainty,
+            None => {
+                self.knows_about(from, subject, detail, 1.0)?;
+                1.0
+            }
+        };
+
+        // Breadth-first, so each object is reached by its shortest path —
+        // the one with the least decay.
+        let social: HashSet<&str> = SOCIAL_EDGES.into_iter().collect();
+        let mut seen: HashSet<ObjectId> = HashSet::from([from, subject]);
+        let mut queue = VecDeque::from([(from, 0usize, start)]);
+        let mut reached = Vec::new();
+        while let Some((id, depth, certainty)) = queue.pop_front() {
+            if depth == hops {
+                continue;
+            }
+            let next = certainty * decay;
+            if next < MIN_RUMOR_CERTAINTY {
+                continue;
+            }
+            let mut neighbours: Vec<ObjectId> = self
+                .get_relationships(id)?
+                .into_iter()
+                .filter(|e| social.contains(e.edge_type.as_str()))
+                .map(|e| if e.from == id { e.to } else { e.from })
+                .collect();
+            neighbours.sort_by_key(|n| n.0);
+            for neighbour in neighbours {
+                if seen.insert(neighbour) {
+                    reached.push(RumorReach {
+                        object_id: neighbour,
+                        hops: depth + 1,
+                        certainty: next,
+                    });
+                    queue.push_back((neighbour, depth + 1, next));
+                }
+            }
+        }
+
+        let existing: HashMap<ObjectId, f32> = self
+            .knowers_of(subject)?
+            .into_iter()
+            .map(|k| (k.knower, k.certainty))
+            .collect();
+        let mut layer = StagingLayer::new("rumor");
+        for reach in &reached {
+            if existing
+                .get(&reach.object_id)
+                .is_some_and(|c| *c >= reach.certainty)
+            {
+                continue;
+            }
+            let knowledge = Knowledge {
+                knower: reach.object_id,
+                subject,
+                detail: detail.to_string(),
+                certainty: reach.certainty,
+            };
+            layer.add_edge(knowledge.to_edge());
+        }
+        self.commit_staging(layer)?;
+        Ok(reached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+
+    fn add(graph: &KnowledgeGraph, object_type: &str, name: &str) -> ObjectId {
+        graph
+            .add_object(ObjectMetadata::new(
+                object_type.to_string(),
+                name.to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_knows_about_keeps_highest_certainty() {
+        let (graph, _temp_dir) = create_test_graph();
+        let guild = add(&graph, "faction", "Thieves' Guild");
+        let party = add(&graph, "party", "The Party");
+
+        graph
+            .knows_about(guild, party, "burned the granary", 0.4)
+            .unwrap();
+        graph
+            .knows_about(guild, party, "a fire, maybe", 0.2)
+            .unwrap();
+        let k = graph.knowledge(guild, party).unwrap().unwrap();
+        assert_eq!(k.detail, "burned the granary");
+        assert!((k.certainty - 0.4).abs() < 1e-6);
+
+        graph
+            .knows_about(guild, party, "burned the granary for coin", 0.9)
+            .unwrap();
+        assert_eq!(
+            graph.knowers_of(party).unwrap()[0].detail,
+            "burned the granary for coin"
+        );
+
+        let err = graph.knows_about(guild, party, "x", 1.5).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    }
+
+    #[test]
+    fn test_spread_rumor_decays_along_social_edges() {
+        let (graph, _temp_dir) = create_test_graph();
+        let party = add(&graph, "party", "The Party");
+        let barkeep = add(&graph, "npc", "Barkeep");
+        let fence = add(&graph, "npc", "Fence");
+        let guild = add(&graph, "faction", "Thieves' Guild");
+        let tavern = add(&graph, "location", "Tavern");
+        graph.connect_objects_str(barkeep, fence, "knows").unwrap();
+        graph
+            .connect_objects_str(fence, guild, "member_of")
+            .unwrap();
+        graph
+            .connect_objects_str(barkeep, tavern, "located_in")
+            .unwrap();
+        graph.connect_objects_str(barkeep, party, "knows").unwrap();
+
+        let reached = graph
+            .spread_rumor(barkeep, party, "burned the granary", 2, 0.5)
+            .unwrap();
+        let ids: Vec<ObjectId> = reached.iter().map(|r| r.object_id).collect();
+        assert_eq!(ids, [fence, guild]);
+        assert_eq!(reached[1].hops, 2);
+
+        let knowers = graph.knowers_of(party).unwrap();
+        assert_eq!(knowers.len(), 3);
+        assert_eq!(knowers[0].knower, barkeep);
+        assert!((knowers[2].certainty - 0.25).abs() < 1e-6);
+        assert!(graph.knowledge(tavern, party).unwrap().is_none());
+
+        // One hop only: the guild is out of reach.
+        let other = add(&graph, "event", "Jailbreak");
+        let reached = graph
+            .spread_rumor(barkeep, other, "jailbreak", 1, 0.5)
+            .unwrap();
+        assert_eq!(reached.len(), 2); // fence, party
+        assert!(graph.knowledge(guild, other).unwrap().is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Scheduled in-world events — the world moves while the party is away.
+//!
+//! A GM schedules future happenings ("the duke's coronation, 1 Ches 1493")
+//! as [`ScheduledEvent`]s, stored as JSON in the `schedule` project setting
+//! alongside the current world date (`world_date`).
+//! [`KnowledgeGraph::advance_world_time`] moves the world date forward and
+//! fires every event that came due, in date order:
+//!
+//! - a [`GraphEvent::ScheduledEventFired`] is emitted for each;
+//! - events marked [`create_object`](ScheduledEvent::create_object) become
+//!   `event` objects (description from the trigger, `date` property set,
+//!   `affects` edges to the related objects), written in one transaction;
+//! - fired events leave the schedule;
+//! - a final [`GraphEvent::WorldTimeAdvanced`] is emitted.
+//!
+//! Dates use the project calendar (see [`crate::calendar`]).
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::calendar::{date_cmp, WorldDate};
+use crate::error::{Result, UForgeError};
+use crate::events::GraphEvent;
+use crate::staging::StagingLayer;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the pending events.
+pub const SCHEDULE_SETTING: &str = "schedule";
+
+/// `project_settings` key holding the current in-world date.
+pub const WORLD_DATE_SETTING: &str = "world_date";
+
+/// Object type of objects created for fired events.
+pub const FIRED_EVENT_TYPE: &str = "event";
+
+/// Edge type from a created event object to each related object.
+pub const AFFECTS_EDGE: &str = "affects";
+
+/// A future in-world happening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledEvent {
+    pub id: Uuid,
+    pub name: String,
+    /// Due at this date.  A date without a time is due at the start of
+    /// that day.
+    pub date: WorldDate,
+    /// What happens, e.g. `"The bridge toll doubles"`.
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// Objects the event concerns.
+    #[serde(default)]
+    pub related: Vec<ObjectId>,
+    /// Whether firing creates an `event` object.
+    #[serde(default)]
+    pub create_object: bool,
+}
+
+impl ScheduledEvent {
+    pub fn new(name: impl Into<String>, date: WorldDate) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            date,
+            trigger: None,
+            related: Vec::new(),
+            create_object: false,
+        }
+    }
+
+    pub fn with_trigger(mut self, trigger: impl Into<String>) -> Self {
+        self.trigger = Some(trigger.into());
+        self
+    }
+
+    pub fn with_related(mut self, object_id: ObjectId) -> Self {
+        self.related.push(object_id);
+        self
+    }
+
+    pub fn creating_object(mut self) -> Self {
+        self.create_object = true;
+        self
+    }
+}
+
+/// A scheduled event that came due.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FiredEvent {
+    pub event: ScheduledEvent,
+    /// The `event` object created for it, when asked for.
+    pub object_id: Option<ObjectId>,
+}
+
+impl KnowledgeGraph {
+    /// The current in-world date, if one has been set.
+    pub fn world_date(&self) -> Result<Option<WorldDate>> {
+        self.storage
+            .get_setting(WORLD_DATE_SETTING)?
+            .map(|text| text.parse())
+            .transpose()
+    }
+
+    /// Set the in-world date without firing anything, e.g. when starting a
+    /// campaign or retconning.
+    pub fn set_world_date(&self, date: WorldDate) -> Result<()> {
+        self.check_world_date(&date)?;
+        self.storage
+            .set_setting(WORLD_DATE_SETTING, &date.to_string())
+    }
+
+    /// Pending events, soonest first.
+    pub fn scheduled_events(&self) -> Result<Vec<ScheduledEvent>> {
+        let mut events: Vec<ScheduledEvent> = match self.storage.get_setting(SCHEDULE_SETTING)? {
+            Some(json) => serde_json::from_str(&json).context("Failed to parse schedule")?,
+            None => Vec::new(),
+        };
+        events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+        Ok(events)
+    }
+
+    /// Add `event` to the schedule and return its id.  Fails with
+    /// [`UForgeError::ValidationFailed`] when the date is not in the project
+    /// calendar.  An event dated before the world date fires on the next
+    /// advance.
+    pub fn schedule_event(&self, event: ScheduledEvent) -> Result<Uuid> {
+        self.check_world_date(&event.date)?;
+        let id = event.id;
+        let mut events = self.scheduled_events()?;
+        events.retain(|e| e.id != id);
+        events.push(event);
+        self.save_schedule(&events)?;
+        Ok(id)
+    }
+
+    /// Remove event `id` from the schedule.  Returns whether it was there.
+    pub fn cancel_scheduled_event(&self, id: Uuid) -> Result<bool> {
+        let mut events = self.scheduled_events()?;
+        let before = events.len();
+        events.retain(|e| e.id != id);
+        if events.len() == before {
+            return Ok(false);
+        }
+        self.save_schedule(&events)?;
+        Ok(true)
+    }
+
+    /// Move the world date to `to`, firing every event due by then (see the
+    /// module docs).  Fails with [`UForgeError::ValidationFailed`] when `to`
+    /// is before the current world date or not in the calendar.
+    pub fn advance_world_time(&self, to: WorldDate) -> Result<Vec<FiredEvent>> {
+        self.check_world_date(&to)?;
+        let from = self.world_date()?;
+        if let Some(from) = from {
+            if to < from {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "Cannot move world time back from {from} to {to}"
+                )));
+            }
+        }
+
+        let (due, pending): (Vec<ScheduledEvent>, Vec<ScheduledEvent>) = self
+            .scheduled_events()?
+            .into_iter()
+            .partition(|e| date_cmp(&e.date, &to).is_le());
+
+        let mut layer = StagingLayer::new("schedule");
+        let mut fired = Vec::with_capacity(due.len());
+        for event in due {
+            let object_id = if event.create_object {
+                let mut object =
+                    ObjectMetadata::new(FIRED_EVENT_TYPE.to_string(), event.name.clone());
+                if let Some(trigger) = &event.trigger {
+                    object = object.with_description(trigger.clone());
+                }
+                object.set_property("date".to_string(), event.date.to_string());
+                let id = layer.add_object(object);
+                for related in &event.related {
+                    if self.get_object(*related)?.is_some() {
+                        layer.connect_str(id, *related, AFFECTS_EDGE);
+                    }
+                }
+                Some(id)
+            } else {
+                None
+            };
+            fired.push(FiredEvent { event, object_id });
+        }
+        self.commit_staging(layer)?;
+        self.save_schedule(&pending)?;
+        self.storage
+            .set_setting(WORLD_DATE_SETTING, &to.to_string())?;
+
+        for event in &fired {
+            self.emit(GraphEvent::ScheduledEventFired(event.clone()));
+        }
+        self.emit(GraphEvent::WorldTimeAdvanced { from, to });
+        Ok(fired)
+    }
+
+    fn check_world_date(&self, date: &WorldDate) -> Result<()> {
+        self.calendar().parse_date(&date.to_string()).map(|_| ())
+    }
+
+    fn save_schedule(&self, events: &[ScheduledEvent]) -> Result<()> {
+        let json = serde_json::to_string(events).context("Failed to serialize schedule")?;
+        self.storage.set_setting(SCHEDULE_SETTING, &json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_advance_world_time_fires_due_events() {
+        let (graph, _temp_dir) = create_test_graph();
+        let duke = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Duke".to_string()))
+            .unwrap();
+        graph.set_world_date(WorldDate::new(1492, 3, 1)).unwrap();
+        let mut events = graph.subscribe_events();
+
+        graph
+            .schedule_event(
+                ScheduledEvent::new("Coronation", WorldDate::new(1492, 3, 10))
+                    .with_trigger("The duke is crowned.")
+                    .with_related(duke)
+                    .creating_object(),
+            )
+            .unwrap();
+        graph
+            .schedule_event(ScheduledEvent::new(
+                "Harvest festival",
+                WorldDate::new(1492, 9, 1),
+            ))
+            .unwrap();
+        let err = graph
+            .schedule_event(ScheduledEvent::new("Bad", WorldDate::new(1492, 2, 30)))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let fired = graph
+            .advance_world_time(WorldDate::new(1492, 3, 10).with_time(9, 0))
+            .unwrap();
+        assert_eq!(fired.len(), 1);
+        let created = graph
+            .get_object(fired[0].object_id.unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(created.object_type, FIRED_EVENT_TYPE);
+        assert_eq!(created.get_property("date").as_deref(), Some("1492-03-10"));
+        assert_eq!(graph.get_relationships(duke).unwrap().len(), 1);
+        assert_eq!(graph.scheduled_events().unwrap().len(), 1);
+        assert_eq!(
+            graph.world_date().unwrap(),
+            Some(WorldDate::new(1492, 3, 10).with_time(9, 0))
+        );
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            GraphEvent::ObjectCreated { object_id, .. } if Some(object_id) == fired[0].object_id
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            GraphEvent::ScheduledEventFired(_)
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            GraphEvent::WorldTimeAdvanced { from: Some(_), .. }
+        ));
+
+        let err = graph
+            .advance_world_time(WorldDate::new(1492, 1, 1))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
+use crate::types::Lifecycle;
+use super::{Denomination, PropertyUi, TypeMapping};
 
 /// Schema definition for a complete TTRPG system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +16,10 @@ pub struct SchemaDefinition {
     pub object_types: HashMap<String, ObjectTypeSchema>,
     pub edge_types: HashMap<String, EdgeTypeSchema>,
     pub metadata: HashMap<String, String>,
+    /// Mappings for importing data authored against other schemas, keyed
+    /// by source type name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub type_mappings: HashMap<String, TypeMapping>,
 }
 
 impl SchemaDefinition {
@@ -29,6 +35,7 @@ impl SchemaDefinition {
             object_types: HashMap::new(),
             edge_types: HashMap::new(),
             metadata: HashMap::new(),
+            type_mappings: HashMap::new(),
         }
     }
 
@@ -102,6 +109,10 @@ impl SchemaDefinition {
         schema.add_object_type("item".to_string(), ObjectTypeSchema::default_item());
         schema.add_object_type("event".to_string(), ObjectTypeSchema::default_event());
         schema.add_object_type("session".to_string(), ObjectTypeSchema::default_session());
+        schema.add_object_type(
+            "interaction".to_string(),
+            ObjectTypeSchema::default_interaction(),
+        );
 
         // Add default edge types
         schema.add_edge_type(
@@ -113,6 +124,7 @@ impl SchemaDefinition {
         schema.add_edge_type("knows".to_string(), EdgeTypeSchema::default_knows());
         schema.add_edge_type("enemy_of".to_string(), EdgeTypeSchema::default_enemy_of());
         schema.add_edge_type("ally_of".to_string(), EdgeTypeSchema::default_ally_of());
+        schema.add_edge_type("involves".to_string(), EdgeTypeSchema::default_involves());
 
         schema
     }
@@ -127,6 +139,14 @@ pub struct ObjectTypeSchema {
     pub required_properties: Vec<String>,
     pub allowed_edges: Vec<String>,
     pub metadata: HashMap<String, String>,
+    /// Lifecycle given to new objects of this type that don't specify one.
+    /// `None` means [`Lifecycle::Canon`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_lifecycle: Option<Lifecycle>,
+    /// Order of the editor form's sections (see
+    /// [`PropertyUi::section`]); sections not listed follow alphabetically.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub form_sections: Vec<String>,
 }
 
 impl ObjectTypeSchema {
@@ -138,9 +158,21 @@ impl ObjectTypeSchema {
             required_properties: Vec::new(),
             allowed_edges: Vec::new(),
             metadata: HashMap::new(),
+            default_lifecycle: None,
+            form_sections: Vec::new(),
         }
     }
 
+    pub fn with_form_sections(mut self, sections: &[&str]) -> Self {
+        self.form_sections = sections.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn with_default_lifecycle(mut self, lifecycle: Lifecycle) -> Self {
+        self.default_lifecycle = Some(lifecycle);
+        self
+    }
+
     pub fn with_property(mut self, name: String, schema: PropertySchema) -> Self {
         self.properties.insert(name, schema);
         self
@@ -160,45 +192,84 @@ impl ObjectTypeSchema {
         self
     }
 
+    /// Fill every property that is absent (or `null`) in `properties` and has
+    /// a schema `default_value`.  Returns the names filled, sorted.
+    pub fn apply_defaults(
+        &self,
+        properties: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Vec<String> {
+        let mut filled = Vec::new();
+        for (name, prop) in &self.properties {
+            let Some(default) = &prop.default_value else {
+                continue;
+            };
+            if properties.get(name).is_none_or(|v| v.is_null()) {
+                properties.insert(name.clone(), default.clone());
+                filled.push(name.clone());
+            }
+        }
+        filled.sort();
+        filled
+    }
+
     // Default object type schemas based on current hardcoded types
     pub fn default_character() -> Self {
         Self::new(
             "character".to_string(),
             "A character in the game world".to_string(),
         )
-        .with_property("age".to_string(), PropertySchema::string("Character's age"))
+        .with_property(
+            "age".to_string(),
+            PropertySchema::string("Character's age")
+                .with_ui(PropertyUi::in_section("Identity", 3)),
+        )
         .with_property(
             "gender".to_string(),
-            PropertySchema::string("Character's gender"),
+            PropertySchema::string("Character's gender")
+                .with_ui(PropertyUi::in_section("Identity", 2)),
         )
         .with_property(
             "occupation".to_string(),
-            PropertySchema::string("Character's occupation"),
+            PropertySchema::string("Character's occupation")
+                .with_ui(
+                    PropertyUi::in_section("Identity", 4).with_placeholder("e.g. blacksmith"),
+                ),
         )
         .with_property(
             "status".to_string(),
-            PropertySchema::string("Character's current status"),
+            PropertySchema::string("Character's current status")
+                .with_ui(
+                    PropertyUi::in_section("Identity", 5)
+                        .with_placeholder("alive, dead, missing…"),
+                ),
         )
         .with_property(
             "species".to_string(),
-            PropertySchema::string("Character's species"),
+            PropertySchema::string("Character's species")
+                .with_ui(PropertyUi::in_section("Identity", 1)),
         )
         .with_property(
             "background".to_string(),
-            PropertySchema::text("Character's background story"),
+            PropertySchema::text("Character's background story")
+                .with_ui(PropertyUi::in_section("Story", 1)),
         )
         .with_property(
             "equipment".to_string(),
-            PropertySchema::array(PropertyType::String),
+            PropertySchema::array(PropertyType::String)
+                .with_ui(PropertyUi::in_section("Gear", 1)),
         )
         .with_property(
             "secrets".to_string(),
-            PropertySchema::array(PropertyType::String),
+            PropertySchema::array(PropertyType::String).with_ui(
+                PropertyUi::in_section("Story", 3).with_help("Hidden from players in handouts"),
+            ),
         )
         .with_property(
             "goals".to_string(),
-            PropertySchema::array(PropertyType::String),
+            PropertySchema::array(PropertyType::String)
+                .with_ui(PropertyUi::in_section("Story", 2)),
         )
+        .with_form_sections(&["Identity", "Story", "Gear"])
         .with_required_property("name".to_string())
         .with_allowed_edge("knows".to_string())
         .with_allowed_edge("enemy_of".to_string())
@@ -322,6 +393,23 @@ impl ObjectTypeSchema {
             .with_required_property("name".to_string())
             .with_allowed_edge("includes".to_string())
     }
+
+    pub fn default_interaction() -> Self {
+        Self::new(
+            "interaction".to_string(),
+            "An encounter among several participants".to_string(),
+        )
+        .with_property(
+            "kind".to_string(),
+            PropertySchema::string("Kind of interaction, e.g. battle or treaty"),
+        )
+        .with_property(
+            "description".to_string(),
+            PropertySchema::text("What happened"),
+        )
+        .with_required_property("name".to_string())
+        .with_allowed_edge("involves".to_string())
+    }
 }
 
 /// Schema definition for a property within an object type
@@ -333,6 +421,14 @@ pub struct PropertySchema {
     pub relationship: Option<RelationshipDefinition>,
     pub default_value: Option<serde_json::Value>,
     pub metadata: HashMap<String, String>,
+    /// Expression deriving this property from the object's other properties
+    /// (see [`ComputedExpression`](super::ComputedExpression)).  Computed
+    /// values are evaluated on read, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub computed: Option<String>,
+    /// How the object editor shows this property.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui: Option<PropertyUi>,
 }
 
 impl PropertySchema {
@@ -344,6 +440,8 @@ impl PropertySchema {
             relationship: None,
             default_value: None,
             metadata: HashMap::new(),
+            computed: None,
+            ui: None,
         }
     }
 
@@ -370,6 +468,30 @@ impl PropertySchema {
         )
     }
 
+    pub fn integer(description: &str) -> Self {
+        Self::new(PropertyType::Integer, description.to_string())
+    }
+
+    pub fn range(description: &str, min: f64, max: f64) -> Self {
+        Self::new(PropertyType::Range(min, max), description.to_string())
+    }
+
+    pub fn currency(description: &str, denominations: Vec<Denomination>) -> Self {
+        Self::new(PropertyType::Currency(denominations), description.to_string())
+    }
+
+    pub fn dice(description: &str) -> Self {
+        Self::new(PropertyType::DiceExpression, description.to_string())
+    }
+
+    pub fn date(description: &str) -> Self {
+        Self::new(PropertyType::Date, description.to_string())
+    }
+
+    pub fn coordinates(description: &str) -> Self {
+        Self::new(PropertyType::Coordinates, description.to_string())
+    }
+
     pub fn reference(target_type: &str) -> Self {
         Self::new(
             PropertyType::Reference(target_type.to_string()),
@@ -391,6 +513,16 @@ impl PropertySchema {
         self.default_value = Some(default);
         self
     }
+
+    pub fn with_computed(mut self, expression: &str) -> Self {
+        self.computed = Some(expression.to_string());
+        self
+    }
+
+    pub fn with_ui(mut self, ui: PropertyUi) -> Self {
+        self.ui = Some(ui);
+        self
+    }
 }
 
 /// Types of properties that can be stored
@@ -404,6 +536,12 @@ pub enum PropertyType {
     Object(HashMap<String, PropertySchema>), // Nested object
     Reference(String),                       // Reference to another object type
     Enum(Vec<String>),                       // Enumerated values
+    Integer,                                 // Whole number
+    Range(f64, f64),                         // Number within inclusive (min, max)
+    Currency(Vec<Denomination>),             // Whole number of the smallest denomination
+    DiceExpression,                          // Dice notation, e.g. "2d6+3"
+    Date,                                    // Day in the project calendar, e.g. "1492-03-15"
+    Coordinates,                             // {"map": id, "x": .., "y": ..}; map optional
 }
 
 impl PropertyType {
@@ -417,6 +555,12 @@ impl PropertyType {
             PropertyType::Object(_) => "object",
             PropertyType::Reference(_) => "reference",
             PropertyType::Enum(_) => "enum",
+            PropertyType::Integer => "integer",
+            PropertyType::Range(..) => "range",
+            PropertyType::Currency(_) => "currency",
+            PropertyType::DiceExpression => "dice",
+            PropertyType::Date => "date",
+            PropertyType::Coordinates => "coordinates",
         }
     }
 }
@@ -603,6 +747,10 @@ impl EdgeTypeSchema {
             PropertySchema::string("Role within the organization"),
         )
         .with_property("rank".to_string(), PropertySchema::string("Rank or level"))
+        .with_property(
+            "since".to_string(),
+            PropertySchema::string("When the membership began"),
+        )
     }
 
     pub fn default_knows() -> Self {
@@ -640,6 +788,18 @@ impl EdgeTypeSchema {
             )
             .bidirectional()
     }
+
+    pub fn default_involves() -> Self {
+        Self::new(
+            "involves".to_string(),
+            "Participation in an interaction".to_string(),
+        )
+        .with_source_types(vec!["interaction".to_string()])
+        .with_property(
+            "role".to_string(),
+            PropertySchema::string("The participant's part in the interaction"),
+        )
+    }
 }
 
 /// Result of schema validation
@@ -648,6 +808,9 @@ pub struct ValidationResult {
     pub valid: bool,
     pub errors: Vec<ValidationError>,
     pub warnings: Vec<ValidationWarning>,
+    /// Machine-applicable repairs for some of `errors`; apply with
+    /// `SchemaManager::apply_fixes`.
+    pub fixes: Vec<ValidationFix>,
 }
 
 impl ValidationResult {
@@ -656,6 +819,7 @@ impl ValidationResult {
             valid: true,
             errors: Vec::new(),
             warnings: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -664,6 +828,7 @@ impl ValidationResult {
             valid: false,
             errors,
             warnings: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -675,6 +840,37 @@ impl ValidationResult {
     pub fn add_warning(&mut self, warning: ValidationWarning) {
         self.warnings.push(warning);
     }
+
+    pub fn add_fix(&mut self, fix: ValidationFix) {
+        self.fixes.push(fix);
+    }
+}
+
+/// A one-click repair for a validation error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidationFix {
+    /// Replace a property's value with its coerced form (e.g. `"3"` → `3`).
+    CoerceValue { property: String, value: serde_json::Value },
+    /// Add a missing required property with its schema default.
+    AddDefault { property: String, value: serde_json::Value },
+    /// Change the object's type to a known one (e.g. `npc` → `character`).
+    ChangeObjectType { from: String, to: String },
+}
+
+impl std::fmt::Display for ValidationFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationFix::CoerceValue { property, value } => {
+                write!(f, "coerce '{property}' to {value}")
+            }
+            ValidationFix::AddDefault { property, value } => {
+                write!(f, "add missing '{property}' with default {value}")
+            }
+            ValidationFix::ChangeObjectType { from, to } => {
+                write!(f, "map type '{from}' to '{to}'")
+            }
+        }
+    }
 }
 
 /// Validation error details
@@ -685,7 +881,7 @@ pub struct ValidationError {
     pub error_type: ValidationErrorType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ValidationErrorType {
     MissingRequired,
     TypeMismatch,
@@ -694,6 +890,18 @@ pub enum ValidationErrorType {
     ValidationRuleFailed,
 }
 
+impl ValidationErrorType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationErrorType::MissingRequired => "missing_required",
+            ValidationErrorType::TypeMismatch => "type_mismatch",
+            ValidationErrorType::InvalidValue => "invalid_value",
+            ValidationErrorType::InvalidReference => "invalid_reference",
+            ValidationErrorType::ValidationRuleFailed => "validation_rule_failed",
+        }
+    }
+}
+
 /// Validation warning details
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationWarning {
@@ -0,0 +1,519 @@
+//! Computed properties: a small arithmetic language over an object's other
+//! properties.
+//!
+//! A [`PropertySchema`](super::PropertySchema) with a `computed` expression is
+//! never stored; its value is derived on read by
+//! [`ObjectTypeSchema::computed_values`], e.g.
+//!
+//! ```text
+//! ability_mod = floor((score - 10) / 2)
+//! carry_capacity = strength * 15
+//! ```
+//!
+//! Supported: number literals, property names, `+ - * / %`, `^` (power,
+//! right-associative), unary minus, parentheses, and the functions `floor`,
+//! `ceil`, `round`, `abs`, `min`, `max` (two or more arguments), and
+//! `clamp(x, lo, hi)`.  Inputs are numeric properties or strings that parse
+//! as numbers; computed properties may refer to other computed properties.
+//! A value whose inputs are missing, non-numeric, or cyclic — or that divides
+//! by zero — is left out rather than guessed.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use anyhow::Result;
+use parking_lot::RwLock;
+use serde_json::{Map, Value};
+
+use super::ObjectTypeSchema;
+use crate::error::UForgeError;
+
+/// Parsed expressions by source text, shared by every schema (and every
+/// clone of one) so each expression is parsed once per process.  `None`
+/// records a source that failed to parse.
+static PARSED: LazyLock<RwLock<HashMap<String, Option<Arc<ComputedExpression>>>>> =
+    LazyLock::new(Default::default);
+
+/// A parsed computed-property expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedExpression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "floor" => Some(Function::Floor),
+            "ceil" => Some(Function::Ceil),
+            "round" => Some(Function::Round),
+            "abs" => Some(Function::Abs),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "clamp" => Some(Function::Clamp),
+            _ => None,
+        }
+    }
+
+    fn accepts(&self, arity: usize) -> bool {
+        match self {
+            Function::Floor | Function::Ceil | Function::Round | Function::Abs => arity == 1,
+            Function::Min | Function::Max => arity >= 2,
+            Function::Clamp => arity == 3,
+        }
+    }
+
+    fn apply(&self, args: &[f64]) -> f64 {
+        match self {
+            Function::Floor => args[0].floor(),
+            Function::Ceil => args[0].ceil(),
+            Function::Round => args[0].round(),
+            Function::Abs => args[0].abs(),
+            Function::Min => args.iter().copied().fold(f64::INFINITY, f64::min),
+            Function::Max => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Function::Clamp => args[0].max(args[1]).min(args[2]),
+        }
+    }
+}
+
+impl ComputedExpression {
+    /// Parse `source`.  Fails with [`UForgeError::ValidationFailed`] on a
+    /// syntax error, an unknown function, or a wrong argument count.
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source).map_err(|e| invalid(source, &e))?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.expression().map_err(|e| invalid(source, &e))?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(source, &format!("unexpected {token:?}")));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Property names the expression reads, sorted and deduplicated.
+    pub fn variables(&self) -> Vec<&str> {
+        fn walk<'a>(node: &'a Node, out: &mut Vec<&'a str>) {
+            match node {
+                Node::Number(_) => {}
+                Node::Variable(name) => out.push(name),
+                Node::Negate(inner) => walk(inner, out),
+                Node::Binary(_, lhs, rhs) => {
+                    walk(lhs, out);
+                    walk(rhs, out);
+                }
+                Node::Call(_, args) => args.iter().for_each(|a| walk(a, out)),
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.root, &mut out);
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Evaluate with `lookup` supplying variable values.  `None` when a
+    /// variable is unavailable or the result is not a finite number.
+    pub fn evaluate(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        eval(&self.root, lookup).filter(|v| v.is_finite())
+    }
+}
+
+fn invalid(source: &str, reason: &str) -> anyhow::Error {
+    UForgeError::ValidationFailed(format!("Invalid expression '{source}': {reason}")).into()
+}
+
+fn eval(node: &Node, lookup: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+    let value = match node {
+        Node::Number(n) => *n,
+        Node::Variable(name) => lookup(name)?,
+        Node::Negate(inner) => -eval(inner, lookup)?,
+        Node::Binary(op, lhs, rhs) => {
+            let (a, b) = (eval(lhs, lookup)?, eval(rhs, lookup)?);
+            match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div if b == 0.0 => return None,
+                BinaryOp::Div => a / b,
+                BinaryOp::Rem if b == 0.0 => return None,
+                BinaryOp::Rem => a % b,
+                BinaryOp::Pow => a.powf(b),
+            }
+        }
+        Node::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|a| eval(a, lookup))
+                .collect::<Option<Vec<f64>>>()?;
+            function.apply(&args)
+        }
+    };
+    value.is_finite().then_some(value)
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| format!("bad number '{text}'"))?;
+            tokens.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '%' | '^' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character '{c}'")),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; precedence from loosest to tightest is
+/// `+ -`, `* / %`, unary `-`, `^`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+type ParseResult = std::result::Result<Node, String>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(c)) if ops.contains(c) => {
+                let c = *c;
+                self.pos += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> std::result::Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {expected:?}, found {token:?}")),
+            None => Err(format!("expected {expected:?} at end of expression")),
+        }
+    }
+
+    fn expression(&mut self) -> ParseResult {
+        let mut lhs = self.term()?;
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            let op = if op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> ParseResult {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.eat_op(&['*', '/', '%']) {
+            let op = match op {
+                '*' => BinaryOp::Mul,
+                '/' => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> ParseResult {
+        if self.eat_op(&['-']).is_some() {
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> ParseResult {
+        let base = self.atom()?;
+        if self.eat_op(&['^']).is_some() {
+            return Ok(Node::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> ParseResult {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Node::Number(n)),
+            Some(Token::LParen) => {
+                let inner = self.expression()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                let function =
+                    Function::parse(&name).ok_or_else(|| format!("unknown function '{name}'"))?;
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    args.push(self.expression()?);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        args.push(self.expression()?);
+                    }
+                }
+                self.expect(Token::RParen)?;
+                if !function.accepts(args.len()) {
+                    return Err(format!("{name}() does not take {} argument(s)", args.len()));
+                }
+                Ok(Node::Call(function, args))
+            }
+            Some(Token::Ident(name)) => Ok(Node::Variable(name)),
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+// ── Evaluation against objects ────────────────────────────────────────────────
+
+/// Numeric reading of a stored property value.
+fn numeric(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whole numbers as JSON integers, everything else as floats.
+fn to_json(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        Value::from(value as i64)
+    } else {
+        Value::from(value)
+    }
+}
+
+/// `source` parsed, from [`PARSED`] when seen before.
+fn cached_parse(source: &str) -> Option<Arc<ComputedExpression>> {
+    if let Some(parsed) = PARSED.read().get(source) {
+        return parsed.clone();
+    }
+    let parsed = ComputedExpression::parse(source).ok().map(Arc::new);
+    PARSED.write().insert(source.to_string(), parsed.clone());
+    parsed
+}
+
+impl ObjectTypeSchema {
+    /// Parsed computed expressions of this type, keyed by property name.
+    /// Properties whose expression fails to parse are skipped.  Parses are
+    /// cached by expression text, so repeated reads don't re-parse.
+    pub fn computed_expressions(&self) -> Vec<(&str, Arc<ComputedExpression>)> {
+        let mut out: Vec<(&str, Arc<ComputedExpression>)> = self
+            .properties
+            .iter()
+            .filter_map(|(name, prop)| {
+                Some((name.as_str(), cached_parse(prop.computed.as_deref()?)?))
+            })
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(b.0));
+        out
+    }
+
+    /// Evaluate every computed property against `properties`.  Values that
+    /// cannot be computed are left out.
+    pub fn computed_values(&self, properties: &Map<String, Value>) -> Map<String, Value> {
+        let expressions = self.computed_expressions();
+        let mut resolved: Map<String, Value> = Map::new();
+        // Computed properties may depend on each other: resolve in passes
+        // until nothing changes, which also stops on cycles.
+        loop {
+            let mut progressed = false;
+            for (name, expr) in &expressions {
+                if resolved.contains_key(*name) {
+                    continue;
+                }
+                let lookup = |var: &str| {
+                    if let Some(value) = resolved.get(var) {
+                        return numeric(value);
+                    }
+                    if expressions.iter().any(|(n, _)| *n == var) {
+                        return None;
+                    }
+                    properties.get(var).and_then(numeric)
+                };
+                if let Some(value) = expr.evaluate(&lookup) {
+                    resolved.insert(name.to_string(), to_json(value));
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                return resolved;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::schema::PropertySchema;
+
+    fn eval_str(source: &str, vars: &[(&str, f64)]) -> Option<f64> {
+        let lookup = |name: &str| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+        ComputedExpression::parse(source).unwrap().evaluate(&lookup)
+    }
+
+    #[test]
+    fn test_expression_evaluation() {
+        assert_eq!(
+            eval_str("floor((score - 10) / 2)", &[("score", 15.0)]),
+            Some(2.0)
+        );
+        assert_eq!(
+            eval_str("floor((score - 10) / 2)", &[("score", 7.0)]),
+            Some(-2.0)
+        );
+        assert_eq!(eval_str("1 + 2 * 3 ^ 2", &[]), Some(19.0));
+        assert_eq!(eval_str("-2 ^ 2", &[]), Some(-4.0));
+        assert_eq!(
+            eval_str("max(a, b, 3) % 4", &[("a", 1.0), ("b", 6.0)]),
+            Some(2.0)
+        );
+        assert_eq!(eval_str("clamp(x, 0, 10)", &[("x", 12.5)]), Some(10.0));
+        assert_eq!(eval_str("x / 0", &[("x", 1.0)]), None);
+        assert_eq!(eval_str("missing + 1", &[]), None);
+
+        let expr = ComputedExpression::parse("level * hd + con_mod * level").unwrap();
+        assert_eq!(expr.variables(), vec!["con_mod", "hd", "level"]);
+
+        for bad in ["1 +", "floor(1, 2)", "sqrt(4)", "(1", "a $ b", "2 3"] {
+            let err = ComputedExpression::parse(bad).unwrap_err();
+            assert_eq!(
+                UForgeError::kind_of(&err),
+                ErrorKind::ValidationFailed,
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_computed_values_chain_and_skip() {
+        let schema = ObjectTypeSchema::new("npc".to_string(), "A character".to_string())
+            .with_property("strength".to_string(), PropertySchema::number("STR score"))
+            .with_property(
+                "str_mod".to_string(),
+                PropertySchema::number("STR modifier").with_computed("floor((strength - 10) / 2)"),
+            )
+            .with_property(
+                "carry".to_string(),
+                PropertySchema::number("Carry bonus").with_computed("str_mod * 1.5"),
+            )
+            .with_property(
+                "loop_a".to_string(),
+                PropertySchema::number("Cyclic").with_computed("loop_b + 1"),
+            )
+            .with_property(
+                "loop_b".to_string(),
+                PropertySchema::number("Cyclic").with_computed("loop_a + 1"),
+            );
+
+        let props = serde_json::json!({ "strength": "17" });
+        let values = schema.computed_values(props.as_object().unwrap());
+        assert_eq!(values.get("str_mod"), Some(&serde_json::json!(3)));
+        assert_eq!(values.get("carry"), Some(&serde_json::json!(4.5)));
+        assert!(!values.contains_key("loop_a") && !values.contains_key("loop_b"));
+
+        let values = schema.computed_values(&Map::new());
+        assert!(values.is_empty());
+
+        // Clones share the parsed expressions instead of parsing again.
+        let copy = schema.clone();
+        let first = schema.computed_expressions();
+        let again = copy.computed_expressions();
+        assert_eq!(first.len(), 4);
+        assert!(first
+            .iter()
+            .zip(&again)
+            .all(|(a, b)| Arc::ptr_eq(&a.1, &b.1)));
+    }
+}
@@ -0,0 +1,305 @@
+//! Editor form layouts generated from schemas.
+//!
+//! A [`PropertySchema`] may carry [`PropertyUi`] hints — position, section,
+//! widget, placeholder and help text — and an [`ObjectTypeSchema`] the order
+//! of its sections.  [`ObjectTypeSchema::form_layout`] turns those into a
+//! [`FormLayout`] the app renders directly, so a new object type gets an
+//! editor without frontend code.  Properties without hints still appear:
+//! the widget follows the [`PropertyType`], required fields come first, the
+//! rest go by name.
+//!
+//! In schema JSON files the hints sit under a property's `"ui"` key and the
+//! section order under a top-level `"sections"` array:
+//!
+//! ```text
+//! "properties": {
+//!   "hp": { "type": "integer", "ui": { "section": "Combat", "order": 1,
+//!            "widget": "number", "help": "Current hit points" } }
+//! },
+//! "sections": ["Identity", "Combat"]
+//! ```
+
+#[cfg(feature = "native")]
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ObjectTypeSchema, PropertySchema, PropertyType};
+#[cfg(feature = "native")]
+use crate::error::{Result, UForgeError};
+#[cfg(feature = "native")]
+use crate::KnowledgeGraph;
+
+/// Input control for a form field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Widget {
+    Text,
+    TextArea,
+    Number,
+    Slider,
+    Checkbox,
+    Select,
+    /// Editable list of values.
+    List,
+    /// Picker for another object.
+    ObjectPicker,
+    Dice,
+    Date,
+    MapPoint,
+    Currency,
+    /// Nested fields of an object property.
+    Group,
+}
+
+impl Widget {
+    /// The widget a property of `property_type` gets without a hint.
+    pub fn for_type(property_type: &PropertyType) -> Self {
+        match property_type {
+            PropertyType::String => Widget::Text,
+            PropertyType::Text => Widget::TextArea,
+            PropertyType::Number | PropertyType::Integer => Widget::Number,
+            PropertyType::Boolean => Widget::Checkbox,
+            PropertyType::Array(_) => Widget::List,
+            PropertyType::Object(_) => Widget::Group,
+            PropertyType::Reference(_) => Widget::ObjectPicker,
+            PropertyType::Enum(_) => Widget::Select,
+            PropertyType::Range(..) => Widget::Slider,
+            PropertyType::Currency(_) => Widget::Currency,
+            PropertyType::DiceExpression => Widget::Dice,
+            PropertyType::Date => Widget::Date,
+            PropertyType::Coordinates => Widget::MapPoint,
+        }
+    }
+}
+
+/// Editor hints for one property.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropertyUi {
+    /// Position within its section, lowest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<i32>,
+    /// Section title; `None` puts the field in the untitled first section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Overrides [`Widget::for_type`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget: Option<Widget>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// Overrides the property description as the field's help text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+}
+
+impl PropertyUi {
+    pub fn in_section(section: &str, order: i32) -> Self {
+        Self {
+            order: Some(order),
+            section: Some(section.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_widget(mut self, widget: Widget) -> Self {
+        self.widget = Some(widget);
+        self
+    }
+
+    pub fn with_placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+}
+
+/// One input of a [`FormLayout`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormField {
+    /// Property name.
+    pub name: String,
+    pub label: String,
+    pub widget: Widget,
+    /// [`PropertyType::name`].
+    pub property_type: &'static str,
+    pub required: bool,
+    /// Computed properties are shown but not edited.
+    pub read_only: bool,
+    pub placeholder: Option<String>,
+    pub help: String,
+    /// Choices of an enum property.
+    pub options: Vec<String>,
+    /// Object type a reference property points at.
+    pub reference_type: Option<String>,
+    pub default_value: Option<Value>,
+}
+
+/// A titled group of fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormSection {
+    pub title: Option<String>,
+    pub fields: Vec<FormField>,
+}
+
+/// The editor form for one object type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormLayout {
+    pub object_type: String,
+    pub description: String,
+    pub sections: Vec<FormSection>,
+}
+
+/// `"danger_level"` → `"Danger level"`.
+fn label_for(name: &str) -> String {
+    let spaced = name.replace('_', " ");
+    let mut chars = spaced.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn form_field(name: &str, prop: &PropertySchema, required: bool) -> FormField {
+    let ui = prop.ui.clone().unwrap_or_default();
+    FormField {
+        name: name.to_string(),
+        label: label_for(name),
+        widget: ui
+            .widget
+            .unwrap_or_else(|| Widget::for_type(&prop.property_type)),
+        property_type: prop.property_type.name(),
+        required,
+        read_only: prop.computed.is_some(),
+        placeholder: ui.placeholder,
+        help: ui.help.unwrap_or_else(|| prop.description.clone()),
+        options: match &prop.property_type {
+            PropertyType::Enum(values) => values.clone(),
+            _ => Vec::new(),
+        },
+        reference_type: match &prop.property_type {
+            PropertyType::Reference(target) => Some(target.clone()),
+            _ => None,
+        },
+        default_value: prop.default_value.clone(),
+    }
+}
+
+impl ObjectTypeSchema {
+    /// The editor form for this type; see the module docs.
+    pub fn form_layout(&self) -> FormLayout {
+        let required = |name: &str| {
+            self.required_properties.iter().any(|r| r == name)
+                || self
+                    .properties
+                    .get(name)
+                    .and_then(|p| p.validation.as_ref())
+                    .is_some_and(|v| v.required)
+        };
+        let mut fields: Vec<(Option<String>, i32, &str, &PropertySchema)> = self
+            .properties
+            .iter()
+            .map(|(name, prop)| {
+                let ui = prop.ui.as_ref();
+                let section = ui.and_then(|u| u.section.clone());
+                let order = ui.and_then(|u| u.order).unwrap_or(i32::MAX);
+                (section, order, name.as_str(), prop)
+            })
+            .collect();
+        fields.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then_with(|| required(b.2).cmp(&required(a.2)))
+                .then_with(|| a.2.cmp(b.2))
+        });
+
+        let rank = |title: &Option<String>| match title {
+            None => (0, None, String::new()),
+            Some(t) => match self.form_sections.iter().position(|s| s == t) {
+                Some(i) => (1, Some(i), String::new()),
+                None => (2, None, t.clone()),
+            },
+        };
+        let mut sections: Vec<FormSection> = Vec::new();
+        for (section, _, name, prop) in fields {
+            let field = form_field(name, prop, required(name));
+            match sections.iter_mut().find(|s| s.title == section) {
+                Some(existing) => existing.fields.push(field),
+                None => sections.push(FormSection {
+                    title: section,
+                    fields: vec![field],
+                }),
+            }
+        }
+        sections.sort_by_key(|s| rank(&s.title));
+
+        FormLayout {
+            object_type: self.name.clone(),
+            description: self.description.clone(),
+            sections,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl KnowledgeGraph {
+    /// The editor form for `object_type` in the `default` schema.
+    pub fn get_form_layout(&self, object_type: &str) -> Result<FormLayout> {
+        self.get_form_layout_in("default", object_type)
+    }
+
+    /// The editor form for `object_type` in `schema_name`.  Fails with
+    /// [`UForgeError::NotFound`] when the schema has no such type.
+    pub fn get_form_layout_in(&self, schema_name: &str, object_type: &str) -> Result<FormLayout> {
+        self.get_schema_manager()
+            .current_schema(schema_name)?
+            .and_then(|schema| schema.object_types.get(object_type).map(|t| t.form_layout()))
+            .ok_or_else(|| {
+                UForgeError::NotFound(format!(
+                    "No object type '{object_type}' in schema '{schema_name}'"
+                ))
+            })
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_form_layout_follows_hints_and_types() {
+        let (graph, _temp_dir) = create_test_graph();
+
+        let character = graph.get_form_layout("character").unwrap();
+        let titles: Vec<_> = character.sections.iter().map(|s| s.title.as_deref()).collect();
+        assert_eq!(titles, [Some("Identity"), Some("Story"), Some("Gear")]);
+        let identity: Vec<_> = character.sections[0]
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(identity, ["species", "gender", "age", "occupation", "status"]);
+        assert_eq!(character.sections[2].fields[0].widget, Widget::List);
+
+        // No hints: one untitled section, required first, widgets by type.
+        let location = graph.get_form_layout("location").unwrap();
+        assert_eq!(location.sections.len(), 1);
+        let first = &location.sections[0].fields[0];
+        assert_eq!((first.name.as_str(), first.required), ("type", true));
+        assert_eq!(first.label, "Type");
+        let features = location.sections[0]
+            .fields
+            .iter()
+            .find(|f| f.name == "notable_features")
+            .unwrap();
+        assert_eq!(
+            (features.widget, features.label.as_str()),
+            (Widget::List, "Notable features")
+        );
+
+        let err = graph.get_form_layout("starship").unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+    }
+}
@@ -1,4 +1,7 @@
-use super::{SchemaDefinition, ObjectTypeSchema, PropertySchema, PropertyType, EdgeTypeSchema, ValidationRule, RelationshipDefinition, Cardinality};
+use super::mapping::load_mappings_file;
+use super::{ComputedExpression, Denomination, PropertyUi, MAPPINGS_FILE, SchemaDefinition, ObjectTypeSchema, PropertySchema, PropertyType, EdgeTypeSchema, ValidationRule, RelationshipDefinition, Cardinality};
+use crate::statblocks::STAT_BLOCK_SCHEMA_KEY;
+use crate::types::Lifecycle;
 use anyhow::{Context, Result};
 use serde_json::{Value, Map};
 use std::fs;
@@ -13,6 +16,12 @@ struct JsonSchemaFile {
     name: String,
     description: String,
     properties: Map<String, Value>,
+    /// Optional top-level `"defaultLifecycle"` (`"draft"`, `"canon"`, …).
+    default_lifecycle: Option<Lifecycle>,
+    /// Optional top-level `"statBlock"` naming the stat block system.
+    stat_block: Option<String>,
+    /// Optional top-level `"sections"`: editor form section order.
+    sections: Vec<String>,
 }
 
 impl SchemaIngestion {
@@ -62,6 +71,9 @@ impl SchemaIngestion {
             let entry = entry.context("Failed to read directory entry")?;
             let path = entry.path();
 
+            if path.file_name().is_some_and(|n| n == MAPPINGS_FILE) {
+                continue;
+            }
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
                 match Self::load_json_schema_file(&path) {
                     Ok(json_schema) => {
@@ -84,6 +96,18 @@ impl SchemaIngestion {
         // Add common edge types that appear in the schemas
         Self::add_common_edge_types(&mut schema_definition);
 
+        // Mappings for importing data authored against other schemas
+        let mappings_path = dir_path.join(MAPPINGS_FILE);
+        if mappings_path.exists() {
+            for mapping in load_mappings_file(&mappings_path)? {
+                schema_definition.add_type_mapping(mapping);
+            }
+            let errors = schema_definition.validate_type_mappings();
+            if !errors.is_empty() {
+                return Err(anyhow::anyhow!("Invalid type mappings: {}", errors.join("; ")));
+            }
+        }
+
         println!("✅ Loaded {} object types from schema directory", schema_definition.object_types.len());
 
         Ok(schema_definition)
@@ -115,10 +139,28 @@ impl SchemaIngestion {
             .ok_or_else(|| anyhow::anyhow!("Missing or invalid 'properties' field"))?
             .clone();
 
+        let default_lifecycle = match obj.get("defaultLifecycle").and_then(|v| v.as_str()) {
+            Some(s) => Some(
+                Lifecycle::parse(s)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown defaultLifecycle '{}'", s))?,
+            ),
+            None => None,
+        };
+
+        let stat_block = obj.get("statBlock").and_then(|v| v.as_str()).map(str::to_string);
+
+        let sections = obj.get("sections")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).map(str::to_string).collect())
+            .unwrap_or_default();
+
         Ok(JsonSchemaFile {
             name,
             description,
             properties,
+            default_lifecycle,
+            stat_block,
+            sections,
         })
     }
 
@@ -126,6 +168,13 @@ impl SchemaIngestion {
     fn convert_json_to_object_schema(json_schema: JsonSchemaFile) -> Result<ObjectTypeSchema> {
         let object_type_name = Self::extract_object_type_name(&json_schema.name);
         let mut object_schema = ObjectTypeSchema::new(object_type_name, json_schema.description);
+        object_schema.default_lifecycle = json_schema.default_lifecycle;
+        object_schema.form_sections = json_schema.sections;
+        if let Some(system) = json_schema.stat_block {
+            object_schema
+                .metadata
+                .insert(STAT_BLOCK_SCHEMA_KEY.to_string(), system);
+        }
 
         for (prop_name, prop_value) in json_schema.properties {
             let prop_obj = prop_value.as_object()
@@ -166,6 +215,38 @@ impl SchemaIngestion {
             "string" => PropertyType::String,
             "number" => PropertyType::Number,
             "boolean" => PropertyType::Boolean,
+            "integer" => PropertyType::Integer,
+            "range" => {
+                let bound = |key: &str| {
+                    prop_obj.get(key).and_then(|v| v.as_f64()).ok_or_else(|| {
+                        anyhow::anyhow!("Range property '{}' missing numeric '{}'", prop_name, key)
+                    })
+                };
+                let (min, max) = (bound("minimum")?, bound("maximum")?);
+                if min > max {
+                    return Err(anyhow::anyhow!("Range property '{}' has minimum above maximum", prop_name));
+                }
+                PropertyType::Range(min, max)
+            },
+            "currency" => {
+                // "denominations": { "gp": 100, "sp": 10, "cp": 1 }
+                let denominations: Vec<Denomination> = prop_obj.get("denominations")
+                    .and_then(|v| v.as_object())
+                    .ok_or_else(|| anyhow::anyhow!("Currency property '{}' missing 'denominations'", prop_name))?
+                    .iter()
+                    .map(|(name, value)| match value.as_u64() {
+                        Some(value) if value > 0 => Ok(Denomination::new(name, value)),
+                        _ => Err(anyhow::anyhow!("Denomination '{}' of '{}' must be a positive integer", name, prop_name)),
+                    })
+                    .collect::<Result<_>>()?;
+                if denominations.is_empty() {
+                    return Err(anyhow::anyhow!("Currency property '{}' has no denominations", prop_name));
+                }
+                PropertyType::Currency(denominations)
+            },
+            "dice" => PropertyType::DiceExpression,
+            "date" => PropertyType::Date,
+            "coordinates" => PropertyType::Coordinates,
             "array" => {
                 // For arrays, try to determine the element type
                 if let Some(items) = prop_obj.get("items") {
@@ -192,6 +273,20 @@ impl SchemaIngestion {
 
         let mut property_schema = PropertySchema::new(property_type, description);
 
+        // Derived value, e.g. "floor((score - 10) / 2)"
+        if let Some(expression) = prop_obj.get("computed").and_then(|v| v.as_str()) {
+            ComputedExpression::parse(expression)
+                .with_context(|| format!("Property '{}' has an invalid computed expression", prop_name))?;
+            property_schema.computed = Some(expression.to_string());
+        }
+
+        // Editor hints, e.g. { "section": "Combat", "order": 1, "widget": "number" }
+        if let Some(ui) = prop_obj.get("ui") {
+            let ui: PropertyUi = serde_json::from_value(ui.clone())
+                .with_context(|| format!("Property '{}' has invalid ui hints", prop_name))?;
+            property_schema.ui = Some(ui);
+        }
+
         // Add validation rules
         let mut validation_rule = ValidationRule::new();
         let mut has_validation = false;
@@ -282,6 +377,13 @@ impl SchemaIngestion {
             ("associated_with", "General association", vec!["quest"], vec!["artifact"]),
             ("found_at", "Discovery location", vec!["artifact"], vec!["location"]),
             ("subquest_of", "Sub-quest relationship", vec!["quest"], vec!["quest"]),
+            ("requires", "Quest prerequisite relationship", vec!["quest"], vec!["quest"]),
+            ("blocked_by", "Quest blocker relationship", vec!["quest"], vec!["quest"]),
+            ("parent_of", "Parent relationship", vec!["player_character", "npc"], vec!["player_character", "npc"]),
+            ("child_of", "Child relationship", vec!["player_character", "npc"], vec!["player_character", "npc"]),
+            ("sibling_of", "Sibling relationship", vec!["player_character", "npc"], vec!["player_character", "npc"]),
+            ("married_to", "Marriage relationship", vec!["player_character", "npc"], vec!["player_character", "npc"]),
+            ("reports_to", "Direct superior relationship", vec!["player_character", "npc"], vec!["player_character", "npc"]),
             ("affects_faction", "Faction impact relationship", vec!["quest"], vec!["faction"]),
         ];
 
@@ -292,6 +394,9 @@ impl SchemaIngestion {
 
             schema_definition.add_edge_type(edge_name.to_string(), edge_schema);
         }
+
+        // Participant edges of interaction nodes (see `crate::interactions`).
+        schema_definition.add_edge_type("involves".to_string(), EdgeTypeSchema::default_involves());
     }
 
     /// Get a list of available schema files in a directory
@@ -325,6 +430,12 @@ impl SchemaIngestion {
         let mut errors = Vec::new();
 
         for file_path in schema_files {
+            if file_path.file_name().is_some_and(|n| n == MAPPINGS_FILE) {
+                if let Err(e) = load_mappings_file(&file_path) {
+                    errors.push(format!("{:?}: {}", file_path, e));
+                }
+                continue;
+            }
             if let Err(e) = Self::load_json_schema_file(&file_path) {
                 errors.push(format!("{:?}: {}", file_path, e));
             }
@@ -525,4 +636,121 @@ mod tests {
         assert_eq!(errors.len(), 1);
         assert!(errors[0].contains("invalid.json"));
     }
+
+    #[test]
+    fn test_ui_hint_conversion() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema_content = r#"{
+            "name": "add_monster",
+            "description": "A monster",
+            "sections": ["Combat"],
+            "properties": {
+                "hp": {
+                    "type": "integer",
+                    "ui": { "section": "Combat", "order": 1, "widget": "slider", "help": "Hit points" }
+                }
+            }
+        }"#;
+        create_test_schema_file(temp_dir.path(), "monster", schema_content).unwrap();
+
+        let schema =
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").unwrap();
+        let monster = &schema.object_types["monster"];
+        assert_eq!(monster.form_sections, ["Combat"]);
+        let field = &monster.form_layout().sections[0].fields[0];
+        assert_eq!(field.widget, crate::schema::Widget::Slider);
+        assert_eq!(field.help, "Hit points");
+    }
+
+    #[test]
+    fn test_computed_property_conversion() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema_content = r#"{
+            "name": "add_monster",
+            "description": "A monster",
+            "properties": {
+                "strength": { "type": "number" },
+                "str_mod": {
+                    "type": "number",
+                    "computed": "floor((strength - 10) / 2)"
+                }
+            }
+        }"#;
+        create_test_schema_file(temp_dir.path(), "monster", schema_content).unwrap();
+
+        let schema =
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").unwrap();
+        let str_mod = &schema.object_types["monster"].properties["str_mod"];
+        assert_eq!(str_mod.computed.as_deref(), Some("floor((strength - 10) / 2)"));
+
+        let broken = schema_content.replace("floor((strength - 10) / 2)", "floor(strength");
+        create_test_schema_file(temp_dir.path(), "monster", &broken).unwrap();
+        assert!(
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").is_err()
+        );
+    }
+
+    #[test]
+    fn test_type_mappings_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema_content = r#"{
+            "name": "add_character",
+            "description": "A character",
+            "properties": { "hit_points": { "type": "number" } }
+        }"#;
+        create_test_schema_file(temp_dir.path(), "character", schema_content).unwrap();
+        let mappings = r#"[
+            { "source_type": "npc", "target_type": "character",
+              "properties": { "hp": "hit_points" } }
+        ]"#;
+        fs::write(temp_dir.path().join(MAPPINGS_FILE), mappings).unwrap();
+
+        let schema =
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").unwrap();
+        assert_eq!(schema.object_types.len(), 1);
+        let mapping = schema.type_mapping("npc").unwrap();
+        assert_eq!(mapping.properties["hp"], "hit_points");
+        assert!(SchemaIngestion::validate_schema_directory(temp_dir.path())
+            .unwrap()
+            .is_empty());
+
+        // A mapping onto a type the schema lacks is rejected.
+        fs::write(
+            temp_dir.path().join(MAPPINGS_FILE),
+            mappings.replace("\"character\"", "\"monster\""),
+        )
+        .unwrap();
+        assert!(
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").is_err()
+        );
+    }
+
+    #[test]
+    fn test_rich_numeric_property_conversion() {
+        let temp_dir = TempDir::new().unwrap();
+        let schema_content = r#"{
+            "name": "add_ship",
+            "description": "A starship",
+            "properties": {
+                "crew": { "type": "integer" },
+                "hull": { "type": "range", "minimum": 0, "maximum": 100 },
+                "value": { "type": "currency", "denominations": { "credit": 1 } },
+                "damage": { "type": "dice" }
+            }
+        }"#;
+        create_test_schema_file(temp_dir.path(), "ship", schema_content).unwrap();
+
+        let schema =
+            SchemaIngestion::load_schemas_from_directory(temp_dir.path(), "test", "1.0.0").unwrap();
+        let props = &schema.object_types["ship"].properties;
+        assert!(matches!(props["crew"].property_type, PropertyType::Integer));
+        assert!(matches!(props["hull"].property_type, PropertyType::Range(min, max) if min == 0.0 && max == 100.0));
+        match &props["value"].property_type {
+            PropertyType::Currency(denominations) => {
+                assert_eq!(denominations, &vec![Denomination::new("credit", 1)])
+            }
+            other => panic!("Expected currency, got {other:?}"),
+        }
+        assert!(matches!(props["damage"].property_type, PropertyType::DiceExpression));
+    }
 }
@@ -1,5 +1,7 @@
-use super::{SchemaDefinition, ObjectTypeSchema, PropertySchema, PropertyType, ValidationResult, ValidationError, ValidationErrorType, ValidationWarning, EdgeTypeSchema, ValidationRule};
+use super::{parse_currency, Cardinality, DiceExpression, SchemaDefinition, ObjectTypeSchema, PropertySchema, PropertyType, ValidationResult, ValidationError, ValidationErrorType, ValidationFix, ValidationWarning, EdgeTypeSchema, ValidationRule};
 use crate::types::{ObjectMetadata, Edge};
+use crate::calendar::load_calendar;
+use crate::geo::{resolve_coordinates, Coordinates};
 use crate::graph::KnowledgeGraphStorage;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -59,6 +61,24 @@ impl SchemaManager {
         }
     }
 
+    /// The schema named `name` from the cache or storage, without the
+    /// default-creation side effect of [`load_schema`](Self::load_schema).
+    /// An unsaved `"default"` schema is returned as the built-in default.
+    pub fn current_schema(&self, name: &str) -> Result<Option<Arc<SchemaDefinition>>> {
+        if let Some(schema) = self.schema_cache.read().get(name) {
+            return Ok(Some(schema.clone()));
+        }
+        match self.storage.get_schema(name)? {
+            Some(schema) => {
+                let schema_arc = Arc::new(schema);
+                self.schema_cache.write().insert(name.to_string(), schema_arc.clone());
+                Ok(Some(schema_arc))
+            }
+            None if name == "default" => Ok(Some(Arc::new(SchemaDefinition::create_default()))),
+            None => Ok(None),
+        }
+    }
+
     /// Save a schema to storage and update cache
     pub async fn save_schema(&self, schema: &SchemaDefinition) -> Result<()> {
         self.storage.save_schema(schema)?;
@@ -89,6 +109,12 @@ impl SchemaManager {
                     message: format!("Unknown object type: {}", object.object_type),
                     error_type: ValidationErrorType::InvalidValue,
                 });
+                if let Some(to) = suggest_object_type(&object.object_type, schema) {
+                    result.add_fix(ValidationFix::ChangeObjectType {
+                        from: object.object_type.clone(),
+                        to,
+                    });
+                }
                 return Ok(result);
             }
         };
@@ -108,6 +134,14 @@ impl SchemaManager {
                     message: format!("Missing required property: {}", required_prop),
                     error_type: ValidationErrorType::MissingRequired,
                 });
+                let default = object_schema.properties.get(required_prop)
+                    .and_then(|p| p.default_value.clone());
+                if let Some(value) = default {
+                    result.add_fix(ValidationFix::AddDefault {
+                        property: required_prop.clone(),
+                        value,
+                    });
+                }
             }
         }
 
@@ -116,7 +150,17 @@ impl SchemaManager {
             for (key, value) in props {
                 if let Some(prop_schema) = object_schema.properties.get(key) {
                     if let Err(validation_error) = self.validate_property_value(key, value, prop_schema) {
+                        let coerced = match validation_error.error_type {
+                            ValidationErrorType::TypeMismatch => coerce_value(&prop_schema.property_type, value),
+                            _ => None,
+                        };
                         result.add_error(validation_error);
+                        if let Some(coerced) = coerced {
+                            result.add_fix(ValidationFix::CoerceValue {
+                                property: key.clone(),
+                                value: coerced,
+                            });
+                        }
                     }
                 } else {
                     // Property not defined in schema - add warning
@@ -131,6 +175,41 @@ impl SchemaManager {
         Ok(result)
     }
 
+    /// Apply `fixes` (from [`ValidationResult::fixes`]) to `object` in place.
+    /// Returns how many were applied; fixes that no longer match the object
+    /// (e.g. the property was since removed) are skipped.
+    pub fn apply_fixes(&self, object: &mut ObjectMetadata, fixes: &[ValidationFix]) -> usize {
+        let mut applied = 0;
+        for fix in fixes {
+            match fix {
+                ValidationFix::ChangeObjectType { from, to } => {
+                    if object.object_type == *from {
+                        object.object_type = to.clone();
+                        applied += 1;
+                    }
+                }
+                ValidationFix::CoerceValue { property, value } => {
+                    if let Some(slot) = object.properties.as_object_mut().and_then(|p| p.get_mut(property)) {
+                        *slot = value.clone();
+                        applied += 1;
+                    }
+                }
+                ValidationFix::AddDefault { property, value } => {
+                    if let Some(props) = object.properties.as_object_mut() {
+                        if !props.contains_key(property) {
+                            props.insert(property.clone(), value.clone());
+                            applied += 1;
+                        }
+                    }
+                }
+            }
+        }
+        if applied > 0 {
+            object.touch();
+        }
+        applied
+    }
+
     /// Validate an edge against schema constraints
     pub async fn validate_edge(&self, edge: &Edge, source_object: &ObjectMetadata, target_object: &ObjectMetadata) -> Result<ValidationResult> {
         let schema = self.load_schema("default").await?;
@@ -139,10 +218,89 @@ impl SchemaManager {
 
     /// Validate an edge against a specific schema
     pub fn validate_edge_with_schema(&self, edge: &Edge, source_object: &ObjectMetadata, target_object: &ObjectMetadata, schema: &SchemaDefinition) -> Result<ValidationResult> {
+        self.validate_staged_edge_with_schema(edge, source_object, target_object, schema, &[])
+    }
+
+    /// Validate an edge that is written together with `staged` edges.
+    /// Cardinality counts the staged edges as if they were already stored.
+    pub fn validate_staged_edge_with_schema(
+        &self,
+        edge: &Edge,
+        source_object: &ObjectMetadata,
+        target_object: &ObjectMetadata,
+        schema: &SchemaDefinition,
+        staged: &[Edge],
+    ) -> Result<ValidationResult> {
         let mut result = ValidationResult::valid();
 
         let edge_type_str = edge.edge_type.as_str();
 
+        // Validate cardinality declared on the source type's relationship
+        // properties.  The edge being validated never counts against itself.
+        let relationship = schema
+            .object_types
+            .get(&source_object.object_type)
+            .and_then(|t| {
+                t.properties
+                    .values()
+                    .filter_map(|p| p.relationship.as_ref())
+                    .find(|r| r.edge_type == edge_type_str)
+            });
+        if let Some(relationship) = relationship {
+            let (one_per_source, one_per_target) = match relationship.cardinality {
+                Cardinality::OneToOne => (true, true),
+                Cardinality::ManyToOne => (true, false),
+                Cardinality::OneToMany => (false, true),
+                Cardinality::ManyToMany => (false, false),
+            };
+            if one_per_source {
+                let existing = self
+                    .storage
+                    .get_edges(edge.from)?
+                    .iter()
+                    .chain(staged)
+                    .any(|e| {
+                        e.id != edge.id
+                            && e.from == edge.from
+                            && e.edge_type == edge.edge_type
+                            && e.to != edge.to
+                    });
+                if existing {
+                    result.add_error(ValidationError {
+                        property: "cardinality".to_string(),
+                        message: format!(
+                            "'{}' already has a '{}' edge; cardinality is {:?}",
+                            source_object.name, edge_type_str, relationship.cardinality
+                        ),
+                        error_type: ValidationErrorType::ValidationRuleFailed,
+                    });
+                }
+            }
+            if one_per_target {
+                let existing = self
+                    .storage
+                    .get_edges(edge.to)?
+                    .iter()
+                    .chain(staged)
+                    .any(|e| {
+                        e.id != edge.id
+                            && e.to == edge.to
+                            && e.edge_type == edge.edge_type
+                            && e.from != edge.from
+                    });
+                if existing {
+                    result.add_error(ValidationError {
+                        property: "cardinality".to_string(),
+                        message: format!(
+                            "'{}' is already the target of a '{}' edge; cardinality is {:?}",
+                            target_object.name, edge_type_str, relationship.cardinality
+                        ),
+                        error_type: ValidationErrorType::ValidationRuleFailed,
+                    });
+                }
+            }
+        }
+
         // Check if edge type exists in schema
         let edge_schema = match schema.edge_types.get(edge_type_str) {
             Some(schema) => schema,
@@ -184,9 +342,7 @@ impl SchemaManager {
         // Validate edge properties if any
         for (key, value) in &edge.metadata {
             if let Some(prop_schema) = edge_schema.properties.get(key) {
-                // Convert string value to JSON for validation
-                let json_value = Value::String(value.clone());
-                if let Err(validation_error) = self.validate_property_value(key, &json_value, prop_schema) {
+                if let Err(validation_error) = self.validate_property_value(key, &edge_property_value(prop_schema, value), prop_schema) {
                     result.add_error(validation_error);
                 }
             }
@@ -195,6 +351,47 @@ impl SchemaManager {
         Ok(result)
     }
 
+    /// Check and normalise `properties` for an `edge_type` edge.
+    ///
+    /// Edge metadata is stored as strings, so each value is first parsed
+    /// into its declared type (`"1203"` for a number, `"yes"` for a boolean)
+    /// and written back in canonical form.  When `edge_type` is defined in
+    /// `schema`, keys it does not declare are errors; undefined edge types
+    /// accept any properties.
+    pub fn validate_edge_properties(
+        &self,
+        edge_type: &str,
+        properties: &mut HashMap<String, String>,
+        schema: &SchemaDefinition,
+    ) -> Vec<ValidationError> {
+        let Some(edge_schema) = schema.edge_types.get(edge_type) else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        for (key, value) in properties.iter_mut() {
+            let Some(prop_schema) = edge_schema.properties.get(key) else {
+                errors.push(ValidationError {
+                    property: key.clone(),
+                    message: format!("Edge type '{edge_type}' does not declare property '{key}'"),
+                    error_type: ValidationErrorType::InvalidValue,
+                });
+                continue;
+            };
+            let json_value = edge_property_value(prop_schema, value);
+            match self.validate_property_value(key, &json_value, prop_schema) {
+                Ok(()) => {
+                    *value = match json_value {
+                        Value::String(s) => s,
+                        Value::Number(n) => n.as_f64().map_or_else(|| n.to_string(), |f| f.to_string()),
+                        other => other.to_string(),
+                    };
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        errors
+    }
+
     /// Register a new object type at runtime
     pub async fn register_object_type(&self, schema_name: &str, type_name: &str, type_schema: ObjectTypeSchema) -> Result<()> {
         let mut schema = (*self.load_schema(schema_name).await?).clone();
@@ -273,7 +470,7 @@ impl SchemaManager {
 
     /// List all available schemas
     pub fn list_schemas(&self) -> Result<Vec<String>> {
-        self.storage.list_schemas()
+        Ok(self.storage.list_schemas()?)
     }
 
     /// Delete a schema
@@ -315,6 +512,12 @@ impl SchemaManager {
             (PropertyType::Object(_), Value::Object(_)) => true,
             (PropertyType::Reference(_), Value::String(_)) => true,
             (PropertyType::Enum(allowed), Value::String(s)) => allowed.contains(s),
+            (PropertyType::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (PropertyType::Range(..), Value::Number(_)) => true,
+            (PropertyType::Currency(_), Value::Number(n)) => n.is_i64(),
+            (PropertyType::DiceExpression, Value::String(s)) => DiceExpression::parse(s).is_ok(),
+            (PropertyType::Date, Value::String(s)) => load_calendar(&self.storage).parse_date(s).is_ok(),
+            (PropertyType::Coordinates, Value::Object(_)) => Coordinates::from_value(value).is_some(),
             _ => false,
         };
 
@@ -338,6 +541,18 @@ impl SchemaManager {
             });
         }
 
+        // Range bounds
+        if let (PropertyType::Range(min, max), Value::Number(n)) = (&schema.property_type, value) {
+            let num_val = n.as_f64().unwrap_or(0.0);
+            if num_val < *min || num_val > *max {
+                return Err(ValidationError {
+                    property: property_name.to_string(),
+                    message: format!("Property '{}' is out of range. Allowed: {} to {}", property_name, min, max),
+                    error_type: ValidationErrorType::ValidationRuleFailed,
+                });
+            }
+        }
+
         // Apply validation rules if present
         if let Some(validation) = &schema.validation {
             self.apply_validation_rules(property_name, value, validation)?;
@@ -454,6 +669,7 @@ impl SchemaManager {
     /// - [`PropertyIssue::TypeMismatch`] — wrong type and no coercion available
     /// - [`PropertyIssue::UnknownProperty`] — key not declared in the schema
     /// - [`PropertyIssue::InvalidEnum`] — string not in the enum's allowed list
+    /// - [`PropertyIssue::OutOfRange`] — number outside a range property's bounds
     ///
     /// Returns an empty vec when the schema or object type is not cached yet.
     pub fn validate_and_coerce_properties(
@@ -499,37 +715,86 @@ impl SchemaManager {
                     }
                 }
 
-                // Number schema + String value: attempt numeric coercion.
-                (PropertyType::Number, Value::String(s)) => {
-                    if let Ok(n) = s.parse::<f64>() {
-                        if let Some(num) = serde_json::Number::from_f64(n) {
-                            coercions.push((key.clone(), Value::Number(num)));
-                        } else {
-                            issues.push(PropertyIssue::TypeMismatch {
-                                key: key.clone(),
-                                expected: "number".to_string(),
-                            });
-                        }
-                    } else {
-                        issues.push(PropertyIssue::TypeMismatch {
+                // Whole numbers are already valid integers and currency amounts.
+                (PropertyType::Integer, Value::Number(n)) if n.is_i64() || n.is_u64() => {}
+                (PropertyType::Currency(_), Value::Number(n)) if n.is_i64() => {}
+
+                // Range: number must be within the bounds.
+                (PropertyType::Range(min, max), Value::Number(n)) => {
+                    let v = n.as_f64().unwrap_or(0.0);
+                    if v < *min || v > *max {
+                        issues.push(PropertyIssue::OutOfRange {
                             key: key.clone(),
-                            expected: "number".to_string(),
+                            value: v,
+                            min: *min,
+                            max: *max,
                         });
                     }
                 }
 
-                // Boolean schema + String value: attempt boolean coercion.
-                (PropertyType::Boolean, Value::String(s)) => {
-                    match s.to_lowercase().as_str() {
-                        "true" | "1" | "yes" => coercions.push((key.clone(), Value::Bool(true))),
-                        "false" | "0" | "no" => coercions.push((key.clone(), Value::Bool(false))),
-                        _ => issues.push(PropertyIssue::TypeMismatch {
+                // Dice: must parse; stored in normalised notation.
+                (PropertyType::DiceExpression, Value::String(s)) => match DiceExpression::parse(s) {
+                    Ok(dice) if dice.to_string() != *s => {
+                        coercions.push((key.clone(), Value::String(dice.to_string())))
+                    }
+                    Ok(_) => {}
+                    Err(_) => issues.push(PropertyIssue::TypeMismatch {
+                        key: key.clone(),
+                        expected: "dice".to_string(),
+                    }),
+                },
+
+                // Date: must be a day in the project calendar; stored canonically.
+                (PropertyType::Date, Value::String(s)) => {
+                    match load_calendar(&self.storage).parse_date(s) {
+                        Ok(date) if date.to_string() != *s => {
+                            coercions.push((key.clone(), Value::String(date.to_string())))
+                        }
+                        Ok(_) => {}
+                        Err(_) => issues.push(PropertyIssue::TypeMismatch {
                             key: key.clone(),
-                            expected: "boolean".to_string(),
+                            expected: "date".to_string(),
                         }),
                     }
                 }
 
+                // Coordinates: stored as an object; text such as
+                // "12, 40 @ Sword Coast" is converted.
+                (PropertyType::Coordinates, Value::Object(_)) if Coordinates::from_value(value).is_some() => {}
+                (PropertyType::Coordinates, Value::String(s)) => match resolve_coordinates(&self.storage, s) {
+                    Some(coords) => coercions.push((key.clone(), coords.to_value())),
+                    None => issues.push(PropertyIssue::TypeMismatch {
+                        key: key.clone(),
+                        expected: "coordinates".to_string(),
+                    }),
+                },
+
+                // Number / Boolean schema + String value, or a numeric schema
+                // with a fractional or textual value: attempt coercion.
+                (ty @ (PropertyType::Number | PropertyType::Boolean), Value::String(_))
+                | (
+                    ty @ (PropertyType::Integer | PropertyType::Range(..) | PropertyType::Currency(_)),
+                    Value::String(_) | Value::Number(_),
+                ) => match coerce_value(ty, value) {
+                    Some(coerced) => {
+                        if let (PropertyType::Range(min, max), Some(v)) = (ty, coerced.as_f64()) {
+                            if v < *min || v > *max {
+                                issues.push(PropertyIssue::OutOfRange {
+                                    key: key.clone(),
+                                    value: v,
+                                    min: *min,
+                                    max: *max,
+                                });
+                            }
+                        }
+                        coercions.push((key.clone(), coerced));
+                    }
+                    None => issues.push(PropertyIssue::TypeMismatch {
+                        key: key.clone(),
+                        expected: ty.name().to_string(),
+                    }),
+                },
+
                 // All other mismatches.
                 _ => {
                     issues.push(PropertyIssue::TypeMismatch {
@@ -548,6 +813,105 @@ impl SchemaManager {
     }
 }
 
+/// `value` converted to `ty`, when there is an unambiguous conversion:
+/// numeric strings to numbers, `true/false/yes/no/1/0` to booleans, numbers
+/// and booleans to strings, enum values matched ignoring case, whole
+/// numbers (or numeric strings) to integers, currency text to amounts of
+/// the smallest denomination, and dice notation to its normalised form.
+fn coerce_value(ty: &PropertyType, value: &Value) -> Option<Value> {
+    match (ty, value) {
+        (PropertyType::Integer, Value::String(_) | Value::Number(_)) => {
+            let n = numeric_value(value)?;
+            (n.fract() == 0.0 && n.abs() < i64::MAX as f64).then(|| Value::from(n as i64))
+        }
+        (PropertyType::Range(..), Value::String(_)) => numeric_value(value)
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        (PropertyType::Currency(denominations), Value::String(s)) => {
+            parse_currency(s, denominations).map(Value::from)
+        }
+        (PropertyType::Currency(_), Value::Number(n)) => n
+            .as_f64()
+            .filter(|n| n.fract() == 0.0 && n.abs() < i64::MAX as f64)
+            .map(|n| Value::from(n as i64)),
+        (PropertyType::DiceExpression, Value::String(s)) => DiceExpression::parse(s)
+            .ok()
+            .map(|dice| Value::String(dice.to_string())),
+        (PropertyType::Number, Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        (PropertyType::Boolean, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(Value::Bool(true)),
+            "false" | "0" | "no" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (PropertyType::String | PropertyType::Text, Value::Number(_) | Value::Bool(_)) => {
+            Some(Value::String(value.to_string()))
+        }
+        (PropertyType::Enum(allowed), Value::String(s)) => allowed
+            .iter()
+            .find(|a| a.eq_ignore_ascii_case(s.trim()))
+            .map(|a| Value::String(a.clone())),
+        _ => None,
+    }
+}
+
+/// A number, or a string holding one (thousands separators allowed).
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().replace(',', "").parse().ok(),
+        _ => None,
+    }
+}
+
+/// An edge metadata string as the JSON value its property schema expects,
+/// falling back to the raw string when it does not parse.
+fn edge_property_value(schema: &PropertySchema, value: &str) -> Value {
+    let raw = Value::String(value.to_string());
+    coerce_value(&schema.property_type, &raw).unwrap_or(raw)
+}
+
+/// Common aliases for object types, checked against the schema by
+/// [`suggest_object_type`].
+const OBJECT_TYPE_ALIASES: &[(&str, &str)] = &[
+    ("npc", "character"),
+    ("pc", "character"),
+    ("player_character", "character"),
+    ("person", "character"),
+    ("place", "location"),
+    ("city", "location"),
+    ("town", "location"),
+    ("organization", "faction"),
+    ("organisation", "faction"),
+    ("guild", "faction"),
+    ("artifact", "item"),
+    ("object", "item"),
+];
+
+/// A type in `schema` that `name` most likely means: a case or plural
+/// variant, or a known alias.
+fn suggest_object_type(name: &str, schema: &SchemaDefinition) -> Option<String> {
+    let lower = name.trim().to_lowercase().replace([' ', '-'], "_");
+    let singular = lower.strip_suffix('s').unwrap_or(&lower);
+    let known = |t: &str| schema.object_types.contains_key(t);
+
+    schema
+        .object_types
+        .keys()
+        .find(|t| t.to_lowercase() == lower || t.to_lowercase() == singular)
+        .cloned()
+        .or_else(|| {
+            OBJECT_TYPE_ALIASES
+                .iter()
+                .find(|(alias, to)| (*alias == lower || *alias == singular) && known(to))
+                .map(|(_, to)| to.to_string())
+        })
+}
+
 /// Describes a validation or coercion result for a single property.
 ///
 /// Returned by [`SchemaManager::validate_and_coerce_properties`]. Coercions are applied
@@ -561,6 +925,8 @@ pub enum PropertyIssue {
     UnknownProperty { key: String },
     /// String value is not in the enum's allowed list.
     InvalidEnum { key: String, value: String, allowed: Vec<String> },
+    /// Number is outside a range property's bounds.
+    OutOfRange { key: String, value: f64, min: f64, max: f64 },
 }
 
 impl fmt::Display for PropertyIssue {
@@ -579,6 +945,9 @@ impl fmt::Display for PropertyIssue {
                     allowed.join(", ")
                 )
             }
+            PropertyIssue::OutOfRange { key, value, min, max } => {
+                write!(f, "property '{key}': {value} is outside {min} to {max}")
+            }
         }
     }
 }
@@ -596,6 +965,7 @@ pub struct SchemaStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::Denomination;
     use crate::types::{ObjectMetadata, Edge, EdgeType};
     use tempfile::TempDir;
 
@@ -746,4 +1116,100 @@ mod tests {
         let result = manager.validate_property_value("color", &invalid_value, &enum_schema);
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_validation_fixes_round_trip() {
+        let (manager, _temp) = create_test_schema_manager();
+
+        let spell_schema = ObjectTypeSchema::new("spell".to_string(), "A magical spell".to_string())
+            .with_property("level".to_string(), PropertySchema::number("Spell level"))
+            .with_property(
+                "school".to_string(),
+                PropertySchema::new(
+                    PropertyType::Enum(vec!["Evocation".to_string(), "Illusion".to_string()]),
+                    "School of magic".to_string(),
+                ),
+            )
+            .with_property(
+                "components".to_string(),
+                PropertySchema::string("Components").with_default(serde_json::json!("V, S")),
+            )
+            .with_required_property("components".to_string());
+        manager.register_object_type("default", "spell", spell_schema).await.unwrap();
+
+        let mut spell = ObjectMetadata::new("spell".to_string(), "Fireball".to_string());
+        spell.properties = serde_json::json!({ "level": "3", "school": "evocation" });
+
+        let result = manager.validate_object(&spell).await.unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.fixes.len(), 3);
+        assert!(result.fixes.contains(&ValidationFix::CoerceValue {
+            property: "level".to_string(),
+            value: serde_json::json!(3.0),
+        }));
+        assert!(result.fixes.contains(&ValidationFix::AddDefault {
+            property: "components".to_string(),
+            value: serde_json::json!("V, S"),
+        }));
+
+        assert_eq!(manager.apply_fixes(&mut spell, &result.fixes), 3);
+        assert_eq!(spell.properties["school"], serde_json::json!("Evocation"));
+        assert!(manager.validate_object(&spell).await.unwrap().valid);
+
+        // Unknown types get a mapping suggestion when one is obvious.
+        let mut npc = ObjectMetadata::new("NPCs".to_string(), "Barliman".to_string());
+        let result = manager.validate_object(&npc).await.unwrap();
+        assert_eq!(
+            result.fixes,
+            vec![ValidationFix::ChangeObjectType {
+                from: "NPCs".to_string(),
+                to: "character".to_string(),
+            }]
+        );
+        manager.apply_fixes(&mut npc, &result.fixes);
+        assert!(manager.validate_object(&npc).await.unwrap().valid);
+    }
+
+    #[tokio::test]
+    async fn test_rich_numeric_types() {
+        let (manager, _temp) = create_test_schema_manager();
+
+        let ship_schema = ObjectTypeSchema::new("ship".to_string(), "A starship".to_string())
+            .with_property("crew".to_string(), PropertySchema::integer("Crew size"))
+            .with_property("hull".to_string(), PropertySchema::range("Hull integrity", 0.0, 100.0))
+            .with_property(
+                "value".to_string(),
+                PropertySchema::currency("Price", vec![Denomination::new("credit", 1)]),
+            )
+            .with_property("damage".to_string(), PropertySchema::dice("Weapon damage"));
+        manager.register_object_type("default", "ship", ship_schema.clone()).await.unwrap();
+        manager.load_schema("default").await.unwrap();
+
+        let mut props = serde_json::json!({
+            "crew": "1,200",
+            "hull": "150",
+            "value": "50,000 credits",
+            "damage": "2D6 + 3",
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let issues = manager.validate_and_coerce_properties("ship", &mut props);
+        assert_eq!(props["crew"], serde_json::json!(1200));
+        assert_eq!(props["value"], serde_json::json!(50_000));
+        assert_eq!(props["damage"], serde_json::json!("2d6+3"));
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0], PropertyIssue::OutOfRange { key, .. } if key == "hull"));
+
+        let check = |key: &str, value: serde_json::Value| {
+            manager.validate_property_value(key, &value, &ship_schema.properties[key]).is_ok()
+        };
+        assert!(check("crew", serde_json::json!(12)));
+        assert!(!check("crew", serde_json::json!(12.5)));
+        assert!(check("hull", serde_json::json!(42.5)));
+        assert!(!check("hull", serde_json::json!(-1)));
+        assert!(!check("value", serde_json::json!("50,000 credits")));
+        assert!(check("damage", serde_json::json!("1d20+5")));
+        assert!(!check("damage", serde_json::json!("lots")));
+    }
 }
@@ -0,0 +1,210 @@
+//! Cross-schema type mappings for imports.
+//!
+//! Data authored against another schema (say, an `npc` type with `hp`) can be
+//! imported into a project whose schema calls it `character` with
+//! `hit_points`.  A schema stores [`TypeMapping`]s keyed by the *source* type
+//! name; [`DataIngestion`](crate::DataIngestion) rewrites each incoming node's
+//! type and properties through them before creating the object.
+//!
+//! In a schema directory, mappings live in [`MAPPINGS_FILE`] — a JSON array
+//! of mappings in the same shape as their serde representation:
+//!
+//! ```json
+//! [
+//!   {
+//!     "source_type": "npc",
+//!     "target_type": "character",
+//!     "properties": { "hp": "hit_points", "title": "name" },
+//!     "drop_properties": ["foundry_id"],
+//!     "set_properties": { "status": "alive" }
+//!   }
+//! ]
+//! ```
+
+use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::path::Path;
+
+#[cfg(feature = "native")]
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::SchemaDefinition;
+
+/// File in a schema directory holding the schema's type mappings.  It is
+/// skipped when loading object-type files.
+pub const MAPPINGS_FILE: &str = "_mappings.json";
+
+/// How one foreign object type maps onto this schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeMapping {
+    /// Type name in the incoming data, e.g. `"npc"`.
+    pub source_type: String,
+    /// Type it becomes in this schema, e.g. `"character"`.
+    pub target_type: String,
+    /// Source property name → target property name.  Unlisted properties
+    /// keep their names.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, String>,
+    /// Source properties discarded on import.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drop_properties: Vec<String>,
+    /// Values set on every mapped object when the data does not supply them.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub set_properties: Map<String, Value>,
+}
+
+impl TypeMapping {
+    pub fn new(source_type: &str, target_type: &str) -> Self {
+        Self {
+            source_type: source_type.to_string(),
+            target_type: target_type.to_string(),
+            properties: HashMap::new(),
+            drop_properties: Vec::new(),
+            set_properties: Map::new(),
+        }
+    }
+
+    pub fn with_property(mut self, source: &str, target: &str) -> Self {
+        self.properties
+            .insert(source.to_string(), target.to_string());
+        self
+    }
+
+    pub fn with_dropped_property(mut self, source: &str) -> Self {
+        self.drop_properties.push(source.to_string());
+        self
+    }
+
+    pub fn with_set_property(mut self, key: &str, value: Value) -> Self {
+        self.set_properties.insert(key.to_string(), value);
+        self
+    }
+
+    /// Rewrite `properties` from the source shape to the target shape.
+    ///
+    /// Dropped properties are removed, renamed ones move to their target
+    /// name (replacing anything already there), and `set_properties` fill
+    /// keys that are still absent or `null`.
+    pub fn apply(&self, properties: &Map<String, Value>) -> Map<String, Value> {
+        let mut renamed = Map::new();
+        let mut kept = Map::new();
+        for (key, value) in properties {
+            if self.drop_properties.contains(key) {
+                continue;
+            }
+            match self.properties.get(key) {
+                Some(target) => renamed.insert(target.clone(), value.clone()),
+                None => kept.insert(key.clone(), value.clone()),
+            };
+        }
+        kept.extend(renamed);
+        for (key, value) in &self.set_properties {
+            if kept.get(key).is_none_or(|v| v.is_null()) {
+                kept.insert(key.clone(), value.clone());
+            }
+        }
+        kept
+    }
+}
+
+impl SchemaDefinition {
+    /// Add (or replace) the mapping for `mapping.source_type`.
+    pub fn add_type_mapping(&mut self, mapping: TypeMapping) {
+        self.type_mappings
+            .insert(mapping.source_type.clone(), mapping);
+        self.touch();
+    }
+
+    /// The mapping for incoming objects of `source_type`, if any.
+    pub fn type_mapping(&self, source_type: &str) -> Option<&TypeMapping> {
+        self.type_mappings.get(source_type)
+    }
+
+    /// Problems with this schema's mappings: targets that are not object
+    /// types of this schema, and renames onto the same target twice.
+    pub fn validate_type_mappings(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut mappings: Vec<&TypeMapping> = self.type_mappings.values().collect();
+        mappings.sort_by(|a, b| a.source_type.cmp(&b.source_type));
+        for mapping in mappings {
+            if !self.object_types.contains_key(&mapping.target_type) {
+                errors.push(format!(
+                    "Mapping for '{}' targets unknown type '{}'",
+                    mapping.source_type, mapping.target_type
+                ));
+            }
+            let mut targets: Vec<&String> = mapping.properties.values().collect();
+            targets.sort();
+            for pair in targets.windows(2) {
+                if pair[0] == pair[1] {
+                    errors.push(format!(
+                        "Mapping for '{}' renames several properties to '{}'",
+                        mapping.source_type, pair[0]
+                    ));
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Read the mappings in a [`MAPPINGS_FILE`].
+#[cfg(feature = "native")]
+pub(super) fn load_mappings_file(path: &Path) -> Result<Vec<TypeMapping>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read mappings file: {path:?}"))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse mappings file: {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::ObjectTypeSchema;
+    use serde_json::json;
+
+    #[test]
+    fn test_type_mapping_apply_and_validate() {
+        let mapping = TypeMapping::new("npc", "character")
+            .with_property("hp", "hit_points")
+            .with_property("title", "name")
+            .with_dropped_property("foundry_id")
+            .with_set_property("status", json!("alive"));
+
+        let source = json!({
+            "title": "Sildar",
+            "name": "ignored",
+            "hp": 27,
+            "foundry_id": "abc",
+            "status": null,
+        });
+        let mapped = mapping.apply(source.as_object().unwrap());
+        assert_eq!(
+            Value::Object(mapped),
+            json!({ "name": "Sildar", "hit_points": 27, "status": "alive" })
+        );
+
+        let mut schema =
+            SchemaDefinition::new("test".to_string(), "1.0".to_string(), String::new());
+        schema.add_object_type(
+            "character".to_string(),
+            ObjectTypeSchema::new("character".to_string(), String::new()),
+        );
+        schema.add_type_mapping(mapping);
+        schema.add_type_mapping(
+            TypeMapping::new("monster", "creature")
+                .with_property("hp", "health")
+                .with_property("life", "health"),
+        );
+        assert_eq!(schema.type_mapping("npc").unwrap().target_type, "character");
+        assert_eq!(
+            schema.validate_type_mappings(),
+            vec![
+                "Mapping for 'monster' targets unknown type 'creature'".to_string(),
+                "Mapping for 'monster' renames several properties to 'health'".to_string(),
+            ]
+        );
+    }
+}
@@ -1,12 +1,32 @@
-//! Schema system: definition types, runtime manager, JSON ingestion.
+//! Schema system: definition types, runtime manager, JSON ingestion,
+//! rich numeric types, computed-property expressions, editor form layouts,
+//! cross-schema import mappings, and directory hot reload.
 mod definition;
+mod expression;
+mod form;
+#[cfg(feature = "native")]
 mod ingestion;
+#[cfg(feature = "native")]
 mod manager;
+mod mapping;
+mod numeric;
+#[cfg(feature = "native")]
+mod watcher;
 
 pub use definition::{
     Cardinality, EdgeTypeSchema, ObjectTypeSchema, PropertySchema, PropertyType,
     RelationshipDefinition, SchemaDefinition, ValidationError, ValidationErrorType,
-    ValidationResult, ValidationRule, ValidationWarning,
+    ValidationFix, ValidationResult, ValidationRule, ValidationWarning,
 };
+pub use expression::ComputedExpression;
+pub use form::{FormField, FormLayout, FormSection, PropertyUi, Widget};
+#[cfg(feature = "native")]
 pub use ingestion::SchemaIngestion;
+#[cfg(feature = "native")]
 pub use manager::{PropertyIssue, SchemaManager, SchemaStats};
+pub use mapping::{TypeMapping, MAPPINGS_FILE};
+pub use numeric::{format_currency, parse_currency, Denomination, DiceExpression};
+#[cfg(feature = "native")]
+pub use watcher::{
+    SchemaDirectoryWatch, SchemaReloadEvent, SchemaWatcher, DEFAULT_SCHEMA_POLL_INTERVAL,
+};
@@ -0,0 +1,293 @@
+//! Parsing and formatting for the rich numeric property types.
+//!
+//! * [`PropertyType::Integer`](super::PropertyType::Integer) — a whole JSON
+//!   number.
+//! * [`PropertyType::Range`](super::PropertyType::Range) — a JSON number
+//!   within inclusive bounds.
+//! * [`PropertyType::Currency`](super::PropertyType::Currency) — a whole
+//!   number of the smallest [`Denomination`], so amounts sort and add
+//!   directly; [`parse_currency`] reads text like `"50,000 credits"` or
+//!   `"5 gp 3 sp"` and [`format_currency`] writes it back.
+//! * [`PropertyType::DiceExpression`](super::PropertyType::DiceExpression) —
+//!   a normalised dice string such as `"2d6+3"`; see [`DiceExpression`].
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::error::UForgeError;
+
+/// Most dice or sides accepted in one dice term.
+const MAX_DICE: u32 = 1000;
+
+/// One unit of a currency, worth `value` of the smallest unit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Denomination {
+    /// Name or abbreviation as written after amounts, e.g. `"gp"`.
+    pub name: String,
+    pub value: u64,
+}
+
+impl Denomination {
+    pub fn new(name: &str, value: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    /// `word` names this denomination, ignoring case and a plural `s`.
+    fn matches(&self, word: &str) -> bool {
+        word.eq_ignore_ascii_case(&self.name)
+            || word
+                .strip_suffix(['s', 'S'])
+                .is_some_and(|w| w.eq_ignore_ascii_case(&self.name))
+    }
+}
+
+/// Parse a currency amount into units of the smallest denomination.
+///
+/// Accepts a bare number (already in the smallest unit) or one or more
+/// `amount name` pairs, with optional thousands separators and fractions
+/// that come out whole: `"50,000 credits"`, `"5gp 3 sp"`, `"2.5 gp"`.
+pub fn parse_currency(text: &str, denominations: &[Denomination]) -> Option<i64> {
+    let text = text.trim().replace(',', "");
+    if let Ok(units) = text.parse::<i64>() {
+        return Some(units);
+    }
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text.as_str()),
+    };
+
+    let mut total = 0f64;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let amount_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..amount_len].parse().ok()?;
+        rest = rest[amount_len..].trim_start();
+        let name_len = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let denomination = denominations
+            .iter()
+            .find(|d| d.matches(&rest[..name_len]))?;
+        total += amount * denomination.value as f64;
+        rest = rest[name_len..].trim_start();
+    }
+    if total.fract() != 0.0 || total > i64::MAX as f64 {
+        return None;
+    }
+    let units = total as i64;
+    Some(if negative { -units } else { units })
+}
+
+/// Write `units` using the largest denominations first, e.g. `"5 gp 3 sp"`.
+/// Zero is written in the smallest denomination.
+pub fn format_currency(units: i64, denominations: &[Denomination]) -> String {
+    let mut ordered: Vec<&Denomination> = denominations.iter().filter(|d| d.value > 0).collect();
+    ordered.sort_by_key(|d| std::cmp::Reverse(d.value));
+    let Some(smallest) = ordered.last() else {
+        return units.to_string();
+    };
+    if units == 0 {
+        return format!("0 {}", smallest.name);
+    }
+
+    let mut remaining = units.unsigned_abs();
+    let mut parts = Vec::new();
+    for d in &ordered {
+        let count = remaining / d.value;
+        if count > 0 {
+            parts.push(format!("{count} {}", d.name));
+            remaining %= d.value;
+        }
+    }
+    if remaining > 0 {
+        // Smallest denomination is not 1: keep the leftover base units.
+        parts.push(remaining.to_string());
+    }
+    let sign = if units < 0 { "-" } else { "" };
+    format!("{sign}{}", parts.join(" "))
+}
+
+/// A parsed dice expression: a sum of dice terms and a flat modifier, e.g.
+/// `2d6+3`, `1d8 + 1d6 - 1`, or `d%` (one d100).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceExpression {
+    /// `(count, sides)` pairs; a negative count subtracts the roll.
+    pub dice: Vec<(i32, u32)>,
+    pub modifier: i64,
+}
+
+impl DiceExpression {
+    /// Parse `text`.  Fails with [`UForgeError::ValidationFailed`] on
+    /// malformed notation or more than 1000 dice or sides in a term.
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = |reason: &str| -> anyhow::Error {
+            UForgeError::ValidationFailed(format!("Invalid dice expression '{text}': {reason}"))
+                .into()
+        };
+        let compact: String = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        if compact.is_empty() {
+            return Err(invalid("empty"));
+        }
+
+        let mut expr = DiceExpression {
+            dice: Vec::new(),
+            modifier: 0,
+        };
+        let mut rest = compact.as_str();
+        let mut first = true;
+        while !rest.is_empty() {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ if first => 1,
+                _ => return Err(invalid("expected '+' or '-'")),
+            };
+            if rest.starts_with(['+', '-']) {
+                rest = &rest[1..];
+            }
+            first = false;
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let term = &rest[..end];
+            rest = &rest[end..];
+
+            match term.split_once('d') {
+                Some((count, sides)) => {
+                    let count: u32 = if count.is_empty() {
+                        1
+                    } else {
+                        count.parse().map_err(|_| invalid("bad dice count"))?
+                    };
+                    let sides: u32 = if sides == "%" {
+                        100
+                    } else {
+                        sides.parse().map_err(|_| invalid("bad die size"))?
+                    };
+                    if count == 0 || sides == 0 || count > MAX_DICE || sides > MAX_DICE {
+                        return Err(invalid("dice count and size must be 1-1000"));
+                    }
+                    expr.dice.push((sign * count as i32, sides));
+                }
+                None => {
+                    let n: i64 = term.parse().map_err(|_| invalid("bad modifier"))?;
+                    expr.modifier += sign as i64 * n;
+                }
+            }
+        }
+        if expr.dice.is_empty() {
+            return Err(invalid("no dice"));
+        }
+        Ok(expr)
+    }
+
+    /// Lowest possible total.
+    pub fn min(&self) -> i64 {
+        self.bound(|count, sides| if count > 0 { count } else { count * sides })
+    }
+
+    /// Highest possible total.
+    pub fn max(&self) -> i64 {
+        self.bound(|count, sides| if count > 0 { count * sides } else { count })
+    }
+
+    /// Expected total.
+    pub fn average(&self) -> f64 {
+        self.dice
+            .iter()
+            .map(|&(count, sides)| count as f64 * (sides as f64 + 1.0) / 2.0)
+            .sum::<f64>()
+            + self.modifier as f64
+    }
+
+    fn bound(&self, term: impl Fn(i64, i64) -> i64) -> i64 {
+        self.dice
+            .iter()
+            .map(|&(count, sides)| term(count as i64, sides as i64))
+            .sum::<i64>()
+            + self.modifier
+    }
+}
+
+impl fmt::Display for DiceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, &(count, sides)) in self.dice.iter().enumerate() {
+            match (i, count < 0) {
+                (_, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, false) => write!(f, "+")?,
+            }
+            write!(f, "{}d{sides}", count.unsigned_abs())?;
+        }
+        match self.modifier {
+            0 => Ok(()),
+            m if m > 0 => write!(f, "+{m}"),
+            m => write!(f, "{m}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn coins() -> Vec<Denomination> {
+        vec![
+            Denomination::new("gp", 100),
+            Denomination::new("sp", 10),
+            Denomination::new("cp", 1),
+        ]
+    }
+
+    #[test]
+    fn test_currency_parse_and_format() {
+        let credits = vec![Denomination::new("credit", 1)];
+        assert_eq!(parse_currency("50,000 credits", &credits), Some(50_000));
+        assert_eq!(format_currency(50_000, &credits), "50000 credit");
+
+        assert_eq!(parse_currency("5gp 3 SP", &coins()), Some(530));
+        assert_eq!(parse_currency("2.5 gp", &coins()), Some(250));
+        assert_eq!(parse_currency("-1 gp", &coins()), Some(-100));
+        assert_eq!(parse_currency("1234", &coins()), Some(1234));
+        assert_eq!(parse_currency("0.5 cp", &coins()), None);
+        assert_eq!(parse_currency("3 pp", &coins()), None);
+
+        assert_eq!(format_currency(530, &coins()), "5 gp 3 sp");
+        assert_eq!(format_currency(-101, &coins()), "-1 gp 1 cp");
+        assert_eq!(format_currency(0, &coins()), "0 cp");
+    }
+
+    #[test]
+    fn test_dice_expression() {
+        let dice = DiceExpression::parse("2D6 + 3").unwrap();
+        assert_eq!(dice.to_string(), "2d6+3");
+        assert_eq!((dice.min(), dice.max(), dice.average()), (5, 15, 10.0));
+
+        let dice = DiceExpression::parse("1d8+d6-1").unwrap();
+        assert_eq!(dice.to_string(), "1d8+1d6-1");
+        assert_eq!((dice.min(), dice.max()), (1, 13));
+
+        let dice = DiceExpression::parse("d%-1d4").unwrap();
+        assert_eq!(dice.to_string(), "1d100-1d4");
+        assert_eq!((dice.min(), dice.max()), (-3, 99));
+
+        for bad in ["", "3", "2d", "d0", "2x6", "1d6+", "5000d6"] {
+            let err = DiceExpression::parse(bad).unwrap_err();
+            assert_eq!(
+                UForgeError::kind_of(&err),
+                ErrorKind::ValidationFailed,
+                "{bad}"
+            );
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Hot reload of a schema directory.
+//!
+//! [`SchemaIngestion::watch_directory`] polls a directory of JSON schema
+//! files and, when their contents change, re-validates and re-loads them and
+//! saves the result through the [`SchemaManager`] — which replaces the cached
+//! compiled schema, so the next validation or lookup sees the new types.
+//! Each reload (or rejected reload) is broadcast as a [`SchemaReloadEvent`].
+//!
+//! Changes are detected by hashing the names and contents of the directory's
+//! `.json` files, so editors that save with unchanged timestamps, and
+//! coarse-mtime filesystems, still trigger a reload.  A directory that fails
+//! validation leaves the previous schema in place until it is fixed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::{SchemaIngestion, SchemaManager};
+
+/// Poll interval used by [`SchemaIngestion::watch_directory`] when none is
+/// given.
+pub const DEFAULT_SCHEMA_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Outcome of a detected change to a watched schema directory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaReloadEvent {
+    /// The directory was re-loaded and the schema hot-swapped.
+    Reloaded {
+        schema_name: String,
+        object_types: usize,
+    },
+    /// The changed directory did not load; the previous schema stays active.
+    Rejected {
+        schema_name: String,
+        errors: Vec<String>,
+    },
+}
+
+/// One schema directory and the state needed to notice changes to it.
+///
+/// [`poll`](Self::poll) does a single check; [`SchemaWatcher`] calls it on a
+/// timer.
+pub struct SchemaDirectoryWatch {
+    directory: PathBuf,
+    schema_name: String,
+    schema_version: String,
+    manager: Arc<SchemaManager>,
+    fingerprint: Option<u64>,
+}
+
+impl SchemaDirectoryWatch {
+    /// Watch `directory`, treating its current contents as already loaded.
+    pub fn new(
+        directory: impl Into<PathBuf>,
+        schema_name: &str,
+        schema_version: &str,
+        manager: Arc<SchemaManager>,
+    ) -> Self {
+        let directory = directory.into();
+        let fingerprint = directory_fingerprint(&directory).ok();
+        Self {
+            directory,
+            schema_name: schema_name.to_string(),
+            schema_version: schema_version.to_string(),
+            manager,
+            fingerprint,
+        }
+    }
+
+    /// Check the directory once.  Returns `None` when nothing changed since
+    /// the last check.
+    pub async fn poll(&mut self) -> Option<SchemaReloadEvent> {
+        let fingerprint = match directory_fingerprint(&self.directory) {
+            Ok(fingerprint) => fingerprint,
+            Err(e) => {
+                // Report a vanished or unreadable directory once, not every tick.
+                self.fingerprint.take()?;
+                return Some(self.rejected(vec![format!("{e:#}")]));
+            }
+        };
+        if self.fingerprint == Some(fingerprint) {
+            return None;
+        }
+        self.fingerprint = Some(fingerprint);
+        Some(self.reload().await)
+    }
+
+    async fn reload(&self) -> SchemaReloadEvent {
+        match SchemaIngestion::validate_schema_directory(&self.directory) {
+            Ok(errors) if !errors.is_empty() => return self.rejected(errors),
+            Err(e) => return self.rejected(vec![format!("{e:#}")]),
+            Ok(_) => {}
+        }
+        let schema = match SchemaIngestion::load_schemas_from_directory(
+            &self.directory,
+            &self.schema_name,
+            &self.schema_version,
+        ) {
+            Ok(schema) => schema,
+            Err(e) => return self.rejected(vec![format!("{e:#}")]),
+        };
+        if let Err(e) = self.manager.save_schema(&schema).await {
+            return self.rejected(vec![format!("{e:#}")]);
+        }
+        info!(
+            schema = %self.schema_name,
+            object_types = schema.object_types.len(),
+            "Schema directory hot-reloaded"
+        );
+        SchemaReloadEvent::Reloaded {
+            schema_name: self.schema_name.clone(),
+            object_types: schema.object_types.len(),
+        }
+    }
+
+    fn rejected(&self, errors: Vec<String>) -> SchemaReloadEvent {
+        warn!(schema = %self.schema_name, ?errors, "Schema directory reload rejected");
+        SchemaReloadEvent::Rejected {
+            schema_name: self.schema_name.clone(),
+            errors,
+        }
+    }
+}
+
+/// A running background watch.  Dropping it (or calling
+/// [`stop`](Self::stop)) ends the watch.
+pub struct SchemaWatcher {
+    sender: broadcast::Sender<SchemaReloadEvent>,
+    task: JoinHandle<()>,
+}
+
+impl SchemaWatcher {
+    /// Subscribe to reload events.  Receivers that fall more than 16 events
+    /// behind get [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<SchemaReloadEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for SchemaWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl SchemaIngestion {
+    /// Start hot-reloading `directory` into the schema `schema_name`,
+    /// checking every `interval` (default [`DEFAULT_SCHEMA_POLL_INTERVAL`]).
+    ///
+    /// The directory's current contents are assumed to be loaded already;
+    /// only later changes trigger a reload.  Must be called from within a
+    /// Tokio runtime.
+    pub fn watch_directory<P: AsRef<Path>>(
+        directory: P,
+        schema_name: &str,
+        schema_version: &str,
+        manager: Arc<SchemaManager>,
+        interval: Option<Duration>,
+    ) -> SchemaWatcher {
+        let mut watch =
+            SchemaDirectoryWatch::new(directory.as_ref(), schema_name, schema_version, manager);
+        let (sender, _) = broadcast::channel(16);
+        let events = sender.clone();
+        let mut ticker = tokio::time::interval(interval.unwrap_or(DEFAULT_SCHEMA_POLL_INTERVAL));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let task = tokio::spawn(async move {
+            loop {
+                ticker.tick().await;
+                if let Some(event) = watch.poll().await {
+                    // No subscribers is fine: the schema was still swapped.
+                    let _ = events.send(event);
+                }
+            }
+        });
+        SchemaWatcher { sender, task }
+    }
+}
+
+/// Hash of the names and contents of the `.json` files in `directory`.
+fn directory_fingerprint(directory: &Path) -> Result<u64> {
+    if !directory.is_dir() {
+        anyhow::bail!("Schema directory does not exist: {directory:?}");
+    }
+    let mut hasher = DefaultHasher::new();
+    for path in SchemaIngestion::list_schema_files(directory)? {
+        path.file_name().hash(&mut hasher);
+        std::fs::read(&path)
+            .with_context(|| format!("Failed to read schema file {path:?}"))?
+            .hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use tempfile::TempDir;
+
+    const NPC: &str = r#"{"name": "npc", "description": "A character", "properties": {"name": {"type": "string"}}}"#;
+    const ITEM: &str =
+        r#"{"name": "item", "description": "A thing", "properties": {"name": {"type": "string"}}}"#;
+
+    #[tokio::test]
+    async fn test_directory_watch_reloads_and_rejects() {
+        let (graph, _db_dir) = create_test_graph();
+        let schema_dir = TempDir::new().unwrap();
+        std::fs::write(schema_dir.path().join("npc.json"), NPC).unwrap();
+
+        let mut watch =
+            SchemaDirectoryWatch::new(schema_dir.path(), "live", "1.0", graph.get_schema_manager());
+        assert_eq!(watch.poll().await, None);
+
+        std::fs::write(schema_dir.path().join("item.json"), ITEM).unwrap();
+        assert_eq!(
+            watch.poll().await,
+            Some(SchemaReloadEvent::Reloaded {
+                schema_name: "live".to_string(),
+                object_types: 2,
+            })
+        );
+        let manager = graph.get_schema_manager();
+        assert!(manager.get_object_type_schema("live", "item").is_some());
+        assert_eq!(watch.poll().await, None);
+
+        // A broken file is reported and the loaded schema is kept.
+        std::fs::write(schema_dir.path().join("item.json"), "{ not json").unwrap();
+        assert!(matches!(
+            watch.poll().await,
+            Some(SchemaReloadEvent::Rejected { errors, .. }) if errors.len() == 1
+        ));
+        assert!(manager.get_object_type_schema("live", "item").is_some());
+
+        std::fs::remove_file(schema_dir.path().join("item.json")).unwrap();
+        assert!(matches!(
+            watch.poll().await,
+            Some(SchemaReloadEvent::Reloaded {
+                object_types: 1,
+                ..
+            })
+        ));
+        assert!(manager.get_object_type_schema("live", "item").is_none());
+    }
+}
@@ -25,6 +25,12 @@
 //! - **Semantic ANN** — sqlite-vec cosine nearest-neighbour search
 //!   (`search_chunks_semantic`). Finds conceptually similar chunks even when
 //!   keywords don't overlap. Requires an embedding worker in the queue.
+//! - **BM25 sparse** — optional any-term keyword retrieval
+//!   (`search_chunks_bm25`) ranked by FTS5's BM25, enabled by
+//!   [`HybridSearchConfig::sparse_weight`].  Rare terms carry the most
+//!   weight, so invented proper nouns ("Ixildar", "Vraskgut") — the terms
+//!   dense embedding models handle worst — decide the ranking even when
+//!   the rest of the query matches nothing.
 //! - **Reranking** — cross-encoder rescoring (`InferenceQueue::rerank`).
 //!   Expensive but highly precise. Applied at the node level using
 //!   concatenated chunk content.
@@ -35,10 +41,12 @@
 //! 2. Score each chunk using Reciprocal Rank Fusion (RRF).
 //! 3. Aggregate chunk scores per parent node — nodes with more matching
 //!    chunks, or chunks found by both search paths, naturally rank higher.
-//! 4. Select the top-N nodes (default 3).
+//! 4. Select the top-N nodes (default 3), or the top `rerank_candidates`
+//!    (default 10) when a reranker will choose the final N.
 //! 5. For each winning node, load full metadata, all text chunks, all edges,
 //!    and connected node summaries.
-//! 6. Optionally rerank the winning nodes using concatenated chunk content.
+//! 6. Optionally rerank the candidates using concatenated chunk content and
+//!    keep the best N.
 //!
 //! # Merge Strategy: Reciprocal Rank Fusion (RRF)
 //!
@@ -53,7 +61,9 @@
 //! ```
 //!
 //! where `k = 60` is the standard RRF constant (Cormack & Clarke, SIGIR 2009)
-//! and `alpha ∈ [0, 1]` controls the FTS / semantic balance.
+//! and `alpha ∈ [0, 1]` controls the FTS / semantic balance.  The BM25
+//! sparse path adds `sparse_weight / (k + sparse_rank)` on top, independent
+//! of `alpha`.
 //!
 //! # Graceful Degradation
 //!
@@ -63,19 +73,40 @@
 //!   RRF-scored results directly.
 //! - Reranker fails at runtime → falls back to RRF-scored results with a warning.
 //! - Neither search path returns results → returns an empty `Vec` (not an error).
+//!
+//! # Pinned objects
+//!
+//! When [`HybridSearchConfig::pinboard`] names a user, matching nodes on that
+//! user's pinboard (see [`crate::pins`]) have their aggregated RRF score
+//! multiplied by `pin_boost` before the top-N cut and are flagged with
+//! [`SearchSources::pinned`].  Pinning never adds a node that did not match.
+//!
+//! # Telemetry
+//!
+//! [`search_hybrid`] records nothing itself; callers that show results to a
+//! user log the search and any opened result through the opt-in, local
+//! search log (see `telemetry.rs` and [`SearchReport`]).
 
+mod preprocess;
 mod sanitize;
+mod telemetry;
 
 use std::collections::HashMap;
 
 use anyhow::Result;
 use tracing::{debug, info, instrument, warn};
 
+use crate::diagnostics::trace_operation;
+use crate::health::record_error;
 use crate::queue::InferenceQueue;
-use crate::types::{Edge, ObjectId, ObjectMetadata, TextChunk};
+use crate::types::{Edge, Lifecycle, ObjectId, ObjectMetadata, TextChunk};
 use crate::KnowledgeGraph;
 
-use sanitize::fts5_sanitize;
+pub use preprocess::{preprocess_query, PreparedQuery, QueryPreprocessing};
+pub(crate) use preprocess::edit_distance;
+pub use telemetry::{
+    normalize_query, ClickedObject, SearchReport, ZeroResultQuery, SEARCH_TELEMETRY_SETTING,
+};
 
 // ── Public configuration ──────────────────────────────────────────────────────
 
@@ -110,6 +141,16 @@ pub struct HybridSearchConfig {
     /// reranking-capable worker registered.
     pub rerank: bool,
 
+    /// Number of top RRF-ranked nodes handed to the reranker.
+    ///
+    /// Reranking only the final `limit` nodes can reorder them but never
+    /// rescue a relevant node that RRF placed just below the cut, so the
+    /// reranker scores this wider pool and the best `limit` are returned.
+    /// Values below `limit` are treated as `limit`.  Each extra candidate
+    /// costs one hydration and a few hundred tokens of cross-encoder input;
+    /// the default of `10` adds tens of milliseconds with a local reranker.
+    pub rerank_candidates: usize,
+
     /// Maximum number of **nodes** to return.
     ///
     /// Applied after node-level aggregation and again after reranking.
@@ -128,6 +169,53 @@ pub struct HybridSearchConfig {
     /// standard semantic contributions at the same rank position.
     /// Set to `1.0` to treat both paths equally.
     pub hq_semantic_boost: f32,
+
+    /// Restrict results to nodes in one of these lifecycle states.
+    ///
+    /// `None` (the default) returns nodes in any state.  Use
+    /// `Some(vec![Lifecycle::Canon])` for authoritative lookups that must not
+    /// surface drafts, retired material, or rumors.  Applied before `limit`,
+    /// so filtered-out nodes never take a result slot.
+    pub lifecycles: Option<Vec<Lifecycle>>,
+
+    /// Also return [archived](crate::archive) objects.  `false` (the
+    /// default) drops them before `limit`, like `lifecycles`.
+    pub include_archived: bool,
+
+    /// Search for a player-facing view.  Objects players may not see are
+    /// dropped before `limit`, like `lifecycles`, and the rest pass through
+    /// [`KnowledgeGraph::redact_search_results`] before reranking, so GM-only
+    /// properties and notes never reach the reranker or the caller.
+    /// `false` (the default) returns everything.
+    pub player_view: bool,
+
+    /// Normalisation, spell-correction, and synonym expansion applied to the
+    /// query before any search stage (see [`preprocess_query`]).
+    ///
+    /// `None` (the default) searches with the query exactly as typed.
+    /// Spell-correction only knows node-name words, so callers that take
+    /// typed queries — the search panel, the agent's search tool — opt in.
+    pub preprocess: Option<QueryPreprocessing>,
+
+    /// User whose pinboard boosts results.  `None` (the default) ignores pins.
+    pub pinboard: Option<String>,
+
+    /// RRF weight of the BM25 sparse keyword path.
+    ///
+    /// `0.0` (the default) skips the path.  The path matches chunks that
+    /// contain *any* query term and ranks them by BM25, so it finds nodes
+    /// whose rare names appear in the query even when the all-terms FTS5
+    /// stage misses them.  `1.0` weighs it like an unblended FTS5 path.
+    pub sparse_weight: f32,
+
+    /// Number of BM25 sparse chunk candidates to retrieve before merging.
+    pub sparse_limit: usize,
+
+    /// Multiplier applied to the RRF score of pinned nodes.
+    ///
+    /// Default is `1.5`.  Only the RRF ranking is boosted; cross-encoder
+    /// scores from reranking are left as the reranker returned them.
+    pub pin_boost: f32,
 }
 
 impl Default for HybridSearchConfig {
@@ -137,8 +225,17 @@ impl Default for HybridSearchConfig {
             fts_limit: 20,
             semantic_limit: 20,
             rerank: true,
+            rerank_candidates: 10,
             limit: 3,
             hq_semantic_boost: 3.0,
+            lifecycles: None,
+            include_archived: false,
+            player_view: false,
+            preprocess: None,
+            pinboard: None,
+            pin_boost: 1.5,
+            sparse_weight: 0.0,
+            sparse_limit: 20,
         }
     }
 }
@@ -220,26 +317,48 @@ pub struct SearchSources {
     /// Cross-encoder relevance score assigned by the reranker, if reranking
     /// was applied (higher = more relevant).
     pub rerank_score: Option<f32>,
+
+    /// Best BM25 score among the node's chunks from the sparse keyword
+    /// path, if it matched (higher = more relevant).
+    pub sparse_score: Option<f32>,
+
+    /// Cosine distance between the query and the node's profile embedding
+    /// (name, type, and key properties), if the profile ANN path matched it.
+    pub profile_distance: Option<f32>,
+
+    /// Whether the node is on the pinboard named by
+    /// [`HybridSearchConfig::pinboard`] (its score was boosted).
+    pub pinned: bool,
 }
 
 impl SearchSources {
     /// Human-readable bracketed label indicating which paths contributed.
     ///
-    /// Examples: `"[FTS]"`, `"[SEM]"`, `"[FTS+SEM+HQ]"`, `"[FTS+SEM+HQ+RR]"`.
+    /// Examples: `"[FTS]"`, `"[SEM]"`, `"[FTS+SEM+HQ]"`, `"[FTS+SEM+HQ+RR]"`,
+    /// `"[SEM+PROF]"`, `"[FTS+PIN]"`, `"[BM25+SEM]"`.
     pub fn label(&self) -> String {
-        let mut parts: Vec<&str> = Vec::with_capacity(4);
+        let mut parts: Vec<&str> = Vec::with_capacity(7);
         if self.fts_rank.is_some() {
             parts.push("FTS");
         }
+        if self.sparse_score.is_some() {
+            parts.push("BM25");
+        }
         if self.semantic_distance.is_some() {
             parts.push("SEM");
         }
         if self.hq_semantic_distance.is_some() {
             parts.push("HQ");
         }
+        if self.profile_distance.is_some() {
+            parts.push("PROF");
+        }
         if self.rerank_score.is_some() {
             parts.push("RR");
         }
+        if self.pinned {
+            parts.push("PIN");
+        }
         if parts.is_empty() {
             "[?]".to_string()
         } else {
@@ -262,6 +381,8 @@ struct ChunkMerge {
     semantic_distance: Option<f32>,
     /// Cosine distance, if this chunk was found by 4096-dim HQ semantic ANN.
     hq_semantic_distance: Option<f32>,
+    /// BM25 score, if this chunk was found by the sparse keyword path.
+    sparse_score: Option<f32>,
 }
 
 /// Per-node accumulator produced by grouping chunk-level RRF scores.
@@ -275,8 +396,14 @@ struct NodeAccumulator {
     best_semantic_distance: Option<f32>,
     /// Best (lowest) 4096-dim HQ semantic distance among the node's matching chunks.
     best_hq_semantic_distance: Option<f32>,
+    /// Best (highest) BM25 score among the node's matching chunks.
+    best_sparse_score: Option<f32>,
+    /// Cosine distance of the node's profile embedding, if it matched.
+    profile_distance: Option<f32>,
     /// Number of distinct chunks that contributed to this node's score.
     matching_chunk_count: usize,
+    /// Whether `pin_boost` was applied.
+    pinned: bool,
 }
 
 // ── Main entry point ──────────────────────────────────────────────────────────
@@ -297,11 +424,16 @@ struct NodeAccumulator {
 ///
 /// # Algorithm
 ///
+/// 0. **Preprocess** — [`preprocess_query`] normalises, spell-corrects, and
+///    synonym-expands the query when `config.preprocess` is set.  Falls back
+///    to the verbatim query (with a warning) if the vocabulary lookup fails.
 /// 1. **FTS5** — `graph.search_chunks_fts(query, config.fts_limit)`.
 ///    Skipped when `alpha == 1.0`.
 /// 2. **Embed** — `queue.embed(query)` to obtain the query vector.
 ///    Skipped when `alpha == 0.0` or no embedding worker is registered.
-/// 3. **Semantic ANN** — `graph.search_chunks_semantic(&vec, config.semantic_limit)`.
+/// 3. **Semantic ANN** — `graph.search_chunks_semantic(&vec, config.semantic_limit)`,
+///    plus `graph.search_profiles_semantic` over object profile embeddings so
+///    nodes with structured data but no matching prose are still found.
 ///    Skipped when step 2 was skipped or failed.
 /// 4. **RRF merge** — deduplicate chunks by `chunk_id`, sum RRF scores from
 ///    both paths.
@@ -313,7 +445,8 @@ struct NodeAccumulator {
 ///    text chunks, all edges, and connected node summaries from the graph.
 /// 7. **Rerank** (optional) — `queue.rerank(query, docs, top_n)` where each
 ///    document is the concatenated chunk content of one node.  Reorders the
-///    results by cross-encoder score.
+///    `config.rerank_candidates` hydrated nodes by cross-encoder score and
+///    keeps the best `config.limit`.
 ///
 /// # Returns
 ///
@@ -332,15 +465,37 @@ pub async fn search_hybrid(
     config: &HybridSearchConfig,
 ) -> Result<Vec<NodeSearchResult>> {
     tracing::Span::current().record("query", query);
+    let mut op = trace_operation("search", "hybrid");
 
     let alpha = config.alpha.clamp(0.0, 1.0);
 
+    // ── Stage 0: Query preprocessing ──────────────────────────────────────────
+    let prepared = match &config.preprocess {
+        None => PreparedQuery::verbatim(query),
+        Some(pre) => match preprocess_query(graph, query, pre) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Query preprocessing failed — searching with the query as typed: {e}");
+                PreparedQuery::verbatim(query)
+            }
+        },
+    };
+    if !prepared.corrections.is_empty() || !prepared.expansions.is_empty() {
+        debug!(
+            corrections = ?prepared.corrections,
+            expansions = ?prepared.expansions,
+            "Query preprocessed to {:?}",
+            prepared.text
+        );
+    }
+    let semantic_query = prepared.semantic_text();
+
     // ── Stage 1: FTS5 search (sync, sub-millisecond) ──────────────────────────
     // Always run first — it is instant and does not need the embedding RTT.
     // Skip only when alpha == 1.0 (pure semantic requested).
 
     let fts_results = if alpha < 1.0 {
-        match fts5_sanitize(query) {
+        match prepared.fts_query.as_deref() {
             None => {
                 debug!("FTS5 stage skipped — query contained no FTS5-safe tokens");
                 Vec::new()
@@ -350,7 +505,7 @@ pub async fn search_hybrid(
                     "Running FTS5 search (sanitised query: {:?}, limit {})",
                     fts_query, config.fts_limit
                 );
-                graph.search_chunks_fts(&fts_query, config.fts_limit)?
+                graph.search_chunks_fts(fts_query, config.fts_limit)?
             }
         }
     } else {
@@ -358,28 +513,57 @@ pub async fn search_hybrid(
         Vec::new()
     };
 
+    // ── Stage 1b: BM25 sparse keyword search ──────────────────────────────────
+    // Any-term match ranked by BM25; runs whenever it has weight, since
+    // keyword evidence for rare names is what the dense paths lack.
+
+    let sparse_results = if config.sparse_weight > 0.0 {
+        let terms = sparse_terms(&prepared);
+        match graph.search_chunks_bm25(&terms, config.sparse_limit) {
+            Ok(results) => results,
+            Err(e) => {
+                warn!("BM25 sparse search failed — skipping sparse path: {e}");
+                record_error("search", format!("BM25 sparse search failed: {e}"));
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
     // ── Stage 2+3: Embed query then ANN search ────────────────────────────────
     // Skip when alpha == 0.0 (pure FTS) or when no embedding worker exists.
 
-    let semantic_results = if alpha > 0.0 && queue.has_embedding() {
+    let (semantic_results, profile_results) = if alpha > 0.0 && queue.has_embedding() {
         debug!("Embedding query for semantic ANN search");
-        match queue.embed(query).await {
+        match queue.embed(&semantic_query).await {
             Err(e) => {
                 warn!("Query embedding failed — falling back to FTS-only results: {e}");
-                Vec::new()
+                record_error("search", format!("Query embedding failed: {e}"));
+                (Vec::new(), Vec::new())
             }
             Ok(query_vec) => {
                 debug!(
                     "Running semantic ANN search (limit {})",
                     config.semantic_limit
                 );
-                match graph.search_chunks_semantic(&query_vec, config.semantic_limit) {
+                let chunks = match graph.search_chunks_semantic(&query_vec, config.semantic_limit) {
                     Ok(results) => results,
                     Err(e) => {
                         warn!("Semantic ANN search failed — falling back to FTS results: {e}");
+                        record_error("search", format!("Semantic ANN search failed: {e}"));
                         Vec::new()
                     }
-                }
+                };
+                let profiles = match graph.search_profiles_semantic(&query_vec, config.semantic_limit) {
+                    Ok(results) => results,
+                    Err(e) => {
+                        warn!("Profile ANN search failed — skipping profile path: {e}");
+                        record_error("search", format!("Profile ANN search failed: {e}"));
+                        Vec::new()
+                    }
+                };
+                (chunks, profiles)
             }
         }
     } else {
@@ -392,7 +576,7 @@ pub async fn search_hybrid(
         } else {
             debug!("Semantic stage skipped (alpha = 0.0)");
         }
-        Vec::new()
+        (Vec::new(), Vec::new())
     };
 
     // ── Stage 2b+3b: HQ embed query then HQ ANN search ───────────────────────
@@ -410,9 +594,10 @@ pub async fn search_hybrid(
             }
             Some(hq_q) => {
                 debug!("Embedding query for HQ semantic ANN search");
-                match hq_q.embed(query).await {
+                match hq_q.embed(&semantic_query).await {
                     Err(e) => {
                         warn!("HQ query embedding failed — skipping HQ path: {e}");
+                        record_error("search", format!("HQ query embedding failed: {e}"));
                         Vec::new()
                     }
                     Ok(query_vec) => {
@@ -424,6 +609,10 @@ pub async fn search_hybrid(
                             Ok(results) => results,
                             Err(e) => {
                                 warn!("HQ semantic ANN search failed — skipping HQ path: {e}");
+                                record_error(
+                                    "search",
+                                    format!("HQ semantic ANN search failed: {e}"),
+                                );
                                 Vec::new()
                             }
                         }
@@ -436,10 +625,12 @@ pub async fn search_hybrid(
     };
 
     debug!(
-        "Candidate pool: {} FTS chunks, {} semantic chunks, {} HQ semantic chunks",
+        "Candidate pool: {} FTS chunks, {} BM25 chunks, {} semantic chunks, {} HQ semantic chunks, {} profiles",
         fts_results.len(),
+        sparse_results.len(),
         semantic_results.len(),
-        hq_semantic_results.len()
+        hq_semantic_results.len(),
+        profile_results.len()
     );
 
     // ── Diagnostic: Stage 1 (FTS5) results ──────────────────────────────────
@@ -496,11 +687,28 @@ pub async fn search_hybrid(
                 fts_rank: None,
                 semantic_distance: None,
                 hq_semantic_distance: None,
+                sparse_score: None,
             });
         entry.rrf_score += score;
         entry.fts_rank = Some(rank);
     }
 
+    for (rank, (chunk_id, obj_id, _content, bm25)) in sparse_results.into_iter().enumerate() {
+        let score = config.sparse_weight / (K + rank as f32);
+        let entry = chunk_merge
+            .entry(chunk_id.hyphenated().to_string())
+            .or_insert_with(|| ChunkMerge {
+                object_id_str: obj_id.hyphenated().to_string(),
+                rrf_score: 0.0,
+                fts_rank: None,
+                semantic_distance: None,
+                hq_semantic_distance: None,
+                sparse_score: None,
+            });
+        entry.rrf_score += score;
+        entry.sparse_score = Some(bm25);
+    }
+
     for (rank, (chunk_id, obj_id, _content, distance)) in semantic_results.into_iter().enumerate() {
         let score = alpha / (K + rank as f32);
         let entry = chunk_merge
@@ -511,6 +719,7 @@ pub async fn search_hybrid(
                 fts_rank: None,
                 semantic_distance: None,
                 hq_semantic_distance: None,
+                sparse_score: None,
             });
         entry.rrf_score += score;
         entry.semantic_distance = Some(distance);
@@ -526,6 +735,7 @@ pub async fn search_hybrid(
                 fts_rank: None,
                 semantic_distance: None,
                 hq_semantic_distance: None,
+                sparse_score: None,
             });
         entry.rrf_score += score;
         entry.hq_semantic_distance = Some(distance);
@@ -571,6 +781,17 @@ pub async fn search_hybrid(
                     .map_or(dist, |prev| prev.min(dist)),
             );
         }
+        if let Some(bm25) = cm.sparse_score {
+            acc.best_sparse_score = Some(acc.best_sparse_score.map_or(bm25, |prev| prev.max(bm25)));
+        }
+    }
+
+    // Profile hits score at node level directly, on the same RRF scale as
+    // the 768-dim chunk path.
+    for (rank, (obj_id, _content, distance)) in profile_results.into_iter().enumerate() {
+        let acc = node_accum.entry(obj_id.hyphenated().to_string()).or_default();
+        acc.total_score += alpha / (K + rank as f32);
+        acc.profile_distance = Some(distance);
     }
 
     // ── Diagnostic: Stage 5 (Node aggregation) before sort ─────────────────
@@ -586,19 +807,76 @@ pub async fn search_hybrid(
         debug!("{buf}");
     }
 
-    // Sort nodes by descending aggregated score and cap at config.limit.
+    // Drop archived nodes and nodes outside the requested lifecycle states
+    // before ranking so they cannot consume result slots.
+    if !config.include_archived {
+        let archived = graph.archived_ids()?;
+        if !archived.is_empty() {
+            node_accum.retain(|obj_id_str, _| {
+                ObjectId::parse_str(obj_id_str).map_or(true, |id| !archived.contains(&id))
+            });
+        }
+    }
+    if let Some(allowed) = &config.lifecycles {
+        let mut kept = HashMap::with_capacity(node_accum.len());
+        for (obj_id_str, acc) in node_accum {
+            let lifecycle = graph.get_object_lifecycle(parse_uuid(&obj_id_str, "object")?)?;
+            if lifecycle.is_some_and(|l| allowed.contains(&l)) {
+                kept.insert(obj_id_str, acc);
+            }
+        }
+        debug!(
+            "{} nodes remain after lifecycle filter {:?}",
+            kept.len(),
+            allowed
+        );
+        node_accum = kept;
+    }
+
+    if config.player_view {
+        let mut kept = HashMap::with_capacity(node_accum.len());
+        for (obj_id_str, acc) in node_accum {
+            let visible = graph
+                .get_object(parse_uuid(&obj_id_str, "object")?)?
+                .is_some_and(|o| graph.is_player_visible(&o));
+            if visible {
+                kept.insert(obj_id_str, acc);
+            }
+        }
+        node_accum = kept;
+    }
+
+    // Boost pinned nodes before ranking so they win close calls for a slot.
+    if let Some(user) = &config.pinboard {
+        let pinned = graph.pinned_ids(user)?;
+        for (obj_id_str, acc) in node_accum.iter_mut() {
+            if pinned.contains(&parse_uuid(obj_id_str, "object")?) {
+                acc.total_score *= config.pin_boost;
+                acc.pinned = true;
+            }
+        }
+    }
+
+    // Sort nodes by descending aggregated score and cap at config.limit, or
+    // at the wider reranking pool when a reranker will pick the final set.
+    let will_rerank = config.rerank && queue.has_reranking();
+    let pool_size = if will_rerank {
+        config.limit.max(config.rerank_candidates)
+    } else {
+        config.limit
+    };
     let mut ranked_nodes: Vec<(String, NodeAccumulator)> = node_accum.into_iter().collect();
     ranked_nodes.sort_by(|a, b| {
         b.1.total_score
             .partial_cmp(&a.1.total_score)
             .unwrap_or(std::cmp::Ordering::Equal)
     });
-    ranked_nodes.truncate(config.limit);
+    ranked_nodes.truncate(pool_size);
 
     debug!(
         "{} nodes after aggregation (capped at {}), matching chunks per node: [{}]",
         ranked_nodes.len(),
-        config.limit,
+        pool_size,
         ranked_nodes
             .iter()
             .map(|(_, acc)| acc.matching_chunk_count.to_string())
@@ -609,7 +887,7 @@ pub async fn search_hybrid(
     // ── Diagnostic: Stage 5 (after sort + truncate) ───────────────────────────
     {
         use std::fmt::Write as _;
-        let mut buf = format!("── HYBRID STAGE 5: After sort + truncate to {} ──\n", config.limit);
+        let mut buf = format!("── HYBRID STAGE 5: After sort + truncate to {pool_size} ──\n");
         for (obj_id, acc) in &ranked_nodes {
             let _ = writeln!(buf, "  KEPT obj={obj_id} score={:.6} chunks={} best_fts_rank={:?} best_sem_dist={:?} best_hq_dist={:?}",
                 acc.total_score, acc.matching_chunk_count, acc.best_fts_rank, acc.best_semantic_distance, acc.best_hq_semantic_distance);
@@ -683,6 +961,9 @@ pub async fn search_hybrid(
                 semantic_distance: acc.best_semantic_distance,
                 hq_semantic_distance: acc.best_hq_semantic_distance,
                 rerank_score: None,
+                sparse_score: acc.best_sparse_score,
+                profile_distance: acc.profile_distance,
+                pinned: acc.pinned,
             },
         });
     }
@@ -699,9 +980,13 @@ pub async fn search_hybrid(
         debug!("{buf}");
     }
 
+    if config.player_view {
+        results = graph.redact_search_results(results)?;
+    }
+
     // ── Stage 7: Optional reranking ───────────────────────────────────────────
 
-    let do_rerank = config.rerank && queue.has_reranking() && !results.is_empty();
+    let do_rerank = will_rerank && !results.is_empty();
 
     if config.rerank && !queue.has_reranking() {
         info!(
@@ -751,10 +1036,12 @@ pub async fn search_hybrid(
             debug!("{buf}");
         }
 
-        match queue.rerank(query, documents, Some(results.len())).await {
+        match queue.rerank(&prepared.text, documents, Some(results.len())).await {
             Err(e) => {
                 warn!("Reranking failed — returning RRF-scored results instead: {e}");
-                // Fall through — results already in RRF order.
+                record_error("search", format!("Reranking failed: {e}"));
+                // Fall through — results already in RRF order; the
+                // candidate pool is cut back to `limit` below.
             }
             Ok(ranked) => {
                 // Apply rerank scores and re-sort.
@@ -775,6 +1062,7 @@ pub async fn search_hybrid(
                         .partial_cmp(&a.score)
                         .unwrap_or(std::cmp::Ordering::Equal)
                 });
+                results.truncate(config.limit);
 
                 // ── Diagnostic: Stage 7 (Rerank output) ─────────────────────
                 {
@@ -790,12 +1078,15 @@ pub async fn search_hybrid(
                 }
 
                 debug!("Returning {} reranked node results", results.len());
+                op.set_items(results.len());
                 return Ok(results);
             }
         }
     }
 
+    results.truncate(config.limit);
     debug!("Returning {} RRF-scored node results", results.len());
+    op.set_items(results.len());
     Ok(results)
 }
 
@@ -806,6 +1097,23 @@ fn parse_uuid(s: &str, label: &str) -> Result<ObjectId> {
         .map_err(|e| anyhow::anyhow!("Invalid {label} UUID '{s}' in hybrid search result: {e}"))
 }
 
+/// Distinct lowercase terms of the prepared query and its synonym
+/// expansions, for the BM25 sparse path.
+fn sparse_terms(prepared: &PreparedQuery) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for text in std::iter::once(&prepared.text).chain(&prepared.expansions) {
+        let Some(sanitized) = sanitize::fts5_sanitize(text) else {
+            continue;
+        };
+        for term in sanitized.split(' ').map(str::to_lowercase) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+    }
+    terms
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -817,8 +1125,9 @@ mod tests {
     use tempfile::TempDir;
 
     use crate::ai::embeddings::{EmbeddingModelInfo, EmbeddingProvider, EmbeddingProviderType};
-    use crate::lemonade::{BuiltProvider, Capability, ProviderSlot};
+    use crate::lemonade::{BuiltProvider, Capability, LemonadeRerankProvider, ProviderSlot};
     use crate::queue::InferenceQueueBuilder;
+    use crate::test_helpers::create_test_graph;
     use crate::types::ChunkType;
     use crate::{KnowledgeGraph, ObjectBuilder};
 
@@ -865,8 +1174,7 @@ mod tests {
     /// Build a graph pre-populated with a handful of objects, edges, chunks,
     /// and mock embeddings so every search path has something to find.
     fn make_graph_with_data() -> (KnowledgeGraph, TempDir) {
-        let tmp = TempDir::new().unwrap();
-        let graph = KnowledgeGraph::new(tmp.path()).unwrap();
+        let (graph, tmp) = create_test_graph();
 
         let wizard_id = ObjectBuilder::character("Gandalf".to_string())
             .with_description(
@@ -972,6 +1280,81 @@ mod tests {
         InferenceQueueBuilder::new().build()
     }
 
+    /// Serve `POST /reranking` on a local port, answering with the scores
+    /// `respond` gives the request body (`None` answers 500), and return
+    /// the base URL.  Stands in for a Lemonade reranking worker.
+    async fn stub_reranker(
+        respond: fn(&serde_json::Value) -> Option<serde_json::Value>,
+    ) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body_start = loop {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break None;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break Some(i + 4);
+                    }
+                };
+                let Some(body_start) = body_start else {
+                    continue;
+                };
+                let head = String::from_utf8_lossy(&buf[..body_start]).to_lowercase();
+                let length: usize = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(0);
+                while buf.len() < body_start + length {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+                let request: serde_json::Value =
+                    serde_json::from_slice(&buf[body_start..]).unwrap_or_default();
+                let (status, body) = match respond(&request) {
+                    Some(json) => ("200 OK", json.to_string()),
+                    None => ("500 Internal Server Error", String::new()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}/api/v1")
+    }
+
+    /// The embedding mock plus a reranking worker at `base_url`.
+    fn make_rerank_queue(base_url: &str) -> InferenceQueue {
+        InferenceQueueBuilder::new()
+            .with_provider(BuiltProvider {
+                name: "mock-embed".to_string(),
+                capability: Capability::Embedding,
+                provider: ProviderSlot::Embedding(Arc::new(MockEmbeddingProvider)),
+                weight: 100,
+            })
+            .with_provider(BuiltProvider {
+                name: "stub-rerank".to_string(),
+                capability: Capability::Reranking,
+                provider: ProviderSlot::Rerank(LemonadeRerankProvider::new(base_url, "stub")),
+                weight: 100,
+            })
+            .build()
+    }
+
     // ── Tests ─────────────────────────────────────────────────────────────────
 
     #[tokio::test]
@@ -1120,6 +1503,100 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_hybrid_lifecycle_filter_excludes_drafts() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_embed_queue();
+
+        let shire = graph
+            .find_by_name("location", "The Shire")
+            .unwrap()
+            .into_iter()
+            .next()
+            .expect("The Shire fixture");
+        graph.set_object_lifecycle(shire.id, Lifecycle::Draft).unwrap();
+        let frodo = graph
+            .find_by_name("character", "Frodo")
+            .unwrap()
+            .into_iter()
+            .next()
+            .expect("Frodo fixture");
+        graph
+            .add_text_chunk(
+                frodo.id,
+                "Frodo is a hobbit of the Shire.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap();
+
+        let config = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            limit: 4,
+            lifecycles: Some(vec![Lifecycle::Canon]),
+            ..Default::default()
+        };
+
+        let results = search_hybrid(&graph, &queue, None, "hobbit", &config)
+            .await
+            .unwrap();
+
+        assert!(!results.is_empty(), "Frodo is canon and should still match");
+        assert!(
+            results.iter().all(|r| r.node.id != shire.id),
+            "Draft node leaked into a canon-only search"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_player_view_hides_and_redacts() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_embed_queue();
+
+        let gm_only = ObjectBuilder::location("Hidden Hobbit Hole".to_string())
+            .with_property(crate::VISIBILITY_KEY.to_string(), "gm".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .add_text_chunk(
+                gm_only,
+                "A hobbit hideout.".to_string(),
+                ChunkType::Description,
+            )
+            .unwrap();
+        let frodo = graph
+            .find_by_name("character", "Frodo")
+            .unwrap()
+            .into_iter()
+            .next()
+            .expect("Frodo fixture");
+        graph
+            .add_text_chunk(
+                frodo.id,
+                "GM: this hobbit is secretly a spy for Sauron.".to_string(),
+                ChunkType::UserNote,
+            )
+            .unwrap();
+
+        let config = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            limit: 10,
+            player_view: true,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "hobbit", &config)
+            .await
+            .unwrap();
+
+        assert!(results.iter().any(|r| r.node.id == frodo.id));
+        assert!(results.iter().all(|r| r.node.id != gm_only));
+        assert!(results
+            .iter()
+            .flat_map(|r| &r.chunks)
+            .all(|c| !c.content.contains("Sauron")));
+    }
+
     #[tokio::test]
     async fn test_hybrid_semantic_only_mode() {
         let (graph, _tmp) = make_graph_with_data();
@@ -1163,6 +1640,7 @@ mod tests {
             rerank: false,
             limit: 10,
             hq_semantic_boost: 3.0,
+            ..Default::default()
         };
 
         let results = search_hybrid(&graph, &queue, None, "hobbit ring", &config)
@@ -1192,6 +1670,7 @@ mod tests {
             rerank: false,
             limit: 10,
             hq_semantic_boost: 3.0,
+            ..Default::default()
         };
 
         let results = search_hybrid(&graph, &queue, None, "hobbit ring journey", &config)
@@ -1220,6 +1699,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_hybrid_preprocessing_corrects_misspelled_names() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_queue_no_workers();
+
+        let verbatim = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            preprocess: None,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "Gandolf staff", &verbatim)
+            .await
+            .unwrap();
+        assert!(results.is_empty(), "Misspelled name should miss without preprocessing");
+
+        let config = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            preprocess: Some(QueryPreprocessing::default()),
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "Gandolf staff", &config)
+            .await
+            .unwrap();
+        assert_eq!(results.first().map(|r| r.node.name.as_str()), Some("Gandalf"));
+    }
+
     #[tokio::test]
     async fn test_hybrid_graceful_no_embedding_worker() {
         // When no embedding worker is registered the function must degrade to
@@ -1327,8 +1834,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_hybrid_empty_graph_returns_empty() {
-        let tmp = TempDir::new().unwrap();
-        let graph = KnowledgeGraph::new(tmp.path()).unwrap();
+        let (graph, _tmp) = create_test_graph();
         let queue = make_embed_queue();
 
         let config = HybridSearchConfig {
@@ -1346,6 +1852,64 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_pinned_nodes_are_boosted() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_queue_no_workers();
+        let city = graph.find_by_name_only("Minas Tirith").unwrap()[0].id;
+        graph.pin_object("gm", city).unwrap();
+
+        let mut config = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            limit: 10,
+            preprocess: None,
+            ..Default::default()
+        };
+        let unpinned = search_hybrid(&graph, &queue, None, "the", &config)
+            .await
+            .unwrap();
+        assert!(unpinned.len() > 1);
+        assert!(unpinned.iter().all(|r| !r.sources.pinned));
+
+        config.pinboard = Some("gm".to_string());
+        config.pin_boost = 1000.0;
+        let pinned = search_hybrid(&graph, &queue, None, "the", &config)
+            .await
+            .unwrap();
+        assert_eq!(pinned[0].node.id, city);
+        assert!(pinned[0].sources.pinned);
+        assert!(pinned[1..].iter().all(|r| !r.sources.pinned));
+    }
+
+    #[tokio::test]
+    async fn test_sparse_path_finds_rare_name_when_fts_misses() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_queue_no_workers();
+
+        // "Vraskgut" is in no chunk, so the all-terms FTS5 stage finds nothing.
+        let keyword_only = HybridSearchConfig {
+            alpha: 0.0,
+            rerank: false,
+            preprocess: None,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "Gandalf Vraskgut", &keyword_only)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+
+        let config = HybridSearchConfig {
+            sparse_weight: 1.0,
+            ..keyword_only
+        };
+        let results = search_hybrid(&graph, &queue, None, "Gandalf Vraskgut", &config)
+            .await
+            .unwrap();
+        assert_eq!(results[0].node.name, "Gandalf");
+        assert_eq!(results[0].sources.label(), "[BM25]");
+    }
+
     #[tokio::test]
     async fn test_search_sources_label() {
         let fts_only = SearchSources {
@@ -1380,9 +1944,33 @@ mod tests {
             semantic_distance: Some(0.05),
             hq_semantic_distance: Some(0.03),
             rerank_score: Some(0.98),
+            sparse_score: None,
+            profile_distance: None,
+            pinned: false,
         };
         assert_eq!(all_four.label(), "[FTS+SEM+HQ+RR]");
 
+        let with_profile = SearchSources {
+            semantic_distance: Some(0.2),
+            profile_distance: Some(0.1),
+            ..Default::default()
+        };
+        assert_eq!(with_profile.label(), "[SEM+PROF]");
+
+        let pinned = SearchSources {
+            fts_rank: Some(1),
+            pinned: true,
+            ..Default::default()
+        };
+        assert_eq!(pinned.label(), "[FTS+PIN]");
+
+        let sparse = SearchSources {
+            sparse_score: Some(4.2),
+            semantic_distance: Some(0.2),
+            ..Default::default()
+        };
+        assert_eq!(sparse.label(), "[BM25+SEM]");
+
         let empty = SearchSources::default();
         assert_eq!(empty.label(), "[?]");
     }
@@ -1394,7 +1982,102 @@ mod tests {
         assert_eq!(c.fts_limit, 20);
         assert_eq!(c.semantic_limit, 20);
         assert!(c.rerank);
+        assert_eq!(c.rerank_candidates, 10);
         assert_eq!(c.limit, 3);
+        assert!(c.preprocess.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rerank_pool_not_returned_without_reranker() {
+        let (graph, _tmp) = make_graph_with_data();
+        let queue = make_embed_queue();
+
+        // Reranking requested but unavailable: the wider candidate pool is
+        // never hydrated into the results.
+        let config = HybridSearchConfig {
+            rerank: true,
+            rerank_candidates: 50,
+            limit: 2,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "hobbit ring wizard", &config)
+            .await
+            .unwrap();
+        assert!(!results.is_empty());
+        assert!(results.len() <= 2);
+        assert!(results.iter().all(|r| r.sources.rerank_score.is_none()));
+    }
+
+    /// Limit used by the stub-reranker tests; the stub scores the candidate
+    /// at this pool index highest, i.e. the one ranked just past the cut.
+    const RERANK_LIMIT: usize = 2;
+
+    #[tokio::test]
+    async fn test_reranker_promotes_candidate_beyond_limit() {
+        let (graph, _tmp) = make_graph_with_data();
+        let base_url = stub_reranker(|request| {
+            let documents = request["documents"].as_array()?.len();
+            let results: Vec<serde_json::Value> = (0..documents)
+                .map(|index| {
+                    let score = if index == RERANK_LIMIT {
+                        1.0
+                    } else {
+                        0.1 / (index + 1) as f32
+                    };
+                    serde_json::json!({ "index": index, "relevance_score": score })
+                })
+                .collect();
+            Some(serde_json::json!({ "results": results }))
+        })
+        .await;
+        let queue = make_rerank_queue(&base_url);
+        let query = "hobbit ring wizard shire";
+
+        // RRF order of the whole pool, without reranking.
+        let rrf = HybridSearchConfig {
+            rerank: false,
+            limit: 10,
+            ..Default::default()
+        };
+        let baseline = search_hybrid(&graph, &queue, None, query, &rrf).await.unwrap();
+        assert!(
+            baseline.len() > RERANK_LIMIT,
+            "need a candidate past the limit, got {}",
+            baseline.len()
+        );
+
+        let config = HybridSearchConfig {
+            rerank: true,
+            rerank_candidates: 10,
+            limit: RERANK_LIMIT,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, query, &config)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), RERANK_LIMIT);
+        assert_eq!(results[0].node.id, baseline[RERANK_LIMIT].node.id);
+        assert_eq!(results[0].sources.rerank_score, Some(1.0));
+        assert!(results.iter().all(|r| r.sources.rerank_score.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_reranker_error_falls_back_to_limit() {
+        let (graph, _tmp) = make_graph_with_data();
+        let base_url = stub_reranker(|_| None).await;
+        let queue = make_rerank_queue(&base_url);
+
+        let config = HybridSearchConfig {
+            rerank: true,
+            rerank_candidates: 10,
+            limit: RERANK_LIMIT,
+            ..Default::default()
+        };
+        let results = search_hybrid(&graph, &queue, None, "hobbit ring wizard shire", &config)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), RERANK_LIMIT);
+        assert!(results.iter().all(|r| r.sources.rerank_score.is_none()));
     }
 
     #[tokio::test]
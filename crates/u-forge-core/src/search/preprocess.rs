@@ -0,0 +1,417 @@
+//! Query preprocessing — normalisation, spell-correction, and synonym
+//! expansion applied before the FTS5 and semantic stages.
+//!
+//! GMs type fast mid-session.  [`preprocess_query`] lowercases and trims the
+//! query, snaps misspelled words to the closest word in the project's node
+//! names ("gandlaf" → "gandalf"), and expands synonyms so that "the company"
+//! also matches chunks that only say "fellowship of the ring".
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::KnowledgeGraph;
+
+use super::sanitize::fts5_sanitize;
+
+/// Words shorter than this are never spell-corrected — short words sit one
+/// edit away from too many others for a correction to be trustworthy.
+const MIN_CORRECTABLE_CHARS: usize = 4;
+
+/// Words at least this long may be corrected by up to two edits; shorter
+/// words by one.
+const TWO_EDIT_MIN_CHARS: usize = 8;
+
+/// Options for [`preprocess_query`].
+#[derive(Debug, Clone)]
+pub struct QueryPreprocessing {
+    /// Lowercase the query.  FTS5 matching is case-insensitive already; this
+    /// keeps a typed `OR` / `NOT` from being read as an FTS5 operator and
+    /// gives the embedding model a consistent input.
+    pub lowercase: bool,
+
+    /// Replace words that occur in neither chunk text nor node names with the
+    /// closest node-name word (≤1 edit, ≤2 for words of 8+ characters).
+    /// Words that match nothing close enough, or whose closest match is a
+    /// tie between several words, are left alone.
+    pub spell_correct: bool,
+
+    /// Synonym phrases keyed by lowercase term or phrase.  Every key found in
+    /// the query is OR-ed with its synonyms in the FTS5 query, and the
+    /// synonyms are appended to the text sent to the embedding model.
+    ///
+    /// Added on top of the project glossary when `use_glossary` is set.
+    pub synonyms: HashMap<String, Vec<String>>,
+
+    /// Merge [`Glossary::synonym_map`](crate::Glossary::synonym_map) into
+    /// `synonyms`.
+    pub use_glossary: bool,
+}
+
+impl Default for QueryPreprocessing {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            spell_correct: true,
+            synonyms: HashMap::new(),
+            use_glossary: true,
+        }
+    }
+}
+
+/// A query after [`preprocess_query`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreparedQuery {
+    /// Normalised, spell-corrected query.  Used for reranking and as the base
+    /// of [`semantic_text`](Self::semantic_text).
+    pub text: String,
+
+    /// `(typed, corrected)` word pairs applied by spell-correction.
+    pub corrections: Vec<(String, String)>,
+
+    /// Synonym phrases added by expansion, in the order they were matched.
+    pub expansions: Vec<String>,
+
+    /// FTS5 `MATCH` expression, or `None` when the query has no FTS5-safe
+    /// tokens.
+    pub fts_query: Option<String>,
+}
+
+impl PreparedQuery {
+    /// The query exactly as typed — no normalisation, correction, or
+    /// expansion.  Used when preprocessing is disabled.
+    pub fn verbatim(query: &str) -> Self {
+        Self {
+            text: query.to_string(),
+            corrections: Vec::new(),
+            expansions: Vec::new(),
+            fts_query: fts5_sanitize(query),
+        }
+    }
+
+    /// Text to embed for semantic search: [`text`](Self::text) followed by
+    /// any synonym expansions.
+    pub fn semantic_text(&self) -> String {
+        if self.expansions.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{} ({})", self.text, self.expansions.join(", "))
+        }
+    }
+}
+
+/// Normalise, spell-correct, and synonym-expand `query`.
+///
+/// Synonyms come from `config.synonyms` plus, when `config.use_glossary` is
+/// set, the project glossary.  Words of a synonym key are never
+/// spell-corrected, so an alias expands even when it is close to a name.
+///
+/// Spell-correction consults the graph: a word is left alone when it occurs
+/// in any chunk ([`KnowledgeGraph::is_indexed_term`]) or node name, and is
+/// otherwise replaced by the nearest word from
+/// [`KnowledgeGraph::name_vocabulary`].  The vocabulary is only loaded when
+/// some word actually needs correcting.
+pub fn preprocess_query(
+    graph: &KnowledgeGraph,
+    query: &str,
+    config: &QueryPreprocessing,
+) -> Result<PreparedQuery> {
+    let mut text = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if config.lowercase {
+        text = text.to_lowercase();
+    }
+
+    let glossary_synonyms;
+    let synonyms = if config.use_glossary {
+        let mut merged = graph.glossary()?.synonym_map();
+        for (key, alts) in &config.synonyms {
+            merged.entry(key.to_lowercase()).or_default().extend(alts.iter().cloned());
+        }
+        glossary_synonyms = merged;
+        &glossary_synonyms
+    } else {
+        &config.synonyms
+    };
+
+    // Expansion wins over correction: a glossary alias that is not a node
+    // name must reach `build_fts_query` as typed, not snapped to a name.
+    let synonym_words: HashSet<String> = synonyms
+        .keys()
+        .flat_map(|key| key.split_whitespace())
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect();
+
+    let mut corrections = Vec::new();
+    if config.spell_correct {
+        let mut vocabulary: Option<(Vec<String>, HashSet<String>)> = None;
+        let mut words: Vec<String> = Vec::new();
+        for word in text.split(' ') {
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+            let needs_check = core.chars().count() >= MIN_CORRECTABLE_CHARS
+                && core.chars().all(char::is_alphanumeric);
+            let lower = core.to_lowercase();
+            if !needs_check || synonym_words.contains(&lower) || graph.is_indexed_term(&lower)? {
+                words.push(word.to_string());
+                continue;
+            }
+            if vocabulary.is_none() {
+                let list = graph.name_vocabulary()?;
+                let set = list.iter().cloned().collect();
+                vocabulary = Some((list, set));
+            }
+            let (list, set) = vocabulary.as_ref().expect("vocabulary loaded above");
+            match correct_word(&lower, list, set) {
+                Some(fixed) => {
+                    corrections.push((core.to_string(), fixed.clone()));
+                    words.push(word.replacen(core, &fixed, 1));
+                }
+                None => words.push(word.to_string()),
+            }
+        }
+        text = words.join(" ");
+    }
+
+    let (fts_query, expansions) = build_fts_query(&text, synonyms);
+    Ok(PreparedQuery {
+        text,
+        corrections,
+        expansions,
+        fts_query,
+    })
+}
+
+/// The one vocabulary word closest to `word`, or `None` when `word` is
+/// already a vocabulary word, nothing is within the edit budget, or several
+/// words tie for closest — a guess between them could turn a correctly
+/// spelled word into the wrong name.
+fn correct_word(word: &str, vocabulary: &[String], known: &HashSet<String>) -> Option<String> {
+    if known.contains(word) {
+        return None;
+    }
+    let len = word.chars().count();
+    let budget = if len >= TWO_EDIT_MIN_CHARS { 2 } else { 1 };
+    let mut best: Option<(usize, &String)> = None;
+    let mut tied = false;
+    for v in vocabulary {
+        if v.chars().count().abs_diff(len) > budget {
+            continue;
+        }
+        let d = edit_distance(word, v);
+        match best {
+            _ if d > budget => {}
+            Some((b, _)) if d > b => {}
+            Some((b, w)) if d == b => tied |= w != v,
+            _ => {
+                best = Some((d, v));
+                tied = false;
+            }
+        }
+    }
+    best.filter(|_| !tied).map(|(_, v)| v.clone())
+}
+
+/// Levenshtein distance over `char`s.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = substitute.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Build the FTS5 expression for `text`, OR-ing each synonym key found in it
+/// with its synonyms.  Keys are matched on sanitised tokens, longest first,
+/// so "the company" wins over "company".  Returns the expression and the
+/// synonyms that were added.
+fn build_fts_query(
+    text: &str,
+    synonyms: &HashMap<String, Vec<String>>,
+) -> (Option<String>, Vec<String>) {
+    let Some(sanitized) = fts5_sanitize(text) else {
+        return (None, Vec::new());
+    };
+    let tokens: Vec<&str> = sanitized.split(' ').collect();
+
+    // Sanitised, lowercased key token lists, longest first.
+    let mut keys: Vec<(Vec<String>, &Vec<String>)> = synonyms
+        .iter()
+        .filter_map(|(key, syns)| {
+            let toks = fts5_sanitize(&key.to_lowercase())?;
+            Some((toks.split(' ').map(str::to_string).collect(), syns))
+        })
+        .collect();
+    keys.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut expansions: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let matched = keys.iter().find(|(key, _)| {
+            tokens.len() - i >= key.len()
+                && key
+                    .iter()
+                    .zip(&tokens[i..])
+                    .all(|(k, t)| k.as_str() == t.to_lowercase())
+        });
+        let Some((key, syns)) = matched else {
+            parts.push(tokens[i].to_string());
+            i += 1;
+            continue;
+        };
+
+        let mut alternatives = vec![format!("\"{}\"", tokens[i..i + key.len()].join(" "))];
+        for syn in syns.iter() {
+            if let Some(s) = fts5_sanitize(syn) {
+                let phrase = format!("\"{s}\"");
+                if !alternatives.contains(&phrase) {
+                    alternatives.push(phrase);
+                    expansions.push(syn.clone());
+                }
+            }
+        }
+        parts.push(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            format!("({})", alternatives.join(" OR "))
+        });
+        i += key.len();
+    }
+    (Some(parts.join(" ")), expansions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::{ChunkType, ObjectBuilder};
+    use tempfile::TempDir;
+
+    fn graph_with_gandalf() -> (KnowledgeGraph, TempDir) {
+        let (graph, tmp) = create_test_graph();
+        let id = ObjectBuilder::character("Gandalf the Grey".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .add_text_chunk(id, "A wizard of great power.".to_string(), ChunkType::Description)
+            .unwrap();
+        (graph, tmp)
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("gandalf", "gandalf"), 0);
+        assert_eq!(edit_distance("gandlf", "gandalf"), 1);
+        assert_eq!(edit_distance("gandolf", "gandalf"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_preprocess_normalises_and_corrects_names() {
+        let (graph, _tmp) = graph_with_gandalf();
+        let prepared =
+            preprocess_query(&graph, "  Where is   GANDOLF? ", &QueryPreprocessing::default())
+                .unwrap();
+        assert_eq!(prepared.text, "where is gandalf?");
+        assert_eq!(prepared.corrections, vec![("gandolf".to_string(), "gandalf".to_string())]);
+        assert_eq!(prepared.fts_query.as_deref(), Some("where is gandalf"));
+    }
+
+    #[test]
+    fn test_preprocess_leaves_indexed_and_short_words_alone() {
+        let (graph, _tmp) = graph_with_gandalf();
+        // "power" is in chunk text, "gry" is too short to correct, and
+        // "wizrd" has no node-name word within one edit.
+        let prepared =
+            preprocess_query(&graph, "power gry wizrd", &QueryPreprocessing::default()).unwrap();
+        assert!(prepared.corrections.is_empty());
+        assert_eq!(prepared.text, "power gry wizrd");
+    }
+
+    #[test]
+    fn test_preprocess_leaves_ambiguous_words_alone() {
+        let (graph, _tmp) = graph_with_gandalf();
+        for name in ["Bard", "Bird"] {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap();
+        }
+        // "bord" is one edit from both "bard" and "bird".
+        let prepared =
+            preprocess_query(&graph, "bord gandolf", &QueryPreprocessing::default()).unwrap();
+        assert_eq!(prepared.text, "bord gandalf");
+        assert_eq!(prepared.corrections, vec![("gandolf".to_string(), "gandalf".to_string())]);
+    }
+
+    #[test]
+    fn test_preprocess_expands_synonyms() {
+        let (graph, _tmp) = graph_with_gandalf();
+        let config = QueryPreprocessing {
+            synonyms: HashMap::from([(
+                "the company".to_string(),
+                vec!["Fellowship of the Ring".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let prepared = preprocess_query(&graph, "Who led the Company?", &config).unwrap();
+        assert_eq!(
+            prepared.fts_query.as_deref(),
+            Some("who led (\"the company\" OR \"Fellowship of the Ring\")")
+        );
+        assert_eq!(prepared.expansions, vec!["Fellowship of the Ring".to_string()]);
+        assert_eq!(
+            prepared.semantic_text(),
+            "who led the company? (Fellowship of the Ring)"
+        );
+    }
+
+    #[test]
+    fn test_preprocess_expands_glossary_terms() {
+        let (graph, _tmp) = graph_with_gandalf();
+        graph
+            .upsert_glossary_entry(crate::GlossaryEntry::new("Mithrandir", "Gandalf the Grey"))
+            .unwrap();
+        let prepared =
+            preprocess_query(&graph, "mithrandir", &QueryPreprocessing::default()).unwrap();
+        assert_eq!(
+            prepared.fts_query.as_deref(),
+            Some("(\"mithrandir\" OR \"Gandalf the Grey\")")
+        );
+
+        let config = QueryPreprocessing { use_glossary: false, ..Default::default() };
+        let prepared = preprocess_query(&graph, "mithrandir", &config).unwrap();
+        assert_eq!(prepared.fts_query.as_deref(), Some("mithrandir"));
+    }
+
+    #[test]
+    fn test_preprocess_expands_glossary_terms_before_correcting() {
+        let (graph, _tmp) = graph_with_gandalf();
+        // "greg" is one edit from the name word "grey".
+        graph
+            .upsert_glossary_entry(crate::GlossaryEntry::new("Greg", "Gregory the Mage"))
+            .unwrap();
+        let prepared = preprocess_query(&graph, "greg", &QueryPreprocessing::default()).unwrap();
+        assert!(prepared.corrections.is_empty());
+        assert_eq!(
+            prepared.fts_query.as_deref(),
+            Some("(\"greg\" OR \"Gregory the Mage\")")
+        );
+    }
+
+    #[test]
+    fn test_verbatim_matches_plain_sanitisation() {
+        let prepared = PreparedQuery::verbatim("Who founded the Foundation?");
+        assert_eq!(prepared.text, "Who founded the Foundation?");
+        assert_eq!(prepared.fts_query.as_deref(), Some("Who founded the Foundation"));
+        assert!(prepared.corrections.is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Local, opt-in search telemetry.
+//!
+//! When enabled with [`KnowledgeGraph::set_search_telemetry`], callers log
+//! each search with [`KnowledgeGraph::record_search`] and each result the
+//! user opens with [`KnowledgeGraph::record_search_click`].  Nothing leaves
+//! the project database.  [`KnowledgeGraph::search_report`] then shows which
+//! queries keep coming back empty — content the world is missing — and which
+//! objects people actually open, for tuning ranking.
+//!
+//! Queries are grouped by a normalised form (lower-cased, whitespace
+//! collapsed), so `"Red Dragon"` and `"red  dragon"` count together.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::{Result, UForgeError};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding `"on"` while telemetry is enabled.
+pub const SEARCH_TELEMETRY_SETTING: &str = "search_telemetry";
+
+/// A query that returned no results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZeroResultQuery {
+    /// The most recent spelling of the query.
+    pub query: String,
+    /// Times it was searched (after normalisation).
+    pub count: usize,
+    pub last_searched: DateTime<Utc>,
+}
+
+/// An object opened from search results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClickedObject {
+    pub object_id: ObjectId,
+    /// `None` if the object has since been deleted.
+    pub name: Option<String>,
+    pub clicks: usize,
+}
+
+/// Result of [`KnowledgeGraph::search_report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchReport {
+    /// Start of the reported window; `None` for all time.
+    pub since: Option<DateTime<Utc>>,
+    pub total_searches: usize,
+    pub zero_result_searches: usize,
+    /// Searches where at least one result was opened.
+    pub clicked_searches: usize,
+    /// Most frequent zero-result queries first.
+    pub zero_result_queries: Vec<ZeroResultQuery>,
+    /// Most opened objects first.
+    pub top_clicked: Vec<ClickedObject>,
+}
+
+impl SearchReport {
+    /// Share of searches that returned nothing (0 when there were none).
+    pub fn zero_result_rate(&self) -> f32 {
+        ratio(self.zero_result_searches, self.total_searches)
+    }
+
+    /// Share of searches where a result was opened (0 when there were none).
+    pub fn click_through_rate(&self) -> f32 {
+        ratio(self.clicked_searches, self.total_searches)
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+/// Lower-case `query` and collapse its whitespace.
+pub fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl KnowledgeGraph {
+    /// Whether searches are being recorded.  Off unless turned on.
+    pub fn search_telemetry_enabled(&self) -> Result<bool> {
+        Ok(self
+            .storage
+            .get_setting(SEARCH_TELEMETRY_SETTING)?
+            .as_deref()
+            == Some("on"))
+    }
+
+    /// Turn search recording on or off.  Turning it off keeps what was
+    /// already recorded; see [`clear_search_telemetry`](Self::clear_search_telemetry).
+    pub fn set_search_telemetry(&self, enabled: bool) -> Result<()> {
+        if enabled {
+            self.storage.set_setting(SEARCH_TELEMETRY_SETTING, "on")
+        } else {
+            self.storage
+                .delete_setting(SEARCH_TELEMETRY_SETTING)
+                .map(|_| ())
+        }
+    }
+
+    /// Log a search that returned `result_count` results.  Returns the id to
+    /// pass to [`record_search_click`](Self::record_search_click), or `None`
+    /// when telemetry is off or the query is blank.
+    pub fn record_search(&self, query: &str, result_count: usize) -> Result<Option<Uuid>> {
+        let normalized = normalize_query(query);
+        if normalized.is_empty() || !self.search_telemetry_enabled()? {
+            return Ok(None);
+        }
+        let id = Uuid::new_v4();
+        self.storage.insert_search_log(
+            &id.to_string(),
+            query.trim(),
+            &normalized,
+            result_count,
+            Utc::now(),
+        )?;
+        Ok(Some(id))
+    }
+
+    /// Log that the result at `rank` (0-based) of search `search_id` was
+    /// opened.
+    ///
+    /// Fails with [`UForgeError::NotFound`] if the search is not in the log
+    /// (e.g. it was cleared).
+    pub fn record_search_click(
+        &self,
+        search_id: Uuid,
+        object_id: ObjectId,
+        rank: usize,
+    ) -> Result<()> {
+        if !self
+            .storage
+            .insert_search_click(&search_id.to_string(), object_id, rank, Utc::now())?
+        {
+            return Err(UForgeError::NotFound(format!("Unknown search {search_id}")));
+        }
+        Ok(())
+    }
+
+    /// Summarise the search log since `since` (all time when `None`), listing
+    /// up to `limit` zero-result queries and clicked objects.
+    pub fn search_report(
+        &self,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<SearchReport> {
+        let counts = self.storage.search_log_counts(since)?;
+        let zero_result_queries = self
+            .storage
+            .zero_result_queries(since, limit)?
+            .into_iter()
+            .map(|(query, count, last_searched)| ZeroResultQuery {
+                query,
+                count,
+                last_searched,
+            })
+            .collect();
+        let mut top_clicked = Vec::new();
+        for (object_id, clicks) in self.storage.top_clicked_objects(since, limit)? {
+            top_clicked.push(ClickedObject {
+                object_id,
+                name: self.get_object(object_id)?.map(|o| o.name),
+                clicks,
+            });
+        }
+        Ok(SearchReport {
+            since,
+            total_searches: counts.searches,
+            zero_result_searches: counts.zero_result_searches,
+            clicked_searches: counts.clicked_searches,
+            zero_result_queries,
+            top_clicked,
+        })
+    }
+
+    /// Delete every recorded search.  Returns how many were removed.
+    pub fn clear_search_telemetry(&self) -> Result<usize> {
+        self.storage.clear_search_log()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_search_telemetry_report() {
+        let (graph, _temp_dir) = create_test_graph();
+        let smaug = ObjectBuilder::character("Smaug".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        // Off by default: nothing is recorded.
+        assert_eq!(graph.record_search("smaug", 1).unwrap(), None);
+
+        graph.set_search_telemetry(true).unwrap();
+        let search = graph.record_search("smaug", 1).unwrap().unwrap();
+        graph.record_search_click(search, smaug, 0).unwrap();
+        graph.record_search("Red Dragon", 0).unwrap();
+        graph.record_search("red   dragon ", 0).unwrap();
+        graph.record_search("Thieves' guild", 0).unwrap();
+        assert_eq!(graph.record_search("   ", 0).unwrap(), None);
+
+        let report = graph.search_report(None, 10).unwrap();
+        assert_eq!(report.total_searches, 4);
+        assert_eq!(report.zero_result_searches, 3);
+        assert_eq!(report.clicked_searches, 1);
+        assert_eq!(report.click_through_rate(), 0.25);
+        assert_eq!(report.zero_result_queries.len(), 2);
+        assert_eq!(
+            normalize_query(&report.zero_result_queries[0].query),
+            "red dragon"
+        );
+        assert_eq!(report.zero_result_queries[0].count, 2);
+        assert_eq!(report.top_clicked[0].name.as_deref(), Some("Smaug"));
+
+        let future = graph.search_report(Some(Utc::now() + chrono::Duration::hours(1)), 10);
+        assert_eq!(future.unwrap().total_searches, 0);
+
+        assert_eq!(graph.clear_search_telemetry().unwrap(), 4);
+        let err = graph.record_search_click(search, smaug, 0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,268 @@
+//! "See also" recommendations for an object page.
+//!
+//! [`KnowledgeGraph::get_similar_objects`] blends three signals into one
+//! score in `0..=1`:
+//!
+//! - **Embedding** — `1 - cosine distance` between object profile
+//!   embeddings (see [`KnowledgeGraph::search_profiles_semantic`]), for the
+//!   nearest [`EMBEDDING_CANDIDATES`] objects.
+//! - **Tags** — Jaccard overlap of the two objects' `tags`
+//!   (case-insensitive).
+//! - **Neighbors** — Jaccard overlap of the objects each is connected to.
+//!
+//! A signal the source object has nothing for — no profile embedding yet,
+//! no tags, no relationships — is dropped and the remaining
+//! [`SimilarityWeights`] are rescaled, so projects without embeddings still
+//! get recommendations from structure alone.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::graph_data::object_tags;
+use crate::types::{ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Profile-embedding neighbours considered per query.
+pub const EMBEDDING_CANDIDATES: usize = 50;
+
+/// Relative weight of each signal in the blended score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityWeights {
+    pub embedding: f32,
+    pub tags: f32,
+    pub neighbors: f32,
+}
+
+impl Default for SimilarityWeights {
+    fn default() -> Self {
+        Self {
+            embedding: 0.5,
+            tags: 0.25,
+            neighbors: 0.25,
+        }
+    }
+}
+
+/// One recommendation from [`KnowledgeGraph::get_similar_objects`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarObject {
+    pub object_id: ObjectId,
+    pub name: String,
+    pub object_type: String,
+    /// Blended score in `0..=1`.
+    pub score: f32,
+    /// `None` when the pair was not compared by embedding.
+    pub embedding_similarity: Option<f32>,
+    /// Lower-cased tags both objects carry.
+    pub shared_tags: Vec<String>,
+    /// Objects both are connected to.
+    pub shared_neighbors: Vec<ObjectId>,
+}
+
+fn lower_tags(object: &ObjectMetadata) -> BTreeSet<String> {
+    object_tags(object)
+        .into_iter()
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn jaccard(shared: usize, a: usize, b: usize) -> f32 {
+    let union = a + b - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f32 / union as f32
+    }
+}
+
+impl KnowledgeGraph {
+    /// Up to `limit` objects most like `object_id`, best first, with
+    /// [`SimilarityWeights::default`].  Fails with
+    /// [`UForgeError::NotFound`] for an unknown object.
+    pub fn get_similar_objects(
+        &self,
+        object_id: ObjectId,
+        limit: usize,
+    ) -> Result<Vec<SimilarObject>> {
+        self.get_similar_objects_with(object_id, limit, &SimilarityWeights::default())
+    }
+
+    /// [`get_similar_objects`](Self::get_similar_objects) with explicit
+    /// weights.  Objects scoring 0 are left out.
+    pub fn get_similar_objects_with(
+        &self,
+        object_id: ObjectId,
+        limit: usize,
+        weights: &SimilarityWeights,
+    ) -> Result<Vec<SimilarObject>> {
+        let source = self
+            .get_object(object_id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {object_id}")))?;
+        let source_tags = lower_tags(&source);
+        let source_neighbors: HashSet<ObjectId> =
+            self.get_neighbors(object_id)?.into_iter().collect();
+
+        // Candidates from each signal.
+        let embedded = self
+            .storage
+            .similar_profiles(object_id, EMBEDDING_CANDIDATES)?;
+        let similarity: HashMap<ObjectId, f32> = embedded
+            .iter()
+            .flatten()
+            .map(|&(id, distance)| (id, (1.0 - distance).clamp(0.0, 1.0)))
+            .collect();
+        let mut candidates: HashMap<ObjectId, Option<ObjectMetadata>> =
+            similarity.keys().map(|&id| (id, None)).collect();
+        if !source_tags.is_empty() {
+            for object in self.get_all_objects()? {
+                if object.id != object_id && !lower_tags(&object).is_disjoint(&source_tags) {
+                    candidates.insert(object.id, Some(object));
+                }
+            }
+        }
+        for &neighbor in &source_neighbors {
+            for id in self.get_neighbors(neighbor)? {
+                if id != object_id {
+                    candidates.entry(id).or_insert(None);
+                }
+            }
+        }
+
+        // Drop signals the source has nothing for and rescale the rest.
+        let active = |on: bool, weight: f32| if on { weight.max(0.0) } else { 0.0 };
+        let w_embedding = active(embedded.is_some(), weights.embedding);
+        let w_tags = active(!source_tags.is_empty(), weights.tags);
+        let w_neighbors = active(!source_neighbors.is_empty(), weights.neighbors);
+        let total = w_embedding + w_tags + w_neighbors;
+        if total <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let mut out = Vec::new();
+        for (id, object) in candidates {
+            let object = match object {
+                Some(object) => object,
+                None => match self.get_object(id)? {
+                    Some(object) => object,
+                    None => continue,
+                },
+            };
+            let tags = lower_tags(&object);
+            let shared_tags: Vec<String> = tags.intersection(&source_tags).cloned().collect();
+            let neighbors: HashSet<ObjectId> = self.get_neighbors(id)?.into_iter().collect();
+            let mut shared_neighbors: Vec<ObjectId> =
+                neighbors.intersection(&source_neighbors).copied().collect();
+            shared_neighbors.sort_by_key(|id| id.0);
+            let embedding_similarity = similarity.get(&id).copied();
+
+            let score = (w_embedding * embedding_similarity.unwrap_or(0.0)
+                + w_tags * jaccard(shared_tags.len(), source_tags.len(), tags.len())
+                + w_neighbors
+                    * jaccard(
+                        shared_neighbors.len(),
+                        source_neighbors.len(),
+                        neighbors.len(),
+                    ))
+                / total;
+            if score <= 0.0 {
+                continue;
+            }
+            out.push(SimilarObject {
+                object_id: id,
+                name: object.name,
+                object_type: object.object_type,
+                score,
+                embedding_similarity,
+                shared_tags,
+                shared_neighbors,
+            });
+        }
+        out.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        out.truncate(limit);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::graph::EMBEDDING_DIMENSIONS;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    fn unit(axis: usize) -> Vec<f32> {
+        let mut v = vec![0.0; EMBEDDING_DIMENSIONS];
+        v[axis] = 1.0;
+        v
+    }
+
+    #[test]
+    fn test_similar_objects_blend_tags_and_neighbors() {
+        let (graph, _temp_dir) = create_test_graph();
+        let town = ObjectBuilder::location("Phandalin".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let guild = ObjectBuilder::faction("Miner's Exchange".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let halia = ObjectBuilder::character("Halia".to_string())
+            .with_tag("Zhentarim".to_string())
+            .with_tag("merchant".to_string())
+            .located_in(town)
+            .member_of(guild)
+            .add_to_graph(&graph)
+            .unwrap();
+        let linene = ObjectBuilder::character("Linene".to_string())
+            .with_tag("merchant".to_string())
+            .located_in(town)
+            .member_of(guild)
+            .add_to_graph(&graph)
+            .unwrap();
+        let agent = ObjectBuilder::character("Zhent agent".to_string())
+            .with_tag("zhentarim".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        ObjectBuilder::character("Stranger".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let similar = graph.get_similar_objects(halia, 5).unwrap();
+        let ids: Vec<_> = similar.iter().map(|s| s.object_id).collect();
+        assert_eq!(ids, vec![linene, agent]);
+        assert_eq!(similar[0].shared_tags, vec!["merchant".to_string()]);
+        assert_eq!(similar[0].shared_neighbors.len(), 2);
+        assert!(similar[0].embedding_similarity.is_none());
+        assert!(similar[0].score > similar[1].score);
+
+        // A close embedding lifts an otherwise unrelated object.
+        let stranger = graph.find_by_name_only("Stranger").unwrap()[0].id;
+        for (id, axis) in [(halia, 0), (stranger, 0), (linene, 1), (agent, 2)] {
+            graph.upsert_profile_embedding(id, &unit(axis)).unwrap();
+        }
+        let similar = graph
+            .get_similar_objects_with(
+                halia,
+                1,
+                &SimilarityWeights {
+                    embedding: 1.0,
+                    tags: 0.0,
+                    neighbors: 0.0,
+                },
+            )
+            .unwrap();
+        assert_eq!(similar[0].object_id, stranger);
+        assert!((similar[0].score - 1.0).abs() < 1e-4);
+
+        let err = graph
+            .get_similar_objects(ObjectId::new_v4(), 5)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,587 @@
+//! Staging layers — "what-if" sketches on top of the canon graph.
+//!
+//! During prep a GM often wants to try ideas out before they become part of
+//! the world: a hypothetical ambush site, an NPC who may or may not betray the
+//! party.  A [`StagingLayer`] is an in-memory overlay holding such objects and
+//! relationships.  Nothing touches the database until the layer is committed.
+//!
+//! - **Sketch** — [`StagingLayer::add_object`] and [`StagingLayer::connect`]
+//!   record hypothetical objects and edges.  Staging a copy of an existing
+//!   object (same id) overrides it inside the layer, so edits can be
+//!   previewed as well; [`StagingLayer::delete_object`] and
+//!   [`StagingLayer::delete_edge`] hide stored rows.
+//! - **Query** — [`KnowledgeGraph::staged`] returns a [`StagedGraph`] view that
+//!   reads the layer merged over the base graph.  Layer entries win over base
+//!   rows with the same id or `(from, to, edge_type)` triple.  Edges touching
+//!   a deleted object are hidden too, matching `ON DELETE CASCADE`.
+//! - **Commit or discard** — [`KnowledgeGraph::commit_staging`] writes the
+//!   whole layer in one transaction.  Discarding is just dropping the layer.
+//!
+//! Staged objects carry no text chunks; their description stays in
+//! `properties` until the object is committed and ingested.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::types::{Edge, EdgeType, ObjectId, ObjectMetadata, QueryResult};
+use crate::{KnowledgeGraph, SCHEMA_EXCEPTION_KEY};
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// An in-memory set of hypothetical objects and relationships.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StagingLayer {
+    name: String,
+    objects: Vec<ObjectMetadata>,
+    edges: Vec<Edge>,
+    /// Stored objects hidden by this layer.
+    #[serde(default)]
+    deleted_objects: Vec<ObjectId>,
+    /// Stored `(from, to, edge_type)` triples hidden by this layer.
+    #[serde(default)]
+    deleted_edges: Vec<(ObjectId, ObjectId, EdgeType)>,
+}
+
+/// What [`KnowledgeGraph::commit_staging`] wrote.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StagingCommit {
+    /// Objects that did not exist in the base graph.
+    pub created: Vec<ObjectId>,
+    /// Existing objects whose staged version replaced the stored one.
+    pub updated: Vec<ObjectId>,
+    pub edges: usize,
+    /// Objects deleted (with their edges and chunks).
+    pub deleted: Vec<ObjectId>,
+    pub edges_deleted: usize,
+}
+
+impl StagingLayer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn objects(&self) -> &[ObjectMetadata] {
+        &self.objects
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn deleted_objects(&self) -> &[ObjectId] {
+        &self.deleted_objects
+    }
+
+    pub fn deleted_edges(&self) -> &[(ObjectId, ObjectId, EdgeType)] {
+        &self.deleted_edges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+            && self.edges.is_empty()
+            && self.deleted_objects.is_empty()
+            && self.deleted_edges.is_empty()
+    }
+
+    /// Whether the layer hides the stored object `id`.
+    pub fn is_deleted(&self, id: ObjectId) -> bool {
+        self.deleted_objects.contains(&id)
+    }
+
+    /// Stage an object, replacing any staged object with the same id and
+    /// undoing a staged deletion of it.
+    pub fn add_object(&mut self, metadata: ObjectMetadata) -> ObjectId {
+        let id = metadata.id;
+        self.deleted_objects.retain(|d| *d != id);
+        match self.objects.iter_mut().find(|o| o.id == id) {
+            Some(existing) => *existing = metadata,
+            None => self.objects.push(metadata),
+        }
+        id
+    }
+
+    /// The staged version of `id`, if the layer has one.
+    pub fn get_object(&self, id: ObjectId) -> Option<&ObjectMetadata> {
+        self.objects.iter().find(|o| o.id == id)
+    }
+
+    /// Forget everything the layer says about `id`: its staged version or
+    /// staged deletion, and every staged edge touching it.  Returns `false`
+    /// if the layer did not mention it.  Base objects are unaffected.
+    pub fn remove_object(&mut self, id: ObjectId) -> bool {
+        let before = self.objects.len() + self.deleted_objects.len();
+        self.objects.retain(|o| o.id != id);
+        self.deleted_objects.retain(|d| *d != id);
+        self.edges.retain(|e| e.from != id && e.to != id);
+        self.objects.len() + self.deleted_objects.len() != before
+    }
+
+    /// Stage the deletion of a stored object.  Its staged version and staged
+    /// edges are dropped; its stored edges are hidden along with it.
+    pub fn delete_object(&mut self, id: ObjectId) {
+        self.remove_object(id);
+        self.deleted_objects.push(id);
+    }
+
+    /// Stage a relationship.  Endpoints may be staged or base objects; they
+    /// are checked on commit.  Re-staging the same triple replaces it.
+    pub fn connect(&mut self, from: ObjectId, to: ObjectId, edge_type: EdgeType) {
+        self.add_edge(Edge::new(from, to, edge_type));
+    }
+
+    /// Stage a relationship using a plain string edge type.
+    pub fn connect_str(&mut self, from: ObjectId, to: ObjectId, edge_type: &str) {
+        self.connect(from, to, EdgeType::new(edge_type));
+    }
+
+    /// Stage a fully-specified edge (weight, metadata).
+    pub fn add_edge(&mut self, edge: Edge) {
+        self.deleted_edges
+            .retain(|(f, t, et)| !(*f == edge.from && *t == edge.to && *et == edge.edge_type));
+        match self.edges.iter_mut().find(|e| same_triple(e, &edge)) {
+            Some(existing) => *existing = edge,
+            None => self.edges.push(edge),
+        }
+    }
+
+    /// Drop a staged relationship.  Returns `false` if it was not staged.
+    pub fn disconnect(&mut self, from: ObjectId, to: ObjectId, edge_type: &str) -> bool {
+        let before = self.edges.len();
+        self.edges
+            .retain(|e| !(e.from == from && e.to == to && e.edge_type.as_str() == edge_type));
+        self.edges.len() != before
+    }
+
+    /// Stage the deletion of a stored relationship.
+    pub fn delete_edge(&mut self, from: ObjectId, to: ObjectId, edge_type: &str) {
+        self.disconnect(from, to, edge_type);
+        if !self.hides_edge(from, to, edge_type) {
+            self.deleted_edges.push((from, to, EdgeType::new(edge_type)));
+        }
+    }
+
+    /// Fold `other` into this layer, as if its deletions, objects, and edges
+    /// had been staged here in that order.
+    pub fn absorb(&mut self, other: StagingLayer) {
+        for (from, to, edge_type) in other.deleted_edges {
+            self.delete_edge(from, to, edge_type.as_str());
+        }
+        for id in other.deleted_objects {
+            self.delete_object(id);
+        }
+        for obj in other.objects {
+            self.add_object(obj);
+        }
+        for edge in other.edges {
+            self.add_edge(edge);
+        }
+    }
+
+    /// Empty the layer without committing it.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.edges.clear();
+        self.deleted_objects.clear();
+        self.deleted_edges.clear();
+    }
+
+    /// Whether a stored edge is hidden, directly or through a deleted endpoint.
+    fn hides_edge(&self, from: ObjectId, to: ObjectId, edge_type: &str) -> bool {
+        self.is_deleted(from)
+            || self.is_deleted(to)
+            || self
+                .deleted_edges
+                .iter()
+                .any(|(f, t, et)| *f == from && *t == to && et.as_str() == edge_type)
+    }
+}
+
+fn same_triple(a: &Edge, b: &Edge) -> bool {
+    a.from == b.from && a.to == b.to && a.edge_type == b.edge_type
+}
+
+/// Read-only view of a [`StagingLayer`] merged over a [`KnowledgeGraph`]'s
+/// main graph (ignoring any active [branch](crate::branches)).
+///
+/// Mirrors the graph's read methods; see the module docs for merge rules.
+pub struct StagedGraph<'a> {
+    graph: &'a KnowledgeGraph,
+    layer: &'a StagingLayer,
+}
+
+impl StagedGraph<'_> {
+    /// The staged version of `id` if there is one, else the stored object.
+    pub fn get_object(&self, id: ObjectId) -> Result<Option<ObjectMetadata>> {
+        if self.layer.is_deleted(id) {
+            return Ok(None);
+        }
+        match self.layer.get_object(id) {
+            Some(staged) => Ok(Some(staged.clone())),
+            None => self.graph.storage.get_node(id),
+        }
+    }
+
+    /// Every stored object (with staged overrides applied) followed by the
+    /// objects that exist only in the layer.
+    pub fn get_all_objects(&self) -> Result<Vec<ObjectMetadata>> {
+        let mut out = self.graph.storage.get_all_objects()?;
+        out.retain(|o| !self.layer.is_deleted(o.id));
+        let mut base_ids = HashSet::new();
+        for obj in &mut out {
+            base_ids.insert(obj.id);
+            if let Some(staged) = self.layer.get_object(obj.id) {
+                *obj = staged.clone();
+            }
+        }
+        out.extend(
+            self.layer
+                .objects
+                .iter()
+                .filter(|o| !base_ids.contains(&o.id))
+                .cloned(),
+        );
+        Ok(out)
+    }
+
+    /// Objects named exactly `name`, staged versions taking precedence.
+    pub fn find_by_name_only(&self, name: &str) -> Result<Vec<ObjectMetadata>> {
+        let mut out: Vec<ObjectMetadata> = self
+            .graph
+            .storage
+            .find_nodes_by_name_only(name)?
+            .into_iter()
+            .filter(|o| self.layer.get_object(o.id).is_none() && !self.layer.is_deleted(o.id))
+            .collect();
+        out.extend(self.layer.objects.iter().filter(|o| o.name == name).cloned());
+        Ok(out)
+    }
+
+    /// Stored and staged edges incident to `id`.  A staged edge replaces a
+    /// stored one with the same triple.
+    pub fn get_relationships(&self, id: ObjectId) -> Result<Vec<Edge>> {
+        if self.layer.is_deleted(id) {
+            return Ok(Vec::new());
+        }
+        let staged: Vec<&Edge> = self
+            .layer
+            .edges
+            .iter()
+            .filter(|e| e.from == id || e.to == id)
+            .filter(|e| !self.layer.is_deleted(e.from) && !self.layer.is_deleted(e.to))
+            .collect();
+        let mut out: Vec<Edge> = self
+            .graph
+            .storage
+            .get_edges(id)?
+            .into_iter()
+            .filter(|e| {
+                !staged.iter().any(|s| same_triple(s, e))
+                    && !self.layer.hides_edge(e.from, e.to, e.edge_type.as_str())
+            })
+            .collect();
+        out.extend(staged.into_iter().cloned());
+        Ok(out)
+    }
+
+    /// Every stored edge the layer neither hides nor replaces, followed by
+    /// the staged edges.
+    pub fn get_all_edges(&self) -> Result<Vec<Edge>> {
+        let mut out: Vec<Edge> = self
+            .graph
+            .storage
+            .get_all_edges()?
+            .into_iter()
+            .filter(|e| {
+                !self.layer.edges.iter().any(|s| same_triple(s, e))
+                    && !self.layer.hides_edge(e.from, e.to, e.edge_type.as_str())
+            })
+            .collect();
+        out.extend(
+            self.layer
+                .edges
+                .iter()
+                .filter(|e| !self.layer.is_deleted(e.from) && !self.layer.is_deleted(e.to))
+                .cloned(),
+        );
+        Ok(out)
+    }
+
+    /// [`KnowledgeGraph::query_subgraph`] over the merged view.  Chunks come
+    /// from the base graph only.
+    pub fn query_subgraph(&self, start: ObjectId, max_hops: usize) -> Result<QueryResult> {
+        self.graph.storage.bfs_subgraph(
+            start,
+            max_hops,
+            |id| self.get_object(id),
+            |id| self.get_relationships(id),
+            |id| self.graph.get_text_chunks(id),
+        )
+    }
+}
+
+// ── KnowledgeGraph API ────────────────────────────────────────────────────────
+
+impl KnowledgeGraph {
+    /// A read view of `layer` merged over this graph.
+    pub fn staged<'a>(&'a self, layer: &'a StagingLayer) -> StagedGraph<'a> {
+        StagedGraph { graph: self, layer }
+    }
+
+    /// Apply every staged deletion, object, and edge in a single transaction.
+    /// With a [branch](crate::branches) active, the layer is folded into the
+    /// branch's overlay instead and the main graph is left alone.
+    ///
+    /// New objects get their schema's default lifecycle and property
+    /// defaults exactly as [`add_object`](Self::add_object) would; existing
+    /// ones are touched as by [`update_object`](Self::update_object).
+    /// Errors — and writes nothing — when a staged edge refers to an object
+    /// that is neither staged nor stored, or that the layer deletes, or (in
+    /// strict edge mode) breaks the schema without carrying
+    /// [`SCHEMA_EXCEPTION_KEY`] metadata.
+    pub fn commit_staging(&self, layer: StagingLayer) -> Result<StagingCommit> {
+        let active = self.branch.read().as_ref().map(|b| b.layer.clone());
+        let Some(branch_layer) = active else {
+            let main = StagingLayer::default();
+            let (layer, result) = self.prepare_commit(layer, &self.staged(&main))?;
+            self.storage.apply_staged(
+                &layer.objects,
+                &layer.edges,
+                &layer.deleted_objects,
+                &layer.deleted_edges,
+            )?;
+            return Ok(result);
+        };
+        let (layer, result) = self.prepare_commit(layer, &self.staged(&branch_layer))?;
+        self.edit_active_branch(|branch| branch.absorb(layer))?;
+        Ok(result)
+    }
+
+    /// Validate `layer` against `base` and fill in what committing it would
+    /// set: defaults on new objects, touched timestamps on existing ones.
+    pub(crate) fn prepare_commit(
+        &self,
+        layer: StagingLayer,
+        base: &StagedGraph<'_>,
+    ) -> Result<(StagingLayer, StagingCommit)> {
+        let mut result = StagingCommit {
+            edges: layer.edges.len(),
+            deleted: layer.deleted_objects.clone(),
+            edges_deleted: layer.deleted_edges.len(),
+            ..Default::default()
+        };
+        let StagingLayer {
+            name,
+            mut objects,
+            edges,
+            deleted_objects,
+            deleted_edges,
+        } = layer;
+        for obj in &mut objects {
+            if base.get_object(obj.id)?.is_some() {
+                obj.touch();
+                result.updated.push(obj.id);
+            } else {
+                if obj.lifecycle.is_none() {
+                    obj.lifecycle = self.default_lifecycle_for(obj);
+                }
+                self.apply_schema_defaults(obj);
+                result.created.push(obj.id);
+            }
+        }
+
+        let staged_ids: HashSet<ObjectId> = objects.iter().map(|o| o.id).collect();
+        let strict = self.strict_edges()?;
+        for edge in &edges {
+            for end in [edge.from, edge.to] {
+                let missing = !staged_ids.contains(&end)
+                    && (deleted_objects.contains(&end) || base.get_object(end)?.is_none());
+                if missing {
+                    return Err(UForgeError::NotFound(format!(
+                        "Staged edge {} {} {} refers to unknown object {end}",
+                        edge.from,
+                        edge.edge_type.as_str(),
+                        edge.to
+                    )));
+                }
+            }
+            if strict {
+                let lookup = |id: ObjectId| -> Result<ObjectMetadata> {
+                    match objects.iter().find(|o| o.id == id) {
+                        Some(o) => Ok(o.clone()),
+                        None => base
+                            .get_object(id)?
+                            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}"))),
+                    }
+                };
+                let violations = self.edge_schema_violations(
+                    edge,
+                    &lookup(edge.from)?,
+                    &lookup(edge.to)?,
+                    &edges,
+                )?;
+                if !violations.is_empty() && !edge.metadata.contains_key(SCHEMA_EXCEPTION_KEY) {
+                    return Err(UForgeError::SchemaConflict(format!(
+                        "Staged edge rejected by strict mode: {}",
+                        violations.join("; ")
+                    )));
+                }
+            }
+        }
+
+        let layer = StagingLayer {
+            name,
+            objects,
+            edges,
+            deleted_objects,
+            deleted_edges,
+        };
+        Ok((layer, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_staged_view_merges_without_touching_base() {
+        let (graph, _tmp) = create_test_graph();
+        let shire = ObjectBuilder::location("The Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let mut layer = StagingLayer::new("ambush");
+        let orcs = layer.add_object(ObjectBuilder::faction("Orc Raiders".to_string()).build());
+        layer.connect_str(orcs, shire, "attacks");
+        let mut renamed = graph.get_object(shire).unwrap().unwrap();
+        renamed.name = "The Burning Shire".to_string();
+        layer.add_object(renamed);
+
+        let view = graph.staged(&layer);
+        assert_eq!(view.get_object(shire).unwrap().unwrap().name, "The Burning Shire");
+        assert_eq!(view.get_all_objects().unwrap().len(), 2);
+        assert_eq!(view.find_by_name_only("Orc Raiders").unwrap().len(), 1);
+        let subgraph = view.query_subgraph(shire, 1).unwrap();
+        assert_eq!(subgraph.objects.len(), 2);
+        assert_eq!(subgraph.edges.len(), 1);
+
+        // Base graph is untouched until commit.
+        assert_eq!(graph.get_object(shire).unwrap().unwrap().name, "The Shire");
+        assert!(graph.get_object(orcs).unwrap().is_none());
+        assert!(graph.get_relationships(shire).unwrap().is_empty());
+
+        assert!(layer.remove_object(orcs));
+        assert!(layer.edges().is_empty());
+    }
+
+    #[test]
+    fn test_staged_deletions_hide_and_commit() {
+        let (graph, _tmp) = create_test_graph();
+        let shire = ObjectBuilder::location("The Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let frodo = ObjectBuilder::character("Frodo".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let sam = ObjectBuilder::character("Sam".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(frodo, shire, "lives_in").unwrap();
+        graph.connect_objects_str(sam, shire, "lives_in").unwrap();
+
+        let mut layer = StagingLayer::new("dark timeline");
+        layer.delete_object(frodo);
+        layer.delete_edge(sam, shire, "lives_in");
+
+        let view = graph.staged(&layer);
+        assert!(view.get_object(frodo).unwrap().is_none());
+        assert!(view.get_relationships(shire).unwrap().is_empty());
+        assert!(view.find_by_name_only("Frodo").unwrap().is_empty());
+        assert_eq!(view.get_all_objects().unwrap().len(), 2);
+
+        let commit = graph.commit_staging(layer).unwrap();
+        assert_eq!(commit.deleted, vec![frodo]);
+        assert_eq!(commit.edges_deleted, 1);
+        assert!(graph.get_object(frodo).unwrap().is_none());
+        assert!(graph.get_relationships(shire).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_commit_staging_writes_everything() {
+        let (graph, _tmp) = create_test_graph();
+        let shire = ObjectBuilder::location("The Shire".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+
+        let mut layer = StagingLayer::new("ambush");
+        let orcs = layer.add_object(ObjectBuilder::faction("Orc Raiders".to_string()).build());
+        layer.connect_str(orcs, shire, "attacks");
+        let mut renamed = graph.get_object(shire).unwrap().unwrap();
+        renamed.name = "The Burning Shire".to_string();
+        layer.add_object(renamed);
+
+        let commit = graph.commit_staging(layer).unwrap();
+        assert_eq!(commit.created, vec![orcs]);
+        assert_eq!(commit.updated, vec![shire]);
+        assert_eq!(commit.edges, 1);
+        assert_eq!(graph.get_object(shire).unwrap().unwrap().name, "The Burning Shire");
+        assert!(graph.get_object(orcs).unwrap().is_some());
+        assert_eq!(graph.get_relationships(shire).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_commit_staging_is_atomic() {
+        let (graph, _tmp) = create_test_graph();
+        let mut layer = StagingLayer::new("broken");
+        let orcs = layer.add_object(ObjectBuilder::faction("Orc Raiders".to_string()).build());
+        layer.connect_str(orcs, ObjectId::new_v4(), "attacks");
+
+        assert!(graph.commit_staging(layer).is_err());
+        assert!(graph.get_object(orcs).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commit_staging_counts_staged_edges_for_cardinality() {
+        use crate::schema::{
+            Cardinality, ObjectTypeSchema, PropertySchema, RelationshipDefinition,
+        };
+
+        let (graph, _tmp) = create_test_graph();
+        let captain =
+            RelationshipDefinition::new("captained_by".to_string(), "Captain".to_string())
+                .with_cardinality(Cardinality::ManyToOne);
+        let ship_schema = ObjectTypeSchema::new("ship".to_string(), "A vessel".to_string())
+            .with_property(
+                "captain".to_string(),
+                PropertySchema::string("Captain").with_relationship(captain),
+            );
+        graph
+            .register_object_type("ship", ship_schema)
+            .await
+            .unwrap();
+        graph.set_strict_edges(true).unwrap();
+
+        let mut layer = StagingLayer::new("mutiny");
+        let ship = layer
+            .add_object(ObjectBuilder::custom("ship".to_string(), "Grey Ship".to_string()).build());
+        let frodo = layer.add_object(ObjectBuilder::character("Frodo".to_string()).build());
+        let sam = layer.add_object(ObjectBuilder::character("Sam".to_string()).build());
+        layer.connect_str(ship, frodo, "captained_by");
+        layer.connect_str(ship, sam, "captained_by");
+
+        let err = graph.commit_staging(layer).unwrap_err();
+        assert!(matches!(err, UForgeError::SchemaConflict(_)));
+        assert!(graph.get_object(ship).unwrap().is_none());
+    }
+}
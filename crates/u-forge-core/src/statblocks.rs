@@ -0,0 +1,789 @@
+//! Mechanical stat blocks for game systems.
+//!
+//! A [`StatBlockLayout`] describes one system's stat block — D&D 5e, Stars
+//! Without Number, Pathfinder 2e, or any custom layout registered with
+//! [`KnowledgeGraph::register_stat_block_layout`] (stored in the
+//! `statblock_layouts` project setting; a custom layout replaces a built-in
+//! one of the same system name).  Layouts group typed [`StatField`]s into
+//! sections; a field with a `derived` expression (the computed-property
+//! language of [`ComputedExpression`]) is never stored but worked out from
+//! the others, e.g. `str_mod = floor((str - 10) / 2)`.
+//!
+//! An object's stat block lives in its [`STAT_BLOCK_KEY`] property as
+//! `{"system": "5e", "values": {...}}`.  The system defaults to the one its
+//! type schema names in `metadata["statblock"]` (`"statBlock"` in a JSON
+//! schema file).  [`KnowledgeGraph::set_stat_block`] validates values
+//! against the layout before saving; [`StatBlock::render_markdown`] and
+//! [`StatBlock::render_text`] lay a block out for prep notes and chat.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Result, UForgeError};
+use crate::schema::ComputedExpression;
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Property holding an object's stat block.
+pub const STAT_BLOCK_KEY: &str = "statblock";
+
+/// `ObjectTypeSchema::metadata` key naming the type's default system.
+pub const STAT_BLOCK_SCHEMA_KEY: &str = "statblock";
+
+/// `project_settings` key holding custom layouts as JSON.
+pub const STAT_BLOCK_LAYOUTS_SETTING: &str = "statblock_layouts";
+
+/// How a stat field's value is typed and displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatFieldType {
+    /// A whole number, e.g. hit points.
+    Integer,
+    /// A whole number shown with its sign, e.g. `+3`.
+    Modifier,
+    /// Any number.
+    Number,
+    /// Free text, e.g. a speed of `"30 ft., fly 60 ft."`.
+    Text,
+    /// Dice notation such as `2d6+1`.
+    Dice,
+    /// A list of entries, e.g. actions or special abilities.
+    List,
+}
+
+/// One field of a stat block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatField {
+    /// Key in the stored values and in derived expressions.
+    pub key: String,
+    pub label: String,
+    pub field_type: StatFieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// Inclusive bounds for numeric fields.
+    #[serde(default)]
+    pub min: Option<i64>,
+    #[serde(default)]
+    pub max: Option<i64>,
+    /// Expression over other fields; a derived field is computed, never
+    /// stored.
+    #[serde(default)]
+    pub derived: Option<String>,
+}
+
+impl StatField {
+    pub fn new(
+        key: impl Into<String>,
+        label: impl Into<String>,
+        field_type: StatFieldType,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            label: label.into(),
+            field_type,
+            required: false,
+            min: None,
+            max: None,
+            derived: None,
+        }
+    }
+
+    pub fn integer(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, StatFieldType::Integer)
+    }
+
+    pub fn modifier(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, StatFieldType::Modifier)
+    }
+
+    pub fn text(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, StatFieldType::Text)
+    }
+
+    pub fn dice(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, StatFieldType::Dice)
+    }
+
+    pub fn list(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, StatFieldType::List)
+    }
+
+    /// A field computed from `expression`, shown as `field_type`.
+    pub fn derived(
+        key: impl Into<String>,
+        label: impl Into<String>,
+        field_type: StatFieldType,
+        expression: impl Into<String>,
+    ) -> Self {
+        Self {
+            derived: Some(expression.into()),
+            ..Self::new(key, label, field_type)
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn range(mut self, min: i64, max: i64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self.field_type,
+            StatFieldType::Integer | StatFieldType::Modifier | StatFieldType::Number
+        )
+    }
+
+    /// Why `value` does not fit this field, if it does not.
+    fn check(&self, value: &Value) -> Option<String> {
+        let fits = match self.field_type {
+            StatFieldType::Integer | StatFieldType::Modifier => value.is_i64(),
+            StatFieldType::Number => value.is_number(),
+            StatFieldType::Text => value.is_string(),
+            StatFieldType::Dice => value.as_str().is_some_and(is_dice),
+            StatFieldType::List => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        };
+        if !fits {
+            let expected = match self.field_type {
+                StatFieldType::Integer | StatFieldType::Modifier => "a whole number",
+                StatFieldType::Number => "a number",
+                StatFieldType::Text => "text",
+                StatFieldType::Dice => "dice notation like 2d6+1",
+                StatFieldType::List => "a list of text entries",
+            };
+            return Some(format!("{} must be {expected}", self.label));
+        }
+        let number = value.as_f64()?;
+        if self.min.is_some_and(|min| number < min as f64)
+            || self.max.is_some_and(|max| number > max as f64)
+        {
+            return Some(format!(
+                "{} must be between {} and {}",
+                self.label,
+                self.min.map_or("-∞".to_string(), |m| m.to_string()),
+                self.max.map_or("∞".to_string(), |m| m.to_string()),
+            ));
+        }
+        None
+    }
+
+    fn display(&self, value: &Value) -> String {
+        match (self.field_type, value) {
+            (StatFieldType::Modifier, Value::Number(n)) => match n.as_i64() {
+                Some(n) if n >= 0 => format!("+{n}"),
+                _ => n.to_string(),
+            },
+            (_, Value::String(s)) => s.clone(),
+            (_, Value::Array(items)) => items
+                .iter()
+                .map(|i| i.as_str().map_or_else(|| i.to_string(), str::to_string))
+                .collect::<Vec<_>>()
+                .join("; "),
+            (_, other) => other.to_string(),
+        }
+    }
+}
+
+/// A titled group of fields, e.g. "Abilities".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatSection {
+    pub title: String,
+    pub fields: Vec<StatField>,
+}
+
+/// One game system's stat block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatBlockLayout {
+    /// Short system id stored on objects, e.g. `"5e"`.
+    pub system: String,
+    /// Display name, e.g. `"D&D 5th Edition"`.
+    pub name: String,
+    pub sections: Vec<StatSection>,
+}
+
+impl StatBlockLayout {
+    pub fn new(system: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            system: system.into(),
+            name: name.into(),
+            sections: Vec::new(),
+        }
+    }
+
+    pub fn with_section(mut self, title: impl Into<String>, fields: Vec<StatField>) -> Self {
+        self.sections.push(StatSection {
+            title: title.into(),
+            fields,
+        });
+        self
+    }
+
+    /// The built-in layouts: D&D 5e, Stars Without Number and Pathfinder 2e.
+    pub fn builtin() -> Vec<StatBlockLayout> {
+        vec![Self::dnd5e(), Self::swn(), Self::pf2e()]
+    }
+
+    /// D&D 5th Edition monster stat block.
+    pub fn dnd5e() -> Self {
+        let abilities = [
+            ("str", "STR"),
+            ("dex", "DEX"),
+            ("con", "CON"),
+            ("int", "INT"),
+            ("wis", "WIS"),
+            ("cha", "CHA"),
+        ];
+        let mut ability_fields = Vec::new();
+        for (key, label) in abilities {
+            ability_fields.push(StatField::integer(key, label).required().range(1, 30));
+            ability_fields.push(StatField::derived(
+                format!("{key}_mod"),
+                format!("{label} mod"),
+                StatFieldType::Modifier,
+                format!("floor(({key} - 10) / 2)"),
+            ));
+        }
+        Self::new("5e", "D&D 5th Edition")
+            .with_section(
+                "Defenses",
+                vec![
+                    StatField::integer("ac", "Armor Class")
+                        .required()
+                        .range(0, 30),
+                    StatField::integer("hp", "Hit Points")
+                        .required()
+                        .range(1, 1000),
+                    StatField::dice("hit_dice", "Hit Dice"),
+                    StatField::text("speed", "Speed"),
+                ],
+            )
+            .with_section("Abilities", ability_fields)
+            .with_section(
+                "Details",
+                vec![
+                    StatField::text("cr", "Challenge"),
+                    StatField::modifier("proficiency_bonus", "Proficiency Bonus").range(2, 9),
+                    StatField::derived(
+                        "passive_perception",
+                        "Passive Perception",
+                        StatFieldType::Integer,
+                        "10 + wis_mod",
+                    ),
+                    StatField::text("senses", "Senses"),
+                    StatField::text("languages", "Languages"),
+                ],
+            )
+            .with_section(
+                "Features",
+                vec![
+                    StatField::list("traits", "Traits"),
+                    StatField::list("actions", "Actions"),
+                ],
+            )
+    }
+
+    /// Stars Without Number NPC stat line.
+    pub fn swn() -> Self {
+        Self::new("swn", "Stars Without Number")
+            .with_section(
+                "Stats",
+                vec![
+                    StatField::integer("hd", "Hit Dice").required().range(0, 40),
+                    StatField::integer("hp", "Hit Points"),
+                    StatField::integer("ac", "Armor Class")
+                        .required()
+                        .range(0, 25),
+                    StatField::modifier("atk", "Attack Bonus").required(),
+                    StatField::dice("damage", "Damage"),
+                    StatField::text("move", "Move"),
+                    StatField::integer("ml", "Morale").range(2, 12),
+                    StatField::modifier("skill", "Skills"),
+                    StatField::derived(
+                        "save",
+                        "Save",
+                        StatFieldType::Integer,
+                        "max(15 - floor(hd / 2), 2)",
+                    ),
+                ],
+            )
+            .with_section(
+                "Features",
+                vec![StatField::list("abilities", "Special Abilities")],
+            )
+    }
+
+    /// Pathfinder 2nd Edition creature stat block.
+    pub fn pf2e() -> Self {
+        let abilities = [
+            ("str", "Str"),
+            ("dex", "Dex"),
+            ("con", "Con"),
+            ("int", "Int"),
+            ("wis", "Wis"),
+            ("cha", "Cha"),
+        ];
+        Self::new("pf2e", "Pathfinder 2nd Edition")
+            .with_section(
+                "Creature",
+                vec![
+                    StatField::integer("level", "Level")
+                        .required()
+                        .range(-1, 25),
+                    StatField::modifier("perception", "Perception").required(),
+                    StatField::text("speed", "Speed"),
+                ],
+            )
+            .with_section(
+                "Abilities",
+                abilities
+                    .into_iter()
+                    .map(|(key, label)| StatField::modifier(key, label).range(-5, 10))
+                    .collect(),
+            )
+            .with_section(
+                "Defenses",
+                vec![
+                    StatField::integer("ac", "AC").required(),
+                    StatField::modifier("fort", "Fort").required(),
+                    StatField::modifier("ref", "Ref").required(),
+                    StatField::modifier("will", "Will").required(),
+                    StatField::integer("hp", "HP").required(),
+                    StatField::derived("will_dc", "Will DC", StatFieldType::Integer, "10 + will"),
+                ],
+            )
+            .with_section(
+                "Features",
+                vec![
+                    StatField::list("strikes", "Strikes"),
+                    StatField::list("abilities", "Abilities"),
+                ],
+            )
+    }
+
+    /// Every field, section by section.
+    pub fn fields(&self) -> impl Iterator<Item = &StatField> {
+        self.sections.iter().flat_map(|s| s.fields.iter())
+    }
+
+    pub fn field(&self, key: &str) -> Option<&StatField> {
+        self.fields().find(|f| f.key == key)
+    }
+
+    /// Problems with the layout itself: a missing system id, duplicate
+    /// keys, or derived expressions that do not parse or read unknown
+    /// fields.
+    pub fn check_layout(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.system.trim().is_empty() {
+            errors.push("A stat block layout needs a system id".to_string());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for field in self.fields() {
+            if !seen.insert(field.key.as_str()) {
+                errors.push(format!("Duplicate stat field '{}'", field.key));
+            }
+        }
+        for field in self.fields() {
+            let Some(source) = &field.derived else {
+                continue;
+            };
+            match ComputedExpression::parse(source) {
+                Ok(expression) => {
+                    for name in expression.variables() {
+                        if self.field(name).is_none_or(|f| !f.is_numeric()) {
+                            errors.push(format!(
+                                "Derived field '{}' reads '{name}', which is not a numeric field",
+                                field.key
+                            ));
+                        }
+                    }
+                }
+                Err(e) => errors.push(format!("Derived field '{}': {e}", field.key)),
+            }
+        }
+        errors
+    }
+
+    /// Problems with `values` against this layout: unknown or derived keys,
+    /// missing required fields, wrong types and out-of-range numbers.
+    /// Empty when the values are valid.
+    pub fn validate(&self, values: &Map<String, Value>) -> Vec<String> {
+        let mut errors = Vec::new();
+        for key in values.keys() {
+            match self.field(key) {
+                None => errors.push(format!("Unknown {} stat '{key}'", self.system)),
+                Some(f) if f.derived.is_some() => {
+                    errors.push(format!("{} is derived and cannot be set", f.label))
+                }
+                Some(_) => {}
+            }
+        }
+        for field in self.fields().filter(|f| f.derived.is_none()) {
+            match values.get(&field.key) {
+                None | Some(Value::Null) if field.required => {
+                    errors.push(format!("{} is required", field.label))
+                }
+                None | Some(Value::Null) => {}
+                Some(value) => errors.extend(field.check(value)),
+            }
+        }
+        errors
+    }
+
+    /// Derived values for `values`, in field order.  A derived field
+    /// may read derived fields declared before it; fields whose inputs are
+    /// missing are left out.
+    pub fn derive(&self, values: &Map<String, Value>) -> Map<String, Value> {
+        let mut derived = Map::new();
+        for field in self.fields() {
+            let Some(expression) = field
+                .derived
+                .as_deref()
+                .and_then(|s| ComputedExpression::parse(s).ok())
+            else {
+                continue;
+            };
+            let lookup = |name: &str| {
+                derived
+                    .get(name)
+                    .or_else(|| values.get(name))
+                    .and_then(Value::as_f64)
+            };
+            if let Some(result) = expression.evaluate(&lookup) {
+                derived.insert(field.key.clone(), number_value(result));
+            }
+        }
+        derived
+    }
+}
+
+/// `NdM`, `dM`, `NdM+K` or `NdM-K`.
+fn is_dice(text: &str) -> bool {
+    let text = text.trim();
+    let (dice, bonus) = match text.find(['+', '-']) {
+        Some(i) => (&text[..i], Some(&text[i + 1..])),
+        None => (text, None),
+    };
+    let Some((count, sides)) = dice.trim().split_once(['d', 'D']) else {
+        return false;
+    };
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    (count.trim().is_empty() || digits(count.trim()))
+        && digits(sides.trim())
+        && bonus.is_none_or(|b| digits(b.trim()))
+}
+
+fn number_value(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
+
+/// An object's stat block with its derived values worked out.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatBlock {
+    pub object_id: ObjectId,
+    pub name: String,
+    pub layout: StatBlockLayout,
+    /// Stored values.
+    pub values: Map<String, Value>,
+    /// Values of derived fields.
+    pub derived: Map<String, Value>,
+}
+
+impl StatBlock {
+    /// Stored or derived value of `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key).or_else(|| self.derived.get(key))
+    }
+
+    /// `(label, value)` rows of each section that has any values.
+    fn rows(&self) -> Vec<(&str, Vec<(&str, String)>)> {
+        self.layout
+            .sections
+            .iter()
+            .map(|section| {
+                let rows = section
+                    .fields
+                    .iter()
+                    .filter_map(|f| {
+                        let value = self.get(&f.key).filter(|v| !v.is_null())?;
+                        Some((f.label.as_str(), f.display(value)))
+                    })
+                    .collect::<Vec<_>>();
+                (section.title.as_str(), rows)
+            })
+            .filter(|(_, rows)| !rows.is_empty())
+            .collect()
+    }
+
+    /// Markdown with a heading per section and one bold label per line.
+    pub fn render_markdown(&self) -> String {
+        let mut out = format!("### {}\n*{}*\n", self.name, self.layout.name);
+        for (title, rows) in self.rows() {
+            out.push_str(&format!("\n#### {title}\n"));
+            for (label, value) in rows {
+                out.push_str(&format!("- **{label}:** {value}\n"));
+            }
+        }
+        out
+    }
+
+    /// Plain text, one `Label: value` line per field.
+    pub fn render_text(&self) -> String {
+        let mut out = format!("{} ({})\n", self.name, self.layout.name);
+        for (title, rows) in self.rows() {
+            out.push_str(&format!("{title}\n"));
+            for (label, value) in rows {
+                out.push_str(&format!("  {label}: {value}\n"));
+            }
+        }
+        out
+    }
+}
+
+impl KnowledgeGraph {
+    /// Built-in layouts, with custom layouts replacing built-ins of the
+    /// same system and the rest appended.
+    pub fn stat_block_layouts(&self) -> Result<Vec<StatBlockLayout>> {
+        let custom: Vec<StatBlockLayout> =
+            match self.storage.get_setting(STAT_BLOCK_LAYOUTS_SETTING)? {
+                Some(json) => {
+                    serde_json::from_str(&json).context("Failed to parse stat block layouts")?
+                }
+                None => Vec::new(),
+            };
+        let mut layouts: Vec<StatBlockLayout> = StatBlockLayout::builtin()
+            .into_iter()
+            .filter(|b| custom.iter().all(|c| c.system != b.system))
+            .collect();
+        layouts.extend(custom);
+        Ok(layouts)
+    }
+
+    /// The layout for `system`, if known.
+    pub fn stat_block_layout(&self, system: &str) -> Result<Option<StatBlockLayout>> {
+        Ok(self
+            .stat_block_layouts()?
+            .into_iter()
+            .find(|l| l.system == system))
+    }
+
+    /// Add or replace a custom layout.  Fails with
+    /// [`UForgeError::ValidationFailed`] when the layout is inconsistent
+    /// (see [`StatBlockLayout::check_layout`]).
+    pub fn register_stat_block_layout(&self, layout: StatBlockLayout) -> Result<()> {
+        let errors = layout.check_layout();
+        if !errors.is_empty() {
+            return Err(UForgeError::ValidationFailed(errors.join("; ")));
+        }
+        let mut custom: Vec<StatBlockLayout> =
+            match self.storage.get_setting(STAT_BLOCK_LAYOUTS_SETTING)? {
+                Some(json) => {
+                    serde_json::from_str(&json).context("Failed to parse stat block layouts")?
+                }
+                None => Vec::new(),
+            };
+        custom.retain(|c| c.system != layout.system);
+        custom.push(layout);
+        let json =
+            serde_json::to_string(&custom).context("Failed to serialize stat block layouts")?;
+        self.storage.set_setting(STAT_BLOCK_LAYOUTS_SETTING, &json)
+    }
+
+    /// Validate `values` against `system` (or the system the object's type
+    /// schema names) and save them as the object's stat block.  Fails with
+    /// [`UForgeError::ValidationFailed`] listing every problem, writing
+    /// nothing.
+    pub fn set_stat_block(
+        &self,
+        id: ObjectId,
+        system: Option<&str>,
+        values: Map<String, Value>,
+    ) -> Result<StatBlock> {
+        let object = self
+            .get_object(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}")))?;
+        let system = match system {
+            Some(system) => system.to_string(),
+            None => self
+                .object_type_schema_for(&object)
+                .and_then(|t| t.metadata.get(STAT_BLOCK_SCHEMA_KEY).cloned())
+                .ok_or_else(|| {
+                    UForgeError::ValidationFailed(format!(
+                        "No stat block system given and type '{}' names none",
+                        object.object_type
+                    ))
+                })?,
+        };
+        let layout = self.stat_block_layout(&system)?.ok_or_else(|| {
+            UForgeError::ValidationFailed(format!("Unknown stat block system '{system}'"))
+        })?;
+        let errors = layout.validate(&values);
+        if !errors.is_empty() {
+            return Err(UForgeError::ValidationFailed(errors.join("; ")));
+        }
+
+        let stored = serde_json::json!({ "system": system, "values": values });
+        self.storage
+            .set_node_property(id, STAT_BLOCK_KEY, &stored)?;
+        Ok(StatBlock {
+            object_id: id,
+            name: object.name,
+            derived: layout.derive(&values),
+            layout,
+            values,
+        })
+    }
+
+    /// The stat block of `id`, or `None` when it has none.  A block whose
+    /// system is no longer registered is reported as
+    /// [`UForgeError::NotFound`].
+    pub fn stat_block(&self, id: ObjectId) -> Result<Option<StatBlock>> {
+        let Some(object) = self.get_object(id)? else {
+            return Ok(None);
+        };
+        let Some(stored) = object.get_json_property(STAT_BLOCK_KEY) else {
+            return Ok(None);
+        };
+        let system = stored
+            .get("system")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let values = stored
+            .get("values")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        let layout = self.stat_block_layout(&system)?.ok_or_else(|| {
+            UForgeError::NotFound(format!("Unknown stat block system '{system}'"))
+        })?;
+        Ok(Some(StatBlock {
+            object_id: id,
+            name: object.name,
+            derived: layout.derive(&values),
+            layout,
+            values,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+    use serde_json::json;
+
+    fn values(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_builtin_layouts_are_consistent() {
+        for layout in StatBlockLayout::builtin() {
+            assert!(layout.check_layout().is_empty(), "{}", layout.system);
+        }
+        assert!(is_dice("2d6+1"));
+        assert!(is_dice("d20"));
+        assert!(!is_dice("2d"));
+        assert!(!is_dice("sword"));
+    }
+
+    #[test]
+    fn test_set_stat_block_validates_and_derives() {
+        let (graph, _temp_dir) = create_test_graph();
+        let goblin = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Goblin".to_string()))
+            .unwrap();
+
+        let err = graph
+            .set_stat_block(
+                goblin,
+                Some("5e"),
+                values(json!({"ac": 15, "hp": "seven", "str_mod": 1, "luck": 3})),
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+        let message = err.to_string();
+        assert!(message.contains("Hit Points must be a whole number"));
+        assert!(message.contains("STR mod is derived"));
+        assert!(message.contains("Unknown 5e stat 'luck'"));
+        assert!(message.contains("DEX is required"));
+        assert!(graph.stat_block(goblin).unwrap().is_none());
+
+        graph
+            .set_stat_block(
+                goblin,
+                Some("5e"),
+                values(json!({
+                    "ac": 15, "hp": 7, "hit_dice": "2d6", "speed": "30 ft.",
+                    "str": 8, "dex": 14, "con": 10, "int": 10, "wis": 8, "cha": 8,
+                    "actions": ["Scimitar. +4 to hit, 5 (1d6 + 2) slashing."]
+                })),
+            )
+            .unwrap();
+        let block = graph.stat_block(goblin).unwrap().unwrap();
+        assert_eq!(block.get("dex_mod"), Some(&json!(2)));
+        assert_eq!(block.get("str_mod"), Some(&json!(-1)));
+        assert_eq!(block.get("passive_perception"), Some(&json!(9)));
+
+        let markdown = block.render_markdown();
+        assert!(markdown.starts_with("### Goblin\n*D&D 5th Edition*"));
+        assert!(markdown.contains("- **DEX mod:** +2"));
+        assert!(markdown.contains("- **STR mod:** -1"));
+        assert!(block.render_text().contains("  Armor Class: 15"));
+    }
+
+    #[test]
+    fn test_custom_layout_and_schema_default() {
+        let (graph, _temp_dir) = create_test_graph();
+
+        let broken = StatBlockLayout::new("mini", "Mini").with_section(
+            "Stats",
+            vec![StatField::derived(
+                "total",
+                "Total",
+                StatFieldType::Integer,
+                "might + missing",
+            )],
+        );
+        let err = graph.register_stat_block_layout(broken).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let layout = StatBlockLayout::new("mini", "Mini").with_section(
+            "Stats",
+            vec![
+                StatField::integer("might", "Might").required(),
+                StatField::derived("double", "Double", StatFieldType::Integer, "might * 2"),
+            ],
+        );
+        graph.register_stat_block_layout(layout).unwrap();
+        assert_eq!(graph.stat_block_layouts().unwrap().len(), 4);
+
+        let hero = graph
+            .add_object(ObjectMetadata::new("npc".to_string(), "Hero".to_string()))
+            .unwrap();
+        let err = graph
+            .set_stat_block(hero, None, values(json!({"might": 3})))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+
+        let block = graph
+            .set_stat_block(hero, Some("mini"), values(json!({"might": 3})))
+            .unwrap();
+        assert_eq!(block.get("double"), Some(&json!(6)));
+    }
+}
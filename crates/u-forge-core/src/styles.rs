@@ -0,0 +1,298 @@
+//! Display styling for object types — colour, icon, and node shape.
+//!
+//! Styles live in the type's schema metadata so they travel with the schema:
+//!
+//! | key     | value                                             |
+//! |---------|---------------------------------------------------|
+//! | `color` | `#rrggbb` hex                                     |
+//! | `icon`  | free-form icon name or emoji, e.g. `"castle"`     |
+//! | `shape` | `circle`, `square`, `diamond`, or `hexagon`       |
+//!
+//! [`KnowledgeGraph::type_style`] resolves the effective [`NodeStyle`]: the
+//! schema values where set and valid, otherwise a stable colour derived from
+//! the type name ([`default_type_color`]), no icon, and a circle.  Graph data
+//! carries the styles of the types it returns ([`GraphData::styles`]) so
+//! renderers and exports never hard-code per-type colours.
+//!
+//! [`GraphData::styles`]: crate::GraphData::styles
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::schema::ObjectTypeSchema;
+use crate::types::ObjectMetadata;
+use crate::KnowledgeGraph;
+
+/// Schema metadata key holding a type's `#rrggbb` colour.
+pub const STYLE_COLOR_KEY: &str = "color";
+/// Schema metadata key holding a type's icon name.
+pub const STYLE_ICON_KEY: &str = "icon";
+/// Schema metadata key holding a type's [`NodeShape`].
+pub const STYLE_SHAPE_KEY: &str = "shape";
+
+/// Outline used to draw a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeShape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    Hexagon,
+}
+
+impl NodeShape {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeShape::Circle => "circle",
+            NodeShape::Square => "square",
+            NodeShape::Diamond => "diamond",
+            NodeShape::Hexagon => "hexagon",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "circle" => Some(NodeShape::Circle),
+            "square" => Some(NodeShape::Square),
+            "diamond" => Some(NodeShape::Diamond),
+            "hexagon" => Some(NodeShape::Hexagon),
+            _ => None,
+        }
+    }
+}
+
+/// Effective display style of an object type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStyle {
+    /// sRGB colour.
+    pub color: [u8; 3],
+    pub icon: Option<String>,
+    pub shape: NodeShape,
+}
+
+impl NodeStyle {
+    /// The unstyled default for `object_type`.
+    pub fn default_for(object_type: &str) -> Self {
+        Self {
+            color: default_type_color(object_type),
+            icon: None,
+            shape: NodeShape::Circle,
+        }
+    }
+
+    /// Apply the style keys found in `schema`'s metadata.  Invalid colours
+    /// and shapes are ignored.
+    fn from_schema(object_type: &str, schema: Option<&ObjectTypeSchema>) -> Self {
+        let mut style = Self::default_for(object_type);
+        let Some(metadata) = schema.map(|s| &s.metadata) else {
+            return style;
+        };
+        if let Some(color) = metadata
+            .get(STYLE_COLOR_KEY)
+            .and_then(|c| parse_hex_color(c))
+        {
+            style.color = color;
+        }
+        style.icon = metadata
+            .get(STYLE_ICON_KEY)
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty());
+        if let Some(shape) = metadata
+            .get(STYLE_SHAPE_KEY)
+            .and_then(|s| NodeShape::parse(s))
+        {
+            style.shape = shape;
+        }
+        style
+    }
+
+    /// `#rrggbb`.
+    pub fn hex_color(&self) -> String {
+        let [r, g, b] = self.color;
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+/// Parse `#rrggbb` or `#rgb` (the `#` is optional).
+pub fn parse_hex_color(s: &str) -> Option<[u8; 3]> {
+    let hex = s.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?]),
+        3 => {
+            let [r, g, b] = [channel(0, 1)?, channel(1, 1)?, channel(2, 1)?];
+            Some([r * 17, g * 17, b * 17])
+        }
+        _ => None,
+    }
+}
+
+/// A stable, visually distinct colour for any type name.
+///
+/// Hue is derived from the type name via an FNV-1a hash scattered with the
+/// golden angle, giving well-separated hues for any set of names.
+/// Saturation and lightness are fixed (~75 % / 76 %) to match the Catppuccin
+/// Mocha accent palette used by the UI.
+pub fn default_type_color(object_type: &str) -> [u8; 3] {
+    let hash = object_type.bytes().fold(2_166_136_261_u32, |acc, b| {
+        acc.wrapping_mul(16_777_619).wrapping_add(b as u32)
+    });
+    let hue = ((hash as f64 * 0.618_033_988_749_895).fract() * 360.0) as f32;
+    hsl_to_rgb(hue, 0.75, 0.76)
+}
+
+/// Convert HSL (h in [0,360), s and l in [0,1]) to sRGB bytes.
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [u8; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r1, g1, b1) = if h < 60.0 {
+        (c, x, 0.0)
+    } else if h < 120.0 {
+        (x, c, 0.0)
+    } else if h < 180.0 {
+        (0.0, c, x)
+    } else if h < 240.0 {
+        (0.0, x, c)
+    } else if h < 300.0 {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+    [
+        ((r1 + m) * 255.0).round() as u8,
+        ((g1 + m) * 255.0).round() as u8,
+        ((b1 + m) * 255.0).round() as u8,
+    ]
+}
+
+impl KnowledgeGraph {
+    /// Effective style of `object_type` in the `"default"` schema.
+    pub fn type_style(&self, object_type: &str) -> NodeStyle {
+        NodeStyle::from_schema(
+            object_type,
+            self.object_type_schema("default", object_type).as_ref(),
+        )
+    }
+
+    /// Effective style of `object`, looked up in its own schema.
+    pub fn object_style(&self, object: &ObjectMetadata) -> NodeStyle {
+        NodeStyle::from_schema(
+            &object.object_type,
+            self.object_type_schema_for(object).as_ref(),
+        )
+    }
+
+    /// Effective styles of `object_types`, keyed by type.
+    pub fn type_styles<'a>(
+        &self,
+        object_types: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, NodeStyle> {
+        object_types
+            .into_iter()
+            .map(|t| (t.to_string(), self.type_style(t)))
+            .collect()
+    }
+
+    /// Set the style keys in the schema metadata of `object_type` (in the
+    /// `"default"` schema).  `None` removes a key, reverting that part of the
+    /// style to the default.
+    ///
+    /// Fails with [`UForgeError::NotFound`] if the type is not registered,
+    /// and with [`UForgeError::ValidationFailed`] for a malformed colour.
+    pub async fn set_type_style(
+        &self,
+        object_type: &str,
+        color: Option<&str>,
+        icon: Option<&str>,
+        shape: Option<NodeShape>,
+    ) -> Result<()> {
+        let schema = self.schema_manager.load_schema("default").await?;
+        let Some(mut type_schema) = schema.object_types.get(object_type).cloned() else {
+            return Err(UForgeError::NotFound(format!(
+                "Unknown object type '{object_type}'"
+            )));
+        };
+        if let Some(color) = color {
+            if parse_hex_color(color).is_none() {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "'{color}' is not a #rrggbb colour"
+                )));
+            }
+        }
+        for (key, value) in [
+            (STYLE_COLOR_KEY, color.map(str::to_string)),
+            (STYLE_ICON_KEY, icon.map(str::to_string)),
+            (STYLE_SHAPE_KEY, shape.map(|s| s.as_str().to_string())),
+        ] {
+            match value {
+                Some(value) => type_schema.metadata.insert(key.to_string(), value),
+                None => type_schema.metadata.remove(key),
+            };
+        }
+        self.register_object_type(object_type, type_schema).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#1e66f5"), Some([0x1e, 0x66, 0xf5]));
+        assert_eq!(parse_hex_color("fff"), Some([255, 255, 255]));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+        assert_eq!(default_type_color("npc"), default_type_color("npc"));
+    }
+
+    #[tokio::test]
+    async fn test_type_style_from_schema() {
+        let (graph, _temp_dir) = create_test_graph();
+        graph
+            .register_object_type(
+                "deity",
+                ObjectTypeSchema::new("deity".to_string(), "A god".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(graph.type_style("deity"), NodeStyle::default_for("deity"));
+
+        graph
+            .set_type_style(
+                "deity",
+                Some("#ffd700"),
+                Some("sun"),
+                Some(NodeShape::Hexagon),
+            )
+            .await
+            .unwrap();
+        let style = graph.type_style("deity");
+        assert_eq!(style.hex_color(), "#ffd700");
+        assert_eq!(style.icon.as_deref(), Some("sun"));
+        assert_eq!(style.shape, NodeShape::Hexagon);
+
+        let pelor = ObjectBuilder::custom("deity".to_string(), "Pelor".to_string()).build();
+        assert_eq!(graph.object_style(&pelor), style);
+
+        let err = graph
+            .set_type_style("deity", Some("gold"), None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::ValidationFailed);
+        let err = graph
+            .set_type_style("pantheon", None, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::NotFound);
+    }
+}
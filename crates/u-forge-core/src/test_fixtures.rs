@@ -0,0 +1,371 @@
+//! Deterministic synthetic worlds for tests and benchmarks.
+//!
+//! Built for this crate's tests; other crates enable the `test-fixtures`
+//! feature to use it.
+//!
+//! [`generate_world`] builds a fantasy setting from a seed: characters,
+//! locations, factions, items and events in roughly the proportions real
+//! campaigns have, wired with the default schema's edge types (`member_of`,
+//! `knows`, `contains`, `involves`, `ally_of`, `enemy_of`) and given one to
+//! three text chunks each.  Edge targets are drawn with a bias towards
+//! early objects, so a few hubs (the capital, the big guilds) collect most
+//! links, as in hand-built worlds.
+//!
+//! The same seed and [`SizeProfile`] always give the same world — names,
+//! ids ([`ObjectId::deterministic`]), timestamps and text — so tests can
+//! assert on it and benchmarks compare like with like.
+//! [`KnowledgeGraph::load_demo_world`] writes one into a project.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::Result;
+use crate::types::{ChunkType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// How big a world [`generate_world`] makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeProfile {
+    /// 25 objects — unit tests.
+    Tiny,
+    /// 250 objects — integration tests.
+    #[default]
+    Small,
+    /// 2 500 objects — a long-running campaign.
+    Medium,
+    /// 25 000 objects — benchmarks.
+    Large,
+}
+
+impl SizeProfile {
+    pub fn object_count(self) -> usize {
+        match self {
+            SizeProfile::Tiny => 25,
+            SizeProfile::Small => 250,
+            SizeProfile::Medium => 2_500,
+            SizeProfile::Large => 25_000,
+        }
+    }
+}
+
+/// An edge between two objects of a [`GeneratedWorld`], by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedEdge {
+    pub from: usize,
+    pub to: usize,
+    pub edge_type: &'static str,
+}
+
+/// A chunk of one object of a [`GeneratedWorld`], by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedChunk {
+    pub object: usize,
+    pub text: String,
+}
+
+/// Output of [`generate_world`].
+#[derive(Debug, Clone)]
+pub struct GeneratedWorld {
+    pub seed: u64,
+    pub objects: Vec<ObjectMetadata>,
+    pub edges: Vec<GeneratedEdge>,
+    pub chunks: Vec<GeneratedChunk>,
+}
+
+/// Counts from [`GeneratedWorld::install`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WorldStats {
+    pub objects: usize,
+    pub edges: usize,
+    pub chunks: usize,
+}
+
+impl GeneratedWorld {
+    /// Write the world into `graph`.  Objects keep their generated ids, so
+    /// installing the same world twice overwrites rather than duplicates.
+    pub fn install(&self, graph: &KnowledgeGraph) -> Result<WorldStats> {
+        let mut stats = WorldStats::default();
+        for object in &self.objects {
+            if graph.get_object(object.id)?.is_some() {
+                graph.update_object(object.clone())?;
+            } else {
+                graph.add_object(object.clone())?;
+                stats.objects += 1;
+            }
+        }
+        for edge in &self.edges {
+            graph.connect_objects_str(
+                self.objects[edge.from].id,
+                self.objects[edge.to].id,
+                edge.edge_type,
+            )?;
+            stats.edges += 1;
+        }
+        for chunk in &self.chunks {
+            let id = self.objects[chunk.object].id;
+            if !graph
+                .get_text_chunks(id)?
+                .iter()
+                .any(|c| c.content == chunk.text)
+            {
+                stats.chunks += graph
+                    .add_text_chunk(id, chunk.text.clone(), ChunkType::UserNote)?
+                    .len();
+            }
+        }
+        Ok(stats)
+    }
+}
+
+impl KnowledgeGraph {
+    /// Generate the world for `seed` and `profile` and install it.
+    pub fn load_demo_world(&self, seed: u64, profile: SizeProfile) -> Result<WorldStats> {
+        generate_world(seed, profile).install(self)
+    }
+}
+
+// ── Generation ────────────────────────────────────────────────────────────────
+
+/// SplitMix64: small, fast, and stable across platforms and releases.
+struct WorldRng(u64);
+
+impl WorldRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must be positive.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// In `0..n`, favouring small values (density `2(1-x)`).
+    fn skewed(&mut self, n: usize) -> usize {
+        self.below(n).min(self.below(n))
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+}
+
+/// Object types and their share of the world, in percent.
+const TYPE_MIX: &[(&str, usize)] = &[
+    ("character", 40),
+    ("location", 25),
+    ("item", 15),
+    ("event", 12),
+    ("faction", 8),
+];
+
+const SYLLABLES: &[&str] = &[
+    "al", "bar", "cor", "dra", "el", "fen", "gal", "hal", "ith", "kor", "lan", "mir", "nor",
+    "oth", "pel", "quin", "ros", "sil", "tar", "ul", "vel", "wyn", "yor", "zan",
+];
+const SPECIES: &[&str] = &["human", "elf", "dwarf", "halfling", "gnome", "orc", "tiefling"];
+const OCCUPATIONS: &[&str] = &[
+    "innkeeper", "blacksmith", "priest", "merchant", "guard captain", "scholar", "thief",
+    "ranger", "noble", "alchemist",
+];
+const LOCATION_KINDS: &[&str] = &["city", "village", "keep", "forest", "ruin", "tavern", "temple", "mine"];
+const MOODS: &[&str] = &["bustling", "eerie", "peaceful", "grim", "festive", "decaying"];
+const ITEM_KINDS: &[&str] = &["sword", "amulet", "tome", "ring", "staff", "map", "chalice"];
+const FACTION_KINDS: &[&str] = &["Guild", "Order", "Circle", "Company", "Brotherhood", "Court"];
+const EVENT_KINDS: &[&str] = &["Siege", "Feast", "Heist", "Plague", "Coronation", "Duel"];
+
+/// Fixed origin for generated timestamps, so worlds are reproducible.
+fn epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Generate the world for `seed`; see the module docs.
+pub fn generate_world(seed: u64, profile: SizeProfile) -> GeneratedWorld {
+    let mut rng = WorldRng(seed);
+    let id_seed = format!("synthetic:{seed}");
+    let total = profile.object_count();
+
+    // Objects, grouped by type so edges can target a type.
+    let mut objects = Vec::with_capacity(total);
+    let mut by_type: Vec<(&str, Vec<usize>)> = Vec::new();
+    let mut used_names = std::collections::HashSet::new();
+    for (ty, share) in TYPE_MIX {
+        let count = (total * share / 100).max(1);
+        let mut indices = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut name = object_name(&mut rng, ty);
+            while !used_names.insert((*ty, name.clone())) {
+                name = format!("{name} {}", to_title(rng.pick(SYLLABLES)));
+            }
+            let mut object = ObjectMetadata::new(ty.to_string(), name.clone());
+            object.id = ObjectId::deterministic(&id_seed, ty, &name);
+            object.created_at = epoch() + Duration::minutes(objects.len() as i64);
+            object.updated_at = object.created_at;
+            object.properties = object_properties(&mut rng, ty);
+            indices.push(objects.len());
+            objects.push(object);
+        }
+        by_type.push((ty, indices));
+    }
+    let of_type = |ty: &str| -> &[usize] {
+        by_type
+            .iter()
+            .find(|(t, _)| *t == ty)
+            .map(|(_, v)| v.as_slice())
+            .unwrap_or(&[])
+    };
+    let (characters, locations, items, events, factions) = (
+        of_type("character").to_vec(),
+        of_type("location").to_vec(),
+        of_type("item").to_vec(),
+        of_type("event").to_vec(),
+        of_type("faction").to_vec(),
+    );
+
+    let mut edges = Vec::new();
+    let mut link = |rng: &mut WorldRng, from: usize, pool: &[usize], edge_type: &'static str| {
+        let to = pool[rng.skewed(pool.len())];
+        if to != from {
+            edges.push(GeneratedEdge { from, to, edge_type });
+        }
+    };
+    // Locations nest into a tree rooted at the first (the capital).
+    for (n, &location) in locations.iter().enumerate().skip(1) {
+        link(&mut rng, location, &locations[..n], "contains");
+    }
+    for &character in &characters {
+        if rng.chance(70) {
+            link(&mut rng, character, &factions, "member_of");
+        }
+        for _ in 0..1 + rng.below(3) {
+            link(&mut rng, character, &characters, "knows");
+        }
+    }
+    for &item in &items {
+        link(&mut rng, item, &locations, "related_to");
+    }
+    for &event in &events {
+        for _ in 0..1 + rng.below(4) {
+            link(&mut rng, event, &characters, "involves");
+        }
+        link(&mut rng, event, &locations, "related_to");
+    }
+    for &faction in &factions {
+        let relation = if rng.chance(50) { "ally_of" } else { "enemy_of" };
+        link(&mut rng, faction, &factions, relation);
+    }
+    edges.sort_by(|a, b| (a.from, a.to, a.edge_type).cmp(&(b.from, b.to, b.edge_type)));
+    edges.dedup();
+
+    let mut chunks = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        for n in 0..1 + rng.below(3) {
+            chunks.push(GeneratedChunk {
+                object: index,
+                text: chunk_text(&mut rng, object, n),
+            });
+        }
+    }
+
+    GeneratedWorld {
+        seed,
+        objects,
+        edges,
+        chunks,
+    }
+}
+
+fn to_title(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn word(rng: &mut WorldRng) -> String {
+    let syllables = 2 + rng.below(2);
+    to_title(&(0..syllables).map(|_| rng.pick(SYLLABLES)).collect::<String>())
+}
+
+fn object_name(rng: &mut WorldRng, ty: &str) -> String {
+    match ty {
+        "character" => format!("{} {}", word(rng), word(rng)),
+        "location" => format!("{} {}", word(rng), to_title(rng.pick(LOCATION_KINDS))),
+        "item" => format!("{} of {}", to_title(rng.pick(ITEM_KINDS)), word(rng)),
+        "event" => format!("{} of {}", rng.pick(EVENT_KINDS), word(rng)),
+        _ => format!("{} of {}", rng.pick(FACTION_KINDS), word(rng)),
+    }
+}
+
+fn object_properties(rng: &mut WorldRng, ty: &str) -> serde_json::Value {
+    match ty {
+        "character" => json!({
+            "species": rng.pick(SPECIES),
+            "occupation": rng.pick(OCCUPATIONS),
+            "status": if rng.chance(90) { "alive" } else { "dead" },
+        }),
+        "location" => json!({
+            "type": rng.pick(LOCATION_KINDS),
+            "atmosphere": rng.pick(MOODS),
+        }),
+        _ => json!({}),
+    }
+}
+
+fn chunk_text(rng: &mut WorldRng, object: &ObjectMetadata, n: usize) -> String {
+    let mood = rng.pick(MOODS);
+    match (object.object_type.as_str(), n) {
+        ("character", 0) => format!(
+            "{} is a {} {} known around the {} quarter.",
+            object.name,
+            rng.pick(SPECIES),
+            rng.pick(OCCUPATIONS),
+            mood
+        ),
+        ("location", 0) => format!("{} is a {mood} place the party has heard rumours about.", object.name),
+        (_, 0) => format!("Notes on {}: the details are still {mood}.", object.name),
+        _ => format!(
+            "Session note {n}: the party learned something {mood} about {} from {}.",
+            object.name,
+            word(rng)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_generate_world_is_deterministic_and_installs() {
+        let a = generate_world(7, SizeProfile::Tiny);
+        let b = generate_world(7, SizeProfile::Tiny);
+        let names = |w: &GeneratedWorld| w.objects.iter().map(|o| o.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&a), names(&b));
+        assert_eq!(a.edges, b.edges);
+        assert_eq!(a.chunks, b.chunks);
+        assert_ne!(names(&a), names(&generate_world(8, SizeProfile::Tiny)));
+        assert!(a.objects.len() >= SizeProfile::Tiny.object_count() - TYPE_MIX.len());
+        assert!(a.edges.iter().any(|e| e.edge_type == "contains"));
+
+        let (graph, _temp_dir) = create_test_graph();
+        let stats = graph.load_demo_world(7, SizeProfile::Tiny).unwrap();
+        assert_eq!(stats.objects, a.objects.len());
+        assert_eq!(stats.chunks, a.chunks.len());
+        // Loading again adds nothing new.
+        let again = graph.load_demo_world(7, SizeProfile::Tiny).unwrap();
+        assert_eq!((again.objects, again.chunks), (0, 0));
+        assert_eq!(graph.get_all_objects().unwrap().len(), a.objects.len());
+    }
+}
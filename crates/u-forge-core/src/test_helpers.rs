@@ -27,6 +27,22 @@
 //!     // ... test body ...
 //! }
 //! ```
+//!
+//! # Graphs
+//!
+//! Unit tests that need an empty graph call [`create_test_graph`]; keep the
+//! returned [`TempDir`] alive for as long as the graph is used.
+
+use tempfile::TempDir;
+
+use crate::KnowledgeGraph;
+
+/// An empty [`KnowledgeGraph`] in a fresh temporary directory.
+pub(crate) fn create_test_graph() -> (KnowledgeGraph, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let graph = KnowledgeGraph::new(temp_dir.path()).unwrap();
+    (graph, temp_dir)
+}
 
 /// Returns the resolved Lemonade URL if integration tests should run.
 ///
@@ -1,25 +1,101 @@
-//! Text splitting utility for chunk-size management.
+//! Token counting and text splitting for chunk-size management and context budgeting.
 
 use std::sync::LazyLock;
 
 use tiktoken_rs::CoreBPE;
 use tracing::info;
 
-use crate::graph::MAX_CHUNK_TOKENS;
+/// Default context window size (in tokens) assumed for the active embedding model.
+///
+/// Used to derive [`MAX_CHUNK_TOKENS`].  The standard embedding model
+/// (`embed-gemma-300m-FLM` / `embeddinggemma-300M-GGUF`) supports a 2048-token
+/// context window.
+pub const DEFAULT_EMBEDDING_CONTEXT_TOKENS: usize = 2048;
+
+/// Maximum number of tokens per stored text chunk.
+///
+/// Chunks larger than this are split at word boundaries by
+/// [`KnowledgeGraph::add_text_chunk`](crate::KnowledgeGraph::add_text_chunk)
+/// before being written to storage.
+///
+/// Set to half of [`DEFAULT_EMBEDDING_CONTEXT_TOKENS`] to give a safe margin
+/// against the `len/4` character heuristic — which tends to *under*count real
+/// tokens for dense prose by 30–50%.  At 2048 heuristic tokens, a 50%
+/// undercount would yield ~3072 real tokens, still well within the 4096 context
+/// window.  Nodes are now embedded as a single flattened document, so this
+/// budget needs to accommodate an entire node's metadata in one chunk.
+pub const MAX_CHUNK_TOKENS: usize = DEFAULT_EMBEDDING_CONTEXT_TOKENS / 2;
 
 /// Cached o200k_harmony BPE tokenizer — constructed once, reused forever.
 ///
 /// `o200k_harmony()` parses a ~200 k-entry vocabulary on every call; caching
-/// it here turns repeated `count_tokens` invocations (e.g. inside
+/// it here turns repeated `count_chunk_tokens` invocations (e.g. inside
 /// [`split_text`]'s per-word loop) from O(N × vocab_parse) into O(N × encode).
 static O200K_BPE: LazyLock<CoreBPE> =
     LazyLock::new(|| tiktoken_rs::o200k_harmony().expect("o200k_harmony is always available"));
 
-/// Count tokens in `text` using the o200k_harmony BPE tokenizer.
-pub(crate) fn count_tokens(text: &str) -> usize {
+/// Cached cl100k_base BPE tokenizer for pre-4o OpenAI models.
+static CL100K_BPE: LazyLock<CoreBPE> =
+    LazyLock::new(|| tiktoken_rs::cl100k_base().expect("cl100k_base is always available"));
+
+/// Model name used when callers have no specific model in mind.
+///
+/// Resolves to o200k_harmony — the encoding used for chunk sizing.
+pub const DEFAULT_TOKENIZER_MODEL: &str = "o200k_harmony";
+
+/// Pick the cached BPE that matches `model`.
+///
+/// `model` may be a model id (`"gpt-4"`, `"Qwen3-8B-GGUF"`) or an encoding
+/// name (`"cl100k_base"`, `"o200k_harmony"`).  Pre-4o OpenAI chat and
+/// embedding models use cl100k_base; everything else — including the local
+/// GGUF models served by Lemonade, whose own tokenizers are not available
+/// here — uses o200k_harmony, which tracks modern open-weight vocabularies
+/// closely enough for budgeting.
+fn bpe_for_model(model: &str) -> &'static CoreBPE {
+    let m = model.to_ascii_lowercase();
+    let cl100k = m == "cl100k_base"
+        || m.starts_with("gpt-3.5")
+        || m.starts_with("text-embedding-ada")
+        || m.starts_with("text-embedding-3")
+        || (m.starts_with("gpt-4") && !m.starts_with("gpt-4o") && !m.starts_with("gpt-4.1"));
+    if cl100k {
+        &CL100K_BPE
+    } else {
+        &O200K_BPE
+    }
+}
+
+/// Count BPE tokens in `text` using the tokenizer that matches `model`.
+///
+/// Use for LLM context budgeting (RAG context assembly, history windows).
+/// Pass [`DEFAULT_TOKENIZER_MODEL`] when the target model is unknown.
+pub fn count_tokens(text: &str, model: &str) -> usize {
+    bpe_for_model(model).encode_with_special_tokens(text).len()
+}
+
+/// Count tokens in `text` with the encoding used for chunk sizing.
+///
+/// Chunking must stay stable regardless of which chat model is configured,
+/// so this is pinned to o200k_harmony and is what
+/// [`TextChunk::token_count`](crate::types::TextChunk::token_count) stores.
+pub(crate) fn count_chunk_tokens(text: &str) -> usize {
     O200K_BPE.encode_with_special_tokens(text).len()
 }
 
+/// Stable 64-bit FNV-1a hash of `text`, rendered as 16 lowercase hex digits.
+///
+/// Used to detect whether chunk content actually changed before archiving a
+/// revision or discarding an embedding.  Not cryptographic — only needs to be
+/// deterministic across runs and builds, which `std::hash` does not promise.
+pub(crate) fn content_hash(text: &str) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = text
+        .bytes()
+        .fold(OFFSET, |h, b| (h ^ u64::from(b)).wrapping_mul(PRIME));
+    format!("{hash:016x}")
+}
+
 /// Bisect `word` at character midpoints until every piece fits within
 /// [`MAX_CHUNK_TOKENS`]. Used for words (or runs of text without whitespace,
 /// such as CJK prose or base64 blobs) that cannot be split at spaces.
@@ -27,7 +103,7 @@ pub(crate) fn count_tokens(text: &str) -> usize {
 /// Logs at `info` level when a hard-split fires — useful signal during
 /// ingestion of non-Latin corpora.
 fn split_oversized_word(word: &str) -> Vec<String> {
-    if count_tokens(word) <= MAX_CHUNK_TOKENS {
+    if count_chunk_tokens(word) <= MAX_CHUNK_TOKENS {
         return vec![word.to_string()];
     }
     info!(
@@ -50,32 +126,57 @@ fn split_oversized_word(word: &str) -> Vec<String> {
 /// Token counts are measured with the o200k_harmony BPE tokenizer so that the
 /// budget is exact and consistent with what is stored in
 /// [`TextChunk::token_count`].
+#[allow(dead_code)]
 pub(crate) fn split_text(text: &str) -> Vec<String> {
+    split_text_for_language(text, None)
+}
+
+/// [`split_text`] with a language hint (BCP 47 tag such as `"ja"` or
+/// `"pt-BR"`).
+///
+/// Languages written without spaces between words (see
+/// [`is_unspaced_language`]) are packed sentence by sentence instead of
+/// word by word, so pieces end at `。`/`！`/`？` rather than being bisected
+/// mid-sentence.  Their original spacing is preserved.  Other languages — and
+/// `None` — use the whitespace strategy.
+pub(crate) fn split_text_for_language(text: &str, language: Option<&str>) -> Vec<String> {
     let text = text.trim();
     if text.is_empty() {
         return vec![];
     }
 
     // Fast path: entire text fits in one chunk.
-    if count_tokens(text) <= MAX_CHUNK_TOKENS {
+    if count_chunk_tokens(text) <= MAX_CHUNK_TOKENS {
         return vec![text.to_string()];
     }
 
+    if language.is_some_and(is_unspaced_language) {
+        let sentences = text.split_inclusive(|c: char| {
+            matches!(c, '。' | '！' | '？' | '!' | '?' | '\n') || c.is_whitespace()
+        });
+        pack_units(sentences, "")
+    } else {
+        pack_units(text.split_whitespace(), " ")
+    }
+}
+
+/// Greedily join `units` with `sep` into pieces that fit the token budget.
+fn pack_units<'a>(units: impl Iterator<Item = &'a str>, sep: &str) -> Vec<String> {
     let mut pieces: Vec<String> = Vec::new();
     let mut current_words: Vec<&str> = Vec::new();
 
-    for word in text.split_whitespace() {
+    for word in units {
         current_words.push(word);
-        let candidate = current_words.join(" ");
-        if count_tokens(&candidate) > MAX_CHUNK_TOKENS {
+        let candidate = current_words.join(sep);
+        if count_chunk_tokens(&candidate) > MAX_CHUNK_TOKENS {
             if current_words.len() == 1 {
                 // Single token-dense word (CJK, base64, etc.) — bisect it.
-                pieces.extend(split_oversized_word(&candidate));
+                pieces.extend(split_oversized_word(candidate.trim()));
                 current_words.clear();
             } else {
                 // Flush everything except the word that pushed us over.
                 current_words.pop();
-                pieces.push(current_words.join(" "));
+                pieces.push(current_words.join(sep).trim().to_string());
                 current_words.clear();
                 current_words.push(word);
             }
@@ -83,16 +184,132 @@ pub(crate) fn split_text(text: &str) -> Vec<String> {
     }
 
     if !current_words.is_empty() {
-        pieces.push(current_words.join(" "));
+        pieces.push(current_words.join(sep).trim().to_string());
     }
 
+    pieces.retain(|p| !p.is_empty());
     pieces
 }
 
+/// Primary subtag of a BCP 47 language tag, lowercased (`"pt-BR"` → `"pt"`).
+pub(crate) fn primary_language(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// `true` for languages conventionally written without spaces between
+/// words: Chinese, Japanese, Thai, Lao, Khmer, Burmese, and Tibetan.
+pub(crate) fn is_unspaced_language(tag: &str) -> bool {
+    matches!(
+        primary_language(tag).as_str(),
+        "zh" | "ja" | "th" | "lo" | "km" | "my" | "bo"
+    )
+}
+
+/// `true` when `text` contains a character from a script written without
+/// spaces between words — the scripts of [`is_unspaced_language`].
+pub(crate) fn has_unspaced_script(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c as u32,
+            0x0E00..=0x0EFF     // Thai, Lao
+            | 0x0F00..=0x0FFF   // Tibetan
+            | 0x1000..=0x109F   // Myanmar
+            | 0x1780..=0x17FF   // Khmer
+            | 0x3040..=0x30FF   // Hiragana, Katakana
+            | 0x31F0..=0x31FF   // Katakana phonetic extensions
+            | 0x3400..=0x4DBF   // CJK extension A
+            | 0x4E00..=0x9FFF   // CJK unified ideographs
+            | 0xF900..=0xFAFF   // CJK compatibility ideographs
+            | 0xFF66..=0xFF9F   // Half-width katakana
+            | 0x20000..=0x3134F // CJK extensions B–G
+        )
+    })
+}
+
+/// `true` when `tag` is English (or empty, which callers treat as the default).
+pub(crate) fn is_english(tag: &str) -> bool {
+    matches!(primary_language(tag).as_str(), "" | "en")
+}
+
+static SCRIPT_OR_STYLE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?is)<(script|style)\b[^>]*>.*?</(script|style)\s*>").unwrap()
+});
+
+static BLOCK_BREAK: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?i)<br\s*/?>|</?(p|div|li|ul|ol|tr|table|h[1-6]|blockquote)\b[^>]*>")
+        .unwrap()
+});
+
+static TAG: LazyLock<regex::Regex> = LazyLock::new(|| regex::Regex::new(r"(?s)<[^>]*>").unwrap());
+
+static ENTITY: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+
+/// Plain text from untrusted HTML, e.g. notes exported from a VTT.
+///
+/// Scripts and styles are dropped with their content, block elements and
+/// `<br>` become line breaks, every other tag is removed, and character
+/// references are decoded.  Lines are trimmed and blank ones dropped, so the
+/// result is ready to chunk.
+pub fn html_to_text(html: &str) -> String {
+    let text = SCRIPT_OR_STYLE.replace_all(html, "");
+    let text = BLOCK_BREAK.replace_all(&text, "\n");
+    let text = TAG.replace_all(&text, "");
+    let text = ENTITY.replace_all(&text, |caps: &regex::Captures| {
+        let name = &caps[1];
+        let decoded = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ if name.starts_with("#x") || name.starts_with("#X") => {
+                u32::from_str_radix(&name[2..], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            _ if name.starts_with('#') => name[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        decoded.map_or_else(|| caps[0].to_string(), String::from)
+    });
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_count_tokens_selects_encoding_by_model() {
+        let text = "The wizard's staff glowed with arcane fire.";
+        assert_eq!(count_tokens(text, DEFAULT_TOKENIZER_MODEL), count_chunk_tokens(text));
+        assert_eq!(count_tokens(text, "Qwen3-8B-GGUF"), count_chunk_tokens(text));
+        assert_eq!(
+            count_tokens(text, "gpt-4"),
+            count_tokens(text, "cl100k_base")
+        );
+        assert!(count_tokens(text, "gpt-3.5-turbo") > 0);
+        assert_eq!(count_tokens("", "gpt-4"), 0);
+    }
+
+    #[test]
+    fn test_content_hash_is_stable_and_sensitive() {
+        // Reference value for FNV-1a 64 of the empty string.
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("Gandalf"), content_hash("Gandalf"));
+        assert_ne!(content_hash("Gandalf"), content_hash("Gandalf."));
+    }
+
     #[test]
     fn test_split_text_short_content_is_not_split() {
         let pieces = split_text("A short description.");
@@ -118,9 +335,9 @@ mod tests {
         // all we assert is that every piece is within budget.
         for piece in &pieces {
             assert!(
-                count_tokens(piece) <= MAX_CHUNK_TOKENS,
+                count_chunk_tokens(piece) <= MAX_CHUNK_TOKENS,
                 "piece exceeds token budget: {} tokens",
-                count_tokens(piece)
+                count_chunk_tokens(piece)
             );
         }
     }
@@ -133,7 +350,7 @@ mod tests {
         let content = (0..repeats).map(|_| word).collect::<Vec<_>>().join(" ");
 
         assert!(
-            count_tokens(&content) > MAX_CHUNK_TOKENS,
+            count_chunk_tokens(&content) > MAX_CHUNK_TOKENS,
             "pre-condition: content must exceed token limit"
         );
 
@@ -141,9 +358,9 @@ mod tests {
         assert!(pieces.len() >= 2, "long content must be split");
         for piece in &pieces {
             assert!(
-                count_tokens(piece) <= MAX_CHUNK_TOKENS,
+                count_chunk_tokens(piece) <= MAX_CHUNK_TOKENS,
                 "piece exceeds token budget: {} tokens",
-                count_tokens(piece)
+                count_chunk_tokens(piece)
             );
             assert!(!piece.is_empty());
         }
@@ -163,9 +380,9 @@ mod tests {
         for piece in &pieces {
             assert!(!piece.is_empty());
             assert!(
-                count_tokens(piece) <= MAX_CHUNK_TOKENS,
+                count_chunk_tokens(piece) <= MAX_CHUNK_TOKENS,
                 "bisected piece still exceeds token budget: {} tokens",
-                count_tokens(piece)
+                count_chunk_tokens(piece)
             );
         }
     }
@@ -179,23 +396,60 @@ mod tests {
         let content: String = std::iter::repeat(cjk_char)
             .take(MAX_CHUNK_TOKENS * 3)
             .collect();
-        assert!(count_tokens(&content) > MAX_CHUNK_TOKENS);
+        assert!(count_chunk_tokens(&content) > MAX_CHUNK_TOKENS);
         let pieces = split_text(&content);
         assert!(pieces.len() >= 2, "CJK blob must be split");
         for piece in &pieces {
             assert!(
-                count_tokens(piece) <= MAX_CHUNK_TOKENS,
+                count_chunk_tokens(piece) <= MAX_CHUNK_TOKENS,
                 "CJK piece exceeds token budget: {} tokens",
-                count_tokens(piece)
+                count_chunk_tokens(piece)
             );
             assert!(!piece.is_empty());
         }
     }
 
+    #[test]
+    fn test_split_text_for_unspaced_language_breaks_at_sentences() {
+        let sentence = format!("{}。", "字".repeat(MAX_CHUNK_TOKENS / 2));
+        let content = sentence.repeat(6);
+        let pieces = split_text_for_language(&content, Some("ja"));
+        assert!(pieces.len() >= 2);
+        for piece in &pieces {
+            assert!(count_chunk_tokens(piece) <= MAX_CHUNK_TOKENS);
+            assert!(piece.ends_with('。'), "piece should end at a sentence boundary");
+        }
+        assert_eq!(pieces.concat(), content);
+    }
+
+    #[test]
+    fn test_language_tag_helpers() {
+        assert_eq!(primary_language("pt-BR"), "pt");
+        assert!(is_unspaced_language("zh_Hant"));
+        assert!(!is_unspaced_language("de"));
+        assert!(has_unspaced_script("the 魔法使い"));
+        assert!(has_unspaced_script("ภาษาไทย"));
+        assert!(!has_unspaced_script("Gandalf the Grey, 한국어"));
+        assert!(is_english("en-GB"));
+        assert!(!is_english("fr"));
+    }
+
     #[test]
     fn test_split_text_leading_trailing_whitespace_is_trimmed() {
         let pieces = split_text("  hello world  ");
         assert_eq!(pieces.len(), 1);
         assert_eq!(pieces[0], "hello world");
     }
+
+    #[test]
+    fn test_html_to_text_strips_markup() {
+        let html = "<h2>The Sunless Citadel</h2><p>Once a <b>fortress</b> &amp; now ruins.<br>\
+                    Beware&nbsp;the &#8220;dragon&#x201D;.</p><script>alert(1)</script>\
+                    <p></p><ul><li>Meepo</li><li>Yusdrayl</li></ul>";
+        assert_eq!(
+            html_to_text(html),
+            "The Sunless Citadel\nOnce a fortress & now ruins.\nBeware the \u{201C}dragon\u{201D}.\nMeepo\nYusdrayl"
+        );
+        assert_eq!(html_to_text("&bogus; <i>x</i>"), "&bogus; x");
+    }
 }
@@ -0,0 +1,326 @@
+//! Text statistics and world completeness — where the prose is thin.
+//!
+//! [`KnowledgeGraph::text_stats`] measures each object's prose: the string
+//! properties a GM writes (everything but the name and `_`-prefixed
+//! internals) plus its non-description chunks (session notes, user notes,
+//! imports).  Description chunks are left out because they mirror the
+//! properties.  For each object it reports words, reading time, Flesch
+//! reading ease, when it was last edited (the object or its newest chunk),
+//! and coverage.
+//!
+//! Coverage compares the words against what the object's place in the
+//! graph suggests: [`BASE_EXPECTED_WORDS`] plus [`WORDS_PER_CONNECTION`] per
+//! edge, capped at [`MAX_EXPECTED_WORDS`] — so a major NPC with a dozen
+//! relationships and four words of description is flagged, while a minor
+//! tavern stays fine with a sentence.  [`KnowledgeGraph::completeness_report`]
+//! sums this per type and lists the thinnest objects, biggest gap first, for
+//! a look before the session.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Result, UForgeError};
+use crate::types::{ChunkType, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Reading speed used for reading times.
+pub const WORDS_PER_MINUTE: f32 = 200.0;
+
+/// Words expected of an object with no connections.
+pub const BASE_EXPECTED_WORDS: usize = 25;
+
+/// Further words expected per edge to or from the object.
+pub const WORDS_PER_CONNECTION: usize = 15;
+
+/// Cap on the words expected of any object.
+pub const MAX_EXPECTED_WORDS: usize = 300;
+
+/// Coverage below which an object counts as thin.
+pub const THIN_COVERAGE: f32 = 0.5;
+
+/// Words in `text`: whitespace-separated runs containing a letter or digit.
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Minutes to read `words` at [`WORDS_PER_MINUTE`].
+pub fn reading_minutes(words: usize) -> f32 {
+    words as f32 / WORDS_PER_MINUTE
+}
+
+/// Vowel groups in `word`, less a silent final `e`; at least 1.
+fn syllables(word: &str) -> usize {
+    let word: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &word {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if count > 1 && word.ends_with(&['e']) && !word.ends_with(&['l', 'e']) {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// Flesch reading ease of English `text` (higher is easier; 60–70 is plain
+/// prose), or `None` without words.
+pub fn flesch_reading_ease(text: &str) -> Option<f32> {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphabetic))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    let sentences = text
+        .split(['.', '!', '?'])
+        .filter(|s| s.chars().any(char::is_alphabetic))
+        .count()
+        .max(1);
+    let syllables: usize = words.iter().map(|w| syllables(w)).sum();
+    let words_per_sentence = words.len() as f32 / sentences as f32;
+    let syllables_per_word = syllables as f32 / words.len() as f32;
+    Some(206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word)
+}
+
+/// Text measures of one object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectTextStats {
+    pub object_id: ObjectId,
+    pub name: String,
+    pub object_type: String,
+    pub words: usize,
+    pub reading_minutes: f32,
+    /// Flesch reading ease; `None` without prose.
+    pub readability: Option<f32>,
+    pub last_edited: DateTime<Utc>,
+    /// Edges to or from the object.
+    pub connections: usize,
+    pub expected_words: usize,
+    /// `words / expected_words`, at most 1.
+    pub coverage: f32,
+}
+
+impl ObjectTextStats {
+    pub fn is_thin(&self) -> bool {
+        self.coverage < THIN_COVERAGE
+    }
+
+    /// Words short of `expected_words`.
+    pub fn missing_words(&self) -> usize {
+        self.expected_words.saturating_sub(self.words)
+    }
+}
+
+/// Totals for one object type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TypeTextStats {
+    pub objects: usize,
+    pub words: usize,
+    pub mean_words: f32,
+    pub reading_minutes: f32,
+    /// Mean coverage of the type's objects.
+    pub completeness: f32,
+    pub thin: usize,
+    pub last_edited: Option<DateTime<Utc>>,
+}
+
+/// Result of [`KnowledgeGraph::completeness_report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompletenessReport {
+    pub objects: usize,
+    pub words: usize,
+    pub reading_minutes: f32,
+    /// Mean coverage over every object, 0–1.
+    pub completeness: f32,
+    pub by_type: BTreeMap<String, TypeTextStats>,
+    /// Thin objects, most missing words first.
+    pub thin: Vec<ObjectTextStats>,
+}
+
+/// The prose of `object`'s properties, one value per line.
+fn property_prose(object: &ObjectMetadata) -> String {
+    let mut out = String::new();
+    let Some(properties) = object.properties.as_object() else {
+        return out;
+    };
+    let mut push = |text: &str| {
+        out.push_str(text);
+        out.push('\n');
+    };
+    for (key, value) in properties {
+        if key == "name" || key.starts_with('_') {
+            continue;
+        }
+        match value {
+            Value::String(s) => push(s.as_str()),
+            Value::Array(items) => items.iter().filter_map(Value::as_str).for_each(&mut push),
+            _ => {}
+        }
+    }
+    out
+}
+
+impl KnowledgeGraph {
+    /// Text measures of every object, in name order.
+    pub fn text_stats(&self) -> Result<Vec<ObjectTextStats>> {
+        let mut connections: HashMap<ObjectId, usize> = HashMap::new();
+        for edge in self.get_all_edges()? {
+            *connections.entry(edge.from).or_default() += 1;
+            *connections.entry(edge.to).or_default() += 1;
+        }
+        let mut objects = self.get_all_objects()?;
+        objects.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.0.cmp(&b.id.0)));
+        objects
+            .iter()
+            .map(|o| self.measure_object(o, connections.get(&o.id).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    /// Text measures of object `id`.  Fails with [`UForgeError::NotFound`]
+    /// for an unknown object.
+    pub fn object_text_stats(&self, id: ObjectId) -> Result<ObjectTextStats> {
+        let object = self
+            .get_object(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}")))?;
+        let connections = self.get_relationships(id)?.len();
+        self.measure_object(&object, connections)
+    }
+
+    /// Per-type totals and up to `thin_limit` of the thinnest objects; see
+    /// the module docs.
+    pub fn completeness_report(&self, thin_limit: usize) -> Result<CompletenessReport> {
+        let stats = self.text_stats()?;
+        let mut by_type: BTreeMap<String, TypeTextStats> = BTreeMap::new();
+        for s in &stats {
+            let t = by_type.entry(s.object_type.clone()).or_default();
+            t.objects += 1;
+            t.words += s.words;
+            t.reading_minutes += s.reading_minutes;
+            t.completeness += s.coverage;
+            t.thin += usize::from(s.is_thin());
+            t.last_edited = t.last_edited.max(Some(s.last_edited));
+        }
+        for t in by_type.values_mut() {
+            t.mean_words = t.words as f32 / t.objects as f32;
+            t.completeness /= t.objects as f32;
+        }
+
+        let words: usize = stats.iter().map(|s| s.words).sum();
+        let completeness = if stats.is_empty() {
+            1.0
+        } else {
+            stats.iter().map(|s| s.coverage).sum::<f32>() / stats.len() as f32
+        };
+        let objects = stats.len();
+        let mut thin: Vec<ObjectTextStats> = stats.into_iter().filter(|s| s.is_thin()).collect();
+        thin.sort_by(|a, b| {
+            b.missing_words()
+                .cmp(&a.missing_words())
+                .then_with(|| b.connections.cmp(&a.connections))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        thin.truncate(thin_limit);
+        Ok(CompletenessReport {
+            objects,
+            words,
+            reading_minutes: reading_minutes(words),
+            completeness,
+            by_type,
+            thin,
+        })
+    }
+
+    fn measure_object(
+        &self,
+        object: &ObjectMetadata,
+        connections: usize,
+    ) -> Result<ObjectTextStats> {
+        let mut prose = property_prose(object);
+        let mut last_edited = object.updated_at;
+        for chunk in self.get_text_chunks(object.id)? {
+            last_edited = last_edited.max(chunk.created_at);
+            if !matches!(chunk.chunk_type, ChunkType::Description) {
+                prose.push_str(&chunk.content);
+                prose.push('\n');
+            }
+        }
+        let words = count_words(&prose);
+        let expected_words =
+            (BASE_EXPECTED_WORDS + WORDS_PER_CONNECTION * connections).min(MAX_EXPECTED_WORDS);
+        Ok(ObjectTextStats {
+            object_id: object.id,
+            name: object.name.clone(),
+            object_type: object.object_type.clone(),
+            words,
+            reading_minutes: reading_minutes(words),
+            readability: flesch_reading_ease(&prose),
+            last_edited,
+            connections,
+            expected_words,
+            coverage: (words as f32 / expected_words as f32).min(1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::ObjectBuilder;
+    use crate::test_helpers::create_test_graph;
+
+    #[test]
+    fn test_completeness_report_flags_thin_major_objects() {
+        let (graph, _temp_dir) = create_test_graph();
+        let tavern_text = "A cosy inn run by Toblen Stonehill. The beds are clean, the ale \
+                           is cheap, and the locals gossip about the Redbrands every night of the week.";
+        let tavern = ObjectBuilder::location("Stonehill Inn".to_string())
+            .with_property("description".to_string(), tavern_text.to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let iarno = ObjectBuilder::character("Iarno Albrek".to_string())
+            .with_property("description".to_string(), "Wizard. Traitor. Glasstaff.".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        for name in ["Redbrands", "Black Spider", "Tresendar Manor", "Phandalin"] {
+            let other = graph
+                .add_object(ObjectMetadata::new("faction".to_string(), name.to_string()))
+                .unwrap();
+            graph.connect_objects_str(iarno, other, "knows").unwrap();
+        }
+        graph
+            .add_text_chunk(iarno, "Seen at the manor.".to_string(), ChunkType::SessionNote)
+            .unwrap();
+
+        let inn = graph.object_text_stats(tavern).unwrap();
+        assert_eq!(inn.words, count_words(tavern_text));
+        assert_eq!((inn.expected_words, inn.coverage), (BASE_EXPECTED_WORDS, 1.0));
+        assert!(inn.readability.is_some() && inn.reading_minutes > 0.0);
+
+        let wizard = graph.object_text_stats(iarno).unwrap();
+        assert_eq!((wizard.words, wizard.connections), (7, 4));
+        assert!(wizard.is_thin());
+
+        let report = graph.completeness_report(10).unwrap();
+        assert_eq!(report.objects, 6);
+        assert_eq!(report.thin[0].object_id, iarno);
+        assert_eq!(report.by_type["faction"].thin, 4);
+        assert_eq!(report.by_type["location"].completeness, 1.0);
+        assert!(report.completeness < 0.5);
+        assert!(graph.object_text_stats(ObjectId::new_v4()).is_err());
+    }
+}
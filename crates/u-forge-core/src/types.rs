@@ -3,6 +3,10 @@ use std::collections::HashMap;
 
 use uuid::Uuid as ForgeUuid;
 
+/// UUIDv5 namespace for [`ObjectId::deterministic`].
+const OBJECT_ID_NAMESPACE: ForgeUuid =
+    ForgeUuid::from_u128(0x6f1c_2a4e_9b3d_5e07_a8c4_1d2f_7e60_b935);
+
 /// Unique identifier for graph objects (nodes).
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -13,6 +17,17 @@ impl ObjectId {
         Self(ForgeUuid::new_v4())
     }
 
+    /// UUIDv5 derived from `seed`, `object_type`, and `name`.
+    ///
+    /// The same triple always yields the same id, so re-importing a dataset
+    /// under one seed addresses the objects it created last time, and test
+    /// fixtures get stable ids.  The seed scopes ids to a project: two
+    /// projects importing the same "Terminus" location get different ids.
+    pub fn deterministic(seed: &str, object_type: &str, name: &str) -> Self {
+        let key = format!("{seed}\u{1f}{object_type}\u{1f}{name}");
+        Self(ForgeUuid::new_v5(&OBJECT_ID_NAMESPACE, key.as_bytes()))
+    }
+
     pub fn parse_str(s: &str) -> Result<Self, uuid::Error> {
         ForgeUuid::parse_str(s).map(Self)
     }
@@ -55,6 +70,36 @@ impl std::fmt::Display for ChunkId {
     }
 }
 
+/// Stable identifier for an edge.
+///
+/// Assigned when the edge is first stored and kept when the same
+/// (source, target, type) edge is written again, so editors can refer to a
+/// relationship across changes to its weight, metadata, type, or endpoints.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EdgeId(pub ForgeUuid);
+
+impl EdgeId {
+    pub fn new_v4() -> Self {
+        Self(ForgeUuid::new_v4())
+    }
+
+    pub fn parse_str(s: &str) -> Result<Self, uuid::Error> {
+        ForgeUuid::parse_str(s).map(Self)
+    }
+
+    /// Return the inner UUID formatted with hyphens (e.g. for SQL params).
+    pub fn hyphenated(&self) -> uuid::fmt::Hyphenated {
+        self.0.hyphenated()
+    }
+}
+
+impl std::fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 /// Edge relationship type — a plain string label (e.g. `"led_by"`, `"located_in"`).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(transparent)]
@@ -85,6 +130,9 @@ impl std::fmt::Display for EdgeType {
 /// An edge connecting two objects in the knowledge graph
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Edge {
+    /// Fresh for a new `Edge`; the stored id once read back from the graph.
+    #[serde(default = "EdgeId::new_v4")]
+    pub id: EdgeId,
     pub from: ObjectId,
     pub to: ObjectId,
     pub edge_type: EdgeType,
@@ -96,6 +144,7 @@ pub struct Edge {
 impl Edge {
     pub fn new(from: ObjectId, to: ObjectId, edge_type: EdgeType) -> Self {
         Self {
+            id: EdgeId::new_v4(),
             from,
             to,
             edge_type,
@@ -116,6 +165,92 @@ impl Edge {
     }
 }
 
+/// A partial update to a stored edge; `None` fields are left unchanged.
+/// See `KnowledgeGraph::update_edge`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EdgeChanges {
+    #[serde(default)]
+    pub from: Option<ObjectId>,
+    #[serde(default)]
+    pub to: Option<ObjectId>,
+    #[serde(default)]
+    pub edge_type: Option<EdgeType>,
+    #[serde(default)]
+    pub weight: Option<f32>,
+    /// Replaces the edge's metadata as a whole.
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl EdgeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.from.is_none()
+            && self.to.is_none()
+            && self.edge_type.is_none()
+            && self.weight.is_none()
+            && self.metadata.is_none()
+    }
+
+    /// `edge` with these changes applied.  The id and `created_at` are kept.
+    pub fn apply_to(&self, edge: &Edge) -> Edge {
+        let mut updated = edge.clone();
+        if let Some(from) = self.from {
+            updated.from = from;
+        }
+        if let Some(to) = self.to {
+            updated.to = to;
+        }
+        if let Some(edge_type) = &self.edge_type {
+            updated.edge_type = edge_type.clone();
+        }
+        if let Some(weight) = self.weight {
+            updated.weight = weight;
+        }
+        if let Some(metadata) = &self.metadata {
+            updated.metadata = metadata.clone();
+        }
+        updated
+    }
+}
+
+/// Everything needed to create one relationship, as sent by editors and
+/// tools.  See `KnowledgeGraph::create_relationship`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRelationshipRequest {
+    pub from: ObjectId,
+    pub to: ObjectId,
+    pub edge_type: String,
+    /// Defaults to 1.0.
+    #[serde(default)]
+    pub weight: Option<f32>,
+    /// Edge properties declared by the edge type's schema (e.g. `role`,
+    /// `since`, `reason`), stored in [`Edge::metadata`].
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+impl CreateRelationshipRequest {
+    pub fn new(from: ObjectId, to: ObjectId, edge_type: impl Into<String>) -> Self {
+        Self {
+            from,
+            to,
+            edge_type: edge_type.into(),
+            weight: None,
+            properties: HashMap::new(),
+        }
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+}
+
 /// Core object metadata stored in the knowledge graph.
 ///
 /// All schema-defined fields — including `"description"` and `"tags"` — live
@@ -132,8 +267,59 @@ pub struct ObjectMetadata {
     pub updated_at: chrono::DateTime<chrono::Utc>,
     /// All schema-defined properties, stored as a JSON object.
     pub properties: serde_json::Value,
+    /// Canon/draft status.  `None` on a new object means "use the type's
+    /// schema default" (falling back to [`Lifecycle::Canon`]); objects read
+    /// back from storage always carry `Some`.
+    #[serde(default)]
+    pub lifecycle: Option<Lifecycle>,
 }
 
+/// Content state of an object.
+///
+/// Lets brainstormed or superseded material live in the graph without
+/// polluting authoritative lookups — queries and search can filter on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    /// Work in progress; not yet part of the setting.
+    Draft,
+    /// Authoritative, established fact.
+    #[default]
+    Canon,
+    /// Formerly canon, kept for history.
+    Retired,
+    /// In-world hearsay that may or may not be true.
+    Rumor,
+}
+
+impl Lifecycle {
+    pub const ALL: [Lifecycle; 4] = [
+        Lifecycle::Draft,
+        Lifecycle::Canon,
+        Lifecycle::Retired,
+        Lifecycle::Rumor,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lifecycle::Draft => "draft",
+            Lifecycle::Canon => "canon",
+            Lifecycle::Retired => "retired",
+            Lifecycle::Rumor => "rumor",
+        }
+    }
+
+    /// Parse the snake_case name.  Returns `None` for unknown values.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.as_str() == s)
+    }
+}
+
+/// Longest string property value (in characters) included in
+/// [`ObjectMetadata::profile_text`].  Longer values are prose and are already
+/// covered by the object's text chunks.
+pub const PROFILE_MAX_VALUE_CHARS: usize = 120;
+
 impl ObjectMetadata {
     pub fn new(object_type: String, name: String) -> Self {
         let now = chrono::Utc::now();
@@ -145,6 +331,7 @@ impl ObjectMetadata {
             created_at: now,
             updated_at: now,
             properties: serde_json::Value::Object(serde_json::Map::new()),
+            lifecycle: None,
         }
     }
 
@@ -175,6 +362,12 @@ impl ObjectMetadata {
         self
     }
 
+    /// Set the lifecycle explicitly, overriding the schema default.
+    pub fn with_lifecycle(mut self, lifecycle: Lifecycle) -> Self {
+        self.lifecycle = Some(lifecycle);
+        self
+    }
+
     /// Set the schema name for this object
     pub fn with_schema(mut self, schema_name: String) -> Self {
         self.schema_name = Some(schema_name);
@@ -273,6 +466,47 @@ impl ObjectMetadata {
 
         parts.join("\n")
     }
+
+    /// Short structured summary used as the object's profile embedding.
+    ///
+    /// Unlike [`flatten_for_embedding`](Self::flatten_for_embedding) this
+    /// keeps only identifying data — name, type, tags, and scalar properties
+    /// whose values are at most [`PROFILE_MAX_VALUE_CHARS`] long — so objects
+    /// with no prose still get a compact vector that search can match.
+    pub fn profile_text(&self) -> String {
+        let mut parts = vec![
+            format!("Name: {}", self.name),
+            format!("Type: {}", self.object_type),
+        ];
+
+        if let Some(props) = self.properties.as_object() {
+            for (key, val) in props {
+                if key.starts_with('_') {
+                    continue;
+                }
+                let val_str = match val {
+                    serde_json::Value::String(s)
+                        if !s.is_empty() && s.chars().count() <= PROFILE_MAX_VALUE_CHARS =>
+                    {
+                        s.clone()
+                    }
+                    serde_json::Value::Number(n) => n.to_string(),
+                    serde_json::Value::Bool(b) => b.to_string(),
+                    serde_json::Value::Array(arr) => {
+                        let items: Vec<&str> = arr.iter().filter_map(|v| v.as_str()).collect();
+                        if items.is_empty() {
+                            continue;
+                        }
+                        items.join(", ")
+                    }
+                    _ => continue,
+                };
+                parts.push(format!("{}: {}", key, val_str));
+            }
+        }
+
+        parts.join("\n")
+    }
 }
 
 /// A text chunk associated with an object (for vector search and AI context)
@@ -284,6 +518,14 @@ pub struct TextChunk {
     pub token_count: usize,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub chunk_type: ChunkType,
+    /// BCP 47 language tag of `content` (e.g. `"de"`, `"ja"`), if known.
+    /// New chunks inherit the project's default language.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// `(start, end)` in milliseconds from the start of the recording, for
+    /// chunks taken from a transcript.
+    #[serde(default)]
+    pub time_range: Option<(u64, u64)>,
 }
 
 /// Types of text chunks
@@ -303,7 +545,7 @@ pub enum ChunkType {
 
 impl TextChunk {
     pub fn new(object_id: ObjectId, content: String, chunk_type: ChunkType) -> Self {
-        let token_count = crate::text::count_tokens(&content).max(1);
+        let token_count = crate::text::count_chunk_tokens(&content).max(1);
         Self {
             id: ChunkId::new_v4(),
             object_id,
@@ -311,8 +553,73 @@ impl TextChunk {
             content,
             created_at: chrono::Utc::now(),
             chunk_type,
+            language: None,
+            time_range: None,
         }
     }
+
+    /// Set the recording time span the chunk covers, in milliseconds.
+    pub fn with_time_range(mut self, start_ms: u64, end_ms: u64) -> Self {
+        self.time_range = Some((start_ms, end_ms));
+        self
+    }
+
+    /// Tag the chunk with a BCP 47 language code.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+}
+
+/// A stored version of a chunk's content, from [`KnowledgeGraph::get_chunk_history`].
+///
+/// [`KnowledgeGraph::get_chunk_history`]: crate::KnowledgeGraph::get_chunk_history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkRevision {
+    /// Monotonically increasing revision number (unique across all chunks).
+    pub revision: i64,
+    pub chunk_id: ChunkId,
+    pub content: String,
+    /// Stable hash of `content`, used to skip no-op edits.
+    pub content_hash: String,
+    /// Free-form tag for who made the edit (e.g. a user name or `"agent"`).
+    pub author: Option<String>,
+    pub revised_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A chunk whose embedding lies close to a reference chunk, from
+/// [`KnowledgeGraph::find_similar_chunks`].
+///
+/// [`KnowledgeGraph::find_similar_chunks`]: crate::KnowledgeGraph::find_similar_chunks
+#[derive(Debug, Clone)]
+pub struct SimilarChunk {
+    pub chunk_id: ChunkId,
+    pub object_id: ObjectId,
+    pub content: String,
+    /// Cosine distance to the reference chunk (`0.0` = identical).
+    pub distance: f32,
+}
+
+/// Cosine distance below which two chunks are treated as the same text.
+///
+/// Tight enough that paraphrases are not merged, loose enough to catch a
+/// re-pasted note with minor whitespace or punctuation changes.
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.05;
+
+/// Chunks whose embeddings are (transitively) within the duplicate threshold
+/// of each other, from [`KnowledgeGraph::find_duplicate_chunks`].
+///
+/// [`KnowledgeGraph::find_duplicate_chunks`]: crate::KnowledgeGraph::find_duplicate_chunks
+#[derive(Debug, Clone)]
+pub struct DuplicateChunkGroup {
+    /// Member chunks, oldest first.  The first entry is the one
+    /// [`KnowledgeGraph::merge_duplicate_chunks`](crate::KnowledgeGraph::merge_duplicate_chunks)
+    /// keeps.
+    pub chunk_ids: Vec<ChunkId>,
+    /// Distinct objects owning the member chunks.
+    pub object_ids: Vec<ObjectId>,
+    /// Largest pairwise distance observed while linking the group.
+    pub max_distance: f32,
 }
 
 /// Query result for graph traversal and search
@@ -399,6 +706,20 @@ mod tests {
         assert!(obj.get_json_property("damage").unwrap().is_object());
     }
 
+    #[test]
+    fn test_deterministic_object_ids() {
+        let id = ObjectId::deterministic("campaign", "location", "Terminus");
+        assert_eq!(id, ObjectId::deterministic("campaign", "location", "Terminus"));
+        assert_eq!(id.0.get_version_num(), 5);
+        assert_ne!(id, ObjectId::deterministic("other", "location", "Terminus"));
+        assert_ne!(id, ObjectId::deterministic("campaign", "faction", "Terminus"));
+        // The separator keeps field boundaries from colliding.
+        assert_ne!(
+            ObjectId::deterministic("a", "bc", "d"),
+            ObjectId::deterministic("ab", "c", "d")
+        );
+    }
+
     #[test]
     fn test_edge_creation() {
         let id1 = ObjectId::new_v4();
@@ -0,0 +1,195 @@
+//! Graph-wide validation — the weekly hygiene pass.
+//!
+//! [`KnowledgeGraph::validate_all_objects`] runs every object through the
+//! schema validator ([`SchemaManager::validate_object_with_schema`]), against
+//! the schema the object names or `default` otherwise, reporting stage
+//! `"validate"` to a [`ProgressSink`] (an [`EventBridge`] streams it to the
+//! UI) and stopping with [`UForgeError::Cancelled`] when cancelled.
+//!
+//! The [`ValidationReport`] lists each problem once as an
+//! [`ObjectValidationIssue`] carrying the object's id and name, so the UI can
+//! link straight to it, and groups them by object type and by kind (the
+//! [`ValidationErrorType`] name, or `"warning"`).
+//!
+//! [`SchemaManager::validate_object_with_schema`]: crate::schema::SchemaManager::validate_object_with_schema
+//! [`ValidationErrorType`]: crate::schema::ValidationErrorType
+//! [`EventBridge`]: crate::progress::EventBridge
+//! [`UForgeError::Cancelled`]: crate::error::UForgeError::Cancelled
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::progress::{Progress, ProgressSink};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// Kind of [`ObjectValidationIssue`]s raised from validation warnings.
+pub const WARNING_KIND: &str = "warning";
+
+/// One problem with one object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectValidationIssue {
+    pub object_id: ObjectId,
+    pub object_name: String,
+    pub object_type: String,
+    pub property: String,
+    pub message: String,
+    /// [`ValidationErrorType::as_str`](crate::schema::ValidationErrorType::as_str),
+    /// or [`WARNING_KIND`].
+    pub kind: String,
+}
+
+impl ObjectValidationIssue {
+    pub fn is_warning(&self) -> bool {
+        self.kind == WARNING_KIND
+    }
+}
+
+/// Counts for one object type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TypeValidationSummary {
+    pub checked: usize,
+    /// Objects with at least one error.
+    pub invalid: usize,
+    pub issues: usize,
+}
+
+/// Result of [`KnowledgeGraph::validate_all_objects`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    pub objects_checked: usize,
+    /// Objects with at least one error.
+    pub objects_invalid: usize,
+    /// Every issue, grouped by object, objects in name order.
+    pub issues: Vec<ObjectValidationIssue>,
+    pub by_type: BTreeMap<String, TypeValidationSummary>,
+    /// Issue indices into `issues`, by kind.
+    pub by_kind: BTreeMap<String, Vec<usize>>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The issues of one kind.
+    pub fn issues_of_kind<'a>(
+        &'a self,
+        kind: &str,
+    ) -> impl Iterator<Item = &'a ObjectValidationIssue> + 'a {
+        self.by_kind
+            .get(kind)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.issues[i])
+    }
+}
+
+impl KnowledgeGraph {
+    /// Validate every object; see the module docs.
+    pub async fn validate_all_objects(
+        &self,
+        progress: &dyn ProgressSink,
+    ) -> Result<ValidationReport> {
+        let mut objects = self.get_all_objects()?;
+        objects.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.0.cmp(&b.id.0)));
+        let manager = self.get_schema_manager();
+        let default_schema = manager.load_schema("default").await?;
+
+        let total = objects.len();
+        let mut report = ValidationReport::default();
+        for (done, object) in objects.iter().enumerate() {
+            progress.check_cancelled()?;
+            progress.report(
+                &Progress::new("validate", done, Some(total)).with_message(object.name.clone()),
+            );
+
+            let named = match &object.schema_name {
+                Some(name) => manager.current_schema(name)?,
+                None => None,
+            };
+            let schema = named.as_ref().unwrap_or(&default_schema);
+            let result = manager.validate_object_with_schema(object, schema)?;
+
+            let summary = report.by_type.entry(object.object_type.clone()).or_default();
+            summary.checked += 1;
+            summary.issues += result.errors.len() + result.warnings.len();
+            if !result.valid {
+                summary.invalid += 1;
+                report.objects_invalid += 1;
+            }
+            report.objects_checked += 1;
+
+            let issue = |property: &str, message: &str, kind: &str| ObjectValidationIssue {
+                object_id: object.id,
+                object_name: object.name.clone(),
+                object_type: object.object_type.clone(),
+                property: property.to_string(),
+                message: message.to_string(),
+                kind: kind.to_string(),
+            };
+            let issues = result
+                .errors
+                .iter()
+                .map(|e| issue(&e.property, &e.message, e.error_type.as_str()))
+                .chain(
+                    result
+                        .warnings
+                        .iter()
+                        .map(|w| issue(&w.property, &w.message, WARNING_KIND)),
+                );
+            for issue in issues {
+                report
+                    .by_kind
+                    .entry(issue.kind.clone())
+                    .or_default()
+                    .push(report.issues.len());
+                report.issues.push(issue);
+            }
+        }
+        progress.report(&Progress::new("validate", total, Some(total)));
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress::{CancellationToken, LatestProgress, NoProgress};
+    use crate::test_helpers::create_test_graph;
+    use crate::types::ObjectMetadata;
+
+    #[tokio::test]
+    async fn test_validate_all_objects_groups_issues() {
+        let (graph, _temp_dir) = create_test_graph();
+        graph
+            .add_object(ObjectMetadata::new("character".to_string(), "Aria".to_string()))
+            .unwrap();
+        let stray = graph
+            .add_object(ObjectMetadata::new("starship".to_string(), "Ebon Hawk".to_string()))
+            .unwrap();
+
+        let progress = LatestProgress::new();
+        let report = graph.validate_all_objects(&progress).await.unwrap();
+        assert_eq!(report.objects_checked, 2);
+        assert_eq!(report.objects_invalid, 1);
+        assert_eq!(report.by_type["starship"].invalid, 1);
+        assert_eq!(report.by_type["character"].invalid, 0);
+        let unknown: Vec<_> = report.issues_of_kind("invalid_value").collect();
+        assert_eq!(unknown[0].object_id, stray);
+        assert_eq!(unknown[0].object_name, "Ebon Hawk");
+        assert_eq!(progress.latest().unwrap().percent(), Some(100.0));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = LatestProgress::new().with_cancellation(token);
+        let err = graph.validate_all_objects(&cancelled).await.unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::Cancelled);
+        assert!(!graph
+            .validate_all_objects(&NoProgress)
+            .await
+            .unwrap()
+            .is_clean());
+    }
+}
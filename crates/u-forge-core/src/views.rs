@@ -0,0 +1,363 @@
+//! Named graph views — saved lenses such as "political map" or "family
+//! trees".
+//!
+//! A [`GraphView`] bundles everything the graph canvas needs to redraw a
+//! particular picture of the world: a [`NodeFilter`], the edge types to
+//! draw, optional focus nodes and hop depth, a clustering mode, and a
+//! layout.  Views are stored as JSON in the `graph_views` project setting,
+//! keyed by case-insensitive name, so they survive
+//! [`KnowledgeGraph::clear_data`] like the other project settings.
+//!
+//! [`KnowledgeGraph::graph_view_data`] turns a view into a
+//! [`GraphDataRequest`] and returns the result, so switching lenses is one
+//! call.  There is no Tauri layer; frontends call these methods directly.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, UForgeError};
+use crate::graph_data::{
+    Aggregation, GraphData, GraphDataRequest, GraphScope, NodeFilter, DEFAULT_MAX_EDGES,
+    DEFAULT_MAX_NODES,
+};
+use crate::types::ObjectId;
+use crate::KnowledgeGraph;
+
+/// `project_settings` key holding the saved views.
+pub const GRAPH_VIEWS_SETTING: &str = "graph_views";
+
+/// Hop depth used around focus nodes when a view does not set one.
+pub const DEFAULT_VIEW_DEPTH: usize = 2;
+
+/// How the canvas places a view's nodes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ViewLayout {
+    /// The shared canvas positions (`node_positions`), force-directed for
+    /// nodes without one.
+    #[default]
+    Shared,
+    /// A fresh force-directed layout, ignoring saved positions.
+    Force,
+    /// Positions belonging to this view only, as `(id, x, y)`; nodes without
+    /// one are placed force-directed.
+    Custom { positions: Vec<(ObjectId, f32, f32)> },
+}
+
+/// A saved combination of filters, edge types, focus and layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphView {
+    /// Unique within the project, compared case-insensitively.
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Which nodes to show.  With focus nodes, applies to their
+    /// neighbourhoods (the focus nodes themselves are always shown).
+    #[serde(default)]
+    pub filter: NodeFilter,
+    /// Edge types to draw; empty draws every type.
+    #[serde(default)]
+    pub edge_types: Vec<String>,
+    /// Nodes to centre the view on; empty shows the whole graph.
+    #[serde(default)]
+    pub focus: Vec<ObjectId>,
+    /// Hops around the focus nodes.
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    #[serde(default)]
+    pub aggregate: Option<Aggregation>,
+    #[serde(default)]
+    pub layout: ViewLayout,
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+    #[serde(default = "default_max_edges")]
+    pub max_edges: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_depth() -> usize {
+    DEFAULT_VIEW_DEPTH
+}
+
+fn default_max_nodes() -> usize {
+    DEFAULT_MAX_NODES
+}
+
+fn default_max_edges() -> usize {
+    DEFAULT_MAX_EDGES
+}
+
+impl GraphView {
+    pub fn new(name: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            name: name.into(),
+            description: None,
+            filter: NodeFilter::default(),
+            edge_types: Vec::new(),
+            focus: Vec::new(),
+            depth: DEFAULT_VIEW_DEPTH,
+            aggregate: None,
+            layout: ViewLayout::default(),
+            max_nodes: DEFAULT_MAX_NODES,
+            max_edges: DEFAULT_MAX_EDGES,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_filter(mut self, filter: NodeFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_edge_types<S: Into<String>>(mut self, types: impl IntoIterator<Item = S>) -> Self {
+        self.edge_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_focus(mut self, focus: impl IntoIterator<Item = ObjectId>, depth: usize) -> Self {
+        self.focus = focus.into_iter().collect();
+        self.depth = depth;
+        self
+    }
+
+    pub fn aggregated(mut self, aggregation: Aggregation) -> Self {
+        self.aggregate = Some(aggregation);
+        self
+    }
+
+    pub fn with_layout(mut self, layout: ViewLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// The [`GraphDataRequest`] that draws this view.
+    pub fn to_request(&self) -> GraphDataRequest {
+        let scope = if !self.focus.is_empty() {
+            GraphScope::Focus {
+                focus: self.focus.clone(),
+                depth: self.depth,
+                filter: self.filter.clone(),
+            }
+        } else if self.filter == NodeFilter::default() {
+            GraphScope::All
+        } else {
+            GraphScope::Filter(self.filter.clone())
+        };
+        let mut request = GraphDataRequest::new(scope)
+            .with_budget(self.max_nodes, self.max_edges)
+            .with_edge_types(self.edge_types.iter().cloned());
+        request.aggregate = self.aggregate;
+        request
+    }
+}
+
+impl KnowledgeGraph {
+    /// Every saved view, by name.
+    pub fn graph_views(&self) -> Result<Vec<GraphView>> {
+        let mut views: Vec<GraphView> = match self.storage.get_setting(GRAPH_VIEWS_SETTING)? {
+            Some(json) => serde_json::from_str(&json).context("Failed to parse graph views")?,
+            None => Vec::new(),
+        };
+        views.sort_by_key(|v| v.name.to_lowercase());
+        Ok(views)
+    }
+
+    /// The view called `name`, if saved.
+    pub fn graph_view(&self, name: &str) -> Result<Option<GraphView>> {
+        Ok(self
+            .graph_views()?
+            .into_iter()
+            .find(|v| v.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Save a new view.  Fails with [`UForgeError::Conflict`] when a view of
+    /// that name exists and [`UForgeError::ValidationFailed`] when the name
+    /// is blank or a focus node does not exist.
+    pub fn create_graph_view(&self, view: GraphView) -> Result<()> {
+        self.check_graph_view(&view)?;
+        let mut views = self.graph_views()?;
+        if views
+            .iter()
+            .any(|v| v.name.eq_ignore_ascii_case(&view.name))
+        {
+            return Err(UForgeError::Conflict(format!(
+                "A view named '{}' already exists",
+                view.name
+            )));
+        }
+        views.push(view);
+        self.save_graph_views(&views)
+    }
+
+    /// Replace the saved view of the same name, keeping its creation time.
+    /// Fails with [`UForgeError::NotFound`] when there is none.
+    pub fn update_graph_view(&self, mut view: GraphView) -> Result<()> {
+        self.check_graph_view(&view)?;
+        let mut views = self.graph_views()?;
+        let Some(existing) = views
+            .iter_mut()
+            .find(|v| v.name.eq_ignore_ascii_case(&view.name))
+        else {
+            return Err(UForgeError::NotFound(format!(
+                "No view named '{}'",
+                view.name
+            )));
+        };
+        view.created_at = existing.created_at;
+        view.updated_at = Utc::now();
+        *existing = view;
+        self.save_graph_views(&views)
+    }
+
+    /// Rename view `from` to `to`.  Changing only the case of a name is
+    /// allowed.
+    pub fn rename_graph_view(&self, from: &str, to: &str) -> Result<()> {
+        if to.trim().is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "View name is empty".to_string(),
+            ));
+        }
+        let mut views = self.graph_views()?;
+        if !from.eq_ignore_ascii_case(to) && views.iter().any(|v| v.name.eq_ignore_ascii_case(to)) {
+            return Err(UForgeError::Conflict(format!(
+                "A view named '{to}' already exists"
+            )));
+        }
+        let Some(view) = views.iter_mut().find(|v| v.name.eq_ignore_ascii_case(from)) else {
+            return Err(UForgeError::NotFound(format!("No view named '{from}'")));
+        };
+        view.name = to.trim().to_string();
+        view.updated_at = Utc::now();
+        self.save_graph_views(&views)
+    }
+
+    /// Delete view `name`.  Returns `false` if it was not saved.
+    pub fn delete_graph_view(&self, name: &str) -> Result<bool> {
+        let mut views = self.graph_views()?;
+        let before = views.len();
+        views.retain(|v| !v.name.eq_ignore_ascii_case(name));
+        if views.len() == before {
+            return Ok(false);
+        }
+        self.save_graph_views(&views)?;
+        Ok(true)
+    }
+
+    /// The graph data view `name` draws.  Focus nodes deleted since the view
+    /// was saved are skipped.
+    pub fn graph_view_data(&self, name: &str) -> Result<GraphData> {
+        let view = self
+            .graph_view(name)?
+            .ok_or_else(|| UForgeError::NotFound(format!("No view named '{name}'")))?;
+        self.get_graph_data(&view.to_request())
+    }
+
+    fn check_graph_view(&self, view: &GraphView) -> Result<()> {
+        if view.name.trim().is_empty() {
+            return Err(UForgeError::ValidationFailed(
+                "View name is empty".to_string(),
+            ));
+        }
+        for id in &view.focus {
+            if self.get_object(*id)?.is_none() {
+                return Err(UForgeError::ValidationFailed(format!(
+                    "Unknown focus object {id}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn save_graph_views(&self, views: &[GraphView]) -> Result<()> {
+        if views.is_empty() {
+            return self.storage.delete_setting(GRAPH_VIEWS_SETTING).map(|_| ());
+        }
+        let json = serde_json::to_string(views).context("Failed to serialize graph views")?;
+        self.storage.set_setting(GRAPH_VIEWS_SETTING, &json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+
+    #[test]
+    fn test_graph_view_crud_and_data() {
+        let (graph, _temp_dir) = create_test_graph();
+        let add = |name: &str| {
+            ObjectBuilder::character(name.to_string())
+                .add_to_graph(&graph)
+                .unwrap()
+        };
+        let (king, prince, duke, spy) = (add("King"), add("Prince"), add("Duke"), add("Spy"));
+        let guild = ObjectBuilder::faction("Guild".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph.connect_objects_str(prince, king, "child_of").unwrap();
+        graph.connect_objects_str(duke, king, "serves").unwrap();
+        graph.connect_objects_str(spy, duke, "knows").unwrap();
+        graph.connect_objects_str(spy, guild, "member_of").unwrap();
+
+        let family = GraphView::new("Family trees")
+            .with_edge_types(["child_of"])
+            .with_focus([king], 1);
+        graph.create_graph_view(family.clone()).unwrap();
+        let err = graph
+            .create_graph_view(GraphView::new("FAMILY TREES"))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+
+        let data = graph.graph_view_data("family trees").unwrap();
+        let ids: HashSet<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, HashSet::from([king, prince, duke]));
+        assert_eq!(data.edges.len(), 1);
+        assert_eq!(data.edges[0].edge_type.as_str(), "child_of");
+
+        // The filter narrows the neighbourhood but keeps the focus.
+        let politics = GraphView::new("Political map")
+            .with_filter(NodeFilter {
+                object_types: vec!["faction".to_string()],
+                ..Default::default()
+            })
+            .with_focus([spy], 1);
+        graph.create_graph_view(politics).unwrap();
+        let data = graph.graph_view_data("Political map").unwrap();
+        let ids: Vec<ObjectId> = data.objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![spy, guild]);
+
+        graph
+            .update_graph_view(family.aggregated(Aggregation::Type))
+            .unwrap();
+        let saved = graph.graph_view("Family trees").unwrap().unwrap();
+        assert_eq!(saved.aggregate, Some(Aggregation::Type));
+        assert!(saved.updated_at >= saved.created_at);
+
+        graph.rename_graph_view("family trees", "Lineages").unwrap();
+        let names: Vec<String> = graph
+            .graph_views()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, vec!["Lineages", "Political map"]);
+
+        assert!(graph.delete_graph_view("lineages").unwrap());
+        assert!(!graph.delete_graph_view("lineages").unwrap());
+        let err = graph.graph_view_data("Lineages").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
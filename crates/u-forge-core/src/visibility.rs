@@ -0,0 +1,271 @@
+//! What players may see: object and property visibility, and redaction.
+//!
+//! An object is hidden from players when its `visibility` property is
+//! `"gm"` or it is a [`Lifecycle::Draft`].  A property is hidden when any of
+//! these hold:
+//!
+//! - it is internal (`_`-prefixed) or one of the always-private
+//!   [`GM_ONLY_PROPERTIES`];
+//! - its schema metadata sets `visibility` to `"gm"`, hiding it on every
+//!   object of the type;
+//! - the object lists it in its [`GM_PROPERTIES_KEY`] array, hiding it on
+//!   that object alone ([`KnowledgeGraph::set_property_gm_only`]).
+//!
+//! Player handouts print only [`KnowledgeGraph::player_visible_properties`].
+//! Assistants answering players set `player_view` on
+//! [`HybridSearchConfig`](crate::search::HybridSearchConfig) or
+//! [`AskAboutConfig`](crate::interrogate::AskAboutConfig), which runs their
+//! results through [`KnowledgeGraph::redact_search_results`] before any
+//! context is built.
+
+use serde_json::{Map, Value};
+
+use crate::error::{Result, UForgeError};
+use crate::search::NodeSearchResult;
+use crate::types::{ChunkType, Lifecycle, ObjectId, ObjectMetadata};
+use crate::KnowledgeGraph;
+
+/// Object property that hides the whole object from players when `"gm"`;
+/// in property schema metadata, hides that property.
+pub const VISIBILITY_KEY: &str = "visibility";
+
+/// Properties players never see, whatever the schema says.
+pub const GM_ONLY_PROPERTIES: [&str; 3] = ["secrets", "gm_notes", VISIBILITY_KEY];
+
+/// Object property listing the names of further properties hidden from
+/// players on that object, e.g. `["secret", "true_name"]`.
+pub const GM_PROPERTIES_KEY: &str = "_gm_properties";
+
+impl KnowledgeGraph {
+    /// Whether players may see `object` at all.
+    pub fn is_player_visible(&self, object: &ObjectMetadata) -> bool {
+        let gm_only = object
+            .get_property(VISIBILITY_KEY)
+            .is_some_and(|v| v.eq_ignore_ascii_case("gm"));
+        !gm_only && object.lifecycle != Some(Lifecycle::Draft)
+    }
+
+    /// Whether players may see property `key` of `object`.  Does not check
+    /// the object itself; see [`is_player_visible`](Self::is_player_visible).
+    pub fn is_property_player_visible(&self, object: &ObjectMetadata, key: &str) -> bool {
+        if key.starts_with('_') || GM_ONLY_PROPERTIES.contains(&key) {
+            return false;
+        }
+        let hidden_on_object = object
+            .get_json_property(GM_PROPERTIES_KEY)
+            .and_then(Value::as_array)
+            .is_some_and(|keys| keys.iter().any(|k| k.as_str() == Some(key)));
+        let hidden_by_schema = self
+            .object_type_schema_for(object)
+            .and_then(|s| s.properties.get(key).cloned())
+            .and_then(|p| p.metadata.get(VISIBILITY_KEY).cloned())
+            .is_some_and(|v| v.eq_ignore_ascii_case("gm"));
+        !hidden_on_object && !hidden_by_schema
+    }
+
+    /// `object`'s properties that players may see, in name order, with
+    /// computed properties evaluated.
+    pub fn player_visible_properties(&self, object: &ObjectMetadata) -> Vec<(String, Value)> {
+        let mut properties = object.properties.as_object().cloned().unwrap_or_default();
+        properties.extend(self.computed_properties(object));
+        let mut visible: Vec<(String, Value)> = properties
+            .into_iter()
+            .filter(|(key, _)| self.is_property_player_visible(object, key))
+            .collect();
+        visible.sort_by(|a, b| a.0.cmp(&b.0));
+        visible
+    }
+
+    /// Hide (`true`) or reveal (`false`) property `key` of one object by
+    /// editing its [`GM_PROPERTIES_KEY`] list.  Schema-level and built-in
+    /// hiding still apply when revealing.
+    pub fn set_property_gm_only(&self, id: ObjectId, key: &str, gm_only: bool) -> Result<()> {
+        let object = self
+            .get_object(id)?
+            .ok_or_else(|| UForgeError::NotFound(format!("Unknown object {id}")))?;
+        let mut keys: Vec<String> = object
+            .get_json_property(GM_PROPERTIES_KEY)
+            .and_then(Value::as_array)
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|k| k.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        keys.retain(|k| k != key);
+        if gm_only {
+            keys.push(key.to_string());
+            keys.sort();
+        }
+        self.storage
+            .set_node_property(id, GM_PROPERTIES_KEY, &Value::from(keys))
+    }
+
+    /// A copy of `object` with only what players may see, or `None` when the
+    /// object itself is hidden.  Computed properties are not added.
+    pub fn redact_for_players(&self, object: &ObjectMetadata) -> Option<ObjectMetadata> {
+        if !self.is_player_visible(object) {
+            return None;
+        }
+        let mut redacted = object.clone();
+        let properties: Map<String, Value> = object
+            .properties
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| self.is_property_player_visible(object, key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        redacted.properties = Value::Object(properties);
+        Some(redacted)
+    }
+
+    /// Search results made safe for a player-facing assistant.
+    ///
+    /// Hidden objects are dropped, along with edges and connected names that
+    /// lead to them.  Each remaining object's metadata is redacted, its
+    /// `Description` chunks — flattened from every property, secrets
+    /// included — are replaced by one chunk rebuilt from the redacted
+    /// metadata (keeping the first chunk's id for citations), and its other
+    /// chunks, which hold notes and imports, are removed.
+    pub fn redact_search_results(
+        &self,
+        results: Vec<NodeSearchResult>,
+    ) -> Result<Vec<NodeSearchResult>> {
+        let mut out = Vec::with_capacity(results.len());
+        for mut result in results {
+            let Some(node) = self.redact_for_players(&result.node) else {
+                continue;
+            };
+
+            let mut hidden = Vec::new();
+            for &other in result.connected_node_names.keys() {
+                if !self
+                    .get_object(other)?
+                    .is_some_and(|o| self.is_player_visible(&o))
+                {
+                    hidden.push(other);
+                }
+            }
+            result
+                .connected_node_names
+                .retain(|id, _| !hidden.contains(id));
+            result
+                .edges
+                .retain(|e| !hidden.contains(&e.from) && !hidden.contains(&e.to));
+
+            let description = result
+                .chunks
+                .into_iter()
+                .find(|c| matches!(c.chunk_type, ChunkType::Description));
+            result.chunks = match description {
+                Some(mut chunk) => {
+                    chunk.content = node.flatten_for_embedding(&[]);
+                    chunk.token_count = crate::text::count_chunk_tokens(&chunk.content).max(1);
+                    vec![chunk]
+                }
+                None => Vec::new(),
+            };
+            result.node = node;
+            out.push(result);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ObjectTypeSchema, PropertySchema};
+    use crate::search::SearchSources;
+    use crate::test_helpers::create_test_graph;
+    use crate::ObjectBuilder;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_property_visibility_and_redaction() {
+        let (graph, _temp_dir) = create_test_graph();
+        let mut true_name = PropertySchema::string("Real name");
+        true_name
+            .metadata
+            .insert(VISIBILITY_KEY.to_string(), "gm".to_string());
+        let npc = ObjectTypeSchema::new("npc".to_string(), "An NPC".to_string())
+            .with_property("true_name".to_string(), true_name);
+        graph.register_object_type("npc", npc).await.unwrap();
+
+        let lair = ObjectBuilder::location("Secret Lair".to_string())
+            .with_property(VISIBILITY_KEY.to_string(), "gm".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let id = ObjectBuilder::custom("npc".to_string(), "Iarno".to_string())
+            .with_description("A wizard of the Lords' Alliance.".to_string())
+            .with_property("true_name".to_string(), "Glasstaff".to_string())
+            .with_property("secret".to_string(), "Leads the Redbrands".to_string())
+            .with_property("title".to_string(), "Townmaster's aide".to_string())
+            .with_relationship("hides_in", lair)
+            .add_to_graph(&graph)
+            .unwrap();
+
+        graph.set_property_gm_only(id, "secret", true).unwrap();
+        let object = graph.get_object(id).unwrap().unwrap();
+        let keys: Vec<String> = graph
+            .player_visible_properties(&object)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, ["description", "title"]);
+
+        graph.set_property_gm_only(id, "secret", false).unwrap();
+        let object = graph.get_object(id).unwrap().unwrap();
+        assert!(graph.is_property_player_visible(&object, "secret"));
+        assert!(!graph.is_property_player_visible(&object, "true_name"));
+        graph.set_property_gm_only(id, "secret", true).unwrap();
+
+        let object = graph.get_object(id).unwrap().unwrap();
+        let flat = object.flatten_for_embedding(&[]);
+        let chunk = graph
+            .add_text_chunk(id, flat, ChunkType::Description)
+            .unwrap()[0];
+        graph
+            .add_text_chunk(id, "GM: he fears Nezznar".to_string(), ChunkType::UserNote)
+            .unwrap();
+        let lair_object = graph.get_object(lair).unwrap().unwrap();
+        let result = NodeSearchResult {
+            node: object,
+            chunks: graph.get_text_chunks(id).unwrap(),
+            edges: graph.get_relationships(id).unwrap(),
+            connected_node_names: HashMap::from([(
+                lair,
+                crate::search::ConnectedNode {
+                    name: lair_object.name.clone(),
+                    object_type: lair_object.object_type.clone(),
+                },
+            )]),
+            score: 1.0,
+            sources: SearchSources::default(),
+        };
+        let hidden_result = NodeSearchResult {
+            node: lair_object,
+            chunks: Vec::new(),
+            edges: Vec::new(),
+            connected_node_names: HashMap::new(),
+            score: 0.5,
+            sources: SearchSources::default(),
+        };
+
+        let redacted = graph
+            .redact_search_results(vec![result, hidden_result])
+            .unwrap();
+        assert_eq!(redacted.len(), 1);
+        let result = &redacted[0];
+        assert!(result.edges.is_empty());
+        assert!(result.connected_node_names.is_empty());
+        assert_eq!(result.chunks.len(), 1);
+        assert_eq!(result.chunks[0].id, chunk);
+        let text = &result.chunks[0].content;
+        assert!(text.contains("Townmaster's aide"));
+        assert!(!text.contains("Glasstaff"));
+        assert!(!text.contains("Redbrands"));
+        assert!(result.node.get_property("secret").is_none());
+    }
+}
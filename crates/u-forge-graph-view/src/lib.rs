@@ -10,5 +10,9 @@ mod spatial;
 
 pub use layout::force_directed_layout;
 pub use observable::{GraphEvent, ObservableGraph};
-pub use snapshot::{build_snapshot, build_snapshot_incremental, EdgeView, GraphSnapshot, LodLevel, NodeView};
+pub use snapshot::{
+    build_scoped_snapshot, build_snapshot, build_snapshot_incremental, EdgeView, GraphSnapshot,
+    LodLevel, NodeView, ScopedSnapshot, CLUSTER_EDGE_TYPE, CLUSTER_NODE_TYPE,
+};
 pub use spatial::NodeEntry;
+pub use u_forge_core::{default_type_color, NodeShape, NodeStyle};
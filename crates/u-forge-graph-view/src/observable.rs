@@ -9,7 +9,10 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use tokio::sync::broadcast;
-use u_forge_core::{EdgeType, KnowledgeGraph, ObjectId, ObjectMetadata};
+use u_forge_core::{
+    CreateRelationshipRequest, Edge, EdgeChanges, EdgeId, EdgeType, KnowledgeGraph, ObjectId,
+    ObjectMetadata,
+};
 
 /// Events emitted by [`ObservableGraph`] after a successful mutation.
 #[derive(Debug, Clone)]
@@ -93,6 +96,40 @@ impl ObservableGraph {
         let _ = self.sender.send(GraphEvent::EdgeAdded { source: from, target: to });
         Ok(())
     }
+
+    /// Create a relationship with typed edge properties and return it.
+    /// Emits [`GraphEvent::EdgeAdded`] on success.
+    pub fn create_relationship(&self, request: CreateRelationshipRequest) -> Result<Edge> {
+        let edge = self.inner.create_relationship(request)?;
+        let _ = self.sender.send(GraphEvent::EdgeAdded { source: edge.from, target: edge.to });
+        Ok(edge)
+    }
+
+    /// Change an existing edge in place.
+    /// Emits [`GraphEvent::EdgeDeleted`] for the old endpoints when they
+    /// changed, then [`GraphEvent::EdgeAdded`] for the new ones.
+    pub fn update_edge(&self, id: EdgeId, changes: EdgeChanges) -> Result<Edge> {
+        let before = self.inner.get_edge(id)?;
+        let edge = self.inner.update_edge(id, changes)?;
+        if let Some(old) = before.filter(|old| (old.from, old.to) != (edge.from, edge.to)) {
+            let _ = self.sender.send(GraphEvent::EdgeDeleted { source: old.from, target: old.to });
+        }
+        let _ = self.sender.send(GraphEvent::EdgeAdded { source: edge.from, target: edge.to });
+        Ok(edge)
+    }
+
+    /// Delete an edge by id.
+    /// Emits [`GraphEvent::EdgeDeleted`] when an edge was removed.
+    pub fn delete_edge_by_id(&self, id: EdgeId) -> Result<bool> {
+        let Some(edge) = self.inner.get_edge(id)? else {
+            return Ok(false);
+        };
+        let removed = self.inner.delete_edge_by_id(id)?;
+        if removed {
+            let _ = self.sender.send(GraphEvent::EdgeDeleted { source: edge.from, target: edge.to });
+        }
+        Ok(removed)
+    }
 }
 
 impl Deref for ObservableGraph {
@@ -4,7 +4,10 @@ use anyhow::Result;
 use glam::Vec2;
 use rstar::RTree;
 use serde_json::Value as JsonValue;
-use u_forge_core::{KnowledgeGraph, ObjectId};
+use u_forge_core::{
+    default_type_color, Edge, EdgeType, GraphData, GraphDataRequest, GraphNodeRef, KnowledgeGraph,
+    NodeStyle, ObjectId, ObjectMetadata,
+};
 
 use crate::layout::force_directed_layout;
 use crate::spatial::NodeEntry;
@@ -60,9 +63,21 @@ pub struct GraphSnapshot {
     /// incremental updates can detect when a type is fully removed (count → 0)
     /// without a full O(N) scan.
     pub type_counts: HashMap<String, usize>,
+    /// Effective schema style of node types, keyed by type.  A type missing
+    /// here is drawn with its default style.
+    pub type_styles: HashMap<String, NodeStyle>,
 }
 
 impl GraphSnapshot {
+    /// Colour of `object_type` nodes: the schema `color` if one is set,
+    /// otherwise the name-derived default.
+    pub fn type_color(&self, object_type: &str) -> [u8; 3] {
+        self.type_styles
+            .get(object_type)
+            .map(|s| s.color)
+            .unwrap_or_else(|| default_type_color(object_type))
+    }
+
     /// Return indices of nodes whose positions fall within the given rectangle.
     pub fn nodes_in_viewport(&self, min: Vec2, max: Vec2) -> Vec<usize> {
         use rstar::AABB;
@@ -160,7 +175,101 @@ pub fn build_snapshot(graph: &KnowledgeGraph) -> Result<GraphSnapshot> {
 
     // Load any previously saved UI positions (empty map on first run)
     let saved_positions = graph.load_layout().unwrap_or_default();
+    let styles = graph.type_styles(objects.iter().map(|o| o.object_type.as_str()));
+
+    Ok(snapshot_from_parts(objects, raw_edges, &saved_positions, styles))
+}
+
+/// `object_type` of the stand-in nodes drawn for collapsed clusters.
+pub const CLUSTER_NODE_TYPE: &str = "cluster";
+
+/// `edge_type` of the stand-in edges drawn between clusters.
+pub const CLUSTER_EDGE_TYPE: &str = "cluster";
+
+/// A snapshot of part of the graph, with the totals needed to tell the user
+/// how much is hidden.
+pub struct ScopedSnapshot {
+    pub snapshot: GraphSnapshot,
+    /// Nodes in the whole graph.
+    pub total_nodes: usize,
+    /// Edges in the whole graph.
+    pub total_edges: usize,
+    /// Nodes the scope selected before the node budget was applied.
+    pub matched_nodes: usize,
+    /// Whether the node or edge budget cut anything the scope selected.
+    pub truncated: bool,
+    /// Cluster key of each [`CLUSTER_NODE_TYPE`] node, for expanding it with
+    /// [`GraphDataRequest::expanding`].  Cluster node ids are generated per
+    /// snapshot and are never saved.
+    pub clusters: HashMap<ObjectId, String>,
+}
+
+/// Build a snapshot of just the nodes and edges selected by `request` — a
+/// focus node's neighbourhood or a filter, within a node and edge budget.
+///
+/// Scoping and pruning happen in [`KnowledgeGraph::get_graph_data`]; this
+/// lays the result out like [`build_snapshot`].  Use it instead of
+/// `build_snapshot` for large worlds.
+pub fn build_scoped_snapshot(
+    graph: &KnowledgeGraph,
+    request: &GraphDataRequest,
+) -> Result<ScopedSnapshot> {
+    let mut data = graph.get_graph_data(request)?;
+    let saved_positions = graph.load_layout().unwrap_or_default();
+    let truncated = data.truncated();
+    let clusters = add_cluster_stand_ins(&mut data);
+    let styles = std::mem::take(&mut data.styles);
+    Ok(ScopedSnapshot {
+        total_nodes: data.total_nodes,
+        total_edges: data.total_edges,
+        matched_nodes: data.matched_nodes,
+        truncated,
+        clusters,
+        snapshot: snapshot_from_parts(data.objects, data.edges, &saved_positions, styles),
+    })
+}
+
+/// Append a [`CLUSTER_NODE_TYPE`] node per cluster and a
+/// [`CLUSTER_EDGE_TYPE`] edge per cluster edge to `data`, so aggregated data
+/// lays out and renders like any other.  Returns each stand-in's cluster key.
+fn add_cluster_stand_ins(data: &mut GraphData) -> HashMap<ObjectId, String> {
+    let mut ids: HashMap<String, ObjectId> = HashMap::new();
+    for cluster in &data.clusters {
+        let mut node = ObjectMetadata::new(CLUSTER_NODE_TYPE.to_string(), cluster.label.clone());
+        node.set_property("cluster_key".to_string(), cluster.key.clone());
+        node.set_json_property("size".to_string(), cluster.size.into());
+        node.set_property(
+            "description".to_string(),
+            format!(
+                "{} objects, including {}",
+                cluster.size,
+                cluster.sample.join(", ")
+            ),
+        );
+        ids.insert(cluster.key.clone(), node.id);
+        data.objects.push(node);
+    }
+    let resolve = |r: &GraphNodeRef| match r {
+        GraphNodeRef::Object(id) => Some(*id),
+        GraphNodeRef::Cluster(key) => ids.get(key).copied(),
+    };
+    for cluster_edge in &data.cluster_edges {
+        if let (Some(from), Some(to)) = (resolve(&cluster_edge.from), resolve(&cluster_edge.to)) {
+            let mut edge = Edge::new(from, to, EdgeType::new(CLUSTER_EDGE_TYPE));
+            edge.weight = cluster_edge.weight;
+            data.edges.push(edge);
+        }
+    }
+    ids.into_iter().map(|(key, id)| (id, key)).collect()
+}
 
+/// Steps 2–6 of [`build_snapshot`] on already-fetched objects and edges.
+fn snapshot_from_parts(
+    objects: Vec<ObjectMetadata>,
+    raw_edges: Vec<Edge>,
+    saved_positions: &HashMap<ObjectId, (f32, f32)>,
+    type_styles: HashMap<String, NodeStyle>,
+) -> GraphSnapshot {
     // Build ObjectId → usize index map
     let id_to_idx: HashMap<ObjectId, usize> = objects
         .iter()
@@ -226,14 +335,15 @@ pub fn build_snapshot(graph: &KnowledgeGraph) -> Result<GraphSnapshot> {
     // Precompute type counts and the sorted unique type list for the legend.
     let (legend_types, type_counts) = build_legend(&nodes);
 
-    Ok(GraphSnapshot {
+    GraphSnapshot {
         nodes,
         edges,
         spatial_index,
         legend_types,
         id_to_idx,
         type_counts,
-    })
+        type_styles,
+    }
 }
 
 /// Rebuild a snapshot incrementally from `prev`, applying only what changed.
@@ -397,6 +507,9 @@ pub fn build_snapshot_incremental(
         }
         (prev.legend_types.clone(), tc)
     };
+    // Styles are re-read every time: a schema edit changes them without
+    // touching any node.
+    let type_styles = graph.type_styles(legend_types.iter().map(String::as_str));
 
     Ok(GraphSnapshot {
         nodes,
@@ -405,6 +518,7 @@ pub fn build_snapshot_incremental(
         legend_types,
         id_to_idx,
         type_counts,
+        type_styles,
     })
 }
 
@@ -423,7 +537,7 @@ fn build_legend(nodes: &[NodeView]) -> (Vec<String>, HashMap<String, usize>) {
 mod tests {
     use super::*;
     use tempfile::TempDir;
-    use u_forge_core::{EdgeType, KnowledgeGraph, ObjectBuilder};
+    use u_forge_core::{Aggregation, EdgeType, GraphScope, KnowledgeGraph, ObjectBuilder};
 
     fn test_graph() -> (TempDir, KnowledgeGraph) {
         let dir = TempDir::new().unwrap();
@@ -468,6 +582,72 @@ mod tests {
         assert_ne!(a_pos, b_pos, "connected nodes should not overlap");
     }
 
+    #[test]
+    fn build_scoped_snapshot_neighborhood() {
+        let (_dir, graph) = test_graph();
+
+        let id_a = ObjectBuilder::character("Alice".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let id_b = ObjectBuilder::character("Bob".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let id_c = ObjectBuilder::character("Carol".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .connect_objects(id_a, id_b, EdgeType::new("knows"))
+            .unwrap();
+        graph
+            .connect_objects(id_b, id_c, EdgeType::new("knows"))
+            .unwrap();
+
+        let scoped =
+            build_scoped_snapshot(&graph, &GraphDataRequest::neighborhood(id_a, 1)).unwrap();
+        assert_eq!(scoped.snapshot.nodes.len(), 2);
+        assert_eq!(scoped.snapshot.edges.len(), 1);
+        assert!(!scoped.snapshot.id_to_idx.contains_key(&id_c));
+        assert_eq!((scoped.total_nodes, scoped.total_edges), (3, 2));
+        assert!(!scoped.truncated);
+    }
+
+    #[test]
+    fn build_scoped_snapshot_clusters_by_type() {
+        let (_dir, graph) = test_graph();
+
+        let id_a = ObjectBuilder::character("Alice".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        let id_b = ObjectBuilder::location("Bree".to_string())
+            .add_to_graph(&graph)
+            .unwrap();
+        graph
+            .connect_objects(id_a, id_b, EdgeType::new("located_in"))
+            .unwrap();
+
+        let request = GraphDataRequest::new(GraphScope::All).aggregated(Aggregation::Type);
+        let scoped = build_scoped_snapshot(&graph, &request).unwrap();
+        assert_eq!(scoped.snapshot.nodes.len(), 2);
+        assert!(scoped
+            .snapshot
+            .nodes
+            .iter()
+            .all(|n| n.object_type == CLUSTER_NODE_TYPE));
+        assert_eq!(scoped.snapshot.edges.len(), 1);
+
+        // Expanding a stand-in replaces it with its members.
+        let (location_node, key) = scoped
+            .clusters
+            .iter()
+            .find(|(_, key)| key.as_str() == "type:location")
+            .unwrap();
+        assert!(scoped.snapshot.id_to_idx.contains_key(location_node));
+        let scoped = build_scoped_snapshot(&graph, &request.expanding(key.clone())).unwrap();
+        assert!(scoped.snapshot.id_to_idx.contains_key(&id_b));
+        assert_eq!(scoped.clusters.len(), 1);
+        assert_eq!(scoped.snapshot.edges.len(), 1);
+    }
+
     #[test]
     fn build_snapshot_skips_layout_when_all_positions_saved() {
         let (_dir, graph) = test_graph();
@@ -0,0 +1,233 @@
+//! Python bindings for u-forge.ai — `import uforge`.
+//!
+//! Lets GMs script analyses and bulk exports of their worlds:
+//!
+//! ```text
+//! import uforge
+//! world = uforge.open("~/worlds/phandelver")
+//! for hit in world.search("dragon", limit=5):
+//!     print(hit["object_name"], hit["content"][:80])
+//! world.export_to("out/", format="markdown", object_types=["character"])
+//! ```
+//!
+//! [`Project`] is the binding's surface in plain Rust: it opens a project
+//! and answers read, search and export calls with JSON values, so it is
+//! tested without a Python interpreter.  The `python` feature wraps it in a
+//! PyO3 class (`src/python.rs`) that hands those values to Python as
+//! dicts and lists; build it with `maturin develop --features python`.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use u_forge_core::{
+    ExportFilter, ExportFormat, KnowledgeGraph, NoProgress, NodeFilter, ObjectId, UForgeError,
+};
+
+#[cfg(feature = "python")]
+mod python;
+
+/// Default number of search hits.
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// An open u-forge project.
+pub struct Project {
+    graph: KnowledgeGraph,
+}
+
+fn parse_id(id: &str) -> Result<ObjectId> {
+    ObjectId::parse_str(id.trim())
+        .map_err(|_| UForgeError::ValidationFailed(format!("Not an object id: '{id}'")).into())
+}
+
+fn to_values<T: serde::Serialize>(items: Vec<T>) -> Result<Vec<Value>> {
+    items
+        .into_iter()
+        .map(|item| Ok(serde_json::to_value(item)?))
+        .collect()
+}
+
+impl Project {
+    /// Open (or create) the project stored in directory `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            graph: KnowledgeGraph::new(path)?,
+        })
+    }
+
+    /// The underlying graph, for Rust callers.
+    pub fn graph(&self) -> &KnowledgeGraph {
+        &self.graph
+    }
+
+    /// Object, edge, chunk and embedding counts.
+    pub fn stats(&self) -> Result<Value> {
+        let stats = self.graph.get_stats()?;
+        Ok(json!({
+            "objects": stats.node_count,
+            "edges": stats.edge_count,
+            "chunks": stats.chunk_count,
+            "tokens": stats.total_tokens,
+            "embedded_chunks": stats.embedded_count,
+        }))
+    }
+
+    /// The object with id `id`, or `None`.
+    pub fn object(&self, id: &str) -> Result<Option<Value>> {
+        self.graph
+            .get_object(parse_id(id)?)?
+            .map(|o| Ok(serde_json::to_value(o)?))
+            .transpose()
+    }
+
+    /// Every object, or those of `object_type`, by name.
+    pub fn objects(&self, object_type: Option<&str>) -> Result<Vec<Value>> {
+        let mut objects = self.graph.get_all_objects()?;
+        if let Some(object_type) = object_type {
+            objects.retain(|o| o.object_type == object_type);
+        }
+        objects.sort_by(|a, b| a.name.cmp(&b.name));
+        to_values(objects)
+    }
+
+    /// Objects named exactly `name`, of any type.
+    pub fn find(&self, name: &str) -> Result<Vec<Value>> {
+        to_values(self.graph.find_by_name_only(name)?)
+    }
+
+    /// Every edge to or from object `id`.
+    pub fn relationships(&self, id: &str) -> Result<Vec<Value>> {
+        to_values(self.graph.get_relationships(parse_id(id)?)?)
+    }
+
+    /// The text chunks of object `id`.
+    pub fn chunks(&self, id: &str) -> Result<Vec<Value>> {
+        to_values(self.graph.get_text_chunks(parse_id(id)?)?)
+    }
+
+    /// Objects, edges and chunks within `hops` of object `id`.
+    pub fn neighborhood(&self, id: &str, hops: usize) -> Result<Value> {
+        let result = self.graph.query_subgraph(parse_id(id)?, hops)?;
+        Ok(json!({
+            "objects": to_values(result.objects)?,
+            "edges": to_values(result.edges)?,
+            "chunks": to_values(result.chunks)?,
+        }))
+    }
+
+    /// Full-text search over chunk text (FTS5 syntax), best first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<Value>> {
+        let mut hits = Vec::new();
+        for (chunk_id, object_id, content) in self.graph.search_chunks_fts(query, limit)? {
+            let object_name = self.graph.get_object(object_id)?.map(|o| o.name);
+            hits.push(json!({
+                "chunk_id": chunk_id,
+                "object_id": object_id,
+                "object_name": object_name,
+                "content": content,
+            }));
+        }
+        Ok(hits)
+    }
+
+    /// Export objects as `format` (`"json"`, `"markdown"` or `"archive"`),
+    /// limited to `object_types` when not empty.  Returns `(path, bytes)`
+    /// per file.
+    pub fn export(
+        &self,
+        format: &str,
+        object_types: &[String],
+        player_visible_only: bool,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        let format: ExportFormat = serde_json::from_value(Value::String(format.to_string()))
+            .map_err(|_| {
+                UForgeError::ValidationFailed(format!("Unknown export format '{format}'"))
+            })?;
+        let filter = ExportFilter {
+            filter: NodeFilter {
+                object_types: object_types.to_vec(),
+                ..Default::default()
+            },
+            player_visible_only,
+        };
+        let export = self.graph.export_selection(&filter, format, &NoProgress)?;
+        Ok(export
+            .files
+            .into_iter()
+            .map(|file| (file.path, file.content))
+            .collect())
+    }
+
+    /// [`export`](Self::export) written under directory `dir`.  Returns
+    /// the number of files written.
+    pub fn export_to(
+        &self,
+        dir: impl AsRef<Path>,
+        format: &str,
+        object_types: &[String],
+        player_visible_only: bool,
+    ) -> Result<usize> {
+        let dir = dir.as_ref();
+        let files = self.export(format, object_types, player_visible_only)?;
+        for (path, content) in &files {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, content)?;
+        }
+        Ok(files.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use u_forge_core::{ChunkType, ErrorKind, ObjectBuilder, EXPORT_DATA_FILE};
+
+    #[test]
+    fn test_project_read_search_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = Project::open(temp_dir.path()).unwrap();
+        let graph = project.graph();
+        let town = ObjectBuilder::location("Phandalin".to_string())
+            .add_to_graph(graph)
+            .unwrap();
+        let sildar = ObjectBuilder::character("Sildar Hallwinter".to_string())
+            .located_in(town)
+            .add_to_graph(graph)
+            .unwrap();
+        graph
+            .add_text_chunk(
+                sildar,
+                "Sildar hunts for the wizard Iarno.".to_string(),
+                ChunkType::Description,
+            )
+            .unwrap();
+
+        assert_eq!(project.stats().unwrap()["objects"], 2);
+        let id = sildar.to_string();
+        assert_eq!(project.object(&id).unwrap().unwrap()["name"], "Sildar Hallwinter");
+        assert_eq!(project.objects(Some("location")).unwrap().len(), 1);
+        assert_eq!(project.relationships(&id).unwrap().len(), 1);
+        let nearby = project.neighborhood(&id, 1).unwrap();
+        assert_eq!(nearby["objects"].as_array().unwrap().len(), 2);
+
+        let hits = project.search("wizard", DEFAULT_SEARCH_LIMIT).unwrap();
+        assert_eq!(hits[0]["object_name"], "Sildar Hallwinter");
+
+        let out = temp_dir.path().join("out");
+        let written = project
+            .export_to(&out, "json", &["character".to_string()], false)
+            .unwrap();
+        assert_eq!(written, 1);
+        let exported = std::fs::read_to_string(out.join(EXPORT_DATA_FILE)).unwrap();
+        assert_eq!(exported.lines().count(), 1);
+        assert!(exported.contains("Sildar Hallwinter"));
+
+        let err = project.object("not-an-id").unwrap_err();
+        assert_eq!(UForgeError::kind_of(&err), ErrorKind::ValidationFailed);
+        assert!(project.export("pdf", &[], false).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! The `uforge` Python module: [`Project`] as a PyO3 class.
+//!
+//! JSON values become dicts and lists via Python's own `json.loads`, which
+//! keeps the conversion in one place.  Long-running calls release the GIL.
+
+use pyo3::exceptions::{PyLookupError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde_json::Value;
+use u_forge_core::{ErrorKind, UForgeError};
+
+use crate::{Project, DEFAULT_SEARCH_LIMIT};
+
+/// Map a binding error onto the closest Python exception.
+fn py_err(err: anyhow::Error) -> PyErr {
+    let message = format!("{err:#}");
+    match UForgeError::kind_of(&err) {
+        ErrorKind::NotFound => PyLookupError::new_err(message),
+        ErrorKind::ValidationFailed => PyValueError::new_err(message),
+        _ => PyRuntimeError::new_err(message),
+    }
+}
+
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    let json = PyModule::import(py, "json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+fn to_py_list(py: Python<'_>, values: Vec<Value>) -> PyResult<PyObject> {
+    to_py(py, &Value::Array(values))
+}
+
+/// An open u-forge project.
+#[pyclass(name = "Project", module = "uforge", frozen)]
+struct PyProject {
+    inner: Project,
+}
+
+#[pymethods]
+impl PyProject {
+    #[new]
+    fn new(py: Python<'_>, path: std::path::PathBuf) -> PyResult<Self> {
+        let inner = py.allow_threads(|| Project::open(path)).map_err(py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Object, edge, chunk and embedding counts.
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.stats().map_err(py_err)?)
+    }
+
+    /// The object with id `id`, or `None`.
+    fn get_object(&self, py: Python<'_>, id: &str) -> PyResult<Option<PyObject>> {
+        self.inner
+            .object(id)
+            .map_err(py_err)?
+            .map(|o| to_py(py, &o))
+            .transpose()
+    }
+
+    /// Every object, or those of `object_type`, by name.
+    #[pyo3(signature = (object_type=None))]
+    fn objects(&self, py: Python<'_>, object_type: Option<&str>) -> PyResult<PyObject> {
+        to_py_list(py, self.inner.objects(object_type).map_err(py_err)?)
+    }
+
+    /// Objects named exactly `name`, of any type.
+    fn find(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        to_py_list(py, self.inner.find(name).map_err(py_err)?)
+    }
+
+    /// Every edge to or from object `id`.
+    fn relationships(&self, py: Python<'_>, id: &str) -> PyResult<PyObject> {
+        to_py_list(py, self.inner.relationships(id).map_err(py_err)?)
+    }
+
+    /// The text chunks of object `id`.
+    fn chunks(&self, py: Python<'_>, id: &str) -> PyResult<PyObject> {
+        to_py_list(py, self.inner.chunks(id).map_err(py_err)?)
+    }
+
+    /// Objects, edges and chunks within `hops` of object `id`.
+    #[pyo3(signature = (id, hops=1))]
+    fn neighborhood(&self, py: Python<'_>, id: &str, hops: usize) -> PyResult<PyObject> {
+        to_py(py, &self.inner.neighborhood(id, hops).map_err(py_err)?)
+    }
+
+    /// Full-text search over chunk text, best first.
+    #[pyo3(signature = (query, limit=DEFAULT_SEARCH_LIMIT))]
+    fn search(&self, py: Python<'_>, query: &str, limit: usize) -> PyResult<PyObject> {
+        let hits = py
+            .allow_threads(|| self.inner.search(query, limit))
+            .map_err(py_err)?;
+        to_py_list(py, hits)
+    }
+
+    /// Export as `"json"`, `"markdown"` or `"archive"`; returns a list of
+    /// `(path, bytes)`.
+    #[pyo3(signature = (format="json", object_types=None, player_visible_only=false))]
+    fn export(
+        &self,
+        py: Python<'_>,
+        format: &str,
+        object_types: Option<Vec<String>>,
+        player_visible_only: bool,
+    ) -> PyResult<Vec<(String, PyObject)>> {
+        let object_types = object_types.unwrap_or_default();
+        let files = py
+            .allow_threads(|| self.inner.export(format, &object_types, player_visible_only))
+            .map_err(py_err)?;
+        Ok(files
+            .into_iter()
+            .map(|(path, content)| (path, PyBytes::new(py, &content).into_any().unbind()))
+            .collect())
+    }
+
+    /// Export into directory `dir`; returns the number of files written.
+    #[pyo3(signature = (dir, format="json", object_types=None, player_visible_only=false))]
+    fn export_to(
+        &self,
+        py: Python<'_>,
+        dir: std::path::PathBuf,
+        format: &str,
+        object_types: Option<Vec<String>>,
+        player_visible_only: bool,
+    ) -> PyResult<usize> {
+        let object_types = object_types.unwrap_or_default();
+        py.allow_threads(|| {
+            self.inner
+                .export_to(dir, format, &object_types, player_visible_only)
+        })
+        .map_err(py_err)
+    }
+}
+
+/// Open (or create) the project stored in directory `path`.
+#[pyfunction]
+fn open(py: Python<'_>, path: std::path::PathBuf) -> PyResult<PyProject> {
+    PyProject::new(py, path)
+}
+
+#[pymodule]
+fn uforge(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyProject>()?;
+    m.add_function(wrap_pyfunction!(open, m)?)?;
+    m.add("DEFAULT_SEARCH_LIMIT", DEFAULT_SEARCH_LIMIT)?;
+    Ok(())
+}
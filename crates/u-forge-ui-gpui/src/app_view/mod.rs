@@ -17,12 +17,13 @@ use u_forge_core::{
         resolve_lemonade_url, Capability, GpuResourceManager, LemonadeServerCatalog, ModelSelector,
         ProviderFactory, QualityTier,
     },
+    progress::{CancellationToken, LatestProgress},
     queue::InferenceQueueBuilder,
     types::ObjectId,
-    AppConfig, EmbeddingOutcome, EmbeddingPlan, EmbeddingProgress, KnowledgeGraph, ObjectMetadata,
-    SchemaManager,
+    Aggregation, AppConfig, EmbeddingOutcome, EmbeddingPlan, GraphDataRequest, GraphScope,
+    IntentKind, KnowledgeGraph, NodeFilter, ObjectMetadata, SchemaManager,
 };
-use u_forge_graph_view::GraphSnapshot;
+use u_forge_graph_view::ScopedSnapshot;
 
 use state::AppState;
 
@@ -32,7 +33,8 @@ use crate::node_editor::NodeEditorPanel;
 use crate::path_picker::{
     PathCancelled, PathConfirmed, PathPickerKind, PathPickerModal, PickerMode,
 };
-use crate::search_panel::SearchPanel;
+use crate::proposal_panel::{ProposalAccepted, ProposalPanel};
+use crate::search_panel::{EmbeddingsRequested, SearchPanel};
 use crate::selection_model::SelectionModel;
 use crate::node_panel::{CreateNodeRequest, DeleteNodeRequest, NodePanel};
 
@@ -68,6 +70,9 @@ pub(crate) const MAX_PANE_RATIO: f32 = 0.9;
 /// Width/height of resize drag handles in pixels.
 pub(crate) const RESIZE_HANDLE_SIZE: f32 = 6.0;
 
+/// Hops around the selected node shown by View → Focus Selection.
+pub(crate) const FOCUS_DEPTH: usize = 2;
+
 // ── Drag marker types ─────────────────────────────────────────────────────────
 
 /// Drag marker for resizing the left sidebar edge.
@@ -99,6 +104,7 @@ impl Render for ResizeRightPanel {
 pub(crate) enum SidebarTab {
     Nodes,
     Search,
+    Proposals,
 }
 
 /// Number of frame-cost samples retained for the rolling perf-overlay average.
@@ -158,6 +164,7 @@ pub struct AppView {
     pub(crate) graph_canvas: Entity<GraphCanvas>,
     pub(crate) node_panel: Entity<NodePanel>,
     pub(crate) search_panel: Entity<SearchPanel>,
+    pub(crate) proposal_panel: Entity<ProposalPanel>,
     pub(crate) node_editor: Entity<NodeEditorPanel>,
     pub(crate) chat_panel: Entity<ChatPanel>,
     #[allow(dead_code)]
@@ -198,7 +205,7 @@ pub struct AppView {
 impl AppView {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        snapshot: GraphSnapshot,
+        scoped: ScopedSnapshot,
         graph: Arc<KnowledgeGraph>,
         schema_mgr: Arc<SchemaManager>,
         data_file: std::path::PathBuf,
@@ -207,7 +214,9 @@ impl AppView {
         tokio_rt: Arc<tokio::runtime::Runtime>,
         cx: &mut Context<Self>,
     ) -> Self {
-        let snapshot_arc = Arc::new(RwLock::new(snapshot));
+        let totals = (scoped.total_nodes, scoped.total_edges);
+        let clusters = scoped.clusters;
+        let snapshot_arc = Arc::new(RwLock::new(scoped.snapshot));
 
         // Build child entities — clone Arc handles before they move into AppState.
         let selection = cx.new(|_cx| SelectionModel::new(snapshot_arc.clone()));
@@ -237,6 +246,29 @@ impl AppView {
                 cx,
             )
         });
+        let embeddings_sub = cx.subscribe(
+            &search_panel,
+            |this: &mut Self, _panel, _ev: &EmbeddingsRequested, cx| {
+                if !this.state.embeddings_loading {
+                    this.init_lemonade(true, cx);
+                }
+            },
+        );
+        // Selecting a collapsed cluster expands it into its members.
+        let cluster_sub = cx.observe(&selection, |this: &mut Self, sel, cx| {
+            let selected = sel.read(cx).selected_node_id;
+            if let Some(key) = selected.and_then(|id| this.state.graph_clusters.get(&id)) {
+                let request = this.state.graph_request.clone().expanding(key.clone());
+                this.set_graph_request(request, cx);
+            }
+        });
+        let proposal_panel = cx.new(|_cx| ProposalPanel::new(graph.clone()));
+        let proposal_sub = cx.subscribe(
+            &proposal_panel,
+            |this: &mut Self, _panel, _ev: &ProposalAccepted, cx| {
+                this.refresh_snapshot(cx);
+            },
+        );
         let node_editor = cx.new(|cx| {
             NodeEditorPanel::new(
                 snapshot_arc.clone(),
@@ -267,7 +299,7 @@ impl AppView {
             },
         );
 
-        let state = AppState::new(
+        let mut state = AppState::new(
             graph,
             snapshot_arc,
             data_file,
@@ -275,12 +307,15 @@ impl AppView {
             app_config,
             tokio_rt,
         );
+        state.graph_totals = totals;
+        state.graph_clusters = clusters;
 
         let mut view = Self {
             state,
             graph_canvas,
             node_panel,
             search_panel,
+            proposal_panel,
             node_editor,
             chat_panel,
             selection,
@@ -294,7 +329,14 @@ impl AppView {
             right_panel_width: DEFAULT_RIGHT_PANEL_W,
             path_picker: None,
             _path_picker_subs: vec![],
-            _node_subs: vec![node_sub_create, node_sub_delete, connect_sub],
+            _node_subs: vec![
+                node_sub_create,
+                node_sub_delete,
+                connect_sub,
+                embeddings_sub,
+                proposal_sub,
+                cluster_sub,
+            ],
             perf_enabled: false,
             last_frame_cost_us: 0,
             frame_times_us: FrameTimeRing::default(),
@@ -306,21 +348,16 @@ impl AppView {
 
     /// Rebuild the in-memory snapshot from the graph and push it to all child views.
     ///
-    /// Uses `build_snapshot_incremental` when a previous snapshot exists so that
-    /// single-mutation events (e.g. agent `UpsertNodeTool`) apply R-tree and
-    /// legend deltas in O(delta × log N) instead of rebuilding from scratch.
+    /// The canvas draws only what `state.graph_request` selects — the whole
+    /// graph, a neighbourhood or a filter — within its node and edge budget,
+    /// so large worlds stay responsive.
     pub(crate) fn refresh_snapshot(&mut self, cx: &mut Context<Self>) {
-        let result = {
-            let prev = self.state.snapshot.read();
-            if prev.nodes.is_empty() && prev.edges.is_empty() {
-                u_forge_graph_view::build_snapshot(&self.state.graph)
-            } else {
-                u_forge_graph_view::build_snapshot_incremental(&self.state.graph, &prev)
-            }
-        };
-        match result {
-            Ok(snap) => {
-                *self.state.snapshot.write() = snap;
+        match u_forge_graph_view::build_scoped_snapshot(&self.state.graph, &self.state.graph_request)
+        {
+            Ok(scoped) => {
+                self.state.graph_totals = (scoped.total_nodes, scoped.total_edges);
+                self.state.graph_clusters = scoped.clusters;
+                *self.state.snapshot.write() = scoped.snapshot;
                 self.node_panel
                     .update(cx, |panel, cx| panel.refresh_groups(cx));
                 cx.notify();
@@ -331,6 +368,79 @@ impl AppView {
         }
     }
 
+    /// Show `request` on the canvas.  Positions of the current view are saved
+    /// first so nodes keep their place when they reappear.
+    pub(crate) fn set_graph_request(&mut self, request: GraphDataRequest, cx: &mut Context<Self>) {
+        self.graph_canvas.read(cx).save_layout();
+        self.state.graph_request = request;
+        self.refresh_snapshot(cx);
+    }
+
+    /// Show the whole graph (within the default budget) on the canvas.
+    pub(crate) fn show_whole_graph(&mut self, cx: &mut Context<Self>) {
+        self.set_graph_scope(GraphScope::All, cx);
+    }
+
+    /// Change what the canvas selects, keeping the budget and any grouping.
+    fn set_graph_scope(&mut self, scope: GraphScope, cx: &mut Context<Self>) {
+        let mut request = self.state.graph_request.clone();
+        request.scope = scope;
+        request.expand.clear();
+        self.set_graph_request(request, cx);
+    }
+
+    /// Collapse the canvas scope into clusters by type, tag or community, or
+    /// show individual nodes again with `None`.  Expanded clusters reset.
+    pub(crate) fn set_aggregation(
+        &mut self,
+        aggregation: Option<Aggregation>,
+        cx: &mut Context<Self>,
+    ) {
+        let mut request = self.state.graph_request.clone();
+        request.aggregate = aggregation;
+        request.expand.clear();
+        self.set_graph_request(request, cx);
+    }
+
+    /// Scope the canvas to the selected node and everything within
+    /// [`FOCUS_DEPTH`] hops of it.
+    pub(crate) fn focus_selection(&mut self, cx: &mut Context<Self>) {
+        let Some(id) = self.selection.read(cx).selected_node_id else {
+            self.state.data_status = Some("Select a node to focus on.".to_string());
+            cx.notify();
+            return;
+        };
+        self.set_graph_scope(
+            GraphScope::Neighborhood {
+                focus: id,
+                depth: FOCUS_DEPTH,
+            },
+            cx,
+        );
+    }
+
+    /// Scope the canvas to objects of the selected node's type.
+    pub(crate) fn filter_to_selected_type(&mut self, cx: &mut Context<Self>) {
+        let Some(id) = self.selection.read(cx).selected_node_id else {
+            self.state.data_status = Some("Select a node to filter by its type.".to_string());
+            cx.notify();
+            return;
+        };
+        let object_type = {
+            let snap = self.state.snapshot.read();
+            snap.id_to_idx
+                .get(&id)
+                .map(|&idx| snap.nodes[idx].object_type.clone())
+        };
+        if let Some(object_type) = object_type {
+            let filter = NodeFilter {
+                object_types: vec![object_type],
+                ..Default::default()
+            };
+            self.set_graph_scope(GraphScope::Filter(filter), cx);
+        }
+    }
+
     pub(crate) fn do_clear_data(&mut self, cx: &mut Context<Self>) {
         match self.state.graph.clear_data() {
             Ok(()) => {
@@ -361,16 +471,61 @@ impl AppView {
     pub(crate) fn do_import_data(&mut self, cx: &mut Context<Self>) {
         let graph = self.state.graph.clone();
         let data_file = self.state.data_file.clone();
+        let tokio_rt = self.state.tokio_rt.clone();
 
         self.state.data_status = Some("Importing…".to_string());
         cx.notify();
 
+        // Shared progress state written by the import, read by the poller.
+        let progress_state = LatestProgress::new();
+        let progress_write = progress_state.clone();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let done_poller = Arc::clone(&done);
+        // Poller: shows the current import stage in the status bar every 500 ms.
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor()
+                .timer(std::time::Duration::from_millis(500))
+                .await;
+            if done_poller.load(Ordering::Relaxed) {
+                return;
+            }
+            let Some(p) = progress_state.latest() else {
+                continue;
+            };
+            let status = match p.total {
+                Some(total) => format!("Importing… {} {}/{total}", p.stage, p.done),
+                None => format!("Importing… {}", p.stage),
+            };
+            let updated = this.update(cx, |view: &mut AppView, cx| {
+                view.state.data_status = Some(status);
+                cx.notify();
+            });
+            if updated.is_err() {
+                return;
+            }
+        })
+        .detach();
+
+        // Worker: runs the import on the tokio runtime.  The guard stops the
+        // poller even if the import panics.
         cx.spawn(async move |this, cx| {
-            let result = u_forge_core::ingest::import_data_only(
-                &graph,
-                data_file.to_str().unwrap_or(""),
-            )
-            .await;
+            let done_guard = state::CancelOnDrop(done);
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    tokio_rt.block_on(async move {
+                        u_forge_core::ingest::import_data_only(
+                            &graph,
+                            data_file.to_str().unwrap_or(""),
+                            None,
+                            &progress_write,
+                        )
+                        .await
+                    })
+                })
+                .await;
+            drop(done_guard);
 
             this.update(cx, |view: &mut AppView, cx| {
                 match result {
@@ -485,6 +640,7 @@ impl AppView {
                             Ok(_) => {
                                 view.state.schema_loaded = true;
                                 view.state.data_status = Some("Schema directory loaded".to_string());
+                                view.watch_schema_dir(dir, cx);
                             }
                             Err(e) => {
                                 view.state.data_status = Some(format!("Schema load failed: {e}"));
@@ -506,6 +662,50 @@ impl AppView {
         .detach();
     }
 
+    /// Hot-reload `dir` into "imported_schemas" so schema edits apply
+    /// without restarting; reload results are shown in the status bar.
+    fn watch_schema_dir(&mut self, dir: std::path::PathBuf, cx: &mut Context<Self>) {
+        let watcher = {
+            let _guard = self.state.tokio_rt.enter();
+            u_forge_core::SchemaIngestion::watch_directory(
+                &dir,
+                "imported_schemas",
+                "1.0.0",
+                self.state.graph.get_schema_manager(),
+                None,
+            )
+        };
+        let mut events = watcher.subscribe();
+        self.state.schema_watcher = Some(watcher);
+
+        cx.spawn(async move |this, cx| {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                let status = match events.recv().await {
+                    Ok(u_forge_core::SchemaReloadEvent::Reloaded { object_types, .. }) => {
+                        format!("Schemas reloaded ({object_types} types)")
+                    }
+                    Ok(u_forge_core::SchemaReloadEvent::Rejected { errors, .. }) => {
+                        format!("Schema reload rejected: {}", errors.join("; "))
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    // Watcher replaced or dropped.
+                    Err(RecvError::Closed) => break,
+                };
+                if this
+                    .update(cx, |view, cx| {
+                        view.state.data_status = Some(status);
+                        cx.notify();
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
     pub(crate) fn do_save(&mut self, cx: &mut Context<Self>) {
         // 1. Save layout positions.
         self.graph_canvas.read(cx).save_layout();
@@ -534,7 +734,13 @@ impl AppView {
             self.refresh_snapshot(cx);
 
             // 3. Re-chunk and embed every saved node so semantic search stays current.
+            //    The intent keeps the work pending across a crash, a cancelled
+            //    plan, or a missing embedding server; the startup sweep rolls
+            //    it forward.
             if !saved_ids.is_empty() {
+                if let Err(e) = self.state.graph.record_intent(IntentKind::Reindex, &saved_ids) {
+                    eprintln!("Failed to record re-index intent: {e}");
+                }
                 self.run_embedding_plan(EmbeddingPlan::rechunk(saved_ids), cx);
             }
         }
@@ -661,9 +867,10 @@ impl AppView {
         let epoch = self.state.embedding_plan_epoch;
 
         // Shared progress state written by the tokio worker, read by the poller.
-        let progress_state: Arc<parking_lot::Mutex<Option<EmbeddingProgress>>> =
-            Arc::new(parking_lot::Mutex::new(None));
-        let progress_write = Arc::clone(&progress_state);
+        // The plan also watches the cancel flag, so a newer plan stops this one.
+        let progress_state = LatestProgress::new()
+            .with_cancellation(CancellationToken::from(Arc::clone(&cancel)));
+        let progress_write = progress_state.clone();
 
         let cancel_poller = Arc::clone(&cancel);
         // Poller: reads shared progress every 500 ms and refreshes the status bar.
@@ -679,15 +886,16 @@ impl AppView {
                     return;
                 }
                 let Some(this) = this.upgrade() else { return };
-                let snap = progress_state.lock().clone();
+                let snap = progress_state.latest();
                 let keep_running = this
                     .update(cx, |view: &mut AppView, cx| {
                         if view.state.embedding_plan_epoch != epoch {
                             return false;
                         }
-                        if let Some(EmbeddingProgress::Rechunking { done, total }) = snap {
+                        if let Some(p) = snap.filter(|p| p.stage == "rechunk") {
+                            let total = p.total.unwrap_or(p.done);
                             view.state.embedding_status =
-                                Some(format!("Re-embedding… ({done}/{total})"));
+                                Some(format!("Re-embedding… ({}/{total})", p.done));
                             cx.notify();
                         }
                         true
@@ -714,7 +922,7 @@ impl AppView {
                                 &graph,
                                 &queue,
                                 hq_queue.as_ref(),
-                                move |p| *progress_write.lock() = Some(p),
+                                &progress_write,
                             )
                             .await
                         })
@@ -735,11 +943,28 @@ impl AppView {
         .detach();
     }
 
-    /// Asynchronously discover Lemonade Server and build the InferenceQueue + ChatProvider.
-    /// FTS5 search works immediately even if this fails.
+    /// Connect to Lemonade, loading embedding models now only when the
+    /// project's [`EmbeddingMode`](u_forge_core::EmbeddingMode) is eager.
     pub(crate) fn do_init_lemonade(&mut self, cx: &mut Context<Self>) {
+        let mode = self.state.graph.embedding_mode().unwrap_or_else(|e| {
+            eprintln!("Failed to read embedding mode: {e:#}");
+            Default::default()
+        });
+        self.init_lemonade(mode.loads_at_startup(), cx);
+    }
+
+    /// Asynchronously discover Lemonade Server and build the InferenceQueue + ChatProvider.
+    /// FTS5 search works immediately even if this fails.  Without `with_embeddings` no embedding,
+    /// high-quality embedding, or reranker model is built, so none is
+    /// downloaded or loaded; saved nodes are still re-chunked for FTS.
+    pub(crate) fn init_lemonade(&mut self, with_embeddings: bool, cx: &mut Context<Self>) {
+        self.state.embeddings_loading = with_embeddings;
         let app_config = self.state.app_config.clone();
         let tokio_rt = self.state.tokio_rt.clone();
+        let language = self.state.graph.get_default_language().unwrap_or_else(|e| {
+            tracing::warn!("Failed to read project language: {e:#}");
+            None
+        });
 
         cx.spawn(async move |this, cx| {
             let result = cx
@@ -762,7 +987,8 @@ impl AppView {
                             "milestone: select — catalog fetched"
                         );
                         let selector =
-                            ModelSelector::new(&catalog, &app_config.models, &app_config.embedding);
+                            ModelSelector::new(&catalog, &app_config.models, &app_config.embedding)
+                                .with_language(language.as_deref());
                         let embed_models = selector.select_embedding_models();
                         let reranker_sel = selector.select_reranker();
 
@@ -776,7 +1002,7 @@ impl AppView {
                         let mut build_futs = Vec::new();
                         for sel in embed_models
                             .iter()
-                            .filter(|s| s.quality_tier == QualityTier::Standard)
+                            .filter(|s| with_embeddings && s.quality_tier == QualityTier::Standard)
                         {
                             let weight = match sel.recipe.as_str() {
                                 "flm" => app_config.embedding.npu_weight,
@@ -790,7 +1016,7 @@ impl AppView {
                             };
                             build_futs.push((sel.clone(), Capability::Embedding, weight));
                         }
-                        if let Some(r_sel) = reranker_sel {
+                        if let Some(r_sel) = reranker_sel.filter(|_| with_embeddings) {
                             build_futs.push((r_sel, Capability::Reranking, 100));
                         }
 
@@ -816,7 +1042,7 @@ impl AppView {
                         let build_results = futures::future::join_all(provider_futs).await;
                         let providers: Vec<_> = build_results.into_iter().flatten().collect();
 
-                        if providers.is_empty() {
+                        if providers.is_empty() && with_embeddings {
                             return Err(anyhow::anyhow!("No embedding providers available"));
                         }
 
@@ -830,7 +1056,11 @@ impl AppView {
                         );
 
                         // Build optional HQ embedding queue.
-                        let hq_queue = build_hq_embed_queue(&catalog, &app_config).await;
+                        let hq_queue = if with_embeddings {
+                            build_hq_embed_queue(&catalog, &app_config).await
+                        } else {
+                            None
+                        };
 
                         // Select ALL LLM models for the UI picker (no device-slot dedup).
                         let all_llm = selector.select_all_llm_models();
@@ -883,6 +1113,7 @@ impl AppView {
                 .await;
 
             this.update(cx, |view: &mut AppView, cx| {
+                view.state.embeddings_loading = false;
                 match result {
                     Ok((lemonade_url, queue, hq_queue, chat_provider, llm_models, preferred_idx)) => {
                         eprintln!("Lemonade connected — embedding queue ready");
@@ -939,7 +1170,8 @@ impl AppView {
                             });
                         }
 
-                        // Trigger bulk embedding for any unembedded chunks.
+                        // Trigger bulk embedding for any unembedded chunks.  Without
+                        // embedding workers this still rolls pending re-chunks forward.
                         view.run_embedding_plan(EmbeddingPlan::embed_all(), cx);
                     }
                     Err(e) => {
@@ -4,6 +4,7 @@ use gpui::{
     anchored, canvas, deferred, div, point, prelude::*, px, relative, rgb, rgba, AnyView, App,
     ClickEvent, Context, Corner, MouseButton, MouseDownEvent, Render, StyleRefinement, Window,
 };
+use u_forge_core::{Aggregation, GraphScope};
 
 use crate::{
     ClearData, ClearSchema, ExportData, ImportData, ImportSchema, SaveLayout, TogglePerfOverlay,
@@ -37,6 +38,14 @@ impl Render for AppView {
         let right_panel_width = self.right_panel_width;
         let embedding_status = self.state.embedding_status.clone();
         let perf_enabled = self.perf_enabled;
+        let scope_all = matches!(self.state.graph_request.scope, GraphScope::All);
+        let scope_focus = matches!(
+            self.state.graph_request.scope,
+            GraphScope::Neighborhood { .. }
+        );
+        let scope_filter = matches!(self.state.graph_request.scope, GraphScope::Filter(_));
+        let aggregate = self.state.graph_request.aggregate;
+        let (total_nodes, total_edges) = self.state.graph_totals;
 
         // Build perf overlay text when enabled.
         // `last_frame_cost_us` is populated by the timing canvas at the bottom of
@@ -256,6 +265,9 @@ impl Render for AppView {
                                     SidebarTab::Search => {
                                         self.search_panel.clone().into_any_element()
                                     }
+                                    SidebarTab::Proposals => {
+                                        self.proposal_panel.clone().into_any_element()
+                                    }
                                 }),
                         )
                         .child(
@@ -528,6 +540,42 @@ impl Render for AppView {
                                         }),
                                     )
                                     .child("Search"),
+                            )
+                            // Proposals button
+                            .child(
+                                div()
+                                    .id("status-proposals-btn")
+                                    .flex()
+                                    .items_center()
+                                    .px_2()
+                                    .h(px(STATUS_BAR_H - 4.0))
+                                    .cursor_pointer()
+                                    .text_color(
+                                        if sidebar_open && sidebar_tab == SidebarTab::Proposals {
+                                            rgba(0xcdd6f4ff)
+                                        } else {
+                                            rgba(0x6c7086ff)
+                                        },
+                                    )
+                                    .when(
+                                        sidebar_open && sidebar_tab == SidebarTab::Proposals,
+                                        |el| el.bg(rgba(0x45475a88)),
+                                    )
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|this, _: &MouseDownEvent, _window, cx| {
+                                            if this.sidebar_open && this.sidebar_tab == SidebarTab::Proposals {
+                                                this.sidebar_open = false;
+                                            } else {
+                                                this.sidebar_open = true;
+                                                this.sidebar_tab = SidebarTab::Proposals;
+                                                this.proposal_panel
+                                                    .update(cx, |panel, cx| panel.refresh(cx));
+                                            }
+                                            cx.notify();
+                                        }),
+                                    )
+                                    .child("Proposals"),
                             ),
                     )
                     // ── Center: graph stats + operation status ────────────────
@@ -542,10 +590,14 @@ impl Render for AppView {
                             .gap(px(12.0))
                             .text_color(rgba(0xa6adc8ff));
                         center.style().flex_grow = Some(1.0);
-                        center = center.child(format!(
-                            "{} nodes  ·  {} edges",
-                            node_count, edge_count
-                        ));
+                        center = center.child(if node_count < total_nodes {
+                            format!(
+                                "{node_count} of {total_nodes} nodes  ·  \
+                                 {edge_count} of {total_edges} edges"
+                            )
+                        } else {
+                            format!("{} nodes  ·  {} edges", node_count, edge_count)
+                        });
                         if let Some(msg) = data_status {
                             center = center.child(div().text_color(rgba(0xa6e3a1ff)).child(msg));
                         }
@@ -875,6 +927,199 @@ impl Render for AppView {
                                         } else {
                                             "    Right Panel      Ctrl+J"
                                         }),
+                                )
+                                // ── Canvas scope ──
+                                .child(div().h(px(1.0)).bg(rgb(0x45475a)))
+                                // Whole Graph
+                                .child(
+                                    div()
+                                        .id("scope-all-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.show_whole_graph(cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if scope_all {
+                                            "  Whole Graph"
+                                        } else {
+                                            "    Whole Graph"
+                                        }),
+                                )
+                                // Focus Selection
+                                .child(
+                                    div()
+                                        .id("scope-focus-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.focus_selection(cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if scope_focus {
+                                            "  Focus Selection"
+                                        } else {
+                                            "    Focus Selection"
+                                        }),
+                                )
+                                // Selection's Type
+                                .child(
+                                    div()
+                                        .id("scope-type-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.filter_to_selected_type(cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if scope_filter {
+                                            "  Selection's Type"
+                                        } else {
+                                            "    Selection's Type"
+                                        }),
+                                )
+                                // ── Grouping (click a group to expand it) ──
+                                .child(div().h(px(1.0)).bg(rgb(0x45475a)))
+                                // Ungrouped
+                                .child(
+                                    div()
+                                        .id("group-none-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.set_aggregation(None, cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if aggregate.is_none() {
+                                            "  Ungrouped"
+                                        } else {
+                                            "    Ungrouped"
+                                        }),
+                                )
+                                // Group by Type
+                                .child(
+                                    div()
+                                        .id("group-type-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.set_aggregation(Some(Aggregation::Type), cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if aggregate == Some(Aggregation::Type) {
+                                            "  Group by Type"
+                                        } else {
+                                            "    Group by Type"
+                                        }),
+                                )
+                                // Group by Tag
+                                .child(
+                                    div()
+                                        .id("group-tag-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.set_aggregation(Some(Aggregation::Tag), cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if aggregate == Some(Aggregation::Tag) {
+                                            "  Group by Tag"
+                                        } else {
+                                            "    Group by Tag"
+                                        }),
+                                )
+                                // Group by Community
+                                .child(
+                                    div()
+                                        .id("group-community-item")
+                                        .flex()
+                                        .flex_row()
+                                        .items_center()
+                                        .h(px(28.0))
+                                        .px_3()
+                                        .text_color(rgba(0xcdd6f4ff))
+                                        .text_xs()
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(
+                                                |this, _: &MouseDownEvent, _window, cx| {
+                                                    this.view_menu_open = false;
+                                                    this.set_aggregation(Some(Aggregation::Community), cx);
+                                                },
+                                            ),
+                                        )
+                                        .child(if aggregate == Some(Aggregation::Community) {
+                                            "  Group by Community"
+                                        } else {
+                                            "    Group by Community"
+                                        }),
                                 ),
                         ),
                 ))
@@ -1,10 +1,13 @@
+use std::collections::HashMap;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
 
 use parking_lot::RwLock;
-use u_forge_core::{queue::InferenceQueue, AppConfig, KnowledgeGraph};
+use u_forge_core::{
+    queue::InferenceQueue, AppConfig, GraphDataRequest, GraphScope, KnowledgeGraph, ObjectId,
+};
 use u_forge_graph_view::GraphSnapshot;
 
 /// Non-render application state owned by [`super::AppView`].
@@ -16,6 +19,15 @@ use u_forge_graph_view::GraphSnapshot;
 pub(crate) struct AppState {
     pub(crate) graph: Arc<KnowledgeGraph>,
     pub(crate) snapshot: Arc<RwLock<GraphSnapshot>>,
+    /// What the canvas shows — scope, budget and aggregation.  Changed from
+    /// the View menu; every snapshot refresh re-runs it.
+    pub(crate) graph_request: GraphDataRequest,
+    /// `(nodes, edges)` in the whole graph as of the last snapshot, so the
+    /// status bar can tell how much the scope hides.
+    pub(crate) graph_totals: (usize, usize),
+    /// Cluster key of each collapsed-cluster stand-in node in the snapshot;
+    /// selecting one expands it.
+    pub(crate) graph_clusters: HashMap<ObjectId, String>,
     pub(crate) data_file: std::path::PathBuf,
     pub(crate) schema_dir: std::path::PathBuf,
     pub(crate) app_config: Arc<AppConfig>,
@@ -24,8 +36,14 @@ pub(crate) struct AppState {
     pub(crate) inference_queue: Option<InferenceQueue>,
     /// High-quality embedding queue (None when HQ embedding is disabled or unavailable).
     pub(crate) hq_queue: Option<InferenceQueue>,
+    /// True while a Lemonade connect that loads embedding models is running,
+    /// so a lazy-mode search does not start a second one.
+    pub(crate) embeddings_loading: bool,
     /// True when at least one non-default schema is present in the graph DB.
     pub(crate) schema_loaded: bool,
+    /// Hot-reloads the last schema directory loaded via the UI; replaced on
+    /// each load and stopped on drop.
+    pub(crate) schema_watcher: Option<u_forge_core::SchemaWatcher>,
     /// Status message displayed in the status bar during/after data operations.
     pub(crate) data_status: Option<String>,
     /// Embedding progress/completion message shown in the status bar.
@@ -66,13 +84,18 @@ impl AppState {
         Self {
             graph,
             snapshot,
+            graph_request: GraphDataRequest::new(GraphScope::All),
+            graph_totals: (0, 0),
+            graph_clusters: HashMap::new(),
             data_file,
             schema_dir,
             app_config,
             tokio_rt,
             schema_loaded,
+            schema_watcher: None,
             inference_queue: None,
             hq_queue: None,
+            embeddings_loading: false,
             data_status: None,
             embedding_status: None,
             embedding_plan_epoch: 0,
@@ -12,8 +12,8 @@ use gpui::{
 };
 use parking_lot::RwLock;
 use u_forge_core::{KnowledgeGraph, ObjectId};
-use u_forge_graph_view::{GraphSnapshot, LodLevel};
-use u_forge_ui_traits::{generate_draw_commands, node_color_for_type, Viewport, NODE_RADIUS};
+use u_forge_graph_view::{GraphSnapshot, LodLevel, CLUSTER_NODE_TYPE};
+use u_forge_ui_traits::{generate_draw_commands, Viewport, NODE_RADIUS};
 
 use crate::selection_model::SelectionModel;
 
@@ -95,12 +95,14 @@ impl GraphCanvas {
         )
     }
 
-    /// Persist all current node positions to the database.
+    /// Persist all current node positions to the database.  Cluster
+    /// stand-ins are generated per snapshot and are not saved.
     pub(crate) fn save_layout(&self) {
         let snap = self.snapshot.read();
         let positions: Vec<(ObjectId, f32, f32)> = snap
             .nodes
             .iter()
+            .filter(|n| n.object_type != CLUSTER_NODE_TYPE)
             .map(|n| (n.id, n.position.x, n.position.y))
             .collect();
         drop(snap);
@@ -259,9 +261,14 @@ impl Render for GraphCanvas {
                             generate_draw_commands(&snap, &viewport, selected_idx, font_scale);
                         let lod = viewport.lod_level();
                         // Clone the precomputed legend so we can drop the read lock.
-                        // `legend_types` is built once in `build_snapshot()` and only
-                        // changes when the snapshot is rebuilt — no per-frame scan.
-                        let legend_types = snap.legend_types.clone();
+                        // `legend_types` and the type styles are built once in
+                        // `build_snapshot()` and only change when the snapshot is
+                        // rebuilt — no per-frame scan.
+                        let legend_types: Vec<(String, [u8; 3])> = snap
+                            .legend_types
+                            .iter()
+                            .map(|t| (t.clone(), snap.type_color(t)))
+                            .collect();
                         drop(snap);
 
                         // ── Edges (batched paths) ────────────────────────────────────
@@ -389,11 +396,11 @@ impl Render for GraphCanvas {
                             let label_size = window.rem_size() * 0.875;
                             let line_h = label_size * 1.3;
 
-                            for (i, type_name) in legend_types.iter().enumerate() {
+                            for (i, (type_name, color)) in legend_types.iter().enumerate() {
                                 let row_y = ly + pad + i as f32 * entry_h;
                                 let center_y = row_y + entry_h / 2.0;
 
-                                let [r, g, b, _] = node_color_for_type(type_name);
+                                let [r, g, b] = *color;
                                 let color_hex = ((r as u32) << 16)
                                     | ((g as u32) << 8)
                                     | (b as u32);
@@ -5,6 +5,7 @@ pub mod chat_message;
 pub mod chat_panel;
 pub mod graph_canvas;
 pub mod node_editor;
+pub mod proposal_panel;
 pub mod search_panel;
 pub mod selection_model;
 pub mod text_field;
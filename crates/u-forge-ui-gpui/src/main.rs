@@ -4,8 +4,8 @@ use gpui::{
     prelude::*, size, App, Application, Bounds, KeyBinding, Menu, MenuItem, WindowBounds,
     WindowOptions, px,
 };
-use u_forge_core::AppConfig;
-use u_forge_graph_view::build_snapshot;
+use u_forge_core::{AppConfig, GraphDataRequest, GraphScope, HookRunner};
+use u_forge_graph_view::build_scoped_snapshot;
 use u_forge_ui_gpui::{
     AppView, ClearData, ClearSchema, ExportData, ImportData, ImportSchema, SaveLayout,
     TogglePerfOverlay, ToggleRightPanel, ToggleSidebar,
@@ -62,11 +62,27 @@ fn main() {
                 }
             }
 
-            let snapshot = build_snapshot(&graph).expect("failed to build snapshot");
+            // Must match the initial `AppState::graph_request`.
+            let request = GraphDataRequest::new(GraphScope::All);
+            let snapshot =
+                build_scoped_snapshot(&graph, &request).expect("failed to build snapshot");
             (snapshot, graph, schema_mgr)
         })
     };
 
+    // Automation hooks come from the local config only; kept alive for the
+    // whole run.
+    let _hook_runner = {
+        let _rt = rt.enter();
+        match HookRunner::spawn(graph.clone(), cfg.hooks.clone()) {
+            Ok(runner) => Some(runner),
+            Err(e) => {
+                eprintln!("Warning: automation hooks disabled: {e}");
+                None
+            }
+        }
+    };
+
     Application::new().run(move |cx: &mut App| {
         // Register keybindings.
         cx.bind_keys([
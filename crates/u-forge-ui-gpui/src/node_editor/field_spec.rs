@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use gpui::Entity;
-use u_forge_core::{Edge, ObjectId, ObjectMetadata, ObjectTypeSchema, PropertyType};
+use u_forge_core::{Edge, EdgeId, ObjectId, ObjectMetadata, ObjectTypeSchema, PropertyType};
 
 use crate::text_field::TextFieldView;
 
@@ -51,6 +51,8 @@ pub(crate) struct FieldSpec {
     pub(crate) required: bool,
     pub(crate) multiline: bool,
     pub(crate) field_kind: PropertyType,
+    /// Computed from other properties (schema `computed`): shown, not edited.
+    pub(crate) read_only: bool,
 }
 
 impl FieldSpec {
@@ -97,6 +99,8 @@ pub(crate) fn values_equal(a: &serde_json::Value, b: &serde_json::Value) -> bool
 /// because a freshly-added row starts with both endpoints unset until the user
 /// picks nodes from the dropdowns.
 pub(crate) struct EditableEdge {
+    /// Id of the stored edge this row edits; `None` for a row added in the editor.
+    pub(crate) id: Option<EdgeId>,
     /// Source node (left-hand side of the relationship).
     pub(crate) from: Option<ObjectId>,
     /// Target node (right-hand side of the relationship).
@@ -114,6 +118,7 @@ impl EditableEdge {
     /// names from the provided lookup map.
     pub(crate) fn from_edge(edge: &Edge, names: &HashMap<ObjectId, String>) -> Self {
         Self {
+            id: Some(edge.id),
             from: Some(edge.from),
             to: Some(edge.to),
             edge_type: edge.edge_type.as_str().to_string(),
@@ -131,6 +136,7 @@ impl EditableEdge {
     /// Create a blank edge row (user will fill via dropdowns).
     pub(crate) fn empty() -> Self {
         Self {
+            id: None,
             from: None,
             to: None,
             edge_type: String::new(),
@@ -181,6 +187,10 @@ pub(crate) struct EditorTab {
 
     /// Which sub-tab (Properties or Edges) is currently visible.
     pub(crate) active_subtab: SubTab,
+
+    /// Errors reported by the last save of this tab, shown above the form.
+    /// Cleared by the next successful save.
+    pub(crate) save_error: Option<String>,
 }
 
 impl EditorTab {
@@ -195,6 +205,7 @@ impl EditorTab {
             required: true,
             multiline: false,
             field_kind: PropertyType::String,
+            read_only: false,
         });
 
         // 2. description — always second
@@ -204,6 +215,7 @@ impl EditorTab {
             required: false,
             multiline: true,
             field_kind: PropertyType::Text,
+            read_only: false,
         });
 
         if let Some(schema) = &self.schema {
@@ -216,7 +228,8 @@ impl EditorTab {
                 .collect();
             required_keys.sort();
 
-            // Collect optional keys
+            // Collect optional keys; computed properties are listed too but
+            // rendered read-only.
             let mut optional_keys: Vec<&String> = schema
                 .properties
                 .keys()
@@ -230,9 +243,14 @@ impl EditorTab {
                         PropertyType::Text
                         | PropertyType::String
                         | PropertyType::Reference(_) => (prop.property_type.clone(), true),
-                        PropertyType::Number | PropertyType::Boolean => {
-                            (prop.property_type.clone(), false)
-                        }
+                        PropertyType::Number
+                        | PropertyType::Boolean
+                        | PropertyType::Integer
+                        | PropertyType::Range(..)
+                        | PropertyType::Currency(_)
+                        | PropertyType::DiceExpression
+                        | PropertyType::Date
+                        | PropertyType::Coordinates => (prop.property_type.clone(), false),
                         PropertyType::Enum(_) | PropertyType::Array(_) => {
                             (prop.property_type.clone(), false)
                         }
@@ -244,6 +262,7 @@ impl EditorTab {
                         required: schema.required_properties.contains(key),
                         multiline,
                         field_kind: kind,
+                        read_only: prop.computed.is_some(),
                     });
                 }
             }
@@ -262,6 +281,7 @@ impl EditorTab {
                     required: false,
                     multiline: true,
                     field_kind: PropertyType::String,
+                    read_only: false,
                 });
             }
         } else {
@@ -279,6 +299,7 @@ impl EditorTab {
                     required: false,
                     multiline: true,
                     field_kind: PropertyType::String,
+                    read_only: false,
                 });
             }
         }
@@ -290,6 +311,7 @@ impl EditorTab {
             required: false,
             multiline: false,
             field_kind: PropertyType::Array(Box::new(PropertyType::String)),
+            read_only: false,
         });
 
         specs
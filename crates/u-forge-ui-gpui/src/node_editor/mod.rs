@@ -6,7 +6,10 @@ use std::sync::Arc;
 
 use gpui::{prelude::*, Context, Entity, Pixels, Subscription};
 use parking_lot::RwLock;
-use u_forge_core::{EdgeType, KnowledgeGraph, ObjectId, ObjectMetadata, PropertyIssue, PropertyType, SchemaManager};
+use u_forge_core::{
+    EdgeChanges, EdgeId, EdgeType, KnowledgeGraph, ObjectId, ObjectMetadata, PropertyIssue,
+    PropertyType, SchemaManager,
+};
 use u_forge_graph_view::GraphSnapshot;
 
 use crate::selection_model::SelectionModel;
@@ -145,6 +148,22 @@ impl NodeEditorPanel {
         self.open_tab_for_metadata(meta, true, cx);
     }
 
+    /// Computed property values for `tab`, evaluated against its edited
+    /// values so they follow the form as the user types.
+    pub(crate) fn computed_values(
+        &self,
+        tab: &EditorTab,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        let mut draft = tab.original.clone();
+        draft.properties = serde_json::Value::Object(
+            tab.edited_values
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        );
+        self.graph.computed_properties(&draft)
+    }
+
     /// Shared logic for opening a tab from `ObjectMetadata`.
     fn open_tab_for_metadata(
         &mut self,
@@ -231,13 +250,20 @@ impl NodeEditorPanel {
             edge_type_entities: Vec::new(),
             is_new,
             active_subtab: field_spec::SubTab::default(),
+            save_error: None,
         };
         let specs = tmp_tab.field_specs();
-        for spec in &specs {
+        for spec in specs.iter().filter(|spec| !spec.read_only) {
             match &spec.field_kind {
                 PropertyType::Text
                 | PropertyType::String
                 | PropertyType::Number
+                | PropertyType::Integer
+                | PropertyType::Range(..)
+                | PropertyType::Currency(_)
+                | PropertyType::DiceExpression
+                | PropertyType::Date
+                | PropertyType::Coordinates
                 | PropertyType::Reference(_)
                 | PropertyType::Object(_) => {
                     let multiline = spec.multiline;
@@ -247,9 +273,24 @@ impl NodeEditorPanel {
                         let mut tf = TextFieldView::new(multiline, &placeholder, cx);
                         let val_str: String = edited_values
                             .get(&key)
-                            .map(|v| match v {
-                                serde_json::Value::String(s) => s.clone(),
-                                other => other.to_string(),
+                            .map(|v| match (v, &spec.field_kind) {
+                                // Amounts are stored in the smallest unit; show
+                                // them in denominations (parsed back on save).
+                                (serde_json::Value::Number(n), PropertyType::Currency(d)) => {
+                                    match n.as_i64() {
+                                        Some(units) => u_forge_core::format_currency(units, d),
+                                        None => n.to_string(),
+                                    }
+                                }
+                                // Shown as "x, y @ map" (parsed back on save).
+                                (obj @ serde_json::Value::Object(_), PropertyType::Coordinates) => {
+                                    match u_forge_core::Coordinates::from_value(obj) {
+                                        Some(coords) => coords.to_string(),
+                                        None => obj.to_string(),
+                                    }
+                                }
+                                (serde_json::Value::String(s), _) => s.clone(),
+                                (other, _) => other.to_string(),
                             })
                             .unwrap_or_default();
                         tf.set_content(&val_str, cx);
@@ -290,6 +331,7 @@ impl NodeEditorPanel {
             edge_type_entities,
             is_new,
             active_subtab: field_spec::SubTab::default(),
+            save_error: None,
         };
 
         // Replace the first unpinned non-dirty tab, or append.
@@ -443,22 +485,35 @@ impl NodeEditorPanel {
             }
             meta.properties = serde_json::Value::Object(props);
 
-            if self.graph.update_object(meta.clone()).is_ok() {
-                // ── Save edge changes ─────────────────────────────────────
-                skipped_edges += Self::save_edges_for_tab(&self.graph, tab);
-
-                saved_ids.push(tab.node_id);
-                tab.original = meta;
-                tab.dirty = false;
-                tab.is_new = false;
-
-                // Refresh original_edges so subsequent dirty checks are correct.
-                tab.original_edges = self
-                    .graph
-                    .get_relationships(tab.node_id)
-                    .unwrap_or_default();
+            match self.graph.update_object(meta.clone()) {
+                Ok(()) => {
+                    // ── Save edge changes ─────────────────────────────────
+                    let (skipped, errors) = Self::save_edges_for_tab(&self.graph, tab);
+                    skipped_edges += skipped;
+
+                    saved_ids.push(tab.node_id);
+                    tab.original = meta;
+                    tab.is_new = false;
+
+                    // Refresh original_edges so subsequent dirty checks are correct.
+                    tab.original_edges = self
+                        .graph
+                        .get_relationships(tab.node_id)
+                        .unwrap_or_default();
+
+                    // A failed edge change leaves the tab dirty so the user
+                    // can fix the row and save again.
+                    if errors.is_empty() {
+                        tab.dirty = false;
+                        tab.save_error = None;
+                    } else {
+                        tab.recompute_dirty();
+                        tab.save_error = Some(errors.join("\n"));
+                    }
 
-                saved += 1;
+                    saved += 1;
+                }
+                Err(e) => tab.save_error = Some(format!("Save failed: {e}")),
             }
         }
         cx.notify();
@@ -466,24 +521,20 @@ impl NodeEditorPanel {
         (saved, saved_ids, discarded_ids, skipped_edges)
     }
 
-    /// Persist edge changes for a single tab: delete removed edges and add new ones.
+    /// Persist edge changes for a single tab: delete removed edges, update
+    /// edited ones in place (keeping their ids and metadata), and add new ones.
     ///
     /// Returns the number of edges that were skipped because one or both endpoints
-    /// were still `None` (incomplete edges left by the user before saving).
+    /// were still `None` (incomplete edges left by the user before saving), and
+    /// a message for every edge change the graph rejected.
     ///
     /// Takes `graph` explicitly (rather than `&self`) so this can be called
     /// while iterating over `&mut self.tabs` without a borrow conflict.
-    fn save_edges_for_tab(graph: &KnowledgeGraph, tab: &EditorTab) -> usize {
-        // Build sets of (from, to, type) triples for comparison.
-        let orig_set: Vec<(ObjectId, ObjectId, String)> = tab
-            .original_edges
-            .iter()
-            .map(|e| (e.from, e.to, e.edge_type.as_str().to_string()))
-            .collect();
-
+    fn save_edges_for_tab(graph: &KnowledgeGraph, tab: &EditorTab) -> (usize, Vec<String>) {
         let incomplete_count = tab.edited_edges.iter().filter(|e| !e.is_complete()).count();
 
-        let edited_set: Vec<(ObjectId, ObjectId, String)> = tab
+        // Complete rows as (id, from, to, type); `id` is `None` for new rows.
+        let edited: Vec<(Option<EdgeId>, ObjectId, ObjectId, String)> = tab
             .edited_edges
             .iter()
             .filter(|e| e.is_complete())
@@ -491,31 +542,53 @@ impl NodeEditorPanel {
                 let (Some(from), Some(to)) = (e.from, e.to) else {
                     unreachable!("is_complete() guarantees both endpoints are Some")
                 };
-                (from, to, e.edge_type.trim().to_string())
+                (e.id, from, to, e.edge_type.trim().to_string())
             })
             .collect();
 
-        // Delete edges that were in original but not in edited.
-        for (from, to, et) in &orig_set {
-            if !edited_set
-                .iter()
-                .any(|(f, t, e)| f == from && t == to && e == et)
-            {
-                let _ = graph.delete_edge(*from, *to, et);
+        let mut errors = Vec::new();
+
+        // Delete original edges whose rows were removed (or left incomplete).
+        for orig in &tab.original_edges {
+            if !edited.iter().any(|(id, ..)| *id == Some(orig.id)) {
+                if let Err(e) = graph.delete_edge_by_id(orig.id) {
+                    errors.push(format!("Failed to delete {} edge: {e}", orig.edge_type.as_str()));
+                }
             }
         }
 
-        // Add edges that are in edited but not in original.
-        for (from, to, et) in &edited_set {
-            if !orig_set
-                .iter()
-                .any(|(f, t, e)| f == from && t == to && e == et)
-            {
-                let _ = graph.connect_objects(*from, *to, EdgeType::new(et.clone()));
+        for (id, from, to, et) in &edited {
+            match id.and_then(|id| tab.original_edges.iter().find(|e| e.id == id)) {
+                // Existing edge: update it in place if the row changed.
+                Some(orig) => {
+                    if orig.from != *from || orig.to != *to || orig.edge_type.as_str() != et {
+                        let changes = EdgeChanges {
+                            from: Some(*from),
+                            to: Some(*to),
+                            edge_type: Some(EdgeType::new(et.clone())),
+                            ..Default::default()
+                        };
+                        if let Err(e) = graph.update_edge(orig.id, changes) {
+                            errors.push(format!("Failed to update {et} edge: {e}"));
+                        }
+                    }
+                }
+                // New row: add it unless it duplicates an original edge.
+                None => {
+                    if !tab.original_edges.iter().any(|e| {
+                        e.from == *from && e.to == *to && e.edge_type.as_str() == et
+                    }) {
+                        if let Err(e) =
+                            graph.connect_objects(*from, *to, EdgeType::new(et.clone()))
+                        {
+                            errors.push(format!("Failed to add {et} edge: {e}"));
+                        }
+                    }
+                }
             }
         }
 
-        incomplete_count
+        (incomplete_count, errors)
     }
 
     /// Return true if any tab has unsaved changes.
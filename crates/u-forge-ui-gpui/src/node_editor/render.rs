@@ -184,6 +184,7 @@ impl Render for NodeEditorPanel {
 
         let tab = &self.tabs[active_idx];
         let specs = tab.field_specs();
+        let computed = self.computed_values(tab);
         let active_subtab = tab.active_subtab;
 
         // ── Sub-tab bar (Properties / Edges) ─────────────────────────────────
@@ -336,6 +337,8 @@ impl Render for NodeEditorPanel {
                 // Label
                 let label_text = if spec.required {
                     format!("{} *", spec.label)
+                } else if spec.read_only {
+                    format!("{} (computed)", spec.label)
                 } else {
                     spec.label.clone()
                 };
@@ -347,6 +350,25 @@ impl Render for NodeEditorPanel {
 
                 // Widget
                 let widget: gpui::AnyElement = match &spec.field_kind {
+                    // Computed values are derived from the other fields; shown
+                    // as static text, blank while their inputs are missing.
+                    _ if spec.read_only => {
+                        let display: SharedString = computed
+                            .get(&spec.key)
+                            .map(|v| v.to_string())
+                            .unwrap_or_default()
+                            .into();
+                        div()
+                            .h(px(28.0))
+                            .px(px(6.0))
+                            .rounded(px(4.0))
+                            .border_1()
+                            .border_color(rgb(0x313244))
+                            .text_base()
+                            .text_color(rgba(0xa6adc8ff))
+                            .child(display)
+                            .into_any_element()
+                    }
                     PropertyType::Boolean => {
                         let checked = value.and_then(|v| v.as_bool()).unwrap_or(false);
                         let key = spec.key.clone();
@@ -704,11 +726,25 @@ impl Render for NodeEditorPanel {
 
         // ── Assemble content area based on active sub-tab ────────────────────
 
-        let base = outer
+        let mut base = outer
             .child(measure_canvas)
             .child(tab_bar)
             .child(subtab_bar);
 
+        // Errors from the last save of this tab (e.g. a rejected edge update).
+        if let Some(error) = self.tabs[active_idx].save_error.clone() {
+            base = base.child(
+                div()
+                    .id("save-error")
+                    .px_3()
+                    .py_1()
+                    .flex_none()
+                    .text_base()
+                    .text_color(rgba(0xf38ba8ff)) // Catppuccin red
+                    .child(error),
+            );
+        }
+
         if active_subtab == SubTab::Properties {
             // ── Properties sub-tab: scrollable form columns + page nav ────────
             let has_prev = current_page_idx > 0;
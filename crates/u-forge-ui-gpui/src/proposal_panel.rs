@@ -0,0 +1,270 @@
+use std::sync::Arc;
+
+use gpui::{
+    div, prelude::*, px, relative, rgb, rgba, Context, EventEmitter, MouseButton, MouseDownEvent,
+    Window,
+};
+use u_forge_core::{KnowledgeGraph, ObjectId, Proposal, ProposalId, ProposalStatus, ProposedChange};
+
+// ── Events ────────────────────────────────────────────────────────────────────
+
+/// Emitted after a proposal was accepted, so the parent `AppView` can
+/// refresh the snapshot.
+pub(crate) struct ProposalAccepted;
+impl EventEmitter<ProposalAccepted> for ProposalPanel {}
+
+// ── Proposal panel ────────────────────────────────────────────────────────────
+
+/// A pending proposal as shown in the list.
+struct ProposalEntry {
+    id: ProposalId,
+    label: String,
+    source: Option<String>,
+}
+
+/// Sidebar review queue for AI proposals: lists pending proposals and
+/// accepts or rejects them.
+pub(crate) struct ProposalPanel {
+    graph: Arc<KnowledgeGraph>,
+    entries: Vec<ProposalEntry>,
+    error: Option<String>,
+}
+
+impl ProposalPanel {
+    pub(crate) fn new(graph: Arc<KnowledgeGraph>) -> Self {
+        let mut panel = Self {
+            graph,
+            entries: Vec::new(),
+            error: None,
+        };
+        panel.reload();
+        panel
+    }
+
+    /// Re-read the pending proposals.  Call when the panel is shown.
+    pub(crate) fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.reload();
+        cx.notify();
+    }
+
+    fn reload(&mut self) {
+        match self.graph.list_proposals(Some(ProposalStatus::Pending)) {
+            Ok(proposals) => {
+                self.entries = proposals.iter().map(|p| self.entry(p)).collect();
+            }
+            Err(e) => self.error = Some(format!("Failed to load proposals: {e}")),
+        }
+    }
+
+    fn entry(&self, proposal: &Proposal) -> ProposalEntry {
+        let name = |id: ObjectId| {
+            self.graph
+                .get_object(id)
+                .ok()
+                .flatten()
+                .map(|o| o.name)
+                .unwrap_or_else(|| "?".to_string())
+        };
+        let label = match &proposal.change {
+            ProposedChange::Object(meta) => format!("{}: {}", meta.object_type, meta.name),
+            ProposedChange::Edge(edge) => format!(
+                "{} \u{2192} {} \u{2192} {}",
+                name(edge.from),
+                edge.edge_type.as_str(),
+                name(edge.to)
+            ),
+            ProposedChange::Property {
+                object_id,
+                key,
+                value,
+            } => format!("{}.{key} = {value}", name(*object_id)),
+        };
+        ProposalEntry {
+            id: proposal.id,
+            label,
+            source: proposal.source.clone(),
+        }
+    }
+
+    fn accept(&mut self, id: ProposalId, cx: &mut Context<Self>) {
+        match self.graph.accept_proposal(id) {
+            Ok(()) => {
+                self.error = None;
+                cx.emit(ProposalAccepted);
+            }
+            Err(e) => self.error = Some(format!("Accept failed: {e}")),
+        }
+        self.refresh(cx);
+    }
+
+    fn reject(&mut self, id: ProposalId, cx: &mut Context<Self>) {
+        match self.graph.reject_proposal(id) {
+            Ok(()) => self.error = None,
+            Err(e) => self.error = Some(format!("Reject failed: {e}")),
+        }
+        self.refresh(cx);
+    }
+}
+
+impl Render for ProposalPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut panel = div()
+            .id("proposal-panel")
+            .flex()
+            .flex_col()
+            .flex_none()
+            .w_full()
+            .h_full()
+            .min_h_0()
+            .bg(rgb(0x181825))
+            .border_r_1()
+            .border_color(rgb(0x313244));
+
+        // Fixed header with a reload button.
+        panel = panel.child(
+            div()
+                .id("proposal-header")
+                .flex()
+                .flex_row()
+                .items_center()
+                .justify_between()
+                .h(px(28.0))
+                .px_3()
+                .flex_none()
+                .border_b_1()
+                .border_color(rgb(0x313244))
+                .text_color(rgba(0xcdd6f4ff))
+                .text_base()
+                .child(format!("Proposals ({})", self.entries.len()))
+                .child(
+                    div()
+                        .id("proposal-reload")
+                        .cursor_pointer()
+                        .text_color(rgba(0x6c7086ff))
+                        .hover(|style| style.text_color(rgba(0xcdd6f4ff)))
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|this, _: &MouseDownEvent, _window, cx| {
+                                this.refresh(cx);
+                            }),
+                        )
+                        .child("\u{21BB}"),
+                ),
+        );
+
+        if let Some(error) = &self.error {
+            panel = panel.child(
+                div()
+                    .px_3()
+                    .py_1()
+                    .flex_none()
+                    .text_color(rgba(0xf38ba8ff))
+                    .child(error.clone()),
+            );
+        }
+
+        let mut scroll_area = div()
+            .id("proposal-scroll")
+            .flex()
+            .flex_col()
+            .overflow_y_scroll()
+            .min_h_0();
+        scroll_area.style().flex_grow = Some(1.0);
+        scroll_area.style().flex_shrink = Some(1.0);
+        scroll_area.style().flex_basis = Some(relative(0.).into());
+
+        if self.entries.is_empty() {
+            scroll_area = scroll_area.child(
+                div()
+                    .px_3()
+                    .py_2()
+                    .text_color(rgba(0x6c7086ff))
+                    .child("No pending proposals"),
+            );
+        }
+
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let id = entry.id;
+            let mut text = div()
+                .flex()
+                .flex_col()
+                .min_w_0()
+                .overflow_hidden()
+                .text_color(rgba(0xa6adc8ff))
+                .child(entry.label.clone());
+            if let Some(source) = &entry.source {
+                text = text.child(div().text_color(rgba(0x6c7086ff)).child(source.clone()));
+            }
+
+            scroll_area = scroll_area.child(
+                div()
+                    .id(("proposal-entry", idx))
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .justify_between()
+                    .gap(px(4.0))
+                    .px_2()
+                    .py_1()
+                    .flex_none()
+                    .text_base()
+                    .border_b_1()
+                    .border_color(rgb(0x313244))
+                    .child(text)
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .flex_none()
+                            .gap(px(2.0))
+                            // Accept.
+                            .child(
+                                div()
+                                    .id(("proposal-accept", idx))
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .w(px(18.0))
+                                    .h(px(18.0))
+                                    .rounded(px(3.0))
+                                    .cursor_pointer()
+                                    .text_color(rgba(0xa6e3a1ff)) // Catppuccin green
+                                    .hover(|style| style.bg(rgba(0xa6e3a122)))
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(move |this, _: &MouseDownEvent, _window, cx| {
+                                            this.accept(id, cx);
+                                        }),
+                                    )
+                                    .child("\u{2713}"),
+                            )
+                            // Reject.
+                            .child(
+                                div()
+                                    .id(("proposal-reject", idx))
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .w(px(18.0))
+                                    .h(px(18.0))
+                                    .rounded(px(3.0))
+                                    .cursor_pointer()
+                                    .text_color(rgba(0xf38ba8aa)) // Catppuccin red (muted)
+                                    .hover(|style| {
+                                        style.bg(rgba(0xf38ba822)).text_color(rgba(0xf38ba8ff))
+                                    })
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(move |this, _: &MouseDownEvent, _window, cx| {
+                                            this.reject(id, cx);
+                                        }),
+                                    )
+                                    .child("\u{2715}"),
+                            ),
+                    ),
+            );
+        }
+
+        panel.child(scroll_area)
+    }
+}
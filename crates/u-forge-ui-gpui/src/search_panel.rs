@@ -1,17 +1,26 @@
 use std::sync::Arc;
 
 use gpui::{
-    div, prelude::*, px, relative, rgb, rgba, Context, Entity, MouseButton, MouseDownEvent, Window,
+    div, prelude::*, px, relative, rgb, rgba, Context, Entity, EventEmitter, MouseButton,
+    MouseDownEvent, Window,
 };
-use tracing::Instrument;
+use tracing::{warn, Instrument};
 use u_forge_core::{
-    queue::InferenceQueue, search_hybrid, AppConfig, HybridSearchConfig, KnowledgeGraph, ObjectId,
+    queue::InferenceQueue, search_hybrid, AppConfig, EmbeddingMode, HybridSearchConfig,
+    KnowledgeGraph, ObjectId, QueryPreprocessing,
 };
 use u_forge_ui_traits::node_color_for_type;
 
 use crate::selection_model::SelectionModel;
 use crate::text_field::{TextFieldView, TextSubmit};
 
+// ── Events ────────────────────────────────────────────────────────────────────
+
+/// A semantic or hybrid search needs embedding models that a project in
+/// [`EmbeddingMode::Lazy`] has not loaded yet.
+pub(crate) struct EmbeddingsRequested;
+impl EventEmitter<EmbeddingsRequested> for SearchPanel {}
+
 // ── Search mode ───────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -37,6 +46,9 @@ pub(crate) struct SearchPanel {
     query_field: Entity<TextFieldView>,
     mode: SearchMode,
     results: Vec<SearchResult>,
+    /// Telemetry id of the search that produced `results`, when search
+    /// telemetry is enabled for the project.
+    search_log_id: Option<uuid::Uuid>,
     searching: bool,
     error: Option<String>,
     search_limit: usize,
@@ -70,6 +82,7 @@ impl SearchPanel {
             query_field,
             mode: SearchMode::Fts5,
             results: Vec::new(),
+            search_log_id: None,
             searching: false,
             error: None,
             search_limit,
@@ -96,8 +109,19 @@ impl SearchPanel {
 
         // Validate queue availability for modes that need it.
         match self.mode {
-            SearchMode::Semantic | SearchMode::Hybrid if self.inference_queue.is_none() => {
-                self.error = Some("Lemonade not available — use FTS5".to_string());
+            SearchMode::Semantic | SearchMode::Hybrid
+                if !self.inference_queue.as_ref().is_some_and(|q| q.has_embedding()) =>
+            {
+                let mode = self.graph.embedding_mode().unwrap_or_default();
+                let message = match mode {
+                    EmbeddingMode::Off => "Embeddings are off for this project — use FTS5",
+                    EmbeddingMode::Lazy if self.inference_queue.is_some() => {
+                        cx.emit(EmbeddingsRequested);
+                        "Loading embedding models — search again in a moment"
+                    }
+                    _ => "Lemonade not available — use FTS5",
+                };
+                self.error = Some(message.to_string());
                 cx.notify();
                 return;
             }
@@ -107,6 +131,7 @@ impl SearchPanel {
         self.searching = true;
         self.error = None;
         self.results.clear();
+        self.search_log_id = None;
         cx.notify();
 
         let graph = self.graph.clone();
@@ -123,6 +148,8 @@ impl SearchPanel {
             SearchMode::Hybrid => "hybrid",
         };
 
+        let logged_query = query.clone();
+
         cx.spawn(async move |this, cx| {
             let result: Result<Vec<ObjectId>, anyhow::Error> = cx
                 .background_executor()
@@ -186,6 +213,11 @@ impl SearchPanel {
                                         rerank: q.has_reranking(),
                                         limit,
                                         hq_semantic_boost: app_config.chat.hq_semantic_boost,
+                                        sparse_weight: app_config.chat.sparse_weight,
+                                        sparse_limit: limit * 4,
+                                        lifecycles: None,
+                                        preprocess: Some(QueryPreprocessing::default()),
+                                        ..Default::default()
                                     };
                                     let results =
                                         search_hybrid(&graph, q, hq_queue.as_ref(), &query, &cfg)
@@ -224,6 +256,13 @@ impl SearchPanel {
                         if panel.results.is_empty() {
                             panel.error = Some("No results found.".to_string());
                         }
+                        panel.search_log_id = panel
+                            .graph
+                            .record_search(&logged_query, panel.results.len())
+                            .unwrap_or_else(|e| {
+                                warn!("Failed to record search telemetry: {e}");
+                                None
+                            });
                     }
                     Err(e) => {
                         panel.error = Some(format!("Search error: {e}"));
@@ -498,6 +537,13 @@ impl Render for SearchPanel {
                     .on_mouse_down(
                         MouseButton::Left,
                         cx.listener(move |this, _: &MouseDownEvent, _window, cx| {
+                            if let Some(search_id) = this.search_log_id {
+                                if let Err(e) =
+                                    this.graph.record_search_click(search_id, node_id, idx)
+                                {
+                                    warn!("Failed to record search click: {e}");
+                                }
+                            }
                             this.selection.update(cx, |sel, cx| {
                                 sel.select_by_id(Some(node_id), cx);
                             });
@@ -93,54 +93,23 @@ pub trait GraphRenderer {
 
 // ── Node color palette ───────────────────────────────────────────────────────
 
-/// Convert HSL (h in [0,360), s and l in [0,1]) to sRGB bytes.
-fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (u8, u8, u8) {
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-    let m = l - c / 2.0;
-    let (r1, g1, b1) = if h < 60.0 {
-        (c, x, 0.0)
-    } else if h < 120.0 {
-        (x, c, 0.0)
-    } else if h < 180.0 {
-        (0.0, c, x)
-    } else if h < 240.0 {
-        (0.0, x, c)
-    } else if h < 300.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-    (
-        ((r1 + m) * 255.0).round() as u8,
-        ((g1 + m) * 255.0).round() as u8,
-        ((b1 + m) * 255.0).round() as u8,
-    )
-}
-
-/// Return a stable, visually distinct RGBA color for any object type name.
+/// Return a stable, visually distinct RGBA color for any object type name,
+/// ignoring schema styling.
 ///
-/// Hue is derived from the type name via FNV-1a hash scattered with the golden
-/// angle (137.5°), giving maximally-separated hues for any set of names.
-/// Saturation and lightness are fixed to match the Catppuccin Mocha accent
-/// palette (~75 % / 76 %), so every color looks at home on the dark background.
+/// Delegates to [`u_forge_graph_view::default_type_color`]: an FNV-1a hash of
+/// the name scattered with the golden angle, at the Catppuccin Mocha accent
+/// saturation and lightness.  Prefer [`GraphSnapshot::type_color`], which
+/// honours a schema-defined `color`.
 pub fn node_color_for_type(object_type: &str) -> [u8; 4] {
-    // FNV-1a hash of the type name — fast and well-distributed.
-    let hash = object_type
-        .bytes()
-        .fold(2_166_136_261_u32, |acc, b| {
-            acc.wrapping_mul(16_777_619).wrapping_add(b as u32)
-        });
-    // Golden-angle scatter: multiply by φ fractional part, wrap to [0, 1).
-    let hue = ((hash as f64 * 0.618_033_988_749_895).fract() * 360.0) as f32;
-    let (r, g, b) = hsl_to_rgb(hue, 0.75, 0.76);
+    let [r, g, b] = u_forge_graph_view::default_type_color(object_type);
     [r, g, b, 255]
 }
 
-/// Internal alias kept for brevity inside this module.
+/// Schema-styled RGBA color of `object_type` in `snapshot`.
 #[inline(always)]
-fn type_color(object_type: &str) -> [u8; 4] {
-    node_color_for_type(object_type)
+fn type_color(snapshot: &GraphSnapshot, object_type: &str) -> [u8; 4] {
+    let [r, g, b] = snapshot.type_color(object_type);
+    [r, g, b, 255]
 }
 
 const EDGE_COLOR: [u8; 4] = [88, 91, 112, 200]; // surface2 with alpha
@@ -208,7 +177,7 @@ pub fn generate_draw_commands(
         let node = &snapshot.nodes[idx];
         let screen_pos = viewport.world_to_screen(node.position);
         let is_selected = selected_idx == Some(idx);
-        let base_color = type_color(&node.object_type);
+        let base_color = type_color(snapshot, &node.object_type);
         let color = if is_selected {
             brighten(base_color, 1.45)
         } else {